@@ -0,0 +1,2 @@
+ALTER TABLE docx_jobs
+    ADD COLUMN IF NOT EXISTS direction_auto_detected BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,5 @@
+CREATE TABLE IF NOT EXISTS usage_unit_policies (
+    module_key TEXT PRIMARY KEY,
+    unit_policy TEXT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
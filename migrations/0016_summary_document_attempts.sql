@@ -0,0 +1,3 @@
+-- Track how many LLM requests each summarizer document needed (including retries)
+ALTER TABLE summary_documents
+    ADD COLUMN IF NOT EXISTS attempt_count INT NOT NULL DEFAULT 0;
@@ -0,0 +1,3 @@
+-- Requested summary length per summarizer job (brief / standard / detailed)
+ALTER TABLE summary_jobs
+    ADD COLUMN IF NOT EXISTS summary_length TEXT NOT NULL DEFAULT 'standard';
@@ -0,0 +1,6 @@
+-- Whether summarizer / info_extract jobs skip documents whose text duplicates an earlier upload
+ALTER TABLE summary_jobs
+    ADD COLUMN IF NOT EXISTS skip_duplicates BOOLEAN NOT NULL DEFAULT FALSE;
+
+ALTER TABLE info_extract_jobs
+    ADD COLUMN IF NOT EXISTS skip_duplicates BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,4 @@
+-- Chunk limits a DOCX translation finished with (after any context-length autotune)
+ALTER TABLE docx_documents
+    ADD COLUMN IF NOT EXISTS chunk_max_paragraphs INT,
+    ADD COLUMN IF NOT EXISTS chunk_max_words INT;
@@ -0,0 +1,30 @@
+-- Per-manuscript grading results, so one grader job can hold several documents
+ALTER TABLE grader_documents
+    ADD COLUMN IF NOT EXISTS ordinal INT NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS attempts_run INT,
+    ADD COLUMN IF NOT EXISTS valid_runs INT,
+    ADD COLUMN IF NOT EXISTS iqm_score DOUBLE PRECISION,
+    ADD COLUMN IF NOT EXISTS justification TEXT,
+    ADD COLUMN IF NOT EXISTS decision_reason TEXT,
+    ADD COLUMN IF NOT EXISTS keyword_main TEXT,
+    ADD COLUMN IF NOT EXISTS keyword_peripherals TEXT[],
+    ADD COLUMN IF NOT EXISTS recommendations JSONB;
+
+-- Jobs created before multi-manuscript support hold exactly one document; copy their
+-- results onto it so the per-document status view keeps showing them.
+UPDATE grader_documents AS d
+SET attempts_run = j.attempts_run,
+    valid_runs = j.valid_runs,
+    iqm_score = j.iqm_score,
+    justification = j.justification,
+    decision_reason = j.decision_reason,
+    keyword_main = j.keyword_main,
+    keyword_peripherals = j.keyword_peripherals,
+    recommendations = j.recommendations
+FROM grader_jobs AS j
+WHERE d.job_id = j.id
+  AND d.iqm_score IS NULL
+  AND j.iqm_score IS NOT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_grader_documents_job_ordinal
+    ON grader_documents (job_id, ordinal);
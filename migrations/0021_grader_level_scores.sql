@@ -0,0 +1,3 @@
+-- Per-level grading scores (Level 1–6, after the DOCX penalty) behind each manuscript's IQM
+ALTER TABLE grader_documents
+    ADD COLUMN IF NOT EXISTS level_scores DOUBLE PRECISION[];
@@ -0,0 +1,10 @@
+-- DOCX score penalty applied to each manuscript (NULL when none), so results can say so
+ALTER TABLE grader_documents
+    ADD COLUMN IF NOT EXISTS docx_penalty DOUBLE PRECISION;
+
+-- Graded DOCX manuscripts so far all received the former fixed 2% penalty.
+UPDATE grader_documents
+SET docx_penalty = 0.02
+WHERE is_docx
+  AND iqm_score IS NOT NULL
+  AND docx_penalty IS NULL;
@@ -0,0 +1,3 @@
+-- Opt-in paragraph numbering of the manuscript text sent to reviewer models
+ALTER TABLE reviewer_jobs
+    ADD COLUMN IF NOT EXISTS numbered_paragraphs BOOLEAN NOT NULL DEFAULT FALSE;
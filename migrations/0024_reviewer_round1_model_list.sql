@@ -0,0 +1,21 @@
+-- Reviewer round-1 models become a variable-length list: fold the fixed round1_model_1..8
+-- keys of an existing configuration into `round1_models`, keeping their order.
+UPDATE module_configs
+SET models = (
+        models
+        - 'round1_model_1' - 'round1_model_2' - 'round1_model_3' - 'round1_model_4'
+        - 'round1_model_5' - 'round1_model_6' - 'round1_model_7' - 'round1_model_8'
+    ) || jsonb_build_object(
+        'round1_models',
+        (
+            SELECT jsonb_agg(btrim(models ->> legacy.key) ORDER BY legacy.position)
+            FROM unnest(ARRAY[
+                'round1_model_1', 'round1_model_2', 'round1_model_3', 'round1_model_4',
+                'round1_model_5', 'round1_model_6', 'round1_model_7', 'round1_model_8'
+            ]) WITH ORDINALITY AS legacy(key, position)
+            WHERE COALESCE(btrim(models ->> legacy.key), '') <> ''
+        )
+    ),
+    updated_at = NOW()
+WHERE module_name = 'reviewer'
+  AND NOT models ? 'round1_models';
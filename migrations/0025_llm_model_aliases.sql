@@ -0,0 +1,5 @@
+CREATE TABLE IF NOT EXISTS llm_model_aliases (
+    poe_model TEXT PRIMARY KEY,
+    openrouter_model TEXT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,4 @@
+-- Unmodified summary responses kept for admin auditing when the summarizer's
+-- store_raw_responses setting is on
+ALTER TABLE summary_documents
+    ADD COLUMN IF NOT EXISTS raw_summary_response TEXT;
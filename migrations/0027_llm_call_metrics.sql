@@ -0,0 +1,17 @@
+-- One row per LLM provider call, feeding the admin call metrics endpoint
+CREATE TABLE IF NOT EXISTS llm_call_metrics (
+    id BIGSERIAL PRIMARY KEY,
+    module_key TEXT,
+    provider TEXT NOT NULL,
+    model TEXT NOT NULL,
+    duration_ms BIGINT NOT NULL,
+    prompt_tokens BIGINT NOT NULL DEFAULT 0,
+    response_tokens BIGINT NOT NULL DEFAULT 0,
+    request_bytes BIGINT NOT NULL DEFAULT 0,
+    response_bytes BIGINT NOT NULL DEFAULT 0,
+    success BOOLEAN NOT NULL,
+    occurred_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_llm_call_metrics_occurred_at
+    ON llm_call_metrics (occurred_at);
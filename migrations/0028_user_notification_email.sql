@@ -0,0 +1,2 @@
+-- Optional address that receives a message when one of the user's long jobs finishes.
+ALTER TABLE users ADD COLUMN IF NOT EXISTS notification_email TEXT;
@@ -0,0 +1,8 @@
+-- Optional https callback POSTed once when a summarizer or info extract job finishes
+ALTER TABLE summary_jobs
+    ADD COLUMN IF NOT EXISTS callback_url TEXT,
+    ADD COLUMN IF NOT EXISTS callback_sent_at TIMESTAMPTZ;
+
+ALTER TABLE info_extract_jobs
+    ADD COLUMN IF NOT EXISTS callback_url TEXT,
+    ADD COLUMN IF NOT EXISTS callback_sent_at TIMESTAMPTZ;
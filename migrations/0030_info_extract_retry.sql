@@ -0,0 +1,6 @@
+-- Lets a retry of failed info extract documents rebuild the result workbook as first requested
+ALTER TABLE info_extract_jobs
+    ADD COLUMN IF NOT EXISTS per_document_sheets BOOLEAN NOT NULL DEFAULT FALSE;
+
+ALTER TABLE info_extract_documents
+    ADD COLUMN IF NOT EXISTS validation_warnings TEXT[] NOT NULL DEFAULT '{}';
@@ -0,0 +1,6 @@
+-- Per-module file retention, edited from the dashboard; modules without a row keep files for the default window
+CREATE TABLE IF NOT EXISTS file_retention_policies (
+    module_key TEXT PRIMARY KEY,
+    retention_days INT NOT NULL CHECK (retention_days > 0),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,10 @@
+-- Job directories compressed by maintenance before their files are purged
+ALTER TABLE summary_jobs ADD COLUMN IF NOT EXISTS files_archived_at TIMESTAMPTZ;
+ALTER TABLE docx_jobs ADD COLUMN IF NOT EXISTS files_archived_at TIMESTAMPTZ;
+ALTER TABLE grader_jobs ADD COLUMN IF NOT EXISTS files_archived_at TIMESTAMPTZ;
+ALTER TABLE info_extract_jobs ADD COLUMN IF NOT EXISTS files_archived_at TIMESTAMPTZ;
+ALTER TABLE reviewer_jobs ADD COLUMN IF NOT EXISTS files_archived_at TIMESTAMPTZ;
+
+-- Days after which a module's job directories are archived; NULL keeps them uncompressed
+ALTER TABLE file_retention_policies ADD COLUMN IF NOT EXISTS archive_after_days INT
+    CHECK (archive_after_days > 0);
@@ -0,0 +1,7 @@
+-- Optional module scope for glossary terms; NULL keeps a term global
+ALTER TABLE glossary_terms ADD COLUMN IF NOT EXISTS scope TEXT;
+
+-- A source term may now be defined once globally and once per scope
+DROP INDEX IF EXISTS idx_glossary_terms_source_lower;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_glossary_terms_scope_source_lower
+    ON glossary_terms ((COALESCE(scope, '')), (LOWER(source_term)));
@@ -0,0 +1,4 @@
+-- Glossary usage recorded by the optional post-translation check; NULL when the check is off
+ALTER TABLE docx_documents
+    ADD COLUMN IF NOT EXISTS glossary_terms_expected INT,
+    ADD COLUMN IF NOT EXISTS glossary_terms_matched INT;
@@ -0,0 +1,22 @@
+-- Audio transcription jobs: one uploaded recording per job, transcript stored as text and as a .txt file
+CREATE TABLE IF NOT EXISTS transcribe_jobs (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    status TEXT NOT NULL,
+    status_detail TEXT,
+    error_message TEXT,
+    original_filename TEXT NOT NULL,
+    source_path TEXT NOT NULL,
+    content_type TEXT NOT NULL,
+    transcript_text TEXT,
+    transcript_path TEXT,
+    tokens_used BIGINT,
+    usage_delta BIGINT NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    files_purged_at TIMESTAMPTZ,
+    files_archived_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_transcribe_jobs_user_created
+    ON transcribe_jobs (user_id, created_at DESC);
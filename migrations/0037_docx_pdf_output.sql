@@ -0,0 +1,6 @@
+-- Optional PDF copy of each translated DOCX, requested per job
+ALTER TABLE docx_jobs
+    ADD COLUMN IF NOT EXISTS pdf_output BOOLEAN NOT NULL DEFAULT FALSE;
+
+ALTER TABLE docx_documents
+    ADD COLUMN IF NOT EXISTS translated_pdf_path TEXT;
@@ -0,0 +1,19 @@
+-- Standalone DOCX → PDF conversion jobs: one uploaded document per job
+CREATE TABLE IF NOT EXISTS docx2pdf_jobs (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    status TEXT NOT NULL,
+    status_detail TEXT,
+    error_message TEXT,
+    original_filename TEXT NOT NULL,
+    source_path TEXT NOT NULL,
+    pdf_path TEXT,
+    usage_delta BIGINT NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    files_purged_at TIMESTAMPTZ,
+    files_archived_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_docx2pdf_jobs_user_created
+    ON docx2pdf_jobs (user_id, created_at DESC);
@@ -0,0 +1,3 @@
+-- Per-document chunk progress, updated as each translation chunk succeeds
+ALTER TABLE docx_documents
+    ADD COLUMN IF NOT EXISTS chunks_completed INT NOT NULL DEFAULT 0;
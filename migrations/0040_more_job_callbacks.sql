@@ -0,0 +1,8 @@
+-- Optional https callback for grader and DOCX translation jobs, as in 0029
+ALTER TABLE grader_jobs
+    ADD COLUMN IF NOT EXISTS callback_url TEXT,
+    ADD COLUMN IF NOT EXISTS callback_sent_at TIMESTAMPTZ;
+
+ALTER TABLE docx_jobs
+    ADD COLUMN IF NOT EXISTS callback_url TEXT,
+    ADD COLUMN IF NOT EXISTS callback_sent_at TIMESTAMPTZ;
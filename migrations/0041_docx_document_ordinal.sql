@@ -0,0 +1,15 @@
+-- Upload position of each document in a DOCX translation batch; rows of one batch share the
+-- same created_at, so ordering by it was arbitrary
+ALTER TABLE docx_documents
+    ADD COLUMN IF NOT EXISTS ordinal INT NOT NULL DEFAULT 0;
+
+UPDATE docx_documents d
+SET ordinal = numbered.position
+FROM (
+    SELECT id, ROW_NUMBER() OVER (PARTITION BY job_id ORDER BY created_at, id) - 1 AS position
+    FROM docx_documents
+) numbered
+WHERE d.id = numbered.id;
+
+CREATE INDEX IF NOT EXISTS idx_docx_documents_job_ordinal
+    ON docx_documents (job_id, ordinal);
@@ -3,12 +3,58 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use sqlx::PgPool;
 use tokio::try_join;
+use tracing::info;
 
 const MODULE_SUMMARIZER: &str = "summarizer";
 const MODULE_TRANSLATE_DOCX: &str = "translate_docx";
 const MODULE_GRADER: &str = "grader";
 const MODULE_REVIEWER: &str = "reviewer";
 const MODULE_INFO_EXTRACT: &str = "info_extract";
+const MODULE_TRANSCRIBE: &str = "transcribe";
+/// Default number of documents a batch module processes in parallel.
+pub const DEFAULT_MAX_CONCURRENT_DOCUMENTS: usize = 5;
+/// Upper bound accepted from the admin UI for per-module document concurrency.
+pub const MAX_CONCURRENT_DOCUMENTS_LIMIT: usize = 50;
+/// Default number of DOCX files accepted in one translator job.
+pub const DEFAULT_DOCX_BATCH_FILES: usize = 20;
+/// Upper bound accepted from the admin UI for the DOCX translator batch size.
+pub const MAX_DOCX_BATCH_FILES: usize = 100;
+/// Default fraction of grader samples trimmed from each end before averaging (interquartile mean).
+pub const DEFAULT_GRADING_TRIM_FRACTION: f64 = 0.25;
+/// Largest per-side trim accepted, so at least one sample always survives.
+pub const MAX_GRADING_TRIM_FRACTION: f64 = 0.45;
+/// Default grader sampling: stop after this many attempts...
+pub const DEFAULT_GRADING_MAX_ATTEMPTS: usize = 30;
+/// ...or once this many valid runs are collected...
+pub const DEFAULT_GRADING_TARGET_SUCCESSES: usize = 12;
+/// ...and fail the job with fewer valid runs than this.
+pub const DEFAULT_GRADING_MIN_SUCCESSES: usize = 8;
+/// Upper bound accepted from the admin UI for grader attempts per job.
+pub const MAX_GRADING_ATTEMPTS_LIMIT: usize = 100;
+/// Default DOCX translation chunk limits, used for models without an override.
+pub const DEFAULT_CHUNK_MAX_PARAGRAPHS: usize = 20;
+pub const DEFAULT_CHUNK_MAX_WORDS: u32 = 700;
+/// Upper bounds accepted from the admin UI for per-model chunk limits.
+pub const MAX_CHUNK_PARAGRAPHS_LIMIT: usize = 200;
+pub const MAX_CHUNK_WORDS_LIMIT: u32 = 20_000;
+/// Default weights of the six journal levels when averaging one grading run.
+pub const DEFAULT_GRADING_WEIGHTS: [f64; 6] = [4.0, 2.0, 1.0, 1.0, 1.0, 1.0];
+/// Default fraction deducted from the scores of manuscripts uploaded as DOCX.
+pub const DEFAULT_GRADER_DOCX_PENALTY: f64 = 0.02;
+/// Largest DOCX penalty accepted from the admin UI.
+pub const MAX_GRADER_DOCX_PENALTY: f64 = 0.5;
+/// Consecutive failed LLM calls after which an info extract job fast-fails its remaining documents.
+pub const DEFAULT_PROVIDER_FAILURE_BUDGET: usize = 5;
+/// Upper bound accepted from the admin UI for the provider failure budget.
+pub const MAX_PROVIDER_FAILURE_BUDGET: usize = 100;
+/// Largest reviewer manuscript accepted by default, in megabytes.
+pub const DEFAULT_REVIEWER_MAX_MANUSCRIPT_MB: u64 = 25;
+/// Most PDF pages a reviewer manuscript may have by default.
+pub const DEFAULT_REVIEWER_MAX_MANUSCRIPT_PAGES: u64 = 80;
+/// Most round-1 reviewer models accepted from the admin UI.
+pub const MAX_REVIEWER_ROUND1_MODELS: usize = 16;
+/// Default share of round-1 reviews that must succeed before the meta-review runs.
+pub const DEFAULT_REVIEWER_ROUND1_SUCCESS_RATIO: f64 = 0.5;
 const LEGACY_GRADER_PROMPT_PREFIX: &str = "You evaluate manuscripts in the domains";
 const PROTOTYPE_GRADER_PROMPT: &str = r#"You are tasked with grading manuscripts in the areas of urban soundscape, architectural acoustics, and healthy habitat. Six prestige levels of well-known journals are listed below for reference, but you do not need to consider manuscript fit to specific journals; these are to convey the relative prestige of each level. For each manuscript, provide your educated guess—expressed as a percentage—for the chance it would be sent out for external review at each of the six journal levels. In making your estimates, consider overall quality, scope breadth, methodological novelty, interest to readership, workload, quality of writing, methodological rigour, and whether the results fully support the claims. Some manuscripts you grade may already be published articles, but please evaluate them as if they are new, without regard to where they were actually published. Note each lower level should have a equal or higher chance than the previous level.
 *Level 1 - High-impact broad journals*
@@ -45,6 +91,7 @@ pub struct ModuleSettings {
     grader: Option<GraderSettings>,
     reviewer: Option<ReviewerSettings>,
     info_extract: Option<InfoExtractSettings>,
+    transcribe: Option<TranscribeSettings>,
 }
 
 impl ModuleSettings {
@@ -59,6 +106,8 @@ impl ModuleSettings {
         let reviewer_prompts = serde_json::to_value(default_reviewer_prompts())?;
         let info_models = serde_json::to_value(default_info_extract_models())?;
         let info_prompts = serde_json::to_value(default_info_extract_prompts())?;
+        let transcribe_models = serde_json::to_value(default_transcribe_models())?;
+        let transcribe_prompts = serde_json::to_value(default_transcribe_prompts())?;
 
         let insert_summarizer = sqlx::query(
             "INSERT INTO module_configs (module_name, models, prompts) VALUES ($1, $2, $3)
@@ -105,6 +154,15 @@ impl ModuleSettings {
         .bind(&info_prompts)
         .execute(pool);
 
+        let insert_transcribe = sqlx::query(
+            "INSERT INTO module_configs (module_name, models, prompts) VALUES ($1, $2, $3)
+             ON CONFLICT (module_name) DO NOTHING",
+        )
+        .bind(MODULE_TRANSCRIBE)
+        .bind(&transcribe_models)
+        .bind(&transcribe_prompts)
+        .execute(pool);
+
         let legacy_like = format!("{LEGACY_GRADER_PROMPT_PREFIX}%");
         let update_grader_prompt = sqlx::query(
             "UPDATE module_configs SET prompts = $1, updated_at = NOW()
@@ -121,9 +179,20 @@ impl ModuleSettings {
             insert_grader,
             insert_reviewer,
             insert_info,
+            insert_transcribe,
             update_grader_prompt
         )?;
 
+        let defaults = [
+            (MODULE_SUMMARIZER, &summarizer_models, &summarizer_prompts),
+            (MODULE_TRANSLATE_DOCX, &docx_models, &docx_prompts),
+            (MODULE_GRADER, &grader_models, &grader_prompts),
+            (MODULE_REVIEWER, &reviewer_models, &reviewer_prompts),
+            (MODULE_INFO_EXTRACT, &info_models, &info_prompts),
+            (MODULE_TRANSCRIBE, &transcribe_models, &transcribe_prompts),
+        ];
+        backfill_module_defaults(pool, &defaults).await?;
+
         Ok(())
     }
 
@@ -137,32 +206,39 @@ impl ModuleSettings {
 
         let mut settings = ModuleSettings::default();
         for row in rows {
-            match row.module_name.as_str() {
-                MODULE_SUMMARIZER => {
-                    settings.summarizer = Some(parse_summarizer_settings(row.models, row.prompts)?);
-                }
-                MODULE_TRANSLATE_DOCX => {
-                    settings.translate_docx = Some(parse_docx_settings(row.models, row.prompts)?);
-                }
-                MODULE_GRADER => {
-                    settings.grader = Some(parse_grader_settings(row.models, row.prompts)?);
-                }
-                MODULE_REVIEWER => {
-                    settings.reviewer = Some(parse_reviewer_settings(row.models, row.prompts)?);
-                }
-                MODULE_INFO_EXTRACT => {
-                    settings.info_extract =
-                        Some(parse_info_extract_settings(row.models, row.prompts)?);
-                }
-                other => {
-                    return Err(anyhow!("unknown module configuration found: {}", other));
-                }
-            }
+            settings.apply_row(row)?;
         }
 
         Ok(settings)
     }
 
+    fn apply_row(&mut self, row: ModuleConfigRow) -> Result<()> {
+        match row.module_name.as_str() {
+            MODULE_SUMMARIZER => {
+                self.summarizer = Some(parse_summarizer_settings(row.models, row.prompts)?);
+            }
+            MODULE_TRANSLATE_DOCX => {
+                self.translate_docx = Some(parse_docx_settings(row.models, row.prompts)?);
+            }
+            MODULE_GRADER => {
+                self.grader = Some(parse_grader_settings(row.models, row.prompts)?);
+            }
+            MODULE_REVIEWER => {
+                self.reviewer = Some(parse_reviewer_settings(row.models, row.prompts)?);
+            }
+            MODULE_INFO_EXTRACT => {
+                self.info_extract = Some(parse_info_extract_settings(row.models, row.prompts)?);
+            }
+            MODULE_TRANSCRIBE => {
+                self.transcribe = Some(parse_transcribe_settings(row.models, row.prompts)?);
+            }
+            other => {
+                return Err(anyhow!("unknown module configuration found: {}", other));
+            }
+        }
+        Ok(())
+    }
+
     pub fn summarizer(&self) -> Option<&SummarizerSettings> {
         self.summarizer.as_ref()
     }
@@ -182,6 +258,374 @@ impl ModuleSettings {
     pub fn info_extract(&self) -> Option<&InfoExtractSettings> {
         self.info_extract.as_ref()
     }
+
+    pub fn transcribe(&self) -> Option<&TranscribeSettings> {
+        self.transcribe.as_ref()
+    }
+}
+
+/// Routes inputs up to `max_input_tokens` (estimated) to `model`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelRoute {
+    pub max_input_tokens: u64,
+    pub model: String,
+}
+
+/// Picks the model for an input of `estimated_tokens`: the smallest route that still
+/// covers the input wins, otherwise the module's configured `default_model` is used.
+pub fn route_model<'a>(
+    default_model: &'a str,
+    routes: &'a [ModelRoute],
+    estimated_tokens: u64,
+) -> &'a str {
+    routes
+        .iter()
+        .filter(|route| estimated_tokens <= route.max_input_tokens)
+        .min_by_key(|route| route.max_input_tokens)
+        .map(|route| route.model.as_str())
+        .unwrap_or(default_model)
+}
+
+/// Parses the admin textarea format: one `max_tokens=model` rule per line.
+/// Blank lines and lines starting with `#` are ignored.
+pub fn parse_model_routes(input: &str) -> Result<Vec<ModelRoute>> {
+    let mut routes = Vec::new();
+
+    for (idx, line) in input.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (limit, model) = line
+            .split_once('=')
+            .ok_or_else(|| anyhow!("line {}: expected `max_tokens=model`", idx + 1))?;
+        let max_input_tokens = limit
+            .trim()
+            .replace([',', '_'], "")
+            .parse::<u64>()
+            .map_err(|_| anyhow!("line {}: invalid token limit `{}`", idx + 1, limit.trim()))?;
+        let model = model.trim();
+        if model.is_empty() || max_input_tokens == 0 {
+            return Err(anyhow!(
+                "line {}: token limit and model are required",
+                idx + 1
+            ));
+        }
+
+        routes.push(ModelRoute {
+            max_input_tokens,
+            model: model.to_string(),
+        });
+    }
+
+    routes.sort_by_key(|route| route.max_input_tokens);
+    Ok(routes)
+}
+
+/// Size limits for one DOCX translation chunk; words are English-equivalent (CJK characters
+/// count 0.7).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkLimits {
+    pub max_paragraphs: usize,
+    pub max_words: u32,
+}
+
+impl Default for ChunkLimits {
+    fn default() -> Self {
+        Self {
+            max_paragraphs: DEFAULT_CHUNK_MAX_PARAGRAPHS,
+            max_words: DEFAULT_CHUNK_MAX_WORDS,
+        }
+    }
+}
+
+/// Chunk limits applied when `model` is the configured translation model.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelChunkLimits {
+    pub model: String,
+    #[serde(flatten)]
+    pub limits: ChunkLimits,
+}
+
+/// Parses the admin textarea format: one `model=max_paragraphs,max_words` rule per line.
+/// Blank lines and lines starting with `#` are ignored.
+pub fn parse_chunk_limits(input: &str) -> Result<Vec<ModelChunkLimits>> {
+    let mut rules = Vec::new();
+
+    for (idx, line) in input.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (model, limits) = line.rsplit_once('=').ok_or_else(|| {
+            anyhow!(
+                "line {}: expected `model=max_paragraphs,max_words`",
+                idx + 1
+            )
+        })?;
+        let (paragraphs, words) = limits
+            .split_once([',', '，'])
+            .ok_or_else(|| anyhow!("line {}: expected `max_paragraphs,max_words`", idx + 1))?;
+        let max_paragraphs = parse_chunk_paragraphs(paragraphs)
+            .ok_or_else(|| anyhow!("line {}: invalid paragraph limit", idx + 1))?;
+        let max_words = parse_chunk_words(words)
+            .ok_or_else(|| anyhow!("line {}: invalid word limit", idx + 1))?;
+        let model = model.trim();
+        if model.is_empty() {
+            return Err(anyhow!("line {}: model is required", idx + 1));
+        }
+
+        rules.push(ModelChunkLimits {
+            model: model.to_string(),
+            limits: ChunkLimits {
+                max_paragraphs,
+                max_words,
+            },
+        });
+    }
+
+    Ok(rules)
+}
+
+/// Parses the module-wide chunk limits from the admin form; blank fields keep the defaults.
+pub fn parse_default_chunk_limits(paragraphs: &str, words: &str) -> Option<ChunkLimits> {
+    let defaults = ChunkLimits::default();
+    let max_paragraphs = if paragraphs.trim().is_empty() {
+        defaults.max_paragraphs
+    } else {
+        parse_chunk_paragraphs(paragraphs)?
+    };
+    let max_words = if words.trim().is_empty() {
+        defaults.max_words
+    } else {
+        parse_chunk_words(words)?
+    };
+    Some(ChunkLimits {
+        max_paragraphs,
+        max_words,
+    })
+}
+
+fn parse_chunk_paragraphs(input: &str) -> Option<usize> {
+    input
+        .trim()
+        .parse::<usize>()
+        .ok()
+        .filter(|value| (1..=MAX_CHUNK_PARAGRAPHS_LIMIT).contains(value))
+}
+
+fn parse_chunk_words(input: &str) -> Option<u32> {
+    input
+        .trim()
+        .replace([',', '_'], "")
+        .parse::<u32>()
+        .ok()
+        .filter(|value| (1..=MAX_CHUNK_WORDS_LIMIT).contains(value))
+}
+
+pub fn format_chunk_limits(rules: &[ModelChunkLimits]) -> String {
+    rules
+        .iter()
+        .map(|rule| {
+            format!(
+                "{}={},{}",
+                rule.model, rule.limits.max_paragraphs, rule.limits.max_words
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Parses a concurrency limit from the admin form, accepting 1..=`MAX_CONCURRENT_DOCUMENTS_LIMIT`.
+pub fn parse_concurrency_limit(input: &str) -> Option<usize> {
+    input
+        .trim()
+        .parse::<usize>()
+        .ok()
+        .filter(|limit| (1..=MAX_CONCURRENT_DOCUMENTS_LIMIT).contains(limit))
+}
+
+/// Parses the DOCX translator batch size from the admin form, accepting 1..=`MAX_DOCX_BATCH_FILES`.
+pub fn parse_batch_limit(input: &str) -> Option<usize> {
+    input
+        .trim()
+        .parse::<usize>()
+        .ok()
+        .filter(|limit| (1..=MAX_DOCX_BATCH_FILES).contains(limit))
+}
+
+/// Parses the grader trim fraction from the admin form, accepting 0..=`MAX_GRADING_TRIM_FRACTION`;
+/// blank restores the default.
+pub fn parse_trim_fraction(input: &str) -> Option<f64> {
+    let trimmed = input.trim();
+    if trimmed.is_empty() {
+        return Some(DEFAULT_GRADING_TRIM_FRACTION);
+    }
+    trimmed
+        .parse::<f64>()
+        .ok()
+        .filter(|fraction| (0.0..=MAX_GRADING_TRIM_FRACTION).contains(fraction))
+}
+
+/// Whether grader sampling counts satisfy `1 <= min <= target <= max <= MAX_GRADING_ATTEMPTS_LIMIT`.
+pub fn sampling_counts_valid(max_attempts: usize, target: usize, min: usize) -> bool {
+    1 <= min
+        && min <= target
+        && target <= max_attempts
+        && max_attempts <= MAX_GRADING_ATTEMPTS_LIMIT
+}
+
+/// Parses the grader sampling counts from the admin form as `(max, target, min)`.
+pub fn parse_sampling_counts(
+    max_attempts: &str,
+    target: &str,
+    min: &str,
+) -> Option<(usize, usize, usize)> {
+    let max_attempts = max_attempts.trim().parse::<usize>().ok()?;
+    let target = target.trim().parse::<usize>().ok()?;
+    let min = min.trim().parse::<usize>().ok()?;
+    sampling_counts_valid(max_attempts, target, min).then_some((max_attempts, target, min))
+}
+
+/// Parses the provider failure budget from the admin form, accepting 0..=`MAX_PROVIDER_FAILURE_BUDGET`
+/// (`0` disables it); blank restores the default.
+pub fn parse_failure_budget(input: &str) -> Option<usize> {
+    let trimmed = input.trim();
+    if trimmed.is_empty() {
+        return Some(DEFAULT_PROVIDER_FAILURE_BUDGET);
+    }
+    trimmed
+        .parse::<usize>()
+        .ok()
+        .filter(|budget| *budget <= MAX_PROVIDER_FAILURE_BUDGET)
+}
+
+/// Whether grading weights are finite, non-negative and not all zero.
+pub fn grading_weights_valid(weights: &[f64; 6]) -> bool {
+    weights
+        .iter()
+        .all(|weight| weight.is_finite() && *weight >= 0.0)
+        && weights.iter().any(|weight| *weight > 0.0)
+}
+
+/// Parses six grading weights separated by commas or whitespace; blank restores the defaults.
+pub fn parse_grading_weights(input: &str) -> Option<[f64; 6]> {
+    let trimmed = input.trim();
+    if trimmed.is_empty() {
+        return Some(DEFAULT_GRADING_WEIGHTS);
+    }
+    let values = trimmed
+        .split(|ch: char| ch == ',' || ch == '，' || ch.is_whitespace())
+        .filter(|part| !part.is_empty())
+        .map(|part| part.parse::<f64>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    let weights: [f64; 6] = values.try_into().ok()?;
+    grading_weights_valid(&weights).then_some(weights)
+}
+
+/// Parses the grader DOCX penalty from the admin form, accepting 0..=`MAX_GRADER_DOCX_PENALTY`;
+/// blank restores the default.
+pub fn parse_docx_penalty(input: &str) -> Option<f64> {
+    let trimmed = input.trim();
+    if trimmed.is_empty() {
+        return Some(DEFAULT_GRADER_DOCX_PENALTY);
+    }
+    trimmed
+        .parse::<f64>()
+        .ok()
+        .filter(|penalty| (0.0..=MAX_GRADER_DOCX_PENALTY).contains(penalty))
+}
+
+/// Parses a token budget from the admin form; blank means `0` (no budget).
+pub fn parse_token_budget(input: &str) -> Option<u64> {
+    let cleaned: String = input.trim().chars().filter(|ch| *ch != ',').collect();
+    if cleaned.is_empty() {
+        return Some(0);
+    }
+    cleaned.parse::<u64>().ok()
+}
+
+/// Parses round-1 reviewer models from the admin textarea, one per line, accepting
+/// 1..=`MAX_REVIEWER_ROUND1_MODELS` entries.
+pub fn parse_round1_models(input: &str) -> Option<Vec<String>> {
+    let models = input
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    (1..=MAX_REVIEWER_ROUND1_MODELS)
+        .contains(&models.len())
+        .then_some(models)
+}
+
+/// Parses round-1 fallback models from the admin textarea, one per line; blank means none.
+pub fn parse_fallback_models(input: &str) -> Vec<String> {
+    input
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn success_ratio_valid(ratio: f64) -> bool {
+    ratio > 0.0 && ratio <= 1.0
+}
+
+/// Parses the round-1 success ratio from the admin form, accepting `(0, 1]`; blank restores
+/// the default.
+pub fn parse_success_ratio(input: &str) -> Option<f64> {
+    let trimmed = input.trim();
+    if trimmed.is_empty() {
+        return Some(DEFAULT_REVIEWER_ROUND1_SUCCESS_RATIO);
+    }
+    trimmed
+        .parse::<f64>()
+        .ok()
+        .filter(|ratio| success_ratio_valid(*ratio))
+}
+
+/// Parses a reviewer manuscript limit from the admin form; `0` disables it and blank restores
+/// `default`.
+pub fn parse_manuscript_limit(input: &str, default: u64) -> Option<u64> {
+    let trimmed = input.trim();
+    if trimmed.is_empty() {
+        return Some(default);
+    }
+    trimmed.parse::<u64>().ok()
+}
+
+/// Clamps a stored concurrency limit so hand-edited settings cannot stall or flood a job.
+pub fn effective_concurrency(limit: usize) -> usize {
+    limit.clamp(1, MAX_CONCURRENT_DOCUMENTS_LIMIT)
+}
+
+fn default_max_concurrent_documents() -> usize {
+    DEFAULT_MAX_CONCURRENT_DOCUMENTS
+}
+
+fn default_reviewer_round1_success_ratio() -> f64 {
+    DEFAULT_REVIEWER_ROUND1_SUCCESS_RATIO
+}
+
+fn default_reviewer_max_manuscript_mb() -> u64 {
+    DEFAULT_REVIEWER_MAX_MANUSCRIPT_MB
+}
+
+fn default_reviewer_max_manuscript_pages() -> u64 {
+    DEFAULT_REVIEWER_MAX_MANUSCRIPT_PAGES
+}
+
+/// Renders routes back into the admin textarea format.
+pub fn format_model_routes(routes: &[ModelRoute]) -> String {
+    routes
+        .iter()
+        .map(|route| format!("{}={}", route.max_input_tokens, route.model))
+        .collect::<Vec<_>>()
+        .join("\n")
 }
 
 #[derive(Clone, Debug)]
@@ -194,6 +638,14 @@ pub struct SummarizerSettings {
 pub struct SummarizerModels {
     pub summary_model: String,
     pub translation_model: String,
+    /// Optional size-based overrides for `summary_model`.
+    #[serde(default)]
+    pub summary_routes: Vec<ModelRoute>,
+    #[serde(default = "default_max_concurrent_documents")]
+    pub max_concurrent_documents: usize,
+    /// Keep each document's unmodified summary response for admins to audit.
+    #[serde(default)]
+    pub store_raw_responses: bool,
 }
 
 impl Default for SummarizerModels {
@@ -224,6 +676,23 @@ pub struct DocxTranslatorSettings {
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct DocxTranslatorModels {
     pub translation_model: String,
+    #[serde(default = "default_docx_direction")]
+    pub default_direction: String,
+    /// Optional size-based overrides for `translation_model`.
+    #[serde(default)]
+    pub translation_routes: Vec<ModelRoute>,
+    /// Maximum DOCX files per job.
+    #[serde(default = "default_docx_batch_files")]
+    pub max_batch_files: usize,
+    /// Chunk limits for translation models without a `chunk_limits` rule.
+    #[serde(default)]
+    pub default_chunk_limits: ChunkLimits,
+    /// Per-model chunk size overrides; models without one use `default_chunk_limits`.
+    #[serde(default)]
+    pub chunk_limits: Vec<ModelChunkLimits>,
+    /// After translating, check that glossary targets appear wherever their source terms did.
+    #[serde(default)]
+    pub glossary_check: bool,
 }
 
 impl Default for DocxTranslatorModels {
@@ -232,6 +701,17 @@ impl Default for DocxTranslatorModels {
     }
 }
 
+impl DocxTranslatorModels {
+    /// Chunk limits configured for `model`, or the module-wide limits.
+    pub fn chunk_limits_for(&self, model: &str) -> ChunkLimits {
+        self.chunk_limits
+            .iter()
+            .find(|rule| rule.model == model)
+            .map(|rule| rule.limits)
+            .unwrap_or(self.default_chunk_limits)
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct DocxTranslatorPrompts {
     #[serde(rename = "en_to_cn")]
@@ -264,6 +744,14 @@ impl Default for InfoExtractSettings {
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct InfoExtractModels {
     pub extraction_model: String,
+    /// Optional size-based overrides for `extraction_model`.
+    #[serde(default)]
+    pub extraction_routes: Vec<ModelRoute>,
+    #[serde(default = "default_max_concurrent_documents")]
+    pub max_concurrent_documents: usize,
+    /// Consecutive failed LLM calls across the job before remaining documents fail fast; `0` = off.
+    #[serde(default = "default_provider_failure_budget")]
+    pub provider_failure_budget: usize,
 }
 
 impl Default for InfoExtractModels {
@@ -284,6 +772,35 @@ impl Default for InfoExtractPrompts {
     }
 }
 
+#[derive(Clone, Debug)]
+pub struct TranscribeSettings {
+    pub models: TranscribeModels,
+    pub prompts: TranscribePrompts,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TranscribeModels {
+    /// Must accept audio input; Poe models reject audio attachments.
+    pub transcription_model: String,
+}
+
+impl Default for TranscribeModels {
+    fn default() -> Self {
+        default_transcribe_models()
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TranscribePrompts {
+    pub transcription_instructions: String,
+}
+
+impl Default for TranscribePrompts {
+    fn default() -> Self {
+        default_transcribe_prompts()
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct GraderSettings {
     pub models: GraderModels,
@@ -294,6 +811,32 @@ pub struct GraderSettings {
 pub struct GraderModels {
     pub grading_model: String,
     pub keyword_model: String,
+    /// Optional size-based overrides for `grading_model`.
+    #[serde(default)]
+    pub grading_routes: Vec<ModelRoute>,
+    /// Tokens one grading sequence may spend before it stops sampling early (once the minimum
+    /// number of valid runs is in); `0` disables the budget.
+    #[serde(default)]
+    pub grading_token_budget: u64,
+    /// Fraction of weighted grading samples dropped from each end before averaging.
+    #[serde(default = "default_grading_trim_fraction")]
+    pub grading_trim_fraction: f64,
+    #[serde(default = "default_grading_max_attempts")]
+    pub max_attempts: usize,
+    #[serde(default = "default_grading_target_successes")]
+    pub target_successes: usize,
+    #[serde(default = "default_grading_min_successes")]
+    pub min_successes: usize,
+    /// Weights of the six journal levels when averaging one grading run.
+    #[serde(default = "default_grading_weights")]
+    pub grading_weights: [f64; 6],
+    /// Fraction deducted from DOCX manuscripts' scores; `0` grades them exactly like PDFs.
+    #[serde(default = "default_grader_docx_penalty")]
+    pub docx_penalty: f64,
+    /// Also attach PDF manuscripts as files so multimodal models can read figures; sends every
+    /// page on each sampling attempt, so it multiplies grading cost.
+    #[serde(default)]
+    pub attach_pdf: bool,
 }
 
 impl Default for GraderModels {
@@ -302,6 +845,40 @@ impl Default for GraderModels {
     }
 }
 
+impl GraderModels {
+    /// `(max_attempts, target_successes, min_successes)`, falling back to the defaults when the
+    /// stored values break `1 <= min <= target <= max <= MAX_GRADING_ATTEMPTS_LIMIT`.
+    pub fn sampling_counts(&self) -> (usize, usize, usize) {
+        if sampling_counts_valid(self.max_attempts, self.target_successes, self.min_successes) {
+            (self.max_attempts, self.target_successes, self.min_successes)
+        } else {
+            (
+                DEFAULT_GRADING_MAX_ATTEMPTS,
+                DEFAULT_GRADING_TARGET_SUCCESSES,
+                DEFAULT_GRADING_MIN_SUCCESSES,
+            )
+        }
+    }
+
+    /// The configured grading weights, or the defaults when the stored ones are invalid.
+    pub fn scoring_weights(&self) -> [f64; 6] {
+        if grading_weights_valid(&self.grading_weights) {
+            self.grading_weights
+        } else {
+            DEFAULT_GRADING_WEIGHTS
+        }
+    }
+
+    /// The configured DOCX penalty, or the default when the stored one is out of range.
+    pub fn docx_penalty_fraction(&self) -> f64 {
+        if (0.0..=MAX_GRADER_DOCX_PENALTY).contains(&self.docx_penalty) {
+            self.docx_penalty
+        } else {
+            DEFAULT_GRADER_DOCX_PENALTY
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct GraderPrompts {
     pub grading_instructions: String,
@@ -322,16 +899,22 @@ pub struct ReviewerSettings {
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ReviewerModels {
-    pub round1_model_1: String,
-    pub round1_model_2: String,
-    pub round1_model_3: String,
-    pub round1_model_4: String,
-    pub round1_model_5: String,
-    pub round1_model_6: String,
-    pub round1_model_7: String,
-    pub round1_model_8: String,
+    /// Models that each write one independent round-1 review, in order.
+    pub round1_models: Vec<String>,
+    /// Share of round-1 reviews (rounded up, at least one) that must succeed.
+    #[serde(default = "default_reviewer_round1_success_ratio")]
+    pub round1_success_ratio: f64,
+    /// Models a round-1 review falls back to, in order, when its own model is unavailable.
+    #[serde(default)]
+    pub round1_fallback_models: Vec<String>,
     pub round2_model: String,
     pub round3_model: String,
+    /// Largest manuscript file accepted at submission, in megabytes; `0` disables the check.
+    #[serde(default = "default_reviewer_max_manuscript_mb")]
+    pub max_manuscript_mb: u64,
+    /// Most PDF pages a manuscript may have (DOCX is counted after conversion); `0` disables it.
+    #[serde(default = "default_reviewer_max_manuscript_pages")]
+    pub max_manuscript_pages: u64,
 }
 
 impl Default for ReviewerModels {
@@ -340,6 +923,20 @@ impl Default for ReviewerModels {
     }
 }
 
+impl ReviewerModels {
+    /// Round-1 reviews that must succeed, from the configured ratio (or the default ratio when
+    /// the stored one is out of range).
+    pub fn round1_min_successes(&self) -> usize {
+        let ratio = if success_ratio_valid(self.round1_success_ratio) {
+            self.round1_success_ratio
+        } else {
+            DEFAULT_REVIEWER_ROUND1_SUCCESS_RATIO
+        };
+        let count = self.round1_models.len();
+        ((count as f64 * ratio).ceil() as usize).clamp(1, count.max(1))
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ReviewerPrompts {
     pub initial_prompt: String,
@@ -395,6 +992,77 @@ fn parse_reviewer_settings(models: Value, prompts: Value) -> Result<ReviewerSett
     Ok(ReviewerSettings { models, prompts })
 }
 
+/// Rows that predate a setting, or where an admin saved a blank value, would otherwise fail to
+/// parse or leave a module running with an empty prompt. Fill only those gaps with the
+/// built-in defaults; anything an admin has set is left untouched.
+async fn backfill_module_defaults(
+    pool: &PgPool,
+    defaults: &[(&str, &Value, &Value)],
+) -> Result<()> {
+    let rows = sqlx::query_as::<_, ModuleConfigRow>(
+        "SELECT module_name, models, prompts FROM module_configs",
+    )
+    .fetch_all(pool)
+    .await
+    .context("failed to read module configurations for backfill")?;
+
+    for row in rows {
+        let Some((_, default_models, default_prompts)) = defaults
+            .iter()
+            .find(|(module, _, _)| *module == row.module_name)
+        else {
+            continue;
+        };
+
+        let models = fill_missing_defaults(&row.models, default_models);
+        let prompts = fill_missing_defaults(&row.prompts, default_prompts);
+        if models.is_none() && prompts.is_none() {
+            continue;
+        }
+
+        sqlx::query(
+            "UPDATE module_configs SET models = $2, prompts = $3, updated_at = NOW()
+             WHERE module_name = $1",
+        )
+        .bind(&row.module_name)
+        .bind(models.as_ref().unwrap_or(&row.models))
+        .bind(prompts.as_ref().unwrap_or(&row.prompts))
+        .execute(pool)
+        .await
+        .with_context(|| format!("failed to backfill defaults for {}", row.module_name))?;
+        info!(module = %row.module_name, "filled missing module settings with defaults");
+    }
+
+    Ok(())
+}
+
+/// Returns `stored` with every key from `defaults` that is missing, null, or a blank string
+/// replaced by the default, or `None` when nothing needed filling.
+fn fill_missing_defaults(stored: &Value, defaults: &Value) -> Option<Value> {
+    let Value::Object(default_map) = defaults else {
+        return None;
+    };
+    let mut merged = match stored {
+        Value::Object(map) => map.clone(),
+        _ => serde_json::Map::new(),
+    };
+
+    let mut changed = !stored.is_object();
+    for (key, default_value) in default_map {
+        let missing = match merged.get(key) {
+            None | Some(Value::Null) => true,
+            Some(Value::String(text)) => text.trim().is_empty(),
+            Some(_) => false,
+        };
+        if missing {
+            merged.insert(key.clone(), default_value.clone());
+            changed = true;
+        }
+    }
+
+    changed.then_some(Value::Object(merged))
+}
+
 fn parse_info_extract_settings(models: Value, prompts: Value) -> Result<InfoExtractSettings> {
     let models: InfoExtractModels = serde_json::from_value(models)
         .map_err(|err| anyhow!("failed to parse info extract models: {err}"))?;
@@ -403,10 +1071,21 @@ fn parse_info_extract_settings(models: Value, prompts: Value) -> Result<InfoExtr
     Ok(InfoExtractSettings { models, prompts })
 }
 
+fn parse_transcribe_settings(models: Value, prompts: Value) -> Result<TranscribeSettings> {
+    let models: TranscribeModels = serde_json::from_value(models)
+        .map_err(|err| anyhow!("failed to parse transcribe models: {err}"))?;
+    let prompts: TranscribePrompts = serde_json::from_value(prompts)
+        .map_err(|err| anyhow!("failed to parse transcribe prompts: {err}"))?;
+    Ok(TranscribeSettings { models, prompts })
+}
+
 fn default_summarizer_models() -> SummarizerModels {
     SummarizerModels {
         summary_model: "openrouter/anthropic/claude-3-haiku".to_string(),
         translation_model: "openrouter/openai/gpt-4o-mini".to_string(),
+        summary_routes: Vec::new(),
+        max_concurrent_documents: DEFAULT_MAX_CONCURRENT_DOCUMENTS,
+        store_raw_responses: false,
     }
 }
 
@@ -421,9 +1100,23 @@ fn default_summarizer_prompts() -> SummarizerPrompts {
 fn default_docx_models() -> DocxTranslatorModels {
     DocxTranslatorModels {
         translation_model: "openrouter/openai/gpt-4o-mini".to_string(),
+        default_direction: default_docx_direction(),
+        translation_routes: Vec::new(),
+        max_batch_files: DEFAULT_DOCX_BATCH_FILES,
+        default_chunk_limits: ChunkLimits::default(),
+        chunk_limits: Vec::new(),
+        glossary_check: false,
     }
 }
 
+fn default_docx_batch_files() -> usize {
+    DEFAULT_DOCX_BATCH_FILES
+}
+
+fn default_docx_direction() -> String {
+    "en_to_cn".to_string()
+}
+
 fn default_docx_prompts() -> DocxTranslatorPrompts {
     DocxTranslatorPrompts {
         en_to_cn: "You are an expert translator for academic manuscripts from English (EN) to Chinese (CN). Maintain formal academic tone and style in CN.\nUse the glossary consistently—each entry is EN -> CN:\n{{GLOSSARY}}\nThe user's input contains multiple paragraphs separated by the exact marker {{PARAGRAPH_SEPARATOR}}. Return the translated paragraphs with the same marker preserved between them.\nIf a paragraph is only a URL or citation, return it unchanged.".to_string(),
@@ -434,9 +1127,16 @@ fn default_docx_prompts() -> DocxTranslatorPrompts {
 fn default_info_extract_models() -> InfoExtractModels {
     InfoExtractModels {
         extraction_model: "openrouter/openai/gpt-4o-mini".to_string(),
+        extraction_routes: Vec::new(),
+        max_concurrent_documents: DEFAULT_MAX_CONCURRENT_DOCUMENTS,
+        provider_failure_budget: DEFAULT_PROVIDER_FAILURE_BUDGET,
     }
 }
 
+fn default_provider_failure_budget() -> usize {
+    DEFAULT_PROVIDER_FAILURE_BUDGET
+}
+
 fn default_info_extract_prompts() -> InfoExtractPrompts {
     InfoExtractPrompts {
         system_prompt: "你是一名科学文献信息抽取助手，只依据提供的正文回答。不得臆测或编造信息，若内容未明确给出请返回 null 并说明不确定性。".to_string(),
@@ -444,13 +1144,58 @@ fn default_info_extract_prompts() -> InfoExtractPrompts {
     }
 }
 
+fn default_transcribe_models() -> TranscribeModels {
+    TranscribeModels {
+        transcription_model: "openrouter/google/gemini-2.5-flash".to_string(),
+    }
+}
+
+fn default_transcribe_prompts() -> TranscribePrompts {
+    TranscribePrompts {
+        transcription_instructions: "Transcribe the attached audio recording verbatim in the language that is spoken. Start a new paragraph when the speaker or topic changes. Do not summarise, translate or add commentary, and return only the transcript text. If a passage is inaudible, write [inaudible] in its place.".to_string(),
+    }
+}
+
 fn default_grader_models() -> GraderModels {
     GraderModels {
         grading_model: "openrouter/openai/gpt-4o-mini".to_string(),
         keyword_model: "openrouter/openai/gpt-4o-mini".to_string(),
+        grading_routes: Vec::new(),
+        grading_token_budget: 0,
+        grading_trim_fraction: DEFAULT_GRADING_TRIM_FRACTION,
+        max_attempts: DEFAULT_GRADING_MAX_ATTEMPTS,
+        target_successes: DEFAULT_GRADING_TARGET_SUCCESSES,
+        min_successes: DEFAULT_GRADING_MIN_SUCCESSES,
+        grading_weights: DEFAULT_GRADING_WEIGHTS,
+        docx_penalty: DEFAULT_GRADER_DOCX_PENALTY,
+        attach_pdf: false,
     }
 }
 
+fn default_grading_weights() -> [f64; 6] {
+    DEFAULT_GRADING_WEIGHTS
+}
+
+fn default_grading_max_attempts() -> usize {
+    DEFAULT_GRADING_MAX_ATTEMPTS
+}
+
+fn default_grading_target_successes() -> usize {
+    DEFAULT_GRADING_TARGET_SUCCESSES
+}
+
+fn default_grading_min_successes() -> usize {
+    DEFAULT_GRADING_MIN_SUCCESSES
+}
+
+fn default_grading_trim_fraction() -> f64 {
+    DEFAULT_GRADING_TRIM_FRACTION
+}
+
+fn default_grader_docx_penalty() -> f64 {
+    DEFAULT_GRADER_DOCX_PENALTY
+}
+
 fn default_grader_prompts() -> GraderPrompts {
     GraderPrompts {
         grading_instructions: PROTOTYPE_GRADER_PROMPT.to_string(),
@@ -460,16 +1205,25 @@ fn default_grader_prompts() -> GraderPrompts {
 
 fn default_reviewer_models() -> ReviewerModels {
     ReviewerModels {
-        round1_model_1: "openrouter/openai/gpt-4o".to_string(),
-        round1_model_2: "openrouter/anthropic/claude-3.5-sonnet".to_string(),
-        round1_model_3: "openrouter/google/gemini-pro-1.5".to_string(),
-        round1_model_4: "openrouter/meta-llama/llama-3.1-70b-instruct".to_string(),
-        round1_model_5: "openrouter/qwen/qwen-2.5-72b-instruct".to_string(),
-        round1_model_6: "openrouter/mistralai/mistral-large-2".to_string(),
-        round1_model_7: "openrouter/x-ai/grok-2".to_string(),
-        round1_model_8: "openrouter/deepseek/deepseek-chat".to_string(),
+        round1_models: [
+            "openrouter/openai/gpt-4o",
+            "openrouter/anthropic/claude-3.5-sonnet",
+            "openrouter/google/gemini-pro-1.5",
+            "openrouter/meta-llama/llama-3.1-70b-instruct",
+            "openrouter/qwen/qwen-2.5-72b-instruct",
+            "openrouter/mistralai/mistral-large-2",
+            "openrouter/x-ai/grok-2",
+            "openrouter/deepseek/deepseek-chat",
+        ]
+        .into_iter()
+        .map(str::to_string)
+        .collect(),
+        round1_success_ratio: DEFAULT_REVIEWER_ROUND1_SUCCESS_RATIO,
+        round1_fallback_models: Vec::new(),
         round2_model: "openrouter/openai/gpt-4o".to_string(),
         round3_model: "openrouter/openai/gpt-4o".to_string(),
+        max_manuscript_mb: DEFAULT_REVIEWER_MAX_MANUSCRIPT_MB,
+        max_manuscript_pages: DEFAULT_REVIEWER_MAX_MANUSCRIPT_PAGES,
     }
 }
 
@@ -527,6 +1281,14 @@ pub async fn update_info_extract_prompts(
     update_prompts(pool, MODULE_INFO_EXTRACT, prompts).await
 }
 
+pub async fn update_transcribe_models(pool: &PgPool, models: &TranscribeModels) -> Result<()> {
+    update_models(pool, MODULE_TRANSCRIBE, models).await
+}
+
+pub async fn update_transcribe_prompts(pool: &PgPool, prompts: &TranscribePrompts) -> Result<()> {
+    update_prompts(pool, MODULE_TRANSCRIBE, prompts).await
+}
+
 async fn update_models<T: Serialize>(pool: &PgPool, module: &str, models: &T) -> Result<()> {
     let payload = serde_json::to_value(models)
         .map_err(|err| anyhow!("failed to serialize models payload: {err}"))?;
@@ -560,3 +1322,339 @@ async fn update_prompts<T: Serialize>(pool: &PgPool, module: &str, prompts: &T)
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn assert_strings_non_empty(value: &Value, context: &str) {
+        let Value::Object(map) = value else {
+            panic!("{context} is not an object");
+        };
+        for (key, value) in map {
+            if let Value::String(text) = value {
+                assert!(!text.trim().is_empty(), "{context}.{key} is empty");
+            }
+        }
+    }
+
+    #[test]
+    fn bootstrap_fills_blank_and_missing_prompts_for_every_module() {
+        let modules = [
+            (
+                MODULE_SUMMARIZER,
+                serde_json::to_value(default_summarizer_models()).unwrap(),
+                serde_json::to_value(default_summarizer_prompts()).unwrap(),
+            ),
+            (
+                MODULE_TRANSLATE_DOCX,
+                serde_json::to_value(default_docx_models()).unwrap(),
+                serde_json::to_value(default_docx_prompts()).unwrap(),
+            ),
+            (
+                MODULE_GRADER,
+                serde_json::to_value(default_grader_models()).unwrap(),
+                serde_json::to_value(default_grader_prompts()).unwrap(),
+            ),
+            (
+                MODULE_REVIEWER,
+                serde_json::to_value(default_reviewer_models()).unwrap(),
+                serde_json::to_value(default_reviewer_prompts()).unwrap(),
+            ),
+            (
+                MODULE_INFO_EXTRACT,
+                serde_json::to_value(default_info_extract_models()).unwrap(),
+                serde_json::to_value(default_info_extract_prompts()).unwrap(),
+            ),
+            (
+                MODULE_TRANSCRIBE,
+                serde_json::to_value(default_transcribe_models()).unwrap(),
+                serde_json::to_value(default_transcribe_prompts()).unwrap(),
+            ),
+        ];
+
+        for (module, default_models, default_prompts) in &modules {
+            // A row seeded before any prompts existed, with one prompt blanked by an admin.
+            let first_key = default_prompts.as_object().unwrap().keys().next().unwrap();
+            let stored_prompts = json!({ first_key.clone(): "   " });
+            let prompts = fill_missing_defaults(&stored_prompts, default_prompts)
+                .expect("blank prompts are filled");
+            let models = fill_missing_defaults(&json!({}), default_models)
+                .expect("missing models are filled");
+
+            let mut settings = ModuleSettings::default();
+            settings
+                .apply_row(ModuleConfigRow {
+                    module_name: module.to_string(),
+                    models,
+                    prompts,
+                })
+                .expect("backfilled row parses");
+
+            let loaded_prompts = match *module {
+                MODULE_SUMMARIZER => serde_json::to_value(&settings.summarizer().unwrap().prompts),
+                MODULE_TRANSLATE_DOCX => {
+                    serde_json::to_value(&settings.translate_docx().unwrap().prompts)
+                }
+                MODULE_GRADER => serde_json::to_value(&settings.grader().unwrap().prompts),
+                MODULE_REVIEWER => serde_json::to_value(&settings.reviewer().unwrap().prompts),
+                MODULE_TRANSCRIBE => serde_json::to_value(&settings.transcribe().unwrap().prompts),
+                _ => serde_json::to_value(&settings.info_extract().unwrap().prompts),
+            }
+            .unwrap();
+            assert_strings_non_empty(&loaded_prompts, module);
+        }
+    }
+
+    #[test]
+    fn bootstrap_keeps_admin_edits() {
+        let defaults = json!({ "a": "default a", "b": "default b" });
+        assert_eq!(
+            fill_missing_defaults(&json!({ "a": "custom", "b": "also custom" }), &defaults),
+            None
+        );
+        assert_eq!(
+            fill_missing_defaults(&json!({ "a": "custom" }), &defaults),
+            Some(json!({ "a": "custom", "b": "default b" }))
+        );
+    }
+
+    #[test]
+    fn routes_small_inputs_to_cheap_model_and_large_to_default() {
+        let routes = parse_model_routes("8000=openrouter/cheap\n# comment\n\n").unwrap();
+        let default_model = "openrouter/large-context";
+
+        assert_eq!(
+            route_model(default_model, &routes, 1_200),
+            "openrouter/cheap"
+        );
+        assert_eq!(
+            route_model(default_model, &routes, 50_000),
+            "openrouter/large-context"
+        );
+        assert_eq!(route_model(default_model, &[], 1_200), default_model);
+    }
+
+    #[test]
+    fn picks_tightest_matching_route() {
+        let routes = parse_model_routes("32,000=mid\n4000=small").unwrap();
+        assert_eq!(routes[0].model, "small");
+        assert_eq!(route_model("big", &routes, 3_000), "small");
+        assert_eq!(route_model("big", &routes, 20_000), "mid");
+        assert_eq!(format_model_routes(&routes), "4000=small\n32000=mid");
+    }
+
+    #[test]
+    fn rejects_malformed_routes() {
+        assert!(parse_model_routes("cheap-model").is_err());
+        assert!(parse_model_routes("abc=model").is_err());
+        assert!(parse_model_routes("1000=").is_err());
+    }
+
+    #[test]
+    fn models_without_routes_deserialize() {
+        let models: InfoExtractModels =
+            serde_json::from_value(serde_json::json!({ "extraction_model": "m" })).unwrap();
+        assert!(models.extraction_routes.is_empty());
+        assert_eq!(
+            models.max_concurrent_documents,
+            DEFAULT_MAX_CONCURRENT_DOCUMENTS
+        );
+    }
+
+    #[test]
+    fn concurrency_limit_is_validated() {
+        assert_eq!(parse_concurrency_limit(" 12 "), Some(12));
+        assert_eq!(parse_concurrency_limit("0"), None);
+        assert_eq!(parse_concurrency_limit("51"), None);
+        assert_eq!(parse_concurrency_limit("five"), None);
+        assert_eq!(effective_concurrency(0), 1);
+        assert_eq!(effective_concurrency(500), MAX_CONCURRENT_DOCUMENTS_LIMIT);
+    }
+
+    #[test]
+    fn docx_batch_limit_defaults_and_validates() {
+        let models: DocxTranslatorModels =
+            serde_json::from_value(serde_json::json!({ "translation_model": "m" })).unwrap();
+        assert_eq!(models.max_batch_files, DEFAULT_DOCX_BATCH_FILES);
+        assert_eq!(parse_batch_limit("20"), Some(20));
+        assert_eq!(parse_batch_limit("0"), None);
+        assert_eq!(parse_batch_limit("101"), None);
+    }
+
+    #[test]
+    fn chunk_limits_parse_per_model_overrides() {
+        let rules = parse_chunk_limits(
+            "# comment\nopenrouter/openai/gpt-4o-mini = 12, 400\n\npoe/Claude=30,1200",
+        )
+        .unwrap();
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].model, "openrouter/openai/gpt-4o-mini");
+        assert_eq!(
+            rules[0].limits,
+            ChunkLimits {
+                max_paragraphs: 12,
+                max_words: 400
+            }
+        );
+        assert_eq!(
+            parse_chunk_limits(&format_chunk_limits(&rules)).unwrap(),
+            rules
+        );
+
+        let mut models = DocxTranslatorModels {
+            chunk_limits: rules,
+            ..DocxTranslatorModels::default()
+        };
+        assert_eq!(models.chunk_limits_for("poe/Claude").max_words, 1200);
+        assert_eq!(models.chunk_limits_for("other"), ChunkLimits::default());
+        models.default_chunk_limits = ChunkLimits {
+            max_paragraphs: 40,
+            max_words: 1500,
+        };
+        assert_eq!(models.chunk_limits_for("other").max_paragraphs, 40);
+        assert_eq!(models.chunk_limits_for("poe/Claude").max_words, 1200);
+
+        assert!(parse_chunk_limits("model=0,400").is_err());
+        assert!(parse_chunk_limits("model=10").is_err());
+        assert!(parse_chunk_limits("=10,400").is_err());
+    }
+
+    #[test]
+    fn default_chunk_limits_must_be_positive() {
+        assert_eq!(
+            parse_default_chunk_limits("", " "),
+            Some(ChunkLimits::default())
+        );
+        assert_eq!(
+            parse_default_chunk_limits("40", "1,500"),
+            Some(ChunkLimits {
+                max_paragraphs: 40,
+                max_words: 1500
+            })
+        );
+        assert_eq!(parse_default_chunk_limits("0", "700"), None);
+        assert_eq!(parse_default_chunk_limits("20", "-5"), None);
+        assert_eq!(parse_default_chunk_limits("201", "700"), None);
+    }
+
+    #[test]
+    fn trim_fraction_rejects_values_that_trim_everything() {
+        assert_eq!(parse_trim_fraction(""), Some(DEFAULT_GRADING_TRIM_FRACTION));
+        assert_eq!(parse_trim_fraction("0.1"), Some(0.1));
+        assert_eq!(parse_trim_fraction("0"), Some(0.0));
+        assert_eq!(parse_trim_fraction("0.5"), None);
+        assert_eq!(parse_trim_fraction("-0.1"), None);
+        assert_eq!(parse_trim_fraction("NaN"), None);
+    }
+
+    #[test]
+    fn failure_budget_defaults_and_validates() {
+        assert_eq!(
+            parse_failure_budget(""),
+            Some(DEFAULT_PROVIDER_FAILURE_BUDGET)
+        );
+        assert_eq!(parse_failure_budget("0"), Some(0));
+        assert_eq!(parse_failure_budget(" 12 "), Some(12));
+        assert_eq!(parse_failure_budget("101"), None);
+        assert_eq!(parse_failure_budget("-1"), None);
+    }
+
+    #[test]
+    fn docx_penalty_defaults_and_validates() {
+        assert_eq!(parse_docx_penalty(""), Some(DEFAULT_GRADER_DOCX_PENALTY));
+        assert_eq!(parse_docx_penalty("0"), Some(0.0));
+        assert_eq!(parse_docx_penalty(" 0.05 "), Some(0.05));
+        assert_eq!(parse_docx_penalty("0.6"), None);
+        assert_eq!(parse_docx_penalty("-0.01"), None);
+        assert_eq!(parse_docx_penalty("NaN"), None);
+
+        let stored = GraderModels {
+            docx_penalty: 2.0,
+            ..GraderModels::default()
+        };
+        assert_eq!(stored.docx_penalty_fraction(), DEFAULT_GRADER_DOCX_PENALTY);
+    }
+
+    #[test]
+    fn round1_models_parse_one_per_line() {
+        assert_eq!(
+            parse_round1_models(" a/model \n\n b/model\n"),
+            Some(vec!["a/model".to_string(), "b/model".to_string()])
+        );
+        assert_eq!(parse_round1_models("  \n"), None);
+        let too_many = vec!["m"; MAX_REVIEWER_ROUND1_MODELS + 1].join("\n");
+        assert_eq!(parse_round1_models(&too_many), None);
+    }
+
+    #[test]
+    fn round1_min_successes_follow_ratio() {
+        assert_eq!(ReviewerModels::default().round1_min_successes(), 4);
+
+        let four = ReviewerModels {
+            round1_models: vec!["m".to_string(); 4],
+            round1_success_ratio: 0.6,
+            ..ReviewerModels::default()
+        };
+        assert_eq!(four.round1_min_successes(), 3);
+
+        let invalid = ReviewerModels {
+            round1_success_ratio: 0.0,
+            ..four.clone()
+        };
+        assert_eq!(invalid.round1_min_successes(), 2);
+
+        assert_eq!(
+            parse_success_ratio(""),
+            Some(DEFAULT_REVIEWER_ROUND1_SUCCESS_RATIO)
+        );
+        assert_eq!(parse_success_ratio("1"), Some(1.0));
+        assert_eq!(parse_success_ratio("0"), None);
+        assert_eq!(parse_success_ratio("1.5"), None);
+    }
+
+    #[test]
+    fn manuscript_limit_defaults_and_validates() {
+        assert_eq!(parse_manuscript_limit("", 25), Some(25));
+        assert_eq!(parse_manuscript_limit(" 0 ", 25), Some(0));
+        assert_eq!(parse_manuscript_limit("120", 80), Some(120));
+        assert_eq!(parse_manuscript_limit("-1", 80), None);
+        assert_eq!(parse_manuscript_limit("1.5", 25), None);
+    }
+
+    #[test]
+    fn grading_weights_parse_and_validate() {
+        assert_eq!(parse_grading_weights(""), Some(DEFAULT_GRADING_WEIGHTS));
+        assert_eq!(
+            parse_grading_weights("3, 3，1 1 0 0.5"),
+            Some([3.0, 3.0, 1.0, 1.0, 0.0, 0.5])
+        );
+        assert_eq!(parse_grading_weights("1, 1, 1, 1, 1"), None);
+        assert_eq!(parse_grading_weights("1, 1, 1, 1, 1, -1"), None);
+        assert_eq!(parse_grading_weights("0, 0, 0, 0, 0, 0"), None);
+        assert_eq!(parse_grading_weights("1, 1, 1, 1, 1, x"), None);
+    }
+
+    #[test]
+    fn sampling_counts_require_min_target_max_order() {
+        assert_eq!(parse_sampling_counts("15", "6", "4"), Some((15, 6, 4)));
+        assert_eq!(parse_sampling_counts("10", "12", "8"), None);
+        assert_eq!(parse_sampling_counts("30", "6", "8"), None);
+        assert_eq!(parse_sampling_counts("30", "12", "0"), None);
+        assert_eq!(parse_sampling_counts("101", "12", "8"), None);
+
+        let models = GraderModels {
+            target_successes: 40,
+            ..GraderModels::default()
+        };
+        assert_eq!(
+            models.sampling_counts(),
+            (
+                DEFAULT_GRADING_MAX_ATTEMPTS,
+                DEFAULT_GRADING_TARGET_SUCCESSES,
+                DEFAULT_GRADING_MIN_SUCCESSES
+            )
+        );
+    }
+}
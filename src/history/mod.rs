@@ -1,16 +1,16 @@
 use std::{collections::HashMap, time::Duration as StdDuration};
 
 use anyhow::{Context, Result};
-use chrono::{DateTime, Duration, Utc};
+use chrono::{DateTime, Utc};
 use sqlx::{PgPool, Row};
 use tracing::warn;
 use uuid::Uuid;
 
-use crate::usage;
+use crate::{maintenance, usage};
 
-pub const HISTORY_RETENTION_HOURS: i64 = 24;
 const HISTORY_LIMIT: i64 = 50;
-const POLL_WINDOW: Duration = Duration::hours(HISTORY_RETENTION_HOURS);
+/// Largest page `fetch_recent_jobs` serves.
+pub const MAX_PAGE_SIZE: i64 = HISTORY_LIMIT;
 
 #[derive(Debug, Clone)]
 pub struct ModuleMetadata {
@@ -57,12 +57,31 @@ const MODULES: &[ModuleMetadata] = &[
         status_path_prefix: "/api/reviewer/jobs/",
         supports_downloads: true,
     },
+    ModuleMetadata {
+        key: usage::MODULE_TRANSCRIBE,
+        label: "音频转写",
+        tool_path: "/tools/transcribe",
+        status_path_prefix: "/api/transcribe/jobs/",
+        supports_downloads: true,
+    },
+    ModuleMetadata {
+        key: usage::MODULE_DOCX_TO_PDF,
+        label: "DOCX 转 PDF",
+        tool_path: "/tools/docx2pdf",
+        status_path_prefix: "/api/docx2pdf/jobs/",
+        supports_downloads: true,
+    },
 ];
 
 pub fn module_metadata(key: &str) -> Option<&'static ModuleMetadata> {
     MODULES.iter().find(|meta| meta.key == key)
 }
 
+/// Every module that records history, in home-page display order.
+pub fn all_modules() -> &'static [ModuleMetadata] {
+    MODULES
+}
+
 #[derive(Debug, Clone)]
 pub struct HistoryEntry {
     pub module: String,
@@ -127,46 +146,188 @@ pub async fn record_job_start(
     Ok(())
 }
 
+/// One page of a user's recent jobs, newest first, with the number of jobs across all pages.
+pub struct HistoryPage {
+    pub entries: Vec<HistoryEntry>,
+    pub total: i64,
+}
+
+/// Optional narrowing of `fetch_recent_jobs`; every field left as `None` matches all jobs.
+#[derive(Debug, Clone, Default)]
+pub struct HistoryFilter {
+    /// Case-insensitive substring of any uploaded filename of the job.
+    pub filename: Option<String>,
+    /// Exact job status, e.g. `completed`.
+    pub status: Option<String>,
+    /// Jobs submitted at or after this instant.
+    pub created_from: Option<DateTime<Utc>>,
+    /// Jobs submitted before this instant.
+    pub created_to: Option<DateTime<Utc>>,
+}
+
+impl HistoryFilter {
+    /// The `ILIKE` pattern for the filename filter, with `%`, `_` and `\` in the search text
+    /// matched literally.
+    fn filename_pattern(&self) -> Option<String> {
+        let needle = self.filename.as_deref()?.trim();
+        if needle.is_empty() {
+            return None;
+        }
+        let mut pattern = String::with_capacity(needle.len() + 2);
+        pattern.push('%');
+        for ch in needle.chars() {
+            if matches!(ch, '%' | '_' | '\\') {
+                pattern.push('\\');
+            }
+            pattern.push(ch);
+        }
+        pattern.push('%');
+        Some(pattern)
+    }
+}
+
+/// Shared `WHERE` clause for the history listing. `$1`..`$3` are user, module and the default
+/// retention window in days; `$4`..`$7` are the `HistoryFilter` binds (status, filename
+/// pattern, from, to). A row stays listed for its module's file retention window, so history
+/// reaches back as far as the files it links to.
+/// Job keys are cast per module inside `CASE`, so only keys of that module are parsed.
+const HISTORY_FILTER_SQL: &str = r#"h.user_id = $1
+    AND ($2::TEXT IS NULL OR h.module = $2)
+    AND h.created_at >= NOW() - make_interval(days => COALESCE(
+        (SELECT p.retention_days FROM file_retention_policies p WHERE p.module_key = h.module),
+        $3::INT))
+    AND ($4::TEXT IS NULL OR CASE h.module
+        WHEN 'summarizer' THEN EXISTS (
+            SELECT 1 FROM summary_jobs j WHERE j.id = h.job_key::UUID AND j.status = $4)
+        WHEN 'translatedocx' THEN EXISTS (
+            SELECT 1 FROM docx_jobs j WHERE j.id = h.job_key::UUID AND j.status = $4)
+        WHEN 'grader' THEN EXISTS (
+            SELECT 1 FROM grader_jobs j WHERE j.id = h.job_key::UUID AND j.status = $4)
+        WHEN 'info_extract' THEN EXISTS (
+            SELECT 1 FROM info_extract_jobs j WHERE j.id = h.job_key::UUID AND j.status = $4)
+        WHEN 'reviewer' THEN EXISTS (
+            SELECT 1 FROM reviewer_jobs j WHERE j.job_id = h.job_key::INTEGER AND j.status = $4)
+        WHEN 'transcribe' THEN EXISTS (
+            SELECT 1 FROM transcribe_jobs j WHERE j.id = h.job_key::UUID AND j.status = $4)
+        WHEN 'docx2pdf' THEN EXISTS (
+            SELECT 1 FROM docx2pdf_jobs j WHERE j.id = h.job_key::UUID AND j.status = $4)
+        ELSE FALSE END)
+    AND ($5::TEXT IS NULL OR CASE h.module
+        WHEN 'summarizer' THEN EXISTS (
+            SELECT 1 FROM summary_documents d
+            WHERE d.job_id = h.job_key::UUID AND d.original_filename ILIKE $5 ESCAPE '\')
+        WHEN 'translatedocx' THEN EXISTS (
+            SELECT 1 FROM docx_documents d
+            WHERE d.job_id = h.job_key::UUID AND d.original_filename ILIKE $5 ESCAPE '\')
+        WHEN 'grader' THEN EXISTS (
+            SELECT 1 FROM grader_documents d
+            WHERE d.job_id = h.job_key::UUID AND d.original_filename ILIKE $5 ESCAPE '\')
+        WHEN 'info_extract' THEN EXISTS (
+            SELECT 1 FROM info_extract_documents d
+            WHERE d.job_id = h.job_key::UUID AND d.original_filename ILIKE $5 ESCAPE '\')
+            OR EXISTS (
+            SELECT 1 FROM info_extract_jobs j
+            WHERE j.id = h.job_key::UUID AND j.spec_filename ILIKE $5 ESCAPE '\')
+        WHEN 'reviewer' THEN EXISTS (
+            SELECT 1 FROM reviewer_jobs j
+            WHERE j.job_id = h.job_key::INTEGER AND j.filename ILIKE $5 ESCAPE '\')
+        WHEN 'transcribe' THEN EXISTS (
+            SELECT 1 FROM transcribe_jobs j
+            WHERE j.id = h.job_key::UUID AND j.original_filename ILIKE $5 ESCAPE '\')
+        WHEN 'docx2pdf' THEN EXISTS (
+            SELECT 1 FROM docx2pdf_jobs j
+            WHERE j.id = h.job_key::UUID AND j.original_filename ILIKE $5 ESCAPE '\')
+        ELSE FALSE END)
+    AND ($6::TIMESTAMPTZ IS NULL OR h.created_at >= $6)
+    AND ($7::TIMESTAMPTZ IS NULL OR h.created_at < $7)"#;
+
+/// Loads page `page` (1-based) of `page_size` recent jobs matching `filter`, optionally for a
+/// single module. `page_size` is clamped to `1..=MAX_PAGE_SIZE`.
 pub async fn fetch_recent_jobs(
     pool: &PgPool,
     user_id: Uuid,
     module_filter: Option<&str>,
-    limit: i64,
-) -> Result<Vec<HistoryEntry>> {
-    let limit = limit.clamp(1, HISTORY_LIMIT);
-    let cutoff = Utc::now() - POLL_WINDOW;
-
-    let rows = if let Some(module) = module_filter {
-        sqlx::query_as::<_, HistoryRow>(
-            "SELECT module, job_key, created_at
-             FROM user_job_history
-             WHERE user_id = $1 AND module = $2 AND created_at >= $3
-             ORDER BY created_at DESC
-             LIMIT $4",
-        )
+    filter: &HistoryFilter,
+    page: i64,
+    page_size: i64,
+) -> Result<HistoryPage> {
+    let page_size = page_size.clamp(1, MAX_PAGE_SIZE);
+    let offset = (page.max(1) - 1).saturating_mul(page_size);
+    let default_days = maintenance::DEFAULT_RETENTION_DAYS as i32;
+    let filename_pattern = filter.filename_pattern();
+
+    let count_sql = format!("SELECT COUNT(*) FROM user_job_history h WHERE {HISTORY_FILTER_SQL}");
+    let total: i64 = sqlx::query_scalar(&count_sql)
         .bind(user_id)
-        .bind(module)
-        .bind(cutoff)
-        .bind(limit)
-        .fetch_all(pool)
+        .bind(module_filter)
+        .bind(default_days)
+        .bind(filter.status.as_deref())
+        .bind(filename_pattern.as_deref())
+        .bind(filter.created_from)
+        .bind(filter.created_to)
+        .fetch_one(pool)
         .await
-        .with_context(|| format!("failed to load history rows for module {module}"))?
-    } else {
-        sqlx::query_as::<_, HistoryRow>(
-            "SELECT module, job_key, created_at
-             FROM user_job_history
-             WHERE user_id = $1 AND created_at >= $2
-             ORDER BY created_at DESC
-             LIMIT $3",
-        )
+        .context("failed to count history rows")?;
+
+    let page_sql = format!(
+        "SELECT h.module, h.job_key, h.created_at
+         FROM user_job_history h
+         WHERE {HISTORY_FILTER_SQL}
+         ORDER BY h.created_at DESC, h.id DESC
+         LIMIT $8 OFFSET $9"
+    );
+    let rows = sqlx::query_as::<_, HistoryRow>(&page_sql)
         .bind(user_id)
-        .bind(cutoff)
-        .bind(limit)
+        .bind(module_filter)
+        .bind(default_days)
+        .bind(filter.status.as_deref())
+        .bind(filename_pattern.as_deref())
+        .bind(filter.created_from)
+        .bind(filter.created_to)
+        .bind(page_size)
+        .bind(offset)
         .fetch_all(pool)
         .await
-        .context("failed to load history rows")?
-    };
+        .context("failed to load history rows")?;
+
+    Ok(HistoryPage {
+        entries: hydrate_rows(pool, rows).await?,
+        total,
+    })
+}
 
+/// Loads up to `per_module` of the user's most recent jobs for each module, newest first.
+pub async fn fetch_recent_jobs_by_module(
+    pool: &PgPool,
+    user_id: Uuid,
+    per_module: i64,
+) -> Result<Vec<HistoryEntry>> {
+    let per_module = per_module.clamp(1, HISTORY_LIMIT);
+
+    let rows = sqlx::query_as::<_, HistoryRow>(
+        "SELECT module, job_key, created_at
+         FROM (
+             SELECT h.module, h.job_key, h.created_at,
+                    ROW_NUMBER() OVER (PARTITION BY h.module ORDER BY h.created_at DESC, h.id DESC) AS row_rank
+             FROM user_job_history h
+             LEFT JOIN file_retention_policies p ON p.module_key = h.module
+             WHERE h.user_id = $1
+               AND h.created_at >= NOW() - make_interval(days => COALESCE(p.retention_days, $2::INT))
+         ) ranked
+         WHERE row_rank <= $3
+         ORDER BY created_at DESC",
+    )
+    .bind(user_id)
+    .bind(maintenance::DEFAULT_RETENTION_DAYS as i32)
+    .bind(per_module)
+    .fetch_all(pool)
+    .await
+    .context("failed to load per-module history rows")?;
+
+    hydrate_rows(pool, rows).await
+}
+
+async fn hydrate_rows(pool: &PgPool, rows: Vec<HistoryRow>) -> Result<Vec<HistoryEntry>> {
     let mut entries: Vec<HistoryEntry> = rows
         .into_iter()
         .map(|row| HistoryEntry {
@@ -211,6 +372,12 @@ pub async fn fetch_recent_jobs(
                 hydrate_int_entries(pool, "reviewer_jobs", "job_id", &mut entries, &indices)
                     .await?;
             }
+            usage::MODULE_TRANSCRIBE => {
+                hydrate_uuid_entries(pool, "transcribe_jobs", "id", &mut entries, &indices).await?;
+            }
+            usage::MODULE_DOCX_TO_PDF => {
+                hydrate_uuid_entries(pool, "docx2pdf_jobs", "id", &mut entries, &indices).await?;
+            }
             other => {
                 warn!(module = other, "unknown module in history table");
             }
@@ -404,17 +571,66 @@ async fn fetch_int_snapshots(
     Ok(map)
 }
 
+/// Deletes history rows older than their module's file retention window.
 pub async fn purge_stale_history(pool: &PgPool) -> Result<u64> {
-    let cutoff = Utc::now() - POLL_WINDOW;
-    let result = sqlx::query("DELETE FROM user_job_history WHERE created_at < $1")
-        .bind(cutoff)
-        .execute(pool)
-        .await
-        .context("failed to delete old history rows")?;
+    let result = sqlx::query(
+        "DELETE FROM user_job_history h
+         WHERE h.created_at < NOW() - make_interval(days => COALESCE(
+             (SELECT p.retention_days FROM file_retention_policies p WHERE p.module_key = h.module),
+             $1::INT))",
+    )
+    .bind(maintenance::DEFAULT_RETENTION_DAYS as i32)
+    .execute(pool)
+    .await
+    .context("failed to delete old history rows")?;
 
     Ok(result.rows_affected())
 }
 
-pub fn retention_interval() -> StdDuration {
-    StdDuration::from_secs((HISTORY_RETENTION_HOURS * 3600) as u64)
+/// How far back history reaches: `module`'s retention window, or the longest window of any
+/// module when listing them all.
+pub async fn retention_interval(pool: &PgPool, module: Option<&str>) -> Result<StdDuration> {
+    let default_days = maintenance::DEFAULT_RETENTION_DAYS as i32;
+    let query = match module {
+        Some(module) => sqlx::query_scalar(
+            "SELECT COALESCE(
+                 (SELECT retention_days FROM file_retention_policies WHERE module_key = $2),
+                 $1::INT)",
+        )
+        .bind(default_days)
+        .bind(module),
+        None => sqlx::query_scalar(
+            "SELECT GREATEST(MAX(retention_days), $1::INT) FROM file_retention_policies",
+        )
+        .bind(default_days),
+    };
+    let days: i32 = query
+        .fetch_one(pool)
+        .await
+        .context("failed to load history retention window")?;
+
+    Ok(StdDuration::from_secs(days as u64 * 86_400))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filename_pattern_matches_wildcards_literally() {
+        let pattern = |filename: Option<&str>| {
+            HistoryFilter {
+                filename: filename.map(str::to_string),
+                ..HistoryFilter::default()
+            }
+            .filename_pattern()
+        };
+        assert_eq!(pattern(None), None);
+        assert_eq!(pattern(Some("   ")), None);
+        assert_eq!(pattern(Some(" Report ")).as_deref(), Some("%Report%"));
+        assert_eq!(
+            pattern(Some(r"50%_a\b' OR 1=1")).as_deref(),
+            Some(r"%50\%\_a\\b' OR 1=1%")
+        );
+    }
 }
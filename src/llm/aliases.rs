@@ -0,0 +1,113 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use anyhow::{Result, anyhow};
+
+/// Admin-maintained map from a Poe model to the equivalent OpenRouter model, both as bare
+/// provider names (e.g. `Claude-Sonnet-4` → `anthropic/claude-sonnet-4`).
+///
+/// `LlmClient::execute` consults it when a Poe call fails with a provider outage (network
+/// error, timeout, 5xx or 429) and retries the same request once on the mapped OpenRouter
+/// model. Shared by every clone of the client so dashboard edits apply immediately.
+#[derive(Clone, Default)]
+pub struct ProviderAliases {
+    poe_to_openrouter: Arc<RwLock<HashMap<String, String>>>,
+}
+
+impl ProviderAliases {
+    pub fn replace(&self, aliases: HashMap<String, String>) {
+        *self
+            .poe_to_openrouter
+            .write()
+            .unwrap_or_else(|err| err.into_inner()) = aliases;
+    }
+
+    /// The OpenRouter model standing in for the Poe model `poe_model`, if one is mapped.
+    pub fn openrouter_for(&self, poe_model: &str) -> Option<String> {
+        self.poe_to_openrouter
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .get(poe_model)
+            .cloned()
+    }
+
+    /// Every alias as `(poe_model, openrouter_model)`, sorted by Poe model.
+    pub fn entries(&self) -> Vec<(String, String)> {
+        let mut entries: Vec<(String, String)> = self
+            .poe_to_openrouter
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .iter()
+            .map(|(poe, openrouter)| (poe.clone(), openrouter.clone()))
+            .collect();
+        entries.sort();
+        entries
+    }
+}
+
+/// Parses `Poe模型=OpenRouter模型` lines as edited on the dashboard. Blank lines and `#`
+/// comments are skipped; `poe/` and `openrouter/` prefixes are accepted and stripped, so
+/// either the bare or the provider-prefixed name can be pasted.
+pub fn parse_alias_lines(input: &str) -> Result<HashMap<String, String>> {
+    let mut aliases = HashMap::new();
+
+    for (idx, line) in input.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (poe, openrouter) = line
+            .split_once('=')
+            .ok_or_else(|| anyhow!("line {}: expected `poe_model=openrouter_model`", idx + 1))?;
+        let poe = poe.trim();
+        let poe = poe.strip_prefix("poe/").unwrap_or(poe);
+        let openrouter = openrouter.trim();
+        let openrouter = openrouter.strip_prefix("openrouter/").unwrap_or(openrouter);
+        if poe.is_empty() || openrouter.is_empty() {
+            return Err(anyhow!("line {}: both models are required", idx + 1));
+        }
+        if aliases
+            .insert(poe.to_string(), openrouter.to_string())
+            .is_some()
+        {
+            return Err(anyhow!("line {}: `{poe}` is mapped twice", idx + 1));
+        }
+    }
+
+    Ok(aliases)
+}
+
+pub fn format_alias_lines(entries: &[(String, String)]) -> String {
+    entries
+        .iter()
+        .map(|(poe, openrouter)| format!("{poe}={openrouter}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alias_lines_round_trip_and_reject_bad_input() {
+        let aliases = parse_alias_lines(
+            "# Poe=OpenRouter\nClaude-Sonnet-4 = anthropic/claude-sonnet-4\n\npoe/GPT-5=openrouter/openai/gpt-5\n",
+        )
+        .unwrap();
+        let map = ProviderAliases::default();
+        map.replace(aliases);
+        assert_eq!(map.openrouter_for("GPT-5").as_deref(), Some("openai/gpt-5"));
+        assert_eq!(
+            format_alias_lines(&map.entries()),
+            "Claude-Sonnet-4=anthropic/claude-sonnet-4\nGPT-5=openai/gpt-5"
+        );
+
+        assert!(parse_alias_lines("Claude-Sonnet-4").is_err());
+        assert!(parse_alias_lines("Claude-Sonnet-4=").is_err());
+        assert!(parse_alias_lines("A=x/a\npoe/A=x/b").is_err());
+    }
+}
@@ -0,0 +1,50 @@
+use std::{
+    sync::{Arc, OnceLock},
+    time::Duration,
+};
+
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
+
+use super::LlmProvider;
+
+/// Size, duration and outcome of one `LlmClient::execute` call.
+#[derive(Debug, Clone)]
+pub struct CallRecord {
+    /// Usage module key set with `LlmRequest::with_module`, if any.
+    pub module: Option<&'static str>,
+    /// Provider that answered (or failed), after any Poe → OpenRouter fallback.
+    pub provider: LlmProvider,
+    /// Provider-prefixed model name, e.g. `openrouter/openai/gpt-4o`.
+    pub model: String,
+    pub duration: Duration,
+    pub prompt_tokens: usize,
+    pub response_tokens: usize,
+    /// Message text plus attachment bytes sent with the request.
+    pub request_bytes: usize,
+    /// Length of the response text; zero for failed calls.
+    pub response_bytes: usize,
+    pub success: bool,
+}
+
+/// Hands a `CallRecord` for every finished call to a single subscriber.
+///
+/// Shared by every clone of the client. Nothing is recorded until `subscribe` is called, and
+/// recording never blocks or fails a call.
+#[derive(Clone, Default)]
+pub struct CallMetrics {
+    sender: Arc<OnceLock<UnboundedSender<CallRecord>>>,
+}
+
+impl CallMetrics {
+    /// Starts recording; `None` when another subscriber already receives the records.
+    pub fn subscribe(&self) -> Option<UnboundedReceiver<CallRecord>> {
+        let (sender, receiver) = unbounded_channel();
+        self.sender.set(sender).ok().map(|()| receiver)
+    }
+
+    pub(crate) fn record(&self, record: CallRecord) {
+        if let Some(sender) = self.sender.get() {
+            let _ = sender.send(record);
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use std::{env, sync::Arc};
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Which concurrency pool an LLM request draws from.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum RequestClass {
+    /// Multi-document modules (summarizer, info_extract, DOCX translator); limited to the shared pool.
+    #[default]
+    Batch,
+    /// Single-manuscript modules a user waits on (grader, reviewer); may also use reserved slots.
+    Interactive,
+}
+
+/// Global cap on in-flight LLM requests, split into a shared pool and slots only interactive
+/// requests may use, so a large batch job cannot starve the grader or reviewer.
+///
+/// Configured via `LLM_MAX_CONCURRENT_REQUESTS` (unset or `0` disables the cap) and
+/// `LLM_INTERACTIVE_RESERVED_SLOTS` (default 0; at least one slot always stays shared).
+#[derive(Clone, Default)]
+pub struct ConcurrencyPools {
+    pools: Option<Arc<Pools>>,
+}
+
+struct Pools {
+    shared: Arc<Semaphore>,
+    reserved: Arc<Semaphore>,
+}
+
+impl ConcurrencyPools {
+    pub fn new(total: usize, interactive_reserved: usize) -> Self {
+        if total == 0 {
+            return Self::default();
+        }
+        let reserved = interactive_reserved.min(total - 1);
+        Self {
+            pools: Some(Arc::new(Pools {
+                shared: Arc::new(Semaphore::new(total - reserved)),
+                reserved: Arc::new(Semaphore::new(reserved)),
+            })),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let read = |key: &str| {
+            env::var(key)
+                .ok()
+                .and_then(|value| value.trim().parse::<usize>().ok())
+                .unwrap_or(0)
+        };
+        Self::new(
+            read("LLM_MAX_CONCURRENT_REQUESTS"),
+            read("LLM_INTERACTIVE_RESERVED_SLOTS"),
+        )
+    }
+
+    /// Waits for a slot in the pools available to `class`. Interactive requests take a reserved
+    /// slot when one is free and otherwise wait on whichever pool frees up first. Returns `None`
+    /// when no cap is configured.
+    pub async fn acquire(&self, class: RequestClass) -> Option<LlmPermit> {
+        let pools = self.pools.as_ref()?;
+        let permit = match class {
+            RequestClass::Batch => pools.shared.clone().acquire_owned().await.ok()?,
+            RequestClass::Interactive => match pools.reserved.clone().try_acquire_owned() {
+                Ok(permit) => permit,
+                Err(_) => tokio::select! {
+                    permit = pools.reserved.clone().acquire_owned() => permit.ok()?,
+                    permit = pools.shared.clone().acquire_owned() => permit.ok()?,
+                },
+            },
+        };
+        Some(LlmPermit { _permit: permit })
+    }
+}
+
+/// Held while a request is in flight; frees its slot when dropped.
+pub struct LlmPermit {
+    _permit: OwnedSemaphorePermit,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::time::timeout;
+
+    use super::*;
+
+    const WAIT: Duration = Duration::from_millis(50);
+
+    #[tokio::test]
+    async fn reserved_slots_stay_available_to_interactive_requests() {
+        let pools = ConcurrencyPools::new(4, 1);
+
+        let mut batch = Vec::new();
+        for _ in 0..3 {
+            batch.push(
+                pools
+                    .acquire(RequestClass::Batch)
+                    .await
+                    .expect("shared slot"),
+            );
+        }
+        assert!(
+            timeout(WAIT, pools.acquire(RequestClass::Batch))
+                .await
+                .is_err(),
+            "batch traffic must not use the reserved slot"
+        );
+
+        let interactive = timeout(WAIT, pools.acquire(RequestClass::Interactive))
+            .await
+            .expect("reserved slot is free")
+            .expect("permit");
+        assert!(
+            timeout(WAIT, pools.acquire(RequestClass::Interactive))
+                .await
+                .is_err()
+        );
+
+        drop(batch.pop());
+        assert!(
+            timeout(WAIT, pools.acquire(RequestClass::Interactive))
+                .await
+                .is_ok(),
+            "interactive requests fall back to the shared pool"
+        );
+        drop(interactive);
+    }
+
+    #[tokio::test]
+    async fn zero_total_disables_the_cap() {
+        let pools = ConcurrencyPools::new(0, 3);
+        assert!(pools.acquire(RequestClass::Batch).await.is_none());
+        assert!(pools.acquire(RequestClass::Interactive).await.is_none());
+    }
+}
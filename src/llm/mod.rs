@@ -1,9 +1,32 @@
-use std::{env, fmt, fs, path::Path};
+use std::{
+    collections::HashMap,
+    env,
+    error::Error as StdError,
+    fmt, fs,
+    path::Path,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
 
 use anyhow::{Context, Result, anyhow, bail};
 use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
-use reqwest::Client;
+use reqwest::{Client, StatusCode};
 use serde::Deserialize;
+use tiktoken_rs::{CoreBPE, cl100k_base_singleton, o200k_base_singleton};
+use tokio::time::sleep;
+use tracing::warn;
+
+use crate::utils::language::is_cjk;
+
+mod aliases;
+mod call_metrics;
+mod concurrency;
+mod pdf_delivery;
+
+pub use aliases::{ProviderAliases, format_alias_lines, parse_alias_lines};
+pub use call_metrics::{CallMetrics, CallRecord};
+pub use concurrency::{ConcurrencyPools, LlmPermit, RequestClass};
+pub use pdf_delivery::{PdfDelivery, PdfDeliveryConfig};
 
 /// Enumerates the supported LLM backends behind the shared utility.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -27,6 +50,17 @@ pub struct LlmRequest {
     pub model: String,
     pub messages: Vec<ChatMessage>,
     pub attachments: Vec<FileAttachment>,
+    /// Overrides the client-wide timeout for this request.
+    pub timeout: Option<Duration>,
+    /// Sampling seed for providers that support deterministic outputs.
+    pub seed: Option<u64>,
+    /// Concurrency pool the request draws from; see `ConcurrencyPools`.
+    pub class: RequestClass,
+    /// Usage module key (`usage::MODULE_*`) the call is reported under in call metrics.
+    pub module: Option<&'static str>,
+    /// Provider-prefixed models tried in order when `model` is unavailable; see
+    /// `LlmClient::execute`.
+    pub fallback_models: Vec<String>,
 }
 
 impl LlmRequest {
@@ -35,13 +69,54 @@ impl LlmRequest {
             model: model.into(),
             messages,
             attachments: Vec::new(),
+            timeout: None,
+            seed: None,
+            class: RequestClass::default(),
+            module: None,
+            fallback_models: Vec::new(),
         }
     }
 
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn with_class(mut self, class: RequestClass) -> Self {
+        self.class = class;
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
     pub fn with_attachments(mut self, attachments: Vec<FileAttachment>) -> Self {
         self.attachments = attachments;
         self
     }
+
+    pub fn with_module(mut self, module: &'static str) -> Self {
+        self.module = Some(module);
+        self
+    }
+
+    pub fn with_fallback_models(mut self, models: Vec<String>) -> Self {
+        self.fallback_models = models;
+        self
+    }
+
+    /// Message text plus attachment bytes; approximates the size of the provider payload.
+    fn payload_bytes(&self) -> usize {
+        let text: usize = self.messages.iter().map(|message| message.text.len()).sum();
+        let files: usize = self
+            .attachments
+            .iter()
+            .map(|attachment| attachment.bytes.len())
+            .sum();
+        text + files
+    }
 }
 
 /// Individual chat message, compatible with OpenAI compliant providers.
@@ -143,6 +218,42 @@ pub struct TokenUsage {
     pub total_tokens: usize,
 }
 
+/// USD prices per million tokens for a model.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct ModelPricing {
+    pub prompt_per_million: f64,
+    pub completion_per_million: f64,
+}
+
+impl ModelPricing {
+    pub const fn new(prompt_per_million: f64, completion_per_million: f64) -> Self {
+        Self {
+            prompt_per_million,
+            completion_per_million,
+        }
+    }
+
+    pub fn cost_usd(&self, usage: &TokenUsage) -> f64 {
+        (usage.prompt_tokens as f64 * self.prompt_per_million
+            + usage.response_tokens as f64 * self.completion_per_million)
+            / 1_000_000.0
+    }
+}
+
+/// Built-in prices keyed by provider-prefixed model name. Entries from
+/// `LLM_PRICING_JSON` are merged on top and take precedence.
+pub const PRICING: &[(&str, ModelPricing)] = &[
+    ("openrouter/openai/gpt-4o", ModelPricing::new(2.5, 10.0)),
+    (
+        "openrouter/openai/gpt-4o-mini",
+        ModelPricing::new(0.15, 0.6),
+    ),
+    (
+        "openrouter/anthropic/claude-3.5-sonnet",
+        ModelPricing::new(3.0, 15.0),
+    ),
+];
+
 /// Full response surface returned to callers.
 #[derive(Debug, Clone)]
 pub struct LlmResponse {
@@ -151,21 +262,382 @@ pub struct LlmResponse {
     pub provider: LlmProvider,
     pub model: String,
     pub raw: serde_json::Value,
+    /// Price of the requested model, when it is listed in the client's price table.
+    pub pricing: Option<ModelPricing>,
+}
+
+impl LlmResponse {
+    /// Estimated USD cost of this call; `None` when the model has no known price.
+    pub fn estimated_cost_usd(&self) -> Option<f64> {
+        self.pricing
+            .map(|pricing| pricing.cost_usd(&self.token_usage))
+    }
+
+    /// Whether the provider cut the answer short at the output token limit.
+    pub fn hit_length_limit(&self) -> bool {
+        self.raw
+            .get("choices")
+            .and_then(|choices| choices.as_array())
+            .is_some_and(|choices| {
+                choices.iter().any(|choice| {
+                    choice
+                        .get("finish_reason")
+                        .and_then(|reason| reason.as_str())
+                        == Some("length")
+                })
+            })
+    }
+}
+
+/// Non-success HTTP status returned by a provider.
+///
+/// Kept as a typed error so retry logic can tell transient upstream failures
+/// apart from requests that will never succeed (bad key, invalid model, ...).
+#[derive(Debug)]
+pub struct LlmHttpError {
+    pub provider: LlmProvider,
+    pub status: StatusCode,
+    pub body: String,
+}
+
+impl fmt::Display for LlmHttpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} call failed with status {}: {}",
+            self.provider, self.status, self.body
+        )
+    }
+}
+
+impl StdError for LlmHttpError {}
+
+/// The provider's moderation blocked the request or the model explicitly refused to answer.
+///
+/// Sending the same input again will be refused again, so callers should surface the reason
+/// instead of retrying.
+#[derive(Debug)]
+pub struct LlmRefusalError {
+    pub provider: LlmProvider,
+    pub reason: String,
+}
+
+impl fmt::Display for LlmRefusalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} refused the request: {}", self.provider, self.reason)
+    }
+}
+
+impl StdError for LlmRefusalError {}
+
+/// Provider did not answer within the configured timeout.
+#[derive(Debug)]
+pub struct LlmTimeoutError {
+    pub provider: LlmProvider,
+    pub timeout: Duration,
+}
+
+impl fmt::Display for LlmTimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} call timed out after {}s",
+            self.provider,
+            self.timeout.as_secs()
+        )
+    }
+}
+
+impl StdError for LlmTimeoutError {}
+
+/// A successful response whose body has no usable answer text.
+#[derive(Debug)]
+pub struct LlmPayloadError {
+    pub provider: LlmProvider,
+    pub body: String,
+}
+
+impl fmt::Display for LlmPayloadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unexpected {} response payload: {}",
+            self.provider, self.body
+        )
+    }
+}
+
+impl StdError for LlmPayloadError {}
+
+/// Coarse category of an error from `LlmClient::execute`, for callers that react differently
+/// to each; see `classify_error`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LlmErrorKind {
+    /// 429 from the provider.
+    RateLimited,
+    /// 401 or 403 that is not a moderation block: bad or unauthorised API key.
+    Auth,
+    /// Any other 4xx, e.g. an unknown model or an oversized prompt.
+    BadRequest,
+    /// 5xx from the provider.
+    Server,
+    /// Connection failures and timeouts.
+    Network,
+    /// Moderation block or explicit model refusal.
+    Refused,
+    /// The provider answered 2xx with a body that is not JSON or has no answer text.
+    Parse,
+    /// Everything else, e.g. a missing API key or an unsupported provider prefix.
+    Other,
+}
+
+impl LlmErrorKind {
+    /// Transient kinds that `execute_with_retry` retries.
+    pub fn is_retryable(self) -> bool {
+        matches!(self, Self::RateLimited | Self::Server | Self::Network)
+    }
+}
+
+/// Categorises an error returned by `LlmClient::execute`, looking through any added context.
+pub fn classify_error(err: &anyhow::Error) -> LlmErrorKind {
+    if is_timeout_error(err) {
+        return LlmErrorKind::Network;
+    }
+    if is_refusal_error(err) {
+        return LlmErrorKind::Refused;
+    }
+    if let Some(http) = err.downcast_ref::<LlmHttpError>() {
+        return match http.status {
+            StatusCode::TOO_MANY_REQUESTS => LlmErrorKind::RateLimited,
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => LlmErrorKind::Auth,
+            status if status.is_server_error() => LlmErrorKind::Server,
+            status if status.is_client_error() => LlmErrorKind::BadRequest,
+            _ => LlmErrorKind::Other,
+        };
+    }
+    if err.downcast_ref::<LlmPayloadError>().is_some()
+        || err
+            .chain()
+            .any(|cause| cause.downcast_ref::<serde_json::Error>().is_some())
+    {
+        return LlmErrorKind::Parse;
+    }
+    if err
+        .chain()
+        .any(|cause| cause.downcast_ref::<reqwest::Error>().is_some())
+    {
+        return LlmErrorKind::Network;
+    }
+    LlmErrorKind::Other
+}
+
+/// Controls how `LlmClient::execute_with_retry` retries transient failures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first one.
+    pub max_attempts: u32,
+    /// Delay before the first retry.
+    pub base_delay: Duration,
+    /// Factor applied to the delay after each failed retry; `1` keeps it constant.
+    pub backoff_multiplier: u32,
+}
+
+impl RetryPolicy {
+    pub const fn new(max_attempts: u32, base_delay: Duration) -> Self {
+        Self {
+            max_attempts,
+            base_delay,
+            backoff_multiplier: 2,
+        }
+    }
+
+    /// Retry with the same delay between every attempt.
+    pub const fn constant(max_attempts: u32, delay: Duration) -> Self {
+        Self {
+            max_attempts,
+            base_delay: delay,
+            backoff_multiplier: 1,
+        }
+    }
+
+    /// Delay to wait after the given failed attempt (1-based).
+    pub fn delay_after(&self, attempt: u32) -> Duration {
+        let factor = self
+            .backoff_multiplier
+            .saturating_pow(attempt.saturating_sub(1));
+        self.base_delay.saturating_mul(factor)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new(3, Duration::from_secs(1))
+    }
+}
+
+/// Consecutive request failures shared by every document of a batch job, so a provider outage
+/// trips once instead of each document running its own retries to exhaustion.
+#[derive(Debug)]
+pub struct ProviderFailureBudget {
+    limit: usize,
+    consecutive: AtomicUsize,
+}
+
+impl ProviderFailureBudget {
+    /// Trips after `limit` consecutive failures; `0` disables the budget.
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            consecutive: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.limit > 0 && self.consecutive.load(Ordering::Relaxed) >= self.limit
+    }
+
+    pub fn record_success(&self) {
+        self.consecutive.store(0, Ordering::Relaxed);
+    }
+
+    pub fn record_failure(&self) {
+        self.consecutive.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Whether an error from `LlmClient::execute` is worth retrying.
+///
+/// Network failures, timeouts, 5xx responses and 429 rate limiting are transient;
+/// every other error (4xx, missing API keys, malformed model names) is not.
+pub fn is_retryable_error(err: &anyhow::Error) -> bool {
+    classify_error(err).is_retryable()
+}
+
+/// Whether an error from `LlmClient::execute` was caused by a request timeout.
+pub fn is_timeout_error(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<LlmTimeoutError>().is_some()
+}
+
+/// Whether a provider rejected the request because the input exceeds the model's context window.
+pub fn is_context_length_error(err: &anyhow::Error) -> bool {
+    let Some(http) = err.downcast_ref::<LlmHttpError>() else {
+        return false;
+    };
+    if !matches!(
+        http.status,
+        StatusCode::BAD_REQUEST | StatusCode::PAYLOAD_TOO_LARGE
+    ) {
+        return false;
+    }
+    let body = http.body.to_ascii_lowercase();
+    [
+        "context length",
+        "context_length",
+        "context window",
+        "maximum context",
+        "too many tokens",
+        "prompt is too long",
+    ]
+    .iter()
+    .any(|needle| body.contains(needle))
+}
+
+/// Whether an error is a moderation block or model refusal (see `LlmRefusalError`).
+pub fn is_refusal_error(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<LlmRefusalError>().is_some()
+}
+
+/// Result of a retried LLM call together with the number of attempts it took.
+pub struct RetriedResponse {
+    pub result: Result<LlmResponse>,
+    pub attempts: u32,
+}
+
+/// Runs `call` under `policy`, retrying errors that `is_retryable_error` accepts.
+pub async fn retry_with_policy<F, Fut>(
+    policy: RetryPolicy,
+    model: &str,
+    mut call: F,
+) -> RetriedResponse
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<LlmResponse>>,
+{
+    let max_attempts = policy.max_attempts.max(1);
+    let mut attempts = 0;
+
+    loop {
+        attempts += 1;
+
+        match call().await {
+            Ok(response) => {
+                return RetriedResponse {
+                    result: Ok(response),
+                    attempts,
+                };
+            }
+            Err(err) if attempts < max_attempts && is_retryable_error(&err) => {
+                let delay = policy.delay_after(attempts);
+                warn!(
+                    ?err,
+                    attempt = attempts,
+                    max_attempts,
+                    model,
+                    delay_ms = delay.as_millis() as u64,
+                    "LLM request failed, will retry"
+                );
+                sleep(delay).await;
+            }
+            Err(err) => {
+                return RetriedResponse {
+                    result: Err(err),
+                    attempts,
+                };
+            }
+        }
+    }
+}
+
+/// Rough token estimate used for routing decisions before a request is sent.
+/// CJK characters count as one token each; other text as one token per four characters.
+pub fn estimate_tokens(text: &str) -> u64 {
+    let (cjk, other) = text.chars().fold((0u64, 0u64), |(cjk, other), ch| {
+        if is_cjk(ch) {
+            (cjk + 1, other)
+        } else {
+            (cjk, other + 1)
+        }
+    });
+    cjk + other.div_ceil(4)
 }
 
+/// Default OpenRouter API root; `OPENROUTER_BASE_URL` replaces it.
+const OPENROUTER_BASE_URL: &str = "https://openrouter.ai/api/v1";
+const POE_BASE_URL: &str = "https://api.poe.com/v1";
+
 /// Main entry point for invoking providers.
 #[derive(Clone)]
 pub struct LlmClient {
     http: Client,
     config: LlmConfig,
+    pools: ConcurrencyPools,
+    aliases: ProviderAliases,
+    call_metrics: CallMetrics,
 }
 
 #[derive(Clone, Default)]
 struct LlmConfig {
     openrouter_api_key: Option<String>,
     poe_api_key: Option<String>,
+    /// Overrides the OpenRouter API root, e.g. for a gateway or a local stub provider.
+    openrouter_base_url: Option<String>,
     openrouter_referer: Option<String>,
     openrouter_title: Option<String>,
+    /// Overrides the Poe API root; only set by tests.
+    poe_base_url: Option<String>,
+    timeout: Option<Duration>,
+    pricing: HashMap<String, ModelPricing>,
+    pdf_delivery: PdfDeliveryConfig,
 }
 
 impl LlmClient {
@@ -173,122 +645,298 @@ impl LlmClient {
     pub fn from_env() -> Result<Self> {
         let openrouter_api_key = env::var("OPENROUTER_API_KEY").ok();
         let poe_api_key = env::var("POE_API_KEY").ok();
+        let openrouter_base_url = env::var("OPENROUTER_BASE_URL")
+            .ok()
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty());
         let openrouter_referer = env::var("OPENROUTER_HTTP_REFERER").ok();
         let openrouter_title = env::var("OPENROUTER_X_TITLE").ok();
+        let pricing = load_pricing(env::var("LLM_PRICING_JSON").ok().as_deref())?;
+        let pdf_delivery = PdfDeliveryConfig::from_env()?;
 
         Ok(Self {
             http: Client::new(),
             config: LlmConfig {
                 openrouter_api_key,
                 poe_api_key,
+                openrouter_base_url,
                 openrouter_referer,
                 openrouter_title,
+                poe_base_url: None,
+                timeout: None,
+                pricing,
+                pdf_delivery,
             },
+            pools: ConcurrencyPools::from_env(),
+            aliases: ProviderAliases::default(),
+            call_metrics: CallMetrics::default(),
         })
     }
 
-    /// Execute a request against the provider encoded in the model name.
-    pub async fn execute(&self, request: LlmRequest) -> Result<LlmResponse> {
-        let model = request.model.clone();
-        let (provider, provider_model) = parse_model_provider(&model)?;
+    /// Build a client from environment variables with a default timeout applied to every request.
+    pub fn from_env_with_timeout(timeout: Duration) -> Result<Self> {
+        let mut client = Self::from_env()?;
+        client.config.timeout = Some(timeout);
+        Ok(client)
+    }
+
+    /// Whether an OpenRouter or Poe API key is set, i.e. whether any call can be attempted.
+    pub fn has_provider_key(&self) -> bool {
+        [&self.config.openrouter_api_key, &self.config.poe_api_key]
+            .into_iter()
+            .flatten()
+            .any(|key| !key.trim().is_empty())
+    }
 
-        match provider {
-            LlmProvider::OpenRouter => self.execute_openrouter(provider_model, request).await,
-            LlmProvider::Poe => self.execute_poe(provider_model, request).await,
+    /// Client whose OpenRouter requests all go to `base_url`, with a placeholder API key.
+    #[cfg(test)]
+    pub(crate) fn with_openrouter_base_url(base_url: &str) -> Self {
+        Self {
+            http: Client::new(),
+            config: LlmConfig {
+                openrouter_api_key: Some("test-key".to_string()),
+                openrouter_base_url: Some(base_url.to_string()),
+                ..LlmConfig::default()
+            },
+            pools: ConcurrencyPools::default(),
+            aliases: ProviderAliases::default(),
+            call_metrics: CallMetrics::default(),
         }
     }
 
-    async fn execute_openrouter(&self, model: &str, request: LlmRequest) -> Result<LlmResponse> {
-        let Some(api_key) = self.config.openrouter_api_key.as_ref() else {
-            bail!("OPENROUTER_API_KEY is not configured but required for OpenRouter requests");
-        };
+    /// Sends Poe requests to `base_url` as well, with a placeholder API key.
+    #[cfg(test)]
+    pub(crate) fn with_poe_base_url(mut self, base_url: &str) -> Self {
+        self.config.poe_api_key = Some("test-key".to_string());
+        self.config.poe_base_url = Some(base_url.to_string());
+        self
+    }
 
-        // Build messages in standard OpenAI format
-        let mut messages = Vec::new();
+    #[cfg(test)]
+    pub(crate) fn with_concurrency(mut self, pools: ConcurrencyPools) -> Self {
+        self.pools = pools;
+        self
+    }
 
-        for msg in &request.messages {
-            // For messages without attachments, use simple string content
-            if request.attachments.is_empty() {
-                messages.push(serde_json::json!({
-                    "role": msg.role.as_str(),
-                    "content": msg.text,
-                }));
-            } else {
-                // For messages with attachments, use array format
-                messages.push(serde_json::json!({
-                    "role": msg.role.as_str(),
-                    "content": [
-                        {
-                            "type": "text",
-                            "text": msg.text,
-                        }
-                    ],
-                }));
-            }
+    #[cfg(test)]
+    pub(crate) fn with_pdf_delivery(mut self, pdf_delivery: PdfDeliveryConfig) -> Self {
+        self.config.pdf_delivery = pdf_delivery;
+        self
+    }
+
+    /// File references for the request's PDFs when `provider` takes them by upload, indexed like
+    /// `request.attachments`; empty when every attachment is sent inline.
+    async fn pdf_references(
+        &self,
+        provider: LlmProvider,
+        request: &LlmRequest,
+    ) -> Result<Vec<Option<String>>> {
+        let delivery = &self.config.pdf_delivery;
+        if delivery.for_provider(provider) != PdfDelivery::Upload {
+            return Ok(Vec::new());
+        }
+        let mut references = Vec::with_capacity(request.attachments.len());
+        for attachment in &request.attachments {
+            references.push(match attachment.kind {
+                AttachmentKind::Pdf => Some(delivery.upload(&self.http, attachment).await?),
+                AttachmentKind::Image | AttachmentKind::Audio => None,
+            });
         }
+        Ok(references)
+    }
 
-        // Add attachments to the last user message
-        if !request.attachments.is_empty() {
-            let mut attachment_target_idx = messages
-                .iter()
-                .rposition(|m| m.get("role").and_then(|r| r.as_str()) == Some("user"));
-
-            if attachment_target_idx.is_none() {
-                // Create empty user entry to pin uploads
-                messages.push(serde_json::json!({
-                    "role": "user",
-                    "content": [],
-                }));
-                attachment_target_idx = Some(messages.len() - 1);
+    /// Poe → OpenRouter model aliases used for outage fallback; loaded by `AppState`.
+    pub fn provider_aliases(&self) -> &ProviderAliases {
+        &self.aliases
+    }
+
+    /// Per-call size and duration records; subscribed to by `AppState` for persistence.
+    pub fn call_metrics(&self) -> &CallMetrics {
+        &self.call_metrics
+    }
+
+    fn openrouter_endpoint(&self) -> String {
+        let base = self
+            .config
+            .openrouter_base_url
+            .as_deref()
+            .unwrap_or(OPENROUTER_BASE_URL);
+        format!("{}/chat/completions", base.trim_end_matches('/'))
+    }
+
+    fn poe_endpoint(&self) -> String {
+        let base = self.config.poe_base_url.as_deref().unwrap_or(POE_BASE_URL);
+        format!("{}/chat/completions", base.trim_end_matches('/'))
+    }
+
+    fn request_timeout(&self, request: &LlmRequest) -> Option<Duration> {
+        request.timeout.or(self.config.timeout)
+    }
+
+    /// Execute a request against the provider encoded in the model name. A Poe request that
+    /// fails with an outage (`is_retryable_error`) is sent once more to the OpenRouter model
+    /// aliased to it in `provider_aliases`, if any.
+    pub async fn execute(&self, request: LlmRequest) -> Result<LlmResponse> {
+        let model = request.model.clone();
+        let (provider, provider_model) = parse_model_provider(&model)?;
+        let _permit = self.pools.acquire(request.class).await;
+
+        let module = request.module;
+        let request_bytes = request.payload_bytes();
+        let started = Instant::now();
+        let outcome = self.dispatch(provider, provider_model, request).await;
+        let duration = started.elapsed();
+
+        self.call_metrics.record(match &outcome {
+            Ok((answered_model, response)) => CallRecord {
+                module,
+                provider: response.provider,
+                model: answered_model.clone(),
+                duration,
+                prompt_tokens: response.token_usage.prompt_tokens,
+                response_tokens: response.token_usage.response_tokens,
+                request_bytes,
+                response_bytes: response.text.len(),
+                success: true,
+            },
+            Err(_) => CallRecord {
+                module,
+                provider,
+                model,
+                duration,
+                prompt_tokens: 0,
+                response_tokens: 0,
+                request_bytes,
+                response_bytes: 0,
+                success: false,
+            },
+        });
+
+        outcome.map(|(_, response)| response)
+    }
+
+    /// Sends `request` to `provider`, then to its fallback models in order while the previous
+    /// attempt failed with an outage (`is_retryable_error`). With an OpenRouter primary, the
+    /// OpenRouter fallbacks go out in the same request as its `models` route list and only the
+    /// others are tried here. Returns the provider-prefixed model that answered alongside the
+    /// response.
+    async fn dispatch(
+        &self,
+        provider: LlmProvider,
+        provider_model: &str,
+        mut request: LlmRequest,
+    ) -> Result<(String, LlmResponse)> {
+        let mut routed = Vec::new();
+        let mut manual = Vec::new();
+        for fallback in std::mem::take(&mut request.fallback_models) {
+            match fallback.strip_prefix("openrouter/") {
+                Some(bare) if provider == LlmProvider::OpenRouter => routed.push(bare.to_string()),
+                _ => manual.push(fallback),
             }
+        }
 
-            for attachment in &request.attachments {
-                if let Some(idx) = attachment_target_idx {
-                    if let Some(entry) = messages.get_mut(idx) {
-                        if let Some(content) = entry.get_mut("content") {
-                            if let Some(array) = content.as_array_mut() {
-                                let base64_data = BASE64.encode(&attachment.bytes);
-                                match attachment.kind {
-                                    AttachmentKind::Image => {
-                                        let data_url = format!(
-                                            "data:{};base64,{}",
-                                            attachment.content_type, base64_data
-                                        );
-                                        array.push(serde_json::json!({
-                                            "type": "image_url",
-                                            "image_url": {
-                                                "url": data_url
-                                            }
-                                        }));
-                                    }
-                                    AttachmentKind::Pdf => {
-                                        let data_url = format!(
-                                            "data:{};base64,{}",
-                                            attachment.content_type, base64_data
-                                        );
-                                        array.push(serde_json::json!({
-                                            "type": "file",
-                                            "file": data_url
-                                        }));
-                                    }
-                                    AttachmentKind::Audio => {
-                                        // Map MIME type to canonical format name expected by OpenRouter
-                                        let format = audio_mime_to_format(&attachment.content_type);
-                                        array.push(serde_json::json!({
-                                            "type": "input_audio",
-                                            "input_audio": {
-                                                "data": base64_data,
-                                                "format": format
-                                            }
-                                        }));
-                                    }
-                                }
-                            }
-                        }
+        let mut outcome = self
+            .dispatch_model(provider, provider_model, &routed, request.clone())
+            .await;
+        for fallback in &manual {
+            let err = match &outcome {
+                Err(err) if is_retryable_error(err) => err,
+                _ => break,
+            };
+            let Ok((provider, provider_model)) = parse_model_provider(fallback) else {
+                warn!(model = %fallback, "skipping fallback model without a known provider");
+                continue;
+            };
+            warn!(
+                fallback_model = %fallback,
+                error = %err,
+                "LLM request failed; trying the next fallback model"
+            );
+            outcome = self
+                .dispatch_model(provider, provider_model, &[], request.clone())
+                .await;
+        }
+        outcome
+    }
+
+    /// One attempt at `provider_model`, falling back from Poe to an aliased OpenRouter model on
+    /// outages. `routed` are bare OpenRouter models sent as the `models` route list.
+    async fn dispatch_model(
+        &self,
+        provider: LlmProvider,
+        provider_model: &str,
+        routed: &[String],
+        request: LlmRequest,
+    ) -> Result<(String, LlmResponse)> {
+        let model = format!("{provider}/{provider_model}");
+        let (priced_model, mut response) = match provider {
+            LlmProvider::OpenRouter => {
+                let response = self
+                    .execute_openrouter(provider_model, routed, request)
+                    .await?;
+                (format!("openrouter/{}", response.model), response)
+            }
+            LlmProvider::Poe => {
+                let fallback = self
+                    .aliases
+                    .openrouter_for(provider_model)
+                    .map(|alias| (alias, request.clone()));
+                match (self.execute_poe(provider_model, request).await, fallback) {
+                    (Ok(response), _) => (model.clone(), response),
+                    (Err(err), Some((alias, request))) if is_retryable_error(&err) => {
+                        warn!(
+                            poe_model = provider_model,
+                            openrouter_model = %alias,
+                            error = %err,
+                            "Poe request failed; falling back to the aliased OpenRouter model"
+                        );
+                        let response = self.execute_openrouter(&alias, &[], request).await?;
+                        (format!("openrouter/{alias}"), response)
                     }
+                    (Err(err), _) => return Err(err),
                 }
             }
-        }
+        };
+        response.pricing = self.config.pricing.get(&priced_model).copied();
+
+        Ok((priced_model, response))
+    }
+
+    /// Execute a request, retrying network and 5xx failures according to `policy`.
+    pub async fn execute_with_retry(
+        &self,
+        request: LlmRequest,
+        policy: RetryPolicy,
+    ) -> Result<LlmResponse> {
+        self.execute_with_retry_counted(request, policy)
+            .await
+            .result
+    }
+
+    /// Like `execute_with_retry`, but also reports how many attempts were made.
+    pub async fn execute_with_retry_counted(
+        &self,
+        request: LlmRequest,
+        policy: RetryPolicy,
+    ) -> RetriedResponse {
+        let model = request.model.clone();
+        retry_with_policy(policy, &model, || self.execute(request.clone())).await
+    }
+
+    async fn execute_openrouter(
+        &self,
+        model: &str,
+        routed: &[String],
+        request: LlmRequest,
+    ) -> Result<LlmResponse> {
+        let Some(api_key) = self.config.openrouter_api_key.as_ref() else {
+            bail!("OPENROUTER_API_KEY is not configured but required for OpenRouter requests");
+        };
+
+        let references = self
+            .pdf_references(LlmProvider::OpenRouter, &request)
+            .await?;
+        let messages = chat_messages(&request, &references);
 
         let prompt_tokens = approximate_token_count(
             &request
@@ -297,16 +945,14 @@ impl LlmClient {
                 .map(|m| m.text.as_str())
                 .collect::<Vec<_>>()
                 .join("\n"),
+            model,
         );
 
-        let payload = serde_json::json!({
-            "model": model,
-            "messages": messages,
-        });
+        let payload = openrouter_payload(model, routed, messages, &request);
 
         let mut req_builder = self
             .http
-            .post("https://openrouter.ai/api/v1/chat/completions")
+            .post(self.openrouter_endpoint())
             .bearer_auth(api_key)
             .json(&payload);
 
@@ -318,53 +964,70 @@ impl LlmClient {
             req_builder = req_builder.header("X-Title", title);
         }
 
-        let response = req_builder.send().await?;
+        let timeout = self.request_timeout(&request);
+        if let Some(timeout) = timeout {
+            req_builder = req_builder.timeout(timeout);
+        }
+
+        let response = req_builder
+            .send()
+            .await
+            .map_err(|err| transport_error(LlmProvider::OpenRouter, timeout, err))?;
         let status = response.status();
         let response_text = response
             .text()
             .await
+            .map_err(|err| transport_error(LlmProvider::OpenRouter, timeout, err))
             .context("failed to read response body")?;
+        if !status.is_success() {
+            return Err(http_failure(
+                LlmProvider::OpenRouter,
+                status,
+                &response_text,
+            ));
+        }
         let body: serde_json::Value = serde_json::from_str(&response_text).with_context(|| {
-            let preview = if response_text.len() > 500 {
-                format!("{}...", &response_text[..500])
-            } else {
-                response_text.clone()
-            };
             format!(
                 "failed to parse OpenRouter response as JSON. Response body: {}",
-                preview
+                response_preview(&response_text)
             )
         })?;
-        if !status.is_success() {
-            bail!("openrouter call failed with status {}: {}", status, body);
-        }
 
         let (text, usage) = extract_text_and_usage(&body)
-            .ok_or_else(|| anyhow!("unexpected OpenRouter response payload: {}", body))?;
+            .ok_or_else(|| missing_text_error(LlmProvider::OpenRouter, &body))?;
 
         let mut token_usage = usage.unwrap_or_else(|| TokenUsage {
             prompt_tokens,
-            response_tokens: approximate_token_count(&text),
-            total_tokens: prompt_tokens + approximate_token_count(&text),
+            response_tokens: approximate_token_count(&text, model),
+            total_tokens: prompt_tokens + approximate_token_count(&text, model),
         });
         if token_usage.prompt_tokens == 0 {
             token_usage.prompt_tokens = prompt_tokens;
         }
         if token_usage.response_tokens == 0 {
-            token_usage.response_tokens = approximate_token_count(&text);
+            token_usage.response_tokens = approximate_token_count(&text, model);
         }
         token_usage.total_tokens = token_usage.prompt_tokens + token_usage.response_tokens;
 
+        // With a route list the answer may come from a fallback, which the body names.
+        let answered_model = body
+            .get("model")
+            .and_then(|value| value.as_str())
+            .filter(|_| !routed.is_empty())
+            .unwrap_or(model)
+            .to_string();
+
         Ok(LlmResponse {
             text,
             token_usage,
             provider: LlmProvider::OpenRouter,
-            model: model.to_string(),
+            model: answered_model,
             raw: body,
+            pricing: None,
         })
     }
 
-    async fn execute_poe(&self, model: &str, request: LlmRequest) -> Result<LlmResponse> {
+    async fn execute_poe(&self, model: &str, mut request: LlmRequest) -> Result<LlmResponse> {
         let Some(api_key) = self.config.poe_api_key.as_ref() else {
             bail!("POE_API_KEY is not configured but required for Poe requests");
         };
@@ -378,123 +1041,49 @@ impl LlmClient {
             }
         }
 
-        // Build messages in standard OpenAI format
-        let mut messages = Vec::new();
-
-        for msg in &request.messages {
-            // For messages without attachments, use simple string content
-            if request.attachments.is_empty() {
-                messages.push(serde_json::json!({
-                    "role": msg.role.as_str(),
-                    "content": msg.text,
-                }));
-            } else {
-                // For messages with attachments, use array format
-                messages.push(serde_json::json!({
-                    "role": msg.role.as_str(),
-                    "content": [
-                        {
-                            "type": "text",
-                            "text": msg.text,
-                        }
-                    ],
-                }));
-            }
-        }
-
-        // Add attachments to the last user message
-        if !request.attachments.is_empty() {
-            let mut attachment_target_idx = messages
-                .iter()
-                .rposition(|m| m.get("role").and_then(|r| r.as_str()) == Some("user"));
-
-            if attachment_target_idx.is_none() {
-                // Create empty user entry to pin uploads
-                messages.push(serde_json::json!({
-                    "role": "user",
-                    "content": [],
-                }));
-                attachment_target_idx = Some(messages.len() - 1);
-            }
-
-            for attachment in &request.attachments {
-                if let Some(idx) = attachment_target_idx {
-                    if let Some(entry) = messages.get_mut(idx) {
-                        if let Some(content) = entry.get_mut("content") {
-                            if let Some(array) = content.as_array_mut() {
-                                let base64_data = BASE64.encode(&attachment.bytes);
-                                match attachment.kind {
-                                    AttachmentKind::Image => {
-                                        let data_url = format!(
-                                            "data:{};base64,{}",
-                                            attachment.content_type, base64_data
-                                        );
-                                        array.push(serde_json::json!({
-                                            "type": "image_url",
-                                            "image_url": {
-                                                "url": data_url
-                                            }
-                                        }));
-                                    }
-                                    AttachmentKind::Pdf => {
-                                        let data_url = format!(
-                                            "data:{};base64,{}",
-                                            attachment.content_type, base64_data
-                                        );
-                                        array.push(serde_json::json!({
-                                            "type": "file",
-                                            "file": data_url
-                                        }));
-                                    }
-                                    AttachmentKind::Audio => {
-                                        // This should never happen due to the check above
-                                        unreachable!(
-                                            "Audio attachments should be rejected earlier"
-                                        );
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
+        request.messages = fold_system_messages(std::mem::take(&mut request.messages));
+        let references = self.pdf_references(LlmProvider::Poe, &request).await?;
+        let messages = chat_messages(&request, &references);
 
         let payload = serde_json::json!({
             "model": model,
             "messages": messages,
         });
 
-        let response = self
+        let mut req_builder = self
             .http
-            .post("https://api.poe.com/v1/chat/completions")
+            .post(self.poe_endpoint())
             .bearer_auth(api_key)
-            .json(&payload)
+            .json(&payload);
+
+        let timeout = self.request_timeout(&request);
+        if let Some(timeout) = timeout {
+            req_builder = req_builder.timeout(timeout);
+        }
+
+        let response = req_builder
             .send()
-            .await?;
+            .await
+            .map_err(|err| transport_error(LlmProvider::Poe, timeout, err))?;
 
         let status = response.status();
         let response_text = response
             .text()
             .await
+            .map_err(|err| transport_error(LlmProvider::Poe, timeout, err))
             .context("failed to read response body")?;
+        if !status.is_success() {
+            return Err(http_failure(LlmProvider::Poe, status, &response_text));
+        }
         let body: serde_json::Value = serde_json::from_str(&response_text).with_context(|| {
-            let preview = if response_text.len() > 500 {
-                format!("{}...", &response_text[..500])
-            } else {
-                response_text.clone()
-            };
             format!(
                 "failed to parse Poe response as JSON. Response body: {}",
-                preview
+                response_preview(&response_text)
             )
         })?;
-        if !status.is_success() {
-            bail!("poe call failed with status {}: {}", status, body);
-        }
 
         let (text, usage) = extract_text_and_usage(&body)
-            .ok_or_else(|| anyhow!("unexpected Poe response payload: {}", body))?;
+            .ok_or_else(|| missing_text_error(LlmProvider::Poe, &body))?;
 
         let prompt_tokens = approximate_token_count(
             &request
@@ -503,17 +1092,18 @@ impl LlmClient {
                 .map(|m| m.text.as_str())
                 .collect::<Vec<_>>()
                 .join("\n"),
+            model,
         );
         let mut token_usage = usage.unwrap_or_else(|| TokenUsage {
             prompt_tokens,
-            response_tokens: approximate_token_count(&text),
-            total_tokens: prompt_tokens + approximate_token_count(&text),
+            response_tokens: approximate_token_count(&text, model),
+            total_tokens: prompt_tokens + approximate_token_count(&text, model),
         });
         if token_usage.prompt_tokens == 0 {
             token_usage.prompt_tokens = prompt_tokens;
         }
         if token_usage.response_tokens == 0 {
-            token_usage.response_tokens = approximate_token_count(&text);
+            token_usage.response_tokens = approximate_token_count(&text, model);
         }
         token_usage.total_tokens = token_usage.prompt_tokens + token_usage.response_tokens;
 
@@ -523,10 +1113,201 @@ impl LlmClient {
             provider: LlmProvider::Poe,
             model: model.to_string(),
             raw: body,
+            pricing: None,
         })
     }
 }
 
+/// Poe bots do not reliably honour the `system` role, so system prompts are joined and
+/// prepended to the first user message (or sent as one when the request has none).
+fn fold_system_messages(messages: Vec<ChatMessage>) -> Vec<ChatMessage> {
+    let (system, mut rest): (Vec<_>, Vec<_>) = messages
+        .into_iter()
+        .partition(|msg| msg.role == MessageRole::System);
+    let instructions = system
+        .iter()
+        .map(|msg| msg.text.trim())
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    if instructions.is_empty() {
+        return rest;
+    }
+
+    match rest.iter_mut().find(|msg| msg.role == MessageRole::User) {
+        Some(first_user) => first_user.text = format!("{instructions}\n\n{}", first_user.text),
+        None => rest.insert(0, ChatMessage::new(MessageRole::User, instructions)),
+    }
+    rest
+}
+
+/// Messages in the OpenAI chat format shared by OpenRouter and Poe. Without attachments each
+/// message is plain text; otherwise every message uses content parts and all attachments are
+/// appended, in request order, to the last user message (a user message is added if none
+/// exists). `references` holds uploaded file URLs by attachment index (see `pdf_references`).
+fn chat_messages(request: &LlmRequest, references: &[Option<String>]) -> Vec<serde_json::Value> {
+    let mut messages: Vec<serde_json::Value> = request
+        .messages
+        .iter()
+        .map(|msg| {
+            if request.attachments.is_empty() {
+                serde_json::json!({
+                    "role": msg.role.as_str(),
+                    "content": msg.text,
+                })
+            } else {
+                serde_json::json!({
+                    "role": msg.role.as_str(),
+                    "content": [
+                        {
+                            "type": "text",
+                            "text": msg.text,
+                        }
+                    ],
+                })
+            }
+        })
+        .collect();
+
+    if request.attachments.is_empty() {
+        return messages;
+    }
+
+    let target = match messages
+        .iter()
+        .rposition(|m| m.get("role").and_then(|r| r.as_str()) == Some("user"))
+    {
+        Some(idx) => idx,
+        None => {
+            // Create empty user entry to pin uploads
+            messages.push(serde_json::json!({
+                "role": "user",
+                "content": [],
+            }));
+            messages.len() - 1
+        }
+    };
+
+    if let Some(parts) = messages[target]["content"].as_array_mut() {
+        parts.extend(
+            request
+                .attachments
+                .iter()
+                .enumerate()
+                .map(|(idx, attachment)| {
+                    let reference = references
+                        .get(idx)
+                        .and_then(|reference| reference.as_deref());
+                    attachment_part(attachment, reference)
+                }),
+        );
+    }
+    messages
+}
+
+fn attachment_part(attachment: &FileAttachment, reference: Option<&str>) -> serde_json::Value {
+    if let (AttachmentKind::Pdf, Some(url)) = (attachment.kind, reference) {
+        return serde_json::json!({
+            "type": "file",
+            "file": {
+                "filename": attachment.filename,
+                "file_data": url,
+            }
+        });
+    }
+
+    let base64_data = BASE64.encode(&attachment.bytes);
+    match attachment.kind {
+        AttachmentKind::Image => serde_json::json!({
+            "type": "image_url",
+            "image_url": {
+                "url": format!("data:{};base64,{}", attachment.content_type, base64_data)
+            }
+        }),
+        AttachmentKind::Pdf => serde_json::json!({
+            "type": "file",
+            "file": format!("data:{};base64,{}", attachment.content_type, base64_data)
+        }),
+        AttachmentKind::Audio => {
+            // Map MIME type to canonical format name expected by OpenRouter
+            let format = audio_mime_to_format(&attachment.content_type);
+            serde_json::json!({
+                "type": "input_audio",
+                "input_audio": {
+                    "data": base64_data,
+                    "format": format
+                }
+            })
+        }
+    }
+}
+
+/// Chat completion body for OpenRouter, which forwards `seed` to models that support it.
+/// Poe does not accept a seed, so its payload omits the field.
+fn openrouter_payload(
+    model: &str,
+    routed: &[String],
+    messages: Vec<serde_json::Value>,
+    request: &LlmRequest,
+) -> serde_json::Value {
+    let mut payload = serde_json::json!({
+        "model": model,
+        "messages": messages,
+    });
+
+    if !routed.is_empty() {
+        let models: Vec<&str> = std::iter::once(model)
+            .chain(routed.iter().map(String::as_str))
+            .collect();
+        payload["models"] = serde_json::json!(models);
+    }
+
+    if let Some(seed) = request.seed {
+        payload["seed"] = serde_json::json!(seed);
+    }
+
+    payload
+}
+
+/// Builds the price table from `PRICING`, overlaid with a JSON object of the form
+/// `{"openrouter/openai/gpt-4o": {"prompt_per_million": 2.5, "completion_per_million": 10}}`.
+fn load_pricing(overrides: Option<&str>) -> Result<HashMap<String, ModelPricing>> {
+    let mut pricing: HashMap<String, ModelPricing> = PRICING
+        .iter()
+        .map(|(model, price)| (model.to_string(), *price))
+        .collect();
+
+    if let Some(raw) = overrides.map(str::trim).filter(|raw| !raw.is_empty()) {
+        let parsed: HashMap<String, ModelPricing> =
+            serde_json::from_str(raw).context("LLM_PRICING_JSON is not a valid price table")?;
+        pricing.extend(parsed);
+    }
+
+    Ok(pricing)
+}
+
+/// Wraps reqwest failures, promoting timeouts to `LlmTimeoutError`.
+fn transport_error(
+    provider: LlmProvider,
+    timeout: Option<Duration>,
+    err: reqwest::Error,
+) -> anyhow::Error {
+    match timeout {
+        Some(timeout) if err.is_timeout() => LlmTimeoutError { provider, timeout }.into(),
+        _ => err.into(),
+    }
+}
+
+fn response_preview(text: &str) -> String {
+    const PREVIEW_CHARS: usize = 500;
+    if text.chars().count() > PREVIEW_CHARS {
+        let truncated: String = text.chars().take(PREVIEW_CHARS).collect();
+        format!("{truncated}...")
+    } else {
+        text.to_string()
+    }
+}
+
 /// Maps audio MIME types to canonical format names expected by OpenRouter.
 /// OpenRouter expects format values like "mp3", "wav", "ogg", etc.
 fn audio_mime_to_format(content_type: &str) -> &'static str {
@@ -563,8 +1344,6 @@ fn audio_mime_to_format(content_type: &str) -> &'static str {
 
 /// Extract assistant text and optional usage metrics from either Responses or Chat Completions payloads.
 fn extract_text_and_usage(value: &serde_json::Value) -> Option<(String, Option<TokenUsage>)> {
-    use tracing::warn;
-
     // Try OpenAI Chat Completion format first (most common)
     if let Ok(chat) = serde_json::from_value::<OpenAiChatCompletionPayload>(value.clone()) {
         if let Some(text) = chat.choices.iter().find_map(|choice| {
@@ -619,6 +1398,90 @@ fn extract_text_and_usage(value: &serde_json::Value) -> Option<(String, Option<T
     None
 }
 
+/// Error for a non-success status: moderation blocks become `LlmRefusalError`, anything else
+/// an `LlmHttpError`.
+fn http_failure(provider: LlmProvider, status: StatusCode, response_text: &str) -> anyhow::Error {
+    let refusal = serde_json::from_str::<serde_json::Value>(response_text)
+        .ok()
+        .and_then(|body| detect_refusal(&body));
+    match refusal {
+        Some(reason) => LlmRefusalError { provider, reason }.into(),
+        None => LlmHttpError {
+            provider,
+            status,
+            body: response_preview(response_text),
+        }
+        .into(),
+    }
+}
+
+/// Error for a successful response without any text: a refusal when the payload says so,
+/// otherwise a generic unexpected-payload error.
+fn missing_text_error(provider: LlmProvider, body: &serde_json::Value) -> anyhow::Error {
+    match detect_refusal(body) {
+        Some(reason) => LlmRefusalError { provider, reason }.into(),
+        None => LlmPayloadError {
+            provider,
+            body: body.to_string(),
+        }
+        .into(),
+    }
+}
+
+/// Finds a moderation block or model refusal in a provider payload and returns its reason.
+///
+/// Recognises OpenRouter moderation errors (`error.metadata.reasons`, or an `error.message`
+/// mentioning moderation/flagging), Chat Completions `message.refusal`, and a
+/// `finish_reason` of `content_filter`.
+fn detect_refusal(body: &serde_json::Value) -> Option<String> {
+    if let Some(error) = body.get("error") {
+        let message = error
+            .get("message")
+            .and_then(|message| message.as_str())
+            .unwrap_or_default()
+            .trim();
+        let reasons: Vec<&str> = error
+            .pointer("/metadata/reasons")
+            .and_then(|reasons| reasons.as_array())
+            .map(|reasons| {
+                reasons
+                    .iter()
+                    .filter_map(|reason| reason.as_str())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let lowered = message.to_ascii_lowercase();
+        if !reasons.is_empty() || lowered.contains("moderation") || lowered.contains("flagged") {
+            let mut reason = if message.is_empty() {
+                "blocked by moderation".to_string()
+            } else {
+                message.to_string()
+            };
+            if !reasons.is_empty() {
+                reason.push_str(&format!(" ({})", reasons.join(", ")));
+            }
+            return Some(reason);
+        }
+    }
+
+    let choices = body.get("choices")?.as_array()?;
+    choices.iter().find_map(|choice| {
+        let refusal = choice
+            .pointer("/message/refusal")
+            .and_then(|refusal| refusal.as_str())
+            .map(str::trim)
+            .filter(|refusal| !refusal.is_empty());
+        if let Some(refusal) = refusal {
+            return Some(refusal.to_string());
+        }
+        (choice
+            .get("finish_reason")
+            .and_then(|reason| reason.as_str())
+            == Some("content_filter"))
+        .then(|| "response blocked by content filter".to_string())
+    })
+}
+
 fn parse_model_provider(model: &str) -> Result<(LlmProvider, &str)> {
     let (provider, name) = model.split_once('/').ok_or_else(|| {
         anyhow!("model must be prefixed with provider, e.g. 'openrouter/openai/gpt-4o'")
@@ -635,14 +1498,33 @@ fn parse_model_provider(model: &str) -> Result<(LlmProvider, &str)> {
     }
 }
 
-fn approximate_token_count(input: &str) -> usize {
-    if input.trim().is_empty() {
-        return 0;
+/// Token count for `input` used when a provider omits usage: exact for OpenAI model families
+/// with a known tokenizer, otherwise the `estimate_tokens` heuristic.
+fn approximate_token_count(input: &str, model: &str) -> usize {
+    match tokenizer_for(model) {
+        Some(bpe) => bpe.encode_ordinary(input).len(),
+        None => estimate_tokens(input) as usize,
+    }
+}
+
+/// BPE tokenizer for a bare provider model name (`openai/gpt-4o`, Poe's `GPT-4o`), when its
+/// family is known.
+fn tokenizer_for(model: &str) -> Option<&'static CoreBPE> {
+    let name = model
+        .rsplit('/')
+        .next()
+        .unwrap_or(model)
+        .to_ascii_lowercase();
+    if ["gpt-4o", "gpt-4.1", "gpt-4.5", "gpt-5", "o1", "o3", "o4"]
+        .iter()
+        .any(|family| name.starts_with(family))
+    {
+        Some(o200k_base_singleton())
+    } else if name.starts_with("gpt-4") || name.starts_with("gpt-3.5") {
+        Some(cl100k_base_singleton())
+    } else {
+        None
     }
-    input
-        .split_whitespace()
-        .filter(|segment| !segment.is_empty())
-        .count()
 }
 
 #[derive(Debug, Deserialize)]
@@ -707,3 +1589,668 @@ struct OpenAiUsage {
     #[serde(default)]
     total_tokens: Option<usize>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pdf(name: &str, bytes: &[u8]) -> FileAttachment {
+        FileAttachment::new(name, "application/pdf", AttachmentKind::Pdf, bytes.to_vec())
+    }
+
+    #[test]
+    fn every_attachment_goes_to_the_last_user_message_in_order() {
+        let request = LlmRequest::new(
+            "openrouter/test/model",
+            vec![
+                ChatMessage::new(MessageRole::System, "Be brief."),
+                ChatMessage::new(MessageRole::User, "Review both files."),
+            ],
+        )
+        .with_attachments(vec![
+            pdf("main.pdf", b"main"),
+            pdf("supplement.pdf", b"supp"),
+        ]);
+
+        let messages = chat_messages(&request, &[]);
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0]["content"].as_array().unwrap().len(), 1);
+
+        let parts = messages[1]["content"].as_array().unwrap();
+        let kinds: Vec<&str> = parts
+            .iter()
+            .map(|part| part["type"].as_str().unwrap())
+            .collect();
+        assert_eq!(kinds, ["text", "file", "file"]);
+        assert_eq!(
+            parts[1]["file"],
+            format!("data:application/pdf;base64,{}", BASE64.encode(b"main"))
+        );
+        assert_eq!(
+            parts[2]["file"],
+            format!("data:application/pdf;base64,{}", BASE64.encode(b"supp"))
+        );
+    }
+
+    #[tokio::test]
+    async fn poe_payload_folds_system_prompts_into_the_first_user_message() {
+        use crate::test_support::{StubProvider, StubReply};
+
+        let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorder = seen.clone();
+        let poe = StubProvider::start(move |request| {
+            recorder.lock().unwrap().push(request.messages.clone());
+            StubReply::text("ok")
+        })
+        .await;
+        let request = LlmRequest::new(
+            "poe/GPT-5",
+            vec![
+                ChatMessage::new(MessageRole::System, "Be brief."),
+                ChatMessage::new(MessageRole::System, "Answer in English."),
+                ChatMessage::new(MessageRole::User, "Review."),
+                ChatMessage::new(MessageRole::Assistant, "Done."),
+                ChatMessage::new(MessageRole::User, "Again."),
+            ],
+        );
+        poe.client()
+            .with_poe_base_url(poe.base_url())
+            .execute(request)
+            .await
+            .unwrap();
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(
+            seen[0],
+            serde_json::json!([
+                { "role": "user", "content": "Be brief.\n\nAnswer in English.\n\nReview." },
+                { "role": "assistant", "content": "Done." },
+                { "role": "user", "content": "Again." },
+            ])
+        );
+
+        let only_system = fold_system_messages(vec![ChatMessage::new(MessageRole::System, "Hi.")]);
+        assert_eq!(only_system.len(), 1);
+        assert_eq!(only_system[0].role, MessageRole::User);
+        assert_eq!(only_system[0].text, "Hi.");
+    }
+
+    #[tokio::test]
+    async fn two_pdfs_reach_the_provider_as_two_file_parts() {
+        use crate::test_support::{StubProvider, StubReply};
+
+        let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorder = seen.clone();
+        let provider = StubProvider::start(move |request| {
+            recorder.lock().unwrap().push(request.files.clone());
+            StubReply::text("ok")
+        })
+        .await;
+
+        let request = LlmRequest::new(
+            "openrouter/test/model",
+            vec![ChatMessage::new(MessageRole::User, "Review both files.")],
+        )
+        .with_attachments(vec![
+            pdf("main.pdf", b"main"),
+            pdf("supplement.pdf", b"supp"),
+        ]);
+        provider.client().execute(request).await.unwrap();
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(
+            seen[0],
+            [
+                format!("data:application/pdf;base64,{}", BASE64.encode(b"main")),
+                format!("data:application/pdf;base64,{}", BASE64.encode(b"supp")),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn pdf_delivery_strategy_sets_the_file_part_shape() {
+        use crate::test_support::{StubProvider, StubReply};
+
+        let main = pdf("main.pdf", b"main");
+        assert_eq!(
+            attachment_part(&main, None),
+            serde_json::json!({
+                "type": "file",
+                "file": format!("data:application/pdf;base64,{}", BASE64.encode(b"main")),
+            })
+        );
+        assert_eq!(
+            attachment_part(&main, Some("https://files.test/main.pdf")),
+            serde_json::json!({
+                "type": "file",
+                "file": { "filename": "main.pdf", "file_data": "https://files.test/main.pdf" },
+            })
+        );
+
+        let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorder = seen.clone();
+        let provider = StubProvider::start(move |request| {
+            recorder.lock().unwrap().push(request.files.clone());
+            StubReply::text("ok")
+        })
+        .await;
+        let delivery = PdfDeliveryConfig::new(
+            PdfDelivery::Upload,
+            PdfDelivery::Base64,
+            Some(provider.upload_url()),
+            None,
+        )
+        .unwrap();
+        let request = LlmRequest::new(
+            "openrouter/test/model",
+            vec![ChatMessage::new(MessageRole::User, "Review both files.")],
+        )
+        .with_attachments(vec![main, pdf("supplement.pdf", b"supp")]);
+        provider
+            .client()
+            .with_pdf_delivery(delivery)
+            .execute(request)
+            .await
+            .unwrap();
+
+        assert_eq!(provider.uploads(), 2);
+        assert_eq!(
+            seen.lock().unwrap()[0],
+            [
+                "https://files.test/main.pdf",
+                "https://files.test/supplement.pdf"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn poe_server_error_falls_back_to_the_aliased_openrouter_model() {
+        use crate::test_support::{StubProvider, StubReply};
+
+        let poe = StubProvider::start(|request| {
+            if request.user.contains("bad request") {
+                StubReply::Error(
+                    StatusCode::BAD_REQUEST,
+                    serde_json::json!({ "error": { "message": "invalid input" } }),
+                )
+            } else {
+                StubReply::Error(
+                    StatusCode::BAD_GATEWAY,
+                    serde_json::json!({ "error": { "message": "Poe is down" } }),
+                )
+            }
+        })
+        .await;
+        let models = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorder = models.clone();
+        let openrouter = StubProvider::start(move |request| {
+            recorder.lock().unwrap().push(request.model.clone());
+            StubReply::text("from openrouter")
+        })
+        .await;
+        let client = openrouter.client().with_poe_base_url(poe.base_url());
+        client.provider_aliases().replace(
+            parse_alias_lines("poe/Claude-Sonnet-4=openrouter/anthropic/claude-sonnet-4").unwrap(),
+        );
+        let request = |model: &str, text: &str| {
+            LlmRequest::new(model, vec![ChatMessage::new(MessageRole::User, text)])
+        };
+
+        let response = client
+            .execute(request("poe/Claude-Sonnet-4", "Summarise."))
+            .await
+            .expect("falls back to OpenRouter");
+        assert_eq!(response.text, "from openrouter");
+        assert_eq!(response.provider, LlmProvider::OpenRouter);
+        assert_eq!(*models.lock().unwrap(), ["anthropic/claude-sonnet-4"]);
+
+        // Client errors and unmapped models surface the Poe failure unchanged.
+        let err = client
+            .execute(request("poe/Claude-Sonnet-4", "bad request"))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<LlmHttpError>().unwrap().status,
+            StatusCode::BAD_REQUEST
+        );
+        let err = client
+            .execute(request("poe/GPT-5", "Summarise."))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<LlmHttpError>().unwrap().status,
+            StatusCode::BAD_GATEWAY
+        );
+        assert_eq!(poe.calls(), 3);
+        assert_eq!(openrouter.calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn openrouter_routes_fallbacks_and_reports_the_answering_model() {
+        use crate::test_support::{StubProvider, StubReply};
+
+        let routes = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorder = routes.clone();
+        let openrouter = StubProvider::start(move |request| {
+            recorder.lock().unwrap().push(request.route.clone());
+            StubReply::TextFrom("mistralai/mistral-large".into(), "review".into())
+        })
+        .await;
+        let poe = StubProvider::start(|_| StubReply::text("unused")).await;
+        let client = openrouter.client().with_poe_base_url(poe.base_url());
+
+        let response = client
+            .execute(
+                LlmRequest::new(
+                    "openrouter/openai/gpt-4o",
+                    vec![ChatMessage::new(MessageRole::User, "Review.")],
+                )
+                .with_fallback_models(vec![
+                    "openrouter/mistralai/mistral-large".into(),
+                    "poe/Claude-Sonnet-4".into(),
+                ]),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.model, "mistralai/mistral-large");
+        assert_eq!(
+            *routes.lock().unwrap(),
+            [vec!["openai/gpt-4o", "mistralai/mistral-large"]]
+        );
+        assert_eq!(poe.calls(), 0);
+    }
+
+    #[tokio::test]
+    async fn poe_fallback_models_are_tried_in_order_on_outages() {
+        use crate::test_support::{StubProvider, StubReply};
+
+        let poe = StubProvider::start(|request| {
+            if request.user.contains("bad request") {
+                StubReply::Error(
+                    StatusCode::BAD_REQUEST,
+                    serde_json::json!({ "error": { "message": "invalid input" } }),
+                )
+            } else {
+                StubReply::Error(
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    serde_json::json!({ "error": { "message": "Poe is down" } }),
+                )
+            }
+        })
+        .await;
+        let openrouter = StubProvider::start(|_| StubReply::text("review")).await;
+        let client = openrouter.client().with_poe_base_url(poe.base_url());
+        let request = |text: &str| {
+            LlmRequest::new("poe/GPT-5", vec![ChatMessage::new(MessageRole::User, text)])
+                .with_fallback_models(vec![
+                    "poe/Claude-Sonnet-4".into(),
+                    "openrouter/openai/gpt-4o".into(),
+                ])
+        };
+
+        let response = client.execute(request("Review.")).await.unwrap();
+        assert_eq!(response.provider, LlmProvider::OpenRouter);
+        assert_eq!(response.model, "openai/gpt-4o");
+        assert_eq!((poe.calls(), openrouter.calls()), (2, 1));
+
+        // A request the provider rejects is not sent to the fallbacks.
+        let err = client.execute(request("bad request")).await.unwrap_err();
+        assert_eq!(classify_error(&err), LlmErrorKind::BadRequest);
+        assert_eq!((poe.calls(), openrouter.calls()), (3, 1));
+    }
+
+    #[tokio::test]
+    async fn concurrency_cap_is_shared_by_every_clone_of_the_client() {
+        use crate::test_support::{StubProvider, StubReply};
+
+        let provider = StubProvider::start(|request| {
+            if request.user.contains("hang") {
+                StubReply::Hang
+            } else {
+                StubReply::text("done")
+            }
+        })
+        .await;
+        // Modules each hold their own clone of the `AppState` client.
+        let grader = provider
+            .client()
+            .with_concurrency(ConcurrencyPools::new(1, 0));
+        let summarizer = grader.clone();
+        let request = |text: &str| {
+            LlmRequest::new(
+                "openrouter/openai/gpt-4o",
+                vec![ChatMessage::new(MessageRole::User, text)],
+            )
+        };
+
+        let stuck = tokio::spawn(async move { grader.execute(request("hang")).await });
+        while provider.calls() == 0 {
+            sleep(Duration::from_millis(5)).await;
+        }
+        assert!(
+            tokio::time::timeout(
+                Duration::from_millis(100),
+                summarizer.execute(request("hi"))
+            )
+            .await
+            .is_err(),
+            "the second module must wait for the only slot"
+        );
+        assert_eq!(provider.calls(), 1);
+
+        stuck.abort();
+        let response = summarizer.execute(request("hi")).await.unwrap();
+        assert_eq!(response.text, "done");
+    }
+
+    #[tokio::test]
+    async fn call_metrics_record_the_answering_model_and_failures() {
+        use crate::test_support::{StubProvider, StubReply};
+
+        let poe = StubProvider::start(|_| {
+            StubReply::Error(
+                StatusCode::BAD_GATEWAY,
+                serde_json::json!({ "error": { "message": "Poe is down" } }),
+            )
+        })
+        .await;
+        let openrouter = StubProvider::start(|_| StubReply::text("summary")).await;
+        let client = openrouter.client().with_poe_base_url(poe.base_url());
+        client
+            .provider_aliases()
+            .replace(parse_alias_lines("GPT-5=openai/gpt-5").unwrap());
+        let mut records = client.call_metrics().subscribe().expect("first subscriber");
+        assert!(client.call_metrics().subscribe().is_none());
+
+        let request = |model: &str| {
+            LlmRequest::new(
+                model,
+                vec![ChatMessage::new(MessageRole::User, "Summarise.")],
+            )
+            .with_module("summarizer")
+        };
+        client.execute(request("poe/GPT-5")).await.unwrap();
+        client
+            .execute(request("poe/Claude-Sonnet-4"))
+            .await
+            .unwrap_err();
+
+        let answered = records.recv().await.unwrap();
+        assert_eq!(answered.module, Some("summarizer"));
+        assert_eq!(answered.provider, LlmProvider::OpenRouter);
+        assert_eq!(answered.model, "openrouter/openai/gpt-5");
+        assert!(answered.success);
+        assert_eq!(answered.request_bytes, "Summarise.".len());
+        assert_eq!(answered.response_bytes, "summary".len());
+
+        let failed = records.recv().await.unwrap();
+        assert_eq!(failed.provider, LlmProvider::Poe);
+        assert_eq!(failed.model, "poe/Claude-Sonnet-4");
+        assert!(!failed.success);
+        assert_eq!(failed.response_bytes, 0);
+    }
+
+    #[test]
+    fn counts_tokens_with_the_model_family_tokenizer() {
+        // Reference encodings from OpenAI's tiktoken.
+        let cl100k = tokenizer_for("openai/gpt-4-turbo").unwrap();
+        assert_eq!(
+            cl100k.encode_ordinary("tiktoken is great!"),
+            [83, 1609, 5963, 374, 2294, 0]
+        );
+        let o200k = tokenizer_for("GPT-4o").unwrap();
+        assert_eq!(o200k.encode_ordinary("hello world"), [24912, 2375]);
+
+        let cjk = "请总结这篇论文的主要贡献。";
+        assert_eq!(approximate_token_count(cjk, "openai/gpt-4o"), 9);
+        assert_eq!(approximate_token_count(cjk, "openai/gpt-3.5-turbo"), 17);
+        assert_eq!(approximate_token_count(cjk, "o3-mini"), 9);
+        // Unknown families fall back to the script-aware heuristic.
+        assert_eq!(
+            approximate_token_count(cjk, "anthropic/claude-sonnet-4"),
+            estimate_tokens(cjk) as usize
+        );
+        assert_eq!(approximate_token_count("", "openai/gpt-4o"), 0);
+    }
+
+    #[test]
+    fn estimates_tokens_by_script() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("abcdefgh"), 2);
+        assert_eq!(estimate_tokens("信息提取"), 4);
+    }
+
+    fn http_error(status: StatusCode) -> anyhow::Error {
+        LlmHttpError {
+            provider: LlmProvider::OpenRouter,
+            status,
+            body: String::new(),
+        }
+        .into()
+    }
+
+    #[test]
+    fn retries_only_transient_http_failures() {
+        assert!(is_retryable_error(&http_error(StatusCode::BAD_GATEWAY)));
+        assert!(is_retryable_error(&http_error(
+            StatusCode::TOO_MANY_REQUESTS
+        )));
+        assert!(!is_retryable_error(&http_error(StatusCode::UNAUTHORIZED)));
+        assert!(!is_retryable_error(&http_error(StatusCode::BAD_REQUEST)));
+        assert!(!is_retryable_error(&anyhow!(
+            "OPENROUTER_API_KEY is not configured"
+        )));
+    }
+
+    #[test]
+    fn classifies_errors_by_provider_outcome() {
+        let kind = |status| classify_error(&http_error(status));
+        assert_eq!(
+            kind(StatusCode::TOO_MANY_REQUESTS),
+            LlmErrorKind::RateLimited
+        );
+        assert_eq!(kind(StatusCode::UNAUTHORIZED), LlmErrorKind::Auth);
+        assert_eq!(kind(StatusCode::NOT_FOUND), LlmErrorKind::BadRequest);
+        assert_eq!(kind(StatusCode::SERVICE_UNAVAILABLE), LlmErrorKind::Server);
+
+        let refusal = http_failure(
+            LlmProvider::OpenRouter,
+            StatusCode::FORBIDDEN,
+            r#"{"error":{"message":"Input was flagged by moderation"}}"#,
+        );
+        assert_eq!(classify_error(&refusal), LlmErrorKind::Refused);
+
+        let timeout: anyhow::Error = LlmTimeoutError {
+            provider: LlmProvider::Poe,
+            timeout: Duration::from_secs(30),
+        }
+        .into();
+        assert_eq!(classify_error(&timeout), LlmErrorKind::Network);
+
+        let not_json = serde_json::from_str::<serde_json::Value>("<html>")
+            .context("failed to parse OpenRouter response as JSON")
+            .unwrap_err();
+        assert_eq!(classify_error(&not_json), LlmErrorKind::Parse);
+        let no_text = missing_text_error(LlmProvider::Poe, &serde_json::json!({"choices": []}));
+        assert_eq!(classify_error(&no_text), LlmErrorKind::Parse);
+        assert!(!LlmErrorKind::Parse.is_retryable());
+
+        assert_eq!(
+            classify_error(&anyhow!("OPENROUTER_API_KEY is not configured")),
+            LlmErrorKind::Other
+        );
+    }
+
+    #[test]
+    fn timeouts_are_distinguishable_and_retryable() {
+        let err: anyhow::Error = LlmTimeoutError {
+            provider: LlmProvider::Poe,
+            timeout: Duration::from_secs(30),
+        }
+        .into();
+        let err = err.context("failed to read response body");
+
+        assert!(is_timeout_error(&err));
+        assert!(is_retryable_error(&err));
+        assert!(!is_timeout_error(&http_error(StatusCode::BAD_GATEWAY)));
+    }
+
+    #[test]
+    fn estimates_cost_only_for_priced_models() {
+        let pricing = load_pricing(Some(
+            r#"{"poe/custom-bot": {"prompt_per_million": 1.0, "completion_per_million": 4.0}}"#,
+        ))
+        .expect("pricing");
+        assert!(pricing.contains_key("openrouter/openai/gpt-4o"));
+
+        let mut response = LlmResponse {
+            text: String::new(),
+            token_usage: TokenUsage {
+                prompt_tokens: 500_000,
+                response_tokens: 250_000,
+                total_tokens: 750_000,
+            },
+            provider: LlmProvider::Poe,
+            model: "custom-bot".to_string(),
+            raw: serde_json::Value::Null,
+            pricing: pricing.get("poe/custom-bot").copied(),
+        };
+        assert_eq!(response.estimated_cost_usd(), Some(1.5));
+
+        response.pricing = pricing.get("poe/unknown-bot").copied();
+        assert_eq!(response.estimated_cost_usd(), None);
+    }
+
+    #[test]
+    fn seed_is_included_in_openrouter_payload_when_set() {
+        let request = LlmRequest::new(
+            "openrouter/openai/gpt-4o",
+            vec![ChatMessage::new(MessageRole::User, "hi")],
+        );
+        let payload = openrouter_payload("openai/gpt-4o", &[], Vec::new(), &request);
+        assert!(payload.get("seed").is_none());
+
+        let payload = openrouter_payload("openai/gpt-4o", &[], Vec::new(), &request.with_seed(42));
+        assert_eq!(payload["seed"], serde_json::json!(42));
+    }
+
+    fn ok_response() -> LlmResponse {
+        LlmResponse {
+            text: "ok".to_string(),
+            token_usage: TokenUsage::default(),
+            provider: LlmProvider::OpenRouter,
+            model: "mock".to_string(),
+            raw: serde_json::Value::Null,
+            pricing: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn retry_counts_attempts_until_success() {
+        let policy = RetryPolicy::constant(4, Duration::ZERO);
+        let mut calls = 0;
+        let outcome = retry_with_policy(policy, "mock", || {
+            calls += 1;
+            let result = if calls < 3 {
+                Err(http_error(StatusCode::SERVICE_UNAVAILABLE))
+            } else {
+                Ok(ok_response())
+            };
+            async move { result }
+        })
+        .await;
+
+        assert!(outcome.result.is_ok());
+        assert_eq!(outcome.attempts, 3);
+    }
+
+    #[tokio::test]
+    async fn retry_stops_on_permanent_error() {
+        let policy = RetryPolicy::constant(4, Duration::ZERO);
+        let outcome = retry_with_policy(policy, "mock", || async {
+            Err(http_error(StatusCode::BAD_REQUEST))
+        })
+        .await;
+
+        assert!(outcome.result.is_err());
+        assert_eq!(outcome.attempts, 1);
+    }
+
+    #[test]
+    fn retry_delay_grows_exponentially() {
+        let policy = RetryPolicy::new(4, Duration::from_secs(2));
+        assert_eq!(policy.delay_after(1), Duration::from_secs(2));
+        assert_eq!(policy.delay_after(2), Duration::from_secs(4));
+        assert_eq!(policy.delay_after(3), Duration::from_secs(8));
+
+        let constant = RetryPolicy::constant(3, Duration::from_secs(2));
+        assert_eq!(constant.delay_after(3), Duration::from_secs(2));
+    }
+    #[test]
+    fn context_length_errors_are_recognised() {
+        let context = anyhow::Error::from(LlmHttpError {
+            provider: LlmProvider::OpenRouter,
+            status: StatusCode::BAD_REQUEST,
+            body: "This endpoint's maximum context length is 16385 tokens.".to_string(),
+        });
+        assert!(is_context_length_error(&context));
+
+        let other = anyhow::Error::from(LlmHttpError {
+            provider: LlmProvider::OpenRouter,
+            status: StatusCode::BAD_REQUEST,
+            body: "invalid model".to_string(),
+        });
+        assert!(!is_context_length_error(&other));
+
+        let mut response = ok_response();
+        assert!(!response.hit_length_limit());
+        response.raw = serde_json::json!({"choices": [{"finish_reason": "length"}]});
+        assert!(response.hit_length_limit());
+    }
+
+    #[test]
+    fn moderation_block_maps_to_refusal_error() {
+        let body = r#"{"error":{"code":403,"message":"Input was flagged by moderation","metadata":{"reasons":["violence"],"flagged_input":"..."}}}"#;
+        let err = http_failure(LlmProvider::OpenRouter, StatusCode::FORBIDDEN, body);
+
+        assert!(is_refusal_error(&err));
+        assert!(!is_retryable_error(&err));
+        assert_eq!(
+            err.to_string(),
+            "openrouter refused the request: Input was flagged by moderation (violence)"
+        );
+
+        let unrelated = http_failure(
+            LlmProvider::OpenRouter,
+            StatusCode::FORBIDDEN,
+            r#"{"error":{"code":403,"message":"Key limit exceeded"}}"#,
+        );
+        assert!(!is_refusal_error(&unrelated));
+    }
+
+    #[test]
+    fn empty_completion_with_refusal_maps_to_refusal_error() {
+        let refused = serde_json::json!({
+            "choices": [{"message": {"content": null, "refusal": "I can't help with that."}, "finish_reason": "stop"}]
+        });
+        assert!(extract_text_and_usage(&refused).is_none());
+        let err = missing_text_error(LlmProvider::Poe, &refused);
+        assert!(is_refusal_error(&err));
+        assert!(err.to_string().contains("I can't help with that."));
+
+        let filtered = serde_json::json!({
+            "choices": [{"message": {"content": ""}, "finish_reason": "content_filter"}]
+        });
+        assert!(is_refusal_error(&missing_text_error(
+            LlmProvider::OpenRouter,
+            &filtered
+        )));
+
+        let empty = serde_json::json!({"choices": []});
+        assert!(!is_refusal_error(&missing_text_error(
+            LlmProvider::OpenRouter,
+            &empty
+        )));
+    }
+}
@@ -0,0 +1,153 @@
+use std::env;
+
+use anyhow::{Context, Result, anyhow, bail};
+use reqwest::Client;
+use serde::Deserialize;
+
+use super::{FileAttachment, LlmProvider};
+
+/// How PDF attachments reach a provider.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum PdfDelivery {
+    /// The whole file inline as a base64 data URL in the chat payload.
+    #[default]
+    Base64,
+    /// Uploaded first to the file store at `LLM_FILE_UPLOAD_URL`; the payload only carries the
+    /// returned URL. Keeps large manuscripts out of every (retried) request body.
+    Upload,
+}
+
+impl PdfDelivery {
+    pub fn parse(value: &str) -> Result<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "" | "base64" | "inline" => Ok(Self::Base64),
+            "upload" | "url" => Ok(Self::Upload),
+            other => bail!("unknown PDF delivery `{other}` (expected base64 or upload)"),
+        }
+    }
+}
+
+/// Per-provider PDF delivery plus the upload endpoint the `Upload` strategy needs.
+///
+/// Configured via `OPENROUTER_PDF_DELIVERY` / `POE_PDF_DELIVERY` (`base64`, the default, or
+/// `upload`), `LLM_FILE_UPLOAD_URL` and optional bearer `LLM_FILE_UPLOAD_TOKEN`. The endpoint
+/// receives the raw PDF (`Content-Type` and `X-Filename` headers set) and answers
+/// `{"url": "..."}` with a URL the provider can fetch.
+#[derive(Clone, Debug, Default)]
+pub struct PdfDeliveryConfig {
+    openrouter: PdfDelivery,
+    poe: PdfDelivery,
+    upload_url: Option<String>,
+    upload_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct UploadResponse {
+    url: String,
+}
+
+impl PdfDeliveryConfig {
+    pub fn new(
+        openrouter: PdfDelivery,
+        poe: PdfDelivery,
+        upload_url: Option<String>,
+        upload_token: Option<String>,
+    ) -> Result<Self> {
+        let upload_url = upload_url
+            .map(|url| url.trim().to_string())
+            .filter(|url| !url.is_empty());
+        let uploads = openrouter == PdfDelivery::Upload || poe == PdfDelivery::Upload;
+        if uploads && upload_url.is_none() {
+            bail!("PDF delivery `upload` requires LLM_FILE_UPLOAD_URL");
+        }
+        Ok(Self {
+            openrouter,
+            poe,
+            upload_url,
+            upload_token: upload_token.filter(|token| !token.trim().is_empty()),
+        })
+    }
+
+    pub fn from_env() -> Result<Self> {
+        let delivery = |key: &str| {
+            env::var(key)
+                .map(|value| PdfDelivery::parse(&value).with_context(|| format!("invalid {key}")))
+                .unwrap_or(Ok(PdfDelivery::Base64))
+        };
+        Self::new(
+            delivery("OPENROUTER_PDF_DELIVERY")?,
+            delivery("POE_PDF_DELIVERY")?,
+            env::var("LLM_FILE_UPLOAD_URL").ok(),
+            env::var("LLM_FILE_UPLOAD_TOKEN").ok(),
+        )
+    }
+
+    pub fn for_provider(&self, provider: LlmProvider) -> PdfDelivery {
+        match provider {
+            LlmProvider::OpenRouter => self.openrouter,
+            LlmProvider::Poe => self.poe,
+        }
+    }
+
+    /// Uploads `attachment` and returns the URL to reference it by.
+    pub async fn upload(&self, http: &Client, attachment: &FileAttachment) -> Result<String> {
+        let url = self
+            .upload_url
+            .as_deref()
+            .ok_or_else(|| anyhow!("LLM_FILE_UPLOAD_URL is not configured"))?;
+        let mut request = http
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, &attachment.content_type)
+            .header("X-Filename", &attachment.filename)
+            .body(attachment.bytes.clone());
+        if let Some(token) = &self.upload_token {
+            request = request.bearer_auth(token);
+        }
+
+        let response = request
+            .send()
+            .await
+            .with_context(|| format!("failed to upload {}", attachment.filename))?;
+        let status = response.status();
+        if !status.is_success() {
+            bail!(
+                "file upload for {} failed with status {status}",
+                attachment.filename
+            );
+        }
+        let body: UploadResponse = response
+            .json()
+            .await
+            .context("file upload response has no `url`")?;
+        Ok(body.url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn upload_delivery_needs_an_upload_url() {
+        assert_eq!(PdfDelivery::parse("").unwrap(), PdfDelivery::Base64);
+        assert_eq!(PdfDelivery::parse(" Upload ").unwrap(), PdfDelivery::Upload);
+        assert!(PdfDelivery::parse("s3").is_err());
+
+        assert!(
+            PdfDeliveryConfig::new(PdfDelivery::Upload, PdfDelivery::Base64, None, None).is_err()
+        );
+        let config = PdfDeliveryConfig::new(
+            PdfDelivery::Base64,
+            PdfDelivery::Upload,
+            Some("https://files.example/upload".into()),
+            Some(" ".into()),
+        )
+        .unwrap();
+        assert_eq!(
+            config.for_provider(LlmProvider::OpenRouter),
+            PdfDelivery::Base64
+        );
+        assert_eq!(config.for_provider(LlmProvider::Poe), PdfDelivery::Upload);
+        assert!(config.upload_token.is_none());
+    }
+}
@@ -0,0 +1,244 @@
+use std::collections::BTreeMap;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use sqlx::{PgPool, Row};
+use tracing::warn;
+
+use crate::llm::{CallRecord, LlmClient};
+
+/// Call metrics older than this are deleted by the maintenance cycle.
+const RETENTION_DAYS: i64 = 30;
+/// Most recent calls aggregated per request, so a busy window cannot load unbounded rows.
+const MAX_SAMPLES: i64 = 50_000;
+
+/// Subscribes to `llm`'s call records and inserts each into `llm_call_metrics` in the
+/// background. Failed inserts are logged and dropped; they never affect the call itself.
+pub fn spawn_recorder(pool: PgPool, llm: &LlmClient) {
+    let Some(mut records) = llm.call_metrics().subscribe() else {
+        return;
+    };
+    tokio::spawn(async move {
+        while let Some(record) = records.recv().await {
+            if let Err(err) = insert_record(&pool, &record).await {
+                warn!(?err, model = %record.model, "failed to record LLM call metrics");
+            }
+        }
+    });
+}
+
+async fn insert_record(pool: &PgPool, record: &CallRecord) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO llm_call_metrics
+            (module_key, provider, model, duration_ms, prompt_tokens, response_tokens,
+             request_bytes, response_bytes, success)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+    )
+    .bind(record.module)
+    .bind(record.provider.to_string())
+    .bind(&record.model)
+    .bind(record.duration.as_millis() as i64)
+    .bind(record.prompt_tokens as i64)
+    .bind(record.response_tokens as i64)
+    .bind(record.request_bytes as i64)
+    .bind(record.response_bytes as i64)
+    .bind(record.success)
+    .execute(pool)
+    .await
+    .context("failed to insert LLM call metrics")?;
+    Ok(())
+}
+
+pub async fn purge_expired(pool: &PgPool) -> Result<u64> {
+    let cutoff = Utc::now() - Duration::days(RETENTION_DAYS);
+    let result = sqlx::query("DELETE FROM llm_call_metrics WHERE occurred_at < $1")
+        .bind(cutoff)
+        .execute(pool)
+        .await
+        .context("failed to delete old LLM call metrics")?;
+
+    Ok(result.rows_affected())
+}
+
+/// One persisted call as read back for aggregation.
+#[derive(Debug, Clone)]
+pub struct CallSample {
+    pub module: Option<String>,
+    pub provider: String,
+    pub model: String,
+    pub duration_ms: i64,
+    pub prompt_tokens: i64,
+    pub response_tokens: i64,
+    pub request_bytes: i64,
+    pub response_bytes: i64,
+    pub success: bool,
+}
+
+/// Calls made since `since`, newest first, capped at `MAX_SAMPLES`. The flag reports whether
+/// the cap cut older calls off.
+pub async fn load_samples(pool: &PgPool, since: DateTime<Utc>) -> Result<(Vec<CallSample>, bool)> {
+    let rows = sqlx::query(
+        "SELECT module_key, provider, model, duration_ms, prompt_tokens, response_tokens,
+                request_bytes, response_bytes, success
+         FROM llm_call_metrics
+         WHERE occurred_at >= $1
+         ORDER BY occurred_at DESC
+         LIMIT $2",
+    )
+    .bind(since)
+    .bind(MAX_SAMPLES + 1)
+    .fetch_all(pool)
+    .await
+    .context("failed to load LLM call metrics")?;
+
+    let truncated = rows.len() as i64 > MAX_SAMPLES;
+    let samples = rows
+        .into_iter()
+        .take(MAX_SAMPLES as usize)
+        .map(|row| -> Result<CallSample> {
+            Ok(CallSample {
+                module: row.try_get("module_key")?,
+                provider: row.try_get("provider")?,
+                model: row.try_get("model")?,
+                duration_ms: row.try_get("duration_ms")?,
+                prompt_tokens: row.try_get("prompt_tokens")?,
+                response_tokens: row.try_get("response_tokens")?,
+                request_bytes: row.try_get("request_bytes")?,
+                response_bytes: row.try_get("response_bytes")?,
+                success: row.try_get("success")?,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok((samples, truncated))
+}
+
+/// Aggregate for one (module, provider, model) combination.
+///
+/// Latency percentiles cover every call, failed ones included; token and size averages cover
+/// successful calls only, since failures carry no response.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CallMetricsGroup {
+    pub module: Option<String>,
+    pub provider: String,
+    pub model: String,
+    pub calls: usize,
+    pub errors: usize,
+    pub error_rate: f64,
+    pub p50_ms: i64,
+    pub p95_ms: i64,
+    pub avg_prompt_tokens: f64,
+    pub avg_response_tokens: f64,
+    pub avg_request_bytes: f64,
+    pub avg_response_bytes: f64,
+}
+
+/// Groups `samples` by module, provider and model, in that sort order.
+pub fn summarize(samples: &[CallSample]) -> Vec<CallMetricsGroup> {
+    let mut grouped: BTreeMap<(Option<&str>, &str, &str), Vec<&CallSample>> = BTreeMap::new();
+    for sample in samples {
+        grouped
+            .entry((
+                sample.module.as_deref(),
+                sample.provider.as_str(),
+                sample.model.as_str(),
+            ))
+            .or_default()
+            .push(sample);
+    }
+
+    grouped
+        .into_iter()
+        .map(|((module, provider, model), calls)| {
+            let mut durations: Vec<i64> = calls.iter().map(|call| call.duration_ms).collect();
+            durations.sort_unstable();
+            let succeeded: Vec<&&CallSample> = calls.iter().filter(|call| call.success).collect();
+            let average = |field: fn(&CallSample) -> i64| {
+                if succeeded.is_empty() {
+                    0.0
+                } else {
+                    succeeded.iter().map(|call| field(call)).sum::<i64>() as f64
+                        / succeeded.len() as f64
+                }
+            };
+            let errors = calls.len() - succeeded.len();
+
+            CallMetricsGroup {
+                module: module.map(str::to_string),
+                provider: provider.to_string(),
+                model: model.to_string(),
+                calls: calls.len(),
+                errors,
+                error_rate: errors as f64 / calls.len() as f64,
+                p50_ms: percentile(&durations, 50),
+                p95_ms: percentile(&durations, 95),
+                avg_prompt_tokens: average(|call| call.prompt_tokens),
+                avg_response_tokens: average(|call| call.response_tokens),
+                avg_request_bytes: average(|call| call.request_bytes),
+                avg_response_bytes: average(|call| call.response_bytes),
+            }
+        })
+        .collect()
+}
+
+/// Nearest-rank percentile of ascending `sorted`; zero when empty.
+fn percentile(sorted: &[i64], pct: usize) -> i64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = (pct * sorted.len()).div_ceil(100).max(1);
+    sorted[rank.min(sorted.len()) - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(model: &str, duration_ms: i64, success: bool) -> CallSample {
+        CallSample {
+            module: Some("summarizer".to_string()),
+            provider: "openrouter".to_string(),
+            model: model.to_string(),
+            duration_ms,
+            prompt_tokens: if success { 1_000 } else { 0 },
+            response_tokens: if success { 200 } else { 0 },
+            request_bytes: 4_000,
+            response_bytes: if success { 800 } else { 0 },
+            success,
+        }
+    }
+
+    #[test]
+    fn groups_report_nearest_rank_percentiles_and_error_rates() {
+        // 1..=20 seconds in shuffled order, two of them failures.
+        let mut samples: Vec<CallSample> = [
+            7, 3, 20, 1, 15, 9, 12, 5, 18, 2, 11, 4, 16, 8, 19, 6, 13, 10, 17, 14,
+        ]
+        .into_iter()
+        .map(|seconds| sample("openrouter/openai/gpt-4o", seconds * 1_000, true))
+        .collect();
+        samples[0].success = false;
+        samples[1].success = false;
+        samples.push(sample("openrouter/openai/gpt-4o-mini", 250, true));
+
+        let groups = summarize(&samples);
+        assert_eq!(groups.len(), 2);
+
+        let gpt4o = &groups[0];
+        assert_eq!(gpt4o.model, "openrouter/openai/gpt-4o");
+        assert_eq!((gpt4o.calls, gpt4o.errors), (20, 2));
+        assert_eq!(gpt4o.error_rate, 0.1);
+        assert_eq!((gpt4o.p50_ms, gpt4o.p95_ms), (10_000, 19_000));
+        assert_eq!(gpt4o.avg_prompt_tokens, 1_000.0);
+        assert_eq!(gpt4o.avg_response_bytes, 800.0);
+
+        let mini = &groups[1];
+        assert_eq!((mini.calls, mini.errors), (1, 0));
+        assert_eq!((mini.p50_ms, mini.p95_ms), (250, 250));
+
+        assert_eq!(percentile(&[10, 20, 30, 40], 50), 20);
+        assert_eq!(percentile(&[10, 20, 30, 40], 95), 40);
+        assert_eq!(percentile(&[], 95), 0);
+    }
+}
@@ -1,8 +1,12 @@
 mod config;
 mod history;
 pub mod llm;
+mod llm_metrics;
 mod maintenance;
 mod modules;
+mod notifications;
+#[cfg(test)]
+mod test_support;
 mod usage;
 mod utils;
 mod web;
@@ -59,7 +63,12 @@ async fn app_main() -> Result<()> {
     let listener = TcpListener::bind(addr)
         .await
         .context("failed to bind listener")?;
-    axum::serve(listener, app).await.context("server error")?;
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await
+    .context("server error")?;
 
     Ok(())
 }
@@ -1,13 +1,23 @@
-use std::{io::ErrorKind, path::PathBuf};
+use std::{collections::HashMap, env, io::ErrorKind, path::PathBuf, sync::OnceLock};
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, anyhow, bail};
 use chrono::{DateTime, Duration, Utc};
 use sqlx::{PgPool, Row};
-use tokio::time::{Duration as TokioDuration, sleep};
+use tokio::{
+    task,
+    time::{Duration as TokioDuration, sleep},
+};
 use tracing::{error, info, warn};
 use uuid::Uuid;
 
-use crate::{AppState, history};
+use crate::{
+    AppState, history, llm_metrics,
+    usage::{
+        self, MODULE_DOCX_TO_PDF, MODULE_GRADER, MODULE_INFO_EXTRACT, MODULE_REVIEWER,
+        MODULE_SUMMARIZER, MODULE_TRANSCRIBE, MODULE_TRANSLATE_DOCX,
+    },
+    web::storage::{archive_job_directory, job_archive_path},
+};
 
 const CLEANUP_INTERVAL_MINUTES: u64 = 15;
 const SUMMARIZER_STORAGE: &str = "storage/summarizer";
@@ -15,12 +25,44 @@ const DOCX_STORAGE: &str = "storage/translatedocx";
 const GRADER_STORAGE: &str = "storage/grader";
 const INFO_EXTRACT_STORAGE: &str = "storage/infoextract";
 const REVIEWER_STORAGE: &str = "storage/reviewer";
+const TRANSCRIBE_STORAGE: &str = "storage/transcribe";
+const DOCX_TO_PDF_STORAGE: &str = "storage/docx2pdf";
+
+/// Days a module keeps job files, and lists jobs in history, when the dashboard sets no
+/// window for it.
+pub const DEFAULT_RETENTION_DAYS: i64 = 1;
+/// Longest window the dashboard accepts.
+pub const MAX_RETENTION_DAYS: i64 = 365;
+
+/// Whether `MAINTENANCE_DRY_RUN` is set. In a dry run the cleanup cycle only logs the job
+/// directories and rows it would archive or purge; files, `files_*_at`, history rows and
+/// call metrics stay untouched.
+pub fn dry_run_enabled() -> bool {
+    static DRY_RUN: OnceLock<bool> = OnceLock::new();
+    *DRY_RUN.get_or_init(|| {
+        env::var("MAINTENANCE_DRY_RUN")
+            .map(|value| {
+                matches!(
+                    value.trim().to_ascii_lowercase().as_str(),
+                    "1" | "true" | "yes" | "on"
+                )
+            })
+            .unwrap_or(false)
+    })
+}
 
 pub fn spawn(state: AppState) {
+    let dry_run = dry_run_enabled();
+    if dry_run {
+        warn!(
+            "MAINTENANCE_DRY_RUN is set; job files will only be reported, not archived or purged"
+        );
+    }
+
     tokio::spawn(async move {
         let interval = TokioDuration::from_secs(CLEANUP_INTERVAL_MINUTES * 60);
         loop {
-            if let Err(err) = run_cleanup_cycle(&state).await {
+            if let Err(err) = run_cleanup_cycle(&state, dry_run).await {
                 error!(?err, "retention cleanup cycle failed");
             }
             sleep(interval).await;
@@ -28,28 +70,274 @@ pub fn spawn(state: AppState) {
     });
 }
 
-async fn run_cleanup_cycle(state: &AppState) -> Result<()> {
-    let pool = state.pool();
-    let cutoff = Utc::now() - Duration::hours(history::HISTORY_RETENTION_HOURS);
+/// How long one module keeps job files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    /// Days after the last update before the job's files are deleted.
+    pub retention_days: i64,
+    /// Days after the last update before the job directory is compressed into
+    /// `<job_dir>.zip`; always shorter than `retention_days`. `None` never archives.
+    pub archive_after_days: Option<i64>,
+}
 
-    let mut purged_jobs = 0_u64;
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        RetentionPolicy {
+            retention_days: DEFAULT_RETENTION_DAYS,
+            archive_after_days: None,
+        }
+    }
+}
 
-    purged_jobs += purge_summarizer(&pool, cutoff).await?;
-    purged_jobs += purge_docx(&pool, cutoff).await?;
-    purged_jobs += purge_grader(&pool, cutoff).await?;
-    purged_jobs += purge_info_extract(&pool, cutoff).await?;
-    purged_jobs += purge_reviewer(&pool, cutoff).await?;
+/// Retention policy of every registered module, falling back to `RetentionPolicy::default`.
+pub async fn retention_policies(pool: &PgPool) -> Result<HashMap<String, RetentionPolicy>> {
+    let mut result: HashMap<String, RetentionPolicy> = usage::REGISTERED_MODULES
+        .iter()
+        .map(|descriptor| (descriptor.key.to_string(), RetentionPolicy::default()))
+        .collect();
+
+    let rows = sqlx::query(
+        "SELECT module_key, retention_days, archive_after_days FROM file_retention_policies",
+    )
+    .fetch_all(pool)
+    .await
+    .context("failed to fetch file retention policies")?;
+
+    for row in rows {
+        let module: String = row.try_get("module_key")?;
+        let retention_days: i32 = row.try_get("retention_days")?;
+        let archive_after_days: Option<i32> = row.try_get("archive_after_days")?;
+        if let Some(entry) = result.get_mut(&module) {
+            *entry = RetentionPolicy {
+                retention_days: i64::from(retention_days),
+                archive_after_days: archive_after_days.map(i64::from),
+            };
+        }
+    }
+
+    Ok(result)
+}
+
+pub async fn save_retention_policies(
+    pool: &PgPool,
+    policies: &HashMap<String, RetentionPolicy>,
+) -> Result<()> {
+    let mut transaction = pool.begin().await?;
+
+    for (module, policy) in policies {
+        sqlx::query(
+            "INSERT INTO file_retention_policies (module_key, retention_days, archive_after_days) \
+             VALUES ($1, $2, $3) \
+             ON CONFLICT (module_key) DO UPDATE SET retention_days = EXCLUDED.retention_days, \
+             archive_after_days = EXCLUDED.archive_after_days, updated_at = NOW()",
+        )
+        .bind(module.as_str())
+        .bind(policy.retention_days as i32)
+        .bind(policy.archive_after_days.map(|days| days as i32))
+        .execute(&mut *transaction)
+        .await?;
+    }
+
+    transaction.commit().await?;
+
+    Ok(())
+}
+
+/// Parses a dashboard retention field: whole days in `1..=MAX_RETENTION_DAYS`.
+pub fn parse_retention_days(input: Option<&str>) -> Result<i64> {
+    let value = input
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .ok_or_else(|| anyhow!("missing retention window"))?;
+    let days: i64 = value
+        .parse()
+        .map_err(|_| anyhow!("invalid retention window"))?;
+    if !(1..=MAX_RETENTION_DAYS).contains(&days) {
+        bail!("retention window out of range");
+    }
+    Ok(days)
+}
+
+/// Parses a dashboard archive field: blank disables archiving, otherwise whole days
+/// shorter than `retention_days`.
+pub fn parse_archive_after_days(input: Option<&str>, retention_days: i64) -> Result<Option<i64>> {
+    let Some(value) = input.map(str::trim).filter(|value| !value.is_empty()) else {
+        return Ok(None);
+    };
+    let days: i64 = value
+        .parse()
+        .map_err(|_| anyhow!("invalid archive window"))?;
+    if !(1..retention_days).contains(&days) {
+        bail!("archive window must be shorter than the retention window");
+    }
+    Ok(Some(days))
+}
+
+async fn run_cleanup_cycle(state: &AppState, dry_run: bool) -> Result<()> {
+    let pool = state.pool();
+    let policies = retention_policies(&pool).await?;
+    let now = Utc::now();
+
+    let purged_jobs = purge_expired_files(&pool, &policies, now, dry_run).await?;
+    let archived_jobs = archive_idle_jobs(&pool, &policies, now, dry_run).await?;
+    if dry_run {
+        // History rows and call metrics are deleted outright, so a dry run leaves them alone.
+        info!(
+            would_purge = purged_jobs,
+            would_archive = archived_jobs,
+            "retention dry run completed"
+        );
+        return Ok(());
+    }
 
     let history_removed = history::purge_stale_history(&pool).await?;
+    let call_metrics_removed = llm_metrics::purge_expired(&pool).await?;
 
-    if purged_jobs > 0 || history_removed > 0 {
-        info!(purged_jobs, history_removed, "retention cleanup completed");
+    if purged_jobs > 0 || archived_jobs > 0 || history_removed > 0 || call_metrics_removed > 0 {
+        info!(
+            purged_jobs,
+            archived_jobs, history_removed, call_metrics_removed, "retention cleanup completed"
+        );
     }
 
     Ok(())
 }
 
-async fn purge_summarizer(pool: &PgPool, cutoff: DateTime<Utc>) -> Result<u64> {
+fn policy_for(policies: &HashMap<String, RetentionPolicy>, module: &str) -> RetentionPolicy {
+    policies.get(module).copied().unwrap_or_default()
+}
+
+/// Removes the files of jobs last updated before `now` minus their module's window.
+async fn purge_expired_files(
+    pool: &PgPool,
+    policies: &HashMap<String, RetentionPolicy>,
+    now: DateTime<Utc>,
+    dry_run: bool,
+) -> Result<u64> {
+    let cutoff = |module: &str| now - Duration::days(policy_for(policies, module).retention_days);
+
+    let mut purged_jobs = 0_u64;
+
+    purged_jobs += purge_summarizer(pool, cutoff(MODULE_SUMMARIZER), dry_run).await?;
+    purged_jobs += purge_docx(pool, cutoff(MODULE_TRANSLATE_DOCX), dry_run).await?;
+    purged_jobs += purge_grader(pool, cutoff(MODULE_GRADER), dry_run).await?;
+    purged_jobs += purge_info_extract(pool, cutoff(MODULE_INFO_EXTRACT), dry_run).await?;
+    purged_jobs += purge_reviewer(pool, cutoff(MODULE_REVIEWER), dry_run).await?;
+    purged_jobs += purge_transcribe(pool, cutoff(MODULE_TRANSCRIBE), dry_run).await?;
+    purged_jobs += purge_docx_to_pdf(pool, cutoff(MODULE_DOCX_TO_PDF), dry_run).await?;
+
+    Ok(purged_jobs)
+}
+
+/// Job table, key column, key type and storage root of every module, for archiving.
+const ARCHIVE_TARGETS: &[(&str, &str, &str, &str, &str)] = &[
+    (
+        MODULE_SUMMARIZER,
+        "summary_jobs",
+        "id",
+        "UUID",
+        SUMMARIZER_STORAGE,
+    ),
+    (
+        MODULE_TRANSLATE_DOCX,
+        "docx_jobs",
+        "id",
+        "UUID",
+        DOCX_STORAGE,
+    ),
+    (MODULE_GRADER, "grader_jobs", "id", "UUID", GRADER_STORAGE),
+    (
+        MODULE_INFO_EXTRACT,
+        "info_extract_jobs",
+        "id",
+        "UUID",
+        INFO_EXTRACT_STORAGE,
+    ),
+    (
+        MODULE_REVIEWER,
+        "reviewer_jobs",
+        "job_id",
+        "INTEGER",
+        REVIEWER_STORAGE,
+    ),
+    (
+        MODULE_TRANSCRIBE,
+        "transcribe_jobs",
+        "id",
+        "UUID",
+        TRANSCRIBE_STORAGE,
+    ),
+    (
+        MODULE_DOCX_TO_PDF,
+        "docx2pdf_jobs",
+        "id",
+        "UUID",
+        DOCX_TO_PDF_STORAGE,
+    ),
+];
+
+/// Compresses the directories of finished jobs idle longer than their module's archive
+/// window. `updated_at` is left alone so the purge window still counts from the job's end.
+async fn archive_idle_jobs(
+    pool: &PgPool,
+    policies: &HashMap<String, RetentionPolicy>,
+    now: DateTime<Utc>,
+    dry_run: bool,
+) -> Result<u64> {
+    let mut archived = 0_u64;
+
+    for &(module, table, id_column, id_type, storage) in ARCHIVE_TARGETS {
+        let Some(days) = policy_for(policies, module).archive_after_days else {
+            continue;
+        };
+        let cutoff = now - Duration::days(days);
+
+        let job_keys: Vec<String> = sqlx::query_scalar(&format!(
+            "SELECT {id_column}::TEXT FROM {table}
+             WHERE files_purged_at IS NULL AND files_archived_at IS NULL
+               AND status IN ('completed', 'failed', 'cancelled') AND updated_at < $1"
+        ))
+        .bind(cutoff)
+        .fetch_all(pool)
+        .await
+        .with_context(|| format!("failed to fetch {module} jobs pending archiving"))?;
+
+        for job_key in job_keys {
+            if dry_run {
+                log_dry_run("archive", module, storage, &job_key);
+                archived += 1;
+                continue;
+            }
+
+            let job_dir = PathBuf::from(storage).join(&job_key);
+            let result = task::spawn_blocking({
+                let job_dir = job_dir.clone();
+                move || archive_job_directory(&job_dir)
+            })
+            .await
+            .map_err(anyhow::Error::from)
+            .and_then(|result| result);
+            if let Err(err) = result {
+                warn!(?err, path = %job_dir.display(), "failed to archive job directory");
+                continue;
+            }
+
+            sqlx::query(&format!(
+                "UPDATE {table} SET files_archived_at = NOW() WHERE {id_column} = $1::{id_type}"
+            ))
+            .bind(&job_key)
+            .execute(pool)
+            .await
+            .with_context(|| format!("failed to mark {module} job {job_key} as archived"))?;
+
+            archived += 1;
+        }
+    }
+
+    Ok(archived)
+}
+
+async fn purge_summarizer(pool: &PgPool, cutoff: DateTime<Utc>, dry_run: bool) -> Result<u64> {
     let rows = sqlx::query(
         "SELECT id FROM summary_jobs WHERE files_purged_at IS NULL AND updated_at < $1",
     )
@@ -64,6 +352,12 @@ async fn purge_summarizer(pool: &PgPool, cutoff: DateTime<Utc>) -> Result<u64> {
         let job_id: Uuid = row.try_get("id")?;
         let job_id_str = job_id.to_string();
 
+        if dry_run {
+            log_dry_run("purge", MODULE_SUMMARIZER, SUMMARIZER_STORAGE, &job_id_str);
+            purged += 1;
+            continue;
+        }
+
         if !remove_job_directory(SUMMARIZER_STORAGE, &job_id_str).await {
             continue;
         }
@@ -97,7 +391,7 @@ async fn purge_summarizer(pool: &PgPool, cutoff: DateTime<Utc>) -> Result<u64> {
     Ok(purged)
 }
 
-async fn purge_docx(pool: &PgPool, cutoff: DateTime<Utc>) -> Result<u64> {
+async fn purge_docx(pool: &PgPool, cutoff: DateTime<Utc>, dry_run: bool) -> Result<u64> {
     let rows =
         sqlx::query("SELECT id FROM docx_jobs WHERE files_purged_at IS NULL AND updated_at < $1")
             .bind(cutoff)
@@ -111,13 +405,19 @@ async fn purge_docx(pool: &PgPool, cutoff: DateTime<Utc>) -> Result<u64> {
         let job_id: Uuid = row.try_get("id")?;
         let job_id_str = job_id.to_string();
 
+        if dry_run {
+            log_dry_run("purge", MODULE_TRANSLATE_DOCX, DOCX_STORAGE, &job_id_str);
+            purged += 1;
+            continue;
+        }
+
         if !remove_job_directory(DOCX_STORAGE, &job_id_str).await {
             continue;
         }
 
         sqlx::query(
             "UPDATE docx_documents
-             SET translated_path = NULL, updated_at = NOW()
+             SET translated_path = NULL, translated_pdf_path = NULL, updated_at = NOW()
              WHERE job_id = $1",
         )
         .bind(job_id)
@@ -141,7 +441,7 @@ async fn purge_docx(pool: &PgPool, cutoff: DateTime<Utc>) -> Result<u64> {
     Ok(purged)
 }
 
-async fn purge_grader(pool: &PgPool, cutoff: DateTime<Utc>) -> Result<u64> {
+async fn purge_grader(pool: &PgPool, cutoff: DateTime<Utc>, dry_run: bool) -> Result<u64> {
     let rows =
         sqlx::query("SELECT id FROM grader_jobs WHERE files_purged_at IS NULL AND updated_at < $1")
             .bind(cutoff)
@@ -155,6 +455,12 @@ async fn purge_grader(pool: &PgPool, cutoff: DateTime<Utc>) -> Result<u64> {
         let job_id: Uuid = row.try_get("id")?;
         let job_id_str = job_id.to_string();
 
+        if dry_run {
+            log_dry_run("purge", MODULE_GRADER, GRADER_STORAGE, &job_id_str);
+            purged += 1;
+            continue;
+        }
+
         if !remove_job_directory(GRADER_STORAGE, &job_id_str).await {
             continue;
         }
@@ -175,7 +481,94 @@ async fn purge_grader(pool: &PgPool, cutoff: DateTime<Utc>) -> Result<u64> {
     Ok(purged)
 }
 
-async fn purge_info_extract(pool: &PgPool, cutoff: DateTime<Utc>) -> Result<u64> {
+async fn purge_transcribe(pool: &PgPool, cutoff: DateTime<Utc>, dry_run: bool) -> Result<u64> {
+    let rows = sqlx::query(
+        "SELECT id FROM transcribe_jobs WHERE files_purged_at IS NULL AND updated_at < $1",
+    )
+    .bind(cutoff)
+    .fetch_all(pool)
+    .await
+    .context("failed to fetch transcribe jobs pending cleanup")?;
+
+    let mut purged = 0_u64;
+
+    for row in rows {
+        let job_id: Uuid = row.try_get("id")?;
+        let job_id_str = job_id.to_string();
+
+        if dry_run {
+            log_dry_run("purge", MODULE_TRANSCRIBE, TRANSCRIBE_STORAGE, &job_id_str);
+            purged += 1;
+            continue;
+        }
+
+        if !remove_job_directory(TRANSCRIBE_STORAGE, &job_id_str).await {
+            continue;
+        }
+
+        sqlx::query(
+            "UPDATE transcribe_jobs
+             SET files_purged_at = NOW(), updated_at = NOW()
+             WHERE id = $1",
+        )
+        .bind(job_id)
+        .execute(pool)
+        .await
+        .context("failed to update transcribe job after cleanup")?;
+
+        purged += 1;
+    }
+
+    Ok(purged)
+}
+
+async fn purge_docx_to_pdf(pool: &PgPool, cutoff: DateTime<Utc>, dry_run: bool) -> Result<u64> {
+    let rows = sqlx::query(
+        "SELECT id FROM docx2pdf_jobs WHERE files_purged_at IS NULL AND updated_at < $1",
+    )
+    .bind(cutoff)
+    .fetch_all(pool)
+    .await
+    .context("failed to fetch DOCX to PDF jobs pending cleanup")?;
+
+    let mut purged = 0_u64;
+
+    for row in rows {
+        let job_id: Uuid = row.try_get("id")?;
+        let job_id_str = job_id.to_string();
+
+        if dry_run {
+            log_dry_run(
+                "purge",
+                MODULE_DOCX_TO_PDF,
+                DOCX_TO_PDF_STORAGE,
+                &job_id_str,
+            );
+            purged += 1;
+            continue;
+        }
+
+        if !remove_job_directory(DOCX_TO_PDF_STORAGE, &job_id_str).await {
+            continue;
+        }
+
+        sqlx::query(
+            "UPDATE docx2pdf_jobs
+             SET files_purged_at = NOW(), updated_at = NOW()
+             WHERE id = $1",
+        )
+        .bind(job_id)
+        .execute(pool)
+        .await
+        .context("failed to update DOCX to PDF job after cleanup")?;
+
+        purged += 1;
+    }
+
+    Ok(purged)
+}
+
+async fn purge_info_extract(pool: &PgPool, cutoff: DateTime<Utc>, dry_run: bool) -> Result<u64> {
     let rows = sqlx::query(
         "SELECT id FROM info_extract_jobs WHERE files_purged_at IS NULL AND updated_at < $1",
     )
@@ -190,6 +583,17 @@ async fn purge_info_extract(pool: &PgPool, cutoff: DateTime<Utc>) -> Result<u64>
         let job_id: Uuid = row.try_get("id")?;
         let job_id_str = job_id.to_string();
 
+        if dry_run {
+            log_dry_run(
+                "purge",
+                MODULE_INFO_EXTRACT,
+                INFO_EXTRACT_STORAGE,
+                &job_id_str,
+            );
+            purged += 1;
+            continue;
+        }
+
         if !remove_job_directory(INFO_EXTRACT_STORAGE, &job_id_str).await {
             continue;
         }
@@ -210,7 +614,7 @@ async fn purge_info_extract(pool: &PgPool, cutoff: DateTime<Utc>) -> Result<u64>
     Ok(purged)
 }
 
-async fn purge_reviewer(pool: &PgPool, cutoff: DateTime<Utc>) -> Result<u64> {
+async fn purge_reviewer(pool: &PgPool, cutoff: DateTime<Utc>, dry_run: bool) -> Result<u64> {
     let rows = sqlx::query(
         "SELECT job_id FROM reviewer_jobs WHERE files_purged_at IS NULL AND updated_at < $1",
     )
@@ -225,6 +629,12 @@ async fn purge_reviewer(pool: &PgPool, cutoff: DateTime<Utc>) -> Result<u64> {
         let job_id: i32 = row.try_get("job_id")?;
         let job_id_str = job_id.to_string();
 
+        if dry_run {
+            log_dry_run("purge", MODULE_REVIEWER, REVIEWER_STORAGE, &job_id_str);
+            purged += 1;
+            continue;
+        }
+
         if !remove_job_directory(REVIEWER_STORAGE, &job_id_str).await {
             continue;
         }
@@ -255,14 +665,302 @@ async fn purge_reviewer(pool: &PgPool, cutoff: DateTime<Utc>) -> Result<u64> {
     Ok(purged)
 }
 
+/// Logs a job a dry run would `action` (archive or purge) with the paths it would touch.
+fn log_dry_run(action: &str, module: &str, root: &str, job_key: &str) {
+    let job_dir = PathBuf::from(root).join(job_key);
+    info!(
+        action,
+        module,
+        job_key,
+        directory = %job_dir.display(),
+        archive = %job_archive_path(&job_dir).display(),
+        "maintenance dry run: would {action} job files"
+    );
+}
+
 async fn remove_job_directory(root: &str, name: &str) -> bool {
     let path = PathBuf::from(root).join(name);
-    match tokio::fs::remove_dir_all(&path).await {
+    let removed = match tokio::fs::remove_dir_all(&path).await {
         Ok(_) => true,
         Err(err) if err.kind() == ErrorKind::NotFound => true,
         Err(err) => {
             warn!(?err, path = %path.display(), "failed to remove job directory");
             false
         }
+    };
+
+    let archive = job_archive_path(&path);
+    match tokio::fs::remove_file(&archive).await {
+        Ok(_) => removed,
+        Err(err) if err.kind() == ErrorKind::NotFound => removed,
+        Err(err) => {
+            warn!(?err, path = %archive.display(), "failed to remove job archive");
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{StubProvider, StubReply, create_user, test_state};
+
+    #[test]
+    fn retention_days_must_be_in_range() {
+        assert_eq!(parse_retention_days(Some(" 30 ")).unwrap(), 30);
+        assert_eq!(
+            parse_retention_days(Some("365")).unwrap(),
+            MAX_RETENTION_DAYS
+        );
+        assert!(parse_retention_days(None).is_err());
+        assert!(parse_retention_days(Some("")).is_err());
+        assert!(parse_retention_days(Some("0")).is_err());
+        assert!(parse_retention_days(Some("366")).is_err());
+        assert!(parse_retention_days(Some("1.5")).is_err());
+
+        assert_eq!(parse_archive_after_days(Some(" "), 30).unwrap(), None);
+        assert_eq!(parse_archive_after_days(Some("7"), 30).unwrap(), Some(7));
+        assert!(parse_archive_after_days(Some("30"), 30).is_err());
+        assert!(parse_archive_after_days(Some("0"), 30).is_err());
+    }
+
+    #[sqlx::test]
+    #[ignore = "needs DATABASE_URL (disposable Postgres)"]
+    async fn files_are_purged_per_module_window(pool: PgPool) {
+        let user_id = create_user(&pool).await;
+        let seed = |table: &'static str, days_ago: i32| {
+            let pool = pool.clone();
+            async move {
+                let job_id = Uuid::new_v4();
+                let sql = match table {
+                    "summary_jobs" => {
+                        "INSERT INTO summary_jobs (id, user_id, status, document_type, updated_at)
+                         VALUES ($1, $2, 'completed', 'research', NOW() - make_interval(days => $3))"
+                    }
+                    _ => {
+                        "INSERT INTO grader_jobs (id, user_id, status, updated_at)
+                         VALUES ($1, $2, 'completed', NOW() - make_interval(days => $3))"
+                    }
+                };
+                sqlx::query(sql)
+                    .bind(job_id)
+                    .bind(user_id)
+                    .bind(days_ago)
+                    .execute(&pool)
+                    .await
+                    .unwrap();
+                job_id
+            }
+        };
+        let purged_at = |table: &'static str, job_id: Uuid| {
+            let pool = pool.clone();
+            async move {
+                sqlx::query_scalar::<_, Option<DateTime<Utc>>>(&format!(
+                    "SELECT files_purged_at FROM {table} WHERE id = $1"
+                ))
+                .bind(job_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap()
+            }
+        };
+
+        let summary_recent = seed("summary_jobs", 0).await;
+        let summary_old = seed("summary_jobs", 2).await;
+        let grader_old = seed("grader_jobs", 2).await;
+        let grader_older = seed("grader_jobs", 8).await;
+
+        let mut policies = HashMap::new();
+        policies.insert(
+            MODULE_GRADER.to_string(),
+            RetentionPolicy {
+                retention_days: 7,
+                archive_after_days: None,
+            },
+        );
+        save_retention_policies(&pool, &policies).await.unwrap();
+        let policies = retention_policies(&pool).await.unwrap();
+        assert_eq!(policies[MODULE_GRADER].retention_days, 7);
+        assert_eq!(policies[MODULE_SUMMARIZER], RetentionPolicy::default());
+
+        let purged = purge_expired_files(&pool, &policies, Utc::now(), false)
+            .await
+            .unwrap();
+        assert_eq!(purged, 2);
+        assert!(purged_at("summary_jobs", summary_recent).await.is_none());
+        assert!(purged_at("summary_jobs", summary_old).await.is_some());
+        assert!(purged_at("grader_jobs", grader_old).await.is_none());
+        assert!(purged_at("grader_jobs", grader_older).await.is_some());
+    }
+
+    #[sqlx::test]
+    #[ignore = "needs DATABASE_URL (disposable Postgres)"]
+    async fn idle_jobs_are_archived_then_purged(pool: PgPool) {
+        let user_id = create_user(&pool).await;
+        let job_id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO summary_jobs (id, user_id, status, document_type, updated_at)
+             VALUES ($1, $2, 'completed', 'research', NOW() - INTERVAL '10 days')",
+        )
+        .bind(job_id)
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+        let job_dir = PathBuf::from(SUMMARIZER_STORAGE).join(job_id.to_string());
+        tokio::fs::create_dir_all(&job_dir).await.unwrap();
+        let summary = job_dir.join("combined_summary.txt");
+        tokio::fs::write(&summary, "汇总摘要").await.unwrap();
+
+        let mut policies = HashMap::new();
+        policies.insert(
+            MODULE_SUMMARIZER.to_string(),
+            RetentionPolicy {
+                retention_days: 30,
+                archive_after_days: Some(3),
+            },
+        );
+        assert_eq!(
+            purge_expired_files(&pool, &policies, Utc::now(), false)
+                .await
+                .unwrap(),
+            0
+        );
+        assert_eq!(
+            archive_idle_jobs(&pool, &policies, Utc::now(), false)
+                .await
+                .unwrap(),
+            1
+        );
+        assert!(!job_dir.exists());
+        assert!(job_archive_path(&job_dir).is_file());
+        assert_eq!(
+            crate::web::storage::read_stored_file(&summary)
+                .await
+                .unwrap(),
+            "汇总摘要".as_bytes()
+        );
+        let (archived_at, updated_at): (Option<DateTime<Utc>>, DateTime<Utc>) =
+            sqlx::query_as("SELECT files_archived_at, updated_at FROM summary_jobs WHERE id = $1")
+                .bind(job_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert!(archived_at.is_some());
+        assert!(updated_at < Utc::now() - Duration::days(9));
+        assert_eq!(
+            archive_idle_jobs(&pool, &policies, Utc::now(), false)
+                .await
+                .unwrap(),
+            0
+        );
+
+        policies.insert(
+            MODULE_SUMMARIZER.to_string(),
+            RetentionPolicy {
+                retention_days: 5,
+                archive_after_days: Some(3),
+            },
+        );
+        assert_eq!(
+            purge_expired_files(&pool, &policies, Utc::now(), false)
+                .await
+                .unwrap(),
+            1
+        );
+        assert!(!job_archive_path(&job_dir).exists());
+    }
+
+    #[sqlx::test]
+    #[ignore = "needs DATABASE_URL (disposable Postgres)"]
+    async fn dry_run_leaves_files_and_rows_untouched(pool: PgPool) {
+        let user_id = create_user(&pool).await;
+        let job_id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO summary_jobs (id, user_id, status, document_type, updated_at)
+             VALUES ($1, $2, 'completed', 'research', NOW() - INTERVAL '10 days')",
+        )
+        .bind(job_id)
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+        let job_dir = PathBuf::from(SUMMARIZER_STORAGE).join(job_id.to_string());
+        tokio::fs::create_dir_all(&job_dir).await.unwrap();
+
+        let mut policies = HashMap::new();
+        policies.insert(
+            MODULE_SUMMARIZER.to_string(),
+            RetentionPolicy {
+                retention_days: 30,
+                archive_after_days: Some(3),
+            },
+        );
+        assert_eq!(
+            archive_idle_jobs(&pool, &policies, Utc::now(), true)
+                .await
+                .unwrap(),
+            1
+        );
+        assert!(job_dir.is_dir());
+        assert!(!job_archive_path(&job_dir).exists());
+
+        policies.insert(MODULE_SUMMARIZER.to_string(), RetentionPolicy::default());
+        assert_eq!(
+            purge_expired_files(&pool, &policies, Utc::now(), true)
+                .await
+                .unwrap(),
+            1
+        );
+        assert!(job_dir.is_dir());
+        let (archived_at, purged_at): (Option<DateTime<Utc>>, Option<DateTime<Utc>>) =
+            sqlx::query_as(
+                "SELECT files_archived_at, files_purged_at FROM summary_jobs WHERE id = $1",
+            )
+            .bind(job_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert!(archived_at.is_none());
+        assert!(purged_at.is_none());
+
+        tokio::fs::remove_dir_all(&job_dir).await.unwrap();
+    }
+
+    #[sqlx::test]
+    #[ignore = "needs DATABASE_URL (disposable Postgres)"]
+    async fn dry_run_cycle_keeps_history_and_call_metrics(pool: PgPool) {
+        let provider = StubProvider::start(|_| StubReply::text("unused")).await;
+        let state = test_state(pool.clone(), &provider).await;
+        let user_id = create_user(&pool).await;
+        sqlx::query(
+            "INSERT INTO user_job_history (user_id, module, job_key, created_at)
+             VALUES ($1, 'summarizer', 'old-job', NOW() - INTERVAL '400 days')",
+        )
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO llm_call_metrics (provider, model, duration_ms, success, occurred_at)
+             VALUES ('openrouter', 'model', 10, TRUE, NOW() - INTERVAL '400 days')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let counts = || async {
+            sqlx::query_as::<_, (i64, i64)>(
+                "SELECT (SELECT COUNT(*) FROM user_job_history), (SELECT COUNT(*) FROM llm_call_metrics)",
+            )
+            .fetch_one(&pool)
+            .await
+            .unwrap()
+        };
+
+        run_cleanup_cycle(&state, true).await.unwrap();
+        assert_eq!(counts().await, (1, 1));
+
+        run_cleanup_cycle(&state, false).await.unwrap();
+        assert_eq!(counts().await, (0, 0));
     }
 }
@@ -4,7 +4,10 @@ use serde_json::{Value, json};
 use uuid::Uuid;
 
 use crate::{
-    GlossaryTermRow, JournalReferenceRow, JournalTopicRow, JournalTopicScoreRow, escape_html,
+    GlossaryTermRow, JournalReferenceRow, JournalTopicRow, JournalTopicScoreRow,
+    config::{MAX_CONCURRENT_DOCUMENTS_LIMIT, ModelRoute, format_model_routes},
+    escape_html,
+    web::GLOSSARY_SCOPES,
 };
 
 pub const MODULE_ADMIN_SHARED_STYLES: &str = r#"
@@ -174,12 +177,48 @@ pub const MODULE_ADMIN_SHARED_STYLES: &str = r#"
         }
 "#;
 
+/// Textarea for size-based model routes, placed under the default model input it overrides.
+pub fn render_model_routes_field(name: &str, routes: &[ModelRoute]) -> String {
+    format!(
+        r#"<label for="{name}">按输入长度路由（可选）</label>
+                <textarea id="{name}" name="{name}" placeholder="8000=openrouter/openai/gpt-4o-mini">{routes}</textarea>
+                <p class="section-note">每行一条“估算 token 上限=模型”：输入不超过上限时改用该模型（多条匹配时取上限最小者），超出所有上限则使用默认模型。留空即始终使用默认模型。</p>"#,
+        name = name,
+        routes = escape_html(&format_model_routes(routes)),
+    )
+}
+
+/// Number input for how many documents of one job are processed in parallel.
+pub fn render_concurrency_field(current: usize) -> String {
+    format!(
+        r#"<label for="max-concurrent-documents">并行处理文档数</label>
+                <input id="max-concurrent-documents" name="max_concurrent_documents" type="number" min="1" max="{max}" value="{current}" required>
+                <p class="section-note">同一任务内同时调用模型的文档数（1-{max}）。额度充足的账号可调高以加快批量任务，遇到限流时应调低。</p>"#,
+        max = MAX_CONCURRENT_DOCUMENTS_LIMIT,
+        current = current,
+    )
+}
+
 pub fn render_glossary_section(terms: &[GlossaryTermRow], redirect: &str) -> String {
     let mut rows = String::new();
     let mut select_options = String::new();
+    let scope_label = |scope: Option<&str>| {
+        GLOSSARY_SCOPES
+            .iter()
+            .find(|(key, _)| Some(*key) == scope)
+            .map(|(_, label)| *label)
+            .unwrap_or("全部模块")
+    };
+    let scope_options = std::iter::once(r#"<option value="">全部模块</option>"#.to_string())
+        .chain(
+            GLOSSARY_SCOPES
+                .iter()
+                .map(|(key, label)| format!(r#"<option value="{key}">仅{label}</option>"#)),
+        )
+        .collect::<String>();
 
     if terms.is_empty() {
-        rows.push_str(r#"<tr><td colspan="4">尚未添加术语。</td></tr>"#);
+        rows.push_str(r#"<tr><td colspan="5">尚未添加术语。</td></tr>"#);
     } else {
         for term in terms {
             rows.push_str(&format!(
@@ -187,6 +226,7 @@ pub fn render_glossary_section(terms: &[GlossaryTermRow], redirect: &str) -> Str
     <td>{source}</td>
     <td>{target}</td>
     <td>{notes}</td>
+    <td>{scope}</td>
     <td>
         <form method="post" action="/dashboard/glossary/delete" onsubmit="return confirm('确认删除该术语吗？');">
             <input type="hidden" name="id" value="{id}">
@@ -203,14 +243,22 @@ pub fn render_glossary_section(terms: &[GlossaryTermRow], redirect: &str) -> Str
                     .map(|n| escape_html(n))
                     .filter(|s| !s.is_empty())
                     .unwrap_or_else(|| "—".to_string()),
+                scope = scope_label(term.scope.as_deref()),
                 id = term.id,
                 redirect = redirect,
             ));
 
+            let label = match term.scope.as_deref() {
+                Some(_) => format!(
+                    "{}（{}）",
+                    escape_html(&term.source_term),
+                    scope_label(term.scope.as_deref())
+                ),
+                None => escape_html(&term.source_term),
+            };
             select_options.push_str(&format!(
                 r#"<option value="{id}">{label}</option>"#,
                 id = term.id,
-                label = escape_html(&term.source_term)
             ));
         }
     }
@@ -220,11 +268,11 @@ pub fn render_glossary_section(terms: &[GlossaryTermRow], redirect: &str) -> Str
     format!(
         r##"<section class="admin">
     <h2>术语表管理</h2>
-    <p class="section-note">该术语表同时用于摘要与 DOCX 翻译模块。</p>
+    <p class="section-note">该术语表同时用于摘要与 DOCX 翻译模块；限定模块的术语只用于该模块，并覆盖同名的通用术语。</p>
     <div class="stack">
         <table class="glossary">
             <thead>
-                <tr><th>英文</th><th>中文</th><th>备注</th><th>操作</th></tr>
+                <tr><th>英文</th><th>中文</th><th>备注</th><th>适用范围</th><th>操作</th></tr>
             </thead>
             <tbody>
                 {rows}
@@ -245,6 +293,10 @@ pub fn render_glossary_section(terms: &[GlossaryTermRow], redirect: &str) -> Str
                     <label for="glossary-notes">备注（可选）</label>
                     <input id="glossary-notes" name="notes" placeholder="填写上下文或使用说明">
                 </div>
+                <div class="field">
+                    <label for="glossary-scope">适用范围</label>
+                    <select id="glossary-scope" name="scope">{scope_options}</select>
+                </div>
                 <button type="submit">保存术语</button>
             </form>
             <form method="post" action="/dashboard/glossary/update">
@@ -268,13 +320,29 @@ pub fn render_glossary_section(terms: &[GlossaryTermRow], redirect: &str) -> Str
                     <label for="glossary-update-notes">备注（可选）</label>
                     <input id="glossary-update-notes" name="notes" placeholder="填写上下文或使用说明"{disabled_attr}>
                 </div>
+                <div class="field">
+                    <label for="glossary-update-scope">适用范围</label>
+                    <select id="glossary-update-scope" name="scope"{disabled_attr}>{scope_options}</select>
+                </div>
                 <button type="submit"{disabled_attr}>保存修改</button>
             </form>
+            <form method="post" action="/dashboard/glossary/import" enctype="multipart/form-data">
+                <h3>批量导入 / 导出</h3>
+                <input type="hidden" name="redirect" value="{redirect}">
+                <div class="field">
+                    <label for="glossary-import-file">CSV 文件（source_term,target_term,notes,scope）</label>
+                    <input id="glossary-import-file" type="file" name="file" accept=".csv,text/csv" required>
+                </div>
+                <p class="section-note">按适用范围和英文术语（不区分大小写）新增或覆盖已有术语；scope 留空为全部模块，或填 summarizer / translatedocx。</p>
+                <button type="submit">导入 CSV</button>
+                <a href="/dashboard/glossary/export">导出当前术语表（CSV）</a>
+            </form>
         </div>
     </div>
 </section>"##,
         rows = rows,
         select_options = select_options,
+        scope_options = scope_options,
         disabled_attr = disabled_attr,
         redirect = redirect,
     )
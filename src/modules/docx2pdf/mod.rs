@@ -0,0 +1,726 @@
+use std::{
+    borrow::Cow,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use axum::{
+    Json, Router,
+    extract::{Multipart, Path as AxumPath, State},
+    http::StatusCode,
+    response::{Html, Redirect, Response},
+    routing::{get, post},
+};
+use axum_extra::extract::cookie::CookieJar;
+use chrono::{DateTime, Utc};
+use sanitize_filename::sanitize;
+use serde::Serialize;
+use sqlx::PgPool;
+use tokio::fs as tokio_fs;
+use tracing::error;
+use uuid::Uuid;
+
+use crate::web::download_limit::hold_permit;
+use crate::web::history_ui;
+use crate::web::storage::JobAccess;
+use crate::web::{
+    FileFieldConfig, FileNaming, ToolPageLayout, ToolPageUser, UPLOAD_WIDGET_SCRIPT,
+    UPLOAD_WIDGET_STYLES, UploadWidgetConfig, ensure_storage_root, process_upload_form,
+    render_upload_widget,
+};
+use crate::{
+    AppState, escape_html, history, render_footer,
+    usage::{self, MODULE_DOCX_TO_PDF},
+    utils::docx_to_pdf::convert_docx_to_pdf,
+    web::{
+        AccessMessages, ApiMessage, JobStatus, JobSubmission, STATUS_CLIENT_SCRIPT,
+        auth::{self, JsonAuthError},
+        ensure_job_owner, json_error, quota_error, require_path, stream_file, verify_job_access,
+    },
+};
+
+const STORAGE_ROOT: &str = "storage/docx2pdf";
+const STATUS_PENDING: &str = "pending";
+const STATUS_PROCESSING: &str = "processing";
+const STATUS_COMPLETED: &str = "completed";
+const STATUS_FAILED: &str = "failed";
+const CONVERSION_FAILED_DETAIL: &str = "文档转换失败，请确认文件是有效的 DOCX 后重试。";
+/// Status detail when the worker stops on an unexpected error (storage or database).
+const JOB_ABORTED_DETAIL: &str = "转换任务意外中断，请重新提交。";
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/tools/docx2pdf", get(docx2pdf_page))
+        .route("/tools/docx2pdf/jobs", post(create_job))
+        .route("/api/docx2pdf/jobs/:id", get(job_status))
+        .route("/api/docx2pdf/jobs/:id/download/pdf", get(download_pdf))
+}
+
+#[derive(sqlx::FromRow)]
+struct JobProcessingRecord {
+    user_id: Uuid,
+    status: String,
+    source_path: String,
+}
+
+#[derive(sqlx::FromRow)]
+struct JobStatusRow {
+    user_id: Uuid,
+    status: String,
+    status_detail: Option<String>,
+    error_message: Option<String>,
+    original_filename: String,
+    pdf_path: Option<String>,
+    files_purged_at: Option<DateTime<Utc>>,
+}
+
+#[derive(sqlx::FromRow)]
+struct PdfDownloadRecord {
+    user_id: Uuid,
+    files_purged_at: Option<DateTime<Utc>>,
+    original_filename: String,
+    pdf_path: Option<String>,
+}
+
+impl JobAccess for PdfDownloadRecord {
+    fn user_id(&self) -> Uuid {
+        self.user_id
+    }
+
+    fn files_purged_at(&self) -> Option<DateTime<Utc>> {
+        self.files_purged_at
+    }
+}
+
+#[derive(Serialize)]
+struct JobStatusResponse {
+    job_id: Uuid,
+    status: String,
+    poll_interval_ms: u64,
+    status_detail: Option<String>,
+    error_message: Option<String>,
+    original_filename: String,
+    /// Present once the PDF exists and has not been purged.
+    pdf_download_url: Option<String>,
+}
+
+pub async fn docx2pdf_page(
+    State(state): State<AppState>,
+    jar: CookieJar,
+) -> Result<Html<String>, Redirect> {
+    let user = auth::require_user_redirect(&state, &jar).await?;
+    let username = escape_html(&user.username);
+    let note_html = format!(
+        "当前登录：<strong>{username}</strong>。上传一个 DOCX 文档，系统会将其转换为 PDF。",
+        username = username,
+    );
+    let page_user = ToolPageUser {
+        note_html: Cow::Owned(note_html),
+        admin_link: None,
+    };
+    let html = state
+        .tool_page_cache()
+        .render(MODULE_DOCX_TO_PDF, &page_user, docx2pdf_page_layout);
+
+    Ok(Html(html))
+}
+
+/// Everything on the conversion page except the per-user header; cached by `ToolPageCache`.
+fn docx2pdf_page_layout() -> ToolPageLayout<'static> {
+    let upload_widget = render_upload_widget(
+        &UploadWidgetConfig::new("docx2pdf-upload", "docx2pdf-file", "file", "DOCX 文件")
+            .with_description("上传需要转换的 Word 文档。")
+            .with_note("每个任务转换一个 DOCX 文件。")
+            .with_accept(".docx"),
+    );
+    let history_panel = history_ui::render_history_panel(MODULE_DOCX_TO_PDF);
+    let new_tab_html = format!(
+        r#"                <section class="panel">
+                    <h2>上传文档</h2>
+                    <form id="docx2pdf-form">
+                        {upload_widget}
+                        <button type="submit">开始转换</button>
+                    </form>
+                    <div id="status-box" class="status-box">等待上传。</div>
+                    <div id="pdf-download" class="downloads"></div>
+                </section>
+"#,
+        upload_widget = upload_widget,
+    );
+
+    let docx2pdf_script = r#"const form = document.getElementById('docx2pdf-form');
+const fileInput = document.getElementById('docx2pdf-file');
+const statusBox = document.getElementById('status-box');
+const pdfDownload = document.getElementById('pdf-download');
+
+let pollTimer = null;
+let activeStatusUrl = null;
+
+const stopPolling = () => {
+    clearTimeout(pollTimer);
+    pollTimer = null;
+    activeStatusUrl = null;
+};
+
+const updateStatus = (message) => {
+    statusBox.textContent = message;
+};
+
+const handleStatusPayload = (payload) => {
+    updateStatus(payload.status_detail || `当前状态：${window.translateJobStatus(payload.status)}`);
+
+    if (payload.status === 'completed') {
+        pdfDownload.innerHTML = '';
+        if (payload.pdf_download_url) {
+            const link = document.createElement('a');
+            link.href = payload.pdf_download_url;
+            link.textContent = '下载 PDF';
+            pdfDownload.appendChild(link);
+        }
+        stopPolling();
+    } else if (payload.status === 'failed') {
+        updateStatus(payload.error_message || '转换失败，请稍后重试。');
+        stopPolling();
+    }
+};
+
+const pollJob = (url) => {
+    stopPolling();
+    activeStatusUrl = url;
+    const poll = async () => {
+        try {
+            const res = await fetch(url, { headers: { 'Accept': 'application/json' } });
+            if (!res.ok) {
+                throw new Error('状态查询失败');
+            }
+            const data = await res.json();
+            handleStatusPayload(data);
+            const delay = window.nextPollDelay(data, 2000);
+            if (url === activeStatusUrl && delay) {
+                pollTimer = setTimeout(poll, delay);
+            }
+        } catch (err) {
+            stopPolling();
+            updateStatus('轮询失败：' + err.message);
+        }
+    };
+    pollTimer = setTimeout(poll, 2000);
+};
+
+if (fileInput) {
+    fileInput.addEventListener('change', () => {
+        if (fileInput.files.length === 0) {
+            updateStatus('等待上传。');
+        } else {
+            updateStatus(`已选择文件：${fileInput.files[0].name}`);
+        }
+    });
+}
+
+form.addEventListener('submit', async (event) => {
+    event.preventDefault();
+    if (!fileInput.files || fileInput.files.length === 0) {
+        updateStatus('请先选择文件。');
+        return;
+    }
+    pdfDownload.innerHTML = '';
+    updateStatus('正在上传文档...');
+    const formData = new FormData(form);
+
+    try {
+        const res = await fetch('/tools/docx2pdf/jobs', { method: 'POST', body: formData });
+        if (!res.ok) {
+            const errorBody = await res.json().catch(() => ({ message: '提交失败' }));
+            updateStatus(errorBody.message || '提交失败');
+            return;
+        }
+        const data = await res.json();
+        updateStatus('任务已创建，正在转换...');
+        fileInput.value = '';
+        if (data.status_url) {
+            pollJob(data.status_url);
+        }
+    } catch (err) {
+        updateStatus('提交失败：' + err.message);
+    }
+});
+"#;
+
+    ToolPageLayout {
+        meta_title: "DOCX 转 PDF | 张圆教授课题组 AI 工具箱",
+        page_heading: "DOCX 转 PDF",
+        tab_group: "docx2pdf",
+        new_tab_label: "新任务",
+        new_tab_html: Cow::Owned(new_tab_html),
+        history_tab_label: "历史记录",
+        history_panel_html: Cow::Owned(history_panel),
+        footer_html: Cow::Owned(render_footer()),
+        extra_style_blocks: vec![
+            Cow::Borrowed(history_ui::HISTORY_STYLES),
+            Cow::Borrowed(UPLOAD_WIDGET_STYLES),
+        ],
+        body_scripts: vec![
+            Cow::Borrowed(STATUS_CLIENT_SCRIPT),
+            Cow::Borrowed(UPLOAD_WIDGET_SCRIPT),
+            Cow::Owned(format!(
+                "<script>
+{}
+</script>",
+                docx2pdf_script
+            )),
+            Cow::Owned(format!(
+                "<script>
+{}
+</script>",
+                history_ui::HISTORY_SCRIPT
+            )),
+        ],
+    }
+}
+
+async fn create_job(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    multipart: Multipart,
+) -> Result<Json<JobSubmission>, (StatusCode, Json<ApiMessage>)> {
+    let user = auth::current_user_or_json_error(&state, &jar)
+        .await
+        .map_err(|JsonAuthError { status, message }| json_error(status, message))?;
+
+    let pool = state.pool();
+
+    ensure_storage_root(STORAGE_ROOT)
+        .await
+        .map_err(internal_error)?;
+
+    let job_id = Uuid::new_v4();
+    let job_dir = PathBuf::from(STORAGE_ROOT).join(job_id.to_string());
+
+    let file_config = FileFieldConfig::new(
+        "file",
+        &["docx"],
+        1,
+        FileNaming::Indexed {
+            prefix: "source_",
+            pad_width: 2,
+        },
+    );
+
+    let upload = match process_upload_form(multipart, &job_dir, &[file_config]).await {
+        Ok(outcome) => outcome,
+        Err(err) => {
+            let _ = tokio_fs::remove_dir_all(&job_dir).await;
+            return Err(json_error(
+                StatusCode::BAD_REQUEST,
+                err.message().to_string(),
+            ));
+        }
+    };
+
+    let Some(file) = upload.first_file_for("file").cloned() else {
+        let _ = tokio_fs::remove_dir_all(&job_dir).await;
+        return Err(json_error(
+            StatusCode::BAD_REQUEST,
+            "请上传一个 DOCX 文件。",
+        ));
+    };
+
+    if let Err(err) = usage::ensure_within_limits(&pool, user.id, MODULE_DOCX_TO_PDF, 1).await {
+        let _ = tokio_fs::remove_dir_all(&job_dir).await;
+        return Err(quota_error(StatusCode::FORBIDDEN, &err));
+    }
+
+    sqlx::query(
+        "INSERT INTO docx2pdf_jobs (id, user_id, status, original_filename, source_path) VALUES ($1, $2, $3, $4, $5)",
+    )
+    .bind(job_id)
+    .bind(user.id)
+    .bind(STATUS_PENDING)
+    .bind(&file.original_name)
+    .bind(file.stored_path.to_string_lossy().to_string())
+    .execute(&pool)
+    .await
+    .map_err(|err| internal_error(err.into()))?;
+
+    if let Err(err) =
+        history::record_job_start(&pool, MODULE_DOCX_TO_PDF, user.id, job_id.to_string()).await
+    {
+        error!(?err, %job_id, "failed to record docx2pdf job history");
+    }
+
+    spawn_job_worker(state.clone(), job_id);
+
+    Ok(Json(JobSubmission::new(
+        job_id,
+        format!("/api/docx2pdf/jobs/{}", job_id),
+    )))
+}
+
+async fn job_status(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    AxumPath(job_id): AxumPath<Uuid>,
+) -> Result<Json<JobStatusResponse>, (StatusCode, Json<ApiMessage>)> {
+    let user = auth::current_user_or_json_error(&state, &jar)
+        .await
+        .map_err(|JsonAuthError { status, message }| json_error(status, message))?;
+
+    let job = sqlx::query_as::<_, JobStatusRow>(
+        "SELECT user_id, status, status_detail, error_message, original_filename, pdf_path, files_purged_at FROM docx2pdf_jobs WHERE id = $1",
+    )
+    .bind(job_id)
+    .fetch_optional(state.pool_ref())
+    .await
+    .map_err(|err| internal_error(err.into()))?
+    .ok_or_else(|| json_error(StatusCode::NOT_FOUND, "未找到任务。"))?;
+
+    ensure_job_owner(job.user_id, &user, "未找到任务。", "无权查看该任务。")?;
+
+    let pdf_download_url = (job.pdf_path.is_some() && job.files_purged_at.is_none())
+        .then(|| format!("/api/docx2pdf/jobs/{job_id}/download/pdf"));
+
+    Ok(Json(JobStatusResponse {
+        job_id,
+        poll_interval_ms: JobStatus::from_str(&job.status).poll_interval_ms(),
+        status: job.status,
+        status_detail: job.status_detail,
+        error_message: job.error_message,
+        original_filename: job.original_filename,
+        pdf_download_url,
+    }))
+}
+
+async fn download_pdf(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    AxumPath(job_id): AxumPath<Uuid>,
+) -> Result<Response, (StatusCode, Json<ApiMessage>)> {
+    let user = auth::current_user_or_json_error(&state, &jar)
+        .await
+        .map_err(|JsonAuthError { status, message }| json_error(status, message))?;
+
+    let pool = state.pool();
+    let job = verify_job_access(
+        || {
+            sqlx::query_as::<_, PdfDownloadRecord>(
+                "SELECT user_id, files_purged_at, original_filename, pdf_path FROM docx2pdf_jobs WHERE id = $1",
+            )
+            .bind(job_id)
+            .fetch_optional(&pool)
+        },
+        &user,
+        AccessMessages {
+            not_found: "未找到任务。",
+            forbidden: "您无权访问该任务。",
+            purged: "该任务的下载文件已过期并被清除。",
+        },
+    )
+    .await?;
+
+    let path = require_path(job.pdf_path.clone(), "PDF 尚未生成。")?;
+    let permit = state.download_limiter().acquire(&user)?;
+
+    stream_file(
+        Path::new(&path),
+        &pdf_download_name(&job.original_filename),
+        "application/pdf",
+    )
+    .await
+    .map(|response| hold_permit(response, permit))
+}
+
+fn spawn_job_worker(state: AppState, job_id: Uuid) {
+    tokio::spawn(async move {
+        if let Err(err) = process_job(state.clone(), job_id).await {
+            error!(?err, %job_id, "docx2pdf job failed");
+            if let Err(update_err) = sqlx::query(
+                "UPDATE docx2pdf_jobs SET status = $2, status_detail = $3, error_message = $4, updated_at = NOW() WHERE id = $1",
+            )
+            .bind(job_id)
+            .bind(STATUS_FAILED)
+            .bind(JOB_ABORTED_DETAIL)
+            .bind(err.to_string())
+            .execute(state.pool_ref())
+            .await
+            {
+                error!(?update_err, %job_id, "failed to update job after error");
+            }
+        }
+    });
+}
+
+async fn process_job(state: AppState, job_id: Uuid) -> Result<()> {
+    let pool = state.pool();
+
+    let job = sqlx::query_as::<_, JobProcessingRecord>(
+        "SELECT user_id, status, source_path FROM docx2pdf_jobs WHERE id = $1",
+    )
+    .bind(job_id)
+    .fetch_one(&pool)
+    .await
+    .context("failed to load docx2pdf job")?;
+
+    if job.status != STATUS_PENDING {
+        return Ok(());
+    }
+
+    update_job_status(&pool, job_id, STATUS_PROCESSING, "正在转换文档...").await?;
+
+    let pdf_path = match convert_docx_to_pdf(Path::new(&job.source_path)).await {
+        Ok(path) => path,
+        Err(err) => {
+            error!(?err, %job_id, "DOCX to PDF conversion failed");
+            mark_job_failed(&pool, job_id, CONVERSION_FAILED_DETAIL).await?;
+            return Ok(());
+        }
+    };
+
+    if let Err(err) = usage::record_usage(&pool, job.user_id, MODULE_DOCX_TO_PDF, 0, 1).await {
+        error!(?err, "failed to record docx2pdf usage");
+    }
+
+    sqlx::query(
+        "UPDATE docx2pdf_jobs SET status = $2, status_detail = $3, pdf_path = $4, usage_delta = 1, updated_at = NOW() WHERE id = $1",
+    )
+    .bind(job_id)
+    .bind(STATUS_COMPLETED)
+    .bind("转换完成。")
+    .bind(pdf_path.to_string_lossy().to_string())
+    .execute(&pool)
+    .await
+    .context("failed to finalize docx2pdf job")?;
+
+    Ok(())
+}
+
+fn pdf_download_name(original_name: &str) -> String {
+    let stem = Path::new(original_name)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("document");
+    format!("{}.pdf", sanitize(stem))
+}
+
+async fn update_job_status(pool: &PgPool, job_id: Uuid, status: &str, detail: &str) -> Result<()> {
+    sqlx::query(
+        "UPDATE docx2pdf_jobs SET status = $2, status_detail = $3, updated_at = NOW() WHERE id = $1",
+    )
+    .bind(job_id)
+    .bind(status)
+    .bind(detail)
+    .execute(pool)
+    .await
+    .context("failed to update docx2pdf job status")?;
+    Ok(())
+}
+
+async fn mark_job_failed(pool: &PgPool, job_id: Uuid, message: &str) -> Result<()> {
+    sqlx::query(
+        "UPDATE docx2pdf_jobs SET status = $2, status_detail = $3, error_message = $3, updated_at = NOW() WHERE id = $1",
+    )
+    .bind(job_id)
+    .bind(STATUS_FAILED)
+    .bind(message)
+    .execute(pool)
+    .await
+    .context("failed to mark docx2pdf job as failed")?;
+    Ok(())
+}
+
+fn internal_error(err: anyhow::Error) -> (StatusCode, Json<ApiMessage>) {
+    error!(?err, "internal error in docx2pdf module");
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(ApiMessage::new("服务器内部错误。")),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::test_support::{
+        StubProvider, StubReply, create_user, multipart_form, recorded_usage, session_jar,
+        test_state,
+    };
+
+    #[test]
+    fn pdf_download_name_keeps_the_document_stem() {
+        assert_eq!(pdf_download_name("报告 终稿.docx"), "报告 终稿.pdf");
+        assert_eq!(pdf_download_name("../draft.DOCX"), "draft.pdf");
+        assert_eq!(pdf_download_name(""), "document.pdf");
+    }
+
+    async fn submit_docx(state: &AppState, pool: &PgPool, user_id: Uuid, bytes: &[u8]) -> Uuid {
+        let Json(submission) = create_job(
+            State(state.clone()),
+            session_jar(pool, user_id).await,
+            multipart_form(&[("file", "draft.docx", bytes)], &[]).await,
+        )
+        .await
+        .unwrap_or_else(|_| panic!("DOCX upload is accepted"));
+        submission.job_id
+    }
+
+    async fn wait_until_settled(pool: &PgPool, job_id: Uuid) -> String {
+        let mut status = String::new();
+        for _ in 0..600 {
+            status = sqlx::query_scalar("SELECT status FROM docx2pdf_jobs WHERE id = $1")
+                .bind(job_id)
+                .fetch_one(pool)
+                .await
+                .unwrap();
+            if status != STATUS_PENDING && status != STATUS_PROCESSING {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        status
+    }
+
+    #[sqlx::test]
+    #[ignore = "needs DATABASE_URL (disposable Postgres)"]
+    async fn open_downloads_count_against_the_per_user_limit(pool: PgPool) {
+        let provider = StubProvider::start(|_| StubReply::text("unused")).await;
+        let state = test_state(pool.clone(), &provider).await;
+        let user_id = create_user(&pool).await;
+
+        let job_id = Uuid::new_v4();
+        let job_dir = PathBuf::from(STORAGE_ROOT).join(job_id.to_string());
+        tokio_fs::create_dir_all(&job_dir).await.unwrap();
+        let pdf_path = job_dir.join("draft.pdf");
+        tokio_fs::write(&pdf_path, b"%PDF-1.4 test").await.unwrap();
+        sqlx::query(
+            "INSERT INTO docx2pdf_jobs (id, user_id, status, original_filename, source_path, pdf_path) VALUES ($1, $2, $3, 'draft.docx', '', $4)",
+        )
+        .bind(job_id)
+        .bind(user_id)
+        .bind(STATUS_COMPLETED)
+        .bind(pdf_path.to_string_lossy().to_string())
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let download = || async {
+            download_pdf(
+                State(state.clone()),
+                session_jar(&pool, user_id).await,
+                AxumPath(job_id),
+            )
+            .await
+        };
+
+        // Responses whose bodies have not been sent yet still hold their slots.
+        let mut open = Vec::new();
+        for _ in 0..crate::web::download_limit::DEFAULT_MAX_CONCURRENT_DOWNLOADS {
+            open.push(
+                download()
+                    .await
+                    .unwrap_or_else(|_| panic!("download within the limit")),
+            );
+        }
+        assert!(matches!(
+            download().await,
+            Err((StatusCode::TOO_MANY_REQUESTS, _))
+        ));
+
+        let body = axum::body::to_bytes(open.pop().unwrap().into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(body.as_ref(), b"%PDF-1.4 test");
+        assert!(download().await.is_ok());
+
+        let _ = tokio_fs::remove_dir_all(&job_dir).await;
+    }
+
+    #[sqlx::test]
+    #[ignore = "needs DATABASE_URL (disposable Postgres)"]
+    async fn invalid_docx_upload_fails_without_billing(pool: PgPool) {
+        let provider = StubProvider::start(|_| StubReply::text("unused")).await;
+        let state = test_state(pool.clone(), &provider).await;
+        let user_id = create_user(&pool).await;
+
+        let rejected = create_job(
+            State(state.clone()),
+            session_jar(&pool, user_id).await,
+            multipart_form(&[("file", "notes.pdf", b"%PDF")], &[]).await,
+        )
+        .await;
+        assert!(matches!(rejected, Err((StatusCode::BAD_REQUEST, _))));
+
+        let job_id = submit_docx(&state, &pool, user_id, b"not really a docx").await;
+        assert_eq!(wait_until_settled(&pool, job_id).await, STATUS_FAILED);
+
+        let Json(response) = job_status(
+            State(state.clone()),
+            session_jar(&pool, user_id).await,
+            AxumPath(job_id),
+        )
+        .await
+        .unwrap_or_else(|_| panic!("owner can read the job"));
+        assert_eq!(response.pdf_download_url, None);
+        assert_eq!(
+            response.error_message.as_deref(),
+            Some(CONVERSION_FAILED_DETAIL)
+        );
+        assert_eq!(
+            recorded_usage(&pool, user_id, MODULE_DOCX_TO_PDF).await,
+            (0, 0)
+        );
+        assert_eq!(provider.calls(), 0);
+
+        let _ =
+            tokio_fs::remove_dir_all(PathBuf::from(STORAGE_ROOT).join(job_id.to_string())).await;
+    }
+
+    #[sqlx::test]
+    #[ignore = "needs DATABASE_URL (disposable Postgres)"]
+    async fn valid_docx_is_converted_and_billed(pool: PgPool) {
+        // The conversion shells out to LibreOffice; without it there is nothing to verify.
+        let installed = std::process::Command::new("which")
+            .arg("libreoffice")
+            .output()
+            .is_ok_and(|output| output.status.success());
+        if !installed {
+            eprintln!("skipping: libreoffice is not installed");
+            return;
+        }
+
+        let provider = StubProvider::start(|_| StubReply::text("unused")).await;
+        let state = test_state(pool.clone(), &provider).await;
+        let user_id = create_user(&pool).await;
+
+        let mut docx = std::io::Cursor::new(Vec::new());
+        docx_rs::Docx::new()
+            .add_paragraph(
+                docx_rs::Paragraph::new().add_run(docx_rs::Run::new().add_text("转换测试段落")),
+            )
+            .build()
+            .pack(&mut docx)
+            .unwrap();
+
+        let job_id = submit_docx(&state, &pool, user_id, docx.get_ref()).await;
+        assert_eq!(wait_until_settled(&pool, job_id).await, STATUS_COMPLETED);
+
+        let Json(response) = job_status(
+            State(state.clone()),
+            session_jar(&pool, user_id).await,
+            AxumPath(job_id),
+        )
+        .await
+        .unwrap_or_else(|_| panic!("owner can read the job"));
+        assert_eq!(
+            response.pdf_download_url,
+            Some(format!("/api/docx2pdf/jobs/{job_id}/download/pdf"))
+        );
+        assert_eq!(
+            recorded_usage(&pool, user_id, MODULE_DOCX_TO_PDF).await,
+            (0, 1)
+        );
+        assert_eq!(provider.calls(), 0);
+
+        let _ =
+            tokio_fs::remove_dir_all(PathBuf::from(STORAGE_ROOT).join(job_id.to_string())).await;
+    }
+}
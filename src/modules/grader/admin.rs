@@ -8,7 +8,12 @@ use tracing::error;
 
 use crate::{
     AppState,
-    config::{GraderModels, GraderPrompts, update_grader_models, update_grader_prompts},
+    config::{
+        GraderModels, GraderPrompts, MAX_GRADER_DOCX_PENALTY, MAX_GRADING_ATTEMPTS_LIMIT,
+        MAX_GRADING_TRIM_FRACTION, parse_docx_penalty, parse_grading_weights, parse_model_routes,
+        parse_sampling_counts, parse_token_budget, parse_trim_fraction, update_grader_models,
+        update_grader_prompts,
+    },
     escape_html, fetch_journal_references, fetch_journal_topic_scores, fetch_journal_topics,
     render_footer,
     web::{
@@ -18,7 +23,8 @@ use crate::{
 };
 
 use super::super::admin_shared::{
-    MODULE_ADMIN_SHARED_STYLES, render_journal_section, render_topic_section,
+    MODULE_ADMIN_SHARED_STYLES, render_journal_section, render_model_routes_field,
+    render_topic_section,
 };
 
 #[derive(Deserialize)]
@@ -26,6 +32,24 @@ pub struct GraderModelForm {
     pub grading_model: String,
     pub keyword_model: String,
     #[serde(default)]
+    pub grading_routes: String,
+    #[serde(default)]
+    pub grading_token_budget: String,
+    #[serde(default)]
+    pub grading_trim_fraction: String,
+    #[serde(default)]
+    pub grading_weights: String,
+    #[serde(default)]
+    pub docx_penalty: String,
+    #[serde(default)]
+    pub max_attempts: String,
+    #[serde(default)]
+    pub target_successes: String,
+    #[serde(default)]
+    pub min_successes: String,
+    #[serde(default)]
+    pub attach_pdf: Option<String>,
+    #[serde(default)]
     pub redirect: Option<String>,
 }
 
@@ -139,6 +163,27 @@ pub async fn settings_page(
                 <input type="hidden" name="redirect" value="{redirect_base}">
                 <label for="grader-model">评分模型</label>
                 <input id="grader-model" name="grading_model" type="text" value="{grading_model}" required>
+                {grading_routes_field}
+                <label for="grading-token-budget">评分 Token 预算</label>
+                <input id="grading-token-budget" name="grading_token_budget" type="number" min="0" step="1" value="{grading_token_budget}">
+                <p class="section-note">单次评估累计消耗超过该值且已获得最少有效次数的结果时提前停止采样；0 表示不限制。</p>
+                <label for="grading-max-attempts">最大尝试次数</label>
+                <input id="grading-max-attempts" name="max_attempts" type="number" min="1" max="{max_attempts_limit}" step="1" value="{max_attempts}" required>
+                <label for="grading-target-successes">目标有效次数</label>
+                <input id="grading-target-successes" name="target_successes" type="number" min="1" max="{max_attempts_limit}" step="1" value="{target_successes}" required>
+                <label for="grading-min-successes">最少有效次数</label>
+                <input id="grading-min-successes" name="min_successes" type="number" min="1" max="{max_attempts_limit}" step="1" value="{min_successes}" required>
+                <p class="section-note">获得目标有效次数后停止采样；尝试次数用尽时，有效结果少于最少有效次数则任务失败。需满足 最少 ≤ 目标 ≤ 最大 ≤ {max_attempts_limit}。调低目标可降低成本，但评分波动会增大。</p>
+                <label for="grading-trim-fraction">评分截尾比例</label>
+                <input id="grading-trim-fraction" name="grading_trim_fraction" type="number" min="0" max="{max_trim_fraction}" step="0.01" value="{grading_trim_fraction}">
+                <p class="section-note">计算平均分前从有效结果两端各剔除的比例（0-{max_trim_fraction}）；默认 0.25 即四分位平均，0 表示不剔除。</p>
+                <label for="grading-weights">六档期刊权重</label>
+                <input id="grading-weights" name="grading_weights" type="text" value="{grading_weights}">
+                <p class="section-note">按第 1-6 档顺序填写 6 个非负数（逗号分隔，不能全为 0），用于计算单次评分的加权平均；留空恢复默认 4, 2, 1, 1, 1, 1。</p>
+                <label for="docx-penalty">DOCX 格式惩罚比例</label>
+                <input id="docx-penalty" name="docx_penalty" type="number" min="0" max="{max_docx_penalty}" step="0.01" value="{docx_penalty}">
+                <p class="section-note">以 DOCX 上传的稿件评分按该比例下调（0-{max_docx_penalty}，默认 0.02 即 2%），结果中会注明；0 表示 DOCX 与 PDF 评分完全一致。</p>
+                <label><input type="checkbox" name="attach_pdf" value="on"{attach_pdf_checked}> 评分时附带 PDF 原件（模型可看到图表与图注；需使用支持 PDF 输入的多模态模型，每次采样都会发送全文，Token 消耗显著增加）</label>
                 <label for="keyword-model">关键词模型</label>
                 <input id="keyword-model" name="keyword_model" type="text" value="{keyword_model}" required>
                 <button type="submit">保存模型</button>
@@ -165,6 +210,23 @@ pub async fn settings_page(
         message_block = message_block,
         redirect_base = redirect_base,
         grading_model = escape_html(&models.grading_model),
+        grading_routes_field = render_model_routes_field("grading_routes", &models.grading_routes),
+        grading_token_budget = models.grading_token_budget,
+        grading_trim_fraction = models.grading_trim_fraction,
+        max_trim_fraction = MAX_GRADING_TRIM_FRACTION,
+        grading_weights = models
+            .grading_weights
+            .iter()
+            .map(|weight| weight.to_string())
+            .collect::<Vec<_>>()
+            .join(", "),
+        docx_penalty = models.docx_penalty_fraction(),
+        max_docx_penalty = MAX_GRADER_DOCX_PENALTY,
+        attach_pdf_checked = if models.attach_pdf { " checked" } else { "" },
+        max_attempts = models.max_attempts,
+        target_successes = models.target_successes,
+        min_successes = models.min_successes,
+        max_attempts_limit = MAX_GRADING_ATTEMPTS_LIMIT,
         keyword_model = escape_html(&models.keyword_model),
         grading_prompt = escape_html(&prompts.grading_instructions),
         keyword_prompt = escape_html(&prompts.keyword_selection),
@@ -193,9 +255,58 @@ pub async fn save_models(
         )));
     }
 
+    let Ok(grading_routes) = parse_model_routes(&form.grading_routes) else {
+        return Ok(Redirect::to(&format!(
+            "{redirect_base}?error=model_routes_invalid"
+        )));
+    };
+
+    let Some(grading_token_budget) = parse_token_budget(&form.grading_token_budget) else {
+        return Ok(Redirect::to(&format!(
+            "{redirect_base}?error=token_budget_invalid"
+        )));
+    };
+
+    let Some(grading_trim_fraction) = parse_trim_fraction(&form.grading_trim_fraction) else {
+        return Ok(Redirect::to(&format!(
+            "{redirect_base}?error=trim_fraction_invalid"
+        )));
+    };
+
+    let Some(grading_weights) = parse_grading_weights(&form.grading_weights) else {
+        return Ok(Redirect::to(&format!(
+            "{redirect_base}?error=grading_weights_invalid"
+        )));
+    };
+
+    let Some(docx_penalty) = parse_docx_penalty(&form.docx_penalty) else {
+        return Ok(Redirect::to(&format!(
+            "{redirect_base}?error=docx_penalty_invalid"
+        )));
+    };
+
+    let Some((max_attempts, target_successes, min_successes)) = parse_sampling_counts(
+        &form.max_attempts,
+        &form.target_successes,
+        &form.min_successes,
+    ) else {
+        return Ok(Redirect::to(&format!(
+            "{redirect_base}?error=grader_sampling_invalid"
+        )));
+    };
+
     let payload = GraderModels {
         grading_model: grading.to_string(),
         keyword_model: keyword.to_string(),
+        grading_routes,
+        grading_token_budget,
+        grading_trim_fraction,
+        max_attempts,
+        target_successes,
+        min_successes,
+        grading_weights,
+        docx_penalty,
+        attach_pdf: form.attach_pdf.is_some(),
     };
 
     if let Err(err) = update_grader_models(state.pool_ref(), &payload).await {
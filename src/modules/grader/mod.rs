@@ -10,40 +10,50 @@ use std::{
 use anyhow::{Context, Result, anyhow};
 use axum::{
     Json, Router,
-    extract::{Multipart, Path as AxumPath, State},
+    extract::{Multipart, Path as AxumPath, Query, State},
     http::StatusCode,
-    response::{Html, Redirect},
+    response::{Html, Redirect, Response},
     routing::{get, post},
 };
 use axum_extra::extract::cookie::CookieJar;
 use pdf_extract::extract_text as extract_pdf_text;
 use quick_xml::{Reader as XmlReader, events::Event};
+use rust_xlsxwriter::Workbook;
 use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
 use sqlx::PgPool;
 use tokio::{fs as tokio_fs, time::sleep};
-use tracing::error;
+use tracing::{error, info, warn};
 use uuid::Uuid;
 use zip::ZipArchive;
 
 mod admin;
 
+use crate::web::download_limit::hold_permit;
 use crate::web::history_ui;
 use crate::web::{
     ensure_storage_root, FileFieldConfig, FileNaming, ToolAdminLink, ToolPageLayout,
-    UPLOAD_WIDGET_SCRIPT, UPLOAD_WIDGET_STYLES, UploadWidgetConfig, process_upload_form,
-    render_tool_page, render_upload_widget,
+    ToolPageUser, UPLOAD_WIDGET_SCRIPT, UPLOAD_WIDGET_STYLES, UploadWidgetConfig,
+    process_upload_form, render_upload_widget,
 };
 use crate::{
-    AppState, JournalReferenceRow, JournalTopicRow, JournalTopicScoreRow, escape_html,
-    fetch_journal_references, fetch_journal_topic_scores, fetch_journal_topics, history,
-    llm::{ChatMessage, LlmClient, LlmRequest, MessageRole},
-    render_footer,
+    AppState, JournalReferenceRow, JournalTopicRow, JournalTopicScoreRow,
+    config::{
+        DEFAULT_GRADING_TRIM_FRACTION, GraderModels, GraderSettings, MAX_GRADING_TRIM_FRACTION,
+        route_model,
+    },
+    escape_html, fetch_journal_references, fetch_journal_topic_scores, fetch_journal_topics,
+    history,
+    llm::{
+        AttachmentKind, ChatMessage, FileAttachment, LlmClient, LlmRequest, MessageRole,
+        RequestClass, estimate_tokens, is_refusal_error,
+    },
+    notifications, render_footer,
     usage::{self, MODULE_GRADER},
     web::{
-        ApiMessage, JobSubmission,
+        ApiMessage, JobStatus, JobSubmission, STATUS_CLIENT_SCRIPT, attachment_response,
         auth::{self, JsonAuthError},
-        json_error,
+        ensure_job_owner, json_error, quota_error,
     },
 };
 
@@ -52,14 +62,17 @@ const STATUS_PENDING: &str = "pending";
 const STATUS_PROCESSING: &str = "processing";
 const STATUS_COMPLETED: &str = "completed";
 const STATUS_FAILED: &str = "failed";
+const INSUFFICIENT_RESULTS_DETAIL: &str = "模型未返回足够的有效结果，请稍后重试。";
+const REFUSED_DETAIL: &str = "模型拒绝评估该稿件（可能触发了内容审核），请检查稿件内容后重试。";
+const EMPTY_DOCUMENT_DETAIL: &str = "未能读取到稿件内容，请检查文件。";
+/// Appended to a graded manuscript's detail when no journal can be recommended because the
+/// admin has not set up journal topics or references yet.
+const JOURNALS_UNCONFIGURED_NOTE: &str = "期刊数据库未配置，仅提供评分。";
+/// Manuscripts accepted per job; each one is graded independently.
+const MAX_MANUSCRIPTS_PER_JOB: usize = 10;
 
-const MAX_ATTEMPTS: usize = 30;
-const TARGET_SUCCESSES: usize = 12;
-const MIN_SUCCESSES: usize = 8;
 const RATE_LIMIT_DELAY: Duration = Duration::from_millis(500);
-const DOCX_PENALTY: f64 = 0.02;
 const MAX_RECOMMENDATIONS: usize = 12;
-const WEIGHTS: [f64; 6] = [4.0, 2.0, 1.0, 1.0, 1.0, 1.0];
 
 const MATCH_SCORE_RULES: &[(i16, Option<f64>)] = &[
     (6, Some(0.90)),
@@ -76,6 +89,11 @@ pub fn router() -> Router<AppState> {
         .route("/tools/grader", get(grader_page))
         .route("/tools/grader/jobs", post(create_job))
         .route("/api/grader/jobs/:id", get(job_status))
+        .route("/api/grader/jobs/:id/explain", get(explain_journal))
+        .route(
+            "/api/grader/jobs/:id/recommendations.xlsx",
+            get(download_recommendations),
+        )
         .route("/dashboard/modules/grader", get(admin::settings_page))
         .route("/dashboard/modules/grader/models", post(admin::save_models))
         .route(
@@ -93,6 +111,7 @@ struct JobProcessingRecord {
 #[derive(sqlx::FromRow, Clone)]
 struct DocumentProcessingRecord {
     id: Uuid,
+    original_filename: String,
     source_path: String,
     is_docx: bool,
 }
@@ -103,9 +122,19 @@ struct JobStatusRow {
     status: String,
     status_detail: Option<String>,
     error_message: Option<String>,
+}
+
+#[derive(sqlx::FromRow)]
+struct JobDocumentRow {
+    id: Uuid,
+    original_filename: String,
+    status: String,
+    status_detail: Option<String>,
     attempts_run: Option<i32>,
     valid_runs: Option<i32>,
     iqm_score: Option<f64>,
+    level_scores: Option<Vec<f64>>,
+    docx_penalty: Option<f64>,
     justification: Option<String>,
     decision_reason: Option<String>,
     keyword_main: Option<String>,
@@ -113,35 +142,35 @@ struct JobStatusRow {
     recommendations: Option<Value>,
 }
 
-#[derive(sqlx::FromRow)]
-struct JobDocumentStatusRow {
-    original_filename: String,
+#[derive(Serialize)]
+struct JobStatusResponse {
+    job_id: Uuid,
     status: String,
+    poll_interval_ms: u64,
     status_detail: Option<String>,
+    error_message: Option<String>,
+    documents: Vec<JobDocumentResult>,
 }
 
+/// Grading result of one manuscript, in upload order.
 #[derive(Serialize)]
-struct JobStatusResponse {
-    job_id: Uuid,
+struct JobDocumentResult {
+    document_id: Uuid,
+    original_filename: String,
     status: String,
     status_detail: Option<String>,
-    error_message: Option<String>,
     attempts_run: Option<i32>,
     valid_runs: Option<i32>,
     iqm_score: Option<f64>,
+    /// Mean score per level (Level 1–6) of the kept runs; empty until graded.
+    level_scores: Vec<f64>,
+    /// Fraction deducted because the manuscript was uploaded as DOCX, if any.
+    docx_penalty: Option<f64>,
     justification: Option<String>,
     decision_reason: Option<String>,
     keyword_main: Option<String>,
     keyword_peripherals: Vec<String>,
     recommendations: Vec<RecommendationDto>,
-    document: JobDocumentStatus,
-}
-
-#[derive(Serialize)]
-struct JobDocumentStatus {
-    original_filename: String,
-    status: String,
-    status_detail: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -163,6 +192,55 @@ struct RecommendationDto {
     low_bound: f64,
 }
 
+#[derive(Deserialize)]
+struct ExplainQuery {
+    #[serde(default)]
+    journal: String,
+    /// Manuscript to explain; defaults to the job's first document.
+    #[serde(default)]
+    document: Option<Uuid>,
+}
+
+#[derive(Deserialize)]
+struct DocumentQuery {
+    /// Manuscript whose recommendations to export; defaults to the job's first document.
+    #[serde(default)]
+    document: Option<Uuid>,
+}
+
+/// Why a single journal was or was not recommended for a graded manuscript.
+#[derive(Debug, Serialize)]
+struct JournalExplanation {
+    journal_name: String,
+    reference_mark: Option<String>,
+    overall_score: f64,
+    low_bound: f64,
+    match_score: f64,
+    /// `None` when the topic match is too weak for the journal to be considered at all.
+    adjusted_threshold: Option<f64>,
+    /// `overall_score - adjusted_threshold`; negative when the manuscript fell short.
+    gap: Option<f64>,
+    passed: bool,
+    recommended: bool,
+    reason: String,
+}
+
+/// Settings and journal reference data loaded once per job and shared by every manuscript.
+struct GradingContext {
+    settings: GraderSettings,
+    llm: LlmClient,
+    topics: Vec<JournalTopicRow>,
+    references: Vec<JournalReferenceRow>,
+    score_map: HashMap<Uuid, HashMap<Uuid, i16>>,
+}
+
+impl GradingContext {
+    /// Recommendations need both topics (to match keywords) and references (to recommend).
+    fn journals_configured(&self) -> bool {
+        !self.topics.is_empty() && !self.references.is_empty()
+    }
+}
+
 #[derive(Clone)]
 struct KeywordSummary {
     main: Option<String>,
@@ -209,7 +287,7 @@ pub async fn grader_page(
     let user = auth::require_user_redirect(&state, &jar).await?;
     let username = escape_html(&user.username);
     let note_html = format!(
-        "当前登录：<strong>{username}</strong>。上传 PDF、DOCX 或 TXT 稿件，系统会估计投稿水平并推荐期刊。",
+        "当前登录：<strong>{username}</strong>。上传 PDF、DOCX 或 TXT 稿件，系统会逐篇估计投稿水平并推荐期刊。",
         username = username,
     );
     let admin_link = if user.is_admin {
@@ -220,16 +298,35 @@ pub async fn grader_page(
     } else {
         None
     };
+    let page_user = ToolPageUser {
+        note_html: Cow::Owned(note_html),
+        admin_link,
+    };
+    let html = state
+        .tool_page_cache()
+        .render(MODULE_GRADER, &page_user, grader_page_layout);
+
+    Ok(Html(html))
+}
+
+/// Everything on the grader page except the per-user header; cached by `ToolPageCache`.
+fn grader_page_layout() -> ToolPageLayout<'static> {
     let upload_widget = render_upload_widget(
         &UploadWidgetConfig::new("grader-upload", "grader-file", "file", "稿件文件")
             .with_description("支持上传 PDF、DOCX 或 TXT 稿件。")
-            .with_note("仅支持单个 PDF / DOCX / TXT 文件。")
+            .with_multiple(Some(MAX_MANUSCRIPTS_PER_JOB))
+            .with_note("每个任务最多可提交 10 份稿件，每份稿件独立评分。")
             .with_accept(".pdf,.docx,.txt"),
     );
     let history_panel = history_ui::render_history_panel(MODULE_GRADER);
     let extra_styles = Cow::Borrowed(
         r#"        .results { background: #ffffff; border-radius: 12px; border: 1px solid #e2e8f0; padding: 1.5rem; box-shadow: 0 10px 30px rgba(15, 23, 42, 0.06); }
         .results h3 { margin-top: 0; }
+        .document-result { border-top: 1px solid #e2e8f0; padding-top: 1rem; margin-bottom: 1.5rem; }
+        .document-result:first-child { border-top: none; padding-top: 0; }
+        .level-bars { display: grid; grid-template-columns: auto 1fr auto; gap: 0.35rem 0.75rem; align-items: center; max-width: 420px; margin: 0.75rem 0; }
+        .level-bar { background: #e2e8f0; border-radius: 999px; height: 0.6rem; overflow: hidden; }
+        .level-bar span { display: block; height: 100%; background: #2563eb; }
 "#,
     );
     let new_tab_html = format!(
@@ -237,15 +334,24 @@ pub async fn grader_page(
                     <h2>提交稿件</h2>
                     <form id="grader-form">
                         {upload_widget}
+                        <label for="callback-url">完成回调地址（可选，https）</label>
+                        <input id="callback-url" name="callback_url" type="url" placeholder="https://example.com/hooks/toolkit">
                         <button type="submit">开始评估</button>
                     </form>
                     <div id="status-box" class="status-box">等待上传。</div>
                 </section>
                 <section id="results-section" class="results" style="display:none;">
                     <h2>评估结果</h2>
-                    <div id="score-summary"></div>
-                    <div id="keyword-summary"></div>
-                    <div id="recommendations"></div>
+                    <div id="document-results"></div>
+                    <div id="journal-explainer">
+                        <h3>查询期刊推荐依据</h3>
+                        <form id="explain-form">
+                            <select id="explain-document"></select>
+                            <input id="explain-journal" type="text" placeholder="输入期刊名称">
+                            <button type="submit">查询</button>
+                        </form>
+                        <div id="explain-result"></div>
+                    </div>
                 </section>
 "#,
         upload_widget = upload_widget,
@@ -255,93 +361,182 @@ pub async fn grader_page(
 const fileInput = document.getElementById('grader-file');
 const statusBox = document.getElementById('status-box');
 const resultsSection = document.getElementById('results-section');
-const scoreSummary = document.getElementById('score-summary');
-const keywordSummary = document.getElementById('keyword-summary');
-const recommendationsBox = document.getElementById('recommendations');
+const documentResults = document.getElementById('document-results');
+const explainer = document.getElementById('journal-explainer');
+const explainForm = document.getElementById('explain-form');
+const explainDocument = document.getElementById('explain-document');
+const explainInput = document.getElementById('explain-journal');
+const explainResult = document.getElementById('explain-result');
 
 let pollTimer = null;
+let activeStatusUrl = null;
+
+const stopPolling = () => {
+    clearTimeout(pollTimer);
+    pollTimer = null;
+    activeStatusUrl = null;
+};
+let currentJobId = null;
+
+const escapeHtml = (value) => String(value ?? '').replace(/[&<>"']/g, (ch) => ({ '&': '&amp;', '<': '&lt;', '>': '&gt;', '"': '&quot;', "'": '&#39;' })[ch]);
 
 const resetResults = () => {
     resultsSection.style.display = 'none';
-    scoreSummary.innerHTML = '';
-    keywordSummary.innerHTML = '';
-    recommendationsBox.innerHTML = '';
+    documentResults.innerHTML = '';
+    explainDocument.innerHTML = '';
+    explainResult.innerHTML = '';
 };
 
-const renderRecommendations = (items) => {
+const renderRecommendations = (doc) => {
+    const items = doc.recommendations;
     if (!items || items.length === 0) {
-        recommendationsBox.innerHTML = '<p class="note">暂无匹配的期刊推荐。</p>';
-        return;
+        return '<p class="note">暂无匹配的期刊推荐。</p>';
     }
     const rows = items.map((item) => {
         const mark = item.reference_mark ? item.reference_mark : '—';
         return `<tr><td>${item.journal_name}</td><td>${mark}</td><td>${item.match_score.toFixed(1)}` +
                `</td><td>${item.adjusted_threshold.toFixed(2)}</td><td>${item.low_bound.toFixed(2)}</td></tr>`;
     }).join('');
-    recommendationsBox.innerHTML = `
-        <h3>期刊推荐</h3>
+    const download = currentJobId
+        ? `<p><a href="/api/grader/jobs/${currentJobId}/recommendations.xlsx?document=${doc.document_id}">下载推荐列表（Excel）</a></p>`
+        : '';
+    return `
+        <h4>期刊推荐</h4>
+        ${download}
         <table>
             <thead><tr><th>期刊</th><th>参考标记</th><th>匹配得分</th><th>调整后阈值</th><th>原始阈值</th></tr></thead>
             <tbody>${rows}</tbody>
         </table>`;
 };
 
-const renderKeywords = (main, peripherals) => {
-    const mainText = main ? `<strong>主要主题：</strong> ${main}` : '<strong>主要主题：</strong> 未识别';
+const renderKeywords = (doc) => {
+    const mainText = doc.keyword_main ? `<strong>主要主题：</strong> ${doc.keyword_main}` : '<strong>主要主题：</strong> 未识别';
+    const peripherals = doc.keyword_peripherals;
     const peripheralText = peripherals && peripherals.length > 0 ? peripherals.join('，') : '无';
-    keywordSummary.innerHTML = `
-        <h3>主题分析</h3>
+    return `
+        <h4>主题分析</h4>
         <p>${mainText}</p>
         <p><strong>相关主题：</strong> ${peripheralText}</p>
     `;
 };
 
-const renderScore = (data) => {
-    if (typeof data.iqm_score !== 'number') {
-        scoreSummary.innerHTML = '<p class="note">尚未产生评分。</p>';
-        return;
+const renderLevelScores = (doc) => {
+    const scores = doc.level_scores || [];
+    if (!scores.length) {
+        return '';
+    }
+    const rows = scores.map((score, index) => {
+        const width = Math.max(0, Math.min(100, score));
+        return `<span>水平 ${index + 1}</span><div class="level-bar"><span style="width: ${width}%"></span></div><span>${score.toFixed(1)}</span>`;
+    }).join('');
+    return `<div class="level-bars">${rows}</div>`;
+};
+
+const renderDocxPenalty = (doc) => {
+    if (typeof doc.docx_penalty !== 'number' || doc.docx_penalty <= 0) {
+        return '';
     }
-    const attempts = data.attempts_run ?? 0;
-    const valid = data.valid_runs ?? 0;
-    const justification = data.justification ? `<p><strong>模型说明：</strong> ${data.justification}</p>` : '';
-    const decision = data.decision_reason ? `<p class="note">${data.decision_reason}</p>` : '';
-    scoreSummary.innerHTML = `
-        <h3>综合评分</h3>
-        <p><strong>IQM 评分：</strong> ${data.iqm_score.toFixed(1)}</p>
+    const percent = Math.round(doc.docx_penalty * 1000) / 10;
+    return `<p class="note">DOCX 格式惩罚 ${percent}% 已应用，以上评分均已按该比例下调。</p>`;
+};
+
+const renderScore = (doc) => {
+    if (typeof doc.iqm_score !== 'number') {
+        return '<p class="note">尚未产生评分。</p>';
+    }
+    const attempts = doc.attempts_run ?? 0;
+    const valid = doc.valid_runs ?? 0;
+    const justification = doc.justification ? `<p><strong>模型说明：</strong> ${doc.justification}</p>` : '';
+    const decision = doc.decision_reason ? `<p class="note">${doc.decision_reason}</p>` : '';
+    return `
+        <h4>综合评分</h4>
+        <p><strong>IQM 评分：</strong> ${doc.iqm_score.toFixed(1)}</p>
+        ${renderLevelScores(doc)}
+        ${renderDocxPenalty(doc)}
         <p class="note">有效结果 ${valid} 次，共尝试 ${attempts} 次。</p>
         ${justification}
         ${decision}
     `;
 };
 
+const renderDocuments = (documents) => {
+    const items = documents || [];
+    documentResults.innerHTML = items.map((doc, index) => {
+        const heading = items.length > 1
+            ? `<h3>稿件 ${index + 1}：${escapeHtml(doc.original_filename)}</h3>`
+            : `<h3>${escapeHtml(doc.original_filename)}</h3>`;
+        if (doc.status !== 'completed') {
+            const detail = doc.status_detail || `当前状态：${doc.status}`;
+            return `<div class="document-result">${heading}<p class="note">${escapeHtml(detail)}</p></div>`;
+        }
+        return `<div class="document-result">${heading}${renderScore(doc)}${renderKeywords(doc)}${renderRecommendations(doc)}</div>`;
+    }).join('');
+
+    const graded = items.filter((doc) => doc.status === 'completed');
+    explainDocument.innerHTML = graded
+        .map((doc) => `<option value="${doc.document_id}">${escapeHtml(doc.original_filename)}</option>`)
+        .join('');
+    explainDocument.style.display = graded.length > 1 ? '' : 'none';
+    explainer.style.display = graded.length > 0 ? '' : 'none';
+};
+
 const updateStatus = (payload) => {
     statusBox.textContent = payload;
 };
 
 const handleStatusPayload = (payload) => {
-    updateStatus(payload.status_detail || `当前状态：${payload.status}`);
+    if (payload.job_id) {
+        currentJobId = payload.job_id;
+    }
+    const detail = payload.status_detail || `当前状态：${payload.status}`;
+    const active = (payload.documents || []).find((doc) => doc.status === 'processing');
+    updateStatus(active && active.status_detail ? `${detail} ${active.status_detail}` : detail);
 
     if (payload.status === 'completed') {
-        renderScore(payload);
-        renderKeywords(payload.keyword_main, payload.keyword_peripherals);
-        renderRecommendations(payload.recommendations);
+        renderDocuments(payload.documents);
         resultsSection.style.display = 'block';
-        if (pollTimer) {
-            clearInterval(pollTimer);
-            pollTimer = null;
-        }
+        stopPolling();
     } else if (payload.status === 'failed') {
         const message = payload.error_message || '评估失败，请稍后重试。';
         statusBox.textContent = message;
-        if (pollTimer) {
-            clearInterval(pollTimer);
-            pollTimer = null;
+        if (payload.documents && payload.documents.length > 1) {
+            renderDocuments(payload.documents);
+            resultsSection.style.display = 'block';
         }
+        stopPolling();
     }
 };
 
+explainForm.addEventListener('submit', async (event) => {
+    event.preventDefault();
+    const journal = explainInput.value.trim();
+    if (!currentJobId || !journal) {
+        return;
+    }
+    explainResult.innerHTML = '<p class="note">查询中…</p>';
+    try {
+        const documentParam = explainDocument.value ? `&document=${explainDocument.value}` : '';
+        const res = await fetch(`/api/grader/jobs/${currentJobId}/explain?journal=${encodeURIComponent(journal)}${documentParam}`);
+        const data = await res.json();
+        if (!res.ok) {
+            explainResult.innerHTML = `<p class="note">${data.message || '查询失败'}</p>`;
+            return;
+        }
+        const threshold = typeof data.adjusted_threshold === 'number' ? data.adjusted_threshold.toFixed(2) : '—';
+        const gap = typeof data.gap === 'number' ? data.gap.toFixed(2) : '—';
+        explainResult.innerHTML = `
+            <p><strong>${data.journal_name}</strong>：${data.passed ? '达到阈值' : '未达到阈值'}${data.recommended ? '（已推荐）' : ''}</p>
+            <p class="note">IQM 评分 ${data.overall_score.toFixed(1)}，匹配得分 ${data.match_score.toFixed(1)}，原始阈值 ${data.low_bound.toFixed(2)}，调整后阈值 ${threshold}，差距 ${gap}。</p>
+            <p class="note">${data.reason}</p>`;
+    } catch (err) {
+        explainResult.innerHTML = `<p class="note">查询失败：${err.message}</p>`;
+    }
+});
+
 const pollJob = (url) => {
-    pollTimer = setInterval(async () => {
+    stopPolling();
+    activeStatusUrl = url;
+    const poll = async () => {
         try {
             const res = await fetch(url, { headers: { 'Accept': 'application/json' } });
             if (!res.ok) {
@@ -349,16 +544,16 @@ const pollJob = (url) => {
             }
             const data = await res.json();
             handleStatusPayload(data);
-            if (data.status === 'completed' || data.status === 'failed') {
-                clearInterval(pollTimer);
-                pollTimer = null;
+            const delay = window.nextPollDelay(data, 3000);
+            if (url === activeStatusUrl && delay) {
+                pollTimer = setTimeout(poll, delay);
             }
         } catch (err) {
-            clearInterval(pollTimer);
-            pollTimer = null;
+            stopPolling();
             updateStatus('轮询失败：' + err.message);
         }
-    }, 3000);
+    };
+    pollTimer = setTimeout(poll, 3000);
 };
 
 const handleFileSelection = () => {
@@ -366,7 +561,11 @@ const handleFileSelection = () => {
         updateStatus('等待上传。');
         return;
     }
-    updateStatus(`已选择文件：${fileInput.files[0].name}`);
+    if (fileInput.files.length === 1) {
+        updateStatus(`已选择文件：${fileInput.files[0].name}`);
+    } else {
+        updateStatus(`已选择 ${fileInput.files.length} 份稿件。`);
+    }
 };
 
 if (fileInput) {
@@ -405,17 +604,14 @@ form.addEventListener('submit', async (event) => {
 });
 "#;
 
-    let html = render_tool_page(ToolPageLayout {
+    ToolPageLayout {
         meta_title: "稿件评估与期刊推荐 | 张圆教授课题组 AI 工具箱",
         page_heading: "稿件评估与期刊推荐",
-        username: &username,
-        note_html: Cow::Owned(note_html),
         tab_group: "grader",
         new_tab_label: "新任务",
         new_tab_html: Cow::Owned(new_tab_html),
         history_tab_label: "历史记录",
         history_panel_html: Cow::Owned(history_panel),
-        admin_link,
         footer_html: Cow::Owned(render_footer()),
         extra_style_blocks: vec![
             Cow::Borrowed(history_ui::HISTORY_STYLES),
@@ -423,6 +619,7 @@ form.addEventListener('submit', async (event) => {
             extra_styles,
         ],
         body_scripts: vec![
+            Cow::Borrowed(STATUS_CLIENT_SCRIPT),
             Cow::Borrowed(UPLOAD_WIDGET_SCRIPT),
             Cow::Owned(format!(
                 "<script>
@@ -437,9 +634,7 @@ form.addEventListener('submit', async (event) => {
                 history_ui::HISTORY_SCRIPT
             )),
         ],
-    });
-
-    Ok(Html(html))
+    }
 }
 
 async fn create_job(
@@ -453,23 +648,21 @@ async fn create_job(
 
     let pool = state.pool();
 
-    if let Err(err) = usage::ensure_within_limits(&pool, user.id, MODULE_GRADER, 1).await {
-        return Err(json_error(StatusCode::FORBIDDEN, err.message()));
-    }
-
     ensure_storage_root(STORAGE_ROOT)
         .await
         .map_err(|err| internal_error(err.into()))?;
 
     let job_id = Uuid::new_v4();
-    let doc_id = Uuid::new_v4();
     let job_dir = PathBuf::from(STORAGE_ROOT).join(job_id.to_string());
 
     let file_config = FileFieldConfig::new(
         "file",
         &["pdf", "docx", "txt"],
-        1,
-        FileNaming::PrefixOnly { prefix: "source_" },
+        MAX_MANUSCRIPTS_PER_JOB,
+        FileNaming::Indexed {
+            prefix: "source_",
+            pad_width: 2,
+        },
     )
     .with_min_files(1);
 
@@ -484,44 +677,62 @@ async fn create_job(
         }
     };
 
+    let callback_url = match notifications::parse_callback_url(upload.first_text("callback_url")) {
+        Ok(url) => url,
+        Err(message) => {
+            let _ = tokio_fs::remove_dir_all(&job_dir).await;
+            return Err(json_error(StatusCode::BAD_REQUEST, message));
+        }
+    };
+
     let files: Vec<_> = upload.files_for("file").cloned().collect();
-    let file = files
-        .first()
-        .expect("file upload guaranteed by process_upload_form");
 
-    let is_docx = file
-        .original_name
-        .rsplit('.')
-        .next()
-        .map(|ext| ext.eq_ignore_ascii_case("docx"))
-        .unwrap_or(false);
+    if let Err(err) =
+        usage::ensure_within_limits(&pool, user.id, MODULE_GRADER, files.len() as i64).await
+    {
+        let _ = tokio_fs::remove_dir_all(&job_dir).await;
+        return Err(quota_error(StatusCode::FORBIDDEN, &err));
+    }
 
     let mut transaction = pool
         .begin()
         .await
         .map_err(|err| internal_error(err.into()))?;
 
-    sqlx::query("INSERT INTO grader_jobs (id, user_id, status) VALUES ($1, $2, $3)")
-        .bind(job_id)
-        .bind(user.id)
-        .bind(STATUS_PENDING)
-        .execute(&mut *transaction)
-        .await
-        .map_err(|err| internal_error(err.into()))?;
-
     sqlx::query(
-        "INSERT INTO grader_documents (id, job_id, original_filename, source_path, is_docx, status) VALUES ($1, $2, $3, $4, $5, $6)",
+        "INSERT INTO grader_jobs (id, user_id, status, callback_url) VALUES ($1, $2, $3, $4)",
     )
-    .bind(doc_id)
     .bind(job_id)
-    .bind(&file.original_name)
-    .bind(file.stored_path.to_string_lossy().to_string())
-    .bind(is_docx)
+    .bind(user.id)
     .bind(STATUS_PENDING)
+    .bind(&callback_url)
     .execute(&mut *transaction)
     .await
     .map_err(|err| internal_error(err.into()))?;
 
+    for (ordinal, file) in files.iter().enumerate() {
+        let is_docx = file
+            .original_name
+            .rsplit('.')
+            .next()
+            .map(|ext| ext.eq_ignore_ascii_case("docx"))
+            .unwrap_or(false);
+
+        sqlx::query(
+            "INSERT INTO grader_documents (id, job_id, ordinal, original_filename, source_path, is_docx, status) VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        )
+        .bind(Uuid::new_v4())
+        .bind(job_id)
+        .bind(ordinal as i32)
+        .bind(&file.original_name)
+        .bind(file.stored_path.to_string_lossy().to_string())
+        .bind(is_docx)
+        .bind(STATUS_PENDING)
+        .execute(&mut *transaction)
+        .await
+        .map_err(|err| internal_error(err.into()))?;
+    }
+
     transaction
         .commit()
         .await
@@ -553,7 +764,7 @@ async fn job_status(
     let pool = state.pool();
 
     let job = sqlx::query_as::<_, JobStatusRow>(
-        "SELECT id, user_id, status, status_detail, error_message, attempts_run, valid_runs, iqm_score, justification, decision_reason, keyword_main, keyword_peripherals, recommendations FROM grader_jobs WHERE id = $1",
+        "SELECT user_id, status, status_detail, error_message FROM grader_jobs WHERE id = $1",
     )
     .bind(job_id)
     .fetch_optional(&pool)
@@ -561,33 +772,30 @@ async fn job_status(
     .map_err(|err| internal_error(err.into()))?
     .ok_or_else(|| json_error(StatusCode::NOT_FOUND, "未找到任务。"))?;
 
-    if job.user_id != user.id && !user.is_admin {
-        return Err((
-            StatusCode::FORBIDDEN,
-            Json(ApiMessage::new("无权查看该任务。")),
-        ));
-    }
+    ensure_job_owner(job.user_id, &user, "未找到任务。", "无权查看该任务。")?;
 
-    let document = sqlx::query_as::<_, JobDocumentStatusRow>(
-        "SELECT original_filename, status, status_detail FROM grader_documents WHERE job_id = $1 LIMIT 1",
+    let documents = sqlx::query_as::<_, JobDocumentRow>(
+        "SELECT id, original_filename, status, status_detail, attempts_run, valid_runs, iqm_score, level_scores, docx_penalty, justification, decision_reason, keyword_main, keyword_peripherals, recommendations FROM grader_documents WHERE job_id = $1 ORDER BY ordinal",
     )
     .bind(job_id)
-    .fetch_optional(&pool)
+    .fetch_all(&pool)
     .await
-    .map_err(|err| internal_error(err.into()))?
-    .unwrap_or(JobDocumentStatusRow {
-        original_filename: "稿件".to_string(),
-        status: STATUS_PENDING.to_string(),
-        status_detail: None,
-    });
+    .map_err(|err| internal_error(err.into()))?;
 
-    let recommendations = job
-        .recommendations
-        .as_ref()
-        .and_then(|value| serde_json::from_value::<Vec<StoredRecommendation>>(value.clone()).ok())
-        .unwrap_or_default();
+    let response = JobStatusResponse {
+        job_id,
+        poll_interval_ms: JobStatus::from_str(&job.status).poll_interval_ms(),
+        status: job.status,
+        status_detail: job.status_detail,
+        error_message: job.error_message,
+        documents: documents.into_iter().map(document_result).collect(),
+    };
+
+    Ok(Json(response))
+}
 
-    let recommendation_dtos = recommendations
+fn document_result(row: JobDocumentRow) -> JobDocumentResult {
+    let recommendations = stored_recommendations(row.recommendations)
         .into_iter()
         .map(|item| RecommendationDto {
             journal_name: item.journal_name,
@@ -598,37 +806,207 @@ async fn job_status(
         })
         .collect();
 
-    let response = JobStatusResponse {
-        job_id,
-        status: job.status,
-        status_detail: job.status_detail,
-        error_message: job.error_message,
-        attempts_run: job.attempts_run,
-        valid_runs: job.valid_runs,
-        iqm_score: job.iqm_score,
-        justification: job.justification,
-        decision_reason: job.decision_reason,
-        keyword_main: job.keyword_main,
-        keyword_peripherals: job.keyword_peripherals.unwrap_or_default(),
-        recommendations: recommendation_dtos,
-        document: JobDocumentStatus {
-            original_filename: document.original_filename,
-            status: document.status,
-            status_detail: document.status_detail,
-        },
+    JobDocumentResult {
+        document_id: row.id,
+        original_filename: row.original_filename,
+        status: row.status,
+        status_detail: row.status_detail,
+        attempts_run: row.attempts_run,
+        valid_runs: row.valid_runs,
+        iqm_score: row.iqm_score,
+        level_scores: row.level_scores.unwrap_or_default(),
+        docx_penalty: row.docx_penalty,
+        justification: row.justification,
+        decision_reason: row.decision_reason,
+        keyword_main: row.keyword_main,
+        keyword_peripherals: row.keyword_peripherals.unwrap_or_default(),
+        recommendations,
+    }
+}
+
+fn stored_recommendations(value: Option<Value>) -> Vec<StoredRecommendation> {
+    value
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+/// Loads one manuscript of a job the user may view. `document` selects a specific upload;
+/// without it the job's first document is used, matching single-manuscript links.
+async fn load_job_document(
+    pool: &PgPool,
+    user: &auth::AuthUser,
+    job_id: Uuid,
+    document: Option<Uuid>,
+) -> Result<JobDocumentRow, (StatusCode, Json<ApiMessage>)> {
+    let (user_id,): (Uuid,) = sqlx::query_as("SELECT user_id FROM grader_jobs WHERE id = $1")
+        .bind(job_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|err| internal_error(err.into()))?
+        .ok_or_else(|| json_error(StatusCode::NOT_FOUND, "未找到任务。"))?;
+
+    ensure_job_owner(user_id, user, "未找到任务。", "无权查看该任务。")?;
+
+    sqlx::query_as::<_, JobDocumentRow>(
+        "SELECT id, original_filename, status, status_detail, attempts_run, valid_runs, iqm_score, level_scores, docx_penalty, justification, decision_reason, keyword_main, keyword_peripherals, recommendations FROM grader_documents WHERE job_id = $1 AND ($2::uuid IS NULL OR id = $2) ORDER BY ordinal LIMIT 1",
+    )
+    .bind(job_id)
+    .bind(document)
+    .fetch_optional(pool)
+    .await
+    .map_err(|err| internal_error(err.into()))?
+    .ok_or_else(|| json_error(StatusCode::NOT_FOUND, "未找到该稿件。"))
+}
+
+/// Serves the stored journal recommendations of a graded manuscript as an XLSX workbook, in
+/// the same order and columns as the page table.
+async fn download_recommendations(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    AxumPath(job_id): AxumPath<Uuid>,
+    Query(query): Query<DocumentQuery>,
+) -> Result<Response, (StatusCode, Json<ApiMessage>)> {
+    let user = auth::current_user_or_json_error(&state, &jar)
+        .await
+        .map_err(|JsonAuthError { status, message }| json_error(status, message))?;
+
+    let pool = state.pool();
+    let document = load_job_document(&pool, &user, job_id, query.document).await?;
+
+    if document.status != STATUS_COMPLETED {
+        return Err(json_error(StatusCode::CONFLICT, "任务尚未完成评分。"));
+    }
+
+    let permit = state.download_limiter().acquire(&user)?;
+    let recommendations = stored_recommendations(document.recommendations);
+    let bytes = recommendations_workbook(&recommendations).map_err(internal_error)?;
+
+    let response = attachment_response(
+        bytes,
+        &format!("grader_recommendations_{}_{}.xlsx", job_id, document.id),
+        "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+    );
+    Ok(hold_permit(response, permit))
+}
+
+fn recommendations_workbook(recommendations: &[StoredRecommendation]) -> Result<Vec<u8>> {
+    let mut workbook = Workbook::new();
+    let worksheet = workbook.add_worksheet();
+    worksheet.set_name("期刊推荐")?;
+
+    for (col, header) in ["期刊", "参考标记", "匹配得分", "调整后阈值", "原始阈值"]
+        .into_iter()
+        .enumerate()
+    {
+        worksheet.write_string(0, col as u16, header)?;
+    }
+
+    for (idx, item) in recommendations.iter().enumerate() {
+        let row = idx as u32 + 1;
+        worksheet.write_string(row, 0, &item.journal_name)?;
+        worksheet.write_string(row, 1, item.reference_mark.as_deref().unwrap_or(""))?;
+        worksheet.write_number(row, 2, item.match_score)?;
+        worksheet.write_number(row, 3, item.adjusted_threshold)?;
+        worksheet.write_number(row, 4, item.low_bound)?;
+    }
+    worksheet.set_column_width(0, 40)?;
+
+    workbook
+        .save_to_buffer()
+        .context("failed to build recommendations workbook")
+}
+
+/// Explains the recommendation decision for one journal of a graded manuscript, including
+/// journals that were filtered out. Uses the current journal reference table and the
+/// manuscript's stored keywords.
+async fn explain_journal(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    AxumPath(job_id): AxumPath<Uuid>,
+    Query(query): Query<ExplainQuery>,
+) -> Result<Json<JournalExplanation>, (StatusCode, Json<ApiMessage>)> {
+    let user = auth::current_user_or_json_error(&state, &jar)
+        .await
+        .map_err(|JsonAuthError { status, message }| json_error(status, message))?;
+
+    let journal = query.journal.trim();
+    if journal.is_empty() {
+        return Err(json_error(StatusCode::BAD_REQUEST, "请提供期刊名称。"));
+    }
+
+    let pool = state.pool();
+    let document = load_job_document(&pool, &user, job_id, query.document).await?;
+
+    let Some(overall_score) = document
+        .iqm_score
+        .filter(|_| document.status == STATUS_COMPLETED)
+    else {
+        return Err(json_error(StatusCode::CONFLICT, "任务尚未完成评分。"));
     };
 
-    Ok(Json(response))
+    let topics = fetch_journal_topics(&pool)
+        .await
+        .map_err(|err| internal_error(err.into()))?;
+    let references = fetch_journal_references(&pool)
+        .await
+        .map_err(|err| internal_error(err.into()))?;
+    let scores = fetch_journal_topic_scores(&pool)
+        .await
+        .map_err(|err| internal_error(err.into()))?;
+    let score_map = build_score_map(&references, &scores);
+    let summary = KeywordSummary {
+        main: document.keyword_main,
+        peripheral: document.keyword_peripherals.unwrap_or_default(),
+    };
+    let recommended = stored_recommendations(document.recommendations);
+
+    explain_recommendation(
+        &references,
+        &score_map,
+        &topics,
+        &summary,
+        overall_score,
+        &recommended,
+        journal,
+    )
+    .map(Json)
+    .ok_or_else(|| json_error(StatusCode::NOT_FOUND, "未找到该期刊，请检查名称。"))
 }
 
 fn spawn_job_worker(state: AppState, job_id: Uuid) {
     tokio::spawn(async move {
-        if let Err(err) = process_job(state, job_id).await {
+        if let Err(err) = process_job(state.clone(), job_id).await {
             error!(?err, %job_id, "grader job failed");
         }
+
+        send_job_callback(&state, job_id).await;
     });
 }
 
+/// Sends the job callback with a recommendations link per graded manuscript.
+async fn send_job_callback(state: &AppState, job_id: Uuid) {
+    notifications::send_job_callback(state, "grader_jobs", MODULE_GRADER, job_id, async || {
+        let documents = sqlx::query_scalar::<_, Uuid>(
+            "SELECT id FROM grader_documents WHERE job_id = $1 AND status = $2 ORDER BY ordinal",
+        )
+        .bind(job_id)
+        .bind(STATUS_COMPLETED)
+        .fetch_all(state.pool_ref())
+        .await
+        .unwrap_or_else(|err| {
+            error!(?err, %job_id, "failed to load grader documents for callback");
+            Vec::new()
+        });
+        documents
+            .into_iter()
+            .map(|document| {
+                format!("/api/grader/jobs/{job_id}/recommendations.xlsx?document={document}")
+            })
+            .collect()
+    })
+    .await;
+}
+
 async fn process_job(state: AppState, job_id: Uuid) -> Result<()> {
     let pool = state.pool();
 
@@ -652,16 +1030,127 @@ async fn process_job(state: AppState, job_id: Uuid) -> Result<()> {
     )
     .await?;
 
-    let doc = sqlx::query_as::<_, DocumentProcessingRecord>(
-        "SELECT id, original_filename, source_path, is_docx FROM grader_documents WHERE job_id = $1",
+    let documents = sqlx::query_as::<_, DocumentProcessingRecord>(
+        "SELECT id, original_filename, source_path, is_docx FROM grader_documents WHERE job_id = $1 ORDER BY ordinal",
     )
     .bind(job_id)
-    .fetch_one(&pool)
+    .fetch_all(&pool)
     .await
-    .context("failed to load grader document")?;
+    .context("failed to load grader documents")?;
+
+    let Some(settings) = state.grader_settings().await else {
+        mark_job_failed(&pool, job_id, "未配置稿件评估设置，请联系管理员。").await?;
+        return Ok(());
+    };
+
+    let topics = fetch_journal_topics(&pool).await.unwrap_or_default();
+    let references = fetch_journal_references(&pool).await.unwrap_or_default();
+    let scores = fetch_journal_topic_scores(&pool).await.unwrap_or_default();
+    let context = GradingContext {
+        score_map: build_score_map(&references, &scores),
+        settings,
+        llm: state.llm_client(),
+        topics,
+        references,
+    };
+
+    let total = documents.len();
+    let mut success_count = 0usize;
+    let mut failures: Vec<&'static str> = Vec::new();
+    let mut total_tokens = 0i64;
 
+    for (index, doc) in documents.iter().enumerate() {
+        update_job_status(
+            &pool,
+            job_id,
+            STATUS_PROCESSING,
+            Some(&format!(
+                "正在评估第 {}/{} 份稿件：{}",
+                index + 1,
+                total,
+                doc.original_filename
+            )),
+        )
+        .await?;
+
+        match grade_document(&pool, job_id, &context, doc).await? {
+            Ok(tokens) => {
+                success_count += 1;
+                total_tokens += tokens;
+            }
+            Err(detail) => {
+                mark_document_failed(&pool, doc.id, detail).await?;
+                failures.push(detail);
+            }
+        }
+    }
+
+    if success_count > 0
+        && let Err(err) = usage::record_usage(
+            &pool,
+            job.user_id,
+            MODULE_GRADER,
+            total_tokens,
+            success_count as i64,
+        )
+        .await
+    {
+        error!(?err, "failed to record grader usage");
+    }
+
+    let (status, detail) = job_outcome(success_count, &failures);
+    let error_message = (status == STATUS_FAILED).then_some(detail.as_str());
+
+    sqlx::query(
+        "UPDATE grader_jobs SET status = $2, status_detail = $3, error_message = $4, usage_delta = $5, updated_at = NOW() WHERE id = $1",
+    )
+    .bind(job_id)
+    .bind(status)
+    .bind(&detail)
+    .bind(error_message)
+    .bind(success_count as i64)
+    .execute(&pool)
+    .await
+    .context("failed to finalize grader job")?;
+
+    Ok(())
+}
+
+/// Final job status and detail once every manuscript has been attempted. A job completes when
+/// at least one manuscript was graded; the others keep their own failure detail.
+fn job_outcome(succeeded: usize, failures: &[&str]) -> (&'static str, String) {
+    match (succeeded, failures) {
+        (0, []) => (STATUS_FAILED, "任务中没有可评估的稿件。".to_string()),
+        (0, [only]) => (STATUS_FAILED, (*only).to_string()),
+        (0, _) => (
+            STATUS_FAILED,
+            format!(
+                "{} 份稿件均评估失败，请查看各稿件的失败原因。",
+                failures.len()
+            ),
+        ),
+        (_, []) => (STATUS_COMPLETED, "评估完成。".to_string()),
+        (_, _) => (
+            STATUS_COMPLETED,
+            format!(
+                "评估完成：{} 份成功，{} 份失败。",
+                succeeded,
+                failures.len()
+            ),
+        ),
+    }
+}
+
+/// Grades one manuscript and stores its score, keywords and recommendations on its document
+/// row. Returns the tokens spent, or the failure detail to show for that manuscript.
+async fn grade_document(
+    pool: &PgPool,
+    job_id: Uuid,
+    context: &GradingContext,
+    doc: &DocumentProcessingRecord,
+) -> Result<std::result::Result<i64, &'static str>> {
     update_document_status(
-        &pool,
+        pool,
         doc.id,
         STATUS_PROCESSING,
         Some("正在读取稿件..."),
@@ -670,11 +1159,16 @@ async fn process_job(state: AppState, job_id: Uuid) -> Result<()> {
     .await?;
 
     let source_path = Path::new(&doc.source_path);
-    let text = read_document_text(source_path).map_err(|err| anyhow!(err))?;
-    let text = text.trim().to_string();
+    let text = match read_document_text(source_path) {
+        Ok(text) => text.trim().to_string(),
+        Err(err) => {
+            error!(?err, %job_id, document_id = %doc.id, "failed to read grader manuscript");
+            return Ok(Err(EMPTY_DOCUMENT_DETAIL));
+        }
+    };
 
     update_document_status(
-        &pool,
+        pool,
         doc.id,
         STATUS_PROCESSING,
         Some(&format!("已提取文本，长度 {} 字符。", text.len())),
@@ -683,67 +1177,59 @@ async fn process_job(state: AppState, job_id: Uuid) -> Result<()> {
     .await?;
 
     if text.is_empty() {
-        mark_job_failed(&pool, job_id, doc.id, "未能读取到稿件内容，请检查文件。").await?;
-        return Ok(());
+        return Ok(Err(EMPTY_DOCUMENT_DETAIL));
     }
 
-    let Some(settings) = state.grader_settings().await else {
-        mark_job_failed(&pool, job_id, doc.id, "未配置稿件评估设置，请联系管理员。").await?;
-        return Ok(());
+    let models = &context.settings.models;
+    let prompts = &context.settings.prompts;
+    let manuscript = Manuscript {
+        text: &text,
+        pdf: if models.attach_pdf {
+            load_pdf_attachment(doc).await
+        } else {
+            None
+        },
     };
-    let models = settings.models.clone();
-    let prompts = settings.prompts.clone();
-
-    let llm = state.llm_client();
 
     let (grading_outcome, grading_tokens) = run_grading_sequence(
-        &pool,
-        job_id,
-        &llm,
-        models.grading_model.as_str(),
+        pool,
+        doc.id,
+        &context.llm,
+        route_model(
+            &models.grading_model,
+            &models.grading_routes,
+            estimate_tokens(&text),
+        ),
         &prompts.grading_instructions,
-        &text,
+        &manuscript,
+        &SamplingPlan::from_models(models),
     )
     .await?;
 
     let mut outcome = match grading_outcome {
-        Some(outcome) => outcome,
-        None => {
-            mark_job_failed(
-                &pool,
-                job_id,
-                doc.id,
-                "模型未返回足够的有效结果，请稍后重试。",
-            )
-            .await?;
-            return Ok(());
-        }
+        Ok(outcome) => outcome,
+        Err(detail) => return Ok(Err(detail)),
     };
 
-    update_job_attempts(
-        &pool,
-        job_id,
+    update_document_attempts(
+        pool,
+        doc.id,
         outcome.attempts_run,
         outcome.valid_runs,
         Some("正在分析主题并匹配期刊..."),
     )
     .await?;
 
-    let topics = fetch_journal_topics(&pool).await.unwrap_or_default();
-    let references = fetch_journal_references(&pool).await.unwrap_or_default();
-    let scores = fetch_journal_topic_scores(&pool).await.unwrap_or_default();
-    let score_map = build_score_map(&references, &scores);
-
     let (keyword_summary, keyword_tokens) = run_keyword_selection(
-        &llm,
+        &context.llm,
         models.keyword_model.as_str(),
         &prompts.keyword_selection,
-        &topics,
+        &context.topics,
         &text,
     )
     .await
     .unwrap_or_else(|err| {
-        error!(?err, %job_id, "keyword selection failed");
+        error!(?err, %job_id, document_id = %doc.id, "keyword selection failed");
         (
             KeywordSummary {
                 main: None,
@@ -753,85 +1239,88 @@ async fn process_job(state: AppState, job_id: Uuid) -> Result<()> {
         )
     });
 
-    if doc.is_docx {
-        apply_docx_penalty(&mut outcome);
+    let docx_penalty = doc
+        .is_docx
+        .then(|| context.settings.models.docx_penalty_fraction())
+        .filter(|penalty| *penalty > 0.0);
+    let mut completed_detail = "评估完成。".to_string();
+    if let Some(penalty) = docx_penalty {
+        apply_docx_penalty(&mut outcome, penalty);
+        completed_detail.push_str(&docx_penalty_note(penalty));
+    }
+    if !context.journals_configured() {
+        completed_detail.push_str(JOURNALS_UNCONFIGURED_NOTE);
     }
 
     let recommendations = build_recommendations(
-        &references,
-        &score_map,
-        &topics,
+        &context.references,
+        &context.score_map,
+        &context.topics,
         &keyword_summary,
         outcome.iqm_score,
     );
 
     let recommendation_json = serde_json::to_value(&recommendations).unwrap_or(json!([]));
 
-    let total_tokens = grading_tokens + keyword_tokens;
-
     let peripherals = if keyword_summary.peripheral.is_empty() {
         None
     } else {
         Some(keyword_summary.peripheral.clone())
     };
 
-    if let Err(err) = usage::record_usage(&pool, job.user_id, MODULE_GRADER, total_tokens, 1).await
-    {
-        error!(?err, "failed to record grader usage");
-    }
-
     sqlx::query(
-        "UPDATE grader_jobs SET status = $2, status_detail = $3, error_message = NULL, attempts_run = $4, valid_runs = $5, iqm_score = $6, justification = $7, decision_reason = $8, keyword_main = $9, keyword_peripherals = $10, recommendations = $11, usage_delta = 1, updated_at = NOW() WHERE id = $1",
+        "UPDATE grader_documents SET status = $2, status_detail = $3, extracted_chars = $4, attempts_run = $5, valid_runs = $6, iqm_score = $7, level_scores = $8, docx_penalty = $9, justification = $10, decision_reason = $11, keyword_main = $12, keyword_peripherals = $13, recommendations = $14, updated_at = NOW() WHERE id = $1",
     )
-    .bind(job_id)
+    .bind(doc.id)
     .bind(STATUS_COMPLETED)
-    .bind("评估完成。")
+    .bind(completed_detail)
+    .bind(text.len() as i32)
     .bind(outcome.attempts_run as i32)
     .bind(outcome.valid_runs as i32)
     .bind(outcome.iqm_score)
+    .bind(outcome.per_level.to_vec())
+    .bind(docx_penalty)
     .bind(outcome.justification)
     .bind(outcome.decision_reason)
     .bind(keyword_summary.main)
     .bind(peripherals.as_ref())
     .bind(recommendation_json)
-    .execute(&pool)
+    .execute(pool)
     .await
-    .context("failed to finalize grader job")?;
-
-    update_document_status(
-        &pool,
-        doc.id,
-        STATUS_COMPLETED,
-        Some("评估完成。"),
-        Some(text.len() as i32),
-    )
-    .await?;
+    .context("failed to store grader document result")?;
 
-    Ok(())
+    Ok(Ok(grading_tokens + keyword_tokens))
 }
 
 async fn run_grading_sequence(
     pool: &PgPool,
-    job_id: Uuid,
+    document_id: Uuid,
     llm: &LlmClient,
     model: &str,
     system_prompt: &str,
-    manuscript: &str,
-) -> Result<(Option<GradingOutcome>, i64)> {
+    manuscript: &Manuscript<'_>,
+    plan: &SamplingPlan,
+) -> Result<(std::result::Result<GradingOutcome, &'static str>, i64)> {
     let mut attempts_run = 0usize;
     let mut valid_scores: Vec<[f64; 6]> = Vec::new();
     let mut justifications: Vec<String> = Vec::new();
     let mut token_total: i64 = 0;
 
-    while attempts_run < MAX_ATTEMPTS && valid_scores.len() < TARGET_SUCCESSES {
+    let stop = loop {
+        if let Some(stop) = sampling_stop(attempts_run, valid_scores.len(), token_total, plan) {
+            break stop;
+        }
         attempts_run += 1;
 
         if attempts_run > 1 {
             sleep(RATE_LIMIT_DELAY).await;
         }
 
-        let request = build_grading_request(model, system_prompt, manuscript);
+        let request =
+            build_grading_request(model, system_prompt, manuscript).with_seed(attempts_run as u64);
 
+        // Single-shot: a failed call already uses up one sampling attempt, so retrying it
+        // here would multiply the sampling plan's call budget.
         match llm.execute(request).await {
             Ok(response) => {
                 token_total += response.token_usage.total_tokens as i64;
@@ -851,14 +1340,18 @@ async fn run_grading_sequence(
                     }
                 }
             }
+            Err(err) if is_refusal_error(&err) => {
+                warn!(?err, %document_id, attempts_run, "grader request refused, stopping sampling");
+                break SamplingStop::Refused;
+            }
             Err(err) => {
                 error!(?err, "grader LLM call failed");
             }
         }
 
-        update_job_attempts(
+        update_document_attempts(
             pool,
-            job_id,
+            document_id,
             attempts_run,
             valid_scores.len(),
             Some(&format!(
@@ -868,18 +1361,25 @@ async fn run_grading_sequence(
             )),
         )
         .await?;
+    };
+
+    if stop == SamplingStop::TokenBudget {
+        info!(%document_id, token_total, token_budget = plan.token_budget, attempts_run, "grader token budget reached, stopping early");
     }
 
-    if valid_scores.len() < MIN_SUCCESSES {
-        return Ok((None, token_total));
+    if stop == SamplingStop::Refused {
+        return Ok((Err(REFUSED_DETAIL), token_total));
+    }
+    if valid_scores.len() < plan.min_successes {
+        return Ok((Err(INSUFFICIENT_RESULTS_DETAIL), token_total));
     }
 
     let weighted_scores: Vec<f64> = valid_scores
         .iter()
-        .map(|scores| weighted_mean(scores))
+        .map(|scores| weighted_mean(scores, &plan.weights))
         .collect();
 
-    let (iqm, kept_indices) = interquartile_mean(&weighted_scores);
+    let (iqm, kept_indices) = interquartile_mean(&weighted_scores, plan.trim_fraction);
     let kept_runs: Vec<&[f64; 6]> = if kept_indices.is_empty() {
         valid_scores.iter().collect()
     } else {
@@ -894,16 +1394,13 @@ async fn run_grading_sequence(
         }
     }
 
-    let decision_reason = format!(
-        "基于 {} 次有效结果的加权评分，取其中 {} 次的四分位平均值。",
-        valid_scores.len(),
-        kept_runs.len()
-    );
+    let decision_reason =
+        describe_decision(plan, stop, valid_scores.len(), kept_runs.len(), token_total);
 
     let justification = justifications.into_iter().next();
 
     Ok((
-        Some(GradingOutcome {
+        Ok(GradingOutcome {
             per_level,
             iqm_score: iqm,
             attempts_run,
@@ -915,17 +1412,142 @@ async fn run_grading_sequence(
     ))
 }
 
-fn build_grading_request(model: &str, system_prompt: &str, manuscript: &str) -> LlmRequest {
+/// Per-job sampling limits, taken from the admin grader settings.
+#[derive(Debug, Clone, Copy)]
+struct SamplingPlan {
+    max_attempts: usize,
+    target_successes: usize,
+    min_successes: usize,
+    token_budget: u64,
+    trim_fraction: f64,
+    weights: [f64; 6],
+}
+
+impl SamplingPlan {
+    fn from_models(models: &GraderModels) -> Self {
+        let (max_attempts, target_successes, min_successes) = models.sampling_counts();
+        Self {
+            max_attempts,
+            target_successes,
+            min_successes,
+            token_budget: models.grading_token_budget,
+            trim_fraction: models.grading_trim_fraction,
+            weights: models.scoring_weights(),
+        }
+    }
+}
+
+/// Why the grading sequence stopped sampling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SamplingStop {
+    TargetReached,
+    MaxAttempts,
+    TokenBudget,
+    /// The model or provider moderation refused the manuscript; further attempts would too.
+    Refused,
+}
+
+/// Decides whether to stop before the next attempt. The token budget only cuts the sequence
+/// short once `min_successes` valid runs are in, so it never turns a gradeable job into a failure.
+fn sampling_stop(
+    attempts_run: usize,
+    valid_runs: usize,
+    tokens_used: i64,
+    plan: &SamplingPlan,
+) -> Option<SamplingStop> {
+    if valid_runs >= plan.target_successes {
+        Some(SamplingStop::TargetReached)
+    } else if attempts_run >= plan.max_attempts {
+        Some(SamplingStop::MaxAttempts)
+    } else if plan.token_budget > 0
+        && tokens_used >= plan.token_budget as i64
+        && valid_runs >= plan.min_successes
+    {
+        Some(SamplingStop::TokenBudget)
+    } else {
+        None
+    }
+}
+
+fn describe_decision(
+    plan: &SamplingPlan,
+    stop: SamplingStop,
+    valid_runs: usize,
+    kept_runs: usize,
+    token_total: i64,
+) -> String {
+    let mut reason = if (plan.trim_fraction - DEFAULT_GRADING_TRIM_FRACTION).abs() < f64::EPSILON {
+        format!(
+            "基于 {} 次有效结果的加权评分，取其中 {} 次的四分位平均值。",
+            valid_runs, kept_runs
+        )
+    } else {
+        format!(
+            "基于 {} 次有效结果的加权评分，两端各剔除 {:.0}% 后取其中 {} 次的平均值。",
+            valid_runs,
+            plan.trim_fraction * 100.0,
+            kept_runs
+        )
+    };
+    reason.push_str(&format!(
+        "采样设置：目标 {} 次有效结果，最少 {} 次，最多尝试 {} 次。",
+        plan.target_successes, plan.min_successes, plan.max_attempts
+    ));
+    if stop == SamplingStop::TokenBudget {
+        reason.push_str(&format!(
+            "累计消耗 {} tokens，已达到预算 {}，提前停止采样。",
+            token_total, plan.token_budget
+        ));
+    }
+    reason
+}
+
+/// What one grading attempt sends: the extracted text, plus the PDF itself when the grader is
+/// set to attach it.
+struct Manuscript<'a> {
+    text: &'a str,
+    pdf: Option<FileAttachment>,
+}
+
+/// Reads a PDF manuscript for attachment; `None` (logged) for other formats or unreadable
+/// files, in which case grading uses the extracted text alone.
+async fn load_pdf_attachment(doc: &DocumentProcessingRecord) -> Option<FileAttachment> {
+    let is_pdf = Path::new(&doc.source_path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("pdf"));
+    if !is_pdf {
+        return None;
+    }
+
+    match tokio_fs::read(&doc.source_path).await {
+        Ok(bytes) => Some(FileAttachment::new(
+            doc.original_filename.clone(),
+            "application/pdf",
+            AttachmentKind::Pdf,
+            bytes,
+        )),
+        Err(err) => {
+            warn!(?err, document_id = %doc.id, "failed to read grader PDF for attachment");
+            None
+        }
+    }
+}
+
+fn build_grading_request(model: &str, system_prompt: &str, manuscript: &Manuscript) -> LlmRequest {
     LlmRequest::new(
         model.to_string(),
         vec![
             ChatMessage::new(MessageRole::System, system_prompt.to_string()),
             ChatMessage::new(
                 MessageRole::User,
-                format!("Manuscript to grade:\n\n{}", manuscript),
+                format!("Manuscript to grade:\n\n{}", manuscript.text),
             ),
         ],
     )
+    .with_attachments(manuscript.pdf.iter().cloned().collect())
+    .with_class(RequestClass::Interactive)
+    .with_module(MODULE_GRADER)
 }
 
 fn parse_grading_response(payload: &str) -> Result<GradingResponsePayload> {
@@ -960,10 +1582,10 @@ fn is_non_decreasing(values: &[f64; 6]) -> bool {
         .all(|window| window[0] <= window[1] + f64::EPSILON)
 }
 
-fn weighted_mean(scores: &[f64; 6]) -> f64 {
+fn weighted_mean(scores: &[f64; 6], weights: &[f64; 6]) -> f64 {
     let mut numerator = 0.0;
     let mut denominator = 0.0;
-    for (score, weight) in scores.iter().zip(WEIGHTS.iter()) {
+    for (score, weight) in scores.iter().zip(weights.iter()) {
         numerator += score * weight;
         denominator += weight;
     }
@@ -974,13 +1596,22 @@ fn weighted_mean(scores: &[f64; 6]) -> f64 {
     }
 }
 
-fn interquartile_mean(values: &[f64]) -> (f64, Vec<usize>) {
+/// Mean of the values left after dropping `trim_fraction` of them (rounded up) from each end;
+/// `0.25` is the classic interquartile mean. Falls back to every value when trimming would
+/// leave nothing.
+fn interquartile_mean(values: &[f64], trim_fraction: f64) -> (f64, Vec<usize>) {
     if values.is_empty() {
         return (0.0, Vec::new());
     }
     let mut indices: Vec<usize> = (0..values.len()).collect();
     indices.sort_by(|&a, &b| values[a].partial_cmp(&values[b]).unwrap());
-    let k = (values.len() + 3) / 4;
+    let fraction = if trim_fraction.is_finite() {
+        trim_fraction.clamp(0.0, MAX_GRADING_TRIM_FRACTION)
+    } else {
+        DEFAULT_GRADING_TRIM_FRACTION
+    };
+    // The epsilon keeps exact products (e.g. 8 × 0.25) from rounding up an extra sample.
+    let k = (values.len() as f64 * fraction - 1e-9).ceil().max(0.0) as usize;
     let kept = if values.len() > 2 * k {
         indices[k..values.len() - k].to_vec()
     } else {
@@ -1035,7 +1666,9 @@ async fn run_keyword_selection(
                 format!("稿件内容（前 10000 字符）：\n\n{}", excerpt),
             ),
         ],
-    );
+    )
+    .with_class(RequestClass::Interactive)
+    .with_module(MODULE_GRADER);
 
     let response = llm
         .execute(request)
@@ -1110,32 +1743,11 @@ fn build_recommendations(
         return Vec::new();
     }
 
-    let mut name_lookup: HashMap<String, Uuid> = HashMap::new();
-    for topic in topics {
-        name_lookup.insert(topic.name.to_lowercase(), topic.id);
-    }
-
-    let mut weights: HashMap<Uuid, i16> = HashMap::new();
-    if let Some(ref main) = summary.main {
-        if let Some(topic_id) = name_lookup.get(&main.to_lowercase()) {
-            weights.insert(*topic_id, 2);
-        }
-    }
-    for keyword in &summary.peripheral {
-        if let Some(topic_id) = name_lookup.get(&keyword.to_lowercase()) {
-            weights.entry(*topic_id).or_insert(1);
-        }
-    }
-
+    let weights = keyword_weights(topics, summary);
     let mut results = Vec::new();
 
     for reference in references {
-        let topic_scores = score_map.get(&reference.id).cloned().unwrap_or_default();
-        let mut match_score: i16 = 0;
-        for (topic_id, journal_score) in topic_scores {
-            let weight = *weights.get(&topic_id).unwrap_or(&0);
-            match_score += weight * journal_score;
-        }
+        let match_score = journal_match_score(reference, score_map, &weights);
 
         let adjusted = adjust_lower_bound(reference.low_bound, match_score);
         let Some(adjusted_threshold) = adjusted else {
@@ -1167,6 +1779,108 @@ fn build_recommendations(
     results
 }
 
+/// Topic weights from the keyword summary: 2 for the main topic, 1 for peripheral topics.
+fn keyword_weights(topics: &[JournalTopicRow], summary: &KeywordSummary) -> HashMap<Uuid, i16> {
+    let mut name_lookup: HashMap<String, Uuid> = HashMap::new();
+    for topic in topics {
+        name_lookup.insert(topic.name.to_lowercase(), topic.id);
+    }
+
+    let mut weights: HashMap<Uuid, i16> = HashMap::new();
+    if let Some(ref main) = summary.main
+        && let Some(topic_id) = name_lookup.get(&main.to_lowercase())
+    {
+        weights.insert(*topic_id, 2);
+    }
+    for keyword in &summary.peripheral {
+        if let Some(topic_id) = name_lookup.get(&keyword.to_lowercase()) {
+            weights.entry(*topic_id).or_insert(1);
+        }
+    }
+    weights
+}
+
+fn journal_match_score(
+    reference: &JournalReferenceRow,
+    score_map: &HashMap<Uuid, HashMap<Uuid, i16>>,
+    weights: &HashMap<Uuid, i16>,
+) -> i16 {
+    score_map
+        .get(&reference.id)
+        .map(|topic_scores| {
+            topic_scores
+                .iter()
+                .map(|(topic_id, journal_score)| {
+                    weights.get(topic_id).copied().unwrap_or(0) * journal_score
+                })
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+/// Applies the same rules as `build_recommendations` to the journal named `journal`
+/// (case-insensitive; falls back to the first partial match) and reports each number involved.
+fn explain_recommendation(
+    references: &[JournalReferenceRow],
+    score_map: &HashMap<Uuid, HashMap<Uuid, i16>>,
+    topics: &[JournalTopicRow],
+    summary: &KeywordSummary,
+    overall_score: f64,
+    recommended: &[StoredRecommendation],
+    journal: &str,
+) -> Option<JournalExplanation> {
+    let needle = journal.trim().to_lowercase();
+    let reference = references
+        .iter()
+        .find(|row| row.journal_name.trim().to_lowercase() == needle)
+        .or_else(|| {
+            references
+                .iter()
+                .find(|row| row.journal_name.to_lowercase().contains(&needle))
+        })?;
+
+    let weights = keyword_weights(topics, summary);
+    let match_score = journal_match_score(reference, score_map, &weights);
+    let adjusted_threshold = adjust_lower_bound(reference.low_bound, match_score);
+    let gap = adjusted_threshold.map(|threshold| overall_score - threshold);
+    let passed = gap.is_some_and(|gap| gap >= 0.0);
+    let is_recommended = recommended
+        .iter()
+        .any(|item| item.journal_id == reference.id);
+
+    let reason = match (adjusted_threshold, gap) {
+        (Some(threshold), Some(gap)) if gap < 0.0 => format!(
+            "评分 {:.2} 低于调整后阈值 {:.2}，差距 {:.2}。",
+            overall_score, threshold, -gap
+        ),
+        (Some(threshold), _) if is_recommended => format!(
+            "评分 {:.2} 达到调整后阈值 {:.2}，已列入推荐。",
+            overall_score, threshold
+        ),
+        (Some(threshold), _) => format!(
+            "评分 {:.2} 达到调整后阈值 {:.2}，但推荐列表仅保留阈值最高的 {} 个期刊。",
+            overall_score, threshold, MAX_RECOMMENDATIONS
+        ),
+        (None, _) => format!(
+            "主题匹配得分 {} 低于 3，该期刊与稿件主题不符，不参与推荐。",
+            match_score
+        ),
+    };
+
+    Some(JournalExplanation {
+        journal_name: reference.journal_name.clone(),
+        reference_mark: reference.reference_mark.clone(),
+        overall_score,
+        low_bound: reference.low_bound,
+        match_score: match_score as f64,
+        adjusted_threshold,
+        gap,
+        passed,
+        recommended: is_recommended,
+        reason,
+    })
+}
+
 fn adjust_lower_bound(base: f64, score: i16) -> Option<f64> {
     for (threshold, multiplier) in MATCH_SCORE_RULES {
         if score >= *threshold {
@@ -1176,13 +1890,19 @@ fn adjust_lower_bound(base: f64, score: i16) -> Option<f64> {
     None
 }
 
-fn apply_docx_penalty(outcome: &mut GradingOutcome) {
-    outcome.iqm_score *= 1.0 - DOCX_PENALTY;
+fn apply_docx_penalty(outcome: &mut GradingOutcome, penalty: f64) {
+    outcome.iqm_score *= 1.0 - penalty;
     for value in outcome.per_level.iter_mut() {
-        *value *= 1.0 - DOCX_PENALTY;
+        *value *= 1.0 - penalty;
     }
 }
 
+/// User-facing note that a DOCX penalty lowered the scores, e.g. "DOCX 格式惩罚 2% 已应用。".
+fn docx_penalty_note(penalty: f64) -> String {
+    let percent = (penalty * 1000.0).round() / 10.0;
+    format!("DOCX 格式惩罚 {}% 已应用。", percent)
+}
+
 async fn update_job_status(
     pool: &PgPool,
     job_id: Uuid,
@@ -1201,24 +1921,24 @@ async fn update_job_status(
     Ok(())
 }
 
-async fn update_job_attempts(
+async fn update_document_attempts(
     pool: &PgPool,
-    job_id: Uuid,
+    document_id: Uuid,
     attempts: usize,
     valid: usize,
     detail: Option<&str>,
 ) -> Result<()> {
     sqlx::query(
-        "UPDATE grader_jobs SET status = $2, status_detail = $3, attempts_run = $4, valid_runs = $5, updated_at = NOW() WHERE id = $1",
+        "UPDATE grader_documents SET status = $2, status_detail = $3, attempts_run = $4, valid_runs = $5, updated_at = NOW() WHERE id = $1",
     )
-    .bind(job_id)
+    .bind(document_id)
     .bind(STATUS_PROCESSING)
     .bind(detail)
     .bind(attempts as i32)
     .bind(valid as i32)
     .execute(pool)
     .await
-    .context("failed to update grader job progress")?;
+    .context("failed to update grader document progress")?;
     Ok(())
 }
 
@@ -1242,12 +1962,8 @@ async fn update_document_status(
     Ok(())
 }
 
-async fn mark_job_failed(
-    pool: &PgPool,
-    job_id: Uuid,
-    document_id: Uuid,
-    message: &str,
-) -> Result<()> {
+/// Fails the whole job, including every manuscript that has not been graded yet.
+async fn mark_job_failed(pool: &PgPool, job_id: Uuid, message: &str) -> Result<()> {
     sqlx::query(
         "UPDATE grader_jobs SET status = $2, status_detail = $3, error_message = $3, updated_at = NOW() WHERE id = $1",
     )
@@ -1258,6 +1974,20 @@ async fn mark_job_failed(
     .await
     .context("failed to mark grader job failed")?;
 
+    sqlx::query(
+        "UPDATE grader_documents SET status = $2, status_detail = $3, updated_at = NOW() WHERE job_id = $1 AND status <> $4",
+    )
+    .bind(job_id)
+    .bind(STATUS_FAILED)
+    .bind(message)
+    .bind(STATUS_COMPLETED)
+    .execute(pool)
+    .await
+    .context("failed to mark grader documents failed")?;
+    Ok(())
+}
+
+async fn mark_document_failed(pool: &PgPool, document_id: Uuid, message: &str) -> Result<()> {
     sqlx::query(
         "UPDATE grader_documents SET status = $2, status_detail = $3, updated_at = NOW() WHERE id = $1",
     )
@@ -1350,22 +2080,582 @@ fn internal_error(err: anyhow::Error) -> (StatusCode, Json<ApiMessage>) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::DEFAULT_GRADING_WEIGHTS;
+    use crate::test_support::{
+        STUB_TOTAL_TOKENS, StubProvider, StubReply, create_user, recorded_usage, session_jar,
+        test_state,
+    };
+    use chrono::Utc;
+
+    const GRADING_REPLY: &str = r#"{"Level 1": 20, "Level 2": 35, "Level 3": 50, "Level 4": 65, "Level 5": 80, "Level 6": 95, "justification": "Sound methods."}"#;
+
+    async fn seed_job(pool: &PgPool, user_id: Uuid, manuscripts: &[(&str, &str)]) -> Uuid {
+        let job_id = Uuid::new_v4();
+        let job_dir = PathBuf::from(STORAGE_ROOT).join(job_id.to_string());
+        fs::create_dir_all(&job_dir).unwrap();
+
+        sqlx::query("INSERT INTO grader_jobs (id, user_id, status) VALUES ($1, $2, $3)")
+            .bind(job_id)
+            .bind(user_id)
+            .bind(STATUS_PENDING)
+            .execute(pool)
+            .await
+            .unwrap();
+
+        for (ordinal, (name, text)) in manuscripts.iter().enumerate() {
+            let path = job_dir.join(format!("source_{:02}_{}", ordinal, name));
+            fs::write(&path, text).unwrap();
+            sqlx::query(
+                "INSERT INTO grader_documents (id, job_id, ordinal, original_filename, source_path, status) VALUES ($1, $2, $3, $4, $5, $6)",
+            )
+            .bind(Uuid::new_v4())
+            .bind(job_id)
+            .bind(ordinal as i32)
+            .bind(*name)
+            .bind(path.to_string_lossy().to_string())
+            .bind(STATUS_PENDING)
+            .execute(pool)
+            .await
+            .unwrap();
+        }
+
+        job_id
+    }
+
+    /// Two valid runs are enough, so a test job needs only a couple of stub calls.
+    async fn use_quick_sampling(state: &AppState) {
+        sqlx::query("UPDATE module_configs SET models = models || $2 WHERE module_name = $1")
+            .bind(MODULE_GRADER)
+            .bind(json!({ "max_attempts": 3, "target_successes": 2, "min_successes": 2 }))
+            .execute(state.pool_ref())
+            .await
+            .unwrap();
+        state.reload_settings().await.unwrap();
+    }
+
+    async fn job_state(pool: &PgPool, job_id: Uuid) -> (String, Option<String>, Option<String>) {
+        sqlx::query_as("SELECT status, status_detail, error_message FROM grader_jobs WHERE id = $1")
+            .bind(job_id)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    async fn documents(pool: &PgPool, job_id: Uuid) -> Vec<JobDocumentRow> {
+        sqlx::query_as(
+            "SELECT id, original_filename, status, status_detail, attempts_run, valid_runs, iqm_score, level_scores, docx_penalty, justification, decision_reason, keyword_main, keyword_peripherals, recommendations FROM grader_documents WHERE job_id = $1 ORDER BY ordinal",
+        )
+        .bind(job_id)
+        .fetch_all(pool)
+        .await
+        .unwrap()
+    }
+
+    #[sqlx::test]
+    #[ignore = "needs DATABASE_URL (disposable Postgres)"]
+    async fn process_job_grades_each_manuscript_independently(pool: PgPool) {
+        let provider = StubProvider::start(|request| {
+            if request.user.starts_with("Manuscript to grade:") {
+                StubReply::text(GRADING_REPLY)
+            } else {
+                StubReply::text(r#"{"main_keyword": null, "peripheral_keywords": []}"#)
+            }
+        })
+        .await;
+        let state = test_state(pool.clone(), &provider).await;
+        use_quick_sampling(&state).await;
+        let user_id = create_user(&pool).await;
+        let job_id = seed_job(
+            &pool,
+            user_id,
+            &[
+                (
+                    "paper.txt",
+                    "We measure soil carbon across three field seasons.",
+                ),
+                ("blank.txt", "   "),
+            ],
+        )
+        .await;
+
+        process_job(state, job_id).await.unwrap();
+
+        let (status, detail, error_message) = job_state(&pool, job_id).await;
+        assert_eq!(status, STATUS_COMPLETED);
+        assert_eq!(detail.as_deref(), Some("评估完成：1 份成功，1 份失败。"));
+        assert_eq!(error_message, None);
+
+        let docs = documents(&pool, job_id).await;
+        assert_eq!(docs[0].status, STATUS_COMPLETED);
+        assert_eq!(docs[0].valid_runs, Some(2));
+        assert!(docs[0].iqm_score.is_some_and(|score| score > 0.0));
+        assert_eq!(
+            docs[0].level_scores.as_deref(),
+            Some(&[20.0, 35.0, 50.0, 65.0, 80.0, 95.0][..])
+        );
+        assert_eq!(docs[0].justification.as_deref(), Some("Sound methods."));
+        assert!(docs[0].recommendations.is_some());
+        assert_eq!(docs[1].status, STATUS_FAILED);
+        assert_eq!(
+            docs[1].status_detail.as_deref(),
+            Some(EMPTY_DOCUMENT_DETAIL)
+        );
+        assert_eq!(docs[1].level_scores, None);
+
+        let (tokens, units) = recorded_usage(&pool, user_id, MODULE_GRADER).await;
+        assert_eq!(tokens, STUB_TOTAL_TOKENS * provider.calls() as i64);
+        assert_eq!(units, 1);
+
+        let _ = fs::remove_dir_all(PathBuf::from(STORAGE_ROOT).join(job_id.to_string()));
+    }
+
+    #[sqlx::test]
+    #[ignore = "needs DATABASE_URL (disposable Postgres)"]
+    async fn recommendation_downloads_count_against_the_per_user_limit(pool: PgPool) {
+        let provider = StubProvider::start(|request| {
+            if request.user.starts_with("Manuscript to grade:") {
+                StubReply::text(GRADING_REPLY)
+            } else {
+                StubReply::text(r#"{"main_keyword": null, "peripheral_keywords": []}"#)
+            }
+        })
+        .await;
+        let state = test_state(pool.clone(), &provider).await;
+        use_quick_sampling(&state).await;
+        let user_id = create_user(&pool).await;
+        let job_id = seed_job(
+            &pool,
+            user_id,
+            &[(
+                "paper.txt",
+                "We measure soil carbon across three field seasons.",
+            )],
+        )
+        .await;
+        process_job(state.clone(), job_id).await.unwrap();
+
+        let download = || async {
+            download_recommendations(
+                State(state.clone()),
+                session_jar(&pool, user_id).await,
+                AxumPath(job_id),
+                Query(DocumentQuery { document: None }),
+            )
+            .await
+        };
+
+        // Responses whose bodies have not been sent yet still hold their slots.
+        let mut open = Vec::new();
+        for _ in 0..crate::web::download_limit::DEFAULT_MAX_CONCURRENT_DOWNLOADS {
+            open.push(
+                download()
+                    .await
+                    .unwrap_or_else(|_| panic!("download within the limit")),
+            );
+        }
+        assert!(matches!(
+            download().await,
+            Err((StatusCode::TOO_MANY_REQUESTS, _))
+        ));
+
+        let body = axum::body::to_bytes(open.pop().unwrap().into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert!(body.starts_with(b"PK"));
+        assert!(download().await.is_ok());
+
+        let _ = fs::remove_dir_all(PathBuf::from(STORAGE_ROOT).join(job_id.to_string()));
+    }
+
+    #[sqlx::test]
+    #[ignore = "needs DATABASE_URL (disposable Postgres)"]
+    async fn empty_journal_database_still_scores_with_a_note(pool: PgPool) {
+        // The migrations seed a journal database; remove it as if the admin never set one up.
+        sqlx::query("DELETE FROM journal_reference_entries")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM journal_topics")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let provider = StubProvider::start(|_| StubReply::text(GRADING_REPLY)).await;
+        let state = test_state(pool.clone(), &provider).await;
+        use_quick_sampling(&state).await;
+        let user_id = create_user(&pool).await;
+        let job_id = seed_job(
+            &pool,
+            user_id,
+            &[(
+                "paper.txt",
+                "We measure soil carbon across three field seasons.",
+            )],
+        )
+        .await;
+
+        process_job(state, job_id).await.unwrap();
+
+        let (status, _, _) = job_state(&pool, job_id).await;
+        assert_eq!(status, STATUS_COMPLETED);
+        let docs = documents(&pool, job_id).await;
+        assert!(docs[0].iqm_score.is_some_and(|score| score > 0.0));
+        assert_eq!(
+            docs[0].status_detail.as_deref(),
+            Some("评估完成。期刊数据库未配置，仅提供评分。")
+        );
+        assert_eq!(docs[0].recommendations, Some(json!([])));
+        // Without topics there is nothing to select keywords from, so only grading is called.
+        assert_eq!(provider.calls(), 2);
+
+        let _ = fs::remove_dir_all(PathBuf::from(STORAGE_ROOT).join(job_id.to_string()));
+    }
+
+    #[sqlx::test]
+    #[ignore = "needs DATABASE_URL (disposable Postgres)"]
+    async fn process_job_fails_refused_manuscript_without_usage(pool: PgPool) {
+        let provider = StubProvider::start(|_| {
+            StubReply::Error(
+                StatusCode::FORBIDDEN,
+                json!({ "error": { "message": "Input was flagged by moderation", "metadata": { "reasons": ["violence"] } } }),
+            )
+        })
+        .await;
+        let state = test_state(pool.clone(), &provider).await;
+        use_quick_sampling(&state).await;
+        let user_id = create_user(&pool).await;
+        let job_id = seed_job(&pool, user_id, &[("paper.txt", "A manuscript.")]).await;
+
+        process_job(state, job_id).await.unwrap();
+
+        let (status, detail, error_message) = job_state(&pool, job_id).await;
+        assert_eq!(status, STATUS_FAILED);
+        assert_eq!(detail.as_deref(), Some(REFUSED_DETAIL));
+        assert_eq!(error_message.as_deref(), Some(REFUSED_DETAIL));
+        let docs = documents(&pool, job_id).await;
+        assert_eq!(docs[0].status, STATUS_FAILED);
+        assert_eq!(provider.calls(), 1);
+        assert_eq!(recorded_usage(&pool, user_id, MODULE_GRADER).await, (0, 0));
+
+        let _ = fs::remove_dir_all(PathBuf::from(STORAGE_ROOT).join(job_id.to_string()));
+    }
 
     #[test]
     fn weighted_mean_calculates_correctly() {
         let scores = [10.0, 20.0, 30.0, 30.0, 30.0, 30.0];
         let expected = (10.0 * 4.0 + 20.0 * 2.0 + 30.0 * 4.0) / 10.0;
-        assert!((weighted_mean(&scores) - expected).abs() < 1e-6);
+        assert!((weighted_mean(&scores, &DEFAULT_GRADING_WEIGHTS) - expected).abs() < 1e-6);
+    }
+
+    #[test]
+    fn recommendations_workbook_lists_each_journal() {
+        use calamine::{Reader, Xlsx};
+
+        let recommendations = vec![
+            StoredRecommendation {
+                journal_id: Uuid::new_v4(),
+                journal_name: "Building and Environment".to_string(),
+                reference_mark: Some("A".to_string()),
+                low_bound: 60.0,
+                adjusted_threshold: 57.5,
+                match_score: 3.0,
+            },
+            StoredRecommendation {
+                journal_id: Uuid::new_v4(),
+                journal_name: "Applied Acoustics".to_string(),
+                reference_mark: None,
+                low_bound: 50.0,
+                adjusted_threshold: 50.0,
+                match_score: 1.5,
+            },
+        ];
+
+        let bytes = recommendations_workbook(&recommendations).unwrap();
+        let mut workbook = Xlsx::new(std::io::Cursor::new(bytes)).unwrap();
+        let range = workbook.worksheet_range("期刊推荐").unwrap().unwrap();
+
+        assert_eq!(range.height(), 3);
+        assert_eq!(
+            range.get((0, 0)).and_then(|cell| cell.get_string()),
+            Some("期刊")
+        );
+        assert_eq!(
+            range.get((1, 0)).and_then(|cell| cell.get_string()),
+            Some("Building and Environment")
+        );
+        assert_eq!(
+            range.get((1, 1)).and_then(|cell| cell.get_string()),
+            Some("A")
+        );
+        assert_eq!(
+            range.get((1, 3)).and_then(|cell| cell.get_float()),
+            Some(57.5)
+        );
+        assert_eq!(
+            range.get((2, 2)).and_then(|cell| cell.get_float()),
+            Some(1.5)
+        );
+        assert_eq!(
+            range.get((2, 4)).and_then(|cell| cell.get_float()),
+            Some(50.0)
+        );
+    }
+
+    #[test]
+    fn weighted_mean_uses_custom_weights() {
+        let scores = [10.0, 20.0, 30.0, 40.0, 50.0, 60.0];
+        let weights = [1.0, 1.0, 2.0, 0.0, 0.0, 0.0];
+        let expected = (10.0 + 20.0 + 30.0 * 2.0) / 4.0;
+        assert!((weighted_mean(&scores, &weights) - expected).abs() < 1e-6);
+
+        let models = GraderModels {
+            grading_weights: weights,
+            ..GraderModels::default()
+        };
+        assert_eq!(SamplingPlan::from_models(&models).weights, weights);
     }
 
     #[test]
     fn interquartile_mean_trims_extremes() {
         let values = vec![10.0, 20.0, 30.0, 40.0, 50.0, 60.0];
-        let (iqm, kept) = interquartile_mean(&values);
+        let (iqm, kept) = interquartile_mean(&values, DEFAULT_GRADING_TRIM_FRACTION);
         assert_eq!(kept, vec![2, 3]);
         assert!((iqm - 35.0).abs() < 1e-6);
     }
 
+    #[test]
+    fn configured_trim_fraction_keeps_middle_subset() {
+        let values: Vec<f64> = (1..=10).rev().map(|value| value as f64 * 10.0).collect();
+        let (mean, kept) = interquartile_mean(&values, 0.1);
+        let mut kept_values: Vec<f64> = kept.iter().map(|&idx| values[idx]).collect();
+        kept_values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(
+            kept_values,
+            vec![20.0, 30.0, 40.0, 50.0, 60.0, 70.0, 80.0, 90.0]
+        );
+        assert!((mean - 55.0).abs() < 1e-6);
+
+        let (_, untrimmed) = interquartile_mean(&values, 0.0);
+        assert_eq!(untrimmed.len(), 10);
+        let (_, single) = interquartile_mean(&[42.0], MAX_GRADING_TRIM_FRACTION);
+        assert_eq!(single, vec![0]);
+    }
+
+    fn journal(name: &str, low_bound: f64) -> JournalReferenceRow {
+        JournalReferenceRow {
+            id: Uuid::new_v4(),
+            journal_name: name.to_string(),
+            reference_mark: None,
+            low_bound,
+            notes: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn explainer_reports_excluded_journal() {
+        let topic = JournalTopicRow {
+            id: Uuid::new_v4(),
+            name: "Ecology".to_string(),
+            description: None,
+            created_at: Utc::now(),
+        };
+        let strict = journal("Nature Ecology", 80.0);
+        let off_topic = journal("Cell", 40.0);
+        let references = vec![strict.clone(), off_topic.clone()];
+        let scores = vec![JournalTopicScoreRow {
+            journal_id: strict.id,
+            topic_id: topic.id,
+            score: 2,
+        }];
+        let score_map = build_score_map(&references, &scores);
+        let summary = KeywordSummary {
+            main: Some("ecology".to_string()),
+            peripheral: Vec::new(),
+        };
+        let topics = vec![topic];
+
+        let recommended = build_recommendations(&references, &score_map, &topics, &summary, 70.0);
+        assert!(recommended.is_empty());
+
+        let explanation = explain_recommendation(
+            &references,
+            &score_map,
+            &topics,
+            &summary,
+            70.0,
+            &recommended,
+            "nature ecology",
+        )
+        .expect("journal found");
+        assert!(!explanation.passed);
+        assert!(!explanation.recommended);
+        assert_eq!(explanation.match_score, 4.0);
+        assert_eq!(explanation.adjusted_threshold, Some(80.0));
+        assert_eq!(explanation.gap, Some(-10.0));
+        assert!(explanation.reason.contains("差距 10.00"));
+
+        let unmatched = explain_recommendation(
+            &references,
+            &score_map,
+            &topics,
+            &summary,
+            70.0,
+            &recommended,
+            "Cell",
+        )
+        .expect("journal found");
+        assert_eq!(unmatched.adjusted_threshold, None);
+        assert_eq!(unmatched.gap, None);
+        assert!(unmatched.reason.contains("不参与推荐"));
+    }
+
+    fn plan_with_budget(token_budget: u64) -> SamplingPlan {
+        SamplingPlan {
+            token_budget,
+            ..SamplingPlan::from_models(&GraderModels::default())
+        }
+    }
+
+    #[test]
+    fn token_budget_stops_sampling_before_target() {
+        // Every attempt costs 10k tokens and every other attempt yields a valid score.
+        let plan = plan_with_budget(150_000);
+        let (mut attempts, mut valid, mut tokens) = (0usize, 0usize, 0i64);
+        let stop = loop {
+            if let Some(stop) = sampling_stop(attempts, valid, tokens, &plan) {
+                break stop;
+            }
+            attempts += 1;
+            tokens += 10_000;
+            if attempts % 2 == 0 {
+                valid += 1;
+            }
+        };
+
+        assert_eq!(stop, SamplingStop::TokenBudget);
+        assert_eq!(attempts, 16);
+        assert_eq!(valid, plan.min_successes);
+        assert!(valid < plan.target_successes);
+    }
+
+    #[test]
+    fn token_budget_waits_for_minimum_successes() {
+        let plan = plan_with_budget(1_000);
+        assert_eq!(
+            sampling_stop(5, plan.min_successes - 1, 1_000_000, &plan),
+            None
+        );
+        let unlimited = plan_with_budget(0);
+        assert_eq!(
+            sampling_stop(5, unlimited.min_successes, 1_000_000, &unlimited),
+            None
+        );
+        assert_eq!(
+            sampling_stop(unlimited.max_attempts, 0, 0, &unlimited),
+            Some(SamplingStop::MaxAttempts)
+        );
+        assert_eq!(
+            sampling_stop(12, unlimited.target_successes, 0, &unlimited),
+            Some(SamplingStop::TargetReached)
+        );
+    }
+
+    #[test]
+    fn configured_sampling_counts_drive_stop_and_reason() {
+        let models = GraderModels {
+            max_attempts: 10,
+            target_successes: 6,
+            min_successes: 4,
+            ..GraderModels::default()
+        };
+        let plan = SamplingPlan::from_models(&models);
+
+        assert_eq!(sampling_stop(6, 5, 0, &plan), None);
+        assert_eq!(
+            sampling_stop(6, 6, 0, &plan),
+            Some(SamplingStop::TargetReached)
+        );
+        assert_eq!(
+            sampling_stop(10, 3, 0, &plan),
+            Some(SamplingStop::MaxAttempts)
+        );
+
+        let reason = describe_decision(&plan, SamplingStop::TargetReached, 6, 4, 0);
+        assert!(reason.contains("目标 6 次有效结果，最少 4 次，最多尝试 10 次"));
+    }
+
+    #[test]
+    fn pdf_attachment_is_sent_only_when_loaded() {
+        let text_only = Manuscript {
+            text: "Body text.",
+            pdf: None,
+        };
+        let request = build_grading_request("openrouter/test/model", "Grade.", &text_only);
+        assert!(request.attachments.is_empty());
+        assert_eq!(
+            request.messages[1].text,
+            "Manuscript to grade:\n\nBody text."
+        );
+
+        let with_pdf = Manuscript {
+            pdf: Some(FileAttachment::new(
+                "paper.pdf",
+                "application/pdf",
+                AttachmentKind::Pdf,
+                b"%PDF".to_vec(),
+            )),
+            ..text_only
+        };
+        let request = build_grading_request("openrouter/test/model", "Grade.", &with_pdf);
+        assert_eq!(request.attachments.len(), 1);
+        assert_eq!(request.attachments[0].filename, "paper.pdf");
+        assert_eq!(
+            request.messages[1].text,
+            "Manuscript to grade:\n\nBody text."
+        );
+    }
+
+    #[test]
+    fn docx_penalty_scales_scores_and_is_noted() {
+        let mut outcome = GradingOutcome {
+            per_level: [50.0, 60.0, 70.0, 80.0, 90.0, 100.0],
+            iqm_score: 80.0,
+            attempts_run: 12,
+            valid_runs: 12,
+            justification: None,
+            decision_reason: String::new(),
+        };
+        apply_docx_penalty(&mut outcome, 0.05);
+        assert!((outcome.iqm_score - 76.0).abs() < 1e-9);
+        assert!((outcome.per_level[5] - 95.0).abs() < 1e-9);
+
+        assert_eq!(docx_penalty_note(0.02), "DOCX 格式惩罚 2% 已应用。");
+        assert_eq!(docx_penalty_note(0.025), "DOCX 格式惩罚 2.5% 已应用。");
+    }
+
+    #[test]
+    fn job_outcome_completes_when_any_manuscript_is_graded() {
+        assert_eq!(
+            job_outcome(1, &[]),
+            (STATUS_COMPLETED, "评估完成。".to_string())
+        );
+        assert_eq!(
+            job_outcome(2, &[REFUSED_DETAIL]),
+            (
+                STATUS_COMPLETED,
+                "评估完成：2 份成功，1 份失败。".to_string()
+            )
+        );
+        assert_eq!(
+            job_outcome(0, &[INSUFFICIENT_RESULTS_DETAIL]),
+            (STATUS_FAILED, INSUFFICIENT_RESULTS_DETAIL.to_string())
+        );
+        let (status, detail) = job_outcome(0, &[REFUSED_DETAIL, EMPTY_DOCUMENT_DETAIL]);
+        assert_eq!(status, STATUS_FAILED);
+        assert!(detail.starts_with("2 份稿件均评估失败"));
+    }
+
     #[test]
     fn adjust_lower_bound_obeys_rules() {
         assert_eq!(adjust_lower_bound(40.0, 6), Some(36.0));
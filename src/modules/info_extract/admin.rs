@@ -1,29 +1,41 @@
 use axum::{
-    extract::{Form, Query, State},
-    response::{Html, Redirect},
+    extract::{Form, Path, Query, State},
+    http::{HeaderValue, StatusCode, header},
+    response::{Html, IntoResponse, Redirect, Response},
 };
 use axum_extra::extract::cookie::CookieJar;
 use serde::Deserialize;
+use uuid::Uuid;
 
 use crate::{
     AppState,
     config::{
-        InfoExtractModels, InfoExtractPrompts, update_info_extract_models,
-        update_info_extract_prompts,
+        InfoExtractModels, InfoExtractPrompts, MAX_PROVIDER_FAILURE_BUDGET,
+        parse_concurrency_limit, parse_failure_budget, parse_model_routes,
+        update_info_extract_models, update_info_extract_prompts,
     },
     escape_html, render_footer,
     web::{
         admin::DashboardQuery,
         admin_utils::{compose_flash_message, sanitize_module_redirect},
+        json_error,
     },
 };
 
-use super::super::admin_shared::MODULE_ADMIN_SHARED_STYLES;
+use super::super::admin_shared::{
+    MODULE_ADMIN_SHARED_STYLES, render_concurrency_field, render_model_routes_field,
+};
 
 #[derive(Deserialize)]
 pub struct ModelForm {
     pub extraction_model: String,
     #[serde(default)]
+    pub extraction_routes: String,
+    #[serde(default)]
+    pub max_concurrent_documents: String,
+    #[serde(default)]
+    pub provider_failure_budget: String,
+    #[serde(default)]
     pub redirect: Option<String>,
 }
 
@@ -69,9 +81,9 @@ pub async fn settings_page(
         main {{ padding: 2rem 1.5rem; max-width: 960px; margin: 0 auto; box-sizing: border-box; }}
         .panel {{ background: #ffffff; border-radius: 12px; border: 1px solid #e2e8f0; padding: 1.5rem; box-shadow: 0 18px 40px rgba(15, 23, 42, 0.08); margin-bottom: 2rem; }}
         label {{ display: block; margin-bottom: 0.5rem; font-weight: 600; color: #0f172a; }}
-        input[type="text"], textarea {{ width: 100%; padding: 0.75rem; border-radius: 8px; border: 1px solid #cbd5f5; background: #f8fafc; color: #0f172a; box-sizing: border-box; font-family: inherit; }}
+        input[type="text"], input[type="number"], textarea {{ width: 100%; padding: 0.75rem; border-radius: 8px; border: 1px solid #cbd5f5; background: #f8fafc; color: #0f172a; box-sizing: border-box; font-family: inherit; }}
         textarea {{ min-height: 160px; }}
-        input[type="text"]:focus, textarea:focus {{ outline: none; border-color: #2563eb; box-shadow: 0 0 0 3px rgba(37, 99, 235, 0.12); }}
+        input[type="text"]:focus, input[type="number"]:focus, textarea:focus {{ outline: none; border-color: #2563eb; box-shadow: 0 0 0 3px rgba(37, 99, 235, 0.12); }}
         button {{ padding: 0.85rem 1.2rem; border: none; border-radius: 8px; background: #2563eb; color: #ffffff; font-weight: 600; cursor: pointer; transition: background 0.15s ease; }}
         button:hover {{ background: #1d4ed8; }}
         .flash {{ padding: 1rem; border-radius: 8px; margin-bottom: 1.5rem; border: 1px solid transparent; }}
@@ -98,6 +110,11 @@ pub async fn settings_page(
                 <input type="hidden" name="redirect" value="{redirect}">
                 <label for="model">信息提取模型</label>
                 <input id="model" name="extraction_model" type="text" value="{model}" required>
+                {routes_field}
+                {concurrency_field}
+                <label for="provider-failure-budget">连续失败上限</label>
+                <input id="provider-failure-budget" name="provider_failure_budget" type="number" min="0" max="{max_failure_budget}" step="1" value="{failure_budget}">
+                <p class="section-note">同一任务内模型调用连续失败达到该次数后，其余文献直接标记为“模型服务暂不可用”而不再逐篇重试；0 表示不限制。</p>
                 <button type="submit">保存模型</button>
             </form>
         </section>
@@ -120,6 +137,10 @@ pub async fn settings_page(
         message_block = message_block,
         redirect = redirect_base,
         model = escape_html(&models.extraction_model),
+        concurrency_field = render_concurrency_field(models.max_concurrent_documents),
+        failure_budget = models.provider_failure_budget,
+        max_failure_budget = MAX_PROVIDER_FAILURE_BUDGET,
+        routes_field = render_model_routes_field("extraction_routes", &models.extraction_routes),
         system_prompt = escape_html(&prompts.system_prompt),
         response_guidance = escape_html(&prompts.response_guidance),
         footer = footer,
@@ -144,8 +165,30 @@ pub async fn save_models(
         )));
     }
 
+    let Some(max_concurrent_documents) = parse_concurrency_limit(&form.max_concurrent_documents)
+    else {
+        return Ok(Redirect::to(&format!(
+            "{redirect}?error=concurrency_invalid"
+        )));
+    };
+
+    let Some(provider_failure_budget) = parse_failure_budget(&form.provider_failure_budget) else {
+        return Ok(Redirect::to(&format!(
+            "{redirect}?error=failure_budget_invalid"
+        )));
+    };
+
+    let Ok(routes) = parse_model_routes(&form.extraction_routes) else {
+        return Ok(Redirect::to(&format!(
+            "{redirect}?error=model_routes_invalid"
+        )));
+    };
+
     let payload = InfoExtractModels {
         extraction_model: model.to_string(),
+        extraction_routes: routes,
+        max_concurrent_documents,
+        provider_failure_budget,
     };
 
     update_info_extract_models(state.pool_ref(), &payload)
@@ -202,3 +245,41 @@ pub async fn save_prompts(
         "{redirect}?status=infoextract_prompts_saved"
     )))
 }
+
+/// The model's stored answer for one document, for debugging prompts when extraction fails.
+/// Documents split into several windows show every window's answer, separated by `---`.
+pub async fn document_raw_response(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Path((job_id, document_id)): Path<(Uuid, Uuid)>,
+) -> Result<Response, Redirect> {
+    let _admin = crate::web::admin::require_admin_user(&state, &jar).await?;
+
+    let stored: Result<Option<Option<String>>, sqlx::Error> = sqlx::query_scalar(
+        "SELECT response_text FROM info_extract_documents WHERE id = $1 AND job_id = $2",
+    )
+    .bind(document_id)
+    .bind(job_id)
+    .fetch_optional(state.pool_ref())
+    .await;
+
+    let response = match stored {
+        Ok(Some(Some(text))) => (
+            [(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("text/plain; charset=utf-8"),
+            )],
+            text,
+        )
+            .into_response(),
+        Ok(Some(None)) => {
+            json_error(StatusCode::NOT_FOUND, "该文献没有保存模型返回。").into_response()
+        }
+        Ok(None) => json_error(StatusCode::NOT_FOUND, "未找到该文献。").into_response(),
+        Err(err) => {
+            tracing::error!(?err, %document_id, "failed to load info extract raw response");
+            json_error(StatusCode::INTERNAL_SERVER_ERROR, "读取模型返回失败。").into_response()
+        }
+    };
+    Ok(response)
+}
@@ -0,0 +1,367 @@
+//! JSON job submission for scripts: documents arrive base64-encoded and the field spec inline,
+//! then go through the same storage, validation and job start as the multipart form.
+
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use axum::{
+    Json,
+    extract::{State, rejection::JsonRejection},
+    http::StatusCode,
+};
+use axum_extra::extract::cookie::CookieJar;
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use rust_xlsxwriter::Workbook;
+use serde::Deserialize;
+use tokio::fs as tokio_fs;
+use uuid::Uuid;
+
+use super::{
+    NewJob, STORAGE_ROOT, WorkbookLayout, internal_error, parse_extraction_spec, start_job,
+    upload_fields,
+};
+use crate::{
+    AppState, notifications,
+    web::{
+        ApiMessage, InlineFile, JobSubmission,
+        auth::{self, JsonAuthError},
+        ensure_storage_root, json_error, store_inline_files,
+    },
+};
+
+/// Name recorded for the spec workbook generated from an inline field list.
+const INLINE_SPEC_FILENAME: &str = "inline_spec.xlsx";
+const MAX_INLINE_FIELDS: usize = 200;
+
+#[derive(Deserialize)]
+pub(super) struct JsonJobRequest {
+    documents: Vec<JsonDocument>,
+    fields: Vec<JsonField>,
+    #[serde(default)]
+    per_document_sheets: bool,
+    #[serde(default)]
+    skip_duplicates: bool,
+    #[serde(default)]
+    callback_url: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct JsonDocument {
+    filename: String,
+    content_base64: String,
+}
+
+/// One spec column: the same name, description, examples, allowed values and type as the
+/// five rows of an uploaded spec workbook.
+#[derive(Deserialize)]
+struct JsonField {
+    name: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    examples: Vec<String>,
+    #[serde(default)]
+    allowed_values: Vec<String>,
+    #[serde(default, rename = "type")]
+    field_type: Option<String>,
+}
+
+/// `POST /api/infoextract/jobs`: the JSON counterpart of `POST /tools/infoextract/jobs`.
+pub(super) async fn create_job_json(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    payload: Result<Json<JsonJobRequest>, JsonRejection>,
+) -> Result<Json<JobSubmission>, (StatusCode, Json<ApiMessage>)> {
+    let user = auth::current_user_or_json_error(&state, &jar)
+        .await
+        .map_err(|JsonAuthError { status, message }| json_error(status, message))?;
+
+    let Json(request) = payload.map_err(|rejection| {
+        json_error(
+            StatusCode::BAD_REQUEST,
+            format!("请求体不是有效的 JSON：{}", rejection.body_text()),
+        )
+    })?;
+
+    if request.documents.is_empty() {
+        return Err(json_error(StatusCode::BAD_REQUEST, "请至少上传一篇论文。"));
+    }
+    let callback_url = notifications::parse_callback_url(request.callback_url.as_deref())
+        .map_err(|message| json_error(StatusCode::BAD_REQUEST, message))?;
+
+    let spec_bytes = render_spec_workbook(&request.fields)
+        .map_err(|message| json_error(StatusCode::BAD_REQUEST, message))?;
+    let fields = parse_extraction_spec(&spec_bytes).map_err(|err| {
+        json_error(
+            StatusCode::BAD_REQUEST,
+            format!("字段定义格式错误：{}", err),
+        )
+    })?;
+
+    let contents = request
+        .documents
+        .iter()
+        .enumerate()
+        .map(|(index, document)| {
+            BASE64.decode(document.content_base64.trim()).map_err(|_| {
+                json_error(
+                    StatusCode::BAD_REQUEST,
+                    format!(
+                        "第 {} 个文件的 content_base64 不是有效的 Base64。",
+                        index + 1
+                    ),
+                )
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut inline: Vec<InlineFile<'_>> = request
+        .documents
+        .iter()
+        .zip(&contents)
+        .map(|(document, bytes)| InlineFile {
+            field_name: "documents",
+            file_name: document.filename.trim(),
+            bytes,
+        })
+        .collect();
+    inline.push(InlineFile {
+        field_name: "spec",
+        file_name: INLINE_SPEC_FILENAME,
+        bytes: &spec_bytes,
+    });
+
+    ensure_storage_root(STORAGE_ROOT)
+        .await
+        .map_err(internal_error)?;
+
+    let job_id = Uuid::new_v4();
+    let job_dir = PathBuf::from(STORAGE_ROOT).join(job_id.to_string());
+
+    let upload = match store_inline_files(&inline, &job_dir, &upload_fields()).await {
+        Ok(outcome) => outcome,
+        Err(err) => {
+            let _ = tokio_fs::remove_dir_all(&job_dir).await;
+            return Err(json_error(
+                StatusCode::BAD_REQUEST,
+                err.message().to_string(),
+            ));
+        }
+    };
+    let documents: Vec<_> = upload.files_for("documents").cloned().collect();
+    let Some(spec_file) = upload.first_file_for("spec").cloned() else {
+        let _ = tokio_fs::remove_dir_all(&job_dir).await;
+        return Err(internal_error(anyhow::anyhow!(
+            "inline spec was not stored"
+        )));
+    };
+
+    start_job(
+        &state,
+        &user,
+        NewJob {
+            job_id,
+            job_dir: &job_dir,
+            spec_file: &spec_file,
+            documents: &documents,
+            fields,
+            layout: WorkbookLayout::from_per_document_sheets(request.per_document_sheets),
+            skip_duplicates: request.skip_duplicates,
+            callback_url,
+        },
+    )
+    .await
+}
+
+/// Writes inline fields as a spec workbook (one column per field, rows: name, description,
+/// `;`-joined examples, `;`-joined allowed values, type), so the job stores a spec that
+/// `parse_extraction_spec` validates now and retries re-read later.
+fn render_spec_workbook(fields: &[JsonField]) -> Result<Vec<u8>, String> {
+    if fields.is_empty() {
+        return Err("请至少定义一个提取字段。".to_string());
+    }
+    if fields.len() > MAX_INLINE_FIELDS {
+        return Err(format!("提取字段最多 {MAX_INLINE_FIELDS} 个。"));
+    }
+    for field in fields {
+        if field.name.trim().is_empty() {
+            return Err("提取字段的 name 不能为空。".to_string());
+        }
+        if field
+            .examples
+            .iter()
+            .chain(&field.allowed_values)
+            .any(|value| value.contains(';'))
+        {
+            return Err(format!(
+                "字段「{}」的示例或枚举值不能包含英文分号。",
+                field.name.trim()
+            ));
+        }
+    }
+
+    write_spec_workbook(fields).map_err(|err| format!("无法生成字段定义表：{err}"))
+}
+
+fn write_spec_workbook(fields: &[JsonField]) -> Result<Vec<u8>> {
+    let mut workbook = Workbook::new();
+    let sheet = workbook.add_worksheet();
+    for (col, field) in fields.iter().enumerate() {
+        let col = u16::try_from(col).context("too many fields")?;
+        let rows = [
+            field.name.trim().to_string(),
+            field.description.clone().unwrap_or_default(),
+            field.examples.join(";"),
+            field.allowed_values.join(";"),
+            field.field_type.clone().unwrap_or_default(),
+        ];
+        for (row, value) in rows.iter().enumerate() {
+            if !value.trim().is_empty() {
+                sheet.write_string(row as u32, col, value)?;
+            }
+        }
+    }
+    Ok(workbook.save_to_buffer()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        modules::info_extract::{FieldType, STATUS_COMPLETED},
+        test_support::{StubProvider, StubReply, create_user, session_jar, test_state},
+    };
+    use serde_json::json;
+    use sqlx::PgPool;
+    use std::time::Duration;
+
+    fn field(name: &str) -> JsonField {
+        JsonField {
+            name: name.to_string(),
+            description: Some("研究开展的地点".to_string()),
+            examples: Vec::new(),
+            allowed_values: Vec::new(),
+            field_type: None,
+        }
+    }
+
+    #[test]
+    fn inline_fields_round_trip_through_the_spec_parser() {
+        let fields = vec![
+            field("研究地点"),
+            JsonField {
+                examples: vec!["120".to_string(), "1,024".to_string()],
+                field_type: Some("number".to_string()),
+                ..field("样本量")
+            },
+            JsonField {
+                description: None,
+                allowed_values: vec!["是".to_string(), "否".to_string()],
+                ..field("随机对照")
+            },
+        ];
+
+        let parsed = parse_extraction_spec(&render_spec_workbook(&fields).unwrap()).unwrap();
+        assert_eq!(parsed.len(), 3);
+        assert_eq!(parsed[0].name, "研究地点");
+        assert_eq!(parsed[1].examples, ["120", "1,024"]);
+        assert_eq!(parsed[1].field_type, FieldType::Number);
+        assert_eq!(parsed[2].description, None);
+        assert_eq!(parsed[2].allowed_values, ["是", "否"]);
+    }
+
+    #[test]
+    fn invalid_inline_fields_are_rejected() {
+        assert!(render_spec_workbook(&[]).is_err());
+        assert!(render_spec_workbook(&[field(" ")]).is_err());
+        let with_semicolon = JsonField {
+            examples: vec!["a;b".to_string()],
+            ..field("地点")
+        };
+        assert!(render_spec_workbook(&[with_semicolon]).is_err());
+
+        // Rules enforced by the spec parser still apply to inline fields.
+        let bare = JsonField {
+            description: None,
+            ..field("地点")
+        };
+        assert!(parse_extraction_spec(&render_spec_workbook(&[bare]).unwrap()).is_err());
+        let bad_type = JsonField {
+            field_type: Some("percent".to_string()),
+            ..field("地点")
+        };
+        assert!(parse_extraction_spec(&render_spec_workbook(&[bad_type]).unwrap()).is_err());
+    }
+
+    #[sqlx::test]
+    #[ignore = "needs DATABASE_URL (disposable Postgres)"]
+    async fn json_submission_runs_like_an_upload(pool: PgPool) {
+        let provider = StubProvider::start(|_| StubReply::text(r#"{"地点": "上海"}"#)).await;
+        let state = test_state(pool.clone(), &provider).await;
+        let user_id = create_user(&pool).await;
+        let submit = |body: serde_json::Value| {
+            let state = state.clone();
+            let pool = pool.clone();
+            async move {
+                let jar = session_jar(&pool, user_id).await;
+                let request = serde_json::from_value(body).unwrap();
+                create_job_json(State(state), jar, Ok(Json(request))).await
+            }
+        };
+
+        let Err((status, _)) = submit(json!({
+            "documents": [{"filename": "paper.txt", "content_base64": "not base64!"}],
+            "fields": [{"name": "地点", "description": "研究开展的城市"}],
+        }))
+        .await
+        else {
+            panic!("undecodable document is rejected");
+        };
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let Json(submission) = submit(json!({
+            "documents": [{
+                "filename": "paper.txt",
+                "content_base64": BASE64.encode("本研究在上海开展。"),
+            }],
+            "fields": [{"name": "地点", "description": "研究开展的城市"}],
+        }))
+        .await
+        .unwrap_or_else(|_| panic!("valid JSON job is accepted"));
+        let job_id = submission.job_id;
+
+        let (spec_filename, spec_path): (String, String) =
+            sqlx::query_as("SELECT spec_filename, spec_path FROM info_extract_jobs WHERE id = $1")
+                .bind(job_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(spec_filename, INLINE_SPEC_FILENAME);
+        assert!(PathBuf::from(spec_path).is_file());
+
+        let mut status = String::new();
+        for _ in 0..100 {
+            status = sqlx::query_scalar("SELECT status FROM info_extract_jobs WHERE id = $1")
+                .bind(job_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+            if status == STATUS_COMPLETED {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        assert_eq!(status, STATUS_COMPLETED);
+        let parsed: serde_json::Value = sqlx::query_scalar(
+            "SELECT parsed_values FROM info_extract_documents WHERE job_id = $1",
+        )
+        .bind(job_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(parsed["地点"], "上海");
+
+        let _ =
+            tokio_fs::remove_dir_all(PathBuf::from(STORAGE_ROOT).join(job_id.to_string())).await;
+    }
+}
@@ -1,5 +1,7 @@
 use std::{
     borrow::Cow,
+    collections::HashSet,
+    future::Future,
     io::Cursor,
     path::{Path, PathBuf},
     sync::Arc,
@@ -9,43 +11,54 @@ use std::{
 use anyhow::{Context, Result, anyhow, bail};
 use axum::{
     Json, Router,
-    extract::{Multipart, Path as AxumPath, State},
-    http::StatusCode,
-    response::{Html, IntoResponse, Redirect},
+    extract::{Multipart, Path as AxumPath, Query, State},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    response::{Html, IntoResponse, Redirect, Response},
     routing::{get, post},
 };
 use axum_extra::extract::cookie::CookieJar;
 use calamine::{DataType, Reader, Xlsx};
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
 use futures::future::join_all;
-use pdf_extract::extract_text as extract_pdf_text;
-use rust_xlsxwriter::Workbook;
-use serde::Serialize;
+use rust_xlsxwriter::{ExcelDateTime, Format, Workbook, Worksheet};
+use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
+use sqlx::PgPool;
 use tokio::{fs as tokio_fs, sync::Semaphore, task, time::sleep};
 use tracing::{error, warn};
 use uuid::Uuid;
 
 mod admin;
+mod api;
 
+use crate::web::download_limit::hold_permit;
 use crate::web::history_ui;
 use crate::web::storage::JobAccess;
 use crate::web::{
-    FileFieldConfig, FileNaming, ToolAdminLink, ToolPageLayout, UPLOAD_WIDGET_SCRIPT,
-    UPLOAD_WIDGET_STYLES, UploadWidgetConfig, process_upload_form, render_tool_page,
-    render_upload_widget,
+    FileFieldConfig, FileNaming, ToolAdminLink, ToolPageLayout, ToolPageUser, UPLOAD_WIDGET_SCRIPT,
+    UPLOAD_WIDGET_STYLES, UploadWidgetConfig, process_upload_form, render_upload_widget,
 };
 use crate::{
     AppState,
-    config::{InfoExtractModels, InfoExtractPrompts},
+    config::{InfoExtractModels, InfoExtractPrompts, effective_concurrency, route_model},
     escape_html, history,
-    llm::{ChatMessage, LlmRequest, MessageRole},
-    render_footer,
+    llm::{
+        ChatMessage, LlmRequest, LlmResponse, MessageRole, ProviderFailureBudget, RetryPolicy,
+        estimate_tokens, is_refusal_error,
+    },
+    notifications, render_footer,
     usage::{self, MODULE_INFO_EXTRACT},
+    utils::{
+        csv::{UTF8_BOM, push_csv_row},
+        document_text::{SUPPORTED_EXTENSIONS, load_document_text},
+        duplicates::{DuplicateRegistry, DuplicateSlot},
+    },
     web::{
-        AccessMessages, ApiMessage, JobStatus, JobSubmission, STATUS_CLIENT_SCRIPT,
+        AccessMessages, ApiMessage, AuthUser, JobStatus, JobSubmission, STATUS_CLIENT_SCRIPT,
+        SavedFile,
         auth::{self, JsonAuthError},
-        ensure_storage_root, json_error, require_path, stream_file, verify_job_access,
+        ensure_job_owner, ensure_storage_root, json_error, quota_error, require_path,
+        stored_file_exists, stream_file, verify_job_access,
     },
 };
 
@@ -54,21 +67,45 @@ const STATUS_PENDING: &str = "pending";
 const STATUS_PROCESSING: &str = "processing";
 const STATUS_COMPLETED: &str = "completed";
 const STATUS_FAILED: &str = "failed";
+const STATUS_CANCELLED: &str = "cancelled";
+const STATUS_SKIPPED: &str = "skipped";
+const CANCELLED_DOCUMENT_DETAIL: &str = "任务已取消，未处理该文献。";
+const PROVIDER_UNAVAILABLE_DETAIL: &str =
+    "模型服务暂不可用（连续多次调用失败），已跳过该文献，请稍后重试。";
 const MAX_DOCUMENTS: usize = 100;
 const MAX_RETRIES: usize = 3;
 const RETRY_DELAY_MS: u64 = 1_500;
+const LLM_RETRY_POLICY: RetryPolicy = RetryPolicy::new(3, Duration::from_millis(RETRY_DELAY_MS));
 const MAX_DOCUMENT_TEXT_CHARS: usize = 20_000;
-const MAX_CONCURRENT_DOCUMENTS: usize = 5;
+const CHUNK_OVERLAP_CHARS: usize = 1_000;
+const MAX_DOCUMENT_CHUNKS: usize = 8;
+/// Excel caps sheet names at 31 characters.
+const MAX_SHEET_NAME_CHARS: usize = 31;
+/// Upper bound on per-document detail sheets, kept well inside what Excel handles comfortably.
+const MAX_DETAIL_SHEETS: usize = 255;
+const SUMMARY_SHEET_NAME: &str = "汇总";
+const INDEX_SHEET_NAME: &str = "文件对照";
 
 pub fn router() -> Router<AppState> {
     Router::new()
         .route("/tools/infoextract", get(info_extract_page))
         .route("/tools/infoextract/jobs", post(create_job))
+        .route("/api/infoextract/jobs", post(api::create_job_json))
+        .route("/api/infoextract/estimate", post(estimate_job))
         .route("/api/infoextract/jobs/:id", get(job_status))
+        .route("/api/infoextract/jobs/:id/cancel", post(cancel_job))
+        .route(
+            "/api/infoextract/jobs/:id/retry-failed",
+            post(retry_failed_documents),
+        )
         .route(
             "/api/infoextract/jobs/:id/download/result",
             get(download_result),
         )
+        .route(
+            "/api/infoextract/jobs/:id/documents/:doc_id/result.json",
+            get(download_document_json),
+        )
         .route("/dashboard/modules/infoextract", get(admin::settings_page))
         .route(
             "/dashboard/modules/infoextract/models",
@@ -78,6 +115,10 @@ pub fn router() -> Router<AppState> {
             "/dashboard/modules/infoextract/prompts",
             post(admin::save_prompts),
         )
+        .route(
+            "/dashboard/modules/infoextract/jobs/:id/documents/:doc_id/raw",
+            get(admin::document_raw_response),
+        )
 }
 
 #[derive(Serialize)]
@@ -85,9 +126,13 @@ struct JobStatusResponse {
     job_id: Uuid,
     status: JobStatus,
     status_label: String,
+    poll_interval_ms: u64,
     status_detail: Option<String>,
     error_message: Option<String>,
     result_download_url: Option<String>,
+    /// Share of documents that have finished (completed, failed, or cancelled), 0–100.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    progress: Option<u8>,
     documents: Vec<JobDocumentStatus>,
 }
 
@@ -100,6 +145,10 @@ struct JobDocumentStatus {
     status_detail: Option<String>,
     error_message: Option<String>,
     attempt_count: i32,
+    result_json_url: Option<String>,
+    /// Admin-only link to the model's stored answer, present once the document has one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    raw_response_url: Option<String>,
 }
 
 #[derive(sqlx::FromRow)]
@@ -119,6 +168,7 @@ struct DocumentRecord {
     status_detail: Option<String>,
     error_message: Option<String>,
     attempt_count: i32,
+    has_raw_response: bool,
 }
 
 #[derive(sqlx::FromRow)]
@@ -136,6 +186,61 @@ struct DownloadRecord {
     files_purged_at: Option<DateTime<Utc>>,
 }
 
+#[derive(sqlx::FromRow)]
+struct RetryRecord {
+    user_id: Uuid,
+    status: String,
+    spec_path: String,
+    per_document_sheets: bool,
+    files_purged_at: Option<DateTime<Utc>>,
+    files_archived_at: Option<DateTime<Utc>>,
+}
+
+impl JobAccess for RetryRecord {
+    fn user_id(&self) -> Uuid {
+        self.user_id
+    }
+
+    fn files_purged_at(&self) -> Option<DateTime<Utc>> {
+        self.files_purged_at
+    }
+}
+
+/// A document kept from an earlier run when only the failed ones are retried.
+#[derive(sqlx::FromRow)]
+struct PriorDocumentRecord {
+    ordinal: i32,
+    original_filename: String,
+    status: String,
+    status_detail: Option<String>,
+    error_message: Option<String>,
+    parsed_values: Option<Value>,
+    validation_warnings: Vec<String>,
+    tokens_used: Option<i64>,
+}
+
+impl PriorDocumentRecord {
+    fn into_result(self) -> DocumentExtractionResult {
+        let success = self.status == STATUS_COMPLETED;
+        let values = match self.parsed_values {
+            Some(Value::Object(map)) if success => Some(map),
+            _ => None,
+        };
+        DocumentExtractionResult {
+            ordinal: self.ordinal,
+            filename: self.original_filename,
+            success: success && values.is_some(),
+            values,
+            error: (!success)
+                .then(|| self.error_message.or(self.status_detail))
+                .flatten(),
+            validation_warnings: self.validation_warnings,
+            tokens_used: self.tokens_used.unwrap_or(0),
+            cancelled: self.status == STATUS_CANCELLED,
+        }
+    }
+}
+
 impl JobAccess for DownloadRecord {
     fn user_id(&self) -> Uuid {
         self.user_id
@@ -152,6 +257,57 @@ struct ExtractionField {
     description: Option<String>,
     examples: Vec<String>,
     allowed_values: Vec<String>,
+    field_type: FieldType,
+}
+
+/// Optional per-field type from row 5 of the spec; controls how values are written to Excel.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum FieldType {
+    #[default]
+    Text,
+    Number,
+    Date,
+}
+
+impl WorkbookLayout {
+    fn from_per_document_sheets(per_document_sheets: bool) -> Self {
+        if per_document_sheets {
+            Self::PerDocumentSheets
+        } else {
+            Self::SingleSheet
+        }
+    }
+}
+
+impl FieldType {
+    fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_lowercase().as_str() {
+            "" | "text" | "文本" => Some(Self::Text),
+            "number" | "数字" | "数值" => Some(Self::Number),
+            "date" | "日期" => Some(Self::Date),
+            _ => None,
+        }
+    }
+}
+
+/// Which documents a `process_job` run extracts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum JobRun {
+    /// Every document of a freshly created job.
+    Full,
+    /// Only documents left `failed` by an earlier run; the others keep their stored results
+    /// and are merged back into the regenerated workbook.
+    RetryFailed,
+}
+
+/// How `generate_result_workbook` lays out the results.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum WorkbookLayout {
+    /// One summary sheet with a row per document.
+    #[default]
+    SingleSheet,
+    /// The summary sheet plus one detail sheet per document holding its fields and raw JSON.
+    PerDocumentSheets,
 }
 
 #[derive(Debug, Clone)]
@@ -160,8 +316,12 @@ struct DocumentExtractionResult {
     filename: String,
     values: Option<Map<String, Value>>,
     error: Option<String>,
+    /// Values that were dropped because they fell outside the field's `allowed_values`.
+    validation_warnings: Vec<String>,
     tokens_used: i64,
     success: bool,
+    /// Set when the job was cancelled before this document started.
+    cancelled: bool,
 }
 
 async fn info_extract_page(
@@ -172,7 +332,7 @@ async fn info_extract_page(
 
     let username = escape_html(&user.username);
     let note_html = format!(
-        "当前登录：<strong>{username}</strong>。上传最多 100 篇 PDF 论文与字段定义表（XLSX），系统将批量抽取自定义信息并生成汇总表。",
+        "当前登录：<strong>{username}</strong>。上传最多 100 篇论文（PDF、DOCX 或 TXT）与字段定义表（XLSX），系统将批量抽取自定义信息并生成汇总表。",
         username = username,
     );
     let admin_link = if user.is_admin {
@@ -183,20 +343,34 @@ async fn info_extract_page(
     } else {
         None
     };
+    let page_user = ToolPageUser {
+        note_html: Cow::Owned(note_html),
+        admin_link,
+    };
+    let html =
+        state
+            .tool_page_cache()
+            .render(MODULE_INFO_EXTRACT, &page_user, info_extract_page_layout);
+
+    Ok(Html(html))
+}
+
+/// Everything on the info-extract page except the per-user header; cached by `ToolPageCache`.
+fn info_extract_page_layout() -> ToolPageLayout<'static> {
     let docs_widget = render_upload_widget(
         &UploadWidgetConfig::new(
             "infoextract-docs",
             "documents",
             "documents",
-            "上传论文（PDF，最多 100 篇）",
+            "上传论文（PDF/DOCX/TXT，最多 100 篇）",
         )
-        .with_description("支持批量上传 PDF，单次任务最多 100 篇。")
+        .with_description("支持批量上传 PDF、DOCX 或 TXT，单次任务最多 100 篇。")
         .with_multiple(Some(MAX_DOCUMENTS))
-        .with_accept(".pdf"),
+        .with_accept(".pdf,.docx,.txt"),
     );
     let spec_widget = render_upload_widget(
         &UploadWidgetConfig::new("infoextract-spec", "spec", "spec", "上传字段定义表（XLSX）")
-            .with_description("第 1 行名称，第 2 行说明，第 3 行示例（分号分隔），第 4 行枚举（分号分隔）。示例与枚举不可同时填写。")
+            .with_description("第 1 行名称，第 2 行说明，第 3 行示例（分号分隔），第 4 行枚举（分号分隔），第 5 行可选类型（text/number/date）。示例与枚举不可同时填写。")
             .with_accept(".xlsx"),
     );
     let history_panel = history_ui::render_history_panel(MODULE_INFO_EXTRACT);
@@ -211,7 +385,12 @@ async fn info_extract_page(
                     <form id="infoextract-form">
 {docs_widget}
 {spec_widget}
+                        <label><input type="checkbox" name="per_document_sheets" id="per-document-sheets"> 每篇文献额外生成独立工作表（含原始 JSON）</label>
+                        <label><input type="checkbox" name="skip_duplicates" id="skip-duplicates"> 跳过内容重复的文献</label>
+                        <label for="callback-url">完成回调地址（可选，https）</label>
+                        <input id="callback-url" name="callback_url" type="url" placeholder="https://example.com/hooks/toolkit">
                         <button type="submit">开始处理</button>
+                        <button type="button" id="estimate-tokens" style="margin-left:0.5rem;background:#475569;">预估 Token</button>
                     </form>
                     <div id="form-status" class="status"></div>
                     <p class="note" style="margin-top:0.75rem;">字段定义表说明：第 1 行名称，第 2 行说明，第 3 行示例（分号分隔），第 4 行枚举（分号分隔）。示例与枚举不可同时填写。</p>
@@ -231,6 +410,7 @@ const jobStatus = document.getElementById('job-status');
 const documentsInput = document.getElementById('documents');
 const specInput = document.getElementById('spec');
 let pollTimer = null;
+let activeStatusUrl = null;
 
 const getStatusLabel = (status, label) => {
     if (label) {
@@ -256,10 +436,9 @@ const setStatus = (message, type = null) => {
 };
 
 const stopPolling = () => {
-    if (pollTimer) {
-        clearInterval(pollTimer);
-        pollTimer = null;
-    }
+    clearTimeout(pollTimer);
+    pollTimer = null;
+    activeStatusUrl = null;
 };
 
 const renderJobStatus = (payload) => {
@@ -276,7 +455,7 @@ const renderJobStatus = (payload) => {
         const error = doc.error_message ? `<div class="note" style="color:#b91c1c;">${doc.error_message}</div>` : '';
         return `
             <tr>
-                <td>${doc.original_filename}</td>
+                <td>${doc.original_filename}${doc.result_json_url ? ` <a href="${doc.result_json_url}">JSON</a>` : ''}${doc.raw_response_url ? ` <a href="${doc.raw_response_url}" target="_blank">原始返回</a>` : ''}</td>
                 <td><span class="${tagClass}">${label}</span></td>
                 <td>${doc.attempt_count ?? 0}</td>
             </tr>
@@ -286,16 +465,27 @@ const renderJobStatus = (payload) => {
     }).join('');
 
     const downloadLink = payload.result_download_url
-        ? `<p class="downloads"><a href="${payload.result_download_url}">下载提取结果 (XLSX)</a></p>`
+        ? `<p class="downloads"><a href="${payload.result_download_url}">下载提取结果 (XLSX)</a> · <a href="${payload.result_download_url}?format=csv">下载 CSV</a></p>`
         : '';
     const statusDetail = payload.status_detail ? `<p class="note">${payload.status_detail}</p>` : '';
     const errorBlock = payload.error_message ? `<p class="note" style="color:#b91c1c;">${payload.error_message}</p>` : '';
 
     const jobStatusLabel = getStatusLabel(payload.status, payload.status_label);
+    const progressBlock = typeof payload.progress === 'number'
+        ? `<p><strong>进度：</strong> <progress max="100" value="${payload.progress}"></progress> ${payload.progress}%</p>`
+        : '';
+    const cancelButton = (payload.status === 'pending' || payload.status === 'processing')
+        ? `<button type="button" style="margin-left:0.75rem;padding:0.35rem 0.8rem;background:#b91c1c;" data-cancel-job="${payload.job_id}">取消任务</button>`
+        : '';
+    const hasFailedDocuments = (payload.documents || []).some((doc) => doc.status === 'failed');
+    const retryButton = (payload.status === 'completed' || payload.status === 'failed') && hasFailedDocuments
+        ? `<button type="button" style="margin-left:0.75rem;padding:0.35rem 0.8rem;background:#475569;" data-retry-job="${payload.job_id}">重试失败文献</button>`
+        : '';
 
     jobStatus.innerHTML = `
         <div class="status">
-            <p><strong>任务状态：</strong> ${jobStatusLabel}</p>
+            <p><strong>任务状态：</strong> ${jobStatusLabel} ${cancelButton} ${retryButton}</p>
+            ${progressBlock}
             ${statusDetail}
             ${errorBlock}
             <table class="job-table">
@@ -316,20 +506,105 @@ const fetchJobStatus = async (url) => {
         const payload = await response.json();
         renderJobStatus(payload);
 
-        if (payload.status === 'completed' || payload.status === 'failed') {
+        if (['completed', 'failed', 'cancelled'].includes(payload.status)) {
             stopPolling();
         }
+        return payload;
     } catch (error) {
         stopPolling();
         setStatus('轮询失败：' + error.message, 'error');
+        return null;
+    }
+};
+
+const pollJobStatus = async (url) => {
+    const payload = await fetchJobStatus(url);
+    if (!payload || url !== activeStatusUrl) {
+        return;
+    }
+    const delay = window.nextPollDelay(payload, 4000);
+    if (delay) {
+        pollTimer = setTimeout(() => pollJobStatus(url), delay);
     }
 };
 
+jobStatus.addEventListener('click', async (event) => {
+    const button = event.target.closest('[data-retry-job]');
+    if (!button) {
+        return;
+    }
+
+    const jobId = button.dataset.retryJob;
+    button.disabled = true;
+    try {
+        const response = await fetch(`/api/infoextract/jobs/${jobId}/retry-failed`, { method: 'POST' });
+        const payload = await response.json().catch(() => ({ message: '重试失败。' }));
+        setStatus(payload.message || '重试失败。', response.ok ? 'success' : 'error');
+        if (!response.ok) {
+            button.disabled = false;
+            return;
+        }
+        stopPolling();
+        activeStatusUrl = `/api/infoextract/jobs/${jobId}`;
+        pollJobStatus(activeStatusUrl);
+    } catch (error) {
+        button.disabled = false;
+        setStatus('重试失败：' + error.message, 'error');
+    }
+});
+
+jobStatus.addEventListener('click', async (event) => {
+    const button = event.target.closest('[data-cancel-job]');
+    if (!button) {
+        return;
+    }
+    if (!confirm('确定要取消该任务吗？已完成的文献结果会保留。')) {
+        return;
+    }
+
+    const jobId = button.dataset.cancelJob;
+    button.disabled = true;
+    try {
+        const response = await fetch(`/api/infoextract/jobs/${jobId}/cancel`, { method: 'POST' });
+        const payload = await response.json().catch(() => ({ message: '取消失败。' }));
+        setStatus(payload.message || '取消失败。', response.ok ? 'success' : 'error');
+        fetchJobStatus(`/api/infoextract/jobs/${jobId}`);
+    } catch (error) {
+        button.disabled = false;
+        setStatus('取消失败：' + error.message, 'error');
+    }
+});
+
+document.getElementById('estimate-tokens').addEventListener('click', async () => {
+    if (!documentsInput || documentsInput.files.length === 0 || !specInput || specInput.files.length === 0) {
+        setStatus('请先选择论文和字段定义表。', 'error');
+        return;
+    }
+
+    setStatus('正在估算 Token...', null);
+    try {
+        const response = await fetch('/api/infoextract/estimate', {
+            method: 'POST',
+            body: new FormData(form),
+        });
+        const payload = await response.json().catch(() => ({ message: '估算失败。' }));
+        if (!response.ok) {
+            setStatus(payload.message || '估算失败。', 'error');
+            return;
+        }
+        const unreadable = payload.documents.filter((doc) => doc.error).length;
+        const note = unreadable ? `，${unreadable} 篇无法读取` : '';
+        setStatus(`预计输入约 ${payload.total_prompt_tokens.toLocaleString()} Token，共 ${payload.total_requests} 次模型调用（${payload.documents.length} 篇${note}），不含重试与模型输出。`, 'success');
+    } catch (error) {
+        setStatus('估算失败：' + error.message, 'error');
+    }
+});
+
 form.addEventListener('submit', async (event) => {
     event.preventDefault();
 
     if (!documentsInput || documentsInput.files.length === 0) {
-        setStatus('请至少上传一篇 PDF。', 'error');
+        setStatus('请至少上传一篇论文。', 'error');
         return;
     }
     if (documentsInput.files.length > __MAX_DOCS__) {
@@ -360,8 +635,8 @@ form.addEventListener('submit', async (event) => {
 
         const payload = await response.json();
         setStatus('任务已创建，正在处理...', 'success');
-        fetchJobStatus(payload.status_url);
-        pollTimer = setInterval(() => fetchJobStatus(payload.status_url), 4000);
+        activeStatusUrl = payload.status_url;
+        pollJobStatus(payload.status_url);
         form.reset();
         if (documentsInput) {
             documentsInput.value = '';
@@ -379,17 +654,14 @@ form.addEventListener('submit', async (event) => {
 
     let info_extract_script = script_template.replace("__MAX_DOCS__", &MAX_DOCUMENTS.to_string());
 
-    let html = render_tool_page(ToolPageLayout {
+    ToolPageLayout {
         meta_title: "信息提取 | Zhang Group AI Toolkit",
         page_heading: "信息提取",
-        username: &username,
-        note_html: Cow::Owned(note_html),
         tab_group: "info_extract",
         new_tab_label: "新任务",
         new_tab_html: Cow::Owned(new_tab_html),
         history_tab_label: "历史记录",
         history_panel_html: Cow::Owned(history_panel),
-        admin_link,
         footer_html: Cow::Owned(render_footer()),
         extra_style_blocks: vec![
             Cow::Borrowed(history_ui::HISTORY_STYLES),
@@ -412,9 +684,7 @@ form.addEventListener('submit', async (event) => {
                 history_ui::HISTORY_SCRIPT
             )),
         ],
-    });
-
-    Ok(Html(html))
+    }
 }
 
 async fn create_job(
@@ -433,25 +703,7 @@ async fn create_job(
     let job_id = Uuid::new_v4();
     let job_dir = PathBuf::from(STORAGE_ROOT).join(job_id.to_string());
 
-    let docs_config = FileFieldConfig::new(
-        "documents",
-        &["pdf"],
-        MAX_DOCUMENTS,
-        FileNaming::Indexed {
-            prefix: "paper_",
-            pad_width: 3,
-        },
-    )
-    .with_min_files(1);
-    let spec_config = FileFieldConfig::new(
-        "spec",
-        &["xlsx"],
-        1,
-        FileNaming::PrefixOnly { prefix: "spec_" },
-    )
-    .with_min_files(1);
-
-    let upload = match process_upload_form(multipart, &job_dir, &[docs_config, spec_config]).await {
+    let upload = match process_upload_form(multipart, &job_dir, &upload_fields()).await {
         Ok(outcome) => outcome,
         Err(err) => {
             let _ = tokio_fs::remove_dir_all(&job_dir).await;
@@ -462,21 +714,31 @@ async fn create_job(
         }
     };
 
+    let layout = match upload.first_text("per_document_sheets").map(str::trim) {
+        Some("on" | "true" | "1" | "yes") => WorkbookLayout::PerDocumentSheets,
+        _ => WorkbookLayout::SingleSheet,
+    };
+    let skip_duplicates = matches!(
+        upload.first_text("skip_duplicates").map(str::trim),
+        Some("on" | "true" | "1" | "yes")
+    );
+    let callback_url = match notifications::parse_callback_url(upload.first_text("callback_url")) {
+        Ok(url) => url,
+        Err(message) => {
+            let _ = tokio_fs::remove_dir_all(&job_dir).await;
+            return Err(json_error(StatusCode::BAD_REQUEST, message));
+        }
+    };
+
     let documents: Vec<_> = upload.files_for("documents").cloned().collect();
     if documents.is_empty() {
         let _ = tokio_fs::remove_dir_all(&job_dir).await;
-        return Err(json_error(
-            StatusCode::BAD_REQUEST,
-            "请至少上传一篇 PDF 论文。",
-        ));
+        return Err(json_error(StatusCode::BAD_REQUEST, "请至少上传一篇论文。"));
     }
 
     if documents.is_empty() {
         let _ = tokio_fs::remove_dir_all(&job_dir).await;
-        return Err(json_error(
-            StatusCode::BAD_REQUEST,
-            "请至少上传一篇 PDF 论文。",
-        ));
+        return Err(json_error(StatusCode::BAD_REQUEST, "请至少上传一篇论文。"));
     }
 
     let spec_file = match upload.first_file_for("spec").cloned() {
@@ -504,14 +766,60 @@ async fn create_job(
         }
     };
 
+    start_job(
+        &state,
+        &user,
+        NewJob {
+            job_id,
+            job_dir: &job_dir,
+            spec_file: &spec_file,
+            documents: &documents,
+            fields,
+            layout,
+            skip_duplicates,
+            callback_url,
+        },
+    )
+    .await
+}
+
+/// A validated submission, from either the multipart form or the JSON API.
+struct NewJob<'a> {
+    job_id: Uuid,
+    job_dir: &'a Path,
+    spec_file: &'a SavedFile,
+    documents: &'a [SavedFile],
+    fields: Vec<ExtractionField>,
+    layout: WorkbookLayout,
+    skip_duplicates: bool,
+    callback_url: Option<String>,
+}
+
+/// Checks the user's quota, inserts the job with its documents and starts the worker.
+async fn start_job(
+    state: &AppState,
+    user: &AuthUser,
+    job: NewJob<'_>,
+) -> Result<Json<JobSubmission>, (StatusCode, Json<ApiMessage>)> {
+    let NewJob {
+        job_id,
+        job_dir,
+        spec_file,
+        documents,
+        fields,
+        layout,
+        skip_duplicates,
+        callback_url,
+    } = job;
+
     let pool = state.pool();
 
     if let Err(err) =
         usage::ensure_within_limits(&pool, user.id, MODULE_INFO_EXTRACT, documents.len() as i64)
             .await
     {
-        let _ = tokio_fs::remove_dir_all(&job_dir).await;
-        return Err(json_error(StatusCode::FORBIDDEN, err.message()));
+        let _ = tokio_fs::remove_dir_all(job_dir).await;
+        return Err(quota_error(StatusCode::FORBIDDEN, &err));
     }
 
     let mut transaction = pool
@@ -520,14 +828,17 @@ async fn create_job(
         .map_err(|err| internal_error(err.into()))?;
 
     sqlx::query(
-        "INSERT INTO info_extract_jobs (id, user_id, status, spec_filename, spec_path)
-         VALUES ($1, $2, $3, $4, $5)",
+        "INSERT INTO info_extract_jobs (id, user_id, status, spec_filename, spec_path, skip_duplicates, callback_url, per_document_sheets)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
     )
     .bind(job_id)
     .bind(user.id)
     .bind(STATUS_PENDING)
     .bind(&spec_file.original_name)
     .bind(spec_file.stored_path.to_string_lossy().to_string())
+    .bind(skip_duplicates)
+    .bind(&callback_url)
+    .bind(layout == WorkbookLayout::PerDocumentSheets)
     .execute(&mut *transaction)
     .await
     .map_err(|err| internal_error(err.into()))?;
@@ -559,7 +870,7 @@ async fn create_job(
         error!(?err, %job_id, "failed to record info extract job history");
     }
 
-    spawn_job_worker(state.clone(), job_id, fields);
+    spawn_job_worker(state.clone(), job_id, fields, layout, JobRun::Full);
 
     Ok(Json(JobSubmission::new(
         job_id,
@@ -567,6 +878,165 @@ async fn create_job(
     )))
 }
 
+/// The documents and spec fields shared by job creation and the token estimate.
+fn upload_fields() -> [FileFieldConfig<'static>; 2] {
+    [
+        FileFieldConfig::new(
+            "documents",
+            SUPPORTED_EXTENSIONS,
+            MAX_DOCUMENTS,
+            FileNaming::Indexed {
+                prefix: "paper_",
+                pad_width: 3,
+            },
+        )
+        .with_min_files(1),
+        FileFieldConfig::new(
+            "spec",
+            &["xlsx"],
+            1,
+            FileNaming::PrefixOnly { prefix: "spec_" },
+        )
+        .with_min_files(1),
+    ]
+}
+
+#[derive(Serialize)]
+struct TokenEstimate {
+    /// Estimated prompt tokens over every request the job would send, before retries.
+    total_prompt_tokens: u64,
+    total_requests: usize,
+    documents: Vec<DocumentTokenEstimate>,
+}
+
+#[derive(Serialize)]
+struct DocumentTokenEstimate {
+    filename: String,
+    prompt_tokens: u64,
+    /// One request per text window; long papers are split like the worker splits them.
+    requests: usize,
+    /// True when the paper is longer than the windows the worker would read.
+    truncated: bool,
+    error: Option<String>,
+}
+
+/// Accepts the same multipart form as `create_job` and estimates the prompt tokens the job
+/// would send, without creating a job, charging usage or calling a model. The uploads are
+/// deleted before answering.
+async fn estimate_job(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    multipart: Multipart,
+) -> Result<Json<TokenEstimate>, (StatusCode, Json<ApiMessage>)> {
+    auth::current_user_or_json_error(&state, &jar)
+        .await
+        .map_err(|JsonAuthError { status, message }| json_error(status, message))?;
+
+    ensure_storage_root(STORAGE_ROOT)
+        .await
+        .map_err(internal_error)?;
+
+    let temp_dir = PathBuf::from(STORAGE_ROOT).join(format!("estimate_{}", Uuid::new_v4()));
+    let result = estimate_uploads(&state, multipart, &temp_dir).await;
+    let _ = tokio_fs::remove_dir_all(&temp_dir).await;
+    result.map(Json)
+}
+
+async fn estimate_uploads(
+    state: &AppState,
+    multipart: Multipart,
+    temp_dir: &Path,
+) -> Result<TokenEstimate, (StatusCode, Json<ApiMessage>)> {
+    let upload = process_upload_form(multipart, temp_dir, &upload_fields())
+        .await
+        .map_err(|err| json_error(StatusCode::BAD_REQUEST, err.message().to_string()))?;
+
+    let spec_file = upload
+        .first_file_for("spec")
+        .ok_or_else(|| json_error(StatusCode::BAD_REQUEST, "请上传字段定义表 XLSX。"))?;
+    let spec_bytes = tokio_fs::read(&spec_file.stored_path)
+        .await
+        .map_err(|err| internal_error(err.into()))?;
+    let fields = parse_extraction_spec(&spec_bytes).map_err(|err| {
+        json_error(
+            StatusCode::BAD_REQUEST,
+            format!("字段定义表格式错误：{}", err),
+        )
+    })?;
+
+    let prompts = state
+        .info_extract_settings()
+        .await
+        .unwrap_or_default()
+        .prompts;
+
+    let mut documents = Vec::new();
+    for file in upload.files_for("documents") {
+        let path = file.stored_path.clone();
+        let text = task::spawn_blocking(move || load_document_text(&path))
+            .await
+            .unwrap_or_else(|err| Err(anyhow!(err)));
+        documents.push(match text {
+            Ok(document_text) => {
+                estimate_document_tokens(&file.original_name, &fields, &prompts, &document_text.text)
+            }
+            Err(err) => {
+                warn!(?err, file = %file.original_name, "failed to read document for token estimate");
+                DocumentTokenEstimate {
+                    filename: file.original_name.clone(),
+                    prompt_tokens: 0,
+                    requests: 0,
+                    truncated: false,
+                    error: Some("无法读取文献内容".to_string()),
+                }
+            }
+        });
+    }
+
+    Ok(TokenEstimate {
+        total_prompt_tokens: documents.iter().map(|doc| doc.prompt_tokens).sum(),
+        total_requests: documents.iter().map(|doc| doc.requests).sum(),
+        documents,
+    })
+}
+
+/// Prompt tokens for one paper: the system prompt plus the spec prompt around each text
+/// window, as `process_single_document` would build them, counted with `estimate_tokens`.
+fn estimate_document_tokens(
+    filename: &str,
+    fields: &[ExtractionField],
+    prompts: &InfoExtractPrompts,
+    text: &str,
+) -> DocumentTokenEstimate {
+    let chunks = split_document_text(text);
+    let total_chunks = chunks.windows.len();
+    let system_tokens = estimate_tokens(prompts.system_prompt.trim());
+    let prompt_tokens = chunks
+        .windows
+        .iter()
+        .enumerate()
+        .map(|(idx, window)| {
+            let chunk = (total_chunks > 1).then_some((idx + 1, total_chunks));
+            let user_prompt = build_user_prompt(
+                filename,
+                fields,
+                prompts.response_guidance.trim(),
+                window,
+                chunk,
+            );
+            system_tokens + estimate_tokens(&user_prompt)
+        })
+        .sum();
+
+    DocumentTokenEstimate {
+        filename: filename.to_string(),
+        prompt_tokens,
+        requests: total_chunks,
+        truncated: chunks.truncated,
+        error: None,
+    }
+}
+
 async fn job_status(
     State(state): State<AppState>,
     jar: CookieJar,
@@ -593,15 +1063,16 @@ async fn job_status(
         )
     })?;
 
-    if job.user_id != user.id && !user.is_admin {
-        return Err((
-            StatusCode::FORBIDDEN,
-            Json(ApiMessage::new("您无权访问该任务。")),
-        ));
-    }
+    ensure_job_owner(
+        job.user_id,
+        &user,
+        "未找到任务或任务已过期。",
+        "您无权访问该任务。",
+    )?;
 
     let documents = sqlx::query_as::<_, DocumentRecord>(
-        "SELECT id, original_filename, status, status_detail, error_message, attempt_count
+        "SELECT id, original_filename, status, status_detail, error_message, attempt_count,
+                response_text IS NOT NULL AS has_raw_response
          FROM info_extract_documents WHERE job_id = $1 ORDER BY ordinal",
     )
     .bind(job_id)
@@ -614,10 +1085,24 @@ async fn job_status(
         .as_ref()
         .map(|_| format!("/api/infoextract/jobs/{}/download/result", job_id));
 
+    let progress = job_progress(documents.iter().map(|doc| doc.status.as_str()));
+
     let documents = documents
         .into_iter()
         .map(|doc| {
             let status = JobStatus::from_str(&doc.status);
+            let result_json_url = (status == JobStatus::Completed).then(|| {
+                format!(
+                    "/api/infoextract/jobs/{}/documents/{}/result.json",
+                    job_id, doc.id
+                )
+            });
+            let raw_response_url = (doc.has_raw_response && user.is_admin).then(|| {
+                format!(
+                    "/dashboard/modules/infoextract/jobs/{}/documents/{}/raw",
+                    job_id, doc.id
+                )
+            });
             JobDocumentStatus {
                 id: doc.id,
                 original_filename: doc.original_filename,
@@ -626,6 +1111,8 @@ async fn job_status(
                 status_detail: doc.status_detail,
                 error_message: doc.error_message,
                 attempt_count: doc.attempt_count,
+                result_json_url,
+                raw_response_url,
             }
         })
         .collect();
@@ -635,76 +1122,410 @@ async fn job_status(
     Ok(Json(JobStatusResponse {
         job_id,
         status_label: status.label_zh().to_string(),
+        poll_interval_ms: status.poll_interval_ms(),
         status,
         status_detail: job.status_detail,
         error_message: job.error_message,
         result_download_url,
+        progress,
         documents,
     }))
 }
 
-async fn download_result(
+/// Percentage of documents in a terminal state, or `None` for a job without documents.
+fn job_progress<'a>(statuses: impl Iterator<Item = &'a str>) -> Option<u8> {
+    let (finished, total) = statuses.fold((0usize, 0usize), |(finished, total), status| {
+        let done = matches!(
+            status,
+            STATUS_COMPLETED | STATUS_FAILED | STATUS_CANCELLED | STATUS_SKIPPED
+        );
+        (finished + usize::from(done), total + 1)
+    });
+    (total > 0).then(|| (finished * 100 / total) as u8)
+}
+
+/// Requests cancellation of a pending or running job. Documents that have not started yet are
+/// marked cancelled; documents already in flight finish and keep their results.
+async fn cancel_job(
     State(state): State<AppState>,
     jar: CookieJar,
     AxumPath(job_id): AxumPath<Uuid>,
-) -> Result<impl IntoResponse, (StatusCode, Json<ApiMessage>)> {
+) -> Result<Json<ApiMessage>, (StatusCode, Json<ApiMessage>)> {
     let user = auth::current_user_or_json_error(&state, &jar)
         .await
         .map_err(|JsonAuthError { status, message }| json_error(status, message))?;
 
     let pool = state.pool();
-    let record = verify_job_access(
-        || {
-            sqlx::query_as::<_, DownloadRecord>(
-                "SELECT user_id, result_path, files_purged_at FROM info_extract_jobs WHERE id = $1",
-            )
+
+    let (owner_id, status): (Uuid, String) =
+        sqlx::query_as("SELECT user_id, status FROM info_extract_jobs WHERE id = $1")
             .bind(job_id)
             .fetch_optional(&pool)
-        },
+            .await
+            .map_err(|err| internal_error(err.into()))?
+            .ok_or_else(|| json_error(StatusCode::NOT_FOUND, "未找到任务或任务已过期。"))?;
+
+    ensure_job_owner(
+        owner_id,
         &user,
-        AccessMessages {
-            not_found: "未找到任务或暂无可下载结果。",
-            forbidden: "您无权下载该任务的结果。",
-            purged: "结果文件已过期并被清除。",
-        },
-    )
-    .await?;
+        "未找到任务或任务已过期。",
+        "您无权取消该任务。",
+    )?;
 
-    let result_path = require_path(record.result_path.clone(), "任务尚未生成结果。")?;
-    let filename = format!("info_extract_{}.xlsx", job_id);
+    if !is_cancellable(&status) {
+        return Err(json_error(StatusCode::CONFLICT, "任务已结束，无法取消。"));
+    }
 
-    stream_file(
-        Path::new(&result_path),
-        &filename,
-        "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+    let updated = sqlx::query(
+        "UPDATE info_extract_jobs SET status = $2, status_detail = $3, updated_at = NOW()
+         WHERE id = $1 AND status IN ($4, $5)",
     )
+    .bind(job_id)
+    .bind(STATUS_CANCELLED)
+    .bind("已请求取消，正在等待处理中的文献结束。")
+    .bind(STATUS_PENDING)
+    .bind(STATUS_PROCESSING)
+    .execute(&pool)
     .await
-}
+    .map_err(|err| internal_error(err.into()))?;
 
-fn ensure_status_detail(truncated: bool) -> Option<String> {
-    if truncated {
-        Some(format!(
-            "正文超过 {} 字符，已截断后送入模型。",
-            MAX_DOCUMENT_TEXT_CHARS
-        ))
-    } else {
-        None
+    if updated.rows_affected() == 0 {
+        return Err(json_error(StatusCode::CONFLICT, "任务已结束，无法取消。"));
     }
-}
 
-fn split_semicolon(input: &str) -> Vec<String> {
-    input
-        .split(';')
-        .map(|item| item.trim())
-        .filter(|item| !item.is_empty())
-        .map(|item| item.to_string())
-        .collect()
+    sqlx::query(
+        "UPDATE info_extract_documents SET status = $2, status_detail = $3, updated_at = NOW()
+         WHERE job_id = $1 AND status = $4",
+    )
+    .bind(job_id)
+    .bind(STATUS_CANCELLED)
+    .bind(CANCELLED_DOCUMENT_DETAIL)
+    .bind(STATUS_PENDING)
+    .execute(&pool)
+    .await
+    .map_err(|err| internal_error(err.into()))?;
+
+    Ok(Json(ApiMessage::new(
+        "已取消任务，未开始的文献将不再处理。",
+    )))
 }
 
-fn cell_to_string(cell: Option<&DataType>) -> Option<String> {
-    let value = cell?;
-    let text = match value {
-        DataType::String(s) => s.trim().to_string(),
+/// Re-queues the documents of a finished job that ended `failed` and reruns only those; the
+/// result workbook is regenerated from the stored results of the others plus the new ones.
+async fn retry_failed_documents(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    AxumPath(job_id): AxumPath<Uuid>,
+) -> Result<Json<ApiMessage>, (StatusCode, Json<ApiMessage>)> {
+    let user = auth::current_user_or_json_error(&state, &jar)
+        .await
+        .map_err(|JsonAuthError { status, message }| json_error(status, message))?;
+
+    let pool = state.pool();
+    let job = verify_job_access(
+        || {
+            sqlx::query_as::<_, RetryRecord>(
+                "SELECT user_id, status, spec_path, per_document_sheets, files_purged_at,
+                        files_archived_at
+                 FROM info_extract_jobs WHERE id = $1",
+            )
+            .bind(job_id)
+            .fetch_optional(&pool)
+        },
+        &user,
+        AccessMessages {
+            not_found: "未找到任务或任务已过期。",
+            forbidden: "您无权重试该任务。",
+            purged: "任务文件已过期并被清除，无法重试。",
+        },
+    )
+    .await?;
+
+    if !is_retryable(&job.status) {
+        return Err(json_error(
+            StatusCode::CONFLICT,
+            "任务尚未结束或已取消，无法重试。",
+        ));
+    }
+    if job.files_archived_at.is_some() {
+        return Err(json_error(
+            StatusCode::CONFLICT,
+            "任务文件已压缩归档，无法重试。",
+        ));
+    }
+
+    let failed: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM info_extract_documents WHERE job_id = $1 AND status = $2",
+    )
+    .bind(job_id)
+    .bind(STATUS_FAILED)
+    .fetch_one(&pool)
+    .await
+    .map_err(|err| internal_error(err.into()))?;
+    if failed == 0 {
+        return Err(json_error(
+            StatusCode::BAD_REQUEST,
+            "该任务没有失败的文献需要重试。",
+        ));
+    }
+
+    if let Err(err) = usage::ensure_within_limits(&pool, user.id, MODULE_INFO_EXTRACT, failed).await
+    {
+        return Err(quota_error(StatusCode::FORBIDDEN, &err));
+    }
+
+    let spec_bytes = tokio_fs::read(&job.spec_path)
+        .await
+        .map_err(|err| internal_error(err.into()))?;
+    let fields = parse_extraction_spec(&spec_bytes).map_err(internal_error)?;
+
+    let mut transaction = pool
+        .begin()
+        .await
+        .map_err(|err| internal_error(err.into()))?;
+
+    let requeued = sqlx::query(
+        "UPDATE info_extract_jobs SET status = $2, status_detail = $3, error_message = NULL, updated_at = NOW()
+         WHERE id = $1 AND status IN ($4, $5)",
+    )
+    .bind(job_id)
+    .bind(STATUS_PENDING)
+    .bind(format!("已重新排队 {} 篇失败的文献。", failed))
+    .bind(STATUS_COMPLETED)
+    .bind(STATUS_FAILED)
+    .execute(&mut *transaction)
+    .await
+    .map_err(|err| internal_error(err.into()))?;
+    if requeued.rows_affected() == 0 {
+        return Err(json_error(
+            StatusCode::CONFLICT,
+            "任务尚未结束或已取消，无法重试。",
+        ));
+    }
+
+    sqlx::query(
+        "UPDATE info_extract_documents SET status = $2, status_detail = NULL, error_message = NULL, updated_at = NOW()
+         WHERE job_id = $1 AND status = $3",
+    )
+    .bind(job_id)
+    .bind(STATUS_PENDING)
+    .bind(STATUS_FAILED)
+    .execute(&mut *transaction)
+    .await
+    .map_err(|err| internal_error(err.into()))?;
+
+    transaction
+        .commit()
+        .await
+        .map_err(|err| internal_error(err.into()))?;
+
+    spawn_job_worker(
+        state.clone(),
+        job_id,
+        fields,
+        WorkbookLayout::from_per_document_sheets(job.per_document_sheets),
+        JobRun::RetryFailed,
+    );
+
+    Ok(Json(ApiMessage::new(format!(
+        "已重新提交 {} 篇失败的文献。",
+        failed
+    ))))
+}
+
+fn is_retryable(status: &str) -> bool {
+    status == STATUS_COMPLETED || status == STATUS_FAILED
+}
+
+fn is_cancellable(status: &str) -> bool {
+    status == STATUS_PENDING || status == STATUS_PROCESSING
+}
+
+async fn job_is_cancelled(pool: &PgPool, job_id: Uuid) -> bool {
+    match sqlx::query_scalar::<_, String>("SELECT status FROM info_extract_jobs WHERE id = $1")
+        .bind(job_id)
+        .fetch_optional(pool)
+        .await
+    {
+        Ok(status) => status.as_deref() == Some(STATUS_CANCELLED),
+        Err(err) => {
+            warn!(?err, %job_id, "读取任务状态失败");
+            false
+        }
+    }
+}
+
+#[derive(Deserialize, Default)]
+struct DownloadQuery {
+    format: Option<String>,
+}
+
+async fn download_result(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    AxumPath(job_id): AxumPath<Uuid>,
+    Query(query): Query<DownloadQuery>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ApiMessage>)> {
+    let user = auth::current_user_or_json_error(&state, &jar)
+        .await
+        .map_err(|JsonAuthError { status, message }| json_error(status, message))?;
+
+    let pool = state.pool();
+    let record = verify_job_access(
+        || {
+            sqlx::query_as::<_, DownloadRecord>(
+                "SELECT user_id, result_path, files_purged_at FROM info_extract_jobs WHERE id = $1",
+            )
+            .bind(job_id)
+            .fetch_optional(&pool)
+        },
+        &user,
+        AccessMessages {
+            not_found: "未找到任务或暂无可下载结果。",
+            forbidden: "您无权下载该任务的结果。",
+            purged: "结果文件已过期并被清除。",
+        },
+    )
+    .await?;
+
+    let result_path = require_path(record.result_path.clone(), "任务尚未生成结果。")?;
+    let permit = state.download_limiter().acquire(&user)?;
+
+    let response = match query.format.as_deref().map(str::trim) {
+        None | Some("") | Some("xlsx") => {
+            let filename = format!("info_extract_{}.xlsx", job_id);
+            stream_file(
+                Path::new(&result_path),
+                &filename,
+                "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+            )
+            .await
+        }
+        Some("csv") => {
+            let csv_path = Path::new(&result_path).with_extension("csv");
+            if !stored_file_exists(&csv_path).await {
+                return Err(json_error(
+                    StatusCode::NOT_FOUND,
+                    "该任务未生成 CSV 结果，请下载 XLSX 文件。",
+                ));
+            }
+            let filename = format!("info_extract_{}.csv", job_id);
+            stream_file(&csv_path, &filename, "text/csv; charset=utf-8").await
+        }
+        Some(_) => Err(json_error(
+            StatusCode::BAD_REQUEST,
+            "不支持的下载格式，仅支持 xlsx 或 csv。",
+        )),
+    }?;
+    Ok(hold_permit(response, permit))
+}
+
+/// Serves a single document's stored `parsed_values` as a JSON attachment.
+async fn download_document_json(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    AxumPath((job_id, document_id)): AxumPath<(Uuid, Uuid)>,
+) -> Result<Response, (StatusCode, Json<ApiMessage>)> {
+    let user = auth::current_user_or_json_error(&state, &jar)
+        .await
+        .map_err(|JsonAuthError { status, message }| json_error(status, message))?;
+
+    let pool = state.pool();
+    verify_job_access(
+        || {
+            sqlx::query_as::<_, DownloadRecord>(
+                "SELECT user_id, result_path, files_purged_at FROM info_extract_jobs WHERE id = $1",
+            )
+            .bind(job_id)
+            .fetch_optional(&pool)
+        },
+        &user,
+        AccessMessages {
+            not_found: "未找到任务或暂无可下载结果。",
+            forbidden: "您无权下载该任务的结果。",
+            purged: "结果文件已过期并被清除。",
+        },
+    )
+    .await?;
+
+    let (ordinal, parsed_values): (i32, Option<Value>) = sqlx::query_as(
+        "SELECT ordinal, parsed_values FROM info_extract_documents WHERE id = $1 AND job_id = $2",
+    )
+    .bind(document_id)
+    .bind(job_id)
+    .fetch_optional(&pool)
+    .await
+    .map_err(|err| internal_error(err.into()))?
+    .ok_or_else(|| json_error(StatusCode::NOT_FOUND, "未找到该文献。"))?;
+
+    let parsed_values =
+        parsed_values.ok_or_else(|| json_error(StatusCode::NOT_FOUND, "该文献暂无提取结果。"))?;
+    let permit = state.download_limiter().acquire(&user)?;
+
+    let body =
+        serde_json::to_vec_pretty(&parsed_values).map_err(|err| internal_error(err.into()))?;
+    let disposition = format!(
+        "attachment; filename=\"info_extract_{}_{:03}.json\"",
+        job_id, ordinal
+    );
+    let disposition = HeaderValue::from_str(&disposition)
+        .map_err(|_| json_error(StatusCode::INTERNAL_SERVER_ERROR, "下载头信息无效。"))?;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/json; charset=utf-8"),
+    );
+    headers.insert(header::CONTENT_DISPOSITION, disposition);
+
+    Ok(hold_permit((headers, body).into_response(), permit))
+}
+
+fn ensure_status_detail(
+    chunks: &DocumentChunks,
+    failed_chunks: usize,
+    ocr_used: bool,
+) -> Option<String> {
+    let total = chunks.windows.len();
+    let mut parts = Vec::new();
+
+    if ocr_used {
+        parts.push("PDF 无可用文本层，已通过 OCR 识别正文。".to_string());
+    }
+
+    if total > 1 {
+        parts.push(format!("正文较长，已分 {total} 段提取并合并结果。"));
+    }
+    if chunks.truncated {
+        parts.push(format!(
+            "正文超过 {} 段上限，其余内容未送入模型。",
+            MAX_DOCUMENT_CHUNKS
+        ));
+    }
+    if failed_chunks > 0 {
+        parts.push(format!("其中 {failed_chunks} 段提取失败。"));
+    }
+
+    if parts.is_empty() {
+        None
+    } else {
+        Some(parts.join(""))
+    }
+}
+
+fn split_semicolon(input: &str) -> Vec<String> {
+    input
+        .split(';')
+        .map(|item| item.trim())
+        .filter(|item| !item.is_empty())
+        .map(|item| item.to_string())
+        .collect()
+}
+
+fn cell_to_string(cell: Option<&DataType>) -> Option<String> {
+    let value = cell?;
+    let text = match value {
+        DataType::String(s) => s.trim().to_string(),
         DataType::Float(f) => {
             let mut s = format!("{f}");
             if s.ends_with(".0") {
@@ -755,6 +1576,16 @@ fn parse_extraction_spec(bytes: &[u8]) -> Result<Vec<ExtractionField>> {
         let description = cell_to_string(range.get((1, col_idx)));
         let examples = cell_to_string(range.get((2, col_idx)));
         let allowed = cell_to_string(range.get((3, col_idx)));
+        let field_type = match cell_to_string(range.get((4, col_idx))) {
+            Some(raw) => FieldType::parse(&raw).ok_or_else(|| {
+                anyhow!(
+                    "第 {} 列的类型「{}」无效，应为 text、number 或 date。",
+                    col_idx + 1,
+                    raw.trim()
+                )
+            })?,
+            None => FieldType::Text,
+        };
 
         if description.is_none() && examples.is_none() && allowed.is_none() {
             bail!("第 {} 列至少需要填写说明、示例或枚举之一。", col_idx + 1);
@@ -771,23 +1602,80 @@ fn parse_extraction_spec(bytes: &[u8]) -> Result<Vec<ExtractionField>> {
                 .map(|raw| split_semicolon(&raw))
                 .unwrap_or_default(),
             allowed_values: allowed.map(|raw| split_semicolon(&raw)).unwrap_or_default(),
+            field_type,
         });
     }
 
     if fields.is_empty() {
-        bail!("字段定义表中未找到有效的列，请检查前四行内容。");
+        bail!("字段定义表中未找到有效的列，请检查前五行内容。");
     }
 
     Ok(fields)
 }
 
-fn clamp_document_text(text: &str) -> (String, bool) {
-    if text.chars().count() <= MAX_DOCUMENT_TEXT_CHARS {
-        return (text.to_string(), false);
+struct DocumentChunks {
+    windows: Vec<String>,
+    truncated: bool,
+}
+
+/// Splits long documents into overlapping windows of `MAX_DOCUMENT_TEXT_CHARS`.
+/// Text that fits in one window is returned as-is so short papers keep a single call.
+fn split_document_text(text: &str) -> DocumentChunks {
+    let chars: Vec<char> = text.chars().collect();
+    if chars.len() <= MAX_DOCUMENT_TEXT_CHARS {
+        return DocumentChunks {
+            windows: vec![text.to_string()],
+            truncated: false,
+        };
+    }
+
+    let step = MAX_DOCUMENT_TEXT_CHARS - CHUNK_OVERLAP_CHARS;
+    let mut windows = Vec::new();
+    let mut start = 0;
+
+    while start < chars.len() && windows.len() < MAX_DOCUMENT_CHUNKS {
+        let end = (start + MAX_DOCUMENT_TEXT_CHARS).min(chars.len());
+        windows.push(chars[start..end].iter().collect());
+        if end == chars.len() {
+            break;
+        }
+        start += step;
+    }
+
+    let covered = (windows.len() - 1) * step + MAX_DOCUMENT_TEXT_CHARS;
+    DocumentChunks {
+        windows,
+        truncated: covered < chars.len(),
+    }
+}
+
+fn is_empty_value(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::String(text) => text.trim().is_empty(),
+        Value::Array(items) => items.is_empty(),
+        Value::Object(map) => map.is_empty(),
+        _ => false,
+    }
+}
+
+/// Merges per-window extraction results, keeping the first non-empty value of each field.
+fn merge_extraction_maps(maps: Vec<Map<String, Value>>) -> Map<String, Value> {
+    let mut merged = Map::new();
+
+    for map in maps {
+        for (key, value) in map {
+            match merged.get(&key) {
+                Some(existing) if !is_empty_value(existing) => {}
+                Some(_) if is_empty_value(&value) => {}
+                _ => {
+                    merged.insert(key, value);
+                }
+            }
+        }
     }
 
-    let clipped: String = text.chars().take(MAX_DOCUMENT_TEXT_CHARS).collect();
-    (clipped, true)
+    merged
 }
 
 fn build_user_prompt(
@@ -795,7 +1683,7 @@ fn build_user_prompt(
     fields: &[ExtractionField],
     guidance: &str,
     doc_text: &str,
-    truncated: bool,
+    chunk: Option<(usize, usize)>,
 ) -> String {
     let mut buffer = String::new();
     buffer.push_str(&format!("文件名：{}\n\n", filename));
@@ -812,6 +1700,11 @@ fn build_user_prompt(
         if !field.allowed_values.is_empty() {
             buffer.push_str(&format!("   枚举值：{}\n", field.allowed_values.join("；")));
         }
+        match field.field_type {
+            FieldType::Text => {}
+            FieldType::Number => buffer.push_str("   类型：数字（仅返回数值，不含单位）\n"),
+            FieldType::Date => buffer.push_str("   类型：日期（使用 YYYY-MM-DD 格式）\n"),
+        }
         buffer.push('\n');
     }
 
@@ -822,10 +1715,9 @@ fn build_user_prompt(
         buffer.push_str("\n\n");
     }
 
-    if truncated {
+    if let Some((index, total)) = chunk {
         buffer.push_str(&format!(
-            "注意：正文已截断至前 {} 个字符，请结合上下文谨慎推理。\n\n",
-            MAX_DOCUMENT_TEXT_CHARS
+            "注意：论文较长，以下仅为第 {index}/{total} 段正文；本段未提及的字段请留空，不要猜测。\n\n",
         ));
     }
 
@@ -853,90 +1745,368 @@ fn extract_object_from_response(text: &str) -> Result<Map<String, Value>> {
     bail!("模型输出不是可解析的 JSON 对象");
 }
 
-fn value_to_string(value: &Value) -> String {
-    match value {
-        Value::Null => String::new(),
-        Value::String(s) => s.to_string(),
-        Value::Number(n) => n.to_string(),
-        Value::Bool(b) => {
-            if *b {
-                "true".to_string()
-            } else {
-                "false".to_string()
+/// Checks each enum-constrained field against `allowed_values` (case-insensitive).
+/// Matches are rewritten to the canonical spelling; mismatches are cleared and
+/// reported so they surface in the result sheet instead of landing silently.
+fn enforce_allowed_values(fields: &[ExtractionField], map: &mut Map<String, Value>) -> Vec<String> {
+    let mut warnings = Vec::new();
+
+    for field in fields
+        .iter()
+        .filter(|field| !field.allowed_values.is_empty())
+    {
+        let Some(value) = map.get_mut(&field.name) else {
+            continue;
+        };
+
+        match value {
+            Value::Array(items) => {
+                let mut rejected = Vec::new();
+                items.retain_mut(|item| match canonical_allowed_value(field, item) {
+                    AllowedMatch::Empty => false,
+                    AllowedMatch::Matched(canonical) => {
+                        *item = Value::String(canonical);
+                        true
+                    }
+                    AllowedMatch::Rejected(raw) => {
+                        rejected.push(raw);
+                        false
+                    }
+                });
+                if !rejected.is_empty() {
+                    warnings.push(allowed_value_warning(&field.name, &rejected.join("；")));
+                }
             }
+            other => match canonical_allowed_value(field, other) {
+                AllowedMatch::Empty => {}
+                AllowedMatch::Matched(canonical) => *other = Value::String(canonical),
+                AllowedMatch::Rejected(raw) => {
+                    warnings.push(allowed_value_warning(&field.name, &raw));
+                    *other = Value::Null;
+                }
+            },
         }
-        Value::Array(items) => items
-            .iter()
-            .map(value_to_string)
-            .filter(|s| !s.is_empty())
-            .collect::<Vec<_>>()
-            .join("；"),
-        Value::Object(obj) => serde_json::to_string(obj).unwrap_or_default(),
     }
-}
 
-fn read_pdf_text(path: &Path) -> Result<String> {
-    extract_pdf_text(path)
-        .with_context(|| format!("无法读取 PDF 文本：{}", path.display()))
-        .map(|content| content.trim().to_string())
+    warnings
 }
 
-fn spawn_job_worker(state: AppState, job_id: Uuid, fields: Vec<ExtractionField>) {
-    tokio::spawn(async move {
-        if let Err(err) = process_job(state.clone(), job_id, fields.clone()).await {
-            error!(?err, %job_id, "信息提取任务失败");
-            let pool = state.pool();
-            if let Err(update_err) = sqlx::query(
-                "UPDATE info_extract_jobs SET status = $2, status_detail = $3, error_message = $4, updated_at = NOW() WHERE id = $1",
-            )
-            .bind(job_id)
-            .bind(STATUS_FAILED)
-            .bind("任务执行出错，已终止。")
-            .bind(err.to_string())
-            .execute(&pool)
-            .await
-            {
-                error!(?update_err, %job_id, "更新任务失败状态时出错");
-            }
-        }
-    });
+enum AllowedMatch {
+    Empty,
+    Matched(String),
+    Rejected(String),
 }
 
-async fn process_job(state: AppState, job_id: Uuid, fields: Vec<ExtractionField>) -> Result<()> {
-    let pool = state.pool();
-    let settings = state.info_extract_settings().await.unwrap_or_default();
+fn canonical_allowed_value(field: &ExtractionField, value: &Value) -> AllowedMatch {
+    let raw = value_to_string(value);
+    let trimmed = raw.trim();
+    if trimmed.is_empty() {
+        return AllowedMatch::Empty;
+    }
+
+    let needle = trimmed.to_lowercase();
+    field
+        .allowed_values
+        .iter()
+        .find(|allowed| allowed.trim().to_lowercase() == needle)
+        .map(|allowed| AllowedMatch::Matched(allowed.trim().to_string()))
+        .unwrap_or_else(|| AllowedMatch::Rejected(trimmed.to_string()))
+}
+
+fn allowed_value_warning(field_name: &str, raw: &str) -> String {
+    format!("「{field_name}」返回值「{raw}」不在允许列表中，已清空")
+}
+
+/// Normalises typed fields: parseable numbers become JSON numbers and dates become
+/// `YYYY-MM-DD`. Anything else is kept as text and reported rather than failing the sheet.
+fn apply_field_types(fields: &[ExtractionField], map: &mut Map<String, Value>) -> Vec<String> {
+    let mut warnings = Vec::new();
+
+    for field in fields {
+        if field.field_type == FieldType::Text {
+            continue;
+        }
+        let Some(value) = map.get_mut(&field.name) else {
+            continue;
+        };
+        if matches!(value, Value::Number(_)) && field.field_type == FieldType::Number {
+            continue;
+        }
+
+        let raw = value_to_string(value);
+        let raw = raw.trim();
+        if raw.is_empty() {
+            continue;
+        }
+
+        match field.field_type {
+            FieldType::Number => match parse_number(raw) {
+                Some(number) => *value = Value::Number(number),
+                None => warnings.push(format!(
+                    "「{}」返回值「{}」不是有效数字，已按文本保留",
+                    field.name, raw
+                )),
+            },
+            FieldType::Date => match parse_date(raw) {
+                Some(date) => *value = Value::String(date.format("%Y-%m-%d").to_string()),
+                None => warnings.push(format!(
+                    "「{}」返回值「{}」不是有效日期，已按文本保留",
+                    field.name, raw
+                )),
+            },
+            FieldType::Text => {}
+        }
+    }
+
+    warnings
+}
+
+fn parse_number(raw: &str) -> Option<serde_json::Number> {
+    let cleaned: String = raw
+        .chars()
+        .filter(|ch| !matches!(ch, ',' | '，' | ' '))
+        .collect();
+    if let Ok(integer) = cleaned.parse::<i64>() {
+        return Some(integer.into());
+    }
+    cleaned
+        .parse::<f64>()
+        .ok()
+        .and_then(serde_json::Number::from_f64)
+}
+
+fn parse_date(raw: &str) -> Option<NaiveDate> {
+    ["%Y-%m-%d", "%Y/%m/%d", "%Y.%m.%d", "%Y年%m月%d日"]
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(raw, format).ok())
+}
+
+/// `date` as an Excel date cell value, or `None` for years Excel cannot represent (before 1900
+/// or after 9999).
+fn excel_date(date: NaiveDate) -> Option<ExcelDateTime> {
+    let year = u16::try_from(date.year())
+        .ok()
+        .filter(|year| (1900..=9999).contains(year))?;
+    ExcelDateTime::from_ymd(year, date.month() as u8, date.day() as u8).ok()
+}
+
+/// Writes a field value, using native number/date cells for typed fields when the value
+/// was normalised by `apply_field_types`, and text otherwise.
+fn write_field_cell(
+    worksheet: &mut Worksheet,
+    row: u32,
+    col: u16,
+    field: &ExtractionField,
+    value: Option<&Value>,
+    date_format: &Format,
+) -> Result<()> {
+    match (field.field_type, value) {
+        (FieldType::Number, Some(Value::Number(number))) => {
+            if let Some(number) = number.as_f64() {
+                worksheet
+                    .write_number(row, col, number)
+                    .context("写入字段值失败")?;
+                return Ok(());
+            }
+        }
+        (FieldType::Date, Some(Value::String(text))) => {
+            if let Some(date) = parse_date(text.trim()) {
+                match excel_date(date) {
+                    Some(excel_date) => {
+                        worksheet
+                            .write_datetime_with_format(row, col, &excel_date, date_format)
+                            .context("写入字段值失败")?;
+                        return Ok(());
+                    }
+                    None => warn!(
+                        field = %field.name,
+                        date = %text,
+                        "date outside Excel's range; written as text"
+                    ),
+                }
+            }
+        }
+        _ => {}
+    }
+
+    let text = value.map(value_to_string).unwrap_or_default();
+    worksheet
+        .write_string(row, col, &text)
+        .context("写入字段值失败")?;
+    Ok(())
+}
+
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::Bool(b) => {
+            if *b {
+                "true".to_string()
+            } else {
+                "false".to_string()
+            }
+        }
+        Value::Array(items) => items
+            .iter()
+            .map(value_to_string)
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+            .join("；"),
+        Value::Object(obj) => serde_json::to_string(obj).unwrap_or_default(),
+    }
+}
 
-    let job_user_id: Uuid =
-        sqlx::query_scalar("SELECT user_id FROM info_extract_jobs WHERE id = $1")
+fn spawn_job_worker(
+    state: AppState,
+    job_id: Uuid,
+    fields: Vec<ExtractionField>,
+    layout: WorkbookLayout,
+    run: JobRun,
+) {
+    tokio::spawn(async move {
+        if let Err(err) = process_job(state.clone(), job_id, fields.clone(), layout, run).await {
+            error!(?err, %job_id, "信息提取任务失败");
+            let pool = state.pool();
+            if let Err(update_err) = sqlx::query(
+                "UPDATE info_extract_jobs SET status = $2, status_detail = $3, error_message = $4, updated_at = NOW() WHERE id = $1",
+            )
             .bind(job_id)
-            .fetch_one(&pool)
+            .bind(STATUS_FAILED)
+            .bind("任务执行出错，已终止。")
+            .bind(err.to_string())
+            .execute(&pool)
             .await
-            .context("无法获取任务所属用户")?;
+            {
+                error!(?update_err, %job_id, "更新任务失败状态时出错");
+            }
+        }
 
-    sqlx::query(
-        "UPDATE info_extract_jobs SET status = $2, status_detail = $3, updated_at = NOW() WHERE id = $1",
+        notifications::notify_job_owner(
+            state.pool_ref(),
+            state.mailer(),
+            "info_extract_jobs",
+            "id",
+            MODULE_INFO_EXTRACT,
+            job_id,
+        )
+        .await;
+
+        send_job_callback(&state, job_id).await;
+    });
+}
+
+/// Sends the job callback with a link to the result workbook.
+async fn send_job_callback(state: &AppState, job_id: Uuid) {
+    notifications::send_job_callback(
+        state,
+        "info_extract_jobs",
+        MODULE_INFO_EXTRACT,
+        job_id,
+        async || {
+            let has_result = sqlx::query_scalar::<_, bool>(
+                "SELECT result_path IS NOT NULL FROM info_extract_jobs WHERE id = $1",
+            )
+            .bind(job_id)
+            .fetch_one(state.pool_ref())
+            .await
+            .unwrap_or_else(|err| {
+                error!(?err, %job_id, "failed to load result path for callback");
+                false
+            });
+            if has_result {
+                vec![format!("/api/infoextract/jobs/{job_id}/download/result")]
+            } else {
+                Vec::new()
+            }
+        },
+    )
+    .await;
+}
+
+async fn process_job(
+    state: AppState,
+    job_id: Uuid,
+    fields: Vec<ExtractionField>,
+    layout: WorkbookLayout,
+    run: JobRun,
+) -> Result<()> {
+    let pool = state.pool();
+    let settings = state.info_extract_settings().await.unwrap_or_default();
+
+    let (job_user_id, skip_duplicates, prior_tokens): (Uuid, bool, i64) = sqlx::query_as(
+        "SELECT user_id, skip_duplicates, COALESCE(total_tokens, 0) FROM info_extract_jobs WHERE id = $1",
+    )
+    .bind(job_id)
+    .fetch_one(&pool)
+    .await
+    .context("无法获取任务所属用户")?;
+
+    let started = sqlx::query(
+        "UPDATE info_extract_jobs SET status = $2, status_detail = $3, updated_at = NOW() WHERE id = $1 AND status <> $4",
     )
     .bind(job_id)
     .bind(STATUS_PROCESSING)
     .bind("任务已启动，正在读取文献。")
+    .bind(STATUS_CANCELLED)
     .execute(&pool)
     .await
     .context("无法更新任务状态")?;
 
+    if started.rows_affected() == 0 {
+        // Cancelled before the worker picked it up; nothing to do.
+        sqlx::query(
+            "UPDATE info_extract_jobs SET status_detail = $2, updated_at = NOW() WHERE id = $1",
+        )
+        .bind(job_id)
+        .bind("任务已取消，未处理任何文献。")
+        .execute(&pool)
+        .await
+        .context("无法更新任务状态")?;
+        return Ok(());
+    }
+
     let documents = sqlx::query_as::<_, DocumentSourceRecord>(
-        "SELECT id, ordinal, original_filename, source_path FROM info_extract_documents WHERE job_id = $1 ORDER BY ordinal",
+        "SELECT id, ordinal, original_filename, source_path FROM info_extract_documents
+         WHERE job_id = $1 AND ($2 OR status = $3) ORDER BY ordinal",
     )
     .bind(job_id)
+    .bind(run == JobRun::Full)
+    .bind(STATUS_PENDING)
     .fetch_all(&pool)
     .await
     .context("无法读取任务文献列表")?;
 
+    // A retry only reruns the documents it re-queued; every other document keeps its result.
+    let prior_results = match run {
+        JobRun::Full => Vec::new(),
+        JobRun::RetryFailed => sqlx::query_as::<_, PriorDocumentRecord>(
+            "SELECT ordinal, original_filename, status, status_detail, error_message, parsed_values,
+                    validation_warnings, tokens_used
+             FROM info_extract_documents WHERE job_id = $1 AND status <> $2",
+        )
+        .bind(job_id)
+        .bind(STATUS_PENDING)
+        .fetch_all(&pool)
+        .await
+        .context("无法读取已完成的文献结果")?,
+    };
+
     let job_dir = PathBuf::from(STORAGE_ROOT).join(job_id.to_string());
 
     let models = settings.models.clone();
     let prompts = settings.prompts.clone();
     let fields_arc = Arc::new(fields.clone());
-    let semaphore = Arc::new(Semaphore::new(MAX_CONCURRENT_DOCUMENTS));
+    let semaphore = Arc::new(Semaphore::new(effective_concurrency(
+        models.max_concurrent_documents,
+    )));
+    let duplicates = Arc::new(DuplicateRegistry::new(
+        documents
+            .iter()
+            .map(|document| document.ordinal.max(0) as usize),
+    ));
+    let failure_budget = Arc::new(ProviderFailureBudget::new(models.provider_failure_budget));
 
     let tasks = documents
         .into_iter()
@@ -946,6 +2116,8 @@ async fn process_job(state: AppState, job_id: Uuid, fields: Vec<ExtractionField>
             let prompts_clone = prompts.clone();
             let fields_clone = fields_arc.clone();
             let semaphore_clone = semaphore.clone();
+            let duplicate_slot = duplicates.slot(document.ordinal.max(0) as usize);
+            let failure_budget_clone = failure_budget.clone();
 
             tokio::spawn(async move {
                 process_single_document(
@@ -956,6 +2128,9 @@ async fn process_job(state: AppState, job_id: Uuid, fields: Vec<ExtractionField>
                     prompts_clone,
                     fields_clone,
                     semaphore_clone,
+                    duplicate_slot,
+                    failure_budget_clone,
+                    skip_duplicates,
                 )
                 .await
             })
@@ -972,14 +2147,33 @@ async fn process_job(state: AppState, job_id: Uuid, fields: Vec<ExtractionField>
         }
     }
 
+    // Usage is charged for this run only; earlier runs were recorded when they finished.
+    let run_tokens: i64 = results.iter().map(|r| r.tokens_used).sum();
+    let run_success_count = results.iter().filter(|r| r.success).count();
+    let prior_skipped = prior_results
+        .iter()
+        .filter(|doc| doc.status == STATUS_SKIPPED)
+        .count();
+    results.extend(
+        prior_results
+            .into_iter()
+            .map(PriorDocumentRecord::into_result),
+    );
     results.sort_by_key(|r| r.ordinal);
 
-    let total_tokens: i64 = results.iter().map(|r| r.tokens_used).sum();
+    let total_tokens = prior_tokens + run_tokens;
     let success_count = results.iter().filter(|r| r.success).count();
-    let total_docs = results.len();
+    let duplicate_count = duplicates.duplicate_count() + prior_skipped;
+    // Skipped duplicates are neither successes nor failures.
+    let skipped_duplicates = if skip_duplicates { duplicate_count } else { 0 };
+    let total_docs = results.len().saturating_sub(skipped_duplicates);
     let failed_docs = total_docs.saturating_sub(success_count);
+    let cancelled = job_is_cancelled(&pool, job_id).await;
 
-    let mut job_status_detail = if success_count == total_docs && total_docs > 0 {
+    let mut job_status_detail = if cancelled {
+        let skipped_docs = results.iter().filter(|r| r.cancelled).count();
+        Some(cancelled_job_detail(success_count, skipped_docs))
+    } else if success_count == total_docs && total_docs > 0 {
         Some(format!("{} 篇文献已全部提取完成。", total_docs))
     } else if success_count > 0 {
         Some(format!(
@@ -991,22 +2185,42 @@ async fn process_job(state: AppState, job_id: Uuid, fields: Vec<ExtractionField>
     } else {
         Some("任务执行失败，未能处理任何文献。".to_string())
     };
+    if failure_budget.is_exhausted() {
+        job_status_detail = job_status_detail
+            .map(|detail| format!("{}模型服务连续调用失败，其余文献已跳过。", detail));
+    }
+    if duplicate_count > 0 {
+        job_status_detail = job_status_detail.map(|detail| {
+            format!(
+                "{}{}",
+                detail,
+                duplicate_job_warning(duplicate_count, skip_duplicates)
+            )
+        });
+    }
 
     let mut job_error_message: Option<String> = None;
     let mut result_path: Option<String> = None;
 
     if success_count > 0 {
         let result_file = job_dir.join("extraction_result.xlsx");
-        if let Err(err) = write_result_workbook(&result_file, &fields, &results).await {
+        if let Err(err) = write_result_workbook(&result_file, &fields, &results, layout).await {
             error!(?err, %job_id, "生成结果表失败");
             job_error_message = Some("提取成功但结果汇总文件生成失败，请联系管理员。".to_string());
             job_status_detail = Some("部分文献完成，但结果文件生成失败。".to_string());
         } else {
+            if let Err(err) =
+                write_result_csv(&result_file.with_extension("csv"), &fields, &results).await
+            {
+                warn!(?err, %job_id, "生成 CSV 结果失败");
+            }
             result_path = Some(result_file.to_string_lossy().to_string());
         }
     }
 
-    let final_status = if success_count > 0 {
+    let final_status = if cancelled {
+        STATUS_CANCELLED
+    } else if success_count > 0 {
         if result_path.is_some() {
             STATUS_COMPLETED
         } else {
@@ -1030,39 +2244,53 @@ async fn process_job(state: AppState, job_id: Uuid, fields: Vec<ExtractionField>
     .await
     .context("无法更新任务最终状态")?;
 
-    if success_count > 0 && result_path.is_some() {
-        if let Err(err) = usage::record_usage(
+    if run_success_count > 0
+        && result_path.is_some()
+        && let Err(err) = usage::record_usage(
             &pool,
             job_user_id,
             MODULE_INFO_EXTRACT,
-            total_tokens,
-            success_count as i64,
+            run_tokens,
+            run_success_count as i64,
         )
         .await
-        {
-            error!(?err, %job_id, "记录用量失败");
-        }
+    {
+        error!(?err, %job_id, "记录用量失败");
     }
 
     Ok(())
 }
 
+fn duplicate_job_warning(count: usize, skipped: bool) -> String {
+    if skipped {
+        format!("检测到 {} 篇重复文献，已跳过处理。", count)
+    } else {
+        format!("检测到 {} 篇重复文献，请核对上传列表。", count)
+    }
+}
+
+fn cancelled_job_detail(completed: usize, skipped: usize) -> String {
+    format!("任务已取消：{} 篇已完成，{} 篇未处理。", completed, skipped)
+}
+
 async fn write_result_workbook(
     path: &Path,
     fields: &[ExtractionField],
     results: &[DocumentExtractionResult],
+    layout: WorkbookLayout,
 ) -> Result<()> {
     let path = path.to_path_buf();
     let fields = fields.to_vec();
     let results = results.to_vec();
 
-    task::spawn_blocking(move || generate_result_workbook(&path, &fields, &results))
+    task::spawn_blocking(move || generate_result_workbook(&path, &fields, &results, layout))
         .await
         .map_err(|err| anyhow!("结果表生成线程异常：{}", err))??;
 
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn process_single_document(
     state: AppState,
     job_id: Uuid,
@@ -1071,8 +2299,11 @@ async fn process_single_document(
     prompts: InfoExtractPrompts,
     fields: Arc<Vec<ExtractionField>>,
     semaphore: Arc<Semaphore>,
+    duplicate_slot: DuplicateSlot,
+    failure_budget: Arc<ProviderFailureBudget>,
+    skip_duplicates: bool,
 ) -> DocumentExtractionResult {
-    let permit = match semaphore.acquire_owned().await {
+    let permit = match semaphore.clone().acquire_owned().await {
         Ok(permit) => permit,
         Err(err) => {
             error!(?err, %job_id, "获取并发许可失败");
@@ -1081,8 +2312,10 @@ async fn process_single_document(
                 filename: document.original_filename,
                 values: None,
                 error: Some("无法开始处理该文献".to_string()),
+                validation_warnings: Vec::new(),
                 tokens_used: 0,
                 success: false,
+                cancelled: false,
             };
         }
     };
@@ -1095,10 +2328,48 @@ async fn process_single_document(
         filename: document.original_filename.clone(),
         values: None,
         error: None,
+        validation_warnings: Vec::new(),
         tokens_used: 0,
         success: false,
+        cancelled: false,
     };
 
+    if job_is_cancelled(&pool, job_id).await {
+        if let Err(err) = sqlx::query(
+            "UPDATE info_extract_documents SET status = $2, status_detail = $3, updated_at = NOW() WHERE id = $1",
+        )
+        .bind(document.id)
+        .bind(STATUS_CANCELLED)
+        .bind(CANCELLED_DOCUMENT_DETAIL)
+        .execute(&pool)
+        .await
+        {
+            warn!(?err, %job_id, document_id = %document.id, "更新文献取消状态失败");
+        }
+        result.error = Some("任务已取消".to_string());
+        result.cancelled = true;
+        drop(permit);
+        return result;
+    }
+
+    if failure_budget.is_exhausted() {
+        if let Err(err) = sqlx::query(
+            "UPDATE info_extract_documents SET status = $2, status_detail = $3, error_message = $4, attempt_count = 0, updated_at = NOW() WHERE id = $1",
+        )
+        .bind(document.id)
+        .bind(STATUS_FAILED)
+        .bind(PROVIDER_UNAVAILABLE_DETAIL)
+        .bind(PROVIDER_UNAVAILABLE_DETAIL)
+        .execute(&pool)
+        .await
+        {
+            warn!(?err, %job_id, document_id = %document.id, "更新文献失败状态失败");
+        }
+        result.error = Some(PROVIDER_UNAVAILABLE_DETAIL.to_string());
+        drop(permit);
+        return result;
+    }
+
     if let Err(err) = sqlx::query(
         "UPDATE info_extract_documents SET status = $2, status_detail = $3, updated_at = NOW() WHERE id = $1",
     )
@@ -1114,106 +2385,140 @@ async fn process_single_document(
         return result;
     }
 
-    let pdf_path = PathBuf::from(&document.source_path);
-    let text = match task::spawn_blocking({
-        let path = pdf_path.clone();
-        move || read_pdf_text(&path)
+    let source_path = PathBuf::from(&document.source_path);
+    let document_text = match task::spawn_blocking({
+        let path = source_path.clone();
+        move || load_document_text(&path)
     })
     .await
     {
         Ok(Ok(content)) => content,
         Ok(Err(err)) => {
-            error!(?err, %job_id, document_id = %document.id, "读取 PDF 失败");
+            error!(?err, %job_id, document_id = %document.id, "读取文献失败");
             let _ = sqlx::query(
                 "UPDATE info_extract_documents SET status = $2, status_detail = $3, error_message = $4, attempt_count = $5, updated_at = NOW() WHERE id = $1",
             )
             .bind(document.id)
             .bind(STATUS_FAILED)
-            .bind("无法读取 PDF 内容")
+            .bind("无法读取文献内容")
             .bind(err.to_string())
             .bind(0_i32)
             .execute(&pool)
             .await;
 
-            result.error = Some("无法读取 PDF 内容".to_string());
+            result.error = Some("无法读取文献内容".to_string());
             drop(permit);
             return result;
         }
         Err(join_err) => {
-            error!(?join_err, %job_id, document_id = %document.id, "PDF 读取线程异常");
+            error!(?join_err, %job_id, document_id = %document.id, "文献读取线程异常");
             let _ = sqlx::query(
                 "UPDATE info_extract_documents SET status = $2, status_detail = $3, error_message = $4, attempt_count = $5, updated_at = NOW() WHERE id = $1",
             )
             .bind(document.id)
             .bind(STATUS_FAILED)
-            .bind("无法读取 PDF 内容")
+            .bind("无法读取文献内容")
             .bind("读取线程异常")
             .bind(0_i32)
             .execute(&pool)
             .await;
 
-            result.error = Some("无法读取 PDF 内容".to_string());
+            result.error = Some("无法读取文献内容".to_string());
             drop(permit);
             return result;
         }
     };
 
-    let (clamped_text, truncated) = clamp_document_text(&text);
-    let status_detail = ensure_status_detail(truncated);
+    // Registration waits for earlier uploads, so it must not hold a concurrency permit.
+    drop(permit);
+    let duplicate_note = duplicate_slot
+        .register(&document.original_filename, &document_text.text)
+        .await
+        .map(|duplicate| duplicate.describe_zh());
+    let permit = match semaphore.acquire_owned().await {
+        Ok(permit) => permit,
+        Err(err) => {
+            error!(?err, %job_id, "获取并发许可失败");
+            result.error = Some("无法开始处理该文献".to_string());
+            return result;
+        }
+    };
+    if let Some(note) = duplicate_note.as_ref().filter(|_| skip_duplicates) {
+        let detail = format!("{}，已跳过。", note);
+        if let Err(err) = sqlx::query(
+            "UPDATE info_extract_documents SET status = $2, status_detail = $3, updated_at = NOW() WHERE id = $1",
+        )
+        .bind(document.id)
+        .bind(STATUS_SKIPPED)
+        .bind(&detail)
+        .execute(&pool)
+        .await
+        {
+            warn!(?err, %job_id, document_id = %document.id, "更新重复文献状态失败");
+        }
+        result.error = Some(detail);
+        drop(permit);
+        return result;
+    }
+
+    let chunks = split_document_text(&document_text.text);
+    let total_chunks = chunks.windows.len();
 
     let mut attempts = 0i32;
     let mut doc_tokens = 0i64;
-    let mut parsed: Option<Map<String, Value>> = None;
+    let mut parsed_chunks: Vec<Map<String, Value>> = Vec::new();
+    let mut failed_chunks = 0usize;
     let mut last_error: Option<String> = None;
-    let mut last_response: Option<String> = None;
-
-    while attempts < MAX_RETRIES as i32 {
-        attempts += 1;
-
-        let mut messages = Vec::new();
-        let system_text = prompts.system_prompt.trim();
-        if !system_text.is_empty() {
-            messages.push(ChatMessage::new(MessageRole::System, system_text));
-        }
+    let mut responses: Vec<String> = Vec::new();
 
+    for (idx, window) in chunks.windows.iter().enumerate() {
+        let chunk = (total_chunks > 1).then_some((idx + 1, total_chunks));
         let user_prompt = build_user_prompt(
             &document.original_filename,
             fields.as_ref(),
             prompts.response_guidance.trim(),
-            &clamped_text,
-            truncated,
+            window,
+            chunk,
         );
-        messages.push(ChatMessage::new(MessageRole::User, user_prompt));
 
-        let request = LlmRequest::new(models.extraction_model.clone(), messages);
-
-        match llm_client.execute(request).await {
-            Ok(response) => {
-                doc_tokens += response.token_usage.total_tokens as i64;
-                last_response = Some(response.text.clone());
-
-                match extract_object_from_response(&response.text) {
-                    Ok(map) => {
-                        parsed = Some(map);
-                        last_error = None;
-                        break;
-                    }
-                    Err(err) => {
-                        warn!(?err, attempt = attempts, document_id = %document.id, "解析模型返回结果失败");
-                        last_error = Some(err.to_string());
-                    }
-                }
-            }
-            Err(err) => {
-                warn!(?err, attempt = attempts, document_id = %document.id, "模型调用失败，准备重试");
-                last_error = Some(err.to_string());
+        let request = build_window_request(&models, &prompts, &user_prompt);
+        let outcome = extract_window(&failure_budget, document.id, || {
+            llm_client.execute_with_retry(request.clone(), LLM_RETRY_POLICY)
+        })
+        .await;
+
+        attempts += outcome.attempts;
+        doc_tokens += outcome.tokens;
+        responses.extend(outcome.last_response);
+        match outcome.parsed {
+            Some(map) => parsed_chunks.push(map),
+            None => {
+                failed_chunks += 1;
+                last_error = outcome.last_error;
             }
         }
+    }
 
-        if attempts < MAX_RETRIES as i32 {
-            sleep(Duration::from_millis(RETRY_DELAY_MS * attempts as u64)).await;
-        }
+    let mut status_detail = ensure_status_detail(&chunks, failed_chunks, document_text.ocr_used);
+    if let Some(note) = duplicate_note {
+        let note = format!("{}。", note);
+        status_detail = Some(match status_detail {
+            Some(detail) => format!("{} {}", note, detail),
+            None => note.clone(),
+        });
+        result.validation_warnings.push(note);
     }
+    let last_response = (!responses.is_empty()).then(|| responses.join("\n\n---\n\n"));
+    let parsed = (!parsed_chunks.is_empty()).then(|| {
+        let mut map = merge_extraction_maps(parsed_chunks);
+        result
+            .validation_warnings
+            .extend(enforce_allowed_values(fields.as_ref(), &mut map));
+        result
+            .validation_warnings
+            .extend(apply_field_types(fields.as_ref(), &mut map));
+        map
+    });
 
     result.tokens_used = doc_tokens;
 
@@ -1221,7 +2526,7 @@ async fn process_single_document(
         Some(map) => {
             let db_value = Value::Object(map.clone());
             if let Err(err) = sqlx::query(
-                "UPDATE info_extract_documents SET status = $2, status_detail = $3, response_text = $4, parsed_values = $5, error_message = NULL, attempt_count = $6, tokens_used = $7, updated_at = NOW() WHERE id = $1",
+                "UPDATE info_extract_documents SET status = $2, status_detail = $3, response_text = $4, parsed_values = $5, error_message = NULL, attempt_count = $6, tokens_used = $7, validation_warnings = $8, updated_at = NOW() WHERE id = $1",
             )
             .bind(document.id)
             .bind(STATUS_COMPLETED)
@@ -1230,6 +2535,7 @@ async fn process_single_document(
             .bind(db_value)
             .bind(attempts)
             .bind(doc_tokens)
+            .bind(&result.validation_warnings)
             .execute(&pool)
             .await
             {
@@ -1276,13 +2582,159 @@ async fn process_single_document(
     result
 }
 
+struct WindowExtraction {
+    parsed: Option<Map<String, Value>>,
+    attempts: i32,
+    tokens: i64,
+    last_error: Option<String>,
+    last_response: Option<String>,
+}
+
+fn build_window_request(
+    models: &InfoExtractModels,
+    prompts: &InfoExtractPrompts,
+    user_prompt: &str,
+) -> LlmRequest {
+    let mut messages = Vec::new();
+    let system_text = prompts.system_prompt.trim();
+    if !system_text.is_empty() {
+        messages.push(ChatMessage::new(MessageRole::System, system_text));
+    }
+    messages.push(ChatMessage::new(MessageRole::User, user_prompt));
+
+    let model = route_model(
+        &models.extraction_model,
+        &models.extraction_routes,
+        estimate_tokens(user_prompt),
+    );
+    LlmRequest::new(model, messages).with_module(MODULE_INFO_EXTRACT)
+}
+
+/// Runs extraction for one window of text, retrying when the response cannot be parsed.
+///
+/// Request errors are not retried here since `call` already retries transient failures; they
+/// count against the job's `failure_budget`, and once it is exhausted no request is sent.
+async fn extract_window<F, Fut>(
+    failure_budget: &ProviderFailureBudget,
+    document_id: Uuid,
+    mut call: F,
+) -> WindowExtraction
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<LlmResponse>>,
+{
+    let mut outcome = WindowExtraction {
+        parsed: None,
+        attempts: 0,
+        tokens: 0,
+        last_error: None,
+        last_response: None,
+    };
+
+    while outcome.attempts < MAX_RETRIES as i32 {
+        if failure_budget.is_exhausted() {
+            outcome.last_error = Some(PROVIDER_UNAVAILABLE_DETAIL.to_string());
+            break;
+        }
+        outcome.attempts += 1;
+
+        match call().await {
+            Ok(response) => {
+                failure_budget.record_success();
+                outcome.tokens += response.token_usage.total_tokens as i64;
+                outcome.last_response = Some(response.text.clone());
+
+                match extract_object_from_response(&response.text) {
+                    Ok(map) => {
+                        outcome.parsed = Some(map);
+                        outcome.last_error = None;
+                        break;
+                    }
+                    Err(err) => {
+                        warn!(?err, attempt = outcome.attempts, %document_id, "解析模型返回结果失败");
+                        outcome.last_error = Some(err.to_string());
+                    }
+                }
+            }
+            Err(err) => {
+                warn!(?err, attempt = outcome.attempts, %document_id, "模型调用失败");
+                // A refusal is an answer about this document, not a sign the provider is down.
+                if !is_refusal_error(&err) {
+                    failure_budget.record_failure();
+                }
+                outcome.last_error = Some(err.to_string());
+                break;
+            }
+        }
+
+        if outcome.attempts < MAX_RETRIES as i32 {
+            sleep(Duration::from_millis(
+                RETRY_DELAY_MS * outcome.attempts as u64,
+            ))
+            .await;
+        }
+    }
+
+    outcome
+}
+
+async fn write_result_csv(
+    path: &Path,
+    fields: &[ExtractionField],
+    results: &[DocumentExtractionResult],
+) -> Result<()> {
+    let contents = render_result_csv(fields, results);
+    tokio_fs::write(path, contents)
+        .await
+        .context("保存 CSV 结果失败")?;
+    Ok(())
+}
+
+/// Serialises the extraction matrix as CSV with a UTF-8 BOM so Excel detects Chinese text.
+fn render_result_csv(fields: &[ExtractionField], results: &[DocumentExtractionResult]) -> String {
+    let mut output = String::from(UTF8_BOM);
+
+    let header = std::iter::once("文件名")
+        .chain(fields.iter().map(|field| field.name.as_str()))
+        .chain(["错误信息", "校验提示"]);
+    push_csv_row(&mut output, header);
+
+    for result in results {
+        let values: Vec<String> = fields
+            .iter()
+            .map(|field| {
+                result
+                    .values
+                    .as_ref()
+                    .and_then(|map| map.get(&field.name))
+                    .map(value_to_string)
+                    .unwrap_or_default()
+            })
+            .collect();
+        let warnings = result.validation_warnings.join("；");
+        let row = std::iter::once(result.filename.as_str())
+            .chain(values.iter().map(String::as_str))
+            .chain([result.error.as_deref().unwrap_or(""), warnings.as_str()]);
+        push_csv_row(&mut output, row);
+    }
+
+    output
+}
+
 fn generate_result_workbook(
     path: &Path,
     fields: &[ExtractionField],
     results: &[DocumentExtractionResult],
+    layout: WorkbookLayout,
 ) -> Result<()> {
     let mut workbook = Workbook::new();
+    let date_format = Format::new().set_num_format("yyyy-mm-dd");
     let worksheet = workbook.add_worksheet();
+    if layout == WorkbookLayout::PerDocumentSheets {
+        worksheet
+            .set_name(SUMMARY_SHEET_NAME)
+            .context("设置汇总表名称失败")?;
+    }
 
     worksheet
         .write_string(0, 0, "文件名")
@@ -1301,6 +2753,12 @@ fn generate_result_workbook(
     worksheet
         .write_string(0, error_col, "错误信息")
         .context("写入错误信息表头失败")?;
+    let warning_col = error_col
+        .checked_add(1)
+        .ok_or_else(|| anyhow!("字段数量过多，超出 Excel 列限制"))?;
+    worksheet
+        .write_string(0, warning_col, "校验提示")
+        .context("写入校验提示表头失败")?;
 
     for (row_idx, result) in results.iter().enumerate() {
         let row = (row_idx + 1) as u32;
@@ -1312,21 +2770,32 @@ fn generate_result_workbook(
             let col: u16 = (col_idx + 1)
                 .try_into()
                 .map_err(|_| anyhow!("字段数量过多，超出 Excel 列限制"))?;
-            let value = result
-                .values
-                .as_ref()
-                .and_then(|map| map.get(&field.name))
-                .map(value_to_string)
-                .unwrap_or_default();
-            worksheet
-                .write_string(row, col, &value)
-                .context("写入字段值失败")?;
+            let value = result.values.as_ref().and_then(|map| map.get(&field.name));
+            write_field_cell(worksheet, row, col, field, value, &date_format)?;
         }
 
         let error_text = result.error.clone().unwrap_or_default();
         worksheet
             .write_string(row, error_col, &error_text)
             .context("写入错误信息失败")?;
+
+        worksheet
+            .write_string(row, warning_col, result.validation_warnings.join("；"))
+            .context("写入校验提示失败")?;
+    }
+
+    if layout == WorkbookLayout::PerDocumentSheets {
+        let mut namer = SheetNamer::new(&[SUMMARY_SHEET_NAME, INDEX_SHEET_NAME]);
+        let entries: Vec<_> = results
+            .iter()
+            .take(MAX_DETAIL_SHEETS)
+            .map(|result| (result, namer.assign(&result.filename)))
+            .collect();
+
+        write_sheet_index(&mut workbook, &entries)?;
+        for (result, sheet) in &entries {
+            write_detail_sheet(&mut workbook, fields, result, &sheet.name, &date_format)?;
+        }
     }
 
     workbook.save(path).context("保存结果工作簿失败")?;
@@ -1334,19 +2803,566 @@ fn generate_result_workbook(
     Ok(())
 }
 
-fn internal_error(err: anyhow::Error) -> (StatusCode, Json<ApiMessage>) {
-    error!(?err, "信息提取模块内部错误");
-    (
-        StatusCode::INTERNAL_SERVER_ERROR,
-        Json(ApiMessage::new("服务器内部错误，请稍后再试。")),
+fn write_detail_sheet(
+    workbook: &mut Workbook,
+    fields: &[ExtractionField],
+    result: &DocumentExtractionResult,
+    sheet_name: &str,
+    date_format: &Format,
+) -> Result<()> {
+    let worksheet = workbook.add_worksheet();
+    worksheet
+        .set_name(sheet_name)
+        .context("设置文献工作表名称失败")?;
+
+    worksheet
+        .write_string(0, 0, "文件名")
+        .and_then(|sheet| sheet.write_string(0, 1, &result.filename))
+        .context("写入文件名失败")?;
+    worksheet
+        .write_string(1, 0, "字段")
+        .and_then(|sheet| sheet.write_string(1, 1, "值"))
+        .context("写入字段表头失败")?;
+
+    let mut row = 2u32;
+    for field in fields {
+        let value = result.values.as_ref().and_then(|map| map.get(&field.name));
+        worksheet
+            .write_string(row, 0, &field.name)
+            .context("写入字段名失败")?;
+        write_field_cell(worksheet, row, 1, field, value, date_format)?;
+        row += 1;
+    }
+
+    let raw_json = result
+        .values
+        .as_ref()
+        .map(|map| serde_json::to_string_pretty(map).unwrap_or_default())
+        .unwrap_or_default();
+    let trailer = [
+        ("原始 JSON", raw_json),
+        ("错误信息", result.error.clone().unwrap_or_default()),
+        ("校验提示", result.validation_warnings.join("；")),
+    ];
+    for (label, value) in trailer {
+        worksheet
+            .write_string(row, 0, label)
+            .and_then(|sheet| sheet.write_string(row, 1, &value))
+            .context("写入文献详情失败")?;
+        row += 1;
+    }
+
+    Ok(())
+}
+
+/// A detail-sheet name and the adjustments needed to make it valid, so the index sheet can
+/// tell users how a name maps back to its file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SheetName {
+    name: String,
+    adjustments: Vec<&'static str>,
+}
+
+/// Hands out Excel-safe, unique (case-insensitively, as Excel compares them) sheet names
+/// derived from filenames.
+struct SheetNamer {
+    used: HashSet<String>,
+}
+
+impl SheetNamer {
+    fn new(reserved: &[&str]) -> Self {
+        let mut used: HashSet<String> = reserved.iter().map(|name| name.to_lowercase()).collect();
+        // Excel reserves "History" for its change-tracking sheet.
+        used.insert("history".to_string());
+        Self { used }
+    }
+
+    fn assign(&mut self, filename: &str) -> SheetName {
+        let mut adjustments = Vec::new();
+        let stem = Path::new(filename)
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or(filename);
+
+        let cleaned: String = stem
+            .chars()
+            .map(|ch| match ch {
+                '[' | ']' | ':' | '*' | '?' | '/' | '\\' => '_',
+                other => other,
+            })
+            .collect();
+        // Excel also rejects names starting or ending with an apostrophe.
+        let mut base = cleaned.trim().trim_matches('\'').to_string();
+        if base != stem {
+            adjustments.push("已替换非法字符");
+        }
+        if base.is_empty() {
+            base = "文献".to_string();
+        }
+
+        if base.chars().count() > MAX_SHEET_NAME_CHARS {
+            base = truncate_sheet_name(&base, MAX_SHEET_NAME_CHARS);
+            adjustments.push("名称过长已截断");
+        }
+
+        let mut name = base.clone();
+        let mut counter = 2;
+        while self.used.contains(&name.to_lowercase()) {
+            let suffix = format!("~{counter}");
+            let keep = MAX_SHEET_NAME_CHARS - suffix.chars().count();
+            name = format!("{}{}", truncate_sheet_name(&base, keep), suffix);
+            counter += 1;
+        }
+        if name != base {
+            adjustments.push("名称重复已加序号");
+        }
+
+        self.used.insert(name.to_lowercase());
+        SheetName { name, adjustments }
+    }
+}
+
+fn truncate_sheet_name(name: &str, max_chars: usize) -> String {
+    let truncated: String = name.chars().take(max_chars).collect();
+    truncated.trim_end_matches('\'').to_string()
+}
+
+/// Lists which detail sheet belongs to which file, noting any renaming.
+fn write_sheet_index(
+    workbook: &mut Workbook,
+    entries: &[(&DocumentExtractionResult, SheetName)],
+) -> Result<()> {
+    let worksheet = workbook.add_worksheet();
+    worksheet
+        .set_name(INDEX_SHEET_NAME)
+        .context("设置对照表名称失败")?;
+    worksheet
+        .write_string(0, 0, "工作表")
+        .and_then(|sheet| sheet.write_string(0, 1, "文件名"))
+        .and_then(|sheet| sheet.write_string(0, 2, "名称调整"))
+        .context("写入对照表表头失败")?;
+
+    for (idx, (result, sheet)) in entries.iter().enumerate() {
+        let row = (idx + 1) as u32;
+        worksheet
+            .write_string(row, 0, &sheet.name)
+            .and_then(|ws| ws.write_string(row, 1, &result.filename))
+            .and_then(|ws| ws.write_string(row, 2, sheet.adjustments.join("；")))
+            .context("写入对照表失败")?;
+    }
+
+    Ok(())
+}
+
+fn internal_error(err: anyhow::Error) -> (StatusCode, Json<ApiMessage>) {
+    error!(?err, "信息提取模块内部错误");
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(ApiMessage::new("服务器内部错误，请稍后再试。")),
     )
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::{
+        STUB_TOTAL_TOKENS, StubProvider, StubReply, create_user, recorded_usage, session_jar,
+        test_state,
+    };
+    use axum::http::StatusCode;
+    use serde_json::json;
+    use sqlx::PgPool;
+    use std::fs;
     use tempfile::tempdir;
 
+    fn location_field() -> Vec<ExtractionField> {
+        vec![ExtractionField {
+            name: "地点".to_string(),
+            description: Some("研究开展的城市".to_string()),
+            examples: Vec::new(),
+            allowed_values: Vec::new(),
+            field_type: FieldType::Text,
+        }]
+    }
+
+    #[test]
+    fn token_estimate_counts_every_window_with_its_prompt() {
+        let prompts = InfoExtractPrompts::default();
+        let fields = location_field();
+
+        let short = estimate_document_tokens("a.txt", &fields, &prompts, "本研究在上海开展。");
+        assert_eq!(short.requests, 1);
+        let user_prompt = build_user_prompt(
+            "a.txt",
+            &fields,
+            prompts.response_guidance.trim(),
+            "本研究在上海开展。",
+            None,
+        );
+        assert_eq!(
+            short.prompt_tokens,
+            estimate_tokens(prompts.system_prompt.trim()) + estimate_tokens(&user_prompt)
+        );
+
+        // Two overlapping windows, each carrying the full spec prompt.
+        let long_text = "word ".repeat(MAX_DOCUMENT_TEXT_CHARS / 5 + 1_000);
+        let long = estimate_document_tokens("b.txt", &fields, &prompts, &long_text);
+        assert_eq!(long.requests, 2);
+        assert!(!long.truncated);
+        assert!(long.prompt_tokens > estimate_tokens(&long_text));
+    }
+
+    async fn seed_job(pool: &PgPool, user_id: Uuid, text: &str) -> Uuid {
+        let job_id = Uuid::new_v4();
+        let job_dir = PathBuf::from(STORAGE_ROOT).join(job_id.to_string());
+        fs::create_dir_all(&job_dir).unwrap();
+        let source_path = job_dir.join("doc_000_paper.txt");
+        fs::write(&source_path, text).unwrap();
+
+        sqlx::query(
+            "INSERT INTO info_extract_jobs (id, user_id, status, spec_filename, spec_path) VALUES ($1, $2, $3, $4, $5)",
+        )
+        .bind(job_id)
+        .bind(user_id)
+        .bind(STATUS_PENDING)
+        .bind("spec.xlsx")
+        .bind(job_dir.join("spec.xlsx").to_string_lossy().to_string())
+        .execute(pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO info_extract_documents (id, job_id, ordinal, original_filename, source_path, status) VALUES ($1, $2, 0, $3, $4, $5)",
+        )
+        .bind(Uuid::new_v4())
+        .bind(job_id)
+        .bind("paper.txt")
+        .bind(source_path.to_string_lossy().to_string())
+        .bind(STATUS_PENDING)
+        .execute(pool)
+        .await
+        .unwrap();
+
+        job_id
+    }
+
+    async fn job_state(pool: &PgPool, job_id: Uuid) -> (String, Option<String>, Option<String>) {
+        sqlx::query_as(
+            "SELECT status, status_detail, result_path FROM info_extract_jobs WHERE id = $1",
+        )
+        .bind(job_id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    #[sqlx::test]
+    #[ignore = "needs DATABASE_URL (disposable Postgres)"]
+    async fn process_job_writes_result_workbook(pool: PgPool) {
+        let provider = StubProvider::start(|_| StubReply::text(r#"{"地点": "上海"}"#)).await;
+        let state = test_state(pool.clone(), &provider).await;
+        let user_id = create_user(&pool).await;
+        let job_id = seed_job(&pool, user_id, "本研究在上海开展。").await;
+
+        process_job(
+            state,
+            job_id,
+            location_field(),
+            WorkbookLayout::SingleSheet,
+            JobRun::Full,
+        )
+        .await
+        .unwrap();
+
+        let (status, detail, result_path) = job_state(&pool, job_id).await;
+        assert_eq!(status, STATUS_COMPLETED);
+        assert_eq!(detail.as_deref(), Some("1 篇文献已全部提取完成。"));
+        let result_path = PathBuf::from(result_path.expect("result workbook path"));
+        assert!(result_path.exists());
+        assert!(result_path.with_extension("csv").exists());
+
+        let (doc_status, parsed): (String, Option<Value>) = sqlx::query_as(
+            "SELECT status, parsed_values FROM info_extract_documents WHERE job_id = $1",
+        )
+        .bind(job_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(doc_status, STATUS_COMPLETED);
+        assert_eq!(parsed.unwrap()["地点"], "上海");
+
+        assert_eq!(
+            recorded_usage(&pool, user_id, MODULE_INFO_EXTRACT).await,
+            (STUB_TOTAL_TOKENS, 1)
+        );
+
+        let _ = fs::remove_dir_all(PathBuf::from(STORAGE_ROOT).join(job_id.to_string()));
+    }
+
+    #[sqlx::test]
+    #[ignore = "needs DATABASE_URL (disposable Postgres)"]
+    async fn retry_failed_reruns_only_failed_documents_and_merges_results(pool: PgPool) {
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        let healthy = Arc::new(AtomicBool::new(false));
+        let provider = StubProvider::start({
+            let healthy = healthy.clone();
+            move |request| {
+                if !request.user.contains("北京") {
+                    StubReply::text(r#"{"地点": "上海"}"#)
+                } else if healthy.load(Ordering::SeqCst) {
+                    StubReply::text(r#"{"地点": "北京"}"#)
+                } else {
+                    StubReply::text("服务繁忙，请稍后再试。")
+                }
+            }
+        })
+        .await;
+        let state = test_state(pool.clone(), &provider).await;
+        let user_id = create_user(&pool).await;
+        let job_id = seed_job(&pool, user_id, "本研究在上海开展。").await;
+        let job_dir = PathBuf::from(STORAGE_ROOT).join(job_id.to_string());
+
+        let mut spec = Workbook::new();
+        let sheet = spec.add_worksheet();
+        sheet.write_string(0, 0, "地点").unwrap();
+        sheet.write_string(1, 0, "研究开展的城市").unwrap();
+        spec.save(job_dir.join("spec.xlsx")).unwrap();
+        let second_source = job_dir.join("doc_001_paper2.txt");
+        fs::write(&second_source, "本研究在北京开展。").unwrap();
+        sqlx::query(
+            "INSERT INTO info_extract_documents (id, job_id, ordinal, original_filename, source_path, status) VALUES ($1, $2, 1, $3, $4, $5)",
+        )
+        .bind(Uuid::new_v4())
+        .bind(job_id)
+        .bind("paper2.txt")
+        .bind(second_source.to_string_lossy().to_string())
+        .bind(STATUS_PENDING)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        process_job(
+            state.clone(),
+            job_id,
+            location_field(),
+            WorkbookLayout::SingleSheet,
+            JobRun::Full,
+        )
+        .await
+        .unwrap();
+        let (status, detail, _) = job_state(&pool, job_id).await;
+        assert_eq!(status, STATUS_COMPLETED);
+        assert_eq!(detail.as_deref(), Some("1 篇成功，1 篇失败。"));
+
+        healthy.store(true, Ordering::SeqCst);
+        let calls_before = provider.calls();
+        let requeued = retry_failed_documents(
+            State(state.clone()),
+            session_jar(&pool, user_id).await,
+            AxumPath(job_id),
+        )
+        .await
+        .unwrap();
+        assert_eq!(requeued.0.message, "已重新提交 1 篇失败的文献。");
+
+        let mut finished = None;
+        for _ in 0..100 {
+            let state = job_state(&pool, job_id).await;
+            if state.0 != STATUS_PENDING && state.0 != STATUS_PROCESSING {
+                finished = Some(state);
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        let (status, detail, result_path) = finished.expect("retry did not finish");
+        assert_eq!(status, STATUS_COMPLETED);
+        assert_eq!(detail.as_deref(), Some("2 篇文献已全部提取完成。"));
+        assert_eq!(provider.calls() - calls_before, 1);
+
+        let csv =
+            fs::read_to_string(PathBuf::from(result_path.unwrap()).with_extension("csv")).unwrap();
+        assert!(csv.contains("上海"));
+        assert!(csv.contains("北京"));
+        assert_eq!(
+            recorded_usage(&pool, user_id, MODULE_INFO_EXTRACT).await.1,
+            2
+        );
+
+        let (status, _) = retry_failed_documents(
+            State(state),
+            session_jar(&pool, user_id).await,
+            AxumPath(job_id),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let _ = fs::remove_dir_all(job_dir);
+    }
+
+    #[sqlx::test]
+    #[ignore = "needs DATABASE_URL (disposable Postgres)"]
+    async fn unparseable_answers_are_kept_for_admins_to_inspect(pool: PgPool) {
+        let provider = StubProvider::start(|_| StubReply::text("抱歉，文中没有提到地点。")).await;
+        let state = test_state(pool.clone(), &provider).await;
+        let user_id = create_user(&pool).await;
+        let job_id = seed_job(&pool, user_id, "本研究在上海开展。").await;
+
+        process_job(
+            state.clone(),
+            job_id,
+            location_field(),
+            WorkbookLayout::SingleSheet,
+            JobRun::Full,
+        )
+        .await
+        .unwrap();
+
+        let document_id: Uuid = sqlx::query_scalar(
+            "SELECT id FROM info_extract_documents WHERE job_id = $1 AND status = $2",
+        )
+        .bind(job_id)
+        .bind(STATUS_FAILED)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+        // Regular users get neither the link nor the answer.
+        let status = job_status(
+            State(state.clone()),
+            session_jar(&pool, user_id).await,
+            AxumPath(job_id),
+        )
+        .await
+        .unwrap();
+        assert_eq!(status.0.documents[0].raw_response_url, None);
+        assert!(
+            admin::document_raw_response(
+                State(state.clone()),
+                session_jar(&pool, user_id).await,
+                AxumPath((job_id, document_id)),
+            )
+            .await
+            .is_err()
+        );
+
+        sqlx::query("UPDATE users SET is_admin = TRUE WHERE id = $1")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let status = job_status(
+            State(state.clone()),
+            session_jar(&pool, user_id).await,
+            AxumPath(job_id),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            status.0.documents[0].raw_response_url.as_deref(),
+            Some(
+                format!("/dashboard/modules/infoextract/jobs/{job_id}/documents/{document_id}/raw")
+                    .as_str()
+            )
+        );
+        let response = admin::document_raw_response(
+            State(state),
+            session_jar(&pool, user_id).await,
+            AxumPath((job_id, document_id)),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert!(
+            String::from_utf8(body.to_vec())
+                .unwrap()
+                .contains("文中没有提到地点")
+        );
+
+        let _ = fs::remove_dir_all(PathBuf::from(STORAGE_ROOT).join(job_id.to_string()));
+    }
+
+    #[sqlx::test]
+    #[ignore = "needs DATABASE_URL (disposable Postgres)"]
+    async fn process_job_fails_without_result_when_provider_rejects(pool: PgPool) {
+        let provider = StubProvider::start(|_| {
+            StubReply::Error(
+                StatusCode::BAD_REQUEST,
+                json!({ "error": { "message": "invalid request" } }),
+            )
+        })
+        .await;
+        let state = test_state(pool.clone(), &provider).await;
+        let user_id = create_user(&pool).await;
+        let job_id = seed_job(&pool, user_id, "本研究在上海开展。").await;
+
+        process_job(
+            state,
+            job_id,
+            location_field(),
+            WorkbookLayout::SingleSheet,
+            JobRun::Full,
+        )
+        .await
+        .unwrap();
+
+        let (status, detail, result_path) = job_state(&pool, job_id).await;
+        assert_eq!(status, STATUS_FAILED);
+        assert_eq!(
+            detail.as_deref(),
+            Some("所有尝试均失败，请检查输入后重试。")
+        );
+        assert_eq!(result_path, None);
+
+        let doc_status: String =
+            sqlx::query_scalar("SELECT status FROM info_extract_documents WHERE job_id = $1")
+                .bind(job_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(doc_status, STATUS_FAILED);
+        assert_eq!(
+            recorded_usage(&pool, user_id, MODULE_INFO_EXTRACT).await,
+            (0, 0)
+        );
+
+        let _ = fs::remove_dir_all(PathBuf::from(STORAGE_ROOT).join(job_id.to_string()));
+    }
+
+    #[tokio::test]
+    async fn exhausted_failure_budget_fails_remaining_documents_fast() {
+        let budget = ProviderFailureBudget::new(2);
+        let mut calls = 0;
+
+        for _ in 0..2 {
+            let outcome = extract_window(&budget, Uuid::new_v4(), || {
+                calls += 1;
+                async { Err::<LlmResponse, _>(anyhow!("503 Service Unavailable")) }
+            })
+            .await;
+            assert_eq!(outcome.attempts, 1);
+            assert!(outcome.last_error.unwrap().contains("503"));
+        }
+        assert!(budget.is_exhausted());
+
+        for _ in 0..3 {
+            let outcome = extract_window(&budget, Uuid::new_v4(), || {
+                calls += 1;
+                async { Err::<LlmResponse, _>(anyhow!("503 Service Unavailable")) }
+            })
+            .await;
+            assert_eq!(outcome.attempts, 0);
+            assert_eq!(
+                outcome.last_error.as_deref(),
+                Some(PROVIDER_UNAVAILABLE_DETAIL)
+            );
+        }
+        assert_eq!(calls, 2);
+    }
+
     #[test]
     fn parse_spec_succeeds_with_examples() {
         let dir = tempdir().unwrap();
@@ -1370,6 +3386,140 @@ mod tests {
         assert_eq!(fields[1].allowed_values, vec!["100", "250", "1000"]);
     }
 
+    #[test]
+    fn parse_spec_reads_optional_type_row() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("spec.xlsx");
+
+        let mut workbook = Workbook::new();
+        let worksheet = workbook.add_worksheet();
+        worksheet.write_string(0, 0, "Sample Size").unwrap();
+        worksheet.write_string(1, 0, "受试者人数").unwrap();
+        worksheet.write_string(4, 0, "Number").unwrap();
+        worksheet.write_string(0, 1, "Published").unwrap();
+        worksheet.write_string(1, 1, "发表日期").unwrap();
+        worksheet.write_string(4, 1, "date").unwrap();
+        worksheet.write_string(0, 2, "Notes").unwrap();
+        worksheet.write_string(1, 2, "备注").unwrap();
+        workbook.save(&path).unwrap();
+
+        let bytes = std::fs::read(&path).unwrap();
+        let fields = parse_extraction_spec(&bytes).unwrap();
+        let types: Vec<_> = fields.iter().map(|field| field.field_type).collect();
+        assert_eq!(
+            types,
+            vec![FieldType::Number, FieldType::Date, FieldType::Text]
+        );
+
+        let mut workbook = Workbook::new();
+        let worksheet = workbook.add_worksheet();
+        worksheet.write_string(0, 0, "Sample Size").unwrap();
+        worksheet.write_string(1, 0, "受试者人数").unwrap();
+        worksheet.write_string(4, 0, "integer").unwrap();
+        workbook.save(&path).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        let err = parse_extraction_spec(&bytes).unwrap_err();
+        assert!(format!("{err}").contains("类型「integer」无效"));
+    }
+
+    #[test]
+    fn typed_fields_are_normalised_or_warned() {
+        let field = |name: &str, field_type| ExtractionField {
+            name: name.to_string(),
+            description: None,
+            examples: Vec::new(),
+            allowed_values: Vec::new(),
+            field_type,
+        };
+        let fields = vec![
+            field("n", FieldType::Number),
+            field("ratio", FieldType::Number),
+            field("bad", FieldType::Number),
+            field("when", FieldType::Date),
+            field("never", FieldType::Date),
+        ];
+        let mut map = Map::new();
+        map.insert("n".into(), Value::String("1,200".into()));
+        map.insert("ratio".into(), Value::String("0.35".into()));
+        map.insert("bad".into(), Value::String("约 100 人".into()));
+        map.insert("when".into(), Value::String("2023年5月1日".into()));
+        map.insert("never".into(), Value::String("spring 2023".into()));
+
+        let warnings = apply_field_types(&fields, &mut map);
+
+        assert_eq!(map["n"], Value::from(1200));
+        assert_eq!(map["ratio"], Value::from(0.35));
+        assert_eq!(map["bad"], Value::String("约 100 人".into()));
+        assert_eq!(map["when"], Value::String("2023-05-01".into()));
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].contains("不是有效数字"));
+        assert!(warnings[1].contains("不是有效日期"));
+
+        let results = vec![DocumentExtractionResult {
+            ordinal: 0,
+            filename: "a.pdf".to_string(),
+            values: Some(map),
+            error: None,
+            validation_warnings: warnings,
+            tokens_used: 0,
+            success: true,
+            cancelled: false,
+        }];
+        let dir = tempdir().expect("temp dir");
+        let path = dir.path().join("typed.xlsx");
+        generate_result_workbook(&path, &fields, &results, WorkbookLayout::SingleSheet)
+            .expect("typed workbook");
+
+        let mut workbook: Xlsx<_> = calamine::open_workbook(&path).expect("open typed");
+        let range = workbook.worksheet_range_at(0).unwrap().unwrap();
+        assert_eq!(range.get((1, 1)), Some(&DataType::Float(1200.0)));
+        assert_eq!(
+            range.get((1, 3)),
+            Some(&DataType::String("约 100 人".to_string()))
+        );
+        assert!(matches!(range.get((1, 4)), Some(DataType::DateTime(_))));
+    }
+
+    #[test]
+    fn dates_outside_excel_range_are_written_as_text() {
+        let fields = vec![ExtractionField {
+            name: "when".to_string(),
+            description: None,
+            examples: Vec::new(),
+            allowed_values: Vec::new(),
+            field_type: FieldType::Date,
+        }];
+        let result = |raw: &str| {
+            let mut map = Map::new();
+            map.insert("when".into(), Value::String(raw.into()));
+            assert!(apply_field_types(&fields, &mut map).is_empty());
+            DocumentExtractionResult {
+                ordinal: 0,
+                filename: "a.pdf".to_string(),
+                values: Some(map),
+                error: None,
+                validation_warnings: Vec::new(),
+                tokens_used: 0,
+                success: true,
+                cancelled: false,
+            }
+        };
+        let results = vec![result("1850年3月1日"), result("2023-05-01")];
+
+        let dir = tempdir().expect("temp dir");
+        let path = dir.path().join("old_dates.xlsx");
+        generate_result_workbook(&path, &fields, &results, WorkbookLayout::SingleSheet)
+            .expect("pre-1900 dates do not fail the export");
+
+        let mut workbook: Xlsx<_> = calamine::open_workbook(&path).expect("open workbook");
+        let range = workbook.worksheet_range_at(0).unwrap().unwrap();
+        assert_eq!(
+            range.get((1, 1)),
+            Some(&DataType::String("1850-03-01".to_string()))
+        );
+        assert!(matches!(range.get((2, 1)), Some(DataType::DateTime(_))));
+    }
+
     #[test]
     fn parse_spec_rejects_empty_definition() {
         let dir = tempdir().unwrap();
@@ -1385,6 +3535,266 @@ mod tests {
         assert!(format!("{err}").contains("至少需要填写"));
     }
 
+    #[test]
+    fn short_documents_stay_in_one_window() {
+        let chunks = split_document_text("短文本");
+        assert_eq!(chunks.windows, vec!["短文本".to_string()]);
+        assert!(!chunks.truncated);
+    }
+
+    #[test]
+    fn long_documents_split_into_overlapping_windows() {
+        let text: String = (0..MAX_DOCUMENT_TEXT_CHARS * 2)
+            .map(|idx| char::from(b'a' + (idx % 26) as u8))
+            .collect();
+        let chunks = split_document_text(&text);
+
+        assert_eq!(chunks.windows.len(), 3);
+        assert!(!chunks.truncated);
+        let first_tail: String = chunks.windows[0]
+            .chars()
+            .skip(MAX_DOCUMENT_TEXT_CHARS - CHUNK_OVERLAP_CHARS)
+            .collect();
+        assert!(chunks.windows[1].starts_with(&first_tail));
+        assert!(text.ends_with(chunks.windows[2].as_str()));
+    }
+
+    #[test]
+    fn merge_prefers_non_empty_values() {
+        let first: Map<String, Value> =
+            serde_json::from_str(r#"{"Location": "", "Sample Size": 120, "Design": null}"#)
+                .unwrap();
+        let second: Map<String, Value> =
+            serde_json::from_str(r#"{"Location": "Shanghai", "Sample Size": 80, "Design": "RCT"}"#)
+                .unwrap();
+
+        let merged = merge_extraction_maps(vec![first, second]);
+        assert_eq!(merged["Location"], Value::String("Shanghai".into()));
+        assert_eq!(merged["Sample Size"], Value::from(120));
+        assert_eq!(merged["Design"], Value::String("RCT".into()));
+    }
+
+    #[test]
+    fn result_csv_has_bom_and_quotes_cells() {
+        let fields = vec![ExtractionField {
+            name: "地点".to_string(),
+            description: None,
+            examples: Vec::new(),
+            allowed_values: Vec::new(),
+            field_type: FieldType::Text,
+        }];
+        let mut values = Map::new();
+        values.insert("地点".to_string(), Value::String("上海, \"浦东\"".into()));
+        let results = vec![
+            DocumentExtractionResult {
+                ordinal: 0,
+                filename: "a.pdf".to_string(),
+                values: Some(values),
+                error: None,
+                validation_warnings: vec!["「类型」返回值「x」不在允许列表中，已清空".to_string()],
+                tokens_used: 0,
+                success: true,
+                cancelled: false,
+            },
+            DocumentExtractionResult {
+                ordinal: 1,
+                filename: "b.pdf".to_string(),
+                values: None,
+                error: Some("解析失败".to_string()),
+                validation_warnings: Vec::new(),
+                tokens_used: 0,
+                success: false,
+                cancelled: false,
+            },
+        ];
+
+        let csv = render_result_csv(&fields, &results);
+        assert!(csv.starts_with(UTF8_BOM));
+        let lines: Vec<&str> = csv.trim_start_matches(UTF8_BOM).split("\r\n").collect();
+        assert_eq!(lines[0], "文件名,地点,错误信息,校验提示");
+        assert_eq!(
+            lines[1],
+            "a.pdf,\"上海, \"\"浦东\"\"\",,「类型」返回值「x」不在允许列表中，已清空"
+        );
+        assert_eq!(lines[2], "b.pdf,,解析失败,");
+    }
+
+    #[test]
+    fn progress_counts_finished_documents() {
+        assert_eq!(job_progress(std::iter::empty()), None);
+        assert_eq!(
+            job_progress(
+                [
+                    STATUS_COMPLETED,
+                    STATUS_FAILED,
+                    STATUS_PROCESSING,
+                    STATUS_PENDING
+                ]
+                .into_iter()
+            ),
+            Some(50)
+        );
+        assert_eq!(
+            job_progress([STATUS_COMPLETED, STATUS_CANCELLED, STATUS_FAILED].into_iter()),
+            Some(100)
+        );
+        assert_eq!(
+            job_progress([STATUS_COMPLETED, STATUS_PENDING, STATUS_PENDING].into_iter()),
+            Some(33)
+        );
+    }
+
+    #[test]
+    fn only_active_jobs_can_be_cancelled() {
+        assert!(is_cancellable(STATUS_PENDING));
+        assert!(is_cancellable(STATUS_PROCESSING));
+        assert!(!is_cancellable(STATUS_COMPLETED));
+        assert!(!is_cancellable(STATUS_FAILED));
+        assert!(!is_cancellable(STATUS_CANCELLED));
+        assert_eq!(JobStatus::from_str(STATUS_CANCELLED), JobStatus::Cancelled);
+        assert_eq!(
+            cancelled_job_detail(2, 3),
+            "任务已取消：2 篇已完成，3 篇未处理。"
+        );
+    }
+
+    #[test]
+    fn per_document_layout_adds_one_sheet_per_document() {
+        let fields = vec![ExtractionField {
+            name: "地点".to_string(),
+            description: None,
+            examples: Vec::new(),
+            allowed_values: Vec::new(),
+            field_type: FieldType::Text,
+        }];
+        let result = |ordinal: i32, filename: &str| DocumentExtractionResult {
+            ordinal,
+            filename: filename.to_string(),
+            values: Some(Map::from_iter([(
+                "地点".to_string(),
+                Value::String("上海".into()),
+            )])),
+            error: None,
+            validation_warnings: Vec::new(),
+            tokens_used: 0,
+            success: true,
+            cancelled: false,
+        };
+        let results = vec![result(0, "paper:one.pdf"), result(1, "paper two.docx")];
+        let dir = tempdir().expect("temp dir");
+
+        let single = dir.path().join("single.xlsx");
+        generate_result_workbook(&single, &fields, &results, WorkbookLayout::SingleSheet)
+            .expect("single-sheet workbook");
+        let workbook: Xlsx<_> = calamine::open_workbook(&single).expect("open single");
+        assert_eq!(workbook.sheet_names().len(), 1);
+
+        let multi = dir.path().join("multi.xlsx");
+        generate_result_workbook(&multi, &fields, &results, WorkbookLayout::PerDocumentSheets)
+            .expect("multi-sheet workbook");
+        let workbook: Xlsx<_> = calamine::open_workbook(&multi).expect("open multi");
+        assert_eq!(
+            workbook.sheet_names().to_vec(),
+            vec!["汇总", "文件对照", "paper_one", "paper two"]
+        );
+    }
+
+    #[test]
+    fn sheet_names_are_valid_unique_and_reported() {
+        let long = "a:very[long]*file?name that keeps going and going'.pdf";
+        let filenames = [
+            long,
+            long,
+            "Report.pdf",
+            "report.docx",
+            "''.pdf",
+            "汇总.pdf",
+        ];
+        let mut namer = SheetNamer::new(&[SUMMARY_SHEET_NAME, INDEX_SHEET_NAME]);
+        let sheets: Vec<SheetName> = filenames.iter().map(|name| namer.assign(name)).collect();
+
+        let mut seen = HashSet::new();
+        for sheet in &sheets {
+            assert!(
+                sheet.name.chars().count() <= MAX_SHEET_NAME_CHARS,
+                "{}",
+                sheet.name
+            );
+            assert!(!sheet.name.chars().any(|ch| "[]:*?/\\".contains(ch)));
+            assert!(!sheet.name.starts_with('\'') && !sheet.name.ends_with('\''));
+            assert!(
+                seen.insert(sheet.name.to_lowercase()),
+                "duplicate {}",
+                sheet.name
+            );
+        }
+
+        assert!(sheets[0].name.starts_with("a_very_long__file_name"));
+        assert_eq!(
+            sheets[0].adjustments,
+            vec!["已替换非法字符", "名称过长已截断"]
+        );
+        assert!(sheets[1].name.ends_with("~2"));
+        assert!(sheets[1].adjustments.contains(&"名称重复已加序号"));
+        assert_eq!(sheets[2].name, "Report");
+        assert!(sheets[2].adjustments.is_empty());
+        assert_eq!(sheets[3].name, "report~2");
+        assert_eq!(sheets[4].name, "文献");
+        assert_eq!(sheets[5].name, "汇总~2");
+
+        let results: Vec<DocumentExtractionResult> = filenames
+            .iter()
+            .enumerate()
+            .map(|(ordinal, filename)| DocumentExtractionResult {
+                ordinal: ordinal as i32,
+                filename: filename.to_string(),
+                values: None,
+                error: None,
+                validation_warnings: Vec::new(),
+                tokens_used: 0,
+                success: true,
+                cancelled: false,
+            })
+            .collect();
+        let dir = tempdir().expect("temp dir");
+        let path = dir.path().join("names.xlsx");
+        generate_result_workbook(&path, &[], &results, WorkbookLayout::PerDocumentSheets)
+            .expect("workbook accepts sanitized names");
+        let workbook: Xlsx<_> = calamine::open_workbook(&path).expect("open workbook");
+        assert_eq!(workbook.sheet_names().len(), filenames.len() + 2);
+    }
+
+    #[test]
+    fn allowed_values_are_normalised_or_cleared() {
+        let field = |name: &str, allowed: &[&str]| ExtractionField {
+            name: name.to_string(),
+            description: None,
+            examples: Vec::new(),
+            allowed_values: allowed.iter().map(|v| v.to_string()).collect(),
+            field_type: FieldType::Text,
+        };
+        let fields = vec![
+            field("Design", &["RCT", "Cohort"]),
+            field("Country", &["China", "UK"]),
+            field("Arms", &["A", "B"]),
+            field("Notes", &[]),
+        ];
+        let mut map: Map<String, Value> = serde_json::from_str(
+            r#"{"Design": " rct ", "Country": "France", "Arms": ["a", "C"], "Notes": "free text"}"#,
+        )
+        .unwrap();
+
+        let warnings = enforce_allowed_values(&fields, &mut map);
+
+        assert_eq!(map["Design"], Value::String("RCT".into()));
+        assert_eq!(map["Country"], Value::Null);
+        assert_eq!(map["Arms"], serde_json::json!(["A"]));
+        assert_eq!(map["Notes"], Value::String("free text".into()));
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].contains("France"));
+        assert!(warnings[1].contains("C"));
+    }
+
     #[test]
     fn extract_object_handles_wrapped_text() {
         let payload =
@@ -1,7 +1,9 @@
 mod admin_shared;
 
+pub mod docx2pdf;
 pub mod grader;
 pub mod info_extract;
 pub mod reviewer;
 pub mod summarizer;
+pub mod transcribe;
 pub mod translatedocx;
@@ -7,7 +7,12 @@ use serde::Deserialize;
 
 use crate::{
     AppState,
-    config::{ReviewerModels, ReviewerPrompts, update_reviewer_models, update_reviewer_prompts},
+    config::{
+        DEFAULT_REVIEWER_MAX_MANUSCRIPT_MB, DEFAULT_REVIEWER_MAX_MANUSCRIPT_PAGES,
+        MAX_REVIEWER_ROUND1_MODELS, ReviewerModels, ReviewerPrompts, parse_fallback_models,
+        parse_manuscript_limit, parse_round1_models, parse_success_ratio, update_reviewer_models,
+        update_reviewer_prompts,
+    },
     escape_html, render_footer,
     web::{admin::DashboardQuery, admin_utils::compose_flash_message},
 };
@@ -16,17 +21,18 @@ use super::super::admin_shared::MODULE_ADMIN_SHARED_STYLES;
 
 #[derive(Deserialize)]
 pub struct ReviewerModelForm {
-    pub round1_model_1: String,
-    pub round1_model_2: String,
-    pub round1_model_3: String,
-    pub round1_model_4: String,
-    pub round1_model_5: String,
-    pub round1_model_6: String,
-    pub round1_model_7: String,
-    pub round1_model_8: String,
+    pub round1_models: String,
+    #[serde(default)]
+    pub round1_success_ratio: String,
+    #[serde(default)]
+    pub round1_fallback_models: String,
     pub round2_model: String,
     pub round3_model: String,
     #[serde(default)]
+    pub max_manuscript_mb: String,
+    #[serde(default)]
+    pub max_manuscript_pages: String,
+    #[serde(default)]
     pub redirect: Option<String>,
 }
 
@@ -82,9 +88,9 @@ pub async fn settings_page(
         main {{ padding: 2rem 1.5rem; max-width: 1100px; margin: 0 auto; box-sizing: border-box; }}
         .panel {{ background: #ffffff; border-radius: 12px; border: 1px solid #e2e8f0; padding: 1.5rem; box-shadow: 0 18px 40px rgba(15, 23, 42, 0.08); margin-bottom: 2rem; }}
         label {{ display: block; margin-bottom: 0.5rem; font-weight: 600; color: #0f172a; }}
-        input[type="text"], textarea {{ width: 100%; padding: 0.75rem; border-radius: 8px; border: 1px solid #cbd5f5; background: #f8fafc; color: #0f172a; box-sizing: border-box; font-family: inherit; }}
+        input[type="text"], input[type="number"], textarea {{ width: 100%; padding: 0.75rem; border-radius: 8px; border: 1px solid #cbd5f5; background: #f8fafc; color: #0f172a; box-sizing: border-box; font-family: inherit; }}
         textarea {{ min-height: 160px; }}
-        input[type="text"]:focus, textarea:focus {{ outline: none; border-color: #2563eb; box-shadow: 0 0 0 3px rgba(37, 99, 235, 0.12); }}
+        input[type="text"]:focus, input[type="number"]:focus, textarea:focus {{ outline: none; border-color: #2563eb; box-shadow: 0 0 0 3px rgba(37, 99, 235, 0.12); }}
         button {{ padding: 0.85rem 1.2rem; border: none; border-radius: 8px; background: #2563eb; color: #ffffff; font-weight: 600; cursor: pointer; transition: background 0.15s ease; }}
         button:hover {{ background: #1d4ed8; }}
         .flash {{ padding: 1rem; border-radius: 8px; margin-bottom: 1.5rem; border: 1px solid transparent; }}
@@ -103,7 +109,7 @@ pub async fn settings_page(
             <h1>审稿助手模块设置</h1>
             <a class="back-link" href="/tools/reviewer">← 返回审稿工具</a>
         </div>
-        <p>配置审稿助手使用的模型和提示词。系统会使用8个不同模型进行首轮审稿，然后使用单一模型生成元审稿和事实核查报告。</p>
+        <p>配置审稿助手使用的模型和提示词。系统会使用多个不同模型并行进行首轮审稿，然后使用单一模型生成元审稿和事实核查报告。</p>
     </header>
     <main>
         <p>当前登录：<strong>{username}</strong></p>
@@ -114,23 +120,15 @@ pub async fn settings_page(
                 <input type="hidden" name="redirect" value="{redirect_base}">
                 <div class="model-group">
                     <div class="model-subgroup">
-                        <h3>第一轮审稿模型（8个并行）</h3>
-                        <label for="round1-model-1">模型 1</label>
-                        <input id="round1-model-1" name="round1_model_1" type="text" value="{round1_model_1}" required>
-                        <label for="round1-model-2">模型 2</label>
-                        <input id="round1-model-2" name="round1_model_2" type="text" value="{round1_model_2}" required>
-                        <label for="round1-model-3">模型 3</label>
-                        <input id="round1-model-3" name="round1_model_3" type="text" value="{round1_model_3}" required>
-                        <label for="round1-model-4">模型 4</label>
-                        <input id="round1-model-4" name="round1_model_4" type="text" value="{round1_model_4}" required>
-                        <label for="round1-model-5">模型 5</label>
-                        <input id="round1-model-5" name="round1_model_5" type="text" value="{round1_model_5}" required>
-                        <label for="round1-model-6">模型 6</label>
-                        <input id="round1-model-6" name="round1_model_6" type="text" value="{round1_model_6}" required>
-                        <label for="round1-model-7">模型 7</label>
-                        <input id="round1-model-7" name="round1_model_7" type="text" value="{round1_model_7}" required>
-                        <label for="round1-model-8">模型 8</label>
-                        <input id="round1-model-8" name="round1_model_8" type="text" value="{round1_model_8}" required>
+                        <h3>第一轮审稿模型（并行）</h3>
+                        <label for="round1-models">审稿模型列表（每行一个，最多 {max_round1_models} 个）</label>
+                        <textarea id="round1-models" name="round1_models" required>{round1_models}</textarea>
+                        <label for="round1-success-ratio">最低成功比例</label>
+                        <input id="round1-success-ratio" name="round1_success_ratio" type="number" min="0.01" max="1" step="0.01" value="{round1_success_ratio}">
+                        <p>至少需有该比例（向上取整，至少 1 份）的首轮审稿成功才会继续；当前配置需 {round1_min_successes} 份。默认 0.5。</p>
+                        <label for="round1-fallback-models">备用模型（每行一个，可留空）</label>
+                        <textarea id="round1-fallback-models" name="round1_fallback_models">{round1_fallback_models}</textarea>
+                        <p>首轮审稿模型暂时不可用（网络错误、超时、5xx 或 429）时按顺序改用这些模型；OpenRouter 模型通过其路由列表自动切换。</p>
                     </div>
                     <div class="model-subgroup">
                         <h3>第二轮元审稿模型</h3>
@@ -142,6 +140,13 @@ pub async fn settings_page(
                         <label for="round3-model">事实核查模型</label>
                         <input id="round3-model" name="round3_model" type="text" value="{round3_model}" required>
                     </div>
+                    <div class="model-subgroup">
+                        <h3>稿件大小上限</h3>
+                        <label for="max-manuscript-mb">文件大小上限（MB，0 表示不限制）</label>
+                        <input id="max-manuscript-mb" name="max_manuscript_mb" type="number" min="0" step="1" value="{max_manuscript_mb}">
+                        <label for="max-manuscript-pages">页数上限（0 表示不限制；DOCX 转换为 PDF 后计数）</label>
+                        <input id="max-manuscript-pages" name="max_manuscript_pages" type="number" min="0" step="1" value="{max_manuscript_pages}">
+                    </div>
                 </div>
                 <button type="submit">保存模型</button>
             </form>
@@ -170,16 +175,15 @@ pub async fn settings_page(
 </body>
 </html>"##,
         username = escape_html(&auth_user.username),
-        round1_model_1 = escape_html(&models.round1_model_1),
-        round1_model_2 = escape_html(&models.round1_model_2),
-        round1_model_3 = escape_html(&models.round1_model_3),
-        round1_model_4 = escape_html(&models.round1_model_4),
-        round1_model_5 = escape_html(&models.round1_model_5),
-        round1_model_6 = escape_html(&models.round1_model_6),
-        round1_model_7 = escape_html(&models.round1_model_7),
-        round1_model_8 = escape_html(&models.round1_model_8),
+        round1_models = escape_html(&models.round1_models.join("\n")),
+        round1_success_ratio = models.round1_success_ratio,
+        round1_min_successes = models.round1_min_successes(),
+        round1_fallback_models = escape_html(&models.round1_fallback_models.join("\n")),
+        max_round1_models = MAX_REVIEWER_ROUND1_MODELS,
         round2_model = escape_html(&models.round2_model),
         round3_model = escape_html(&models.round3_model),
+        max_manuscript_mb = models.max_manuscript_mb,
+        max_manuscript_pages = models.max_manuscript_pages,
         initial_prompt = escape_html(&prompts.initial_prompt),
         initial_prompt_zh = escape_html(&prompts.initial_prompt_zh),
         secondary_prompt = escape_html(&prompts.secondary_prompt),
@@ -200,17 +204,44 @@ pub async fn save_models(
         return e;
     }
 
+    let limits = (
+        parse_manuscript_limit(&form.max_manuscript_mb, DEFAULT_REVIEWER_MAX_MANUSCRIPT_MB),
+        parse_manuscript_limit(
+            &form.max_manuscript_pages,
+            DEFAULT_REVIEWER_MAX_MANUSCRIPT_PAGES,
+        ),
+    );
+    let Some(round1_models) = parse_round1_models(&form.round1_models) else {
+        let redirect_path = form
+            .redirect
+            .unwrap_or_else(|| "/dashboard/modules/reviewer".to_string());
+        return Redirect::to(&format!(
+            "{}?error=reviewer_round1_models_invalid",
+            redirect_path
+        ));
+    };
+    let Some(round1_success_ratio) = parse_success_ratio(&form.round1_success_ratio) else {
+        let redirect_path = form
+            .redirect
+            .unwrap_or_else(|| "/dashboard/modules/reviewer".to_string());
+        return Redirect::to(&format!("{}?error=success_ratio_invalid", redirect_path));
+    };
+
+    let (Some(max_manuscript_mb), Some(max_manuscript_pages)) = limits else {
+        let redirect_path = form
+            .redirect
+            .unwrap_or_else(|| "/dashboard/modules/reviewer".to_string());
+        return Redirect::to(&format!("{}?error=manuscript_limit_invalid", redirect_path));
+    };
+
     let models = ReviewerModels {
-        round1_model_1: form.round1_model_1,
-        round1_model_2: form.round1_model_2,
-        round1_model_3: form.round1_model_3,
-        round1_model_4: form.round1_model_4,
-        round1_model_5: form.round1_model_5,
-        round1_model_6: form.round1_model_6,
-        round1_model_7: form.round1_model_7,
-        round1_model_8: form.round1_model_8,
+        round1_models,
+        round1_success_ratio,
+        round1_fallback_models: parse_fallback_models(&form.round1_fallback_models),
         round2_model: form.round2_model,
         round3_model: form.round3_model,
+        max_manuscript_mb,
+        max_manuscript_pages,
     };
 
     match update_reviewer_models(state.pool_ref(), &models).await {
@@ -2,9 +2,10 @@ use std::{
     borrow::Cow,
     fs,
     path::{Path, PathBuf},
+    time::Duration,
 };
 
-use anyhow::{Context, Result, anyhow};
+use anyhow::{Result, anyhow};
 use axum::{
     Json, Router,
     extract::{Multipart, Path as AxumPath, State},
@@ -17,29 +18,38 @@ use chrono::Utc;
 use serde::Serialize;
 use serde_json::json;
 use sqlx::PgPool;
-use tokio::{fs as tokio_fs, time::sleep};
-use tracing::error;
+use tokio::fs as tokio_fs;
+use tracing::{error, warn};
 use uuid::Uuid;
 
 mod admin;
 
+use crate::web::download_limit::hold_permit;
 use crate::web::history_ui;
 use crate::web::storage::JobAccess;
 use crate::web::{
-    FileFieldConfig, FileNaming, ToolAdminLink, ToolPageLayout, UPLOAD_WIDGET_SCRIPT,
-    UPLOAD_WIDGET_STYLES, UploadWidgetConfig, process_upload_form, render_tool_page,
-    render_upload_widget,
+    FileFieldConfig, FileNaming, ToolAdminLink, ToolPageLayout, ToolPageUser, UPLOAD_WIDGET_SCRIPT,
+    UPLOAD_WIDGET_STYLES, UploadWidgetConfig, process_upload_form, render_upload_widget,
 };
 use crate::{
-    AppState, escape_html, history,
-    llm::{AttachmentKind, ChatMessage, FileAttachment, LlmClient, LlmRequest, MessageRole},
-    render_footer,
+    AppState,
+    config::ReviewerModels,
+    escape_html, history,
+    llm::{
+        AttachmentKind, ChatMessage, FileAttachment, LlmClient, LlmRequest, MessageRole,
+        RequestClass, RetryPolicy,
+    },
+    notifications, render_footer,
     usage::{self, MODULE_REVIEWER},
-    utils::docx_to_pdf::convert_docx_to_pdf,
+    utils::{
+        document_text::{pdf_page_count, read_document_text},
+        docx_text::{DocxSection, text_docx_bytes, write_text_docx},
+        docx_to_pdf::convert_docx_to_pdf,
+    },
     web::{
-        AccessMessages,
+        AccessMessages, ApiMessage, JobStatus, STATUS_CLIENT_SCRIPT, attachment_response,
         auth::{self, JsonAuthError},
-        ensure_storage_root, json_error, require_path, stream_file, verify_job_access,
+        ensure_storage_root, json_error, quota_error, require_path, stream_file, verify_job_access,
     },
 };
 
@@ -48,9 +58,14 @@ const STATUS_PENDING: &str = "pending";
 const STATUS_PROCESSING: &str = "processing";
 const STATUS_COMPLETED: &str = "completed";
 const STATUS_FAILED: &str = "failed";
+const STATUS_CANCELLED: &str = "cancelled";
 
-const ROUND1_RETRIES: usize = 3;
-const ROUND1_MIN_SUCCESSES: usize = 4;
+const LLM_RETRY_POLICY: RetryPolicy = RetryPolicy::constant(3, Duration::from_secs(2));
+/// How often in-flight review calls re-read the job status to notice a cancellation.
+const CANCEL_CHECK_INTERVAL: Duration = Duration::from_secs(3);
+/// Scanned manuscripts may be uploaded as one image per page instead of a PDF.
+const PAGE_IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg"];
+const MAX_PAGE_IMAGES: usize = 40;
 
 fn json_response(status: StatusCode, message: impl Into<String>) -> Response {
     json_error(status, message).into_response()
@@ -61,10 +76,15 @@ pub fn router() -> Router<AppState> {
         .route("/tools/reviewer", get(reviewer_page))
         .route("/tools/reviewer/jobs", post(create_job))
         .route("/api/reviewer/jobs/:id", get(job_status))
+        .route("/api/reviewer/jobs/:id/cancel", post(cancel_job))
         .route(
             "/api/reviewer/jobs/:job_id/round/:round/review/:idx/download",
             get(download_review),
         )
+        .route(
+            "/api/reviewer/jobs/:id/report/combined.docx",
+            get(download_combined_report),
+        )
         .route("/dashboard/modules/reviewer", get(admin::settings_page))
         .route(
             "/dashboard/modules/reviewer/models",
@@ -97,6 +117,7 @@ impl JobAccess for JobRow {
 #[derive(Serialize)]
 struct JobStatusResponse {
     status: String,
+    poll_interval_ms: u64,
     #[serde(skip_serializing_if = "Option::is_none")]
     status_detail: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -106,12 +127,15 @@ struct JobStatusResponse {
     #[serde(skip_serializing_if = "Option::is_none")]
     round3_review: Option<ReviewInfo>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    combined_report_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     error: Option<String>,
 }
 
 #[derive(Serialize)]
 struct ReviewInfo {
     model: String,
+    status: String,
     available: bool,
     download_url: Option<String>,
 }
@@ -135,10 +159,36 @@ async fn reviewer_page(
     } else {
         None
     };
+    let page_user = ToolPageUser {
+        note_html: Cow::Owned(note_html),
+        admin_link,
+    };
+    let html = state
+        .tool_page_cache()
+        .render(MODULE_REVIEWER, &page_user, reviewer_page_layout);
+
+    Ok(Html(html))
+}
+
+/// Everything on the reviewer page except the per-user header; cached by `ToolPageCache`.
+fn reviewer_page_layout() -> ToolPageLayout<'static> {
     let upload_widget = render_upload_widget(
         &UploadWidgetConfig::new("reviewer-upload", "reviewer-file", "file", "稿件文件")
-            .with_description("支持上传 PDF 或 DOCX。DOCX 将自动转换为 PDF 参与审稿。")
-            .with_accept(".pdf,.docx"),
+            .with_description(
+                "支持上传一份 PDF 或 DOCX（DOCX 将自动转换为 PDF 参与审稿），或按页顺序上传多张 PNG/JPG 页面图片。",
+            )
+            .with_accept(".pdf,.docx,.png,.jpg,.jpeg")
+            .with_multiple(Some(MAX_PAGE_IMAGES)),
+    );
+    let supplement_widget = render_upload_widget(
+        &UploadWidgetConfig::new(
+            "reviewer-supplement-upload",
+            "reviewer-supplement",
+            "supplement",
+            "补充材料（可选）",
+        )
+        .with_description("可附上一份 PDF 格式的补充材料，将与稿件一同发送给每一轮审稿模型。")
+        .with_accept(".pdf"),
     );
     let history_panel = history_ui::render_history_panel(MODULE_REVIEWER);
     let new_tab_html = format!(
@@ -146,11 +196,13 @@ async fn reviewer_page(
                     <h2>提交稿件</h2>
                     <form id="reviewer-form">
                         {upload_widget}
+                        {supplement_widget}
                         <label for="language">审稿语言</label>
                         <select id="language" name="language">
                             <option value="english">英文</option>
                             <option value="chinese">中文</option>
                         </select>
+                        <label><input type="checkbox" name="number_paragraphs" id="number-paragraphs"> 为稿件正文添加段落编号（[P1]、[P2]…），审稿意见将按编号引用位置</label>
                         <button type="submit">开始审稿</button>
                     </form>
                     <div id="submission-status" class="status-box">等待上传。</div>
@@ -161,14 +213,17 @@ async fn reviewer_page(
                 </section>
 "#,
         upload_widget = upload_widget,
+        supplement_widget = supplement_widget,
     );
 
     let reviewer_script = r#"const form = document.getElementById('reviewer-form');
 const statusBox = document.getElementById('submission-status');
 const jobStatus = document.getElementById('job-status');
 const fileInput = document.getElementById('reviewer-file');
+const supplementInput = document.getElementById('reviewer-supplement');
 const languageSelect = document.getElementById('language');
 let pollTimer = null;
+let activeJobId = null;
 
 const setStatus = (message, type = null) => {
     statusBox.textContent = message;
@@ -179,14 +234,13 @@ const setStatus = (message, type = null) => {
 };
 
 const stopPolling = () => {
-    if (pollTimer) {
-        clearInterval(pollTimer);
-        pollTimer = null;
-    }
+    clearTimeout(pollTimer);
+    pollTimer = null;
+    activeJobId = null;
 };
 
 const renderReviewCard = (title, review) => {
-    const status = review.available ? 'completed' : 'processing';
+    const status = review.status || (review.available ? 'completed' : 'processing');
     const tag = `<span class="status-tag ${status}">${status}</span>`;
     const download = review.download_url
         ? `<p class="downloads"><a href="${review.download_url}">下载 DOCX</a></p>`
@@ -221,11 +275,19 @@ const renderJobStatus = (payload) => {
 
     const cards = reviews.length ? reviews.join('') : '<p class="note">评审结果准备中...</p>';
     const detail = payload.status_detail ? `<p class="note">${payload.status_detail}</p>` : '';
+    const combined = payload.combined_report_url
+        ? `<p class="downloads"><a href="${payload.combined_report_url}">下载合并报告 (DOCX)</a></p>`
+        : '';
+
+    const cancelButton = activeJobId && (payload.status === 'pending' || payload.status === 'processing')
+        ? `<button type="button" style="margin-left:0.75rem;padding:0.35rem 0.8rem;background:#b91c1c;" data-cancel-job="${activeJobId}">取消任务</button>`
+        : '';
 
     jobStatus.innerHTML = `
         <div class="status">
-            <p><strong>任务状态：</strong> ${payload.status}</p>
+            <p><strong>任务状态：</strong> ${payload.status} ${cancelButton}</p>
             ${detail}
+            ${combined}
             <div class="reviews">${cards}</div>
         </div>
     `;
@@ -240,20 +302,59 @@ const fetchStatus = async (jobId) => {
         const payload = await response.json();
         renderJobStatus(payload);
 
-        if (payload.status === 'completed' || payload.status === 'failed') {
+        if (['completed', 'failed', 'cancelled'].includes(payload.status)) {
             stopPolling();
             if (payload.status === 'completed') {
                 setStatus('审稿完成，可查看下方下载链接。', 'success');
+            } else if (payload.status === 'cancelled') {
+                setStatus('任务已取消，已完成的评审仍可下载。', null);
             } else {
                 setStatus('任务失败，请查看状态信息。', 'error');
             }
         }
+        return payload;
     } catch (error) {
         stopPolling();
         setStatus('轮询失败：' + error.message, 'error');
+        return null;
+    }
+};
+
+const pollStatus = async (jobId) => {
+    const payload = await fetchStatus(jobId);
+    if (!payload || jobId !== activeJobId) {
+        return;
+    }
+    const delay = window.nextPollDelay(payload, 5000);
+    if (delay) {
+        pollTimer = setTimeout(() => pollStatus(jobId), delay);
     }
 };
 
+jobStatus.addEventListener('click', async (event) => {
+    const button = event.target.closest('[data-cancel-job]');
+    if (!button) {
+        return;
+    }
+    if (!confirm('确定要取消该任务吗？已完成的评审报告会保留。')) {
+        return;
+    }
+
+    const jobId = Number(button.dataset.cancelJob);
+    button.disabled = true;
+    try {
+        const response = await fetch(`/api/reviewer/jobs/${jobId}/cancel`, { method: 'POST' });
+        const payload = await response.json().catch(() => ({ message: '取消失败。' }));
+        setStatus(response.ok ? '已请求取消任务，正在停止进行中的评审...' : (payload.message || '取消失败。'), response.ok ? 'success' : 'error');
+        await fetchStatus(jobId);
+        // The worker settles the cancelled reviews within a few seconds; show the final state.
+        setTimeout(() => fetchStatus(jobId), 4000);
+    } catch (error) {
+        button.disabled = false;
+        setStatus('取消失败：' + error.message, 'error');
+    }
+});
+
 form.addEventListener('submit', async (event) => {
     event.preventDefault();
     if (!fileInput || fileInput.files.length === 0) {
@@ -281,36 +382,36 @@ form.addEventListener('submit', async (event) => {
         const payload = await response.json();
         setStatus('任务已创建，正在执行审稿流程...', 'success');
         renderJobStatus(null);
-        fetchStatus(payload.job_id);
-        pollTimer = setInterval(() => fetchStatus(payload.job_id), 5000);
+        activeJobId = payload.job_id;
+        pollStatus(payload.job_id);
         form.reset();
-        if (fileInput) {
-            fileInput.value = '';
-            fileInput.dispatchEvent(new Event('change'));
-        }
+        [fileInput, supplementInput].forEach((input) => {
+            if (input) {
+                input.value = '';
+                input.dispatchEvent(new Event('change'));
+            }
+        });
     } catch (error) {
         setStatus('提交失败：' + error.message, 'error');
     }
 });
 "#;
 
-    let html = render_tool_page(ToolPageLayout {
+    ToolPageLayout {
         meta_title: "审稿助手 | Zhang Group AI Toolkit",
         page_heading: "审稿助手",
-        username: &username,
-        note_html: Cow::Owned(note_html),
         tab_group: "reviewer",
         new_tab_label: "新任务",
         new_tab_html: Cow::Owned(new_tab_html),
         history_tab_label: "历史记录",
         history_panel_html: Cow::Owned(history_panel),
-        admin_link,
         footer_html: Cow::Owned(render_footer()),
         extra_style_blocks: vec![
             Cow::Borrowed(history_ui::HISTORY_STYLES),
             Cow::Borrowed(UPLOAD_WIDGET_STYLES),
         ],
         body_scripts: vec![
+            Cow::Borrowed(STATUS_CLIENT_SCRIPT),
             Cow::Borrowed(UPLOAD_WIDGET_SCRIPT),
             Cow::Owned(format!(
                 "<script>
@@ -325,9 +426,7 @@ form.addEventListener('submit', async (event) => {
                 history_ui::HISTORY_SCRIPT
             )),
         ],
-    });
-
-    Ok(Html(html))
+    }
 }
 
 async fn create_job(
@@ -341,7 +440,7 @@ async fn create_job(
 
     if let Err(e) = usage::ensure_within_limits(state.pool_ref(), user.id, MODULE_REVIEWER, 1).await
     {
-        return Err(json_response(StatusCode::TOO_MANY_REQUESTS, e.message()));
+        return Err(quota_error(StatusCode::TOO_MANY_REQUESTS, &e).into_response());
     }
 
     ensure_storage_root(STORAGE_ROOT)
@@ -351,24 +450,34 @@ async fn create_job(
     let temp_dir = PathBuf::from(STORAGE_ROOT).join(format!("tmp_{}", Uuid::new_v4()));
     let file_config = FileFieldConfig::new(
         "file",
-        &["pdf", "docx"],
-        1,
+        &["pdf", "docx", "png", "jpg", "jpeg"],
+        MAX_PAGE_IMAGES,
         FileNaming::PrefixOnly {
             prefix: "manuscript_",
         },
     )
     .with_min_files(1);
-
-    let upload = match process_upload_form(multipart, &temp_dir, &[file_config]).await {
-        Ok(outcome) => outcome,
-        Err(err) => {
-            let _ = tokio_fs::remove_dir_all(&temp_dir).await;
-            return Err(json_response(
-                StatusCode::BAD_REQUEST,
-                err.message().to_string(),
-            ));
-        }
-    };
+    let supplement_config = FileFieldConfig::new(
+        "supplement",
+        &["pdf"],
+        1,
+        FileNaming::PrefixOnly {
+            prefix: "supplement_",
+        },
+    )
+    .with_min_files(0);
+
+    let upload =
+        match process_upload_form(multipart, &temp_dir, &[file_config, supplement_config]).await {
+            Ok(outcome) => outcome,
+            Err(err) => {
+                let _ = tokio_fs::remove_dir_all(&temp_dir).await;
+                return Err(json_response(
+                    StatusCode::BAD_REQUEST,
+                    err.message().to_string(),
+                ));
+            }
+        };
 
     let language = upload
         .first_text("language")
@@ -380,36 +489,76 @@ async fn create_job(
         return Err(json_response(StatusCode::BAD_REQUEST, "Invalid language"));
     }
 
-    let file = match upload.first_file_for("file").cloned() {
-        Some(file) => file,
-        None => {
-            let _ = tokio_fs::remove_dir_all(&temp_dir).await;
-            return Err(json_response(StatusCode::BAD_REQUEST, "No file provided"));
-        }
+    let numbered = matches!(
+        upload.first_text("number_paragraphs").map(str::trim),
+        Some("on" | "true" | "1" | "yes")
+    );
+
+    let files: Vec<_> = upload.files_for("file").cloned().collect();
+    let Some(file) = files.first().cloned() else {
+        let _ = tokio_fs::remove_dir_all(&temp_dir).await;
+        return Err(json_response(StatusCode::BAD_REQUEST, "No file provided"));
     };
 
-    let ext = file
-        .original_name
-        .rsplit('.')
-        .next()
-        .unwrap_or("")
-        .to_lowercase();
-    if ext != "pdf" && ext != "docx" {
+    let ext = file_extension(&file.original_name);
+    let page_images = files
+        .iter()
+        .all(|file| PAGE_IMAGE_EXTENSIONS.contains(&file_extension(&file.original_name).as_str()));
+    if !page_images && (files.len() > 1 || (ext != "pdf" && ext != "docx")) {
         let _ = tokio_fs::remove_dir_all(&temp_dir).await;
         return Err(json_response(
             StatusCode::BAD_REQUEST,
-            "Only PDF and DOCX files are accepted",
+            "Upload a single PDF or DOCX file, or only PNG/JPG page images",
+        ));
+    }
+
+    let Some(reviewer_settings) = state.reviewer_settings().await else {
+        let _ = tokio_fs::remove_dir_all(&temp_dir).await;
+        return Err(json_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Reviewer settings not configured",
         ));
+    };
+
+    // Page images count one page each and are limited by their combined size. DOCX pages are
+    // only known after conversion, so those are checked in the worker.
+    let (manuscript_size, pages) = if page_images {
+        (
+            files.iter().map(|file| file.file_size).sum(),
+            Some(files.len()),
+        )
+    } else if ext == "pdf" && reviewer_settings.models.max_manuscript_pages > 0 {
+        (
+            file.file_size,
+            count_pdf_pages(file.stored_path.clone()).await,
+        )
+    } else {
+        (file.file_size, None)
+    };
+    if let Some(message) = manuscript_limit_error(&reviewer_settings.models, manuscript_size, pages)
+    {
+        let _ = tokio_fs::remove_dir_all(&temp_dir).await;
+        return Err(json_response(StatusCode::PAYLOAD_TOO_LARGE, message));
+    }
+
+    let supplement = upload.first_file_for("supplement").cloned();
+    if let Some(message) = supplement
+        .as_ref()
+        .and_then(|file| supplement_limit_error(&reviewer_settings.models, file.file_size))
+    {
+        let _ = tokio_fs::remove_dir_all(&temp_dir).await;
+        return Err(json_response(StatusCode::PAYLOAD_TOO_LARGE, message));
     }
 
     let job_id: i32 = match sqlx::query_scalar(
-        "INSERT INTO reviewer_jobs (user_id, filename, language, status)
-         VALUES ($1, $2, $3, $4) RETURNING job_id",
+        "INSERT INTO reviewer_jobs (user_id, filename, language, status, numbered_paragraphs)
+         VALUES ($1, $2, $3, $4, $5) RETURNING job_id",
     )
     .bind(user.id)
-    .bind(&file.original_name)
+    .bind(manuscript_display_name(&file.original_name, files.len()))
     .bind(&language)
     .bind(STATUS_PENDING)
+    .bind(numbered)
     .fetch_one(state.pool_ref())
     .await
     {
@@ -434,26 +583,46 @@ async fn create_job(
         ));
     }
 
-    let manuscript_path = final_dir.join(&file.stored_name);
-    if let Err(e) = tokio_fs::rename(&file.stored_path, &manuscript_path).await {
-        let _ = tokio_fs::remove_dir_all(&temp_dir).await;
-        error!("Failed to persist manuscript: {e}");
-        return Err(json_response(
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "Failed to save file",
-        ));
+    let mut manuscript_paths = Vec::with_capacity(files.len());
+    for file in &files {
+        let path = final_dir.join(&file.stored_name);
+        if let Err(e) = tokio_fs::rename(&file.stored_path, &path).await {
+            let _ = tokio_fs::remove_dir_all(&temp_dir).await;
+            error!("Failed to persist manuscript: {e}");
+            return Err(json_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to save file",
+            ));
+        }
+        manuscript_paths.push(path);
     }
+    let manuscript = if page_images {
+        Manuscript::PageImages(manuscript_paths)
+    } else {
+        Manuscript::Document {
+            path: manuscript_paths.remove(0),
+            ext,
+        }
+    };
+    let supplement_path = match supplement {
+        Some(file) => {
+            let path = final_dir.join(&file.stored_name);
+            if let Err(e) = tokio_fs::rename(&file.stored_path, &path).await {
+                let _ = tokio_fs::remove_dir_all(&temp_dir).await;
+                error!("Failed to persist supplementary file: {e}");
+                return Err(json_response(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Failed to save file",
+                ));
+            }
+            Some(path)
+        }
+        None => None,
+    };
     let _ = tokio_fs::remove_dir_all(&temp_dir).await;
 
     let pool = state.pool().clone();
     let llm_client = state.llm_client().clone();
-    let reviewer_settings = state.reviewer_settings().await.ok_or_else(|| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "Reviewer settings not configured",
-        )
-            .into_response()
-    })?;
 
     if let Err(err) =
         history::record_job_start(&pool, MODULE_REVIEWER, user.id, job_id.to_string()).await
@@ -462,36 +631,119 @@ async fn create_job(
     }
 
     let language_clone = language.clone();
-    let ext_clone = ext.clone();
+    let mailer = state.mailer().cloned();
     tokio::spawn(async move {
         if let Err(e) = process_reviewer_job(
             pool.clone(),
             llm_client,
             job_id,
             user.id,
-            manuscript_path.clone(),
+            manuscript,
+            supplement_path,
             &language_clone,
-            &ext_clone,
+            numbered,
             reviewer_settings,
         )
         .await
         {
+            if e.downcast_ref::<JobCancelled>().is_some() {
+                return;
+            }
             error!("Job {job_id} failed: {e}");
             let _ = sqlx::query(
                 "UPDATE reviewer_jobs SET status = $1, status_detail = $2, updated_at = NOW()
-                 WHERE job_id = $3",
+                 WHERE job_id = $3 AND status <> $4",
             )
             .bind(STATUS_FAILED)
             .bind(format!("Error: {e}"))
             .bind(job_id)
+            .bind(STATUS_CANCELLED)
             .execute(&pool)
             .await;
         }
+
+        notifications::notify_job_owner(
+            &pool,
+            mailer.as_ref(),
+            "reviewer_jobs",
+            "job_id",
+            MODULE_REVIEWER,
+            job_id,
+        )
+        .await;
     });
 
     Ok(Json(json!({ "job_id": job_id })))
 }
 
+/// Explains why a manuscript exceeds the configured reviewer limits, if it does. Every parallel
+/// round-1 review receives the whole file, so oversize inputs are refused up front.
+fn manuscript_limit_error(
+    models: &ReviewerModels,
+    file_size: u64,
+    pages: Option<usize>,
+) -> Option<String> {
+    let max_bytes = models.max_manuscript_mb.saturating_mul(1024 * 1024);
+    if max_bytes > 0 && file_size > max_bytes {
+        return Some(format!(
+            "Manuscript is {:.1} MB; the reviewer accepts files up to {} MB.",
+            file_size as f64 / (1024.0 * 1024.0),
+            models.max_manuscript_mb
+        ));
+    }
+
+    let max_pages = models.max_manuscript_pages;
+    match pages {
+        Some(pages) if max_pages > 0 && pages as u64 > max_pages => Some(format!(
+            "Manuscript has {pages} pages; the reviewer accepts up to {max_pages} pages."
+        )),
+        _ => None,
+    }
+}
+
+/// The optional supplementary PDF is sent alongside the manuscript on every call, so it is held
+/// to the same size limit.
+fn supplement_limit_error(models: &ReviewerModels, file_size: u64) -> Option<String> {
+    let max_bytes = models.max_manuscript_mb.saturating_mul(1024 * 1024);
+    (max_bytes > 0 && file_size > max_bytes).then(|| {
+        format!(
+            "Supplementary file is {:.1} MB; the reviewer accepts files up to {} MB.",
+            file_size as f64 / (1024.0 * 1024.0),
+            models.max_manuscript_mb
+        )
+    })
+}
+
+/// Page count of a PDF, or `None` (logged) when it cannot be parsed; the review itself may
+/// still succeed since providers read the file independently.
+async fn count_pdf_pages(path: PathBuf) -> Option<usize> {
+    let counted = tokio::task::spawn_blocking(move || pdf_page_count(&path)).await;
+    match counted {
+        Ok(Ok(pages)) => Some(pages),
+        Ok(Err(err)) => {
+            warn!(?err, "failed to count reviewer manuscript pages");
+            None
+        }
+        Err(err) => {
+            warn!(?err, "page count task panicked");
+            None
+        }
+    }
+}
+
+fn file_extension(name: &str) -> String {
+    name.rsplit('.').next().unwrap_or("").to_lowercase()
+}
+
+/// Name recorded for the job: the file itself, or the first of several page images.
+fn manuscript_display_name(first: &str, files: usize) -> String {
+    if files > 1 {
+        format!("{first} 等 {files} 张页面图片")
+    } else {
+        first.to_string()
+    }
+}
+
 async fn job_status(
     State(state): State<AppState>,
     jar: CookieJar,
@@ -547,6 +799,7 @@ async fn job_status(
     let mut round1_reviews = Vec::new();
     let mut round2_review = None;
     let mut round3_review = None;
+    let mut any_available = false;
 
     for doc in docs {
         let DocRow {
@@ -563,12 +816,14 @@ async fn job_status(
                 .as_ref()
                 .map(|path| !path.is_empty())
                 .unwrap_or(false);
+        any_available |= has_file;
 
         match round {
             1 => {
                 let idx = review_index.unwrap_or(0);
                 round1_reviews.push(ReviewInfo {
                     model: model_name,
+                    status,
                     available: has_file,
                     download_url: if has_file {
                         Some(format!(
@@ -582,6 +837,7 @@ async fn job_status(
             2 => {
                 round2_review = Some(ReviewInfo {
                     model: model_name,
+                    status,
                     available: has_file,
                     download_url: if has_file {
                         Some(format!(
@@ -595,6 +851,7 @@ async fn job_status(
             3 => {
                 round3_review = Some(ReviewInfo {
                     model: model_name,
+                    status,
                     available: has_file,
                     download_url: if has_file {
                         Some(format!(
@@ -610,6 +867,7 @@ async fn job_status(
     }
 
     Ok(Json(JobStatusResponse {
+        poll_interval_ms: JobStatus::from_str(&job.status).poll_interval_ms(),
         status: job.status,
         status_detail: job.status_detail,
         round1_reviews: if !round1_reviews.is_empty() {
@@ -619,10 +877,74 @@ async fn job_status(
         },
         round2_review,
         round3_review,
+        combined_report_url: any_available
+            .then(|| format!("/api/reviewer/jobs/{job_id}/report/combined.docx")),
         error: None,
     }))
 }
 
+/// Requests cancellation of a pending or running job. The worker notices within
+/// `CANCEL_CHECK_INTERVAL`, aborts the calls in flight and marks their reviews cancelled;
+/// reports that were already written stay downloadable.
+async fn cancel_job(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    AxumPath(job_id): AxumPath<i32>,
+) -> Result<Json<ApiMessage>, Response> {
+    let user = auth::current_user_or_json_error(&state, &jar)
+        .await
+        .map_err(|JsonAuthError { status, message }| {
+            (status, Json(json!({ "message": message }))).into_response()
+        })?;
+
+    let job = verify_job_access(
+        || {
+            sqlx::query_as::<_, JobRow>(
+                "SELECT user_id, status, status_detail, files_purged_at
+                 FROM reviewer_jobs WHERE job_id = $1",
+            )
+            .bind(job_id)
+            .fetch_optional(state.pool_ref())
+        },
+        &user,
+        AccessMessages {
+            not_found: "Job not found",
+            forbidden: "Access denied",
+            purged: "审稿文件已过期并被清除。",
+        },
+    )
+    .await
+    .map_err(|err| err.into_response())?;
+
+    let finished = || json_response(StatusCode::CONFLICT, "Job has already finished");
+    if job.status != STATUS_PENDING && job.status != STATUS_PROCESSING {
+        return Err(finished());
+    }
+
+    let updated = sqlx::query(
+        "UPDATE reviewer_jobs SET status = $2, status_detail = $3, updated_at = NOW()
+         WHERE job_id = $1 AND status IN ($4, $5)",
+    )
+    .bind(job_id)
+    .bind(STATUS_CANCELLED)
+    .bind("Cancellation requested; stopping the reviews in progress...")
+    .bind(STATUS_PENDING)
+    .bind(STATUS_PROCESSING)
+    .execute(state.pool_ref())
+    .await
+    .map_err(|e| {
+        error!("Database error: {e}");
+        json_response(StatusCode::INTERNAL_SERVER_ERROR, "Database error")
+    })?;
+    if updated.rows_affected() == 0 {
+        return Err(finished());
+    }
+
+    Ok(Json(ApiMessage::new(
+        "Job cancelled; completed reviews remain available for download.",
+    )))
+}
+
 async fn download_review(
     State(state): State<AppState>,
     jar: CookieJar,
@@ -681,6 +1003,10 @@ async fn download_review(
         .file_name()
         .and_then(|n| n.to_str())
         .unwrap_or("review.docx");
+    let permit = state
+        .download_limiter()
+        .acquire(&user)
+        .map_err(|err| err.into_response())?;
 
     stream_file(
         Path::new(&file_path),
@@ -688,129 +1014,397 @@ async fn download_review(
         "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
     )
     .await
+    .map(|response| hold_permit(response, permit))
     .map_err(|err| err.into_response())
 }
 
+#[derive(sqlx::FromRow)]
+struct ReportRow {
+    round: i32,
+    review_index: Option<i32>,
+    model_name: String,
+    review_text: String,
+}
+
+/// Concatenates every completed review of a job into one DOCX, in the order round 1 (by
+/// index), round 2, round 3. Built from `review_text`, so no per-round file is re-read.
+async fn download_combined_report(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    AxumPath(job_id): AxumPath<i32>,
+) -> Result<Response, Response> {
+    let user = auth::current_user_or_json_error(&state, &jar)
+        .await
+        .map_err(|JsonAuthError { status, message }| {
+            (status, Json(json!({ "message": message }))).into_response()
+        })?;
+
+    verify_job_access(
+        || {
+            sqlx::query_as::<_, JobRow>(
+                "SELECT user_id, status, status_detail, files_purged_at
+                 FROM reviewer_jobs WHERE job_id = $1",
+            )
+            .bind(job_id)
+            .fetch_optional(state.pool_ref())
+        },
+        &user,
+        AccessMessages {
+            not_found: "Job not found",
+            forbidden: "Access denied",
+            purged: "审稿文件已过期并被清除。",
+        },
+    )
+    .await
+    .map_err(|err| err.into_response())?;
+
+    // Only reviews whose DOCX is still downloadable, so the combined report never contains
+    // output that the per-review links no longer offer (e.g. after a failed job).
+    let rows = sqlx::query_as::<_, ReportRow>(
+        "SELECT round, review_index, model_name, review_text FROM reviewer_documents
+         WHERE job_id = $1 AND status = $2 AND review_text IS NOT NULL AND file_path IS NOT NULL
+         ORDER BY round, review_index",
+    )
+    .bind(job_id)
+    .bind(STATUS_COMPLETED)
+    .fetch_all(state.pool_ref())
+    .await
+    .map_err(|e| {
+        error!("Database error: {e}");
+        json_response(StatusCode::INTERNAL_SERVER_ERROR, "Database error")
+    })?;
+
+    if rows.is_empty() {
+        return Err(json_response(
+            StatusCode::NOT_FOUND,
+            "No completed reviews available",
+        ));
+    }
+
+    let permit = state
+        .download_limiter()
+        .acquire(&user)
+        .map_err(|err| err.into_response())?;
+
+    let bytes = combined_report_docx(&rows).map_err(|e| {
+        error!("Failed to build combined report for job {job_id}: {e}");
+        json_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to build combined report",
+        )
+    })?;
+    Ok(hold_permit(
+        attachment_response(
+            bytes,
+            &format!("reviewer_{job_id}_combined.docx"),
+            "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        ),
+        permit,
+    ))
+}
+
+fn report_heading(row: &ReportRow) -> String {
+    match row.round {
+        1 => format!(
+            "第一轮评审 {}（{}）",
+            row.review_index.unwrap_or(0) + 1,
+            row.model_name
+        ),
+        2 => format!("第二轮元审稿（{}）", row.model_name),
+        3 => format!("第三轮事实核查（{}）", row.model_name),
+        round => format!("第 {round} 轮（{}）", row.model_name),
+    }
+}
+
+fn combined_report_docx(rows: &[ReportRow]) -> Result<Vec<u8>> {
+    let headings: Vec<String> = rows.iter().map(report_heading).collect();
+    let sections: Vec<DocxSection<'_>> = rows
+        .iter()
+        .zip(&headings)
+        .map(|(row, heading)| DocxSection {
+            heading: Some(heading),
+            body: row.review_text.trim(),
+        })
+        .collect();
+    text_docx_bytes(&sections)
+}
+
+/// Files the reviewer pipeline writes into the job directory, tracked so they can be removed
+/// deterministically once the job settles.
+#[derive(Default)]
+struct ReviewArtifacts {
+    /// Conversion by-products (the PDF rendered from a DOCX upload); never kept.
+    intermediates: Vec<PathBuf>,
+    /// Round DOCX reports; kept only when the whole pipeline succeeds.
+    reports: Vec<PathBuf>,
+}
+
+impl ReviewArtifacts {
+    fn track_intermediate(&mut self, path: PathBuf) {
+        self.intermediates.push(path);
+    }
+
+    fn track_report(&mut self, path: PathBuf) {
+        self.reports.push(path);
+    }
+
+    /// Removes conversion by-products, plus the round reports when the job failed.
+    async fn cleanup(self, succeeded: bool) {
+        let reports = if succeeded { Vec::new() } else { self.reports };
+        for path in self.intermediates.into_iter().chain(reports) {
+            match tokio_fs::remove_file(&path).await {
+                Ok(()) => {}
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                Err(err) => {
+                    error!(?err, path = %path.display(), "failed to remove reviewer artifact");
+                }
+            }
+        }
+    }
+}
+
 // Background processing function
+#[allow(clippy::too_many_arguments)]
 async fn process_reviewer_job(
     pool: PgPool,
     llm_client: LlmClient,
     job_id: i32,
     user_id: Uuid,
-    manuscript_path: PathBuf,
+    manuscript: Manuscript,
+    supplement_path: Option<PathBuf>,
     language: &str,
-    ext: &str,
+    numbered: bool,
     settings: crate::config::ReviewerSettings,
 ) -> Result<()> {
-    // Update status to processing
-    sqlx::query(
-        "UPDATE reviewer_jobs SET status = $1, status_detail = $2, updated_at = NOW()
-         WHERE job_id = $3",
+    let mut artifacts = ReviewArtifacts::default();
+    let result = run_review_pipeline(
+        pool.clone(),
+        llm_client,
+        job_id,
+        user_id,
+        manuscript,
+        supplement_path,
+        language,
+        numbered,
+        settings,
+        &mut artifacts,
     )
-    .bind(STATUS_PROCESSING)
-    .bind("Starting review process...")
-    .bind(job_id)
-    .execute(&pool)
-    .await?;
+    .await;
 
-    // Convert DOCX to PDF if needed
-    let pdf_path = if ext == "docx" {
-        convert_docx_to_pdf(&manuscript_path).await?
-    } else {
-        manuscript_path.clone()
-    };
+    let cancelled = result
+        .as_ref()
+        .is_err_and(|err| err.downcast_ref::<JobCancelled>().is_some());
+    if cancelled
+        && let Err(err) = settle_cancelled_job(&pool, job_id, user_id, &mut artifacts).await
+    {
+        error!(?err, job_id, "failed to record reviewer job cancellation");
+    }
 
-    // Round 1: 8 parallel reviews with retry
-    sqlx::query(
-        "UPDATE reviewer_jobs SET status_detail = $1, updated_at = NOW() WHERE job_id = $2",
+    artifacts.cleanup(result.is_ok() || cancelled).await;
+    if result.is_err() && !cancelled {
+        // The round reports were removed above, so stop advertising them for download.
+        if let Err(err) =
+            sqlx::query("UPDATE reviewer_documents SET file_path = NULL WHERE job_id = $1")
+                .bind(job_id)
+                .execute(&pool)
+                .await
+        {
+            error!(?err, job_id, "failed to clear reviewer document paths");
+        }
+    }
+
+    result
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn run_review_pipeline(
+    pool: PgPool,
+    llm_client: LlmClient,
+    job_id: i32,
+    user_id: Uuid,
+    manuscript: Manuscript,
+    supplement_path: Option<PathBuf>,
+    language: &str,
+    numbered: bool,
+    settings: crate::config::ReviewerSettings,
+    artifacts: &mut ReviewArtifacts,
+) -> Result<()> {
+    // Update status to processing, unless the job was cancelled while it was queued
+    let started = sqlx::query(
+        "UPDATE reviewer_jobs SET status = $1, status_detail = $2, updated_at = NOW()
+         WHERE job_id = $3 AND status = $4",
     )
-    .bind("Round 1: Running 8 parallel reviews...")
+    .bind(STATUS_PROCESSING)
+    .bind("Starting review process...")
     .bind(job_id)
+    .bind(STATUS_PENDING)
     .execute(&pool)
     .await?;
+    if started.rows_affected() == 0 {
+        return Err(JobCancelled.into());
+    }
+
+    // Page images are sent as they are; a document is sent as one PDF, converted from DOCX
+    // if needed. Only documents have text to number.
+    let (mut attachments, text_source) = match manuscript {
+        Manuscript::Document { path, ext } => {
+            let pdf_path = if ext == "docx" {
+                // Track the expected output before converting so a partial PDF is cleaned up too.
+                artifacts.track_intermediate(path.with_extension("pdf"));
+                let pdf_path = convert_docx_to_pdf(&path).await?;
+                if settings.models.max_manuscript_pages > 0 {
+                    let pages = count_pdf_pages(pdf_path.clone()).await;
+                    if let Some(message) = manuscript_limit_error(&settings.models, 0, pages) {
+                        return Err(anyhow!(message));
+                    }
+                }
+                pdf_path
+            } else {
+                path.clone()
+            };
+            (
+                vec![ReviewAttachment::pdf(pdf_path, "manuscript.pdf")],
+                Some(path),
+            )
+        }
+        Manuscript::PageImages(pages) => (
+            pages
+                .into_iter()
+                .enumerate()
+                .map(|(idx, path)| ReviewAttachment::page_image(path, idx))
+                .collect::<Vec<_>>(),
+            None,
+        ),
+    };
+    let notes = AttachmentNotes {
+        page_images: attachments
+            .iter()
+            .filter(|attachment| attachment.kind == AttachmentKind::Image)
+            .count(),
+        has_supplement: supplement_path.is_some(),
+    };
+    attachments.extend(supplement_path.map(|path| ReviewAttachment::pdf(path, "supplement.pdf")));
+
+    // Round 1: one parallel review per configured model, with retry
+    let round1_models = &settings.models.round1_models;
+    if round1_models.is_empty() {
+        return Err(anyhow!("No round 1 reviewer models are configured"));
+    }
+    let round1_min_successes = settings.models.round1_min_successes();
+
+    update_status_detail(
+        &pool,
+        job_id,
+        &format!(
+            "Round 1: Running {} parallel reviews...",
+            round1_models.len()
+        ),
+    )
+    .await?;
 
-    let round1_models = vec![
-        settings.models.round1_model_1.clone(),
-        settings.models.round1_model_2.clone(),
-        settings.models.round1_model_3.clone(),
-        settings.models.round1_model_4.clone(),
-        settings.models.round1_model_5.clone(),
-        settings.models.round1_model_6.clone(),
-        settings.models.round1_model_7.clone(),
-        settings.models.round1_model_8.clone(),
-    ];
+    let numbered_text = match text_source {
+        Some(path) if numbered => load_numbered_manuscript(path).await,
+        _ => None,
+    };
 
     let round1_prompt = if language == "chinese" {
         &settings.prompts.initial_prompt_zh
     } else {
         &settings.prompts.initial_prompt
     };
+    let round1_prompt = with_attachment_notes(
+        with_numbered_manuscript(round1_prompt, numbered_text.as_deref(), language),
+        notes,
+        language,
+    );
 
     let mut round1_results = Vec::new();
-    let mut round1_futures = Vec::new();
+    let mut round1_tasks = Vec::new();
 
-    for (idx, model) in round1_models.iter().enumerate() {
+    for idx in 0..round1_models.len() {
         let pool_clone = pool.clone();
         let llm_clone = llm_client.clone();
-        let pdf_path_clone = pdf_path.clone();
+        let attachments_clone = attachments.clone();
         let prompt_clone = round1_prompt.clone();
-        let model_clone = model.clone();
+        let models_clone = settings.models.clone();
 
-        round1_futures.push(tokio::spawn(async move {
+        round1_tasks.push(tokio::spawn(async move {
             run_round1_review(
                 pool_clone,
                 llm_clone,
                 job_id,
                 idx as i32,
-                &pdf_path_clone,
+                &attachments_clone,
                 &prompt_clone,
-                &model_clone,
+                &models_clone,
             )
             .await
         }));
     }
 
-    for (idx, future) in round1_futures.into_iter().enumerate() {
-        match future.await {
-            Ok(Ok(review_text)) => {
-                round1_results.push((idx, review_text));
-            }
-            Ok(Err(e)) => {
-                error!("Round 1 review {idx} failed: {e}");
-            }
-            Err(e) => {
-                error!("Round 1 review {idx} task panicked: {e}");
+    let mut cancelled_at = None;
+    {
+        let cancellation = wait_for_cancellation(&pool, job_id);
+        tokio::pin!(cancellation);
+        for (idx, task) in round1_tasks.iter_mut().enumerate() {
+            let joined = tokio::select! {
+                joined = task => joined,
+                () = &mut cancellation => {
+                    cancelled_at = Some(idx);
+                    break;
+                }
+            };
+            match joined {
+                Ok(Ok(review_text)) => {
+                    round1_results.push((idx, review_text));
+                }
+                Ok(Err(e)) => {
+                    error!("Round 1 review {idx} failed: {e}");
+                }
+                Err(e) => {
+                    error!("Round 1 review {idx} task panicked: {e}");
+                }
             }
         }
     }
+    if let Some(first_unjoined) = cancelled_at {
+        let unjoined = round1_tasks.split_off(first_unjoined);
+        for task in &unjoined {
+            task.abort();
+        }
+        // Wait for the aborted tasks so none of them updates its row after it is cancelled.
+        for task in unjoined {
+            let _ = task.await;
+        }
+        return Err(JobCancelled.into());
+    }
 
-    if round1_results.len() < ROUND1_MIN_SUCCESSES {
+    if round1_results.len() < round1_min_successes {
         return Err(anyhow!(
-            "Round 1 failed: only {} out of 8 reviews succeeded (minimum {})",
+            "Round 1 failed: only {} out of {} reviews succeeded (minimum {})",
             round1_results.len(),
-            ROUND1_MIN_SUCCESSES
+            round1_models.len(),
+            round1_min_successes
         ));
     }
 
-    sqlx::query(
-        "UPDATE reviewer_jobs SET status_detail = $1, updated_at = NOW() WHERE job_id = $2",
+    update_status_detail(
+        &pool,
+        job_id,
+        &format!(
+            "Round 1 completed: {}/{} reviews succeeded",
+            round1_results.len(),
+            round1_models.len()
+        ),
     )
-    .bind(format!(
-        "Round 1 completed: {}/{} reviews succeeded",
-        round1_results.len(),
-        8
-    ))
-    .bind(job_id)
-    .execute(&pool)
     .await?;
 
     // Convert Round 1 reviews to DOCX and save
     for (idx, review_text) in &round1_results {
-        let docx_path = PathBuf::from(STORAGE_ROOT)
-            .join(job_id.to_string())
-            .join(format!("round1_review_{}.docx", idx + 1));
+        let docx_path = report_path(job_id, 1, *idx as i32);
 
-        text_to_docx(&review_text, &docx_path).await?;
+        artifacts.track_report(docx_path.clone());
+        write_text_docx(review_text, &docx_path)?;
 
         sqlx::query(
             "UPDATE reviewer_documents SET file_path = $1, updated_at = NOW()
@@ -824,13 +1418,8 @@ async fn process_reviewer_job(
     }
 
     // Round 2: Meta-review
-    sqlx::query(
-        "UPDATE reviewer_jobs SET status_detail = $1, updated_at = NOW() WHERE job_id = $2",
-    )
-    .bind("Round 2: Generating meta-review...")
-    .bind(job_id)
-    .execute(&pool)
-    .await?;
+    ensure_not_cancelled(&pool, job_id).await?;
+    update_status_detail(&pool, job_id, "Round 2: Generating meta-review...").await?;
 
     let round2_prompt = if language == "chinese" {
         &settings.prompts.secondary_prompt_zh
@@ -844,21 +1433,28 @@ async fn process_reviewer_job(
         .collect::<Vec<_>>()
         .join("\n");
 
-    let round2_text = run_round2_review(
+    let round2_text = until_cancelled(
         &pool,
-        &llm_client,
         job_id,
-        &pdf_path,
-        round2_prompt,
-        &combined_reviews,
-        &settings.models.round2_model,
+        run_round2_review(
+            &pool,
+            &llm_client,
+            job_id,
+            &attachments,
+            &with_attachment_notes(
+                with_numbered_manuscript(round2_prompt, numbered_text.as_deref(), language),
+                notes,
+                language,
+            ),
+            &combined_reviews,
+            &settings.models.round2_model,
+        ),
     )
     .await?;
 
-    let round2_docx = PathBuf::from(STORAGE_ROOT)
-        .join(job_id.to_string())
-        .join("round2_meta_review.docx");
-    text_to_docx(&round2_text, &round2_docx).await?;
+    let round2_docx = report_path(job_id, 2, 0);
+    artifacts.track_report(round2_docx.clone());
+    write_text_docx(&round2_text, &round2_docx)?;
 
     sqlx::query(
         "UPDATE reviewer_documents SET file_path = $1, updated_at = NOW()
@@ -870,13 +1466,8 @@ async fn process_reviewer_job(
     .await?;
 
     // Round 3: Fact-checking
-    sqlx::query(
-        "UPDATE reviewer_jobs SET status_detail = $1, updated_at = NOW() WHERE job_id = $2",
-    )
-    .bind("Round 3: Fact-checking...")
-    .bind(job_id)
-    .execute(&pool)
-    .await?;
+    ensure_not_cancelled(&pool, job_id).await?;
+    update_status_detail(&pool, job_id, "Round 3: Fact-checking...").await?;
 
     let round3_prompt = if language == "chinese" {
         &settings.prompts.final_prompt_zh
@@ -884,21 +1475,28 @@ async fn process_reviewer_job(
         &settings.prompts.final_prompt
     };
 
-    let round3_text = run_round3_review(
+    let round3_text = until_cancelled(
         &pool,
-        &llm_client,
         job_id,
-        &pdf_path,
-        round3_prompt,
-        &round2_text,
-        &settings.models.round3_model,
+        run_round3_review(
+            &pool,
+            &llm_client,
+            job_id,
+            &attachments,
+            &with_attachment_notes(
+                with_numbered_manuscript(round3_prompt, numbered_text.as_deref(), language),
+                notes,
+                language,
+            ),
+            &round2_text,
+            &settings.models.round3_model,
+        ),
     )
     .await?;
 
-    let round3_docx = PathBuf::from(STORAGE_ROOT)
-        .join(job_id.to_string())
-        .join("round3_final_report.docx");
-    text_to_docx(&round3_text, &round3_docx).await?;
+    let round3_docx = report_path(job_id, 3, 0);
+    artifacts.track_report(round3_docx.clone());
+    write_text_docx(&round3_text, &round3_docx)?;
 
     sqlx::query(
         "UPDATE reviewer_documents SET file_path = $1, updated_at = NOW()
@@ -909,20 +1507,174 @@ async fn process_reviewer_job(
     .execute(&pool)
     .await?;
 
+    // Mark job as completed; a cancellation that landed during round 3 still wins
+    let completed = sqlx::query(
+        "UPDATE reviewer_jobs SET status = $1, status_detail = $2, updated_at = NOW()
+         WHERE job_id = $3 AND status = $4",
+    )
+    .bind(STATUS_COMPLETED)
+    .bind("All rounds completed successfully")
+    .bind(job_id)
+    .bind(STATUS_PROCESSING)
+    .execute(&pool)
+    .await?;
+    if completed.rows_affected() == 0 {
+        return Err(JobCancelled.into());
+    }
+
     // Record usage (tokens are not tracked for reviewer module)
     usage::record_usage(&pool, user_id, MODULE_REVIEWER, 0, 1).await?;
 
-    // Mark job as completed
+    Ok(())
+}
+
+/// Returned by the pipeline once it notices the job was cancelled through `cancel_job`.
+#[derive(Debug)]
+struct JobCancelled;
+
+impl std::fmt::Display for JobCancelled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Job was cancelled")
+    }
+}
+
+impl std::error::Error for JobCancelled {}
+
+fn report_path(job_id: i32, round: i32, review_index: i32) -> PathBuf {
+    let file_name = match round {
+        1 => format!("round1_review_{}.docx", review_index + 1),
+        2 => "round2_meta_review.docx".to_string(),
+        _ => "round3_final_report.docx".to_string(),
+    };
+    PathBuf::from(STORAGE_ROOT)
+        .join(job_id.to_string())
+        .join(file_name)
+}
+
+/// Progress updates only apply while the job is running, so they never overwrite the detail
+/// of a cancelled job.
+async fn update_status_detail(pool: &PgPool, job_id: i32, detail: &str) -> Result<()> {
     sqlx::query(
-        "UPDATE reviewer_jobs SET status = $1, status_detail = $2, updated_at = NOW()
-         WHERE job_id = $3",
+        "UPDATE reviewer_jobs SET status_detail = $1, updated_at = NOW()
+         WHERE job_id = $2 AND status = $3",
+    )
+    .bind(detail)
+    .bind(job_id)
+    .bind(STATUS_PROCESSING)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+async fn job_is_cancelled(pool: &PgPool, job_id: i32) -> bool {
+    match sqlx::query_scalar::<_, String>("SELECT status FROM reviewer_jobs WHERE job_id = $1")
+        .bind(job_id)
+        .fetch_optional(pool)
+        .await
+    {
+        Ok(status) => status.as_deref() == Some(STATUS_CANCELLED),
+        Err(err) => {
+            warn!(?err, job_id, "failed to read reviewer job status");
+            false
+        }
+    }
+}
+
+async fn ensure_not_cancelled(pool: &PgPool, job_id: i32) -> Result<()> {
+    if job_is_cancelled(pool, job_id).await {
+        return Err(JobCancelled.into());
+    }
+    Ok(())
+}
+
+/// Resolves once the job has been cancelled, re-reading its status every
+/// `CANCEL_CHECK_INTERVAL`.
+async fn wait_for_cancellation(pool: &PgPool, job_id: i32) {
+    loop {
+        tokio::time::sleep(CANCEL_CHECK_INTERVAL).await;
+        if job_is_cancelled(pool, job_id).await {
+            return;
+        }
+    }
+}
+
+/// Runs `round`, dropping it (and the provider call in flight) if the job is cancelled first.
+async fn until_cancelled<T>(
+    pool: &PgPool,
+    job_id: i32,
+    round: impl std::future::Future<Output = Result<T>>,
+) -> Result<T> {
+    tokio::select! {
+        result = round => result,
+        () = wait_for_cancellation(pool, job_id) => Err(JobCancelled.into()),
+    }
+}
+
+/// Finishes a cancelled job: reviews that completed before the cancellation still get their
+/// DOCX report, reviews that were still running are marked cancelled.
+async fn settle_cancelled_job(
+    pool: &PgPool,
+    job_id: i32,
+    user_id: Uuid,
+    artifacts: &mut ReviewArtifacts,
+) -> Result<()> {
+    let unsaved: Vec<(i32, Option<i32>, String)> = sqlx::query_as(
+        "SELECT round, review_index, review_text FROM reviewer_documents
+         WHERE job_id = $1 AND status = $2 AND review_text IS NOT NULL AND file_path IS NULL",
     )
+    .bind(job_id)
     .bind(STATUS_COMPLETED)
-    .bind("All rounds completed successfully")
+    .fetch_all(pool)
+    .await?;
+    for (round, review_index, review_text) in unsaved {
+        let docx_path = report_path(job_id, round, review_index.unwrap_or(0));
+        artifacts.track_report(docx_path.clone());
+        write_text_docx(&review_text, &docx_path)?;
+        sqlx::query(
+            "UPDATE reviewer_documents SET file_path = $1, updated_at = NOW()
+             WHERE job_id = $2 AND round = $3 AND review_index IS NOT DISTINCT FROM $4",
+        )
+        .bind(docx_path.to_string_lossy().to_string())
+        .bind(job_id)
+        .bind(round)
+        .bind(review_index)
+        .execute(pool)
+        .await?;
+    }
+
+    sqlx::query(
+        "UPDATE reviewer_documents SET status = $1, error = $2, updated_at = NOW()
+         WHERE job_id = $3 AND status = $4",
+    )
+    .bind(STATUS_CANCELLED)
+    .bind("Cancelled before this review finished")
     .bind(job_id)
-    .execute(&pool)
+    .bind(STATUS_PROCESSING)
+    .execute(pool)
     .await?;
 
+    sqlx::query(
+        "UPDATE reviewer_jobs SET status_detail = $1, updated_at = NOW()
+         WHERE job_id = $2 AND status = $3",
+    )
+    .bind("Cancelled; completed reviews remain available for download.")
+    .bind(job_id)
+    .bind(STATUS_CANCELLED)
+    .execute(pool)
+    .await?;
+
+    // Finished reviews stay downloadable, so a cancelled job with any of them is billed like
+    // a completed one (one unit; tokens are not tracked for this module).
+    let completed_reviews: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM reviewer_documents WHERE job_id = $1 AND status = $2",
+    )
+    .bind(job_id)
+    .bind(STATUS_COMPLETED)
+    .fetch_one(pool)
+    .await?;
+    if completed_reviews > 0 {
+        usage::record_usage(pool, user_id, MODULE_REVIEWER, 0, 1).await?;
+    }
     Ok(())
 }
 
@@ -931,10 +1683,11 @@ async fn run_round1_review(
     llm_client: LlmClient,
     job_id: i32,
     idx: i32,
-    pdf_path: &Path,
+    attachments: &[ReviewAttachment],
     prompt: &str,
-    model: &str,
+    models: &ReviewerModels,
 ) -> Result<String> {
+    let model = models.round1_models[idx as usize].as_str();
     // Create document record
     sqlx::query(
         "INSERT INTO reviewer_documents (job_id, round, review_index, model_name, status)
@@ -947,32 +1700,31 @@ async fn run_round1_review(
     .execute(&pool)
     .await?;
 
-    let mut last_error = None;
-    for attempt in 0..ROUND1_RETRIES {
-        match call_llm(&llm_client, model, prompt, pdf_path).await {
-            Ok(text) => {
-                sqlx::query(
-                    "UPDATE reviewer_documents SET review_text = $1, status = $2, updated_at = NOW()
-                     WHERE job_id = $3 AND round = 1 AND review_index = $4"
-                )
-                .bind(&text)
-                .bind(STATUS_COMPLETED)
-                .bind(job_id)
-                .bind(idx)
-                .execute(&pool)
-                .await?;
-                return Ok(text);
-            }
-            Err(e) => {
-                last_error = Some(e);
-                if attempt < ROUND1_RETRIES - 1 {
-                    sleep(std::time::Duration::from_secs(2)).await;
-                }
-            }
+    let error_msg = match call_llm(
+        &llm_client,
+        model,
+        &models.round1_fallback_models,
+        prompt,
+        attachments,
+    )
+    .await
+    {
+        Ok(text) => {
+            sqlx::query(
+                "UPDATE reviewer_documents SET review_text = $1, status = $2, updated_at = NOW()
+                 WHERE job_id = $3 AND round = 1 AND review_index = $4",
+            )
+            .bind(&text)
+            .bind(STATUS_COMPLETED)
+            .bind(job_id)
+            .bind(idx)
+            .execute(&pool)
+            .await?;
+            return Ok(text);
         }
-    }
+        Err(err) => err.to_string(),
+    };
 
-    let error_msg = last_error.unwrap().to_string();
     sqlx::query(
         "UPDATE reviewer_documents SET status = $1, error = $2, updated_at = NOW()
          WHERE job_id = $3 AND round = 1 AND review_index = $4",
@@ -984,16 +1736,14 @@ async fn run_round1_review(
     .execute(&pool)
     .await?;
 
-    Err(anyhow!(
-        "Round 1 review {idx} failed after {ROUND1_RETRIES} attempts: {error_msg}"
-    ))
+    Err(anyhow!("Round 1 review {idx} failed: {error_msg}"))
 }
 
 async fn run_round2_review(
     pool: &PgPool,
     llm_client: &LlmClient,
     job_id: i32,
-    pdf_path: &Path,
+    attachments: &[ReviewAttachment],
     prompt: &str,
     combined_reviews: &str,
     model: &str,
@@ -1009,7 +1759,14 @@ async fn run_round2_review(
     .await?;
 
     let full_prompt = format!("{}\n\n{}", prompt, combined_reviews);
-    let text = call_llm(llm_client, model, &full_prompt, pdf_path).await?;
+    let text = match call_llm(llm_client, model, &[], &full_prompt, attachments).await {
+        Ok(text) => text,
+        Err(err) => {
+            let error_msg = err.to_string();
+            mark_round_failed(pool, job_id, 2, &error_msg).await?;
+            return Err(anyhow!("Round 2 meta-review failed: {error_msg}"));
+        }
+    };
 
     sqlx::query(
         "UPDATE reviewer_documents SET review_text = $1, status = $2, updated_at = NOW()
@@ -1028,7 +1785,7 @@ async fn run_round3_review(
     pool: &PgPool,
     llm_client: &LlmClient,
     job_id: i32,
-    pdf_path: &Path,
+    attachments: &[ReviewAttachment],
     prompt: &str,
     round2_text: &str,
     model: &str,
@@ -1044,7 +1801,14 @@ async fn run_round3_review(
     .await?;
 
     let full_prompt = format!("{}\n\n=== Review Report ===\n\n{}", prompt, round2_text);
-    let text = call_llm(llm_client, model, &full_prompt, pdf_path).await?;
+    let text = match call_llm(llm_client, model, &[], &full_prompt, attachments).await {
+        Ok(text) => text,
+        Err(err) => {
+            let error_msg = err.to_string();
+            mark_round_failed(pool, job_id, 3, &error_msg).await?;
+            return Err(anyhow!("Round 3 fact-check failed: {error_msg}"));
+        }
+    };
 
     sqlx::query(
         "UPDATE reviewer_documents SET review_text = $1, status = $2, updated_at = NOW()
@@ -1059,44 +1823,824 @@ async fn run_round3_review(
     Ok(text)
 }
 
+/// Records the final error of a single-review round (2 or 3) once `call_llm` has exhausted its
+/// retries, so the document does not stay `processing`.
+async fn mark_round_failed(pool: &PgPool, job_id: i32, round: i32, error_msg: &str) -> Result<()> {
+    sqlx::query(
+        "UPDATE reviewer_documents SET status = $1, error = $2, updated_at = NOW()
+         WHERE job_id = $3 AND round = $4",
+    )
+    .bind(STATUS_FAILED)
+    .bind(error_msg)
+    .bind(job_id)
+    .bind(round)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Manuscript text split into paragraphs tagged `[P1]`, `[P2]`, … so reviews can cite
+/// locations; `None` (logged) when no text can be extracted, in which case the job carries on
+/// with the attachment alone.
+async fn load_numbered_manuscript(path: PathBuf) -> Option<String> {
+    let extracted = tokio::task::spawn_blocking(move || read_document_text(&path)).await;
+    let text = match extracted {
+        Ok(Ok(text)) => text,
+        Ok(Err(err)) => {
+            warn!(
+                ?err,
+                "failed to extract reviewer manuscript text for numbering"
+            );
+            return None;
+        }
+        Err(err) => {
+            warn!(?err, "manuscript numbering task panicked");
+            return None;
+        }
+    };
+
+    let numbered = number_paragraphs(&text);
+    if numbered.is_none() {
+        warn!("reviewer manuscript has no extractable text to number");
+    }
+    numbered
+}
+
+/// Tags each blank-line separated paragraph with `[Pn]`, joining its wrapped lines.
+fn number_paragraphs(text: &str) -> Option<String> {
+    let mut numbered = Vec::new();
+    let mut current: Vec<&str> = Vec::new();
+    for line in text.lines().chain(std::iter::once("")) {
+        let line = line.trim();
+        if !line.is_empty() {
+            current.push(line);
+        } else if !current.is_empty() {
+            numbered.push(format!("[P{}] {}", numbered.len() + 1, current.join(" ")));
+            current.clear();
+        }
+    }
+
+    if numbered.is_empty() {
+        None
+    } else {
+        Some(numbered.join("\n\n"))
+    }
+}
+
+/// Appends the numbered manuscript text to a round prompt, asking for paragraph citations.
+fn with_numbered_manuscript(prompt: &str, numbered_text: Option<&str>, language: &str) -> String {
+    let Some(numbered_text) = numbered_text else {
+        return prompt.to_string();
+    };
+    let instructions = if language == "chinese" {
+        "=== 带段落编号的稿件正文 ===\n以下为附件稿件的正文，已按段落编号（[P1]、[P2]……）。提及稿件中的具体位置时，请引用对应的段落编号，例如 [P12]。"
+    } else {
+        "=== Numbered Manuscript Text ===\nThe attached manuscript's text is reproduced below with numbered paragraphs ([P1], [P2], ...). When you refer to a specific location in the manuscript, cite its paragraph number, e.g. [P12]."
+    };
+    format!("{prompt}\n\n{instructions}\n\n{numbered_text}")
+}
+
+/// What the prompt should say about the attachments beyond a single manuscript PDF.
+#[derive(Clone, Copy, Default)]
+struct AttachmentNotes {
+    page_images: usize,
+    has_supplement: bool,
+}
+
+/// Tells the model how the manuscript was attached when it arrives as page images, and that
+/// `supplement.pdf` is supplementary material, not part of the manuscript under review.
+fn with_attachment_notes(prompt: String, notes: AttachmentNotes, language: &str) -> String {
+    let chinese = language == "chinese";
+    let mut prompt = prompt;
+    if notes.page_images > 0 {
+        let pages = notes.page_images;
+        let note = if chinese {
+            format!(
+                "稿件以 {pages} 张页面图片的形式附上（page_1 至 page_{pages}），按页码顺序排列。"
+            )
+        } else {
+            format!(
+                "The manuscript is attached as {pages} page images (page_1 to page_{pages}) in page order."
+            )
+        };
+        prompt = format!("{prompt}\n\n{note}");
+    }
+    if notes.has_supplement {
+        let note = if chinese {
+            "附件 supplement.pdf 为作者提供的补充材料，可用于核对稿件内容，但不属于待审稿件本身。"
+        } else {
+            "The attached supplement.pdf is supplementary material provided by the authors. Use it to check the manuscript's claims, but do not review it as part of the manuscript."
+        };
+        prompt = format!("{prompt}\n\n{note}");
+    }
+    prompt
+}
+
+/// The uploaded manuscript, as handed to the worker.
+enum Manuscript {
+    /// A single PDF or DOCX file; DOCX is converted to PDF before round 1.
+    Document { path: PathBuf, ext: String },
+    /// One image per page, in upload order.
+    PageImages(Vec<PathBuf>),
+}
+
+/// A file sent with every review call: the manuscript (one PDF or its page images) first, then
+/// the optional supplementary PDF.
+#[derive(Clone, Debug)]
+struct ReviewAttachment {
+    path: PathBuf,
+    /// Name the model sees, independent of the stored filename.
+    name: String,
+    kind: AttachmentKind,
+    content_type: &'static str,
+}
+
+impl ReviewAttachment {
+    fn pdf(path: PathBuf, name: &str) -> Self {
+        Self {
+            path,
+            name: name.to_string(),
+            kind: AttachmentKind::Pdf,
+            content_type: "application/pdf",
+        }
+    }
+
+    /// Page `idx` (zero-based) of a manuscript uploaded as images.
+    fn page_image(path: PathBuf, idx: usize) -> Self {
+        let ext = file_extension(&path.to_string_lossy());
+        let content_type = if ext == "png" {
+            "image/png"
+        } else {
+            "image/jpeg"
+        };
+        Self {
+            name: format!("page_{}.{ext}", idx + 1),
+            path,
+            kind: AttachmentKind::Image,
+            content_type,
+        }
+    }
+
+    fn load(&self) -> Result<FileAttachment> {
+        Ok(FileAttachment::new(
+            self.name.clone(),
+            self.content_type,
+            self.kind,
+            fs::read(&self.path)?,
+        ))
+    }
+}
+
 async fn call_llm(
     llm_client: &LlmClient,
     model: &str,
+    fallback_models: &[String],
     prompt: &str,
-    pdf_path: &Path,
+    attachments: &[ReviewAttachment],
 ) -> Result<String> {
-    let pdf_bytes = fs::read(pdf_path)?;
-    let attachment = FileAttachment::new(
-        "manuscript.pdf",
-        "application/pdf",
-        AttachmentKind::Pdf,
-        pdf_bytes,
-    );
+    let attachments = attachments
+        .iter()
+        .map(ReviewAttachment::load)
+        .collect::<Result<Vec<_>>>()?;
 
     let request = LlmRequest::new(
         model.to_string(),
         vec![ChatMessage::new(MessageRole::User, prompt)],
     )
-    .with_attachments(vec![attachment]);
+    .with_attachments(attachments)
+    .with_fallback_models(fallback_models.to_vec())
+    .with_class(RequestClass::Interactive)
+    .with_module(MODULE_REVIEWER);
 
-    let response = llm_client.execute(request).await?;
+    let response = llm_client
+        .execute_with_retry(request, LLM_RETRY_POLICY)
+        .await?;
     Ok(response.text)
 }
 
-async fn text_to_docx(text: &str, output_path: &Path) -> Result<()> {
-    use docx_rs::*;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::update_reviewer_models;
+    use crate::test_support::{
+        StubProvider, StubReply, blank_pdf, create_user, multipart_form, recorded_usage,
+        session_jar, test_state,
+    };
+
+    fn limited_models(max_manuscript_mb: u64, max_manuscript_pages: u64) -> ReviewerModels {
+        ReviewerModels {
+            max_manuscript_mb,
+            max_manuscript_pages,
+            ..ReviewerModels::default()
+        }
+    }
 
-    let mut doc = Docx::new();
-    for paragraph_text in text.split("\n\n") {
-        let para = Paragraph::new().add_run(Run::new().add_text(paragraph_text));
-        doc = doc.add_paragraph(para);
+    #[test]
+    fn manuscript_limits_reject_large_files_and_long_pdfs() {
+        let models = limited_models(2, 10);
+        assert_eq!(manuscript_limit_error(&models, 1024, Some(10)), None);
+        assert_eq!(
+            manuscript_limit_error(&models, 3 * 1024 * 1024, Some(1)).as_deref(),
+            Some("Manuscript is 3.0 MB; the reviewer accepts files up to 2 MB.")
+        );
+        assert_eq!(
+            manuscript_limit_error(&models, 1024, Some(11)).as_deref(),
+            Some("Manuscript has 11 pages; the reviewer accepts up to 10 pages.")
+        );
+        // Unknown page counts and disabled limits never reject.
+        assert_eq!(manuscript_limit_error(&models, 1024, None), None);
+        assert_eq!(
+            manuscript_limit_error(&limited_models(0, 0), u64::MAX, Some(10_000)),
+            None
+        );
     }
 
-    let file = fs::File::create(output_path)
-        .with_context(|| format!("failed to create DOCX at {}", output_path.display()))?;
-    doc.build()
-        .pack(file)
-        .with_context(|| format!("failed to pack DOCX to {}", output_path.display()))?;
+    #[test]
+    fn number_paragraphs_tags_blank_line_separated_blocks() {
+        let text = "Introduction\n\nSoil carbon declines\nunder tillage.\n\n\n  Methods  ";
+        assert_eq!(
+            number_paragraphs(text).as_deref(),
+            Some("[P1] Introduction\n\n[P2] Soil carbon declines under tillage.\n\n[P3] Methods")
+        );
+        assert_eq!(number_paragraphs(" \n\n "), None);
+    }
 
-    Ok(())
+    #[tokio::test]
+    async fn numbered_manuscript_text_is_sent_to_the_model() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let manuscript = dir.path().join("manuscript.docx");
+        docx_rs::Docx::new()
+            .add_paragraph(
+                docx_rs::Paragraph::new().add_run(docx_rs::Run::new().add_text("Introduction")),
+            )
+            .add_paragraph(
+                docx_rs::Paragraph::new()
+                    .add_run(docx_rs::Run::new().add_text("Soil carbon declines under tillage.")),
+            )
+            .build()
+            .pack(fs::File::create(&manuscript).unwrap())
+            .unwrap();
+        let pdf = dir.path().join("manuscript.pdf");
+        fs::write(&pdf, blank_pdf(1)).unwrap();
+
+        let sent = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorder = sent.clone();
+        let provider = StubProvider::start(move |request| {
+            recorder.lock().unwrap().push(request.user.clone());
+            StubReply::text("Review citing [P2].")
+        })
+        .await;
+
+        let numbered = load_numbered_manuscript(manuscript).await;
+        let prompt = with_numbered_manuscript("Review this.", numbered.as_deref(), "english");
+        let review = call_llm(
+            &provider.client(),
+            "openrouter/test/model",
+            &[],
+            &prompt,
+            &[ReviewAttachment::pdf(pdf, "manuscript.pdf")],
+        )
+        .await
+        .unwrap();
+        assert_eq!(review, "Review citing [P2].");
+
+        let sent = sent.lock().unwrap();
+        assert_eq!(sent.len(), 1);
+        assert!(sent[0].starts_with("Review this.\n\n=== Numbered Manuscript Text ==="));
+        assert!(sent[0].ends_with("[P1] Introduction\n\n[P2] Soil carbon declines under tillage."));
+        assert_eq!(
+            with_numbered_manuscript("Review this.", None, "english"),
+            "Review this."
+        );
+    }
+
+    #[tokio::test]
+    async fn supplement_is_sent_as_a_second_pdf() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let manuscript = dir.path().join("manuscript.pdf");
+        let supplement = dir.path().join("supplement_data.pdf");
+        fs::write(&manuscript, blank_pdf(1)).unwrap();
+        fs::write(&supplement, blank_pdf(2)).unwrap();
+
+        let sent = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorder = sent.clone();
+        let provider = StubProvider::start(move |request| {
+            recorder
+                .lock()
+                .unwrap()
+                .push((request.user.clone(), request.files.len()));
+            StubReply::text("Review")
+        })
+        .await;
+
+        let notes = AttachmentNotes {
+            has_supplement: true,
+            ..AttachmentNotes::default()
+        };
+        let prompt = with_attachment_notes("Review this.".to_string(), notes, "english");
+        call_llm(
+            &provider.client(),
+            "openrouter/test/model",
+            &[],
+            &prompt,
+            &[
+                ReviewAttachment::pdf(manuscript, "manuscript.pdf"),
+                ReviewAttachment::pdf(supplement, "supplement.pdf"),
+            ],
+        )
+        .await
+        .unwrap();
+
+        let sent = sent.lock().unwrap();
+        assert_eq!(sent.len(), 1);
+        assert!(sent[0].0.contains("supplementary material"));
+        assert_eq!(sent[0].1, 2);
+        assert_eq!(
+            with_attachment_notes(
+                "Review this.".to_string(),
+                AttachmentNotes::default(),
+                "english"
+            ),
+            "Review this."
+        );
+    }
+
+    #[tokio::test]
+    async fn page_images_are_sent_in_order_in_one_request() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let first = dir.path().join("manuscript_scan_1.png");
+        let second = dir.path().join("manuscript_scan_2.JPG");
+        fs::write(&first, b"first page").unwrap();
+        fs::write(&second, b"second page").unwrap();
+
+        let sent = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorder = sent.clone();
+        let provider = StubProvider::start(move |request| {
+            recorder.lock().unwrap().push((
+                request.user.clone(),
+                request.images.clone(),
+                request.files.len(),
+            ));
+            StubReply::text("Review")
+        })
+        .await;
+
+        let notes = AttachmentNotes {
+            page_images: 2,
+            ..AttachmentNotes::default()
+        };
+        let prompt = with_attachment_notes("Review this.".to_string(), notes, "english");
+        call_llm(
+            &provider.client(),
+            "openrouter/test/model",
+            &[],
+            &prompt,
+            &[
+                ReviewAttachment::page_image(first, 0),
+                ReviewAttachment::page_image(second, 1),
+            ],
+        )
+        .await
+        .unwrap();
+
+        let sent = sent.lock().unwrap();
+        assert_eq!(provider.calls(), 1);
+        let (user, images, files) = &sent[0];
+        assert!(user.contains("attached as 2 page images"));
+        assert_eq!(*files, 0);
+        assert_eq!(images.len(), 2);
+        assert!(images[0].starts_with("data:image/png;base64,"));
+        assert!(images[1].starts_with("data:image/jpeg;base64,"));
+        assert_eq!(
+            manuscript_display_name("scan_1.png", 2),
+            "scan_1.png 等 2 张页面图片"
+        );
+    }
+
+    #[sqlx::test]
+    #[ignore = "needs DATABASE_URL (disposable Postgres)"]
+    async fn create_job_rejects_oversize_manuscript_before_review(pool: PgPool) {
+        let provider = StubProvider::start(|_| StubReply::text("Review")).await;
+        let state = test_state(pool.clone(), &provider).await;
+        update_reviewer_models(&pool, &limited_models(1, 2))
+            .await
+            .unwrap();
+        state.reload_settings().await.unwrap();
+        let user_id = create_user(&pool).await;
+
+        let long_pdf = blank_pdf(3);
+        let large_docx = vec![0u8; 1024 * 1024 + 1];
+        let cases: [(&str, &[u8], &str); 2] = [
+            (
+                "long.pdf",
+                &long_pdf,
+                "Manuscript has 3 pages; the reviewer accepts up to 2 pages.",
+            ),
+            (
+                "large.docx",
+                &large_docx,
+                "Manuscript is 1.0 MB; the reviewer accepts files up to 1 MB.",
+            ),
+        ];
+        for (filename, bytes, expected) in cases {
+            let form =
+                multipart_form(&[("file", filename, bytes)], &[("language", "english")]).await;
+            let jar = session_jar(&pool, user_id).await;
+
+            let response = create_job(State(state.clone()), jar, form)
+                .await
+                .expect_err("oversize manuscript must be rejected");
+            assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            let payload: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(payload["message"], expected);
+        }
+
+        let jobs: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM reviewer_jobs")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(jobs, 0);
+        assert_eq!(provider.calls(), 0);
+    }
+
+    #[sqlx::test]
+    #[ignore = "needs DATABASE_URL (disposable Postgres)"]
+    async fn legacy_round1_model_keys_migrate_to_list(pool: PgPool) {
+        sqlx::query(
+            "INSERT INTO module_configs (module_name, models, prompts) VALUES ('reviewer', $1, '{}')",
+        )
+        .bind(serde_json::json!({
+            "round1_model_1": "vendor/a",
+            "round1_model_2": " ",
+            "round1_model_3": "vendor/c",
+            "round2_model": "vendor/meta",
+        }))
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        sqlx::query(include_str!(
+            "../../../migrations/0024_reviewer_round1_model_list.sql"
+        ))
+        .execute(&pool)
+        .await
+        .unwrap();
+        crate::config::ModuleSettings::ensure_defaults(&pool)
+            .await
+            .unwrap();
+
+        let settings = crate::config::ModuleSettings::load(&pool).await.unwrap();
+        let models = &settings.reviewer().unwrap().models;
+        assert_eq!(models.round1_models, ["vendor/a", "vendor/c"]);
+        assert_eq!(models.round2_model, "vendor/meta");
+        assert_eq!(models.round1_min_successes(), 1);
+    }
+
+    #[sqlx::test]
+    #[ignore = "needs DATABASE_URL (disposable Postgres)"]
+    async fn cancel_aborts_round_one_and_keeps_finished_reviews(pool: PgPool) {
+        // The first model answers; the second never does until the job is cancelled.
+        let provider = StubProvider::start(|request| {
+            if request.model.ends_with("slow") {
+                StubReply::Hang
+            } else {
+                StubReply::text("Fast review")
+            }
+        })
+        .await;
+        let state = test_state(pool.clone(), &provider).await;
+        let owner = create_user(&pool).await;
+        let job_id: i32 = sqlx::query_scalar(
+            "INSERT INTO reviewer_jobs (user_id, filename, language, status)
+             VALUES ($1, 'paper.pdf', 'english', 'pending') RETURNING job_id",
+        )
+        .bind(owner)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        let dir = tempfile::tempdir().expect("temp dir");
+        let pdf = dir.path().join("paper.pdf");
+        fs::write(&pdf, blank_pdf(1)).unwrap();
+        let job_dir = PathBuf::from(STORAGE_ROOT).join(job_id.to_string());
+        fs::create_dir_all(&job_dir).unwrap();
+
+        let settings = crate::config::ReviewerSettings {
+            models: ReviewerModels {
+                round1_models: vec!["openrouter/test/fast".into(), "openrouter/test/slow".into()],
+                round1_success_ratio: 0.5,
+                ..ReviewerModels::default()
+            },
+            prompts: crate::config::ReviewerPrompts::default(),
+        };
+        let worker = tokio::spawn({
+            let pool = pool.clone();
+            let client = provider.client();
+            async move {
+                let manuscript = Manuscript::Document {
+                    path: pdf,
+                    ext: "pdf".into(),
+                };
+                process_reviewer_job(
+                    pool, client, job_id, owner, manuscript, None, "english", false, settings,
+                )
+                .await
+            }
+        });
+        // Wait for the first review to finish while the second is still in flight.
+        while sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM reviewer_documents WHERE job_id = $1 AND status = 'completed'",
+        )
+        .bind(job_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap()
+            == 0
+        {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+
+        let cancelled = cancel_job(
+            State(state.clone()),
+            session_jar(&pool, owner).await,
+            AxumPath(job_id),
+        )
+        .await;
+        assert!(cancelled.is_ok(), "owner can cancel a running job");
+        let err = tokio::time::timeout(Duration::from_secs(30), worker)
+            .await
+            .expect("worker stops after cancellation")
+            .unwrap()
+            .expect_err("cancelled jobs do not complete");
+        assert!(err.downcast_ref::<JobCancelled>().is_some());
+
+        let docs: Vec<(i32, String, Option<String>)> = sqlx::query_as(
+            "SELECT review_index, status, file_path FROM reviewer_documents
+             WHERE job_id = $1 ORDER BY review_index",
+        )
+        .bind(job_id)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(docs.len(), 2);
+        let finished_path = docs[0].2.clone().expect("finished review keeps its report");
+        assert_eq!(docs[0].1, STATUS_COMPLETED);
+        assert!(Path::new(&finished_path).exists());
+        assert_eq!(
+            (docs[1].1.as_str(), docs[1].2.as_deref()),
+            (STATUS_CANCELLED, None)
+        );
+        let status: String =
+            sqlx::query_scalar("SELECT status FROM reviewer_jobs WHERE job_id = $1")
+                .bind(job_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(status, STATUS_CANCELLED);
+        assert_eq!(recorded_usage(&pool, owner, MODULE_REVIEWER).await, (0, 1));
+
+        let again = cancel_job(
+            State(state.clone()),
+            session_jar(&pool, owner).await,
+            AxumPath(job_id),
+        )
+        .await
+        .expect_err("a settled job cannot be cancelled twice");
+        assert_eq!(again.status(), StatusCode::CONFLICT);
+        let _ = fs::remove_dir_all(job_dir);
+    }
+
+    #[sqlx::test]
+    #[ignore = "needs DATABASE_URL (disposable Postgres)"]
+    async fn combined_report_orders_rounds_and_checks_access(pool: PgPool) {
+        let provider = StubProvider::start(|_| StubReply::text("Review")).await;
+        let state = test_state(pool.clone(), &provider).await;
+        let owner = create_user(&pool).await;
+        let job_id: i32 = sqlx::query_scalar(
+            "INSERT INTO reviewer_jobs (user_id, filename, language, status)
+             VALUES ($1, 'paper.pdf', 'english', 'completed') RETURNING job_id",
+        )
+        .bind(owner)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        // Inserted out of order; the failed review and the one without a file are left out.
+        let docs = [
+            (
+                3,
+                None,
+                "Fact check text",
+                STATUS_COMPLETED,
+                Some("r3.docx"),
+            ),
+            (
+                1,
+                Some(1),
+                "Second review text",
+                STATUS_COMPLETED,
+                Some("r1b.docx"),
+            ),
+            (
+                2,
+                None,
+                "Meta review text",
+                STATUS_COMPLETED,
+                Some("r2.docx"),
+            ),
+            (
+                1,
+                Some(0),
+                "First review text",
+                STATUS_COMPLETED,
+                Some("r1a.docx"),
+            ),
+            (1, Some(2), "Failed review text", STATUS_FAILED, None),
+        ];
+        for (round, index, text, status, path) in docs {
+            sqlx::query(
+                "INSERT INTO reviewer_documents
+                 (job_id, round, review_index, model_name, review_text, file_path, status)
+                 VALUES ($1, $2, $3, 'vendor/model', $4, $5, $6)",
+            )
+            .bind(job_id)
+            .bind(round)
+            .bind(index)
+            .bind(text)
+            .bind(path)
+            .bind(status)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let response = download_combined_report(
+            State(state.clone()),
+            session_jar(&pool, owner).await,
+            AxumPath(job_id),
+        )
+        .await
+        .expect("owner can download the combined report");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(body.to_vec())).unwrap();
+        let mut xml = String::new();
+        std::io::Read::read_to_string(&mut archive.by_name("word/document.xml").unwrap(), &mut xml)
+            .unwrap();
+        let positions: Vec<usize> = [
+            "First review text",
+            "Second review text",
+            "Meta review text",
+            "Fact check text",
+        ]
+        .iter()
+        .map(|text| xml.find(text).expect("review text present"))
+        .collect();
+        assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(xml.contains("第一轮评审 2（vendor/model）"));
+        assert!(!xml.contains("Failed review text"));
+
+        let stranger = create_user(&pool).await;
+        let forbidden = download_combined_report(
+            State(state.clone()),
+            session_jar(&pool, stranger).await,
+            AxumPath(job_id),
+        )
+        .await
+        .expect_err("other users are refused");
+        assert_eq!(forbidden.status(), StatusCode::FORBIDDEN);
+
+        sqlx::query("UPDATE reviewer_jobs SET files_purged_at = NOW() WHERE job_id = $1")
+            .bind(job_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let gone = download_combined_report(
+            State(state),
+            session_jar(&pool, owner).await,
+            AxumPath(job_id),
+        )
+        .await
+        .expect_err("purged jobs have no report");
+        assert_eq!(gone.status(), StatusCode::GONE);
+    }
+
+    #[sqlx::test]
+    #[ignore = "needs DATABASE_URL (disposable Postgres)"]
+    async fn later_rounds_retry_and_record_final_failure(pool: PgPool) {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let pdf = dir.path().join("manuscript.pdf");
+        fs::write(&pdf, blank_pdf(1)).unwrap();
+        let pdf = ReviewAttachment::pdf(pdf, "manuscript.pdf");
+        let user_id = create_user(&pool).await;
+        let job_id: i32 = sqlx::query_scalar(
+            "INSERT INTO reviewer_jobs (user_id, filename, language, status)
+             VALUES ($1, 'paper.pdf', 'english', 'processing') RETURNING job_id",
+        )
+        .bind(user_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        let bad_gateway = || {
+            StubReply::Error(
+                StatusCode::BAD_GATEWAY,
+                json!({ "error": { "message": "upstream unavailable" } }),
+            )
+        };
+
+        // One transient failure is retried and the meta-review still completes.
+        let attempts = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = attempts.clone();
+        let flaky = StubProvider::start(move |_| {
+            if counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst) == 0 {
+                bad_gateway()
+            } else {
+                StubReply::text("Meta review")
+            }
+        })
+        .await;
+        let text = run_round2_review(
+            &pool,
+            &flaky.client(),
+            job_id,
+            std::slice::from_ref(&pdf),
+            "Summarise.",
+            "Review 1",
+            "openrouter/test/meta",
+        )
+        .await
+        .expect("round 2 recovers from one transient error");
+        assert_eq!(text, "Meta review");
+        assert_eq!(flaky.calls(), 2);
+
+        // A persistent failure marks the round 3 document failed instead of leaving it processing.
+        let down = StubProvider::start(move |_| bad_gateway()).await;
+        let err = run_round3_review(
+            &pool,
+            &down.client(),
+            job_id,
+            std::slice::from_ref(&pdf),
+            "Check.",
+            &text,
+            "openrouter/test/check",
+        )
+        .await
+        .expect_err("round 3 gives up after its retries");
+        assert!(err.to_string().starts_with("Round 3 fact-check failed"));
+        assert_eq!(down.calls(), LLM_RETRY_POLICY.max_attempts as usize);
+
+        let rows: Vec<(i32, String, Option<String>)> = sqlx::query_as(
+            "SELECT round, status, error FROM reviewer_documents WHERE job_id = $1 ORDER BY round",
+        )
+        .bind(job_id)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(rows[0].0, 2);
+        assert_eq!(rows[0].1, STATUS_COMPLETED);
+        assert_eq!(rows[1].0, 3);
+        assert_eq!(rows[1].1, STATUS_FAILED);
+        assert!(rows[1].2.as_deref().is_some_and(|error| !error.is_empty()));
+    }
+
+    #[tokio::test]
+    async fn failed_job_leaves_no_conversion_artifacts() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let manuscript = dir.path().join("manuscript.docx");
+        let pdf = manuscript.with_extension("pdf");
+        let report = dir.path().join("round1_review_1.docx");
+        for path in [&manuscript, &pdf, &report] {
+            fs::write(path, b"x").unwrap();
+        }
+
+        let mut artifacts = ReviewArtifacts::default();
+        artifacts.track_intermediate(pdf.clone());
+        artifacts.track_report(report.clone());
+        // Never created; cleanup must tolerate it.
+        artifacts.track_report(dir.path().join("round2_meta_review.docx"));
+        artifacts.cleanup(false).await;
+
+        assert!(manuscript.exists());
+        assert!(!pdf.exists());
+        assert!(!report.exists());
+    }
+
+    #[tokio::test]
+    async fn successful_job_keeps_only_reports() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let pdf = dir.path().join("manuscript.pdf");
+        let report = dir.path().join("round3_final_report.docx");
+        fs::write(&pdf, b"x").unwrap();
+        fs::write(&report, b"x").unwrap();
+
+        let mut artifacts = ReviewArtifacts::default();
+        artifacts.track_intermediate(pdf.clone());
+        artifacts.track_report(report.clone());
+        artifacts.cleanup(true).await;
+
+        assert!(!pdf.exists());
+        assert!(report.exists());
+    }
 }
@@ -9,22 +9,32 @@ use tracing::error;
 use crate::{
     AppState,
     config::{
-        SummarizerModels, SummarizerPrompts, update_summarizer_models, update_summarizer_prompts,
+        SummarizerModels, SummarizerPrompts, parse_concurrency_limit, parse_model_routes,
+        update_summarizer_models, update_summarizer_prompts,
     },
     escape_html, fetch_glossary_terms, render_footer,
     web::{
         admin::DashboardQuery,
-        admin_utils::{compose_flash_message, sanitize_module_redirect},
+        admin_utils::{compose_query_flash, sanitize_module_redirect},
     },
 };
 
-use super::super::admin_shared::{MODULE_ADMIN_SHARED_STYLES, render_glossary_section};
+use super::super::admin_shared::{
+    MODULE_ADMIN_SHARED_STYLES, render_concurrency_field, render_glossary_section,
+    render_model_routes_field,
+};
 
 #[derive(Deserialize)]
 pub struct SummarizerModelForm {
     pub summary_model: String,
     pub translation_model: String,
     #[serde(default)]
+    pub summary_routes: String,
+    #[serde(default)]
+    pub max_concurrent_documents: String,
+    #[serde(default)]
+    pub store_raw_responses: Option<String>,
+    #[serde(default)]
     pub redirect: Option<String>,
 }
 
@@ -60,7 +70,7 @@ pub async fn settings_page(
             Vec::new()
         });
 
-    let message_block = compose_flash_message(params.status.as_deref(), params.error.as_deref());
+    let message_block = compose_query_flash(&params);
     let redirect_base = "/dashboard/modules/summarizer";
     let glossary_html = render_glossary_section(&glossary_terms, redirect_base);
     let footer = render_footer();
@@ -84,9 +94,9 @@ pub async fn settings_page(
         main {{ padding: 2rem 1.5rem; max-width: 960px; margin: 0 auto; box-sizing: border-box; }}
         .panel {{ background: #ffffff; border-radius: 12px; border: 1px solid #e2e8f0; padding: 1.5rem; box-shadow: 0 18px 40px rgba(15, 23, 42, 0.08); margin-bottom: 2rem; }}
         label {{ display: block; margin-bottom: 0.5rem; font-weight: 600; color: #0f172a; }}
-        input[type="text"], textarea {{ width: 100%; padding: 0.75rem; border-radius: 8px; border: 1px solid #cbd5f5; background: #f8fafc; color: #0f172a; box-sizing: border-box; font-family: inherit; }}
+        input[type="text"], input[type="number"], textarea {{ width: 100%; padding: 0.75rem; border-radius: 8px; border: 1px solid #cbd5f5; background: #f8fafc; color: #0f172a; box-sizing: border-box; font-family: inherit; }}
         textarea {{ min-height: 140px; }}
-        input[type="text"]:focus, textarea:focus {{ outline: none; border-color: #2563eb; box-shadow: 0 0 0 3px rgba(37, 99, 235, 0.12); }}
+        input[type="text"]:focus, input[type="number"]:focus, textarea:focus {{ outline: none; border-color: #2563eb; box-shadow: 0 0 0 3px rgba(37, 99, 235, 0.12); }}
         button {{ padding: 0.85rem 1.2rem; border: none; border-radius: 8px; background: #2563eb; color: #ffffff; font-weight: 600; cursor: pointer; transition: background 0.15s ease; }}
         button:hover {{ background: #1d4ed8; }}
         .flash {{ padding: 1rem; border-radius: 8px; margin-bottom: 1.5rem; border: 1px solid transparent; }}
@@ -114,8 +124,11 @@ pub async fn settings_page(
                 <input type="hidden" name="redirect" value="{redirect_base}">
                 <label for="summary-model">摘要模型</label>
                 <input id="summary-model" name="summary_model" type="text" value="{summary_model}" required>
+                {summary_routes_field}
                 <label for="translation-model">翻译模型</label>
                 <input id="translation-model" name="translation_model" type="text" value="{translation_model}" required>
+                {concurrency_field}
+                <label><input type="checkbox" name="store_raw_responses" value="on"{store_raw_checked}> 保存模型原始返回（仅管理员可在任务状态中下载，用于核对清理后的摘要）</label>
                 <button type="submit">保存模型</button>
             </form>
         </section>
@@ -141,6 +154,13 @@ pub async fn settings_page(
         message_block = message_block,
         redirect_base = redirect_base,
         summary_model = escape_html(&models.summary_model),
+        concurrency_field = render_concurrency_field(models.max_concurrent_documents),
+        store_raw_checked = if models.store_raw_responses {
+            " checked"
+        } else {
+            ""
+        },
+        summary_routes_field = render_model_routes_field("summary_routes", &models.summary_routes),
         translation_model = escape_html(&models.translation_model),
         research_prompt = escape_html(&prompts.research_summary),
         general_prompt = escape_html(&prompts.general_summary),
@@ -169,9 +189,25 @@ pub async fn save_models(
         )));
     }
 
+    let Some(max_concurrent_documents) = parse_concurrency_limit(&form.max_concurrent_documents)
+    else {
+        return Ok(Redirect::to(&format!(
+            "{redirect_base}?error=concurrency_invalid"
+        )));
+    };
+
+    let Ok(summary_routes) = parse_model_routes(&form.summary_routes) else {
+        return Ok(Redirect::to(&format!(
+            "{redirect_base}?error=model_routes_invalid"
+        )));
+    };
+
     let payload = SummarizerModels {
         summary_model: summary.to_string(),
         translation_model: translation.to_string(),
+        summary_routes,
+        max_concurrent_documents,
+        store_raw_responses: form.store_raw_responses.is_some(),
     };
 
     if let Err(err) = update_summarizer_models(state.pool_ref(), &payload).await {
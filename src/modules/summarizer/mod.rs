@@ -1,7 +1,8 @@
 use std::{
     borrow::Cow,
     fs,
-    io::{Read, Write},
+    future::Future,
+    io::Write,
     path::{Path, PathBuf},
     sync::Arc,
     time::Duration,
@@ -10,42 +11,52 @@ use std::{
 use anyhow::{Context, Result, anyhow};
 use axum::{
     Json, Router,
-    extract::{Multipart, Path as AxumPath, State},
+    extract::{Multipart, Path as AxumPath, Query, State},
     http::{StatusCode, header},
     response::{Html, IntoResponse, Redirect, Response},
     routing::{get, post},
 };
 use axum_extra::extract::cookie::CookieJar;
 use chrono::{DateTime, Utc};
-use pdf_extract::extract_text as extract_pdf_text;
-use quick_xml::{Reader as XmlReader, events::Event};
 use sanitize_filename::sanitize;
-use serde::Serialize;
-use tokio::{fs as tokio_fs, sync::Semaphore, time::sleep};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    fs as tokio_fs,
+    sync::{AcquireError, Semaphore},
+    time::sleep,
+};
 use tracing::{error, warn};
 use uuid::Uuid;
-use zip::ZipArchive;
 
 mod admin;
 
+use crate::web::download_limit::hold_permit;
 use crate::web::history_ui;
 use crate::web::storage::JobAccess;
 use crate::web::{
-    FileFieldConfig, FileNaming, ToolAdminLink, ToolPageLayout, UPLOAD_WIDGET_SCRIPT,
-    UPLOAD_WIDGET_STYLES, UploadWidgetConfig, process_upload_form, render_tool_page,
-    render_upload_widget,
+    FileFieldConfig, FileNaming, ToolAdminLink, ToolPageLayout, ToolPageUser, UPLOAD_WIDGET_SCRIPT,
+    UPLOAD_WIDGET_STYLES, UploadWidgetConfig, process_upload_form, render_upload_widget,
 };
 use crate::{
     AppState, GlossaryTermRow,
-    config::SummarizerPrompts,
+    config::{SummarizerPrompts, effective_concurrency, route_model},
     escape_html, fetch_glossary_terms, history,
-    llm::{ChatMessage, LlmRequest, MessageRole},
-    render_footer,
+    llm::{ChatMessage, LlmRequest, MessageRole, RetriedResponse, RetryPolicy, estimate_tokens},
+    notifications, render_footer,
     usage::{self, MODULE_SUMMARIZER},
+    utils::{
+        document_preview::{DocumentPreview, load_preview, supports_preview},
+        document_text::read_document_text,
+        docx_text::{DocxSection, text_docx_bytes},
+        duplicates::{DuplicateMatch, DuplicateRegistry, DuplicateSlot},
+        prompt_template::{fill_placeholders, single_line},
+    },
     web::{
         AccessMessages, ApiMessage, JobStatus, JobSubmission, STATUS_CLIENT_SCRIPT,
+        attachment_response,
         auth::{self, JsonAuthError},
-        ensure_storage_root, json_error, require_path, verify_job_access,
+        ensure_job_owner, ensure_storage_root, glossary_for_scope, json_error, quota_error,
+        read_stored_file, require_path, verify_job_access,
     },
 };
 
@@ -54,11 +65,10 @@ const STATUS_PENDING: &str = "pending";
 const STATUS_PROCESSING: &str = "processing";
 const STATUS_COMPLETED: &str = "completed";
 const STATUS_FAILED: &str = "failed";
+const STATUS_SKIPPED: &str = "skipped";
 
 const GLOSSARY_PLACEHOLDER: &str = "{{GLOSSARY}}";
-const MAX_RETRIES: u32 = 3;
-const INITIAL_RETRY_DELAY_MS: u64 = 1000;
-const MAX_CONCURRENT_DOCUMENTS: usize = 5;
+const LLM_RETRY_POLICY: RetryPolicy = RetryPolicy::new(3, Duration::from_millis(1000));
 
 pub fn router() -> Router<AppState> {
     Router::new()
@@ -69,6 +79,14 @@ pub fn router() -> Router<AppState> {
             "/api/summarizer/jobs/:id/combined/:variant",
             get(download_combined_output),
         )
+        .route(
+            "/api/summarizer/jobs/:id/documents/:doc_id/download/:variant",
+            get(download_document_output),
+        )
+        .route(
+            "/api/summarizer/jobs/:id/documents/:doc_id/preview",
+            get(document_preview),
+        )
         .route("/dashboard/modules/summarizer", get(admin::settings_page))
         .route(
             "/dashboard/modules/summarizer/models",
@@ -99,6 +117,20 @@ async fn summarizer_page(
     } else {
         None
     };
+    let page_user = ToolPageUser {
+        note_html: Cow::Owned(note_html),
+        admin_link,
+    };
+    let html =
+        state
+            .tool_page_cache()
+            .render(MODULE_SUMMARIZER, &page_user, summarizer_page_layout);
+
+    Ok(Html(html))
+}
+
+/// Everything on the summarizer page except the per-user header; cached by `ToolPageCache`.
+fn summarizer_page_layout() -> ToolPageLayout<'static> {
     let upload_widget = render_upload_widget(
         &UploadWidgetConfig::new("summarizer-upload", "files", "files", "上传文件")
             .with_description("支持上传 PDF、DOCX 或 TXT 文档。")
@@ -117,7 +149,21 @@ async fn summarizer_page(
                             <option value="research">科研论文</option>
                             <option value="other">其他文档</option>
                         </select>
+                        <label for="summary-length">摘要篇幅</label>
+                        <select id="summary-length" name="summary_length">
+                            <option value="brief">简要（约 150–250 词）</option>
+                            <option value="standard" selected>标准（约 400–600 词）</option>
+                            <option value="detailed">详细（约 800–1200 词）</option>
+                            <option value="custom">自定义字数</option>
+                        </select>
+                        <div id="summary-words-field" hidden>
+                            <label for="summary-words">目标字数（50–3000 词）</label>
+                            <input id="summary-words" name="summary_words" type="number" min="50" max="3000" step="10" value="500">
+                        </div>
                         <label><input type="checkbox" name="translate" id="translate" checked> 生成中文译文</label>
+                        <label><input type="checkbox" name="skip_duplicates" id="skip-duplicates"> 跳过内容重复的文件</label>
+                        <label for="callback-url">完成回调地址（可选，https）</label>
+                        <input id="callback-url" name="callback_url" type="url" placeholder="https://example.com/hooks/toolkit">
                         <button type="submit">开始处理</button>
                     </form>
                     <div id="submission-status" class="status"></div>
@@ -134,9 +180,15 @@ async fn summarizer_page(
 const statusBox = document.getElementById('submission-status');
 const jobStatus = document.getElementById('job-status');
 const fileInput = document.getElementById('files');
+const summaryLengthSelect = document.getElementById('summary-length');
+const summaryWordsField = document.getElementById('summary-words-field');
 let activeJobId = null;
 let statusTimer = null;
 
+summaryLengthSelect.addEventListener('change', () => {
+    summaryWordsField.hidden = summaryLengthSelect.value !== 'custom';
+});
+
 form.addEventListener('submit', async (event) => {
     event.preventDefault();
 
@@ -193,12 +245,13 @@ function pollStatus() {
         const payload = await response.json();
         renderStatus(payload);
 
-        if (payload.status === 'completed' || payload.status === 'failed') {
+        const delay = window.nextPollDelay(payload, 4000);
+        if (payload.status === 'completed' || payload.status === 'failed' || delay === 0) {
             activeJobId = null;
             return;
         }
 
-        statusTimer = setTimeout(pollStatus, 4000);
+        statusTimer = setTimeout(pollStatus, delay);
     }).catch((err) => {
         console.error(err);
         jobStatus.innerHTML = '<p class="note">无法加载任务状态，请刷新页面。</p>';
@@ -220,14 +273,20 @@ function renderStatus(payload) {
         const detail = doc.status_detail ? `<div class="note">${doc.status_detail}</div>` : '';
         const error = doc.error_message ? `<div class="note">${doc.error_message}</div>` : '';
         const statusLabel = getStatusLabel(doc.status, doc.status_label);
-        return `<tr><td>${doc.original_filename}</td><td>${statusLabel}</td></tr>${detail ? `<tr><td colspan=2>${detail}</td></tr>` : ''}${error ? `<tr><td colspan=2>${error}</td></tr>` : ''}`;
+        const links = [
+            doc.summary_url ? `<a href="${doc.summary_url}">摘要</a>` : '',
+            doc.translation_url ? `<a href="${doc.translation_url}">译文</a>` : '',
+            doc.raw_summary_url ? `<a href="${doc.raw_summary_url}">原始返回</a>` : '',
+            doc.preview_url ? `<a href="${doc.preview_url}" target="_blank" rel="noopener">预览首页</a>` : '',
+        ].filter(Boolean).join(' ');
+        return `<tr><td>${doc.original_filename}</td><td>${statusLabel}</td><td>${doc.attempt_count ?? 0}</td><td>${links || '—'}</td></tr>${detail ? `<tr><td colspan=4>${detail}</td></tr>` : ''}${error ? `<tr><td colspan=4>${error}</td></tr>` : ''}`;
     }).join('');
     if (!docRows) {
-        docRows = '<tr><td colspan="2">暂无文件记录。</td></tr>';
+        docRows = '<tr><td colspan="4">暂无文件记录。</td></tr>';
     }
 
-    const combinedSummary = payload.combined_summary_url ? `<a href="${payload.combined_summary_url}">下载汇总摘要</a>` : '';
-    const combinedTranslation = payload.combined_translation_url ? `<a href="${payload.combined_translation_url}">下载汇总译文</a>` : '';
+    const combinedSummary = payload.combined_summary_url ? `<a href="${payload.combined_summary_url}">下载汇总摘要</a> <a href="${payload.combined_summary_url}?format=docx">(DOCX)</a>` : '';
+    const combinedTranslation = payload.combined_translation_url ? `<a href="${payload.combined_translation_url}">下载汇总译文</a> <a href="${payload.combined_translation_url}?format=docx">(DOCX)</a>` : '';
     const combinedBlock = combinedSummary || combinedTranslation ? `<p class="downloads">${combinedSummary} ${combinedTranslation}</p>` : '';
     const errorBlock = payload.error_message ? `<p class="note">${payload.error_message}</p>` : '';
     const detailBlock = payload.status_detail ? `<p class="note">${payload.status_detail}</p>` : '';
@@ -239,7 +298,7 @@ function renderStatus(payload) {
             ${detailBlock}
             ${errorBlock}
             <table>
-                <thead><tr><th>文件名</th><th>状态</th></tr></thead>
+                <thead><tr><th>文件名</th><th>状态</th><th>尝试次数</th><th>下载</th></tr></thead>
                 <tbody>${docRows}</tbody>
             </table>
             ${combinedBlock}
@@ -248,17 +307,14 @@ function renderStatus(payload) {
 }
 "#;
 
-    let html = render_tool_page(ToolPageLayout {
+    ToolPageLayout {
         meta_title: "文档摘要与翻译 | 张圆教授课题组 AI 工具箱",
         page_heading: "文档摘要与翻译",
-        username: &username,
-        note_html: Cow::Owned(note_html),
         tab_group: "summarizer",
         new_tab_label: "新任务",
         new_tab_html: Cow::Owned(new_tab_html),
         history_tab_label: "历史记录",
         history_panel_html: Cow::Owned(history_panel),
-        admin_link,
         footer_html: Cow::Owned(render_footer()),
         extra_style_blocks: vec![
             Cow::Borrowed(history_ui::HISTORY_STYLES),
@@ -280,9 +336,7 @@ function renderStatus(payload) {
                 history_ui::HISTORY_SCRIPT
             )),
         ],
-    });
-
-    Ok(Html(html))
+    }
 }
 
 async fn create_job(
@@ -295,7 +349,9 @@ async fn create_job(
         .map_err(|JsonAuthError { status, message }| json_error(status, message))?;
 
     let mut document_type = DocumentKind::ResearchArticle;
+    let mut summary_length = SummaryLength::Standard;
     let mut translate = true;
+    let mut skip_duplicates = false;
 
     ensure_storage_root(STORAGE_ROOT)
         .await
@@ -329,10 +385,42 @@ async fn create_job(
         document_type = DocumentKind::from_str(value.trim());
     }
 
+    if let Some(value) = upload.first_text("summary_length") {
+        summary_length = match value.trim() {
+            "custom" => {
+                let words = upload
+                    .first_text("summary_words")
+                    .and_then(|words| words.trim().parse::<u32>().ok())
+                    .filter(|words| SUMMARY_WORD_RANGE.contains(words));
+                let Some(words) = words else {
+                    let _ = tokio_fs::remove_dir_all(&job_dir).await;
+                    return Err(json_error(
+                        StatusCode::BAD_REQUEST,
+                        "自定义摘要字数需为 50–3000 之间的整数。",
+                    ));
+                };
+                SummaryLength::Words(words)
+            }
+            other => SummaryLength::from_str(other),
+        };
+    }
+
     if let Some(value) = upload.first_text("translate") {
         translate = matches!(value.trim(), "on" | "true" | "1" | "yes");
     }
 
+    if let Some(value) = upload.first_text("skip_duplicates") {
+        skip_duplicates = matches!(value.trim(), "on" | "true" | "1" | "yes");
+    }
+
+    let callback_url = match notifications::parse_callback_url(upload.first_text("callback_url")) {
+        Ok(url) => url,
+        Err(message) => {
+            let _ = tokio_fs::remove_dir_all(&job_dir).await;
+            return Err(json_error(StatusCode::BAD_REQUEST, message));
+        }
+    };
+
     let files: Vec<_> = upload.files_for("files").cloned().collect();
 
     let pool = state.pool();
@@ -341,7 +429,7 @@ async fn create_job(
         usage::ensure_within_limits(&pool, user.id, MODULE_SUMMARIZER, files.len() as i64).await
     {
         let _ = tokio_fs::remove_dir_all(&job_dir).await;
-        return Err(json_error(StatusCode::FORBIDDEN, err.message()));
+        return Err(quota_error(StatusCode::FORBIDDEN, &err));
     }
 
     let mut transaction = pool
@@ -350,13 +438,16 @@ async fn create_job(
         .map_err(|err| internal_error(err.into()))?;
 
     sqlx::query(
-        "INSERT INTO summary_jobs (id, user_id, status, document_type, translate) VALUES ($1, $2, $3, $4, $5)",
+        "INSERT INTO summary_jobs (id, user_id, status, document_type, translate, summary_length, skip_duplicates, callback_url) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
     )
     .bind(job_id)
     .bind(user.id)
     .bind(STATUS_PENDING)
     .bind(document_type.as_str())
     .bind(translate)
+    .bind(summary_length.to_db_value())
+    .bind(skip_duplicates)
+    .bind(&callback_url)
     .execute(&mut *transaction)
     .await
     .map_err(|err| internal_error(err.into()))?;
@@ -418,15 +509,15 @@ async fn job_status(
         )
     })?;
 
-    if job.user_id != user.id && !user.is_admin {
-        return Err((
-            StatusCode::FORBIDDEN,
-            Json(ApiMessage::new("您无权访问该任务。")),
-        ));
-    }
+    ensure_job_owner(
+        job.user_id,
+        &user,
+        "未找到任务或任务已失效。",
+        "您无权访问该任务。",
+    )?;
 
     let documents = sqlx::query_as::<_, DocumentRecord>(
-        "SELECT id, original_filename, status, status_detail, error_message FROM summary_documents WHERE job_id = $1 ORDER BY ordinal",
+        "SELECT id, original_filename, status, status_detail, error_message, attempt_count, summary_text IS NOT NULL AS has_summary, translation_text IS NOT NULL AS has_translation, raw_summary_response IS NOT NULL AS has_raw_summary FROM summary_documents WHERE job_id = $1 ORDER BY ordinal",
     )
     .bind(job_id)
     .fetch_all(&pool)
@@ -437,6 +528,22 @@ async fn job_status(
         .into_iter()
         .map(|doc| {
             let status = JobStatus::from_str(&doc.status);
+            let download_url = |variant: &str| {
+                format!(
+                    "/api/summarizer/jobs/{}/documents/{}/download/{}",
+                    job_id, doc.id, variant
+                )
+            };
+            let summary_url = doc.has_summary.then(|| download_url("summary"));
+            let translation_url = doc.has_translation.then(|| download_url("translation"));
+            let raw_summary_url =
+                (doc.has_raw_summary && user.is_admin).then(|| download_url("raw-summary"));
+            let preview_url = supports_preview(&doc.original_filename).then(|| {
+                format!(
+                    "/api/summarizer/jobs/{}/documents/{}/preview",
+                    job_id, doc.id
+                )
+            });
             JobDocumentStatus {
                 id: doc.id,
                 original_filename: doc.original_filename,
@@ -444,6 +551,11 @@ async fn job_status(
                 status,
                 status_detail: doc.status_detail,
                 error_message: doc.error_message,
+                attempt_count: doc.attempt_count,
+                summary_url,
+                translation_url,
+                raw_summary_url,
+                preview_url,
             }
         })
         .collect();
@@ -453,6 +565,7 @@ async fn job_status(
     let response = JobStatusResponse {
         job_id: job.id,
         status_label: status.label_zh().to_string(),
+        poll_interval_ms: status.poll_interval_ms(),
         status,
         status_detail: job.status_detail,
         error_message: job.error_message,
@@ -470,10 +583,163 @@ async fn job_status(
     Ok(Json(response))
 }
 
+/// Serves one document's summary or translation straight from `summary_documents`.
+async fn download_document_output(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    AxumPath((job_id, document_id, variant)): AxumPath<(Uuid, Uuid, String)>,
+) -> Result<Response, (StatusCode, Json<ApiMessage>)> {
+    let user = auth::current_user_or_json_error(&state, &jar)
+        .await
+        .map_err(|JsonAuthError { status, message }| json_error(status, message))?;
+
+    let pool = state.pool();
+
+    verify_job_access(
+        || {
+            sqlx::query_as::<_, CombinedJobRecord>(
+                "SELECT user_id, combined_summary_path, combined_translation_path, files_purged_at FROM summary_jobs WHERE id = $1",
+            )
+            .bind(job_id)
+            .fetch_optional(&pool)
+        },
+        &user,
+        AccessMessages {
+            not_found: "未找到任务。",
+            forbidden: "您无权访问该任务。",
+            purged: "该任务的下载文件已过期并被清除。",
+        },
+    )
+    .await?;
+
+    let document = sqlx::query_as::<_, DocumentOutputRecord>(
+        "SELECT original_filename, summary_text, translation_text, raw_summary_response FROM summary_documents WHERE id = $1 AND job_id = $2",
+    )
+    .bind(document_id)
+    .bind(job_id)
+    .fetch_optional(&pool)
+    .await
+    .map_err(|err| internal_error(err.into()))?
+    .ok_or_else(|| json_error(StatusCode::NOT_FOUND, "未找到该文件。"))?;
+
+    let (text, suffix) = match variant.as_str() {
+        "summary" => (document.summary_text, "summary"),
+        "translation" => (document.translation_text, "translation"),
+        "raw-summary" if user.is_admin => (document.raw_summary_response, "raw-summary"),
+        "raw-summary" => {
+            return Err(json_error(
+                StatusCode::FORBIDDEN,
+                "仅管理员可查看模型原始返回。",
+            ));
+        }
+        _ => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiMessage::new("未知的下载类型。")),
+            ));
+        }
+    };
+    let text = text.ok_or_else(|| {
+        json_error(
+            StatusCode::NOT_FOUND,
+            match suffix {
+                "summary" => "该文件的摘要尚不可用。",
+                "raw-summary" => "该文件未保存模型原始返回。",
+                _ => "该文件的译文尚不可用。",
+            },
+        )
+    })?;
+
+    let permit = state.download_limiter().acquire(&user)?;
+
+    Ok(hold_permit(
+        attachment_response(
+            text.into_bytes(),
+            &sanitize_for_output(&document.original_filename, suffix, "txt"),
+            "text/plain; charset=utf-8",
+        ),
+        permit,
+    ))
+}
+
+async fn document_preview(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    AxumPath((job_id, document_id)): AxumPath<(Uuid, Uuid)>,
+) -> Result<Response, (StatusCode, Json<ApiMessage>)> {
+    let user = auth::current_user_or_json_error(&state, &jar)
+        .await
+        .map_err(|JsonAuthError { status, message }| json_error(status, message))?;
+
+    let pool = state.pool();
+
+    verify_job_access(
+        || {
+            sqlx::query_as::<_, CombinedJobRecord>(
+                "SELECT user_id, combined_summary_path, combined_translation_path, files_purged_at FROM summary_jobs WHERE id = $1",
+            )
+            .bind(job_id)
+            .fetch_optional(&pool)
+        },
+        &user,
+        AccessMessages {
+            not_found: "未找到任务。",
+            forbidden: "您无权访问该任务。",
+            purged: "该任务的上传文件已过期并被清除。",
+        },
+    )
+    .await?;
+
+    let source_path = sqlx::query_scalar::<_, String>(
+        "SELECT source_path FROM summary_documents WHERE id = $1 AND job_id = $2",
+    )
+    .bind(document_id)
+    .bind(job_id)
+    .fetch_optional(&pool)
+    .await
+    .map_err(|err| internal_error(err.into()))?
+    .ok_or_else(|| json_error(StatusCode::NOT_FOUND, "未找到该文件。"))?;
+
+    let cache_path = PathBuf::from(STORAGE_ROOT)
+        .join(job_id.to_string())
+        .join(format!("preview_{}.png", document_id.simple()));
+
+    match load_preview(Path::new(&source_path), &cache_path).await {
+        Ok(DocumentPreview::Png(bytes)) => Ok((
+            [
+                (header::CONTENT_TYPE, "image/png"),
+                (header::CACHE_CONTROL, "private, max-age=3600"),
+            ],
+            bytes,
+        )
+            .into_response()),
+        Ok(DocumentPreview::Missing) => {
+            Err(json_error(StatusCode::NOT_FOUND, "原始文件已不存在。"))
+        }
+        Ok(DocumentPreview::Unsupported) => Err(json_error(
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "仅支持预览 PDF 或 DOCX 文件。",
+        )),
+        Err(err) => {
+            error!(?err, %job_id, %document_id, "failed to render document preview");
+            Err(json_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "生成预览失败，请稍后再试。",
+            ))
+        }
+    }
+}
+
+#[derive(Deserialize, Default)]
+struct CombinedDownloadQuery {
+    format: Option<String>,
+}
+
 async fn download_combined_output(
     State(state): State<AppState>,
     jar: CookieJar,
     AxumPath((job_id, variant)): AxumPath<(Uuid, String)>,
+    Query(query): Query<CombinedDownloadQuery>,
 ) -> Result<Response, (StatusCode, Json<ApiMessage>)> {
     let user = auth::current_user_or_json_error(&state, &jar)
         .await
@@ -498,11 +764,11 @@ async fn download_combined_output(
     )
     .await?;
 
-    let (path, suffix) = match variant.as_str() {
+    let (path, suffix, text_column) = match variant.as_str() {
         "summary" => require_path(job.combined_summary_path.clone(), "汇总摘要尚不可用。")
-            .map(|path| (path, "combined-summary"))?,
+            .map(|path| (path, "combined-summary", "summary_text"))?,
         "translation" => require_path(job.combined_translation_path.clone(), "汇总译文尚不可用。")
-            .map(|path| (path, "combined-translation"))?,
+            .map(|path| (path, "combined-translation", "translation_text"))?,
         _ => {
             return Err((
                 StatusCode::BAD_REQUEST,
@@ -511,19 +777,67 @@ async fn download_combined_output(
         }
     };
 
-    serve_file(Path::new(&path), "combined.txt", suffix)
-        .await
-        .map_err(|err| internal_error(err.into()))
+    let permit = state.download_limiter().acquire(&user)?;
+
+    let response = match query.format.as_deref().map(str::trim) {
+        None | Some("") | Some("txt") => serve_file(Path::new(&path), "combined.txt", suffix)
+            .await
+            .map_err(internal_error),
+        Some("docx") => {
+            // Rendered from the per-document columns rather than by re-parsing the text file,
+            // so Markdown-style `#` lines inside summaries cannot be mistaken for headings.
+            let sections = sqlx::query_as::<_, CombinedSectionRow>(&format!(
+                "SELECT ordinal, original_filename, {text_column} AS body FROM summary_documents WHERE job_id = $1 AND status = $2 AND {text_column} IS NOT NULL ORDER BY ordinal"
+            ))
+            .bind(job_id)
+            .bind(STATUS_COMPLETED)
+            .fetch_all(&pool)
+            .await
+            .map_err(|err| internal_error(err.into()))?;
+
+            let bytes = combined_docx_bytes(&sections).map_err(internal_error)?;
+            Ok(attachment_response(
+                bytes,
+                &sanitize_for_output("combined.docx", suffix, "docx"),
+                "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+            ))
+        }
+        Some(_) => Err(json_error(
+            StatusCode::BAD_REQUEST,
+            "不支持的下载格式，仅支持 txt 或 docx。",
+        )),
+    }?;
+    Ok(hold_permit(response, permit))
 }
 
-fn build_translation_prompt(prompts: &SummarizerPrompts, glossary: &[GlossaryTermRow]) -> String {
-    let glossary_block = glossary
+fn combined_docx_bytes(rows: &[CombinedSectionRow]) -> Result<Vec<u8>> {
+    let headings: Vec<String> = rows
         .iter()
+        .map(|row| format_heading(row.ordinal.max(0) as usize, &row.original_filename))
+        .collect();
+    let sections: Vec<DocxSection<'_>> = rows
+        .iter()
+        .zip(&headings)
+        .map(|(row, heading)| DocxSection {
+            heading: Some(heading),
+            body: row.body.trim(),
+        })
+        .collect();
+    text_docx_bytes(&sections)
+}
+
+/// Fills `{{GLOSSARY}}` in the translation prompt with one `- EN: … -> CN: …` line per term,
+/// or appends the block when the template has no placeholder. Terms are flattened to a single
+/// line and substituted in one pass, so line breaks or placeholder text inside a term cannot
+/// reshape the prompt.
+fn build_translation_prompt(prompts: &SummarizerPrompts, glossary: &[GlossaryTermRow]) -> String {
+    let glossary_block = glossary_for_scope(glossary, MODULE_SUMMARIZER)
+        .into_iter()
         .map(|term| {
             format!(
                 "- EN: {} -> CN: {}",
-                term.source_term.trim(),
-                term.target_term.trim()
+                single_line(&term.source_term),
+                single_line(&term.target_term)
             )
         })
         .collect::<Vec<_>>()
@@ -536,9 +850,10 @@ fn build_translation_prompt(prompts: &SummarizerPrompts, glossary: &[GlossaryTer
     };
 
     if prompts.translation.contains(GLOSSARY_PLACEHOLDER) {
-        prompts
-            .translation
-            .replace(GLOSSARY_PLACEHOLDER, &substitution)
+        fill_placeholders(
+            &prompts.translation,
+            &[(GLOSSARY_PLACEHOLDER, substitution.as_str())],
+        )
     } else {
         format!("{}\n{}", prompts.translation.trim_end(), substitution)
     }
@@ -555,7 +870,7 @@ fn translation_enabled_text(enabled: bool) -> &'static str {
     if enabled { "enabled" } else { "disabled" }
 }
 
-fn sanitize_for_output(filename: &str, suffix: &str) -> String {
+fn sanitize_for_output(filename: &str, suffix: &str, extension: &str) -> String {
     let mut base = Path::new(filename)
         .file_stem()
         .and_then(|s| s.to_str())
@@ -565,38 +880,38 @@ fn sanitize_for_output(filename: &str, suffix: &str) -> String {
         base = "document".to_string();
     }
     let safe_base = sanitize(base);
-    format!("{}_{}.txt", safe_base, suffix)
+    format!("{}_{}.{}", safe_base, suffix, extension)
 }
 
 async fn serve_file(path: &Path, original_name: &str, suffix: &str) -> Result<Response> {
-    let bytes = tokio_fs::read(path)
+    let bytes = read_stored_file(path)
         .await
         .with_context(|| format!("failed to read file at {}", path.display()))?;
 
-    let filename = sanitize_for_output(original_name, suffix);
-
-    let mut headers = axum::http::HeaderMap::new();
-    headers.insert(
-        header::CONTENT_TYPE,
-        header::HeaderValue::from_static("text/plain; charset=utf-8"),
-    );
-    headers.insert(
-        header::CONTENT_DISPOSITION,
-        header::HeaderValue::from_str(&format!(r#"attachment; filename="{}""#, filename))
-            .unwrap_or_else(|_| header::HeaderValue::from_static("attachment")),
-    );
+    let filename = sanitize_for_output(original_name, suffix, "txt");
 
-    Ok((headers, bytes).into_response())
+    Ok(attachment_response(
+        bytes,
+        &filename,
+        "text/plain; charset=utf-8",
+    ))
 }
 
-fn build_summary_request(model: &str, prompt: &str, text: &str) -> LlmRequest {
+fn build_summary_request(
+    model: &str,
+    prompt: &str,
+    length: SummaryLength,
+    text: &str,
+) -> LlmRequest {
+    let system_prompt = format!("{}\n\n{}", prompt.trim_end(), length.instruction());
     LlmRequest::new(
         model.to_string(),
         vec![
-            ChatMessage::new(MessageRole::System, prompt),
+            ChatMessage::new(MessageRole::System, system_prompt),
             ChatMessage::new(MessageRole::User, text.to_string()),
         ],
     )
+    .with_module(MODULE_SUMMARIZER)
 }
 
 fn build_translation_request(model: &str, prompt: String, summary: &str) -> LlmRequest {
@@ -613,88 +928,7 @@ fn build_translation_request(model: &str, prompt: String, summary: &str) -> LlmR
             ),
         ],
     )
-}
-
-fn extract_docx_text(path: &Path) -> Result<String> {
-    let file = fs::File::open(path)
-        .with_context(|| format!("failed to open DOCX file {}", path.display()))?;
-    let mut archive = ZipArchive::new(file)
-        .with_context(|| format!("failed to open DOCX archive {}", path.display()))?;
-
-    let mut document = archive
-        .by_name("word/document.xml")
-        .with_context(|| format!("missing word/document.xml in {}", path.display()))?;
-
-    let mut xml = String::new();
-    document
-        .read_to_string(&mut xml)
-        .with_context(|| format!("failed to read DOCX XML for {}", path.display()))?;
-
-    let mut reader = XmlReader::from_str(&xml);
-    let mut buf = Vec::new();
-    let mut output = String::new();
-    let mut in_text_node = false;
-
-    loop {
-        match reader.read_event_into(&mut buf) {
-            Ok(Event::Start(ref e)) => match e.name().as_ref() {
-                b"w:p" => {
-                    if !output.is_empty() {
-                        output.push_str("\n\n");
-                    }
-                }
-                b"w:tab" => output.push('\t'),
-                b"w:br" => output.push('\n'),
-                b"w:t" => in_text_node = true,
-                _ => {}
-            },
-            Ok(Event::Empty(ref e)) => match e.name().as_ref() {
-                b"w:p" => {
-                    if !output.is_empty() {
-                        output.push_str("\n\n");
-                    }
-                }
-                b"w:tab" => output.push('\t'),
-                b"w:br" => output.push('\n'),
-                _ => {}
-            },
-            Ok(Event::Text(e)) => {
-                if in_text_node {
-                    let value = e.unescape().map_err(|err| anyhow!(err))?.into_owned();
-                    output.push_str(&value);
-                }
-            }
-            Ok(Event::End(ref e)) => {
-                if e.name().as_ref() == b"w:t" {
-                    in_text_node = false;
-                }
-            }
-            Ok(Event::Eof) => break,
-            Err(err) => return Err(anyhow!("failed to parse DOCX XML: {}", err)),
-            _ => {}
-        }
-        buf.clear();
-    }
-
-    Ok(output.trim().to_string())
-}
-
-fn read_document_text(path: &Path) -> Result<String> {
-    let extension = path
-        .extension()
-        .and_then(|ext| ext.to_str())
-        .unwrap_or("")
-        .to_lowercase();
-
-    match extension.as_str() {
-        "pdf" => extract_pdf_text(path)
-            .with_context(|| format!("failed to extract PDF text from {}", path.display())),
-        "docx" => extract_docx_text(path),
-        "txt" => fs::read_to_string(path)
-            .with_context(|| format!("failed to read text file {}", path.display())),
-        other => Err(anyhow!("Unsupported file type: {}", other)),
-    }
-    .map(|content| content.trim().to_string())
+    .with_module(MODULE_SUMMARIZER)
 }
 
 fn combined_output_path(job_dir: &Path, variant: &str) -> PathBuf {
@@ -716,38 +950,69 @@ fn format_heading(idx: usize, filename: &str) -> String {
     format!("Document {} — {}", idx + 1, filename)
 }
 
-async fn execute_llm_with_retry(
-    client: &crate::llm::LlmClient,
-    request: crate::llm::LlmRequest,
-    operation: &str,
-) -> Result<crate::llm::LlmResponse> {
-    let mut attempt = 0;
-    let mut last_error = None;
+struct TranslationOutcome {
+    result: std::result::Result<String, String>,
+    tokens: i64,
+    /// LLM requests sent, including transient retries made inside `call`.
+    attempts: u32,
+}
 
-    while attempt < MAX_RETRIES {
-        attempt += 1;
+/// Runs the translation call, retrying when the model answers with empty text.
+///
+/// Request errors are returned as-is since `call` already retries transient failures.
+async fn translate_summary<F, Fut>(policy: RetryPolicy, mut call: F) -> TranslationOutcome
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = RetriedResponse>,
+{
+    let max_attempts = policy.max_attempts.max(1);
+    let mut tokens = 0_i64;
+    let mut attempts = 0_u32;
+
+    for attempt in 1..=max_attempts {
+        let retried = call().await;
+        attempts += retried.attempts;
+
+        match retried.result {
+            Ok(response) => {
+                tokens += response.token_usage.total_tokens as i64;
+                let text = response.text.trim().to_string();
+                if !text.is_empty() {
+                    return TranslationOutcome {
+                        result: Ok(text),
+                        tokens,
+                        attempts,
+                    };
+                }
 
-        match client.execute(request.clone()).await {
-            Ok(response) => return Ok(response),
-            Err(err) => {
                 warn!(
-                    ?err,
                     attempt,
-                    max_retries = MAX_RETRIES,
-                    operation,
-                    "LLM request failed, will retry"
+                    max_attempts,
+                    provider = %response.provider,
+                    model = %response.model,
+                    "translation response was empty"
                 );
-                last_error = Some(err);
-
-                if attempt < MAX_RETRIES {
-                    let delay = INITIAL_RETRY_DELAY_MS * (2_u64.pow(attempt - 1));
-                    sleep(Duration::from_millis(delay)).await;
+                if attempt < max_attempts {
+                    sleep(policy.delay_after(attempt)).await;
                 }
             }
+            Err(err) => {
+                return TranslationOutcome {
+                    result: Err(err.to_string()),
+                    tokens,
+                    attempts,
+                };
+            }
         }
     }
 
-    Err(last_error.unwrap_or_else(|| anyhow!("LLM request failed after {} retries", MAX_RETRIES)))
+    TranslationOutcome {
+        result: Err(format!(
+            "Translation response was empty after {max_attempts} attempts."
+        )),
+        tokens,
+        attempts,
+    }
 }
 
 struct DocumentProcessingResult {
@@ -756,26 +1021,67 @@ struct DocumentProcessingResult {
     original_filename: String,
     success: bool,
     summary_text: Option<String>,
+    /// The summary response as returned, when `store_raw_responses` is on.
+    raw_summary: Option<String>,
     translation_text: Option<String>,
     summary_tokens: i64,
     translation_tokens: i64,
+    attempt_count: i32,
     error_message: Option<String>,
     status_detail: Option<String>,
+    /// Earlier document in the batch with the same (or normalised-equal) text.
+    duplicate_of: Option<DuplicateMatch>,
+    /// Set when `duplicate_of` is present and the job skips duplicates.
+    skipped: bool,
+}
+
+/// Failed result for a document that could not get a concurrency permit (the job's semaphore
+/// was closed); the result loop records the failure on the document.
+fn permit_unavailable(
+    job_id: Uuid,
+    document: ProcessingDocumentRecord,
+    idx: usize,
+    err: AcquireError,
+) -> DocumentProcessingResult {
+    error!(?err, %job_id, document_id = %document.id, "failed to acquire summarizer permit");
+    DocumentProcessingResult {
+        document_id: document.id,
+        idx,
+        original_filename: document.original_filename,
+        success: false,
+        summary_text: None,
+        raw_summary: None,
+        translation_text: None,
+        summary_tokens: 0,
+        translation_tokens: 0,
+        attempt_count: 0,
+        error_message: Some(err.to_string()),
+        status_detail: Some("Unable to start processing the document.".to_string()),
+        duplicate_of: None,
+        skipped: false,
+    }
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn process_single_document(
     state: AppState,
     job_id: Uuid,
     document: ProcessingDocumentRecord,
     idx: usize,
     document_kind: DocumentKind,
+    summary_length: SummaryLength,
     models: crate::config::SummarizerModels,
     prompts: crate::config::SummarizerPrompts,
     translation_prompt: String,
     should_translate: bool,
+    skip_duplicates: bool,
+    duplicate_slot: DuplicateSlot,
     semaphore: Arc<Semaphore>,
 ) -> DocumentProcessingResult {
-    let _permit = semaphore.acquire().await.expect("semaphore closed");
+    let permit = match semaphore.acquire().await {
+        Ok(permit) => permit,
+        Err(err) => return permit_unavailable(job_id, document, idx, err),
+    };
 
     let pool = state.pool();
     let status_detail = format!("Reading {}", document.original_filename);
@@ -823,28 +1129,64 @@ async fn process_single_document(
                 original_filename: document.original_filename,
                 success: false,
                 summary_text: None,
+                raw_summary: None,
                 translation_text: None,
                 summary_tokens: 0,
                 translation_tokens: 0,
+                attempt_count: 0,
                 error_message: Some(err.to_string()),
                 status_detail: Some("Unable to extract text from the document.".to_string()),
+                duplicate_of: None,
+                skipped: false,
             };
         }
     };
 
+    // Registration waits for earlier uploads, so it must not hold a concurrency permit.
+    drop(permit);
+    let duplicate_of = duplicate_slot
+        .register(&document.original_filename, &text)
+        .await;
+    let _permit = match semaphore.acquire().await {
+        Ok(permit) => permit,
+        Err(err) => return permit_unavailable(job_id, document, idx, err),
+    };
+    if let Some(duplicate) = duplicate_of.as_ref().filter(|_| skip_duplicates) {
+        return DocumentProcessingResult {
+            document_id: document.id,
+            idx,
+            original_filename: document.original_filename,
+            success: false,
+            summary_text: None,
+            raw_summary: None,
+            translation_text: None,
+            summary_tokens: 0,
+            translation_tokens: 0,
+            attempt_count: 0,
+            error_message: None,
+            status_detail: Some(format!("{}，已跳过。", duplicate.describe_zh())),
+            duplicate_of: duplicate_of.clone(),
+            skipped: true,
+        };
+    }
+
     // Generate summary with retry
     let summary_prompt = document_prompt(&prompts, document_kind);
+    let summary_model = route_model(
+        &models.summary_model,
+        &models.summary_routes,
+        estimate_tokens(&text),
+    );
     let summary_request =
-        build_summary_request(models.summary_model.as_str(), summary_prompt, &text);
+        build_summary_request(summary_model, summary_prompt, summary_length, &text);
     let llm_client = state.llm_client();
 
-    let summary_response = match execute_llm_with_retry(
-        &llm_client,
-        summary_request,
-        &format!("summarization for {}", document.original_filename),
-    )
-    .await
-    {
+    let summary_attempt = llm_client
+        .execute_with_retry_counted(summary_request, LLM_RETRY_POLICY)
+        .await;
+    let mut attempt_count = summary_attempt.attempts as i32;
+
+    let summary_response = match summary_attempt.result {
         Ok(resp) => resp,
         Err(err) => {
             error!(?err, document_id = %document.id, "summarization request failed after retries");
@@ -863,16 +1205,23 @@ async fn process_single_document(
                 original_filename: document.original_filename,
                 success: false,
                 summary_text: None,
+                raw_summary: None,
                 translation_text: None,
                 summary_tokens: 0,
                 translation_tokens: 0,
+                attempt_count,
                 error_message: Some(err.to_string()),
                 status_detail: Some("Summarization failed.".to_string()),
+                duplicate_of,
+                skipped: false,
             };
         }
     };
 
     let summary_text = summary_response.text.trim().to_string();
+    let raw_summary = models
+        .store_raw_responses
+        .then(|| summary_response.text.clone());
     let summary_tokens = summary_response.token_usage.total_tokens as i64;
 
     // Handle translation if needed
@@ -899,23 +1248,20 @@ async fn process_single_document(
             &summary_text,
         );
 
-        match execute_llm_with_retry(
-            &llm_client,
-            translation_request,
-            &format!("translation for {}", document.original_filename),
-        )
-        .await
-        {
-            Ok(response) => {
-                let text = response.text.trim().to_string();
-                translation_tokens = response.token_usage.total_tokens as i64;
-                translation_text = Some(text);
-            }
+        let outcome = translate_summary(LLM_RETRY_POLICY, || {
+            llm_client.execute_with_retry_counted(translation_request.clone(), LLM_RETRY_POLICY)
+        })
+        .await;
+
+        translation_tokens = outcome.tokens;
+        attempt_count += outcome.attempts as i32;
+        match outcome.result {
+            Ok(text) => translation_text = Some(text),
             Err(err) => {
-                error!(?err, document_id = %document.id, "translation request failed after retries");
+                error!(%err, document_id = %document.id, "translation failed after retries");
                 translation_status_detail =
                     Some("Translation failed; summary available.".to_string());
-                translation_error = Some(err.to_string());
+                translation_error = Some(err);
             }
         }
     }
@@ -926,18 +1272,22 @@ async fn process_single_document(
         original_filename: document.original_filename,
         success: true,
         summary_text: Some(summary_text),
+        raw_summary,
         translation_text,
         summary_tokens,
         translation_tokens,
+        attempt_count,
         error_message: translation_error,
         status_detail: translation_status_detail,
+        duplicate_of,
+        skipped: false,
     }
 }
 
 async fn process_job(state: AppState, job_id: Uuid) -> Result<()> {
     let pool = state.pool();
     let job = sqlx::query_as::<_, ProcessingJobRecord>(
-        "SELECT user_id, status, document_type, translate FROM summary_jobs WHERE id = $1",
+        "SELECT user_id, status, document_type, translate, summary_length, skip_duplicates FROM summary_jobs WHERE id = $1",
     )
     .bind(job_id)
     .fetch_one(&pool)
@@ -949,6 +1299,7 @@ async fn process_job(state: AppState, job_id: Uuid) -> Result<()> {
     }
 
     let document_kind = DocumentKind::from_str(&job.document_type);
+    let summary_length = SummaryLength::from_str(&job.summary_length);
 
     sqlx::query(
         "UPDATE summary_jobs SET status = $2, status_detail = $3, updated_at = NOW() WHERE id = $1",
@@ -983,7 +1334,10 @@ async fn process_job(state: AppState, job_id: Uuid) -> Result<()> {
     let translation_prompt = build_translation_prompt(&prompts, &glossary_terms);
 
     // Create semaphore for concurrency control
-    let semaphore = Arc::new(Semaphore::new(MAX_CONCURRENT_DOCUMENTS));
+    let semaphore = Arc::new(Semaphore::new(effective_concurrency(
+        models.max_concurrent_documents,
+    )));
+    let duplicates = Arc::new(DuplicateRegistry::new(0..documents.len()));
 
     // Spawn concurrent document processing tasks
     let mut tasks = Vec::new();
@@ -1001,10 +1355,13 @@ async fn process_job(state: AppState, job_id: Uuid) -> Result<()> {
             document,
             idx,
             document_kind,
+            summary_length,
             models_clone,
             prompts_clone,
             translation_prompt_clone,
             job.translate,
+            job.skip_duplicates,
+            duplicates.slot(idx),
             semaphore_clone,
         ));
 
@@ -1020,22 +1377,42 @@ async fn process_job(state: AppState, job_id: Uuid) -> Result<()> {
     let mut success_count = 0_i64;
     let mut summary_tokens_total = 0_i64;
     let mut translation_tokens_total = 0_i64;
+    let mut duplicate_count = 0_usize;
 
     // Sort results by index to maintain order
     let mut processed_results: Vec<DocumentProcessingResult> =
         results.into_iter().filter_map(|r| r.ok()).collect();
     processed_results.sort_by_key(|r| r.idx);
 
-    for result in processed_results {
+    for mut result in processed_results {
         let heading = format_heading(result.idx, &result.original_filename);
 
+        if let Some(duplicate) = result.duplicate_of.as_ref() {
+            duplicate_count += 1;
+            if result.skipped {
+                let _ = sqlx::query("UPDATE summary_documents SET status = $2, status_detail = $3, updated_at = NOW() WHERE id = $1")
+                    .bind(result.document_id)
+                    .bind(STATUS_SKIPPED)
+                    .bind(result.status_detail.as_deref())
+                    .execute(&pool)
+                    .await;
+                continue;
+            }
+            let note = format!("{}。", duplicate.describe_zh());
+            result.status_detail = Some(match result.status_detail.take() {
+                Some(detail) => format!("{} {}", note, detail),
+                None => note,
+            });
+        }
+
         // Handle failed documents - persist failure information
         if !result.success {
-            let _ = sqlx::query("UPDATE summary_documents SET status = $2, status_detail = $3, error_message = $4, updated_at = NOW() WHERE id = $1")
+            let _ = sqlx::query("UPDATE summary_documents SET status = $2, status_detail = $3, error_message = $4, attempt_count = $5, updated_at = NOW() WHERE id = $1")
                 .bind(result.document_id)
                 .bind(STATUS_FAILED)
                 .bind(result.status_detail.as_deref())
                 .bind(result.error_message.as_deref())
+                .bind(result.attempt_count)
                 .execute(&pool)
                 .await;
             continue;
@@ -1118,7 +1495,7 @@ async fn process_job(state: AppState, job_id: Uuid) -> Result<()> {
         }
 
         // Update database with results - propagate error on failure
-        if let Err(err) = sqlx::query("UPDATE summary_documents SET status = $2, status_detail = $3, summary_text = $4, translation_text = $5, summary_path = NULL, translation_path = NULL, summary_tokens = $6, translation_tokens = $7, error_message = $8, updated_at = NOW() WHERE id = $1")
+        if let Err(err) = sqlx::query("UPDATE summary_documents SET status = $2, status_detail = $3, summary_text = $4, translation_text = $5, summary_path = NULL, translation_path = NULL, summary_tokens = $6, translation_tokens = $7, error_message = $8, attempt_count = $9, raw_summary_response = $10, updated_at = NOW() WHERE id = $1")
             .bind(result.document_id)
             .bind(STATUS_COMPLETED)
             .bind(result.status_detail.as_deref())
@@ -1127,6 +1504,8 @@ async fn process_job(state: AppState, job_id: Uuid) -> Result<()> {
             .bind(result.summary_tokens)
             .bind(result.translation_tokens)
             .bind(result.error_message.as_deref())
+            .bind(result.attempt_count)
+            .bind(result.raw_summary.as_ref())
             .execute(&pool)
             .await
         {
@@ -1148,14 +1527,15 @@ async fn process_job(state: AppState, job_id: Uuid) -> Result<()> {
         success_count += 1;
     }
 
-    let status_detail = if success_count > 0 {
-        Some(format!(
-            "Completed with {} successful documents",
-            success_count
-        ))
+    let mut status_detail = if success_count > 0 {
+        format!("Completed with {} successful documents", success_count)
     } else {
-        Some("Job finished but no documents were successfully processed".to_string())
+        "Job finished but no documents were successfully processed".to_string()
     };
+    if duplicate_count > 0 {
+        status_detail.push_str(&duplicate_warning(duplicate_count, job.skip_duplicates));
+    }
+    let status_detail = Some(status_detail);
 
     let job_status = if success_count > 0 {
         STATUS_COMPLETED
@@ -1194,6 +1574,14 @@ async fn process_job(state: AppState, job_id: Uuid) -> Result<()> {
     Ok(())
 }
 
+fn duplicate_warning(count: usize, skipped: bool) -> String {
+    if skipped {
+        format!("；检测到 {} 个重复文件，已跳过处理。", count)
+    } else {
+        format!("；检测到 {} 个重复文件，请核对上传列表。", count)
+    }
+}
+
 fn spawn_job_worker(state: AppState, job_id: Uuid) {
     tokio::spawn(async move {
         if let Err(err) = process_job(state.clone(), job_id).await {
@@ -1212,9 +1600,49 @@ fn spawn_job_worker(state: AppState, job_id: Uuid) {
                 error!(?update_err, %job_id, "failed to update job after error");
             }
         }
+
+        notifications::notify_job_owner(
+            state.pool_ref(),
+            state.mailer(),
+            "summary_jobs",
+            "id",
+            MODULE_SUMMARIZER,
+            job_id,
+        )
+        .await;
+
+        send_job_callback(&state, job_id).await;
     });
 }
 
+/// Sends the job callback with links to the combined outputs.
+async fn send_job_callback(state: &AppState, job_id: Uuid) {
+    notifications::send_job_callback(
+        state,
+        "summary_jobs",
+        MODULE_SUMMARIZER,
+        job_id,
+        async || {
+            let available = sqlx::query_as::<_, (bool, bool)>(
+                "SELECT combined_summary_path IS NOT NULL, combined_translation_path IS NOT NULL FROM summary_jobs WHERE id = $1",
+            )
+            .bind(job_id)
+            .fetch_one(state.pool_ref())
+            .await
+            .unwrap_or_else(|err| {
+                error!(?err, %job_id, "failed to load combined outputs for callback");
+                (false, false)
+            });
+            [("summary", available.0), ("translation", available.1)]
+                .into_iter()
+                .filter(|(_, available)| *available)
+                .map(|(variant, _)| format!("/api/summarizer/jobs/{job_id}/combined/{variant}"))
+                .collect()
+        },
+    )
+    .await;
+}
+
 async fn update_document_status(
     pool: &sqlx::PgPool,
     document_id: Uuid,
@@ -1265,6 +1693,67 @@ impl DocumentKind {
     }
 }
 
+/// Target summary length chosen per job; every document in the job gets the same guidance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SummaryLength {
+    Brief,
+    Standard,
+    Detailed,
+    /// Explicit word-count target, stored as `words:<n>`.
+    Words(u32),
+}
+
+/// Word-count targets accepted for `SummaryLength::Words`.
+const SUMMARY_WORD_RANGE: std::ops::RangeInclusive<u32> = 50..=3000;
+
+impl SummaryLength {
+    fn from_str(value: &str) -> Self {
+        let value = value.to_lowercase();
+        if let Some(words) = value
+            .strip_prefix("words:")
+            .and_then(|words| words.trim().parse::<u32>().ok())
+        {
+            return SummaryLength::Words(
+                words.clamp(*SUMMARY_WORD_RANGE.start(), *SUMMARY_WORD_RANGE.end()),
+            );
+        }
+        match value.as_str() {
+            "brief" | "short" => SummaryLength::Brief,
+            "detailed" | "long" => SummaryLength::Detailed,
+            _ => SummaryLength::Standard,
+        }
+    }
+
+    fn to_db_value(self) -> String {
+        match self {
+            SummaryLength::Brief => "brief".to_string(),
+            SummaryLength::Standard => "standard".to_string(),
+            SummaryLength::Detailed => "detailed".to_string(),
+            SummaryLength::Words(words) => format!("words:{words}"),
+        }
+    }
+
+    fn instruction(self) -> String {
+        match self {
+            SummaryLength::Brief => {
+                "Length requirement: keep the summary brief, about 150-250 words, covering only the main purpose, key findings, and conclusion.".to_string()
+            }
+            SummaryLength::Standard => {
+                "Length requirement: write a standard-length summary of about 400-600 words.".to_string()
+            }
+            SummaryLength::Detailed => {
+                "Length requirement: write a detailed summary of about 800-1200 words, covering methods, key results with figures where available, limitations, and implications.".to_string()
+            }
+            SummaryLength::Words(words) => format!(
+                "Length requirement: write about {} words (between {} and {} words).",
+                words,
+                words * 85 / 100,
+                words * 115 / 100
+            ),
+        }
+    }
+}
+
 #[derive(sqlx::FromRow)]
 struct JobRecord {
     id: Uuid,
@@ -1285,6 +1774,25 @@ struct DocumentRecord {
     status: String,
     status_detail: Option<String>,
     error_message: Option<String>,
+    attempt_count: i32,
+    has_summary: bool,
+    has_translation: bool,
+    has_raw_summary: bool,
+}
+
+#[derive(sqlx::FromRow)]
+struct DocumentOutputRecord {
+    original_filename: String,
+    summary_text: Option<String>,
+    translation_text: Option<String>,
+    raw_summary_response: Option<String>,
+}
+
+#[derive(sqlx::FromRow)]
+struct CombinedSectionRow {
+    ordinal: i32,
+    original_filename: String,
+    body: String,
 }
 
 #[derive(sqlx::FromRow)]
@@ -1310,6 +1818,7 @@ struct JobStatusResponse {
     job_id: Uuid,
     status: JobStatus,
     status_label: String,
+    poll_interval_ms: u64,
     status_detail: Option<String>,
     error_message: Option<String>,
     created_at: String,
@@ -1327,6 +1836,12 @@ struct JobDocumentStatus {
     status_label: String,
     status_detail: Option<String>,
     error_message: Option<String>,
+    attempt_count: i32,
+    summary_url: Option<String>,
+    translation_url: Option<String>,
+    /// Admin-only link to the unmodified summary response.
+    raw_summary_url: Option<String>,
+    preview_url: Option<String>,
 }
 
 #[derive(sqlx::FromRow, Clone)]
@@ -1335,6 +1850,8 @@ struct ProcessingJobRecord {
     status: String,
     document_type: String,
     translate: bool,
+    summary_length: String,
+    skip_duplicates: bool,
 }
 
 #[derive(sqlx::FromRow)]
@@ -1355,10 +1872,315 @@ fn internal_error(err: anyhow::Error) -> (StatusCode, Json<ApiMessage>) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::{
+        STUB_TOTAL_TOKENS, StubProvider, StubReply, blank_pdf, create_user, recorded_usage,
+        session_jar, test_state,
+    };
     use chrono::Utc;
-    use std::io::Write;
-    use tempfile::tempdir;
-    use zip::write::SimpleFileOptions;
+    use serde_json::json;
+    use sqlx::PgPool;
+
+    async fn seed_job(pool: &PgPool, user_id: Uuid, translate: bool, text: &str) -> Uuid {
+        let job_id = Uuid::new_v4();
+        let job_dir = PathBuf::from(STORAGE_ROOT).join(job_id.to_string());
+        fs::create_dir_all(&job_dir).unwrap();
+        let source_path = job_dir.join("source_000_paper.txt");
+        fs::write(&source_path, text).unwrap();
+
+        sqlx::query(
+            "INSERT INTO summary_jobs (id, user_id, status, document_type, translate, summary_length, skip_duplicates) VALUES ($1, $2, $3, $4, $5, $6, FALSE)",
+        )
+        .bind(job_id)
+        .bind(user_id)
+        .bind(STATUS_PENDING)
+        .bind(DocumentKind::ResearchArticle.as_str())
+        .bind(translate)
+        .bind(SummaryLength::Standard.to_db_value())
+        .execute(pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO summary_documents (id, job_id, ordinal, original_filename, source_path, status) VALUES ($1, $2, 0, $3, $4, $5)",
+        )
+        .bind(Uuid::new_v4())
+        .bind(job_id)
+        .bind("paper.txt")
+        .bind(source_path.to_string_lossy().to_string())
+        .bind(STATUS_PENDING)
+        .execute(pool)
+        .await
+        .unwrap();
+
+        job_id
+    }
+
+    async fn job_state(pool: &PgPool, job_id: Uuid) -> (String, Option<String>, Option<String>) {
+        sqlx::query_as(
+            "SELECT status, status_detail, combined_summary_path FROM summary_jobs WHERE id = $1",
+        )
+        .bind(job_id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    #[sqlx::test]
+    #[ignore = "needs DATABASE_URL (disposable Postgres)"]
+    async fn closed_semaphore_fails_the_document_instead_of_panicking(pool: PgPool) {
+        let provider = StubProvider::start(|_| StubReply::text("unused")).await;
+        let state = test_state(pool.clone(), &provider).await;
+        let user_id = create_user(&pool).await;
+        let job_id = seed_job(&pool, user_id, false, "Full text of a field study.").await;
+        let document = sqlx::query_as::<_, ProcessingDocumentRecord>(
+            "SELECT id, original_filename, source_path FROM summary_documents WHERE job_id = $1",
+        )
+        .bind(job_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        let settings = state.summarizer_settings().await.unwrap();
+        let semaphore = Arc::new(Semaphore::new(1));
+        semaphore.close();
+
+        let result = process_single_document(
+            state.clone(),
+            job_id,
+            document,
+            0,
+            DocumentKind::ResearchArticle,
+            SummaryLength::Standard,
+            settings.models.clone(),
+            settings.prompts.clone(),
+            String::new(),
+            false,
+            false,
+            Arc::new(DuplicateRegistry::new(0..1)).slot(0),
+            semaphore,
+        )
+        .await;
+
+        assert!(!result.success);
+        assert_eq!(
+            result.status_detail.as_deref(),
+            Some("Unable to start processing the document.")
+        );
+        assert_eq!(provider.calls(), 0);
+
+        let _ = fs::remove_dir_all(PathBuf::from(STORAGE_ROOT).join(job_id.to_string()));
+    }
+
+    #[sqlx::test]
+    #[ignore = "needs DATABASE_URL (disposable Postgres)"]
+    async fn process_job_summarizes_and_translates_documents(pool: PgPool) {
+        let provider = StubProvider::start(|request| {
+            if request
+                .user
+                .starts_with("Translate the following text to Chinese")
+            {
+                StubReply::text("土壤碳储量随耕作减少。")
+            } else {
+                StubReply::text("Soil carbon declines under tillage.")
+            }
+        })
+        .await;
+        let state = test_state(pool.clone(), &provider).await;
+        let user_id = create_user(&pool).await;
+        let job_id = seed_job(&pool, user_id, true, "Full text of a field study.").await;
+
+        process_job(state, job_id).await.unwrap();
+
+        let (status, detail, summary_path) = job_state(&pool, job_id).await;
+        assert_eq!(status, STATUS_COMPLETED);
+        assert_eq!(
+            detail.as_deref(),
+            Some("Completed with 1 successful documents")
+        );
+
+        let (doc_status, summary, translation): (String, Option<String>, Option<String>) =
+            sqlx::query_as(
+                "SELECT status, summary_text, translation_text FROM summary_documents WHERE job_id = $1",
+            )
+            .bind(job_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(doc_status, STATUS_COMPLETED);
+        assert_eq!(
+            summary.as_deref(),
+            Some("Soil carbon declines under tillage.")
+        );
+        assert_eq!(translation.as_deref(), Some("土壤碳储量随耕作减少。"));
+
+        let combined = fs::read_to_string(summary_path.expect("combined summary path")).unwrap();
+        assert!(combined.contains("paper.txt"));
+        assert!(combined.contains("Soil carbon declines under tillage."));
+
+        assert_eq!(
+            recorded_usage(&pool, user_id, MODULE_SUMMARIZER).await,
+            (2 * STUB_TOTAL_TOKENS, 1)
+        );
+
+        let _ = fs::remove_dir_all(PathBuf::from(STORAGE_ROOT).join(job_id.to_string()));
+    }
+
+    #[sqlx::test]
+    #[ignore = "needs DATABASE_URL (disposable Postgres)"]
+    async fn raw_summary_response_is_kept_for_admins_when_enabled(pool: PgPool) {
+        let raw = "Here is the summary:\n\nSoil carbon declines under tillage.\n\n";
+        let provider = StubProvider::start(move |_| StubReply::text(raw)).await;
+        let state = test_state(pool.clone(), &provider).await;
+        let models = crate::config::SummarizerModels {
+            store_raw_responses: true,
+            ..crate::config::SummarizerModels::default()
+        };
+        crate::config::update_summarizer_models(&pool, &models)
+            .await
+            .unwrap();
+        state.reload_settings().await.unwrap();
+        let user_id = create_user(&pool).await;
+        let job_id = seed_job(&pool, user_id, false, "Full text of a field study.").await;
+
+        process_job(state.clone(), job_id).await.unwrap();
+
+        let (document_id, summary, stored_raw): (Uuid, Option<String>, Option<String>) =
+            sqlx::query_as(
+                "SELECT id, summary_text, raw_summary_response FROM summary_documents WHERE job_id = $1",
+            )
+            .bind(job_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(
+            summary.as_deref(),
+            Some("Here is the summary:\n\nSoil carbon declines under tillage.")
+        );
+        assert_eq!(stored_raw.as_deref(), Some(raw));
+
+        // The owner is not an admin, so the raw response is neither linked nor downloadable.
+        let status = job_status(
+            State(state.clone()),
+            session_jar(&pool, user_id).await,
+            AxumPath(job_id),
+        )
+        .await
+        .unwrap();
+        assert_eq!(status.0.documents[0].raw_summary_url, None);
+        let denied = download_document_output(
+            State(state),
+            session_jar(&pool, user_id).await,
+            AxumPath((job_id, document_id, "raw-summary".to_string())),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(denied.0, StatusCode::FORBIDDEN);
+
+        let _ = fs::remove_dir_all(PathBuf::from(STORAGE_ROOT).join(job_id.to_string()));
+    }
+
+    #[sqlx::test]
+    #[ignore = "needs DATABASE_URL (disposable Postgres)"]
+    async fn document_preview_serves_owned_documents_only(pool: PgPool) {
+        let provider = StubProvider::start(|_| StubReply::text("unused")).await;
+        let state = test_state(pool.clone(), &provider).await;
+        let owner = create_user(&pool).await;
+        let stranger = create_user(&pool).await;
+        let job_id = seed_job(&pool, owner, false, "Full text of a field study.").await;
+        let job_dir = PathBuf::from(STORAGE_ROOT).join(job_id.to_string());
+
+        // A PDF upload whose thumbnail was already rendered.
+        let document_id = Uuid::new_v4();
+        let source_path = job_dir.join("source_001_scan.pdf");
+        fs::write(&source_path, blank_pdf(1)).unwrap();
+        sqlx::query(
+            "INSERT INTO summary_documents (id, job_id, ordinal, original_filename, source_path, status) VALUES ($1, $2, 1, 'scan.pdf', $3, $4)",
+        )
+        .bind(document_id)
+        .bind(job_id)
+        .bind(source_path.to_string_lossy().to_string())
+        .bind(STATUS_PENDING)
+        .execute(&pool)
+        .await
+        .unwrap();
+        fs::write(
+            job_dir.join(format!("preview_{}.png", document_id.simple())),
+            b"\x89PNG cached",
+        )
+        .unwrap();
+
+        let preview = |user_id: Uuid, document_id: Uuid| {
+            let state = state.clone();
+            let pool = pool.clone();
+            async move {
+                document_preview(
+                    State(state),
+                    session_jar(&pool, user_id).await,
+                    AxumPath((job_id, document_id)),
+                )
+                .await
+            }
+        };
+
+        let response = preview(owner, document_id)
+            .await
+            .unwrap_or_else(|_| panic!("owner can preview the document"));
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "image/png");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(body.as_ref(), b"\x89PNG cached");
+
+        assert!(matches!(
+            preview(owner, Uuid::new_v4()).await,
+            Err((StatusCode::NOT_FOUND, _))
+        ));
+        assert!(matches!(
+            preview(stranger, document_id).await,
+            Err((StatusCode::FORBIDDEN, _))
+        ));
+
+        let _ = fs::remove_dir_all(&job_dir);
+    }
+
+    #[sqlx::test]
+    #[ignore = "needs DATABASE_URL (disposable Postgres)"]
+    async fn process_job_fails_when_provider_rejects_summary(pool: PgPool) {
+        let provider = StubProvider::start(|_| {
+            StubReply::Error(
+                StatusCode::BAD_REQUEST,
+                json!({ "error": { "message": "invalid request" } }),
+            )
+        })
+        .await;
+        let state = test_state(pool.clone(), &provider).await;
+        let user_id = create_user(&pool).await;
+        let job_id = seed_job(&pool, user_id, false, "Full text of a field study.").await;
+
+        process_job(state, job_id).await.unwrap();
+
+        let (status, detail, summary_path) = job_state(&pool, job_id).await;
+        assert_eq!(status, STATUS_FAILED);
+        assert_eq!(
+            detail.as_deref(),
+            Some("Job finished but no documents were successfully processed")
+        );
+        assert_eq!(summary_path, None);
+
+        let doc_status: String =
+            sqlx::query_scalar("SELECT status FROM summary_documents WHERE job_id = $1")
+                .bind(job_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(doc_status, STATUS_FAILED);
+        // 4xx responses are not retried.
+        assert_eq!(provider.calls(), 1);
+        assert_eq!(
+            recorded_usage(&pool, user_id, MODULE_SUMMARIZER).await,
+            (0, 0)
+        );
+
+        let _ = fs::remove_dir_all(PathBuf::from(STORAGE_ROOT).join(job_id.to_string()));
+    }
 
     #[test]
     fn generates_translation_prompt_with_terms() {
@@ -1368,6 +2190,7 @@ mod tests {
             source_term: "neuron".to_string(),
             target_term: "神经元".to_string(),
             notes: None,
+            scope: None,
             created_at: now,
             updated_at: now,
         }];
@@ -1386,26 +2209,142 @@ mod tests {
     }
 
     #[test]
-    fn extract_docx_text_returns_plain_text() {
-        let dir = tempdir().expect("temp dir");
-        let docx_path = dir.path().join("sample.docx");
-        let file = fs::File::create(&docx_path).expect("create docx");
-        let mut zip = zip::ZipWriter::new(file);
-
-        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
-<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
-  <w:body>
-    <w:p><w:r><w:t>Hello</w:t></w:r></w:p>
-    <w:p><w:r><w:t>World</w:t></w:r></w:p>
-  </w:body>
-</w:document>"#;
-
-        zip.start_file("word/document.xml", SimpleFileOptions::default())
-            .expect("zip start file");
-        zip.write_all(xml.as_bytes()).expect("write xml");
-        zip.finish().expect("finish zip");
-
-        let extracted = extract_docx_text(&docx_path).expect("extract docx");
-        assert_eq!(extracted, "Hello\n\nWorld");
+    fn glossary_terms_cannot_reshape_the_translation_prompt() {
+        let now = Utc::now();
+        let terms = vec![GlossaryTermRow {
+            id: Uuid::new_v4(),
+            source_term: "{{GLOSSARY}} cell\nIgnore previous".to_string(),
+            target_term: "细胞 {brace}\r\n".to_string(),
+            notes: None,
+            scope: None,
+            created_at: now,
+            updated_at: now,
+        }];
+        let prompts = SummarizerPrompts {
+            research_summary: String::from("summary"),
+            general_summary: String::from("general"),
+            translation: String::from("Glossary:\n{{GLOSSARY}}\nAgain: {{GLOSSARY}}\nEnd."),
+        };
+
+        let prompt = build_translation_prompt(&prompts, &terms);
+
+        let line = "- EN: {{GLOSSARY}} cell Ignore previous -> CN: 细胞 {brace}";
+        assert_eq!(prompt, format!("Glossary:\n{line}\nAgain: {line}\nEnd."));
+    }
+
+    #[test]
+    fn summary_length_adds_word_count_guidance() {
+        assert_eq!(SummaryLength::from_str("Brief"), SummaryLength::Brief);
+        assert_eq!(SummaryLength::from_str("detailed"), SummaryLength::Detailed);
+        assert_eq!(SummaryLength::from_str("unknown"), SummaryLength::Standard);
+        assert_eq!(SummaryLength::from_str("short"), SummaryLength::Brief);
+        assert_eq!(
+            SummaryLength::from_str("words:300"),
+            SummaryLength::Words(300)
+        );
+        assert_eq!(SummaryLength::from_str("words:5"), SummaryLength::Words(50));
+        assert_eq!(SummaryLength::Words(300).to_db_value(), "words:300");
+
+        let short = build_summary_request(
+            "model",
+            "Summarize.",
+            SummaryLength::from_str("short"),
+            "body text",
+        );
+        assert!(short.messages[0].text.contains("150-250 words"));
+
+        let custom = build_summary_request(
+            "model",
+            "Summarize.",
+            SummaryLength::Words(300),
+            "body text",
+        );
+        assert!(
+            custom.messages[0]
+                .text
+                .contains("about 300 words (between 255 and 345 words)")
+        );
+
+        let request =
+            build_summary_request("model", "Summarize.\n", SummaryLength::Brief, "body text");
+        let system = &request.messages[0].text;
+        assert!(system.starts_with("Summarize.\n\nLength requirement"));
+        assert!(system.contains("150-250 words"));
+    }
+
+    fn mock_response(text: &str) -> crate::llm::LlmResponse {
+        crate::llm::LlmResponse {
+            text: text.to_string(),
+            token_usage: crate::llm::TokenUsage {
+                prompt_tokens: 5,
+                response_tokens: 5,
+                total_tokens: 10,
+            },
+            provider: crate::llm::LlmProvider::OpenRouter,
+            model: "mock".to_string(),
+            raw: serde_json::Value::Null,
+            pricing: None,
+        }
+    }
+
+    fn single_attempt(text: &str) -> RetriedResponse {
+        RetriedResponse {
+            result: Ok(mock_response(text)),
+            attempts: 1,
+        }
+    }
+
+    #[tokio::test]
+    async fn empty_translation_is_retried_then_reported() {
+        let policy = RetryPolicy::constant(3, Duration::ZERO);
+        let mut calls = 0;
+        let outcome = translate_summary(policy, || {
+            calls += 1;
+            async { single_attempt("  \n") }
+        })
+        .await;
+
+        assert_eq!(calls, 3);
+        assert_eq!(outcome.tokens, 30);
+        assert_eq!(outcome.attempts, 3);
+        assert!(outcome.result.unwrap_err().contains("empty"));
+    }
+
+    #[tokio::test]
+    async fn empty_translation_recovers_on_retry() {
+        let policy = RetryPolicy::constant(3, Duration::ZERO);
+        let mut calls = 0;
+        let outcome = translate_summary(policy, || {
+            calls += 1;
+            let text = if calls == 1 { "" } else { "译文" };
+            async move { single_attempt(text) }
+        })
+        .await;
+
+        assert_eq!(calls, 2);
+        assert_eq!(outcome.result.unwrap(), "译文");
+    }
+
+    #[tokio::test]
+    async fn attempt_count_includes_transient_retries() {
+        let policy = RetryPolicy::constant(3, Duration::ZERO);
+        let mut calls = 0;
+        let outcome = translate_summary(policy, || {
+            calls += 1;
+            // First call needed two requests (one transient failure) and came back empty.
+            let retried = if calls == 1 {
+                RetriedResponse {
+                    result: Ok(mock_response("")),
+                    attempts: 2,
+                }
+            } else {
+                single_attempt("译文")
+            };
+            async move { retried }
+        })
+        .await;
+
+        assert_eq!(outcome.result.unwrap(), "译文");
+        assert_eq!(outcome.attempts, 3);
     }
 }
@@ -0,0 +1,204 @@
+use axum::{
+    extract::{Form, Query, State},
+    response::{Html, Redirect},
+};
+use axum_extra::extract::cookie::CookieJar;
+use serde::Deserialize;
+use tracing::error;
+
+use crate::{
+    AppState,
+    config::{
+        TranscribeModels, TranscribePrompts, update_transcribe_models, update_transcribe_prompts,
+    },
+    escape_html, render_footer,
+    web::{
+        admin::DashboardQuery,
+        admin_utils::{compose_flash_message, sanitize_module_redirect},
+    },
+};
+
+use super::super::admin_shared::MODULE_ADMIN_SHARED_STYLES;
+
+#[derive(Deserialize)]
+pub struct TranscribeModelForm {
+    pub transcription_model: String,
+    #[serde(default)]
+    pub redirect: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct TranscribePromptForm {
+    pub transcription_instructions: String,
+    #[serde(default)]
+    pub redirect: Option<String>,
+}
+
+pub async fn settings_page(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Query(params): Query<DashboardQuery>,
+) -> Result<Html<String>, Redirect> {
+    let admin = crate::web::admin::require_admin_user(&state, &jar).await?;
+
+    let settings = state.transcribe_settings().await;
+    let models = settings
+        .as_ref()
+        .map(|settings| settings.models.clone())
+        .unwrap_or_default();
+    let prompts = settings
+        .map(|settings| settings.prompts)
+        .unwrap_or_default();
+
+    let redirect_base = "/dashboard/modules/transcribe";
+    let message_block = compose_flash_message(params.status.as_deref(), params.error.as_deref());
+    let footer = render_footer();
+    let shared_styles = MODULE_ADMIN_SHARED_STYLES;
+
+    let html = format!(
+        r#"<!DOCTYPE html>
+<html lang="zh-CN">
+<head>
+    <meta charset="UTF-8">
+    <title>音频转写模块设置</title>
+    <meta name="viewport" content="width=device-width, initial-scale=1">
+    <meta name="robots" content="noindex,nofollow">
+    <style>
+        :root {{ color-scheme: light; }}
+        body {{ font-family: "Helvetica Neue", Arial, sans-serif; margin: 0; background: #f8fafc; color: #0f172a; }}
+        header {{ background: #ffffff; padding: 2rem 1.5rem; border-bottom: 1px solid #e2e8f0; }}
+        .header-bar {{ display: flex; justify-content: space-between; align-items: center; flex-wrap: wrap; gap: 1rem; }}
+        .back-link {{ display: inline-flex; align-items: center; gap: 0.4rem; color: #1d4ed8; text-decoration: none; font-weight: 600; background: #e0f2fe; padding: 0.5rem 0.95rem; border-radius: 999px; border: 1px solid #bfdbfe; transition: background 0.15s ease, border 0.15s ease; }}
+        .back-link:hover {{ background: #bfdbfe; border-color: #93c5fd; }}
+        main {{ padding: 2rem 1.5rem; max-width: 960px; margin: 0 auto; box-sizing: border-box; }}
+        .panel {{ background: #ffffff; border-radius: 12px; border: 1px solid #e2e8f0; padding: 1.5rem; box-shadow: 0 18px 40px rgba(15, 23, 42, 0.08); margin-bottom: 2rem; }}
+        label {{ display: block; margin-bottom: 0.5rem; font-weight: 600; color: #0f172a; }}
+        input[type="text"], textarea {{ width: 100%; padding: 0.75rem; border-radius: 8px; border: 1px solid #cbd5f5; background: #f8fafc; color: #0f172a; box-sizing: border-box; font-family: inherit; }}
+        textarea {{ min-height: 160px; }}
+        input[type="text"]:focus, textarea:focus {{ outline: none; border-color: #2563eb; box-shadow: 0 0 0 3px rgba(37, 99, 235, 0.12); }}
+        button {{ padding: 0.85rem 1.2rem; border: none; border-radius: 8px; background: #2563eb; color: #ffffff; font-weight: 600; cursor: pointer; transition: background 0.15s ease; }}
+        button:hover {{ background: #1d4ed8; }}
+        .flash {{ padding: 1rem; border-radius: 8px; margin-bottom: 1.5rem; border: 1px solid transparent; }}
+        .flash.success {{ background: #ecfdf3; border-color: #bbf7d0; color: #166534; }}
+        .flash.error {{ background: #fef2f2; border-color: #fecaca; color: #b91c1c; }}
+        .app-footer {{ margin-top: 3rem; text-align: center; font-size: 0.85rem; color: #94a3b8; }}
+{shared_styles}
+    </style>
+</head>
+<body>
+    <header>
+        <div class="header-bar">
+            <h1>音频转写模块设置</h1>
+            <a class="back-link" href="/tools/transcribe">← 返回模块</a>
+        </div>
+        <p>配置用于录音转写的模型与提示词。</p>
+    </header>
+    <main>
+        <p>当前登录：<strong>{admin}</strong></p>
+        {message_block}
+        <section class="panel">
+            <h2>模型配置</h2>
+            <form method="post" action="/dashboard/modules/transcribe/models">
+                <input type="hidden" name="redirect" value="{redirect}">
+                <label for="model">转写模型</label>
+                <input id="model" name="transcription_model" type="text" value="{model}" required>
+                <p class="section-note">模型需支持音频输入，请使用 <code>openrouter/</code> 前缀的模型；Poe 模型不接受音频附件。</p>
+                <button type="submit">保存模型</button>
+            </form>
+        </section>
+        <section class="panel">
+            <h2>提示词配置</h2>
+            <form method="post" action="/dashboard/modules/transcribe/prompts">
+                <input type="hidden" name="redirect" value="{redirect}">
+                <label for="instructions">转写指令</label>
+                <textarea id="instructions" name="transcription_instructions" required>{instructions}</textarea>
+                <button type="submit">保存提示词</button>
+            </form>
+        </section>
+        {footer}
+    </main>
+</body>
+</html>"#,
+        admin = escape_html(&admin.username),
+        message_block = message_block,
+        redirect = redirect_base,
+        model = escape_html(&models.transcription_model),
+        instructions = escape_html(&prompts.transcription_instructions),
+        footer = footer,
+        shared_styles = shared_styles,
+    );
+
+    Ok(Html(html))
+}
+
+pub async fn save_models(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Form(form): Form<TranscribeModelForm>,
+) -> Result<Redirect, Redirect> {
+    let _admin = crate::web::admin::require_admin_user(&state, &jar).await?;
+    let redirect_base = sanitize_module_redirect(form.redirect.as_deref());
+
+    let model = form.transcription_model.trim();
+    if model.is_empty() {
+        return Ok(Redirect::to(&format!(
+            "{redirect_base}?error=transcribe_invalid_models"
+        )));
+    }
+
+    let payload = TranscribeModels {
+        transcription_model: model.to_string(),
+    };
+
+    if let Err(err) = update_transcribe_models(state.pool_ref(), &payload).await {
+        error!(?err, "failed to update transcribe models");
+        return Ok(Redirect::to(&format!("{redirect_base}?error=unknown")));
+    }
+
+    if let Err(err) = state.reload_settings().await {
+        error!(
+            ?err,
+            "failed to reload module settings after transcribe model update"
+        );
+    }
+
+    Ok(Redirect::to(&format!(
+        "{redirect_base}?status=transcribe_models_saved"
+    )))
+}
+
+pub async fn save_prompts(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Form(form): Form<TranscribePromptForm>,
+) -> Result<Redirect, Redirect> {
+    let _admin = crate::web::admin::require_admin_user(&state, &jar).await?;
+    let redirect_base = sanitize_module_redirect(form.redirect.as_deref());
+
+    let instructions = form.transcription_instructions.trim();
+    if instructions.is_empty() {
+        return Ok(Redirect::to(&format!(
+            "{redirect_base}?error=transcribe_invalid_prompts"
+        )));
+    }
+
+    let payload = TranscribePrompts {
+        transcription_instructions: instructions.to_string(),
+    };
+
+    if let Err(err) = update_transcribe_prompts(state.pool_ref(), &payload).await {
+        error!(?err, "failed to update transcribe prompts");
+        return Ok(Redirect::to(&format!("{redirect_base}?error=unknown")));
+    }
+
+    if let Err(err) = state.reload_settings().await {
+        error!(
+            ?err,
+            "failed to reload module settings after transcribe prompt update"
+        );
+    }
+
+    Ok(Redirect::to(&format!(
+        "{redirect_base}?status=transcribe_prompts_saved"
+    )))
+}
@@ -0,0 +1,843 @@
+use std::{
+    borrow::Cow,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use axum::{
+    Json, Router,
+    extract::{Multipart, Path as AxumPath, State},
+    http::StatusCode,
+    response::{Html, Redirect, Response},
+    routing::{get, post},
+};
+use axum_extra::extract::cookie::CookieJar;
+use chrono::{DateTime, Utc};
+use sanitize_filename::sanitize;
+use serde::Serialize;
+use sqlx::PgPool;
+use tokio::fs as tokio_fs;
+use tracing::error;
+use uuid::Uuid;
+
+mod admin;
+
+use crate::web::download_limit::hold_permit;
+use crate::web::history_ui;
+use crate::web::storage::JobAccess;
+use crate::web::{
+    FileFieldConfig, FileNaming, ToolAdminLink, ToolPageLayout, ToolPageUser, UPLOAD_WIDGET_SCRIPT,
+    UPLOAD_WIDGET_STYLES, UploadWidgetConfig, ensure_storage_root, process_upload_form,
+    render_upload_widget,
+};
+use crate::{
+    AppState, escape_html, history,
+    llm::{AttachmentKind, ChatMessage, FileAttachment, LlmRequest, MessageRole, RetryPolicy},
+    render_footer,
+    usage::{self, MODULE_TRANSCRIBE},
+    web::{
+        AccessMessages, ApiMessage, JobStatus, JobSubmission, STATUS_CLIENT_SCRIPT,
+        auth::{self, JsonAuthError},
+        ensure_job_owner, json_error, quota_error, require_path, stream_file, verify_job_access,
+    },
+};
+
+const STORAGE_ROOT: &str = "storage/transcribe";
+const STATUS_PENDING: &str = "pending";
+const STATUS_PROCESSING: &str = "processing";
+const STATUS_COMPLETED: &str = "completed";
+const STATUS_FAILED: &str = "failed";
+const TRANSCRIPT_FILENAME: &str = "transcript.txt";
+/// Extensions accepted for upload; each maps to a MIME type in `audio_content_type`.
+const AUDIO_EXTENSIONS: &[&str] = &["mp3", "wav", "m4a", "ogg", "flac", "webm", "aac"];
+/// Recordings are sent inline as base64, so they are capped well below provider body limits.
+const MAX_AUDIO_MB: u64 = 25;
+const LLM_RETRY_POLICY: RetryPolicy = RetryPolicy::new(3, Duration::from_secs(1));
+const TRANSCRIPTION_FAILED_DETAIL: &str = "模型转写失败，请确认所选模型支持音频输入后重试。";
+/// Status detail when the worker stops on an unexpected error (storage or database).
+const JOB_ABORTED_DETAIL: &str = "转写任务意外中断，请重新提交。";
+const EMPTY_TRANSCRIPT_DETAIL: &str = "模型未返回转写文本，请检查录音内容后重试。";
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/tools/transcribe", get(transcribe_page))
+        .route("/tools/transcribe/jobs", post(create_job))
+        .route("/api/transcribe/jobs/:id", get(job_status))
+        .route(
+            "/api/transcribe/jobs/:id/transcript.txt",
+            get(download_transcript),
+        )
+        .route("/dashboard/modules/transcribe", get(admin::settings_page))
+        .route(
+            "/dashboard/modules/transcribe/models",
+            post(admin::save_models),
+        )
+        .route(
+            "/dashboard/modules/transcribe/prompts",
+            post(admin::save_prompts),
+        )
+}
+
+#[derive(sqlx::FromRow)]
+struct JobProcessingRecord {
+    user_id: Uuid,
+    status: String,
+    original_filename: String,
+    source_path: String,
+    content_type: String,
+}
+
+#[derive(sqlx::FromRow)]
+struct JobStatusRow {
+    user_id: Uuid,
+    status: String,
+    status_detail: Option<String>,
+    error_message: Option<String>,
+    original_filename: String,
+    transcript_text: Option<String>,
+    transcript_path: Option<String>,
+    files_purged_at: Option<DateTime<Utc>>,
+}
+
+#[derive(sqlx::FromRow)]
+struct TranscriptDownloadRecord {
+    user_id: Uuid,
+    files_purged_at: Option<DateTime<Utc>>,
+    original_filename: String,
+    transcript_path: Option<String>,
+}
+
+impl JobAccess for TranscriptDownloadRecord {
+    fn user_id(&self) -> Uuid {
+        self.user_id
+    }
+
+    fn files_purged_at(&self) -> Option<DateTime<Utc>> {
+        self.files_purged_at
+    }
+}
+
+#[derive(Serialize)]
+struct JobStatusResponse {
+    job_id: Uuid,
+    status: String,
+    poll_interval_ms: u64,
+    status_detail: Option<String>,
+    error_message: Option<String>,
+    original_filename: String,
+    transcript: Option<String>,
+    /// Present once the transcript file exists and has not been purged.
+    transcript_download_url: Option<String>,
+}
+
+pub async fn transcribe_page(
+    State(state): State<AppState>,
+    jar: CookieJar,
+) -> Result<Html<String>, Redirect> {
+    let user = auth::require_user_redirect(&state, &jar).await?;
+    let username = escape_html(&user.username);
+    let note_html = format!(
+        "当前登录：<strong>{username}</strong>。上传一段录音，系统会调用支持音频输入的模型将其转写为文字。",
+        username = username,
+    );
+    let admin_link = if user.is_admin {
+        Some(ToolAdminLink {
+            href: "/dashboard/modules/transcribe",
+            label: "模块管理",
+        })
+    } else {
+        None
+    };
+    let page_user = ToolPageUser {
+        note_html: Cow::Owned(note_html),
+        admin_link,
+    };
+    let html =
+        state
+            .tool_page_cache()
+            .render(MODULE_TRANSCRIBE, &page_user, transcribe_page_layout);
+
+    Ok(Html(html))
+}
+
+/// Everything on the transcription page except the per-user header; cached by `ToolPageCache`.
+fn transcribe_page_layout() -> ToolPageLayout<'static> {
+    let upload_widget = render_upload_widget(
+        &UploadWidgetConfig::new("transcribe-upload", "transcribe-file", "file", "音频文件")
+            .with_description("支持 MP3、WAV、M4A、OGG、FLAC、WEBM 或 AAC 录音。")
+            .with_note("每个任务转写一段录音，文件不超过 25 MB。")
+            .with_accept(".mp3,.wav,.m4a,.ogg,.flac,.webm,.aac"),
+    );
+    let history_panel = history_ui::render_history_panel(MODULE_TRANSCRIBE);
+    let extra_styles = Cow::Borrowed(
+        r#"        .results { background: #ffffff; border-radius: 12px; border: 1px solid #e2e8f0; padding: 1.5rem; box-shadow: 0 10px 30px rgba(15, 23, 42, 0.06); }
+        .results h2 { margin-top: 0; }
+        .transcript { white-space: pre-wrap; line-height: 1.7; background: #f8fafc; border: 1px solid #e2e8f0; border-radius: 8px; padding: 1rem; max-height: 480px; overflow-y: auto; }
+"#,
+    );
+    let new_tab_html = format!(
+        r#"                <section class="panel">
+                    <h2>上传录音</h2>
+                    <form id="transcribe-form">
+                        {upload_widget}
+                        <button type="submit">开始转写</button>
+                    </form>
+                    <div id="status-box" class="status-box">等待上传。</div>
+                </section>
+                <section id="results-section" class="results" style="display:none;">
+                    <h2>转写结果</h2>
+                    <div id="transcript-download" class="downloads"></div>
+                    <div id="transcript-text" class="transcript"></div>
+                </section>
+"#,
+        upload_widget = upload_widget,
+    );
+
+    let transcribe_script = r#"const form = document.getElementById('transcribe-form');
+const fileInput = document.getElementById('transcribe-file');
+const statusBox = document.getElementById('status-box');
+const resultsSection = document.getElementById('results-section');
+const transcriptDownload = document.getElementById('transcript-download');
+const transcriptText = document.getElementById('transcript-text');
+
+let pollTimer = null;
+let activeStatusUrl = null;
+
+const stopPolling = () => {
+    clearTimeout(pollTimer);
+    pollTimer = null;
+    activeStatusUrl = null;
+};
+
+const updateStatus = (message) => {
+    statusBox.textContent = message;
+};
+
+const resetResults = () => {
+    resultsSection.style.display = 'none';
+    transcriptDownload.innerHTML = '';
+    transcriptText.textContent = '';
+};
+
+const handleStatusPayload = (payload) => {
+    updateStatus(payload.status_detail || `当前状态：${window.translateJobStatus(payload.status)}`);
+
+    if (payload.status === 'completed') {
+        transcriptText.textContent = payload.transcript || '';
+        transcriptDownload.innerHTML = '';
+        if (payload.transcript_download_url) {
+            const link = document.createElement('a');
+            link.href = payload.transcript_download_url;
+            link.textContent = '下载文字稿 (TXT)';
+            transcriptDownload.appendChild(link);
+        }
+        resultsSection.style.display = 'block';
+        stopPolling();
+    } else if (payload.status === 'failed') {
+        updateStatus(payload.error_message || '转写失败，请稍后重试。');
+        stopPolling();
+    }
+};
+
+const pollJob = (url) => {
+    stopPolling();
+    activeStatusUrl = url;
+    const poll = async () => {
+        try {
+            const res = await fetch(url, { headers: { 'Accept': 'application/json' } });
+            if (!res.ok) {
+                throw new Error('状态查询失败');
+            }
+            const data = await res.json();
+            handleStatusPayload(data);
+            const delay = window.nextPollDelay(data, 3000);
+            if (url === activeStatusUrl && delay) {
+                pollTimer = setTimeout(poll, delay);
+            }
+        } catch (err) {
+            stopPolling();
+            updateStatus('轮询失败：' + err.message);
+        }
+    };
+    pollTimer = setTimeout(poll, 3000);
+};
+
+if (fileInput) {
+    fileInput.addEventListener('change', () => {
+        if (fileInput.files.length === 0) {
+            updateStatus('等待上传。');
+        } else {
+            updateStatus(`已选择文件：${fileInput.files[0].name}`);
+        }
+    });
+}
+
+form.addEventListener('submit', async (event) => {
+    event.preventDefault();
+    if (!fileInput.files || fileInput.files.length === 0) {
+        updateStatus('请先选择文件。');
+        return;
+    }
+    resetResults();
+    updateStatus('正在上传录音...');
+    const formData = new FormData(form);
+
+    try {
+        const res = await fetch('/tools/transcribe/jobs', { method: 'POST', body: formData });
+        if (!res.ok) {
+            const errorBody = await res.json().catch(() => ({ message: '提交失败' }));
+            updateStatus(errorBody.message || '提交失败');
+            return;
+        }
+        const data = await res.json();
+        updateStatus('任务已创建，正在排队转写...');
+        fileInput.value = '';
+        if (data.status_url) {
+            pollJob(data.status_url);
+        }
+    } catch (err) {
+        updateStatus('提交失败：' + err.message);
+    }
+});
+"#;
+
+    ToolPageLayout {
+        meta_title: "音频转写 | 张圆教授课题组 AI 工具箱",
+        page_heading: "音频转写",
+        tab_group: "transcribe",
+        new_tab_label: "新任务",
+        new_tab_html: Cow::Owned(new_tab_html),
+        history_tab_label: "历史记录",
+        history_panel_html: Cow::Owned(history_panel),
+        footer_html: Cow::Owned(render_footer()),
+        extra_style_blocks: vec![
+            Cow::Borrowed(history_ui::HISTORY_STYLES),
+            Cow::Borrowed(UPLOAD_WIDGET_STYLES),
+            extra_styles,
+        ],
+        body_scripts: vec![
+            Cow::Borrowed(STATUS_CLIENT_SCRIPT),
+            Cow::Borrowed(UPLOAD_WIDGET_SCRIPT),
+            Cow::Owned(format!(
+                "<script>
+{}
+</script>",
+                transcribe_script
+            )),
+            Cow::Owned(format!(
+                "<script>
+{}
+</script>",
+                history_ui::HISTORY_SCRIPT
+            )),
+        ],
+    }
+}
+
+async fn create_job(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    multipart: Multipart,
+) -> Result<Json<JobSubmission>, (StatusCode, Json<ApiMessage>)> {
+    let user = auth::current_user_or_json_error(&state, &jar)
+        .await
+        .map_err(|JsonAuthError { status, message }| json_error(status, message))?;
+
+    let pool = state.pool();
+
+    ensure_storage_root(STORAGE_ROOT)
+        .await
+        .map_err(internal_error)?;
+
+    let job_id = Uuid::new_v4();
+    let job_dir = PathBuf::from(STORAGE_ROOT).join(job_id.to_string());
+
+    let file_config = FileFieldConfig::new(
+        "file",
+        AUDIO_EXTENSIONS,
+        1,
+        FileNaming::Indexed {
+            prefix: "source_",
+            pad_width: 2,
+        },
+    );
+
+    let upload = match process_upload_form(multipart, &job_dir, &[file_config]).await {
+        Ok(outcome) => outcome,
+        Err(err) => {
+            let _ = tokio_fs::remove_dir_all(&job_dir).await;
+            return Err(json_error(
+                StatusCode::BAD_REQUEST,
+                err.message().to_string(),
+            ));
+        }
+    };
+
+    let Some(file) = upload.first_file_for("file").cloned() else {
+        let _ = tokio_fs::remove_dir_all(&job_dir).await;
+        return Err(json_error(StatusCode::BAD_REQUEST, "请上传一个音频文件。"));
+    };
+
+    if file.file_size > MAX_AUDIO_MB * 1024 * 1024 {
+        let _ = tokio_fs::remove_dir_all(&job_dir).await;
+        return Err(json_error(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            format!(
+                "音频文件为 {:.1} MB，最大支持 {MAX_AUDIO_MB} MB。",
+                file.file_size as f64 / (1024.0 * 1024.0)
+            ),
+        ));
+    }
+
+    let Some(content_type) = audio_content_type(&file.original_name) else {
+        let _ = tokio_fs::remove_dir_all(&job_dir).await;
+        return Err(json_error(StatusCode::BAD_REQUEST, "不支持的音频格式。"));
+    };
+
+    if let Err(err) = usage::ensure_within_limits(&pool, user.id, MODULE_TRANSCRIBE, 1).await {
+        let _ = tokio_fs::remove_dir_all(&job_dir).await;
+        return Err(quota_error(StatusCode::FORBIDDEN, &err));
+    }
+
+    sqlx::query(
+        "INSERT INTO transcribe_jobs (id, user_id, status, original_filename, source_path, content_type) VALUES ($1, $2, $3, $4, $5, $6)",
+    )
+    .bind(job_id)
+    .bind(user.id)
+    .bind(STATUS_PENDING)
+    .bind(&file.original_name)
+    .bind(file.stored_path.to_string_lossy().to_string())
+    .bind(content_type)
+    .execute(&pool)
+    .await
+    .map_err(|err| internal_error(err.into()))?;
+
+    if let Err(err) =
+        history::record_job_start(&pool, MODULE_TRANSCRIBE, user.id, job_id.to_string()).await
+    {
+        error!(?err, %job_id, "failed to record transcribe job history");
+    }
+
+    spawn_job_worker(state.clone(), job_id);
+
+    Ok(Json(JobSubmission::new(
+        job_id,
+        format!("/api/transcribe/jobs/{}", job_id),
+    )))
+}
+
+async fn job_status(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    AxumPath(job_id): AxumPath<Uuid>,
+) -> Result<Json<JobStatusResponse>, (StatusCode, Json<ApiMessage>)> {
+    let user = auth::current_user_or_json_error(&state, &jar)
+        .await
+        .map_err(|JsonAuthError { status, message }| json_error(status, message))?;
+
+    let job = sqlx::query_as::<_, JobStatusRow>(
+        "SELECT user_id, status, status_detail, error_message, original_filename, transcript_text, transcript_path, files_purged_at FROM transcribe_jobs WHERE id = $1",
+    )
+    .bind(job_id)
+    .fetch_optional(state.pool_ref())
+    .await
+    .map_err(|err| internal_error(err.into()))?
+    .ok_or_else(|| json_error(StatusCode::NOT_FOUND, "未找到任务。"))?;
+
+    ensure_job_owner(job.user_id, &user, "未找到任务。", "无权查看该任务。")?;
+
+    let transcript_download_url = (job.transcript_path.is_some() && job.files_purged_at.is_none())
+        .then(|| format!("/api/transcribe/jobs/{job_id}/transcript.txt"));
+
+    Ok(Json(JobStatusResponse {
+        job_id,
+        poll_interval_ms: JobStatus::from_str(&job.status).poll_interval_ms(),
+        status: job.status,
+        status_detail: job.status_detail,
+        error_message: job.error_message,
+        original_filename: job.original_filename,
+        transcript: job.transcript_text,
+        transcript_download_url,
+    }))
+}
+
+async fn download_transcript(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    AxumPath(job_id): AxumPath<Uuid>,
+) -> Result<Response, (StatusCode, Json<ApiMessage>)> {
+    let user = auth::current_user_or_json_error(&state, &jar)
+        .await
+        .map_err(|JsonAuthError { status, message }| json_error(status, message))?;
+
+    let pool = state.pool();
+    let job = verify_job_access(
+        || {
+            sqlx::query_as::<_, TranscriptDownloadRecord>(
+                "SELECT user_id, files_purged_at, original_filename, transcript_path FROM transcribe_jobs WHERE id = $1",
+            )
+            .bind(job_id)
+            .fetch_optional(&pool)
+        },
+        &user,
+        AccessMessages {
+            not_found: "未找到任务。",
+            forbidden: "您无权访问该任务。",
+            purged: "该任务的下载文件已过期并被清除。",
+        },
+    )
+    .await?;
+
+    let path = require_path(job.transcript_path.clone(), "文字稿尚未生成。")?;
+    let permit = state.download_limiter().acquire(&user)?;
+
+    stream_file(
+        Path::new(&path),
+        &transcript_download_name(&job.original_filename),
+        "text/plain; charset=utf-8",
+    )
+    .await
+    .map(|response| hold_permit(response, permit))
+}
+
+fn spawn_job_worker(state: AppState, job_id: Uuid) {
+    tokio::spawn(async move {
+        if let Err(err) = process_job(state.clone(), job_id).await {
+            error!(?err, %job_id, "transcribe job failed");
+            if let Err(update_err) = sqlx::query(
+                "UPDATE transcribe_jobs SET status = $2, status_detail = $3, error_message = $4, updated_at = NOW() WHERE id = $1",
+            )
+            .bind(job_id)
+            .bind(STATUS_FAILED)
+            .bind(JOB_ABORTED_DETAIL)
+            .bind(err.to_string())
+            .execute(state.pool_ref())
+            .await
+            {
+                error!(?update_err, %job_id, "failed to update job after error");
+            }
+        }
+    });
+}
+
+async fn process_job(state: AppState, job_id: Uuid) -> Result<()> {
+    let pool = state.pool();
+
+    let job = sqlx::query_as::<_, JobProcessingRecord>(
+        "SELECT user_id, status, original_filename, source_path, content_type FROM transcribe_jobs WHERE id = $1",
+    )
+    .bind(job_id)
+    .fetch_one(&pool)
+    .await
+    .context("failed to load transcribe job")?;
+
+    if job.status != STATUS_PENDING {
+        return Ok(());
+    }
+
+    let Some(settings) = state.transcribe_settings().await else {
+        mark_job_failed(&pool, job_id, "未配置音频转写设置，请联系管理员。").await?;
+        return Ok(());
+    };
+
+    update_job_status(&pool, job_id, STATUS_PROCESSING, "正在转写录音...").await?;
+
+    let bytes = tokio_fs::read(&job.source_path)
+        .await
+        .with_context(|| format!("failed to read audio file {}", job.source_path))?;
+
+    let request = build_transcription_request(
+        &settings.models.transcription_model,
+        &settings.prompts.transcription_instructions,
+        FileAttachment::new(
+            job.original_filename.clone(),
+            job.content_type.clone(),
+            AttachmentKind::Audio,
+            bytes,
+        ),
+    );
+
+    let response = match state
+        .llm_client()
+        .execute_with_retry(request, LLM_RETRY_POLICY)
+        .await
+    {
+        Ok(response) => response,
+        Err(err) => {
+            error!(?err, %job_id, "transcription request failed");
+            mark_job_failed(&pool, job_id, TRANSCRIPTION_FAILED_DETAIL).await?;
+            return Ok(());
+        }
+    };
+
+    let transcript = response.text.trim();
+    if transcript.is_empty() {
+        mark_job_failed(&pool, job_id, EMPTY_TRANSCRIPT_DETAIL).await?;
+        return Ok(());
+    }
+
+    let transcript_path = Path::new(&job.source_path)
+        .with_file_name(TRANSCRIPT_FILENAME)
+        .to_string_lossy()
+        .to_string();
+    tokio_fs::write(&transcript_path, transcript)
+        .await
+        .with_context(|| format!("failed to write transcript to {transcript_path}"))?;
+
+    let tokens = response.token_usage.total_tokens as i64;
+    if let Err(err) = usage::record_usage(&pool, job.user_id, MODULE_TRANSCRIBE, tokens, 1).await {
+        error!(?err, "failed to record transcribe usage");
+    }
+
+    sqlx::query(
+        "UPDATE transcribe_jobs SET status = $2, status_detail = $3, transcript_text = $4, transcript_path = $5, tokens_used = $6, usage_delta = 1, updated_at = NOW() WHERE id = $1",
+    )
+    .bind(job_id)
+    .bind(STATUS_COMPLETED)
+    .bind(format!("转写完成，共 {} 字。", transcript.chars().count()))
+    .bind(transcript)
+    .bind(&transcript_path)
+    .bind(tokens)
+    .execute(&pool)
+    .await
+    .context("failed to finalize transcribe job")?;
+
+    Ok(())
+}
+
+/// One user turn carrying the admin's instructions and the recording as an `input_audio` part.
+fn build_transcription_request(
+    model: &str,
+    instructions: &str,
+    audio: FileAttachment,
+) -> LlmRequest {
+    LlmRequest::new(
+        model.to_string(),
+        vec![ChatMessage::new(
+            MessageRole::User,
+            instructions.trim().to_string(),
+        )],
+    )
+    .with_attachments(vec![audio])
+    .with_module(MODULE_TRANSCRIBE)
+}
+
+/// MIME type sent with the recording, from its extension; `None` for unsupported formats.
+fn audio_content_type(filename: &str) -> Option<&'static str> {
+    let extension = Path::new(filename)
+        .extension()
+        .and_then(|ext| ext.to_str())?
+        .to_ascii_lowercase();
+    match extension.as_str() {
+        "mp3" => Some("audio/mpeg"),
+        "wav" => Some("audio/wav"),
+        "m4a" => Some("audio/mp4"),
+        "ogg" => Some("audio/ogg"),
+        "flac" => Some("audio/flac"),
+        "webm" => Some("audio/webm"),
+        "aac" => Some("audio/aac"),
+        _ => None,
+    }
+}
+
+fn transcript_download_name(original_name: &str) -> String {
+    let stem = Path::new(original_name)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("audio");
+    format!("{}_transcript.txt", sanitize(stem))
+}
+
+async fn update_job_status(pool: &PgPool, job_id: Uuid, status: &str, detail: &str) -> Result<()> {
+    sqlx::query(
+        "UPDATE transcribe_jobs SET status = $2, status_detail = $3, updated_at = NOW() WHERE id = $1",
+    )
+    .bind(job_id)
+    .bind(status)
+    .bind(detail)
+    .execute(pool)
+    .await
+    .context("failed to update transcribe job status")?;
+    Ok(())
+}
+
+async fn mark_job_failed(pool: &PgPool, job_id: Uuid, message: &str) -> Result<()> {
+    sqlx::query(
+        "UPDATE transcribe_jobs SET status = $2, status_detail = $3, error_message = $3, updated_at = NOW() WHERE id = $1",
+    )
+    .bind(job_id)
+    .bind(STATUS_FAILED)
+    .bind(message)
+    .execute(pool)
+    .await
+    .context("failed to mark transcribe job as failed")?;
+    Ok(())
+}
+
+fn internal_error(err: anyhow::Error) -> (StatusCode, Json<ApiMessage>) {
+    error!(?err, "internal error in transcribe module");
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(ApiMessage::new("服务器内部错误。")),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{
+        STUB_TOTAL_TOKENS, StubProvider, StubReply, create_user, multipart_form, recorded_usage,
+        session_jar, test_state,
+    };
+
+    #[test]
+    fn every_accepted_extension_has_an_audio_content_type() {
+        for extension in AUDIO_EXTENSIONS {
+            let content_type = audio_content_type(&format!("clip.{extension}"))
+                .unwrap_or_else(|| panic!("{extension} has no content type"));
+            assert!(content_type.starts_with("audio/"));
+        }
+        assert_eq!(audio_content_type("Interview.MP3"), Some("audio/mpeg"));
+        assert_eq!(audio_content_type("notes.txt"), None);
+        assert_eq!(audio_content_type("no_extension"), None);
+    }
+
+    #[test]
+    fn transcription_request_attaches_the_recording() {
+        let request = build_transcription_request(
+            "openrouter/google/gemini-2.5-flash",
+            "  Transcribe verbatim.\n",
+            FileAttachment::new("a.wav", "audio/wav", AttachmentKind::Audio, vec![1, 2]),
+        );
+        assert_eq!(request.messages.len(), 1);
+        assert_eq!(request.messages[0].role, MessageRole::User);
+        assert_eq!(request.messages[0].text, "Transcribe verbatim.");
+        assert_eq!(request.attachments.len(), 1);
+        assert_eq!(request.attachments[0].kind, AttachmentKind::Audio);
+        assert_eq!(request.module, Some(MODULE_TRANSCRIBE));
+    }
+
+    #[sqlx::test]
+    #[ignore = "needs DATABASE_URL (disposable Postgres)"]
+    async fn uploaded_recording_is_transcribed(pool: PgPool) {
+        let provider = StubProvider::start(|request| {
+            if request.audio == ["mp3"] {
+                StubReply::text("  大家好，今天讨论样本量。\n")
+            } else {
+                StubReply::text("")
+            }
+        })
+        .await;
+        let state = test_state(pool.clone(), &provider).await;
+        let user_id = create_user(&pool).await;
+
+        let rejected = create_job(
+            State(state.clone()),
+            session_jar(&pool, user_id).await,
+            multipart_form(&[("file", "notes.txt", b"text")], &[]).await,
+        )
+        .await;
+        assert!(matches!(rejected, Err((StatusCode::BAD_REQUEST, _))));
+
+        let Json(submission) = create_job(
+            State(state.clone()),
+            session_jar(&pool, user_id).await,
+            multipart_form(&[("file", "interview.mp3", b"ID3-audio-bytes")], &[]).await,
+        )
+        .await
+        .unwrap_or_else(|_| panic!("audio upload is accepted"));
+        let job_id = submission.job_id;
+
+        let mut status = String::new();
+        for _ in 0..100 {
+            status = sqlx::query_scalar("SELECT status FROM transcribe_jobs WHERE id = $1")
+                .bind(job_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+            if status != STATUS_PENDING && status != STATUS_PROCESSING {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        assert_eq!(status, STATUS_COMPLETED);
+
+        let Json(response) = job_status(
+            State(state.clone()),
+            session_jar(&pool, user_id).await,
+            AxumPath(job_id),
+        )
+        .await
+        .unwrap_or_else(|_| panic!("owner can read the job"));
+        assert_eq!(
+            response.transcript.as_deref(),
+            Some("大家好，今天讨论样本量。")
+        );
+        assert_eq!(
+            response.transcript_download_url,
+            Some(format!("/api/transcribe/jobs/{job_id}/transcript.txt"))
+        );
+
+        let transcript_path: String =
+            sqlx::query_scalar("SELECT transcript_path FROM transcribe_jobs WHERE id = $1")
+                .bind(job_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(
+            tokio_fs::read_to_string(&transcript_path).await.unwrap(),
+            "大家好，今天讨论样本量。"
+        );
+        assert_eq!(
+            recorded_usage(&pool, user_id, MODULE_TRANSCRIBE).await,
+            (STUB_TOTAL_TOKENS, 1)
+        );
+        assert_eq!(provider.calls(), 1);
+
+        let _ =
+            tokio_fs::remove_dir_all(PathBuf::from(STORAGE_ROOT).join(job_id.to_string())).await;
+    }
+
+    #[sqlx::test]
+    #[ignore = "needs DATABASE_URL (disposable Postgres)"]
+    async fn worker_errors_fail_the_job_instead_of_leaving_it_processing(pool: PgPool) {
+        let provider = StubProvider::start(|_| StubReply::text("unused")).await;
+        let state = test_state(pool.clone(), &provider).await;
+        let user_id = create_user(&pool).await;
+        let job_id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO transcribe_jobs (id, user_id, status, original_filename, source_path, content_type) VALUES ($1, $2, $3, 'lost.mp3', 'storage/transcribe/missing/lost.mp3', 'audio/mpeg')",
+        )
+        .bind(job_id)
+        .bind(user_id)
+        .bind(STATUS_PENDING)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        spawn_job_worker(state, job_id);
+
+        let mut row = (String::new(), None);
+        for _ in 0..100 {
+            row = sqlx::query_as::<_, (String, Option<String>)>(
+                "SELECT status, error_message FROM transcribe_jobs WHERE id = $1",
+            )
+            .bind(job_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+            if row.0 != STATUS_PENDING && row.0 != STATUS_PROCESSING {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        assert_eq!(row.0, STATUS_FAILED);
+        assert!(
+            row.1
+                .unwrap_or_default()
+                .contains("failed to read audio file")
+        );
+        assert_eq!(provider.calls(), 0);
+    }
+}
@@ -9,20 +9,38 @@ use tracing::error;
 use crate::{
     AppState,
     config::{
-        DocxTranslatorModels, DocxTranslatorPrompts, update_docx_models, update_docx_prompts,
+        DEFAULT_CHUNK_MAX_PARAGRAPHS, DEFAULT_CHUNK_MAX_WORDS, DocxTranslatorModels,
+        DocxTranslatorPrompts, MAX_CHUNK_PARAGRAPHS_LIMIT, MAX_CHUNK_WORDS_LIMIT,
+        MAX_DOCX_BATCH_FILES, format_chunk_limits, parse_batch_limit, parse_chunk_limits,
+        parse_default_chunk_limits, parse_model_routes, update_docx_models, update_docx_prompts,
     },
     escape_html, fetch_glossary_terms, render_footer,
     web::{
         admin::DashboardQuery,
-        admin_utils::{compose_flash_message, sanitize_module_redirect},
+        admin_utils::{compose_query_flash, sanitize_module_redirect},
     },
 };
 
-use super::super::admin_shared::{MODULE_ADMIN_SHARED_STYLES, render_glossary_section};
+use super::super::admin_shared::{
+    MODULE_ADMIN_SHARED_STYLES, render_glossary_section, render_model_routes_field,
+};
 
 #[derive(Deserialize)]
 pub struct DocxModelForm {
     pub translation_model: String,
+    pub default_direction: String,
+    #[serde(default)]
+    pub translation_routes: String,
+    #[serde(default)]
+    pub max_batch_files: String,
+    #[serde(default)]
+    pub chunk_max_paragraphs: String,
+    #[serde(default)]
+    pub chunk_max_words: String,
+    #[serde(default)]
+    pub chunk_limits: String,
+    #[serde(default)]
+    pub glossary_check: Option<String>,
     #[serde(default)]
     pub redirect: Option<String>,
 }
@@ -58,11 +76,26 @@ pub async fn settings_page(
             Vec::new()
         });
 
-    let message_block = compose_flash_message(params.status.as_deref(), params.error.as_deref());
+    let message_block = compose_query_flash(&params);
     let redirect_base = "/dashboard/modules/translatedocx";
     let glossary_html = render_glossary_section(&glossary_terms, redirect_base);
     let footer = render_footer();
     let shared_styles = MODULE_ADMIN_SHARED_STYLES;
+    let direction_options = [
+        ("en_to_cn", "英文 → 中文"),
+        ("cn_to_en", "中文 → 英文"),
+        ("auto", "自动识别"),
+    ]
+    .iter()
+    .map(|(value, label)| {
+        let selected = if *value == models.default_direction {
+            " selected"
+        } else {
+            ""
+        };
+        format!(r#"<option value="{value}"{selected}>{label}</option>"#)
+    })
+    .collect::<String>();
 
     let html = format!(
         r##"<!DOCTYPE html>
@@ -82,7 +115,7 @@ pub async fn settings_page(
         main {{ padding: 2rem 1.5rem; max-width: 960px; margin: 0 auto; box-sizing: border-box; }}
         .panel {{ background: #ffffff; border-radius: 12px; border: 1px solid #e2e8f0; padding: 1.5rem; box-shadow: 0 18px 40px rgba(15, 23, 42, 0.08); margin-bottom: 2rem; }}
         label {{ display: block; margin-bottom: 0.5rem; font-weight: 600; color: #0f172a; }}
-        input[type="text"], textarea {{ width: 100%; padding: 0.75rem; border-radius: 8px; border: 1px solid #cbd5f5; background: #f8fafc; color: #0f172a; box-sizing: border-box; font-family: inherit; }}
+        input[type="text"], input[type="number"], select, textarea {{ width: 100%; padding: 0.75rem; border-radius: 8px; border: 1px solid #cbd5f5; background: #f8fafc; color: #0f172a; box-sizing: border-box; font-family: inherit; }}
         textarea {{ min-height: 140px; }}
         input[type="text"]:focus, textarea:focus {{ outline: none; border-color: #2563eb; box-shadow: 0 0 0 3px rgba(37, 99, 235, 0.12); }}
         button {{ padding: 0.85rem 1.2rem; border: none; border-radius: 8px; background: #2563eb; color: #ffffff; font-weight: 600; cursor: pointer; transition: background 0.15s ease; }}
@@ -112,6 +145,22 @@ pub async fn settings_page(
                 <input type="hidden" name="redirect" value="{redirect_base}">
                 <label for="translation-model">翻译模型</label>
                 <input id="translation-model" name="translation_model" type="text" value="{translation_model}" required>
+                {translation_routes_field}
+                <label for="default-direction">默认翻译方向</label>
+                <select id="default-direction" name="default_direction">{direction_options}</select>
+                <p class="note">选择“自动识别”后，用户未指定方向时将根据文档语言自动判断。</p>
+                <label for="max-batch-files">单次任务文件上限</label>
+                <input id="max-batch-files" name="max_batch_files" type="number" min="1" max="{max_batch_limit}" value="{max_batch_files}" required>
+                <p class="note">每个翻译任务最多可上传的 DOCX 文件数（1-{max_batch_limit}）。</p>
+                <label for="chunk-max-paragraphs">每段最多段落数</label>
+                <input id="chunk-max-paragraphs" name="chunk_max_paragraphs" type="number" min="1" max="{max_chunk_paragraphs}" step="1" value="{chunk_max_paragraphs}">
+                <label for="chunk-max-words">每段最多等效词数</label>
+                <input id="chunk-max-words" name="chunk_max_words" type="number" min="1" max="{max_chunk_words}" step="1" value="{chunk_max_words}">
+                <p class="note">翻译时每次请求包含的段落上限与等效词数上限（中文字符按 0.7 词计），适用于下方未单独配置的模型。长上下文的强模型可调大以减少分段，较弱的模型可调小；留空恢复默认 {default_chunk_paragraphs} 段 / {default_chunk_words} 词。</p>
+                <label for="chunk-limits">分段大小（按模型，可选）</label>
+                <textarea id="chunk-limits" name="chunk_limits" placeholder="openrouter/openai/gpt-4o-mini=20,700">{chunk_limits}</textarea>
+                <p class="note">每行一条“模型=每段最多段落数,每段最多等效词数”，按翻译模型匹配；未配置的模型使用上方的分段大小。任务中若模型提示超出上下文或输出被截断，后续分段会自动减半。</p>
+                <label><input type="checkbox" name="glossary_check" value="on"{glossary_check_checked}> 翻译完成后检查术语表执行情况（原文出现的术语在译文中未使用对应译法时，在文档状态中提示，不影响任务结果）</label>
                 <button type="submit">保存模型</button>
             </form>
         </section>
@@ -136,6 +185,23 @@ pub async fn settings_page(
         message_block = message_block,
         redirect_base = redirect_base,
         translation_model = escape_html(&models.translation_model),
+        translation_routes_field =
+            render_model_routes_field("translation_routes", &models.translation_routes),
+        direction_options = direction_options,
+        max_batch_files = models.max_batch_files,
+        max_batch_limit = MAX_DOCX_BATCH_FILES,
+        chunk_max_paragraphs = models.default_chunk_limits.max_paragraphs,
+        chunk_max_words = models.default_chunk_limits.max_words,
+        max_chunk_paragraphs = MAX_CHUNK_PARAGRAPHS_LIMIT,
+        max_chunk_words = MAX_CHUNK_WORDS_LIMIT,
+        chunk_limits = escape_html(&format_chunk_limits(&models.chunk_limits)),
+        glossary_check_checked = if models.glossary_check {
+            " checked"
+        } else {
+            ""
+        },
+        default_chunk_paragraphs = DEFAULT_CHUNK_MAX_PARAGRAPHS,
+        default_chunk_words = DEFAULT_CHUNK_MAX_WORDS,
         en_to_cn = escape_html(&prompts.en_to_cn),
         cn_to_en = escape_html(&prompts.cn_to_en),
         glossary_html = glossary_html,
@@ -155,14 +221,47 @@ pub async fn save_models(
     let redirect_base = sanitize_module_redirect(form.redirect.as_deref());
 
     let translation = form.translation_model.trim();
-    if translation.is_empty() {
+    let default_direction = form.default_direction.trim();
+    if translation.is_empty() || !matches!(default_direction, "en_to_cn" | "cn_to_en" | "auto") {
         return Ok(Redirect::to(&format!(
             "{redirect_base}?error=docx_invalid_models"
         )));
     }
 
+    let Ok(translation_routes) = parse_model_routes(&form.translation_routes) else {
+        return Ok(Redirect::to(&format!(
+            "{redirect_base}?error=model_routes_invalid"
+        )));
+    };
+
+    let Some(max_batch_files) = parse_batch_limit(&form.max_batch_files) else {
+        return Ok(Redirect::to(&format!(
+            "{redirect_base}?error=docx_batch_invalid"
+        )));
+    };
+
+    let Some(default_chunk_limits) =
+        parse_default_chunk_limits(&form.chunk_max_paragraphs, &form.chunk_max_words)
+    else {
+        return Ok(Redirect::to(&format!(
+            "{redirect_base}?error=chunk_default_invalid"
+        )));
+    };
+
+    let Ok(chunk_limits) = parse_chunk_limits(&form.chunk_limits) else {
+        return Ok(Redirect::to(&format!(
+            "{redirect_base}?error=chunk_limits_invalid"
+        )));
+    };
+
     let payload = DocxTranslatorModels {
         translation_model: translation.to_string(),
+        default_direction: default_direction.to_string(),
+        translation_routes,
+        max_batch_files,
+        default_chunk_limits,
+        chunk_limits,
+        glossary_check: form.glossary_check.is_some(),
     };
 
     if let Err(err) = update_docx_models(state.pool_ref(), &payload).await {
@@ -1,8 +1,10 @@
 use std::{
     borrow::Cow,
+    collections::{HashMap, HashSet},
     fs,
-    io::Read,
+    io::{Read, Seek, Write},
     path::{Path, PathBuf},
+    time::Duration,
 };
 
 use anyhow::{Context, Result, anyhow};
@@ -15,35 +17,51 @@ use axum::{
 };
 use axum_extra::extract::cookie::CookieJar;
 use chrono::{DateTime, Utc};
-use docx_rs::{BreakType, Docx, Paragraph, Run};
-use quick_xml::{Reader as XmlReader, events::Event};
+use docx_rs::{BreakType, Docx, Paragraph, Run, Style, StyleType, Table, TableCell, TableRow};
+use quick_xml::{
+    Reader as XmlReader,
+    events::{BytesStart, Event},
+};
 use sanitize_filename::sanitize;
 use serde::Serialize;
 use tokio::fs as tokio_fs;
-use tracing::error;
+use tracing::{error, warn};
 use uuid::Uuid;
-use zip::ZipArchive;
+use zip::{ZipArchive, ZipWriter, write::SimpleFileOptions};
 
 mod admin;
 
+use crate::web::download_limit::hold_permit;
 use crate::web::history_ui;
 use crate::web::storage::JobAccess;
 use crate::web::{
-    FileFieldConfig, FileNaming, ToolAdminLink, ToolPageLayout, UPLOAD_WIDGET_SCRIPT,
-    UPLOAD_WIDGET_STYLES, UploadWidgetConfig, process_upload_form, render_tool_page,
-    render_upload_widget,
+    FileFieldConfig, FileNaming, ToolAdminLink, ToolPageLayout, ToolPageUser, UPLOAD_WIDGET_SCRIPT,
+    UPLOAD_WIDGET_STYLES, UploadWidgetConfig, process_upload_form, render_upload_widget,
 };
 use crate::{
     AppState, GlossaryTermRow,
-    config::DocxTranslatorPrompts,
+    config::{
+        ChunkLimits, DEFAULT_DOCX_BATCH_FILES, DocxTranslatorModels, DocxTranslatorPrompts,
+        MAX_DOCX_BATCH_FILES, route_model,
+    },
     escape_html, fetch_glossary_terms, history,
-    llm::{ChatMessage, LlmRequest, MessageRole},
-    render_footer,
+    llm::{
+        ChatMessage, LlmErrorKind, LlmRequest, MessageRole, RetryPolicy, classify_error,
+        estimate_tokens, is_context_length_error,
+    },
+    notifications, render_footer,
     usage::{self, MODULE_TRANSLATE_DOCX},
+    utils::{
+        docx_to_pdf::convert_docx_to_pdf,
+        language::{DetectedLanguage, detect_language},
+        prompt_template::{fill_placeholders, single_line},
+    },
     web::{
         AccessMessages, ApiMessage, JobStatus, JobSubmission, STATUS_CLIENT_SCRIPT,
+        attachment_response,
         auth::{self, JsonAuthError},
-        ensure_storage_root, json_error, require_path, stream_file, verify_job_access,
+        ensure_job_owner, ensure_storage_root, glossary_for_scope, json_error, quota_error,
+        read_stored_file_blocking, require_path, stream_file, verify_job_access,
     },
 };
 
@@ -54,8 +72,17 @@ const STATUS_COMPLETED: &str = "completed";
 const STATUS_FAILED: &str = "failed";
 
 const PARAGRAPH_SEPARATOR: &str = "[[__PARAGRAPH_BREAK__]]";
-const CHUNK_MAX_PARAGRAPHS: usize = 20;
-const CHUNK_MAX_EQUIVALENT_WORDS: f64 = 700.0;
+/// Floor for `ChunkTuner` when it shrinks chunks after context-length errors.
+const MIN_CHUNK_PARAGRAPHS: usize = 1;
+const MIN_CHUNK_WORDS: u32 = 100;
+const DIRECTION_AUTO: &str = "auto";
+const DIRECTION_SAMPLE_PARAGRAPHS: usize = 20;
+/// Documents read (in upload order) while looking for text to detect the direction from.
+const DIRECTION_SAMPLE_DOCUMENTS: usize = 3;
+const LLM_RETRY_POLICY: RetryPolicy = RetryPolicy::new(4, Duration::from_secs(2));
+/// Shown on a completed document whose requested PDF copy could not be produced.
+const PDF_CONVERSION_FAILED_NOTE: &str =
+    "PDF conversion failed; the translated DOCX is still available.";
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum TranslationDirection {
@@ -90,6 +117,31 @@ impl TranslationDirection {
     }
 }
 
+/// Direction requested at submission time; `Auto` is resolved from the source text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DirectionMode {
+    Fixed(TranslationDirection),
+    Auto,
+}
+
+impl DirectionMode {
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "en_to_cn" => Some(DirectionMode::Fixed(TranslationDirection::EnToCn)),
+            "cn_to_en" => Some(DirectionMode::Fixed(TranslationDirection::CnToEn)),
+            DIRECTION_AUTO => Some(DirectionMode::Auto),
+            _ => None,
+        }
+    }
+
+    fn as_db_value(self) -> &'static str {
+        match self {
+            DirectionMode::Fixed(direction) => direction.as_db_value(),
+            DirectionMode::Auto => DIRECTION_AUTO,
+        }
+    }
+}
+
 pub fn router() -> Router<AppState> {
     Router::new()
         .route("/tools/translatedocx", get(translatedocx_page))
@@ -99,6 +151,10 @@ pub fn router() -> Router<AppState> {
             "/api/translatedocx/jobs/:id/documents/:doc_id/download/:variant",
             get(download_document_output),
         )
+        .route(
+            "/api/translatedocx/jobs/:id/download/all",
+            get(download_all_outputs),
+        )
         .route(
             "/dashboard/modules/translatedocx",
             get(admin::settings_page),
@@ -118,7 +174,11 @@ async fn translatedocx_page(
     jar: CookieJar,
 ) -> Result<Html<String>, Redirect> {
     let user = auth::require_user_redirect(&state, &jar).await?;
-
+    let models = state
+        .translate_docx_settings()
+        .await
+        .map(|settings| settings.models)
+        .unwrap_or_default();
     let username = escape_html(&user.username);
     let note_html = format!(
         "当前登录：<strong>{username}</strong>。上传 DOCX 文件，按照术语表进行精准翻译。",
@@ -132,10 +192,47 @@ async fn translatedocx_page(
     } else {
         None
     };
+    let page_user = ToolPageUser {
+        note_html: Cow::Owned(note_html),
+        admin_link,
+    };
+    let html = state
+        .tool_page_cache()
+        .render(MODULE_TRANSLATE_DOCX, &page_user, move || {
+            translatedocx_page_layout(models)
+        });
+
+    Ok(Html(html))
+}
+
+/// Everything on the translator page except the per-user header; cached by `ToolPageCache`
+/// and rebuilt when settings reload.
+fn translatedocx_page_layout(models: DocxTranslatorModels) -> ToolPageLayout<'static> {
+    let default_direction = models.default_direction;
+    let max_batch_files = batch_limit(models.max_batch_files);
+    let direction_options = [
+        ("en_to_cn", "英文 → 中文"),
+        ("cn_to_en", "中文 → 英文"),
+        (DIRECTION_AUTO, "自动识别"),
+    ]
+    .iter()
+    .map(|(value, label)| {
+        let selected = if *value == default_direction {
+            " selected"
+        } else {
+            ""
+        };
+        format!(r#"<option value="{value}"{selected}>{label}</option>"#)
+    })
+    .collect::<Vec<_>>()
+    .join("\n                            ");
+
+    let batch_note = format!("每个任务最多可提交 {max_batch_files} 个文件，每个文件生成一份译文。");
     let upload_widget = render_upload_widget(
         &UploadWidgetConfig::new("translator-upload", "files", "files", "上传 DOCX 文件")
-            .with_description("支持上传单个 DOCX 文档。")
-            .with_note("本工具一次仅支持处理 1 个文件。")
+            .with_description("支持批量上传 DOCX 文档。")
+            .with_multiple(Some(max_batch_files))
+            .with_note(&batch_note)
             .with_accept(".docx"),
     );
     let history_panel = history_ui::render_history_panel(MODULE_TRANSLATE_DOCX);
@@ -146,9 +243,12 @@ async fn translatedocx_page(
                         {upload_widget}
                         <label for="direction">翻译方向</label>
                         <select id="direction" name="direction">
-                            <option value="en_to_cn">英文 → 中文</option>
-                            <option value="cn_to_en">中文 → 英文</option>
+                            {direction_options}
                         </select>
+                        <p class="note">选择“自动识别”时，系统会根据文档正文的语言自动确定翻译方向。</p>
+                        <label><input type="checkbox" name="pdf_output" id="pdf-output"> 同时生成 PDF 版译文</label>
+                        <label for="callback-url">完成回调地址（可选，https）</label>
+                        <input id="callback-url" name="callback_url" type="url" placeholder="https://example.com/hooks/toolkit">
                         <button type="submit">开始翻译</button>
                     </form>
                     <div id="submission-status" class="status"></div>
@@ -159,6 +259,7 @@ async fn translatedocx_page(
                 </section>
 "#,
         upload_widget = upload_widget,
+        direction_options = direction_options,
     );
 
     let translator_script = r#"const form = document.getElementById('translator-form');
@@ -168,6 +269,7 @@ const fileInput = document.getElementById('files');
 const directionSelect = document.getElementById('direction');
 let activeJobId = null;
 let statusTimer = null;
+let activeStatusUrl = null;
 
 form.addEventListener('submit', async (event) => {
     event.preventDefault();
@@ -177,13 +279,10 @@ form.addEventListener('submit', async (event) => {
         return;
     }
 
-    if (fileInput.files.length > 1) {
-        statusBox.textContent = '一次仅支持上传 1 个文件。';
-        return;
-    }
-
     const directionValue = directionSelect.value;
-    const directionLabel = directionValue === 'cn_to_en' ? '中文 → 英文' : '英文 → 中文';
+    const directionLabel = directionValue === 'cn_to_en'
+        ? '中文 → 英文'
+        : (directionValue === 'auto' ? '自动识别方向' : '英文 → 中文');
     statusBox.textContent = `正在上传文档（${directionLabel}）...`;
     const data = new FormData(form);
 
@@ -215,30 +314,35 @@ form.addEventListener('submit', async (event) => {
 });
 
 function pollStatus(url) {
-    if (statusTimer) {
-        clearInterval(statusTimer);
-    }
+    clearTimeout(statusTimer);
+    activeStatusUrl = url;
 
     const fetchStatus = async () => {
+        let delay = 4000;
         try {
             const response = await fetch(url);
-            if (!response.ok) {
+            if (response.ok) {
+                const payload = await response.json();
+                renderStatus(payload);
+                delay = window.nextPollDelay(payload, delay);
+                if (payload.status === 'completed' || payload.status === 'failed') {
+                    delay = 0;
+                }
+            } else {
                 jobStatus.textContent = '暂时无法加载任务状态。';
-                return;
-            }
-            const payload = await response.json();
-            renderStatus(payload);
-
-            if (payload.status === 'completed' || payload.status === 'failed') {
-                clearInterval(statusTimer);
             }
         } catch (error) {
             jobStatus.textContent = '暂时无法加载任务状态。';
         }
+
+        // A newer submission has taken over polling.
+        if (url !== activeStatusUrl) {
+            return;
+        }
+        statusTimer = delay ? setTimeout(fetchStatus, delay) : null;
     };
 
     fetchStatus();
-    statusTimer = setInterval(fetchStatus, 4000);
 }
 
 function getStatusLabel(status, label) {
@@ -258,7 +362,9 @@ function renderStatus(payload) {
     }
 
     let docRows = payload.documents.map((doc) => {
-        const downloadLink = doc.translated_download_url ? `<a href="${doc.translated_download_url}">下载译文 DOCX</a>` : '处理中';
+        const pdfLink = doc.translated_pdf_download_url ? ` <a href="${doc.translated_pdf_download_url}">下载译文 PDF</a>` : '';
+        const downloadLink = doc.translated_download_url ? `<a href="${doc.translated_download_url}">下载译文 DOCX</a>${pdfLink}` : '处理中';
+        const progressRow = doc.status === 'processing' && doc.chunk_count ? `<tr><td colspan="3"><div class="note">已完成分段 ${doc.chunks_completed}/${doc.chunk_count}，已用 tokens ${doc.translation_tokens}</div></td></tr>` : '';
         const detailRow = doc.status_detail ? `<tr><td colspan="3"><div class="note">${doc.status_detail}</div></td></tr>` : '';
         const errorRow = doc.error_message ? `<tr><td colspan="3"><div class="note">${doc.error_message}</div></td></tr>` : '';
         const statusLabel = getStatusLabel(doc.status, doc.status_label);
@@ -268,6 +374,7 @@ function renderStatus(payload) {
                 <td>${statusLabel}</td>
                 <td class="downloads">${downloadLink}</td>
             </tr>
+            ${progressRow}
             ${detailRow}
             ${errorRow}
         `;
@@ -276,10 +383,12 @@ function renderStatus(payload) {
         docRows = '<tr><td colspan="3">暂无文件记录。</td></tr>';
     }
 
-    const directionBlock = payload.translation_direction ? `<p class="note">翻译方向：${payload.translation_direction}</p>` : '';
+    const directionSuffix = payload.direction_auto_detected ? '（自动识别）' : '';
+    const directionBlock = payload.translation_direction ? `<p class="note">翻译方向：${payload.translation_direction}${directionSuffix}</p>` : '';
     const detailBlock = payload.status_detail ? `<p class="note">${payload.status_detail}</p>` : '';
     const errorBlock = payload.error_message ? `<p class="note">${payload.error_message}</p>` : '';
     const jobStatusLabel = getStatusLabel(payload.status, payload.status_label);
+    const downloadAllBlock = payload.download_all_url && payload.documents.length > 1 ? `<p class="downloads"><a href="${payload.download_all_url}">下载全部译文（ZIP）</a></p>` : '';
 
     jobStatus.innerHTML = `
         <div class="status">
@@ -287,6 +396,7 @@ function renderStatus(payload) {
             ${directionBlock}
             ${detailBlock}
             ${errorBlock}
+            ${downloadAllBlock}
             <table>
                 <thead><tr><th>文件名</th><th>状态</th><th>下载</th></tr></thead>
                 <tbody>${docRows}</tbody>
@@ -296,17 +406,14 @@ function renderStatus(payload) {
 }
 "#;
 
-    let html = render_tool_page(ToolPageLayout {
+    ToolPageLayout {
         meta_title: "DOCX 文档翻译 | 张圆教授课题组 AI 工具箱",
         page_heading: "DOCX 文档翻译",
-        username: &username,
-        note_html: Cow::Owned(note_html),
         tab_group: "translatedocx",
         new_tab_label: "新任务",
         new_tab_html: Cow::Owned(new_tab_html),
         history_tab_label: "历史记录",
         history_panel_html: Cow::Owned(history_panel),
-        admin_link,
         footer_html: Cow::Owned(render_footer()),
         extra_style_blocks: vec![
             Cow::Borrowed(history_ui::HISTORY_STYLES),
@@ -328,9 +435,7 @@ function renderStatus(payload) {
                 history_ui::HISTORY_SCRIPT
             )),
         ],
-    });
-
-    Ok(Html(html))
+    }
 }
 
 async fn create_job(
@@ -351,11 +456,21 @@ async fn create_job(
     let job_id = Uuid::new_v4();
     let job_dir = PathBuf::from(STORAGE_ROOT).join(job_id.to_string());
 
+    let max_batch_files = batch_limit(
+        state
+            .translate_docx_settings()
+            .await
+            .map(|settings| settings.models.max_batch_files)
+            .unwrap_or(DEFAULT_DOCX_BATCH_FILES),
+    );
     let file_config = FileFieldConfig::new(
         "files",
         &["docx"],
-        1,
-        FileNaming::PrefixOnly { prefix: "source_" },
+        max_batch_files,
+        FileNaming::Indexed {
+            prefix: "source_",
+            pad_width: 3,
+        },
     )
     .with_min_files(1);
 
@@ -370,19 +485,36 @@ async fn create_job(
         }
     };
 
-    let mut direction = TranslationDirection::EnToCn;
-    if let Some(value) = upload.first_text("direction") {
-        direction = TranslationDirection::from_form_value(value.trim());
-    }
+    let default_mode = state
+        .translate_docx_settings()
+        .await
+        .and_then(|settings| DirectionMode::parse(&settings.models.default_direction))
+        .unwrap_or(DirectionMode::Fixed(TranslationDirection::EnToCn));
+    let direction_mode = upload
+        .first_text("direction")
+        .and_then(|value| DirectionMode::parse(value.trim()))
+        .unwrap_or(default_mode);
+
+    let pdf_output = matches!(
+        upload.first_text("pdf_output").map(str::trim),
+        Some("on" | "true" | "1" | "yes")
+    );
+
+    let callback_url = match notifications::parse_callback_url(upload.first_text("callback_url")) {
+        Ok(url) => url,
+        Err(message) => {
+            let _ = tokio_fs::remove_dir_all(&job_dir).await;
+            return Err(json_error(StatusCode::BAD_REQUEST, message));
+        }
+    };
 
     let files: Vec<_> = upload.files_for("files").cloned().collect();
-    let file = files
-        .first()
-        .expect("at least one file guaranteed by process_upload_form");
 
-    if let Err(err) = usage::ensure_within_limits(&pool, user.id, MODULE_TRANSLATE_DOCX, 1).await {
+    if let Err(err) =
+        usage::ensure_within_limits(&pool, user.id, MODULE_TRANSLATE_DOCX, files.len() as i64).await
+    {
         let _ = tokio_fs::remove_dir_all(&job_dir).await;
-        return Err(json_error(StatusCode::FORBIDDEN, err.message()));
+        return Err(quota_error(StatusCode::FORBIDDEN, &err));
     }
 
     let mut transaction = pool
@@ -391,27 +523,32 @@ async fn create_job(
         .map_err(|err| internal_error(err.into()))?;
 
     sqlx::query(
-        "INSERT INTO docx_jobs (id, user_id, status, translation_direction) VALUES ($1, $2, $3, $4)",
+        "INSERT INTO docx_jobs (id, user_id, status, translation_direction, pdf_output, callback_url) VALUES ($1, $2, $3, $4, $5, $6)",
     )
     .bind(job_id)
     .bind(user.id)
     .bind(STATUS_PENDING)
-    .bind(direction.as_db_value())
+    .bind(direction_mode.as_db_value())
+    .bind(pdf_output)
+    .bind(&callback_url)
     .execute(&mut *transaction)
     .await
     .map_err(|err| internal_error(err.into()))?;
 
-    sqlx::query(
-        "INSERT INTO docx_documents (id, job_id, original_filename, source_path, status) VALUES ($1, $2, $3, $4, $5)",
-    )
-    .bind(Uuid::new_v4())
-    .bind(job_id)
-    .bind(&file.original_name)
-    .bind(file.stored_path.to_string_lossy().to_string())
-    .bind(STATUS_PENDING)
-    .execute(&mut *transaction)
-    .await
-    .map_err(|err| internal_error(err.into()))?;
+    for (ordinal, file) in files.iter().enumerate() {
+        sqlx::query(
+            "INSERT INTO docx_documents (id, job_id, ordinal, original_filename, source_path, status) VALUES ($1, $2, $3, $4, $5, $6)",
+        )
+        .bind(Uuid::new_v4())
+        .bind(job_id)
+        .bind(ordinal as i32)
+        .bind(&file.original_name)
+        .bind(file.stored_path.to_string_lossy().to_string())
+        .bind(STATUS_PENDING)
+        .execute(&mut *transaction)
+        .await
+        .map_err(|err| internal_error(err.into()))?;
+    }
 
     transaction
         .commit()
@@ -444,7 +581,7 @@ async fn job_status(
     let pool = state.pool();
 
     let job = sqlx::query_as::<_, JobRecord>(
-        "SELECT id, user_id, status, status_detail, error_message, translation_direction, created_at, updated_at FROM docx_jobs WHERE id = $1",
+        "SELECT id, user_id, status, status_detail, error_message, translation_direction, direction_auto_detected, created_at, updated_at FROM docx_jobs WHERE id = $1",
     )
     .bind(job_id)
     .fetch_optional(&pool)
@@ -457,23 +594,22 @@ async fn job_status(
         )
     })?;
 
-    if job.user_id != user.id && !user.is_admin {
-        return Err((
-            StatusCode::FORBIDDEN,
-            Json(ApiMessage::new("您无权访问该任务。")),
-        ));
-    }
+    ensure_job_owner(job.user_id, &user, "未找到任务。", "您无权访问该任务。")?;
 
-    let direction = TranslationDirection::from_db_value(&job.translation_direction);
+    let direction_label = if job.translation_direction == DIRECTION_AUTO {
+        "自动识别中"
+    } else {
+        TranslationDirection::from_db_value(&job.translation_direction).display_label()
+    };
     let documents = sqlx::query_as::<_, DocumentRecord>(
-        "SELECT id, original_filename, status, status_detail, translated_path, error_message FROM docx_documents WHERE job_id = $1 ORDER BY created_at",
+        "SELECT id, original_filename, status, status_detail, translated_path, translated_pdf_path, error_message, glossary_terms_expected, glossary_terms_matched, chunks_completed, chunk_count, translation_tokens FROM docx_documents WHERE job_id = $1 ORDER BY ordinal",
     )
     .bind(job_id)
     .fetch_all(&pool)
     .await
     .map_err(|err| internal_error(err.into()))?;
 
-    let docs = documents
+    let docs: Vec<JobDocumentStatus> = documents
         .into_iter()
         .map(|doc| {
             let status = JobStatus::from_str(&doc.status);
@@ -484,27 +620,45 @@ async fn job_status(
                 status,
                 status_detail: doc.status_detail,
                 error_message: doc.error_message,
+                glossary_terms_expected: doc.glossary_terms_expected,
+                glossary_terms_matched: doc.glossary_terms_matched,
+                chunks_completed: doc.chunks_completed,
+                chunk_count: doc.chunk_count,
+                translation_tokens: doc.translation_tokens.unwrap_or(0),
                 translated_download_url: doc.translated_path.map(|_| {
                     format!(
                         "/api/translatedocx/jobs/{job_id}/documents/{}/download/translated",
                         doc.id
                     )
                 }),
+                translated_pdf_download_url: doc.translated_pdf_path.map(|_| {
+                    format!(
+                        "/api/translatedocx/jobs/{job_id}/documents/{}/download/translated_pdf",
+                        doc.id
+                    )
+                }),
             }
         })
         .collect();
 
     let status = JobStatus::from_str(&job.status);
+    let download_all_url = docs
+        .iter()
+        .any(|doc| doc.translated_download_url.is_some())
+        .then(|| format!("/api/translatedocx/jobs/{job_id}/download/all"));
 
     let response = JobStatusResponse {
         job_id: job.id,
         status_label: status.label_zh().to_string(),
+        poll_interval_ms: status.poll_interval_ms(),
         status,
         status_detail: job.status_detail,
         error_message: job.error_message,
         created_at: job.created_at.to_rfc3339(),
         updated_at: job.updated_at.to_rfc3339(),
-        translation_direction: direction.display_label().to_string(),
+        translation_direction: direction_label.to_string(),
+        direction_auto_detected: job.direction_auto_detected,
+        download_all_url,
         documents: docs,
     };
 
@@ -517,12 +671,16 @@ async fn download_document_output(
     AxumPath(params): AxumPath<(Uuid, Uuid, String)>,
 ) -> Result<Response, (StatusCode, Json<ApiMessage>)> {
     let (job_id, document_id, variant) = params;
-    if variant != "translated" {
-        return Err(json_error(
-            StatusCode::BAD_REQUEST,
-            "Unknown download variant.",
-        ));
-    }
+    let is_pdf = match variant.as_str() {
+        "translated" => false,
+        "translated_pdf" => true,
+        _ => {
+            return Err(json_error(
+                StatusCode::BAD_REQUEST,
+                "Unknown download variant.",
+            ));
+        }
+    };
 
     let user = auth::current_user_or_json_error(&state, &jar)
         .await
@@ -532,7 +690,7 @@ async fn download_document_output(
     let document = verify_job_access(
         || {
             sqlx::query_as::<_, DocumentDownloadRecord>(
-                "SELECT j.user_id, j.files_purged_at, d.original_filename, d.translated_path FROM docx_documents d INNER JOIN docx_jobs j ON j.id = d.job_id WHERE d.id = $1 AND d.job_id = $2",
+                "SELECT j.user_id, j.files_purged_at, d.original_filename, d.translated_path, d.translated_pdf_path FROM docx_documents d INNER JOIN docx_jobs j ON j.id = d.job_id WHERE d.id = $1 AND d.job_id = $2",
             )
             .bind(document_id)
             .bind(job_id)
@@ -547,15 +705,122 @@ async fn download_document_output(
     )
     .await?;
 
-    let path = require_path(document.translated_path.clone(), "译文文件尚未生成。")?;
-    let download_name = sanitize_for_docx(&document.original_filename);
+    let (path, download_name, content_type) = if is_pdf {
+        (
+            require_path(document.translated_pdf_path.clone(), "PDF 译文尚未生成。")?,
+            sanitize_for_pdf(&document.original_filename),
+            "application/pdf",
+        )
+    } else {
+        (
+            require_path(document.translated_path.clone(), "译文文件尚未生成。")?,
+            sanitize_for_docx(&document.original_filename),
+            "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        )
+    };
+    let permit = state.download_limiter().acquire(&user)?;
+
+    stream_file(Path::new(&path), &download_name, content_type)
+        .await
+        .map(|response| hold_permit(response, permit))
+}
+
+async fn download_all_outputs(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    AxumPath(job_id): AxumPath<Uuid>,
+) -> Result<Response, (StatusCode, Json<ApiMessage>)> {
+    let user = auth::current_user_or_json_error(&state, &jar)
+        .await
+        .map_err(|JsonAuthError { status, message }| json_error(status, message))?;
+
+    let pool = state.pool();
+    verify_job_access(
+        || {
+            sqlx::query_as::<_, (Uuid, Option<DateTime<Utc>>)>(
+                "SELECT user_id, files_purged_at FROM docx_jobs WHERE id = $1",
+            )
+            .bind(job_id)
+            .fetch_optional(&pool)
+        },
+        &user,
+        AccessMessages {
+            not_found: "Job not found.",
+            forbidden: "You do not have access to this job.",
+            purged: "译文下载已过期并被清除。",
+        },
+    )
+    .await?;
 
-    stream_file(
-        Path::new(&path),
-        &download_name,
-        "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+    let documents = sqlx::query_as::<_, (String, String)>(
+        "SELECT original_filename, translated_path FROM docx_documents WHERE job_id = $1 AND status = $2 AND translated_path IS NOT NULL ORDER BY ordinal",
     )
+    .bind(job_id)
+    .bind(STATUS_COMPLETED)
+    .fetch_all(&pool)
+    .await
+    .map_err(|err| internal_error(err.into()))?;
+
+    if documents.is_empty() {
+        return Err(json_error(StatusCode::NOT_FOUND, "暂无已完成的译文。"));
+    }
+
+    let permit = state.download_limiter().acquire(&user)?;
+    let entries = documents
+        .into_iter()
+        .map(|(original_filename, path)| {
+            (sanitize_for_docx(&original_filename), PathBuf::from(path))
+        })
+        .collect::<Vec<_>>();
+    let bytes = tokio::task::spawn_blocking(move || {
+        let mut buffer = std::io::Cursor::new(Vec::new());
+        write_translated_archive(&mut buffer, &entries)?;
+        Ok::<_, anyhow::Error>(buffer.into_inner())
+    })
     .await
+    .unwrap_or_else(|err| Err(anyhow!(err)))
+    .map_err(|err| {
+        error!(?err, %job_id, "failed to build translated DOCX archive");
+        json_error(StatusCode::INTERNAL_SERVER_ERROR, "打包译文失败。")
+    })?;
+
+    let archive_name = format!("translated_{}.zip", job_id.simple());
+    Ok(hold_permit(
+        attachment_response(bytes, &archive_name, "application/zip"),
+        permit,
+    ))
+}
+
+/// Writes each `(entry name, path)` into a ZIP, suffixing repeated names so batches with
+/// identically named inputs keep every translation.
+fn write_translated_archive<W: Write + Seek>(
+    writer: W,
+    entries: &[(String, PathBuf)],
+) -> Result<()> {
+    let mut zip = ZipWriter::new(writer);
+    let options = SimpleFileOptions::default();
+    let mut used_names = HashSet::new();
+
+    for (name, path) in entries {
+        let mut entry_name = name.clone();
+        let mut duplicate = 1;
+        while !used_names.insert(entry_name.clone()) {
+            duplicate += 1;
+            let stem = name.strip_suffix(".docx").unwrap_or(name);
+            entry_name = format!("{stem}_{duplicate}.docx");
+        }
+
+        let bytes = read_stored_file_blocking(path)
+            .with_context(|| format!("failed to read translated DOCX {}", path.display()))?;
+        zip.start_file(entry_name.as_str(), options)
+            .with_context(|| format!("failed to add {entry_name} to archive"))?;
+        zip.write_all(&bytes)
+            .with_context(|| format!("failed to write {entry_name} to archive"))?;
+    }
+
+    zip.finish()
+        .context("failed to finalize translated DOCX archive")?;
+    Ok(())
 }
 
 fn spawn_job_worker(state: AppState, job_id: Uuid) {
@@ -576,13 +841,47 @@ fn spawn_job_worker(state: AppState, job_id: Uuid) {
                 error!(?update_err, %job_id, "failed to update job after error");
             }
         }
+
+        send_job_callback(&state, job_id).await;
     });
 }
 
+/// Sends the job callback with the download links of every translated document.
+async fn send_job_callback(state: &AppState, job_id: Uuid) {
+    notifications::send_job_callback(
+        state,
+        "docx_jobs",
+        MODULE_TRANSLATE_DOCX,
+        job_id,
+        async || {
+            let documents = sqlx::query_as::<_, (Uuid, bool)>(
+                "SELECT id, translated_pdf_path IS NOT NULL FROM docx_documents WHERE job_id = $1 AND translated_path IS NOT NULL ORDER BY ordinal",
+            )
+            .bind(job_id)
+            .fetch_all(state.pool_ref())
+            .await
+            .unwrap_or_else(|err| {
+                error!(?err, %job_id, "failed to load translated documents for callback");
+                Vec::new()
+            });
+            documents
+                .into_iter()
+                .flat_map(|(document, has_pdf)| {
+                    let base =
+                        format!("/api/translatedocx/jobs/{job_id}/documents/{document}/download");
+                    std::iter::once(format!("{base}/translated"))
+                        .chain(has_pdf.then(|| format!("{base}/translated_pdf")))
+                })
+                .collect()
+        },
+    )
+    .await;
+}
+
 async fn process_job(state: AppState, job_id: Uuid) -> Result<()> {
     let pool = state.pool();
     let job = sqlx::query_as::<_, ProcessingJobRecord>(
-        "SELECT user_id, status, translation_direction FROM docx_jobs WHERE id = $1",
+        "SELECT user_id, status, translation_direction, pdf_output FROM docx_jobs WHERE id = $1",
     )
     .bind(job_id)
     .fetch_one(&pool)
@@ -603,16 +902,55 @@ async fn process_job(state: AppState, job_id: Uuid) -> Result<()> {
     .await
     .context("failed to update job status")?;
 
-    let direction = TranslationDirection::from_db_value(&job.translation_direction);
-
     let documents = sqlx::query_as::<_, ProcessingDocumentRecord>(
-        "SELECT id, original_filename, source_path FROM docx_documents WHERE job_id = $1 ORDER BY created_at",
+        "SELECT id, original_filename, source_path FROM docx_documents WHERE job_id = $1 ORDER BY ordinal",
     )
     .bind(job_id)
     .fetch_all(&pool)
     .await
     .context("failed to load job documents")?;
 
+    let direction = match DirectionMode::parse(&job.translation_direction) {
+        Some(DirectionMode::Auto) => {
+            // Batches share one direction: sample documents in upload order until one has
+            // enough recognisable text (an image-only cover file should not decide the job).
+            let mut detected = None;
+            for document in documents.iter().take(DIRECTION_SAMPLE_DOCUMENTS) {
+                let paragraphs = tokio::task::spawn_blocking({
+                    let path = document.source_path.clone();
+                    move || extract_docx_paragraphs(Path::new(&path))
+                })
+                .await
+                .unwrap_or_else(|err| Err(anyhow!(err)))
+                .map(|extracted| extracted.paragraphs)
+                .unwrap_or_else(|err| {
+                    error!(?err, %job_id, "failed to read DOCX content for direction detection");
+                    Vec::new()
+                });
+                detected = detect_direction(&paragraphs);
+                if detected.is_some() {
+                    break;
+                }
+            }
+            if detected.is_none() {
+                warn!(%job_id, "no recognisable text for direction detection, defaulting to EN → CN");
+            }
+            let resolved = detected.unwrap_or(TranslationDirection::EnToCn);
+
+            sqlx::query(
+                "UPDATE docx_jobs SET translation_direction = $2, direction_auto_detected = TRUE, updated_at = NOW() WHERE id = $1",
+            )
+            .bind(job_id)
+            .bind(resolved.as_db_value())
+            .execute(&pool)
+            .await
+            .context("failed to record detected translation direction")?;
+
+            resolved
+        }
+        _ => TranslationDirection::from_db_value(&job.translation_direction),
+    };
+
     let job_dir = PathBuf::from(STORAGE_ROOT).join(job_id.to_string());
     let settings = state
         .translate_docx_settings()
@@ -626,7 +964,9 @@ async fn process_job(state: AppState, job_id: Uuid) -> Result<()> {
         Vec::new()
     });
     let translation_prompt = build_translation_prompt(&prompts, &glossary_terms, direction);
+    let checked_terms = glossary_for_scope(&glossary_terms, MODULE_TRANSLATE_DOCX);
     let llm_client = state.llm_client();
+    let mut tuner = ChunkTuner::new(models.chunk_limits_for(&models.translation_model));
 
     let mut success_count = 0_i64;
     let mut translation_tokens_total = 0_i64;
@@ -647,14 +987,18 @@ async fn process_job(state: AppState, job_id: Uuid) -> Result<()> {
         .await?;
         update_job_status(&pool, job_id, Some(&status_detail)).await?;
 
-        let paragraphs = match tokio::task::spawn_blocking({
+        let ExtractedDocx {
+            paragraphs,
+            styles,
+            cells,
+        } = match tokio::task::spawn_blocking({
             let path = document.source_path.clone();
             move || extract_docx_paragraphs(Path::new(&path))
         })
         .await
         .unwrap_or_else(|err| Err(anyhow!(err)))
         {
-            Ok(paragraphs) => paragraphs,
+            Ok(extracted) => extracted,
             Err(err) => {
                 error!(?err, document_id = %document.id, "failed to read DOCX content");
                 update_document_status(
@@ -681,7 +1025,7 @@ async fn process_job(state: AppState, job_id: Uuid) -> Result<()> {
             continue;
         }
 
-        let chunks = plan_translation_chunks(&paragraphs);
+        let mut chunks = plan_translation_chunks(&paragraphs, 0, tuner.limits());
         if chunks.is_empty() {
             update_document_status(
                 &pool,
@@ -697,12 +1041,17 @@ async fn process_job(state: AppState, job_id: Uuid) -> Result<()> {
         let mut translated_paragraphs = paragraphs.clone();
         let mut translation_tokens_for_doc = 0_i64;
         let mut chunk_failure = false;
+        let mut realigned_chunks = 0usize;
+        record_chunk_progress(&pool, document.id, 0, chunks.len(), 0).await?;
 
         const MAX_RETRIES: usize = 3;
 
-        for chunk in &chunks {
+        let mut chunk_pos = 0;
+        while chunk_pos < chunks.len() {
+            let chunk = chunks[chunk_pos].clone();
             let mut retry_count = 0;
             let mut chunk_success = false;
+            let mut replan = false;
 
             while retry_count <= MAX_RETRIES && !chunk_success {
                 let retry_info = if retry_count > 0 {
@@ -726,50 +1075,76 @@ async fn process_job(state: AppState, job_id: Uuid) -> Result<()> {
                 .await?;
 
                 let request = build_translation_request(
-                    models.translation_model.as_str(),
+                    route_model(
+                        &models.translation_model,
+                        &models.translation_routes,
+                        estimate_tokens(&chunk.source_text),
+                    ),
                     translation_prompt.clone(),
                     &chunk.source_text,
                     direction,
                 );
 
-                let response = match llm_client.execute(request).await {
+                let response = match llm_client
+                    .execute_with_retry(request, LLM_RETRY_POLICY)
+                    .await
+                {
                     Ok(resp) => resp,
+                    Err(err) if is_context_length_error(&err) && tuner.shrink() => {
+                        warn!(
+                            ?err,
+                            document_id = %document.id,
+                            chunk_id = chunk.id,
+                            limits = ?tuner.limits(),
+                            "translation chunk exceeded the model context, shrinking chunks"
+                        );
+                        replan = true;
+                        break;
+                    }
                     Err(err) => {
                         error!(
                             ?err,
                             document_id = %document.id,
                             chunk_id = chunk.id,
-                            retry_count = retry_count,
                             "translation request failed"
                         );
 
-                        if retry_count >= MAX_RETRIES {
-                            chunk_failure = true;
-                            update_document_status(
-                                &pool,
-                                document.id,
-                                STATUS_FAILED,
-                                Some("Translation request failed after retries."),
-                                Some(&format!(
-                                    "Failed after {} attempts: {}",
-                                    MAX_RETRIES + 1,
-                                    err
-                                )),
-                            )
-                            .await?;
-                            break;
-                        }
-
-                        retry_count += 1;
-                        tokio::time::sleep(tokio::time::Duration::from_secs(
-                            2_u64.pow(retry_count as u32),
-                        ))
-                        .await;
-                        continue;
+                        chunk_failure = true;
+                        let detail = match classify_error(&err) {
+                            LlmErrorKind::Refused => {
+                                "The model refused to translate this document (content moderation)."
+                            }
+                            LlmErrorKind::RateLimited => {
+                                "The translation provider is rate limiting requests; please try again later."
+                            }
+                            LlmErrorKind::Auth => {
+                                "The translation provider rejected the API key; please contact an administrator."
+                            }
+                            _ => "Translation request failed.",
+                        };
+                        update_document_status(
+                            &pool,
+                            document.id,
+                            STATUS_FAILED,
+                            Some(detail),
+                            Some(&err.to_string()),
+                        )
+                        .await?;
+                        break;
                     }
                 };
 
                 translation_tokens_for_doc += response.token_usage.total_tokens as i64;
+                if response.hit_length_limit() && tuner.shrink() {
+                    warn!(
+                        document_id = %document.id,
+                        chunk_id = chunk.id,
+                        limits = ?tuner.limits(),
+                        "translation was cut off at the output limit, shrinking chunks"
+                    );
+                    replan = true;
+                    break;
+                }
                 let translated = response.text.trim().to_string();
 
                 if translated.is_empty() {
@@ -807,8 +1182,25 @@ async fn process_job(state: AppState, job_id: Uuid) -> Result<()> {
                     continue;
                 }
 
-                match apply_chunk_translation(&mut translated_paragraphs, chunk, &translated) {
-                    Ok(_) => {
+                match apply_chunk_translation(
+                    &mut translated_paragraphs,
+                    &chunk,
+                    &translated,
+                    retry_count >= MAX_RETRIES,
+                ) {
+                    Ok(ChunkFit::Exact) => {
+                        chunk_success = true;
+                    }
+                    Ok(ChunkFit::Realigned { segments }) => {
+                        warn!(
+                            document_id = %document.id,
+                            chunk_id = chunk.id,
+                            retry_count = retry_count,
+                            expected_segments = chunk.paragraph_indices.len(),
+                            segments,
+                            "Translation segments did not match paragraphs, realigned proportionally"
+                        );
+                        realigned_chunks += 1;
                         chunk_success = true;
                     }
                     Err(err) => {
@@ -847,16 +1239,49 @@ async fn process_job(state: AppState, job_id: Uuid) -> Result<()> {
             if chunk_failure {
                 break;
             }
+            if replan {
+                // Re-plan this chunk and everything after it with the smaller limits.
+                let start = chunk.paragraph_indices[0];
+                chunks.truncate(chunk_pos);
+                chunks.extend(plan_translation_chunks(&paragraphs, start, tuner.limits()));
+                for (position, planned) in chunks.iter_mut().enumerate() {
+                    planned.id = position;
+                }
+                continue;
+            }
+            chunk_pos += 1;
+            record_chunk_progress(
+                &pool,
+                document.id,
+                chunk_pos,
+                chunks.len(),
+                translation_tokens_for_doc,
+            )
+            .await?;
         }
 
         if chunk_failure {
             continue;
         }
 
+        let compliance = models.glossary_check.then(|| {
+            check_glossary_compliance(
+                &paragraphs,
+                &translated_paragraphs,
+                &checked_terms,
+                direction,
+            )
+        });
+
         let translated_path = job_dir.join(format!("translated_{}.docx", success_count + 1));
         let translated_path_clone = translated_path.clone();
         tokio::task::spawn_blocking(move || {
-            write_translated_docx(&translated_path_clone, &translated_paragraphs)
+            write_translated_docx(
+                &translated_path_clone,
+                &translated_paragraphs,
+                &styles,
+                &cells,
+            )
         })
         .await
         .unwrap_or_else(|err| Err(anyhow!(err)))
@@ -864,12 +1289,51 @@ async fn process_job(state: AppState, job_id: Uuid) -> Result<()> {
 
         let translated_path_string = translated_path.to_string_lossy().to_string();
 
-        sqlx::query("UPDATE docx_documents SET status = $2, status_detail = NULL, translated_path = $3, translation_tokens = $4, chunk_count = $5, updated_at = NOW() WHERE id = $1")
+        let mut pdf_warning = None;
+        let translated_pdf_path = if job.pdf_output {
+            update_document_status(
+                &pool,
+                document.id,
+                STATUS_PROCESSING,
+                Some("Converting translation to PDF"),
+                None,
+            )
+            .await?;
+            match convert_docx_to_pdf(&translated_path).await {
+                Ok(pdf_path) => Some(pdf_path.to_string_lossy().to_string()),
+                Err(err) => {
+                    warn!(?err, document_id = %document.id, "failed to convert translated DOCX to PDF");
+                    pdf_warning = Some(PDF_CONVERSION_FAILED_NOTE.to_string());
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let warnings: Vec<String> = [
+            realignment_warning(realigned_chunks),
+            compliance.as_ref().and_then(GlossaryCompliance::warning),
+            pdf_warning,
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        let completed_detail = (!warnings.is_empty()).then(|| warnings.join(" "));
+
+        let limits = tuner.limits();
+        sqlx::query("UPDATE docx_documents SET status = $2, status_detail = $8, translated_path = $3, translation_tokens = $4, chunk_count = $5, chunk_max_paragraphs = $6, chunk_max_words = $7, glossary_terms_expected = $9, glossary_terms_matched = $10, translated_pdf_path = $11, updated_at = NOW() WHERE id = $1")
             .bind(document.id)
             .bind(STATUS_COMPLETED)
             .bind(&translated_path_string)
             .bind(translation_tokens_for_doc)
             .bind(chunks.len() as i32)
+            .bind(limits.max_paragraphs as i32)
+            .bind(limits.max_words as i32)
+            .bind(completed_detail)
+            .bind(compliance.as_ref().map(|check| check.expected as i32))
+            .bind(compliance.as_ref().map(|check| check.matched as i32))
+            .bind(translated_pdf_path)
             .execute(&pool)
             .await
             .context("failed to update document record")?;
@@ -928,6 +1392,7 @@ fn build_translation_prompt(
     terms: &[GlossaryTermRow],
     direction: TranslationDirection,
 ) -> String {
+    let terms = glossary_for_scope(terms, MODULE_TRANSLATE_DOCX);
     let (template, glossary) = match direction {
         TranslationDirection::EnToCn => {
             let glossary = if terms.is_empty() {
@@ -937,7 +1402,8 @@ fn build_translation_prompt(
                 for term in terms {
                     lines.push(format!(
                         "EN: {} -> CN: {}",
-                        term.source_term, term.target_term
+                        single_line(&term.source_term),
+                        single_line(&term.target_term)
                     ));
                 }
                 lines.join("\n")
@@ -952,7 +1418,8 @@ fn build_translation_prompt(
                 for term in terms {
                     lines.push(format!(
                         "CN: {} -> EN: {}",
-                        term.target_term, term.source_term
+                        single_line(&term.target_term),
+                        single_line(&term.source_term)
                     ));
                 }
                 lines.join("\n")
@@ -961,9 +1428,32 @@ fn build_translation_prompt(
         }
     };
 
-    template
-        .replace("{{GLOSSARY}}", &glossary)
-        .replace("{{PARAGRAPH_SEPARATOR}}", PARAGRAPH_SEPARATOR)
+    fill_placeholders(
+        template,
+        &[
+            ("{{GLOSSARY}}", glossary.as_str()),
+            ("{{PARAGRAPH_SEPARATOR}}", PARAGRAPH_SEPARATOR),
+        ],
+    )
+}
+
+/// Picks a direction from the first non-empty paragraphs of the source document.
+/// Text with no recognisable script falls back to the EN → CN default.
+/// Returns `None` when the sampled paragraphs contain neither CJK nor Latin text.
+fn detect_direction(paragraphs: &[String]) -> Option<TranslationDirection> {
+    let sample = paragraphs
+        .iter()
+        .map(|paragraph| paragraph.trim())
+        .filter(|paragraph| !paragraph.is_empty())
+        .take(DIRECTION_SAMPLE_PARAGRAPHS)
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    match detect_language(&sample) {
+        DetectedLanguage::Chinese => Some(TranslationDirection::CnToEn),
+        DetectedLanguage::English => Some(TranslationDirection::EnToCn),
+        DetectedLanguage::Unknown => None,
+    }
 }
 
 fn build_translation_request(
@@ -991,14 +1481,106 @@ fn build_translation_request(
             ChatMessage::new(MessageRole::User, instruction),
         ],
     )
+    .with_module(MODULE_TRANSLATE_DOCX)
+}
+
+/// Dominant formatting of one source paragraph, reapplied to its translation. Translation
+/// rewrites a paragraph as a whole, so run-level formatting collapses to the style covering
+/// most of its text.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct ParagraphStyle {
+    /// Heading level 1-6 from the paragraph style (`Title` counts as level 1).
+    heading: Option<u8>,
+    bold: bool,
+    italic: bool,
+}
+
+#[derive(Debug, Default)]
+struct ExtractedDocx {
+    paragraphs: Vec<String>,
+    /// Parallel to `paragraphs`.
+    styles: Vec<ParagraphStyle>,
+    /// Parallel to `paragraphs`; `Some` for paragraphs inside a table cell.
+    cells: Vec<Option<CellLocation>>,
+}
+
+impl ExtractedDocx {
+    fn push(&mut self, state: &mut ParagraphState, cell: Option<CellLocation>) {
+        let (text, style) = state.finish();
+        self.paragraphs.push(text);
+        self.styles.push(style);
+        self.cells.push(cell);
+    }
+}
+
+/// Position of a paragraph within a top-level table. Nested tables are flattened into the
+/// enclosing cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CellLocation {
+    table: usize,
+    row: usize,
+    col: usize,
+}
+
+#[derive(Default)]
+struct TableCursor {
+    depth: usize,
+    count: usize,
+    row: Option<usize>,
+    col: Option<usize>,
+}
+
+impl TableCursor {
+    fn location(&self) -> Option<CellLocation> {
+        if self.depth == 0 {
+            return None;
+        }
+        Some(CellLocation {
+            table: self.count.saturating_sub(1),
+            row: self.row.unwrap_or(0),
+            col: self.col.unwrap_or(0),
+        })
+    }
+}
+
+#[derive(Default)]
+struct ParagraphState {
+    text: String,
+    heading: Option<u8>,
+    styled_chars: usize,
+    bold_chars: usize,
+    italic_chars: usize,
+}
+
+impl ParagraphState {
+    fn finish(&mut self) -> (String, ParagraphStyle) {
+        let state = std::mem::take(self);
+        let style = ParagraphStyle {
+            heading: state.heading,
+            bold: state.styled_chars > 0 && state.bold_chars * 2 > state.styled_chars,
+            italic: state.styled_chars > 0 && state.italic_chars * 2 > state.styled_chars,
+        };
+        (state.text.trim_end().to_string(), style)
+    }
 }
 
-fn extract_docx_paragraphs(path: &Path) -> Result<Vec<String>> {
+fn extract_docx_paragraphs(path: &Path) -> Result<ExtractedDocx> {
     let file = fs::File::open(path)
         .with_context(|| format!("failed to open DOCX file {}", path.display()))?;
     let mut archive = ZipArchive::new(file)
         .with_context(|| format!("failed to open DOCX archive {}", path.display()))?;
 
+    let heading_styles = match archive.by_name("word/styles.xml") {
+        Ok(mut styles) => {
+            let mut xml = String::new();
+            styles
+                .read_to_string(&mut xml)
+                .with_context(|| format!("failed to read DOCX styles for {}", path.display()))?;
+            parse_heading_styles(&xml)
+        }
+        Err(_) => HashMap::new(),
+    };
+
     let mut document = archive
         .by_name("word/document.xml")
         .with_context(|| format!("missing word/document.xml in {}", path.display()))?;
@@ -1008,56 +1590,99 @@ fn extract_docx_paragraphs(path: &Path) -> Result<Vec<String>> {
         .read_to_string(&mut xml)
         .with_context(|| format!("failed to read DOCX XML for {}", path.display()))?;
 
-    let mut reader = XmlReader::from_str(&xml);
+    parse_document_xml(&xml, &heading_styles)
+}
+
+fn parse_document_xml(xml: &str, heading_styles: &HashMap<String, u8>) -> Result<ExtractedDocx> {
+    let mut reader = XmlReader::from_str(xml);
     let mut buf = Vec::new();
-    let mut paragraphs = Vec::new();
-    let mut current = String::new();
+    let mut extracted = ExtractedDocx::default();
+    let mut current = ParagraphState::default();
     let mut in_text_node = false;
     let mut in_paragraph = false;
+    let mut in_paragraph_props = false;
+    let mut in_run_props = false;
+    let mut run_bold = false;
+    let mut run_italic = false;
+    let mut table = TableCursor::default();
 
     loop {
-        match reader.read_event_into(&mut buf) {
-            Ok(Event::Start(ref e)) => match e.name().as_ref() {
+        let event = reader.read_event_into(&mut buf);
+        let (element, is_empty) = match &event {
+            Ok(Event::Start(e)) => (Some(e.clone()), false),
+            Ok(Event::Empty(e)) => (Some(e.clone()), true),
+            _ => (None, false),
+        };
+
+        if let Some(e) = element {
+            match e.name().as_ref() {
                 b"w:p" => {
                     if in_paragraph {
-                        paragraphs.push(current.trim_end().to_string());
-                        current.clear();
+                        extracted.push(&mut current, table.location());
                     }
                     in_paragraph = true;
                 }
-                b"w:br" => current.push('\n'),
-                b"w:tab" => current.push('\t'),
-                b"w:t" => in_text_node = true,
-                _ => {}
-            },
-            Ok(Event::Empty(ref e)) => match e.name().as_ref() {
-                b"w:p" => {
-                    if in_paragraph {
-                        paragraphs.push(current.trim_end().to_string());
-                        current.clear();
+                b"w:tbl" if !is_empty => {
+                    if table.depth == 0 {
+                        table.count += 1;
+                        table.row = None;
                     }
-                    in_paragraph = true;
+                    table.depth += 1;
                 }
-                b"w:br" => current.push('\n'),
-                b"w:tab" => current.push('\t'),
-                _ => {}
-            },
-            Ok(Event::Text(e)) => {
-                if in_text_node {
-                    let value = e.unescape().map_err(|err| anyhow!(err))?.into_owned();
-                    current.push_str(&value);
+                b"w:tr" if !is_empty && table.depth == 1 => {
+                    table.row = Some(table.row.map_or(0, |row| row + 1));
+                    table.col = None;
+                }
+                b"w:tc" if !is_empty && table.depth == 1 => {
+                    table.col = Some(table.col.map_or(0, |col| col + 1));
+                }
+                b"w:pPr" if !is_empty => in_paragraph_props = true,
+                b"w:pStyle" if in_paragraph_props => {
+                    if let Some(style_id) = xml_attr(&e, b"w:val") {
+                        current.heading = heading_styles
+                            .get(&style_id)
+                            .copied()
+                            .or_else(|| heading_level_from_name(&style_id));
+                    }
                 }
+                b"w:r" if !is_empty => {
+                    run_bold = false;
+                    run_italic = false;
+                }
+                b"w:rPr" if !is_empty && !in_paragraph_props => in_run_props = true,
+                b"w:b" if in_run_props => run_bold = xml_toggle_on(&e),
+                b"w:i" if in_run_props => run_italic = xml_toggle_on(&e),
+                b"w:br" => current.text.push('\n'),
+                b"w:tab" => current.text.push('\t'),
+                b"w:t" if !is_empty => in_text_node = true,
+                _ => {}
             }
-            Ok(Event::End(ref e)) => {
-                if e.name().as_ref() == b"w:t" {
-                    in_text_node = false;
+        }
+
+        match event {
+            Ok(Event::Text(e)) if in_text_node => {
+                let value = e.unescape().map_err(|err| anyhow!(err))?.into_owned();
+                let visible = value.chars().filter(|ch| !ch.is_whitespace()).count();
+                current.styled_chars += visible;
+                if run_bold {
+                    current.bold_chars += visible;
                 }
-                if e.name().as_ref() == b"w:p" {
-                    paragraphs.push(current.trim_end().to_string());
-                    current.clear();
-                    in_paragraph = false;
+                if run_italic {
+                    current.italic_chars += visible;
                 }
+                current.text.push_str(&value);
             }
+            Ok(Event::End(ref e)) => match e.name().as_ref() {
+                b"w:t" => in_text_node = false,
+                b"w:pPr" => in_paragraph_props = false,
+                b"w:rPr" => in_run_props = false,
+                b"w:tbl" => table.depth = table.depth.saturating_sub(1),
+                b"w:p" => {
+                    extracted.push(&mut current, table.location());
+                    in_paragraph = false;
+                }
+                _ => {}
+            },
             Ok(Event::Eof) => break,
             Err(err) => return Err(anyhow!("failed to parse DOCX XML: {}", err)),
             _ => {}
@@ -1065,11 +1690,71 @@ fn extract_docx_paragraphs(path: &Path) -> Result<Vec<String>> {
         buf.clear();
     }
 
-    if !current.is_empty() {
-        paragraphs.push(current.trim_end().to_string());
+    if !current.text.is_empty() {
+        extracted.push(&mut current, table.location());
     }
 
-    Ok(paragraphs)
+    Ok(extracted)
+}
+
+/// Maps paragraph style ids to heading levels using the style names in `word/styles.xml`, so
+/// localised ids (e.g. Chinese Word's `1`, `2` for "heading 1", "heading 2") are recognised.
+fn parse_heading_styles(xml: &str) -> HashMap<String, u8> {
+    let mut reader = XmlReader::from_str(xml);
+    let mut buf = Vec::new();
+    let mut styles = HashMap::new();
+    let mut current_id: Option<String> = None;
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(ref e)) if e.name().as_ref() == b"w:style" => {
+                current_id = xml_attr(e, b"w:styleId");
+            }
+            Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) if e.name().as_ref() == b"w:name" => {
+                let level = xml_attr(e, b"w:val").and_then(|name| heading_level_from_name(&name));
+                if let (Some(id), Some(level)) = (current_id.as_ref(), level) {
+                    styles.insert(id.clone(), level);
+                }
+            }
+            Ok(Event::End(ref e)) if e.name().as_ref() == b"w:style" => current_id = None,
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    styles
+}
+
+fn heading_level_from_name(name: &str) -> Option<u8> {
+    let normalized: String = name
+        .chars()
+        .filter(|ch| !ch.is_whitespace())
+        .collect::<String>()
+        .to_ascii_lowercase();
+    if normalized == "title" {
+        return Some(1);
+    }
+    normalized
+        .strip_prefix("heading")
+        .and_then(|level| level.parse::<u8>().ok())
+        .filter(|level| (1..=6).contains(level))
+}
+
+fn xml_attr(element: &BytesStart<'_>, key: &[u8]) -> Option<String> {
+    element
+        .attributes()
+        .flatten()
+        .find(|attr| attr.key.as_ref() == key)
+        .and_then(|attr| attr.unescape_value().ok().map(|value| value.into_owned()))
+}
+
+/// `<w:b/>` and `<w:b w:val="true"/>` switch a toggle property on; `w:val="0"`/`"false"` off.
+fn xml_toggle_on(element: &BytesStart<'_>) -> bool {
+    !matches!(
+        xml_attr(element, b"w:val").as_deref(),
+        Some("0" | "false" | "off" | "none")
+    )
 }
 
 #[derive(Debug, Clone)]
@@ -1079,7 +1764,43 @@ struct TranslationChunk {
     source_text: String,
 }
 
-fn plan_translation_chunks(paragraphs: &[String]) -> Vec<TranslationChunk> {
+/// Chunk limits for the rest of a job, halved (down to a floor) whenever the model reports
+/// that a chunk did not fit its context or output window.
+#[derive(Debug)]
+struct ChunkTuner {
+    limits: ChunkLimits,
+}
+
+impl ChunkTuner {
+    fn new(limits: ChunkLimits) -> Self {
+        Self { limits }
+    }
+
+    fn limits(&self) -> ChunkLimits {
+        self.limits
+    }
+
+    /// Halves the limits; returns false when they are already at the floor.
+    fn shrink(&mut self) -> bool {
+        let shrunk = ChunkLimits {
+            max_paragraphs: (self.limits.max_paragraphs / 2).max(MIN_CHUNK_PARAGRAPHS),
+            max_words: (self.limits.max_words / 2).max(MIN_CHUNK_WORDS),
+        };
+        if shrunk == self.limits {
+            return false;
+        }
+        self.limits = shrunk;
+        true
+    }
+}
+
+/// Groups paragraphs from `start` onwards into chunks that stay within `limits`. Chunk ids are
+/// numbered from zero.
+fn plan_translation_chunks(
+    paragraphs: &[String],
+    start: usize,
+    limits: ChunkLimits,
+) -> Vec<TranslationChunk> {
     let mut chunks = Vec::new();
     let mut current_indices = Vec::new();
     let mut current_words = 0.0;
@@ -1102,7 +1823,7 @@ fn plan_translation_chunks(paragraphs: &[String]) -> Vec<TranslationChunk> {
 
     let mut chunk_id = 0usize;
 
-    for (idx, paragraph) in paragraphs.iter().enumerate() {
+    for (idx, paragraph) in paragraphs.iter().enumerate().skip(start) {
         if paragraph.trim().is_empty() {
             if !current_indices.is_empty() {
                 let indices = std::mem::take(&mut current_indices);
@@ -1115,8 +1836,8 @@ fn plan_translation_chunks(paragraphs: &[String]) -> Vec<TranslationChunk> {
 
         let para_words = calculate_equivalent_words(paragraph.trim());
         let would_exceed = !current_indices.is_empty()
-            && (current_indices.len() >= CHUNK_MAX_PARAGRAPHS
-                || current_words + para_words > CHUNK_MAX_EQUIVALENT_WORDS);
+            && (current_indices.len() >= limits.max_paragraphs
+                || current_words + para_words > f64::from(limits.max_words));
         if would_exceed {
             let indices = std::mem::take(&mut current_indices);
             push_chunk(chunk_id, indices, paragraphs);
@@ -1164,47 +1885,285 @@ fn calculate_equivalent_words(text: &str) -> f64 {
     count
 }
 
+/// How a chunk's translated segments were mapped back onto its paragraphs.
+#[derive(Debug, PartialEq)]
+enum ChunkFit {
+    /// One segment per paragraph (after dropping empty segments).
+    Exact,
+    /// Segment count still differed, so `segments` segments were spread proportionally.
+    Realigned { segments: usize },
+}
+
+/// Writes a chunk's translation into `paragraphs`. Chunks never contain blank paragraphs, so
+/// empty segments (a stray separator) are dropped first. If the count still differs, it is
+/// realigned proportionally when off by one or when `last_attempt` is set, and otherwise
+/// returned as an error so the chunk is retried. Paragraphs that receive no segment during
+/// realignment keep their source text.
 fn apply_chunk_translation(
     paragraphs: &mut [String],
     chunk: &TranslationChunk,
     translated: &str,
-) -> Result<()> {
+    last_attempt: bool,
+) -> Result<ChunkFit> {
     let parts: Vec<&str> = translated
         .split(PARAGRAPH_SEPARATOR)
         .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
         .collect();
-    if parts.len() != chunk.paragraph_indices.len() {
+    let expected = chunk.paragraph_indices.len();
+
+    if parts.len() == expected {
+        for (idx, &paragraph_index) in chunk.paragraph_indices.iter().enumerate() {
+            paragraphs[paragraph_index] = parts[idx].to_string();
+        }
+        return Ok(ChunkFit::Exact);
+    }
+
+    if parts.is_empty() || (parts.len().abs_diff(expected) > 1 && !last_attempt) {
         return Err(anyhow!(
             "translation returned {} segments but {} were expected",
             parts.len(),
-            chunk.paragraph_indices.len()
+            expected
         ));
     }
 
-    for (idx, &paragraph_index) in chunk.paragraph_indices.iter().enumerate() {
-        paragraphs[paragraph_index] = parts[idx].to_string();
+    let sources: Vec<&str> = chunk.source_text.split(PARAGRAPH_SEPARATOR).collect();
+    let source_weights: Vec<f64> = sources
+        .iter()
+        .map(|source| calculate_equivalent_words(source).max(1.0))
+        .collect();
+    let mut realigned: Vec<Option<String>> = vec![None; expected];
+    for (part, target) in parts
+        .iter()
+        .zip(proportional_targets(&source_weights, &parts))
+    {
+        match &mut realigned[target] {
+            Some(paragraph) => {
+                paragraph.push(' ');
+                paragraph.push_str(part);
+            }
+            slot => *slot = Some(part.to_string()),
+        }
+    }
+    // Paragraphs no segment landed on keep their source text rather than going blank.
+    for (idx, (text, &paragraph_index)) in realigned
+        .into_iter()
+        .zip(&chunk.paragraph_indices)
+        .enumerate()
+    {
+        paragraphs[paragraph_index] =
+            text.unwrap_or_else(|| sources.get(idx).copied().unwrap_or_default().to_string());
+    }
+
+    Ok(ChunkFit::Realigned {
+        segments: parts.len(),
+    })
+}
+
+/// For each translated segment, the paragraph whose share of the source text covers the
+/// segment's midpoint within the translation, so segments stay in order and land near the
+/// paragraph they most likely translate.
+fn proportional_targets(source_weights: &[f64], parts: &[&str]) -> Vec<usize> {
+    let source_total: f64 = source_weights.iter().sum();
+    let part_weights: Vec<f64> = parts
+        .iter()
+        .map(|part| calculate_equivalent_words(part).max(1.0))
+        .collect();
+    let part_total: f64 = part_weights.iter().sum();
+
+    let mut targets = Vec::with_capacity(parts.len());
+    let mut before = 0.0;
+    for weight in part_weights {
+        let midpoint = (before + weight / 2.0) / part_total;
+        before += weight;
+
+        let mut covered = 0.0;
+        let target = source_weights
+            .iter()
+            .position(|source| {
+                covered += source / source_total;
+                covered >= midpoint
+            })
+            .unwrap_or(source_weights.len() - 1);
+        targets.push(target);
     }
+    targets
+}
 
-    Ok(())
+fn realignment_warning(realigned_chunks: usize) -> Option<String> {
+    (realigned_chunks > 0).then(|| {
+        format!(
+            "Paragraph boundaries were approximated in {realigned_chunks} chunk(s) because the model returned a different number of segments; check those paragraphs."
+        )
+    })
+}
+
+/// Outcome of the optional glossary check over one translated document.
+#[derive(Debug, Default, PartialEq)]
+struct GlossaryCompliance {
+    /// Paragraph/term pairs where the source term appeared.
+    expected: usize,
+    /// Of those, pairs whose translation contains the glossary target term.
+    matched: usize,
+    /// Distinct `source -> target` pairs that were not followed, in first-seen order.
+    missing: Vec<String>,
+}
+
+impl GlossaryCompliance {
+    const MAX_LISTED: usize = 5;
+
+    fn warning(&self) -> Option<String> {
+        if self.matched >= self.expected {
+            return None;
+        }
+        let mut listed = self.missing[..self.missing.len().min(Self::MAX_LISTED)].join("; ");
+        if self.missing.len() > Self::MAX_LISTED {
+            listed.push_str(&format!(
+                " and {} more",
+                self.missing.len() - Self::MAX_LISTED
+            ));
+        }
+        Some(format!(
+            "Glossary check: {}/{} term occurrences used the glossary translation (missing: {listed}).",
+            self.matched, self.expected
+        ))
+    }
+}
+
+/// Compares each source paragraph with its translation: whenever a glossary term occurs in the
+/// source side, the paragraph counts as expected and as matched if the translation contains
+/// the glossary counterpart.
+fn check_glossary_compliance(
+    source: &[String],
+    translated: &[String],
+    terms: &[&GlossaryTermRow],
+    direction: TranslationDirection,
+) -> GlossaryCompliance {
+    let mut compliance = GlossaryCompliance::default();
+    for (original, output) in source.iter().zip(translated) {
+        for term in terms {
+            let (from, to) = match direction {
+                TranslationDirection::EnToCn => (&term.source_term, &term.target_term),
+                TranslationDirection::CnToEn => (&term.target_term, &term.source_term),
+            };
+            if !contains_term(original, from) {
+                continue;
+            }
+            compliance.expected += 1;
+            if contains_term(output, to) {
+                compliance.matched += 1;
+                continue;
+            }
+            let pair = format!("{} -> {}", from.trim(), to.trim());
+            if !compliance.missing.contains(&pair) {
+                compliance.missing.push(pair);
+            }
+        }
+    }
+    compliance
 }
 
-fn write_translated_docx(path: &Path, paragraphs: &[String]) -> Result<()> {
+/// Case-insensitive term search. Terms written in Latin script must start on a word boundary
+/// and end on one, optionally after a plural `s`/`es`, so `cell` matches `Cells` but not
+/// `cellular` or `subcell`.
+fn contains_term(text: &str, term: &str) -> bool {
+    let term = term.trim().to_lowercase();
+    if term.is_empty() {
+        return false;
+    }
+    let text = text.to_lowercase();
+    let is_word = |ch: Option<char>| ch.is_some_and(char::is_alphanumeric);
+    let latin_start = term.starts_with(|ch: char| ch.is_ascii_alphanumeric());
+    let latin_end = term.ends_with(|ch: char| ch.is_ascii_alphanumeric());
+
+    text.match_indices(&term).any(|(start, found)| {
+        if latin_start && is_word(text[..start].chars().next_back()) {
+            return false;
+        }
+        if !latin_end {
+            return true;
+        }
+        let rest = &text[start + found.len()..];
+        [
+            rest,
+            rest.strip_prefix('s').unwrap_or(rest),
+            rest.strip_prefix("es").unwrap_or(rest),
+        ]
+        .iter()
+        .any(|tail| !is_word(tail.chars().next()))
+    })
+}
+
+/// Font sizes (half-points) for the heading styles registered in translated output.
+const HEADING_SIZES: [usize; 6] = [32, 28, 26, 24, 22, 22];
+
+fn write_translated_docx(
+    path: &Path,
+    paragraphs: &[String],
+    styles: &[ParagraphStyle],
+    cells: &[Option<CellLocation>],
+) -> Result<()> {
     let mut docx = Docx::new();
-    for paragraph_text in paragraphs {
-        let mut paragraph = Paragraph::new();
-        if paragraph_text.is_empty() {
-            paragraph = paragraph.add_run(Run::new());
-        } else {
-            let mut first = true;
-            for segment in paragraph_text.split('\n') {
-                if !first {
-                    paragraph = paragraph.add_run(Run::new().add_break(BreakType::TextWrapping));
-                }
-                paragraph = paragraph.add_run(Run::new().add_text(segment));
-                first = false;
+    for (idx, size) in HEADING_SIZES.iter().enumerate() {
+        let level = idx + 1;
+        docx = docx.add_style(
+            Style::new(format!("Heading{}", level), StyleType::Paragraph)
+                .name(format!("heading {}", level))
+                .size(*size)
+                .bold()
+                .outline_lvl(idx),
+        );
+    }
+
+    let mut idx = 0;
+    while idx < paragraphs.len() {
+        let Some(first_cell) = cells.get(idx).copied().flatten() else {
+            let style = styles.get(idx).copied().unwrap_or_default();
+            docx = docx.add_paragraph(build_paragraph(&paragraphs[idx], style));
+            idx += 1;
+            continue;
+        };
+
+        // Gather every consecutive paragraph belonging to this table into row/cell buckets.
+        let mut rows: Vec<Vec<Vec<Paragraph>>> = Vec::new();
+        while let Some(cell) = cells
+            .get(idx)
+            .copied()
+            .flatten()
+            .filter(|cell| cell.table == first_cell.table)
+        {
+            if rows.len() <= cell.row {
+                rows.resize_with(cell.row + 1, Vec::new);
             }
+            let row = &mut rows[cell.row];
+            if row.len() <= cell.col {
+                row.resize_with(cell.col + 1, Vec::new);
+            }
+            let style = styles.get(idx).copied().unwrap_or_default();
+            row[cell.col].push(build_paragraph(&paragraphs[idx], style));
+            idx += 1;
         }
-        docx = docx.add_paragraph(paragraph);
+
+        let table_rows = rows
+            .into_iter()
+            .map(|row| {
+                TableRow::new(
+                    row.into_iter()
+                        .map(|cell_paragraphs| {
+                            let mut cell = TableCell::new();
+                            if cell_paragraphs.is_empty() {
+                                cell = cell.add_paragraph(Paragraph::new());
+                            }
+                            for paragraph in cell_paragraphs {
+                                cell = cell.add_paragraph(paragraph);
+                            }
+                            cell
+                        })
+                        .collect(),
+                )
+            })
+            .collect();
+        docx = docx.add_table(Table::new(table_rows));
     }
 
     let file = fs::File::create(path)
@@ -1215,6 +2174,35 @@ fn write_translated_docx(path: &Path, paragraphs: &[String]) -> Result<()> {
     Ok(())
 }
 
+fn build_paragraph(text: &str, style: ParagraphStyle) -> Paragraph {
+    let styled_run = |run: Run| {
+        let run = if style.bold { run.bold() } else { run };
+        if style.italic { run.italic() } else { run }
+    };
+
+    let mut paragraph = Paragraph::new();
+    if let Some(level) = style.heading {
+        paragraph = paragraph.style(&format!("Heading{}", level));
+    }
+    if text.is_empty() {
+        return paragraph.add_run(Run::new());
+    }
+    let mut first = true;
+    for segment in text.split('\n') {
+        if !first {
+            paragraph = paragraph.add_run(Run::new().add_break(BreakType::TextWrapping));
+        }
+        paragraph = paragraph.add_run(styled_run(Run::new().add_text(segment)));
+        first = false;
+    }
+    paragraph
+}
+
+/// Clamps the stored batch size so hand-edited settings cannot disable uploads.
+fn batch_limit(limit: usize) -> usize {
+    limit.clamp(1, MAX_DOCX_BATCH_FILES)
+}
+
 fn sanitize_for_docx(original_name: &str) -> String {
     let stem = Path::new(original_name)
         .file_stem()
@@ -1224,6 +2212,11 @@ fn sanitize_for_docx(original_name: &str) -> String {
     format!("{}_translated.docx", safe_stem)
 }
 
+fn sanitize_for_pdf(original_name: &str) -> String {
+    let docx_name = sanitize_for_docx(original_name);
+    format!("{}.pdf", docx_name.trim_end_matches(".docx"))
+}
+
 async fn update_document_status(
     pool: &sqlx::PgPool,
     document_id: Uuid,
@@ -1242,6 +2235,26 @@ async fn update_document_status(
     Ok(())
 }
 
+/// Stores how many chunks of a document are done and the tokens spent so far, so the status API
+/// can show progress while a long document is still translating.
+async fn record_chunk_progress(
+    pool: &sqlx::PgPool,
+    document_id: Uuid,
+    completed: usize,
+    total: usize,
+    tokens: i64,
+) -> Result<()> {
+    sqlx::query("UPDATE docx_documents SET chunks_completed = $2, chunk_count = $3, translation_tokens = $4, updated_at = NOW() WHERE id = $1")
+        .bind(document_id)
+        .bind(completed as i32)
+        .bind(total as i32)
+        .bind(tokens)
+        .execute(pool)
+        .await
+        .context("failed to record chunk progress")?;
+    Ok(())
+}
+
 async fn update_job_status(pool: &sqlx::PgPool, job_id: Uuid, detail: Option<&str>) -> Result<()> {
     sqlx::query("UPDATE docx_jobs SET status_detail = $2, updated_at = NOW() WHERE id = $1")
         .bind(job_id)
@@ -1260,6 +2273,7 @@ struct JobRecord {
     status_detail: Option<String>,
     error_message: Option<String>,
     translation_direction: String,
+    direction_auto_detected: bool,
     created_at: DateTime<Utc>,
     updated_at: DateTime<Utc>,
 }
@@ -1271,7 +2285,13 @@ struct DocumentRecord {
     status: String,
     status_detail: Option<String>,
     translated_path: Option<String>,
+    translated_pdf_path: Option<String>,
     error_message: Option<String>,
+    glossary_terms_expected: Option<i32>,
+    glossary_terms_matched: Option<i32>,
+    chunks_completed: i32,
+    chunk_count: Option<i32>,
+    translation_tokens: Option<i64>,
 }
 
 #[derive(Serialize)]
@@ -1279,11 +2299,14 @@ struct JobStatusResponse {
     job_id: Uuid,
     status: JobStatus,
     status_label: String,
+    poll_interval_ms: u64,
     status_detail: Option<String>,
     error_message: Option<String>,
     created_at: String,
     updated_at: String,
     translation_direction: String,
+    direction_auto_detected: bool,
+    download_all_url: Option<String>,
     documents: Vec<JobDocumentStatus>,
 }
 
@@ -1295,7 +2318,18 @@ struct JobDocumentStatus {
     status_label: String,
     status_detail: Option<String>,
     error_message: Option<String>,
+    /// Glossary check counts; `null` when the check was off for this document.
+    glossary_terms_expected: Option<i32>,
+    glossary_terms_matched: Option<i32>,
+    /// Chunks translated so far and the current plan's total (`null` until the document is
+    /// chunked; the total can grow when chunks shrink after context-length errors).
+    chunks_completed: i32,
+    chunk_count: Option<i32>,
+    /// Tokens spent on this document so far, including failed or shrunk attempts.
+    translation_tokens: i64,
     translated_download_url: Option<String>,
+    /// Set when the job asked for a PDF copy and conversion succeeded.
+    translated_pdf_download_url: Option<String>,
 }
 
 #[derive(sqlx::FromRow)]
@@ -1304,6 +2338,7 @@ struct DocumentDownloadRecord {
     files_purged_at: Option<DateTime<Utc>>,
     original_filename: String,
     translated_path: Option<String>,
+    translated_pdf_path: Option<String>,
 }
 
 impl JobAccess for DocumentDownloadRecord {
@@ -1321,6 +2356,7 @@ struct ProcessingJobRecord {
     user_id: Uuid,
     status: String,
     translation_direction: String,
+    pdf_output: bool,
 }
 
 #[derive(sqlx::FromRow)]
@@ -1341,7 +2377,260 @@ fn internal_error(err: anyhow::Error) -> (StatusCode, Json<ApiMessage>) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::{
+        STUB_TOTAL_TOKENS, StubProvider, StubReply, create_user, recorded_usage, session_jar,
+        test_state,
+    };
     use chrono::Utc;
+    use serde_json::json;
+    use sqlx::PgPool;
+
+    async fn seed_job(pool: &PgPool, user_id: Uuid, paragraphs: &[&str]) -> Uuid {
+        let job_id = Uuid::new_v4();
+        let job_dir = PathBuf::from(STORAGE_ROOT).join(job_id.to_string());
+        fs::create_dir_all(&job_dir).unwrap();
+        let source_path = job_dir.join("source_000_paper.docx");
+        let docx = paragraphs.iter().fold(Docx::new(), |docx, text| {
+            docx.add_paragraph(Paragraph::new().add_run(Run::new().add_text(*text)))
+        });
+        docx.build()
+            .pack(fs::File::create(&source_path).unwrap())
+            .unwrap();
+
+        sqlx::query(
+            "INSERT INTO docx_jobs (id, user_id, status, translation_direction) VALUES ($1, $2, $3, $4)",
+        )
+        .bind(job_id)
+        .bind(user_id)
+        .bind(STATUS_PENDING)
+        .bind(TranslationDirection::EnToCn.as_db_value())
+        .execute(pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO docx_documents (id, job_id, original_filename, source_path, status) VALUES ($1, $2, $3, $4, $5)",
+        )
+        .bind(Uuid::new_v4())
+        .bind(job_id)
+        .bind("paper.docx")
+        .bind(source_path.to_string_lossy().to_string())
+        .bind(STATUS_PENDING)
+        .execute(pool)
+        .await
+        .unwrap();
+
+        job_id
+    }
+
+    async fn job_state(pool: &PgPool, job_id: Uuid) -> (String, Option<String>) {
+        sqlx::query_as("SELECT status, status_detail FROM docx_jobs WHERE id = $1")
+            .bind(job_id)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[sqlx::test]
+    #[ignore = "needs DATABASE_URL (disposable Postgres)"]
+    async fn process_job_writes_translated_docx(pool: PgPool) {
+        let provider = StubProvider::start(|request| {
+            let source = request
+                .user
+                .split_once("Input text:\n")
+                .map(|(_, text)| text)
+                .unwrap_or_default();
+            let translated = source
+                .split(PARAGRAPH_SEPARATOR)
+                .map(|paragraph| format!("译文：{}", paragraph.trim()))
+                .collect::<Vec<_>>()
+                .join(PARAGRAPH_SEPARATOR);
+            StubReply::Text(translated)
+        })
+        .await;
+        let state = test_state(pool.clone(), &provider).await;
+        let user_id = create_user(&pool).await;
+        let job_id = seed_job(&pool, user_id, &["Introduction", "Soil carbon declines."]).await;
+
+        process_job(state, job_id).await.unwrap();
+
+        let (status, detail) = job_state(&pool, job_id).await;
+        assert_eq!(status, STATUS_COMPLETED);
+        assert_eq!(
+            detail.as_deref(),
+            Some("Completed 1 translated document(s) (英文 → 中文)")
+        );
+
+        let (doc_status, translated_path, chunk_count, chunks_completed, tokens): (
+            String,
+            Option<String>,
+            Option<i32>,
+            i32,
+            Option<i64>,
+        ) = sqlx::query_as(
+            "SELECT status, translated_path, chunk_count, chunks_completed, translation_tokens FROM docx_documents WHERE job_id = $1",
+        )
+        .bind(job_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(doc_status, STATUS_COMPLETED);
+        assert_eq!(chunk_count, Some(1));
+        assert_eq!(chunks_completed, 1);
+        assert_eq!(tokens, Some(STUB_TOTAL_TOKENS));
+        let translated = extract_docx_paragraphs(Path::new(&translated_path.unwrap())).unwrap();
+        assert_eq!(
+            translated.paragraphs,
+            vec!["译文：Introduction", "译文：Soil carbon declines."]
+        );
+
+        assert_eq!(
+            recorded_usage(&pool, user_id, MODULE_TRANSLATE_DOCX).await,
+            (STUB_TOTAL_TOKENS, 1)
+        );
+
+        let _ = fs::remove_dir_all(PathBuf::from(STORAGE_ROOT).join(job_id.to_string()));
+    }
+
+    #[sqlx::test]
+    #[ignore = "needs DATABASE_URL (disposable Postgres)"]
+    async fn process_job_fails_refused_document(pool: PgPool) {
+        let provider = StubProvider::start(|_| {
+            StubReply::Error(
+                StatusCode::FORBIDDEN,
+                json!({ "error": { "message": "Input was flagged by moderation", "metadata": { "reasons": ["violence"] } } }),
+            )
+        })
+        .await;
+        let state = test_state(pool.clone(), &provider).await;
+        let user_id = create_user(&pool).await;
+        let job_id = seed_job(&pool, user_id, &["Soil carbon declines."]).await;
+
+        process_job(state, job_id).await.unwrap();
+
+        let (status, detail) = job_state(&pool, job_id).await;
+        assert_eq!(status, STATUS_FAILED);
+        assert_eq!(
+            detail.as_deref(),
+            Some("Job finished but no documents were successfully translated")
+        );
+
+        let (doc_status, doc_detail, translated_path): (String, Option<String>, Option<String>) =
+            sqlx::query_as(
+                "SELECT status, status_detail, translated_path FROM docx_documents WHERE job_id = $1",
+            )
+            .bind(job_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(doc_status, STATUS_FAILED);
+        assert_eq!(
+            doc_detail.as_deref(),
+            Some("The model refused to translate this document (content moderation).")
+        );
+        assert_eq!(translated_path, None);
+        assert_eq!(
+            recorded_usage(&pool, user_id, MODULE_TRANSLATE_DOCX).await,
+            (0, 0)
+        );
+
+        let _ = fs::remove_dir_all(PathBuf::from(STORAGE_ROOT).join(job_id.to_string()));
+    }
+
+    #[sqlx::test]
+    #[ignore = "needs DATABASE_URL (disposable Postgres)"]
+    async fn batch_documents_are_listed_in_upload_order(pool: PgPool) {
+        let provider = StubProvider::start(|_| StubReply::text("unused")).await;
+        let state = test_state(pool.clone(), &provider).await;
+        let user_id = create_user(&pool).await;
+        let job_id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO docx_jobs (id, user_id, status, translation_direction) VALUES ($1, $2, $3, $4)",
+        )
+        .bind(job_id)
+        .bind(user_id)
+        .bind(STATUS_COMPLETED)
+        .bind(TranslationDirection::EnToCn.as_db_value())
+        .execute(&pool)
+        .await
+        .unwrap();
+        // One statement, so every row gets the same created_at.
+        sqlx::query(
+            "INSERT INTO docx_documents (id, job_id, ordinal, original_filename, source_path, status)
+             SELECT gen_random_uuid(), $1, ordinal, name, '', $2
+             FROM UNNEST($3::INT[], $4::TEXT[]) AS uploads(ordinal, name)",
+        )
+        .bind(job_id)
+        .bind(STATUS_COMPLETED)
+        .bind(vec![2, 0, 3, 1])
+        .bind(vec!["c.docx", "a.docx", "d.docx", "b.docx"])
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let Json(response) = job_status(
+            State(state),
+            session_jar(&pool, user_id).await,
+            AxumPath(job_id),
+        )
+        .await
+        .unwrap_or_else(|_| panic!("owner can read the job"));
+        let names: Vec<_> = response
+            .documents
+            .iter()
+            .map(|doc| doc.original_filename.as_str())
+            .collect();
+        assert_eq!(names, ["a.docx", "b.docx", "c.docx", "d.docx"]);
+    }
+
+    #[sqlx::test]
+    #[ignore = "needs DATABASE_URL (disposable Postgres)"]
+    async fn chunk_progress_is_kept_when_a_later_chunk_fails(pool: PgPool) {
+        let calls = std::sync::atomic::AtomicUsize::new(0);
+        let provider = StubProvider::start(move |request| {
+            if calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst) > 0 {
+                return StubReply::Error(
+                    StatusCode::FORBIDDEN,
+                    json!({ "error": { "message": "Input was flagged by moderation" } }),
+                );
+            }
+            let source = request
+                .user
+                .split_once("Input text:\n")
+                .map(|(_, text)| text)
+                .unwrap_or_default();
+            StubReply::Text(
+                source
+                    .split(PARAGRAPH_SEPARATOR)
+                    .map(|paragraph| format!("译文：{}", paragraph.trim()))
+                    .collect::<Vec<_>>()
+                    .join(PARAGRAPH_SEPARATOR),
+            )
+        })
+        .await;
+        let state = test_state(pool.clone(), &provider).await;
+        let user_id = create_user(&pool).await;
+        let paragraphs: Vec<String> = (0..ChunkLimits::default().max_paragraphs + 1)
+            .map(|index| format!("Paragraph {index}."))
+            .collect();
+        let paragraphs: Vec<&str> = paragraphs.iter().map(String::as_str).collect();
+        let job_id = seed_job(&pool, user_id, &paragraphs).await;
+
+        process_job(state, job_id).await.unwrap();
+
+        let (doc_status, chunks_completed, chunk_count, tokens): (String, i32, Option<i32>, Option<i64>) =
+            sqlx::query_as(
+                "SELECT status, chunks_completed, chunk_count, translation_tokens FROM docx_documents WHERE job_id = $1",
+            )
+            .bind(job_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(doc_status, STATUS_FAILED);
+        assert_eq!((chunks_completed, chunk_count), (1, Some(2)));
+        assert_eq!(tokens, Some(STUB_TOTAL_TOKENS));
+
+        let _ = fs::remove_dir_all(PathBuf::from(STORAGE_ROOT).join(job_id.to_string()));
+    }
 
     #[test]
     fn glossary_prompt_includes_terms() {
@@ -1357,6 +2646,7 @@ mod tests {
             source_term: "neuron".to_string(),
             target_term: "神经元".to_string(),
             notes: None,
+            scope: None,
             created_at: now,
             updated_at: now,
         }];
@@ -1373,7 +2663,7 @@ mod tests {
     #[test]
     fn plan_chunks_splits_long_documents() {
         let paragraphs = vec!["Paragraph".repeat(10); 30];
-        let chunks = plan_translation_chunks(&paragraphs);
+        let chunks = plan_translation_chunks(&paragraphs, 0, ChunkLimits::default());
         assert!(!chunks.is_empty());
         assert!(
             chunks
@@ -1382,6 +2672,88 @@ mod tests {
         );
     }
 
+    #[test]
+    fn context_error_shrinks_later_chunks() {
+        let paragraphs = vec!["word ".repeat(30); 40];
+        let mut tuner = ChunkTuner::new(ChunkLimits::default());
+        let planned = plan_translation_chunks(&paragraphs, 0, tuner.limits());
+        assert_eq!(planned[0].paragraph_indices.len(), 20);
+
+        // The first chunk hit a context error: re-plan from its first paragraph.
+        assert!(tuner.shrink());
+        let replanned =
+            plan_translation_chunks(&paragraphs, planned[0].paragraph_indices[0], tuner.limits());
+        assert_eq!(
+            tuner.limits(),
+            ChunkLimits {
+                max_paragraphs: 10,
+                max_words: 350
+            }
+        );
+        assert!(replanned.len() > planned.len());
+        assert!(
+            replanned
+                .iter()
+                .all(|chunk| chunk.paragraph_indices.len() <= 10)
+        );
+        assert_eq!(replanned[0].paragraph_indices[0], 0);
+
+        let tail = plan_translation_chunks(&paragraphs, 35, tuner.limits());
+        assert_eq!(tail[0].id, 0);
+        assert_eq!(tail[0].paragraph_indices, vec![35, 36, 37, 38, 39]);
+
+        let mut floor = ChunkTuner::new(ChunkLimits {
+            max_paragraphs: MIN_CHUNK_PARAGRAPHS,
+            max_words: MIN_CHUNK_WORDS,
+        });
+        assert!(!floor.shrink());
+    }
+
+    #[test]
+    fn glossary_check_counts_terms_used_in_translation() {
+        let now = Utc::now();
+        let term = |source: &str, target: &str| GlossaryTermRow {
+            id: Uuid::new_v4(),
+            source_term: source.to_string(),
+            target_term: target.to_string(),
+            notes: None,
+            scope: None,
+            created_at: now,
+            updated_at: now,
+        };
+        let neuron = term("neuron", "神经元");
+        let cell = term("cell", "细胞");
+        let terms = [&neuron, &cell];
+        let source = [
+            "Neurons fire.".to_string(),
+            "Cellular signals and one cell.".to_string(),
+            "A subcell region.".to_string(),
+        ];
+        let translated = [
+            "神经元放电。".to_string(),
+            "细胞信号和一个单元。".to_string(),
+            "亚区域。".to_string(),
+        ];
+
+        let check =
+            check_glossary_compliance(&source, &translated, &terms, TranslationDirection::EnToCn);
+        assert_eq!((check.expected, check.matched), (2, 2));
+        assert_eq!(check.warning(), None);
+
+        let reverse = check_glossary_compliance(
+            &translated,
+            &[
+                "Neurons fire.".to_string(),
+                "Signals and one unit.".to_string(),
+            ],
+            &terms,
+            TranslationDirection::CnToEn,
+        );
+        assert_eq!((reverse.expected, reverse.matched), (2, 1));
+        assert_eq!(reverse.missing, ["细胞 -> cell"]);
+        assert!(reverse.warning().unwrap().contains("1/2"));
+    }
+
     #[test]
     fn apply_chunk_translation_matches_segments() {
         let mut paragraphs = vec!["A".to_string(), "B".to_string()];
@@ -1390,10 +2762,265 @@ mod tests {
             paragraph_indices: vec![0, 1],
             source_text: "A".to_string(),
         };
-        let result =
-            apply_chunk_translation(&mut paragraphs, &chunk, "一[[__PARAGRAPH_BREAK__]]二");
-        assert!(result.is_ok());
+        let result = apply_chunk_translation(
+            &mut paragraphs,
+            &chunk,
+            "一[[__PARAGRAPH_BREAK__]]二",
+            false,
+        );
+        assert_eq!(result.unwrap(), ChunkFit::Exact);
         assert_eq!(paragraphs[0], "一");
         assert_eq!(paragraphs[1], "二");
+
+        // A trailing separator leaves an empty segment that is dropped, not realigned.
+        let result = apply_chunk_translation(
+            &mut paragraphs,
+            &chunk,
+            "甲[[__PARAGRAPH_BREAK__]]乙[[__PARAGRAPH_BREAK__]] ",
+            false,
+        );
+        assert_eq!(result.unwrap(), ChunkFit::Exact);
+        assert_eq!(paragraphs, ["甲", "乙"]);
+    }
+
+    #[test]
+    fn segment_mismatches_are_realigned_in_order() {
+        let source = [
+            "Short title",
+            "A much longer body paragraph with many more words in it",
+            "End",
+        ];
+        let mut paragraphs: Vec<String> = source.iter().map(|text| text.to_string()).collect();
+        let chunk = TranslationChunk {
+            id: 0,
+            paragraph_indices: vec![0, 1, 2],
+            source_text: source.join(PARAGRAPH_SEPARATOR),
+        };
+
+        // One segment too many: the extra piece joins its neighbour, nothing is lost.
+        let fit = apply_chunk_translation(
+            &mut paragraphs,
+            &chunk,
+            "短标题[[__PARAGRAPH_BREAK__]]较长的正文段落前半部分，内容很多[[__PARAGRAPH_BREAK__]]正文后半部分，同样很长[[__PARAGRAPH_BREAK__]]结束",
+            false,
+        )
+        .unwrap();
+        assert_eq!(fit, ChunkFit::Realigned { segments: 4 });
+        assert_eq!(
+            paragraphs,
+            [
+                "短标题",
+                "较长的正文段落前半部分，内容很多 正文后半部分，同样很长",
+                "结束"
+            ]
+        );
+
+        // Far off: retried first, realigned only on the last attempt.
+        let far_off = "全部内容合成一段";
+        let chunk = TranslationChunk {
+            id: 0,
+            paragraph_indices: vec![0, 1, 2, 3],
+            source_text: ["a", "b", "c", "d"].join(PARAGRAPH_SEPARATOR),
+        };
+        let mut paragraphs = vec![String::new(); 4];
+        assert!(apply_chunk_translation(&mut paragraphs, &chunk, far_off, false).is_err());
+        assert_eq!(
+            apply_chunk_translation(&mut paragraphs, &chunk, far_off, true).unwrap(),
+            ChunkFit::Realigned { segments: 1 }
+        );
+        // The lone segment lands on one paragraph; the others fall back to their source.
+        assert!(paragraphs.iter().all(|paragraph| !paragraph.is_empty()));
+        assert_eq!(paragraphs.iter().filter(|p| *p == far_off).count(), 1);
+        assert_eq!(
+            paragraphs
+                .iter()
+                .filter(|p| ["a", "b", "c", "d"].contains(&p.as_str()))
+                .count(),
+            3
+        );
+        assert!(
+            apply_chunk_translation(&mut paragraphs, &chunk, "[[__PARAGRAPH_BREAK__]]", true)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn extraction_keeps_dominant_paragraph_formatting() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let source = dir.path().join("source.docx");
+        let docx = Docx::new()
+            .add_style(Style::new("1", StyleType::Paragraph).name("heading 1"))
+            .add_paragraph(
+                Paragraph::new()
+                    .style("1")
+                    .add_run(Run::new().add_text("Introduction")),
+            )
+            .add_paragraph(
+                Paragraph::new()
+                    .add_run(Run::new().add_text("Mostly bold text").bold())
+                    .add_run(Run::new().add_text(" tail")),
+            )
+            .add_paragraph(Paragraph::new().add_run(Run::new().add_text("Plain").italic()))
+            .add_table(Table::new(vec![
+                TableRow::new(vec![
+                    TableCell::new()
+                        .add_paragraph(Paragraph::new().add_run(Run::new().add_text("Cell"))),
+                    TableCell::new()
+                        .add_paragraph(Paragraph::new().add_run(Run::new().add_text("Right"))),
+                ]),
+                TableRow::new(vec![
+                    TableCell::new()
+                        .add_paragraph(Paragraph::new().add_run(Run::new().add_text("Below"))),
+                    TableCell::new().add_paragraph(Paragraph::new()),
+                ]),
+            ]))
+            .add_paragraph(Paragraph::new().add_run(Run::new().add_text("After")));
+        docx.build()
+            .pack(fs::File::create(&source).unwrap())
+            .unwrap();
+
+        let extracted = extract_docx_paragraphs(&source).expect("extract");
+        assert_eq!(
+            extracted.paragraphs,
+            vec![
+                "Introduction",
+                "Mostly bold text tail",
+                "Plain",
+                "Cell",
+                "Right",
+                "Below",
+                "",
+                "After"
+            ]
+        );
+        assert_eq!(extracted.styles[0].heading, Some(1));
+        assert!(extracted.styles[1].bold && !extracted.styles[1].italic);
+        assert!(extracted.styles[2].italic);
+        assert_eq!(extracted.styles[3], ParagraphStyle::default());
+        let cell = |row, col| Some(CellLocation { table: 0, row, col });
+        assert_eq!(
+            extracted.cells,
+            vec![
+                None,
+                None,
+                None,
+                cell(0, 0),
+                cell(0, 1),
+                cell(1, 0),
+                cell(1, 1),
+                None
+            ]
+        );
+
+        let output = dir.path().join("translated.docx");
+        let translated = vec![
+            "引言".to_string(),
+            "主要为粗体".to_string(),
+            "普通".to_string(),
+            "单元格".to_string(),
+            "右侧".to_string(),
+            "下方".to_string(),
+            String::new(),
+            "之后".to_string(),
+        ];
+        write_translated_docx(&output, &translated, &extracted.styles, &extracted.cells)
+            .expect("write");
+        let round_trip = extract_docx_paragraphs(&output).expect("re-extract");
+        assert_eq!(round_trip.paragraphs, translated);
+        assert_eq!(round_trip.styles[..3], extracted.styles[..3]);
+        assert_eq!(round_trip.cells, extracted.cells);
+    }
+
+    #[test]
+    fn chunks_mix_paragraphs_and_cells() {
+        let mut paragraphs = vec![
+            "Heading".to_string(),
+            "Cell A".to_string(),
+            "Cell B".to_string(),
+        ];
+        let chunks = plan_translation_chunks(&paragraphs, 0, ChunkLimits::default());
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].paragraph_indices, vec![0, 1, 2]);
+
+        let mismatched = apply_chunk_translation(
+            &mut paragraphs,
+            &chunks[0],
+            "标题[[__PARAGRAPH_BREAK__]]单元格甲",
+            false,
+        );
+        assert!(matches!(
+            mismatched,
+            Ok(ChunkFit::Realigned { segments: 2 })
+        ));
+
+        let fit = apply_chunk_translation(
+            &mut paragraphs,
+            &chunks[0],
+            "标题[[__PARAGRAPH_BREAK__]]单元格甲[[__PARAGRAPH_BREAK__]]单元格乙",
+            false,
+        )
+        .expect("segment counts match");
+        assert_eq!(fit, ChunkFit::Exact);
+        assert_eq!(paragraphs, vec!["标题", "单元格甲", "单元格乙"]);
+    }
+
+    #[test]
+    fn pdf_download_names_follow_the_docx_names() {
+        assert_eq!(sanitize_for_docx("报告 v2.docx"), "报告 v2_translated.docx");
+        assert_eq!(sanitize_for_pdf("报告 v2.docx"), "报告 v2_translated.pdf");
+        assert_eq!(sanitize_for_pdf("a/b.docx"), "b_translated.pdf");
+    }
+
+    #[test]
+    fn archive_keeps_every_translation_under_unique_names() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let first = dir.path().join("translated_1.docx");
+        let second = dir.path().join("translated_2.docx");
+        fs::write(&first, b"first").unwrap();
+        fs::write(&second, b"second").unwrap();
+
+        let entries = vec![
+            (sanitize_for_docx("report.docx"), first),
+            (sanitize_for_docx("report.docx"), second),
+        ];
+        let mut buffer = std::io::Cursor::new(Vec::new());
+        write_translated_archive(&mut buffer, &entries).expect("archive");
+
+        let mut archive = ZipArchive::new(std::io::Cursor::new(buffer.into_inner())).unwrap();
+        let mut names: Vec<String> = archive.file_names().map(str::to_string).collect();
+        names.sort();
+        assert_eq!(
+            names,
+            vec!["report_translated.docx", "report_translated_2.docx"]
+        );
+        let mut body = String::new();
+        archive
+            .by_name("report_translated_2.docx")
+            .unwrap()
+            .read_to_string(&mut body)
+            .unwrap();
+        assert_eq!(body, "second");
+    }
+
+    #[test]
+    fn auto_direction_detects_chinese_source() {
+        let paragraphs = vec![
+            String::new(),
+            "城市声景研究的主要目的是理解人们对声环境的感知。".to_string(),
+            "本研究在三个城市公园开展了问卷调查（n = 320），并使用 SPSS 进行分析。".to_string(),
+        ];
+        assert_eq!(
+            detect_direction(&paragraphs),
+            Some(TranslationDirection::CnToEn)
+        );
+
+        let english = vec!["Urban soundscape research examines perception.".to_string()];
+        assert_eq!(
+            detect_direction(&english),
+            Some(TranslationDirection::EnToCn)
+        );
+
+        let no_text = vec!["".to_string(), "2024 — 3.1".to_string()];
+        assert_eq!(detect_direction(&no_text), None);
     }
 }
@@ -0,0 +1,645 @@
+use std::{
+    env,
+    fmt::Display,
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::{Context, Result, bail};
+use lettre::{
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+    message::{Mailbox, header::ContentType},
+    transport::smtp::authentication::Credentials,
+};
+use reqwest::{
+    Client, Url,
+    dns::{Addrs, Name, Resolve, Resolving},
+    redirect::Policy,
+};
+use serde::Serialize;
+use sqlx::{Encode, PgPool, Postgres, Type};
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::{AppState, history};
+
+/// How the SMTP connection is secured.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum SmtpSecurity {
+    /// Plain connection upgraded with STARTTLS (submission port 587).
+    #[default]
+    StartTls,
+    /// TLS from the first byte (SMTPS port 465).
+    Tls,
+    /// Unencrypted; only for relays on a trusted network.
+    None,
+}
+
+impl SmtpSecurity {
+    pub fn parse(value: &str) -> Result<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "" | "starttls" => Ok(Self::StartTls),
+            "tls" | "smtps" => Ok(Self::Tls),
+            "none" | "plain" => Ok(Self::None),
+            other => bail!("unknown SMTP security `{other}` (expected starttls, tls or none)"),
+        }
+    }
+}
+
+/// SMTP sender for job completion notices.
+///
+/// Configured via `SMTP_HOST`, `SMTP_FROM` and optional `SMTP_PORT`, `SMTP_SECURITY`
+/// (`starttls`, the default, `tls` or `none`), `SMTP_USERNAME` and `SMTP_PASSWORD`. Without
+/// `SMTP_HOST` no mailer is built and notifications are skipped.
+#[derive(Clone)]
+pub struct Mailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl Mailer {
+    pub fn new(
+        host: Option<String>,
+        port: Option<u16>,
+        security: SmtpSecurity,
+        credentials: Option<(String, String)>,
+        from: Option<String>,
+    ) -> Result<Option<Self>> {
+        let Some(host) = host
+            .map(|host| host.trim().to_string())
+            .filter(|host| !host.is_empty())
+        else {
+            return Ok(None);
+        };
+        let from = from
+            .filter(|from| !from.trim().is_empty())
+            .context("SMTP_HOST is set but SMTP_FROM is missing")?;
+        let from: Mailbox = from
+            .trim()
+            .parse()
+            .with_context(|| format!("invalid SMTP_FROM `{from}`"))?;
+
+        let mut builder = match security {
+            SmtpSecurity::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&host)
+                .with_context(|| format!("invalid SMTP host `{host}`"))?,
+            SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&host)
+                .with_context(|| format!("invalid SMTP host `{host}`"))?,
+            SmtpSecurity::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&host),
+        };
+        if let Some(port) = port {
+            builder = builder.port(port);
+        }
+        if let Some((username, password)) = credentials {
+            builder = builder.credentials(Credentials::new(username, password));
+        }
+
+        Ok(Some(Self {
+            transport: builder.build(),
+            from,
+        }))
+    }
+
+    pub fn from_env() -> Result<Option<Self>> {
+        let port = env::var("SMTP_PORT")
+            .ok()
+            .filter(|value| !value.trim().is_empty())
+            .map(|value| value.trim().parse::<u16>().context("invalid SMTP_PORT"))
+            .transpose()?;
+        let security = env::var("SMTP_SECURITY")
+            .map(|value| SmtpSecurity::parse(&value).context("invalid SMTP_SECURITY"))
+            .unwrap_or(Ok(SmtpSecurity::StartTls))?;
+        let credentials = match (env::var("SMTP_USERNAME"), env::var("SMTP_PASSWORD")) {
+            (Ok(username), Ok(password)) if !username.trim().is_empty() => {
+                Some((username.trim().to_string(), password))
+            }
+            _ => None,
+        };
+        Self::new(
+            env::var("SMTP_HOST").ok(),
+            port,
+            security,
+            credentials,
+            env::var("SMTP_FROM").ok(),
+        )
+    }
+
+    async fn send(&self, to: &str, subject: String, body: String) -> Result<()> {
+        let to: Mailbox = to
+            .parse()
+            .with_context(|| format!("invalid recipient `{to}`"))?;
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(body)
+            .context("failed to build notification email")?;
+        self.transport
+            .send(message)
+            .await
+            .context("SMTP delivery failed")?;
+        Ok(())
+    }
+}
+
+/// Subject and body of the notice for a job of `module` that ended as `succeeded`.
+fn compose_job_message(module: &str, job_key: &str, succeeded: bool) -> (String, String) {
+    let (label, tool_path) = history::module_metadata(module)
+        .map(|meta| (meta.label, meta.tool_path))
+        .unwrap_or((module, "/"));
+    let outcome = if succeeded { "已完成" } else { "失败" };
+    let subject = format!("【{label}】任务{outcome}");
+    let body = format!(
+        "您提交的「{label}」任务（编号 {job_key}）{outcome}。\n\n请登录工具箱并打开 {tool_path} 查看结果与历史记录。\n"
+    );
+    (subject, body)
+}
+
+/// Emails the job owner when a job reaches `completed` or `failed`. Does nothing when the user
+/// has no notification email or the status is anything else (e.g. a cancellation the user
+/// triggered); callers skip it entirely when `AppState::mailer` is `None`. Delivery errors
+/// are logged, never returned, so a notice can't fail the job it reports on.
+pub async fn notify_job_finished(
+    pool: &PgPool,
+    mailer: &Mailer,
+    module: &str,
+    job_key: &str,
+    user_id: Uuid,
+    status: &str,
+) {
+    let succeeded = match status {
+        "completed" => true,
+        "failed" => false,
+        _ => return,
+    };
+
+    let email: Option<String> =
+        match sqlx::query_scalar("SELECT notification_email FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_optional(pool)
+            .await
+        {
+            Ok(email) => email.flatten(),
+            Err(err) => {
+                error!(?err, %user_id, "failed to load notification email");
+                return;
+            }
+        };
+    let Some(email) = email.filter(|email| !email.trim().is_empty()) else {
+        return;
+    };
+
+    let (subject, body) = compose_job_message(module, job_key, succeeded);
+    match mailer.send(email.trim(), subject, body).await {
+        Ok(()) => info!(module, job_key, %user_id, "sent job completion email"),
+        Err(err) => error!(?err, module, job_key, %user_id, "failed to send job completion email"),
+    }
+}
+
+/// Emails the owner of the job whose `key_column` is `job_key` in `table` about its final
+/// status, via `notify_job_finished`. Does nothing without a mailer; a failed owner lookup is
+/// logged like a failed delivery.
+pub async fn notify_job_owner<K>(
+    pool: &PgPool,
+    mailer: Option<&Mailer>,
+    table: &'static str,
+    key_column: &'static str,
+    module: &str,
+    job_key: K,
+) where
+    K: for<'q> Encode<'q, Postgres> + Type<Postgres> + Display + Send,
+{
+    let Some(mailer) = mailer else {
+        return;
+    };
+    let job_key_text = job_key.to_string();
+    let owner = sqlx::query_as::<_, (Uuid, String)>(&format!(
+        "SELECT user_id, status FROM {table} WHERE {key_column} = $1"
+    ))
+    .bind(job_key)
+    .fetch_one(pool)
+    .await;
+    match owner {
+        Ok((user_id, status)) => {
+            notify_job_finished(pool, mailer, module, &job_key_text, user_id, &status).await
+        }
+        Err(err) => {
+            error!(?err, module, job_key = %job_key_text, "failed to load job owner for notification")
+        }
+    }
+}
+
+/// Delivery attempts per job callback, spaced by a doubling delay.
+const CALLBACK_ATTEMPTS: u32 = 3;
+const CALLBACK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Validates the optional `callback_url` job form field: blank means no callback, anything
+/// else must be an absolute `https` URL whose host is not `localhost` or a literal
+/// non-public IP. Host names are checked again when the callback is resolved.
+pub fn parse_callback_url(value: Option<&str>) -> Result<Option<String>, &'static str> {
+    let Some(value) = value.map(str::trim).filter(|value| !value.is_empty()) else {
+        return Ok(None);
+    };
+    match Url::parse(value) {
+        Ok(url) if url.scheme() == "https" && url.host_str().is_some() => {
+            if literal_host_is_blocked(&url) {
+                return Err("回调地址不能指向本机或内网地址。");
+            }
+            Ok(Some(url.into()))
+        }
+        _ => Err("回调地址需为有效的 https URL。"),
+    }
+}
+
+/// Whether `ip` may receive callbacks: loopback, private, link-local (including cloud metadata
+/// at 169.254.169.254), shared, unique-local, multicast and unspecified addresses are refused,
+/// as are IPv4-mapped IPv6 forms of them.
+fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                || ip.is_documentation()
+                || a == 0
+                || (a == 100 && (64..128).contains(&b)))
+        }
+        IpAddr::V6(ip) => {
+            if let Some(mapped) = ip.to_ipv4_mapped() {
+                return is_public_ip(IpAddr::V4(mapped));
+            }
+            let first = ip.segments()[0];
+            !(ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                || (first & 0xfe00) == 0xfc00
+                || (first & 0xffc0) == 0xfe80)
+        }
+    }
+}
+
+/// `localhost` and literal IP hosts never reach the resolver, so they are checked up front.
+fn literal_host_is_blocked(url: &Url) -> bool {
+    let Some(host) = url.host_str() else {
+        return true;
+    };
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    if let Ok(ip) = host.parse::<IpAddr>() {
+        return !is_public_ip(ip);
+    }
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+    host == "localhost" || host.ends_with(".localhost")
+}
+
+/// DNS resolver for the webhook client that fails when a callback host resolves to any
+/// non-public address. reqwest connects to exactly the addresses returned here, so a host
+/// re-pointed between validation and delivery is caught at connect time.
+struct PublicOnlyResolver;
+
+impl Resolve for PublicOnlyResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let host = name.as_str().to_string();
+            let addrs: Vec<SocketAddr> =
+                tokio::net::lookup_host((host.as_str(), 0)).await?.collect();
+            if addrs.is_empty() || addrs.iter().any(|addr| !is_public_ip(addr.ip())) {
+                return Err(
+                    format!("callback host `{host}` resolves to a non-public address").into(),
+                );
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// JSON body POSTed to a job's callback URL once it reaches a terminal status.
+/// `download_urls` are paths on this toolkit; fetching them needs a logged-in session.
+#[derive(Debug, Serialize)]
+pub struct JobCallback {
+    pub job_id: String,
+    pub module: &'static str,
+    pub status: String,
+    pub download_urls: Vec<String>,
+}
+
+/// HTTP client for job completion callbacks. It only talks to public addresses (see
+/// `PublicOnlyResolver`) and never follows redirects.
+#[derive(Clone)]
+pub struct WebhookClient {
+    http: Client,
+    retry_delay: Duration,
+    allow_private_hosts: bool,
+}
+
+impl WebhookClient {
+    pub fn new() -> Result<Self> {
+        let http = Client::builder()
+            .timeout(CALLBACK_TIMEOUT)
+            .redirect(Policy::none())
+            .dns_resolver(Arc::new(PublicOnlyResolver))
+            .build()
+            .context("failed to build webhook HTTP client")?;
+        Ok(Self {
+            http,
+            retry_delay: Duration::from_secs(2),
+            allow_private_hosts: false,
+        })
+    }
+
+    /// Lets tests deliver to a listener on `127.0.0.1`.
+    #[cfg(test)]
+    pub fn allowing_private_hosts(mut self) -> Self {
+        self.allow_private_hosts = true;
+        self
+    }
+
+    #[cfg(test)]
+    pub fn with_retry_delay(mut self, retry_delay: Duration) -> Self {
+        self.retry_delay = retry_delay;
+        self
+    }
+
+    /// POSTs `payload` to `url`, retrying network errors and non-2xx answers up to
+    /// `CALLBACK_ATTEMPTS` times in total. Failures are logged, never returned, like the email
+    /// notice.
+    pub async fn deliver_job_callback(&self, url: &str, payload: &JobCallback) {
+        let blocked = Url::parse(url).map_or(true, |parsed| literal_host_is_blocked(&parsed));
+        if blocked && !self.allow_private_hosts {
+            warn!(url, job_id = %payload.job_id, "job callback refused: non-public host");
+            return;
+        }
+        let mut delay = self.retry_delay;
+        for attempt in 1..=CALLBACK_ATTEMPTS {
+            let outcome = self.http.post(url).json(payload).send().await;
+            let err = match outcome {
+                Ok(response) if response.status().is_success() => {
+                    info!(url, job_id = %payload.job_id, "delivered job callback");
+                    return;
+                }
+                Ok(response) => format!("status {}", response.status()),
+                Err(err) => err.to_string(),
+            };
+            if attempt == CALLBACK_ATTEMPTS {
+                error!(url, job_id = %payload.job_id, %err, "job callback failed, giving up");
+                return;
+            }
+            warn!(url, job_id = %payload.job_id, attempt, %err, "job callback failed, retrying");
+            tokio::time::sleep(delay).await;
+            delay *= 2;
+        }
+    }
+}
+
+/// Claims the callback of job `job_id` in `table` (at most once, and only once the job is
+/// `completed` or `failed`) and POSTs the outcome with the links `download_urls` builds.
+/// `table` must be a module job table with `callback_url` and `callback_sent_at` columns.
+pub async fn send_job_callback(
+    state: &AppState,
+    table: &'static str,
+    module: &'static str,
+    job_id: Uuid,
+    download_urls: impl AsyncFnOnce() -> Vec<String>,
+) {
+    let claimed = sqlx::query_as::<_, (String, String)>(&format!(
+        "UPDATE {table} SET callback_sent_at = NOW()
+         WHERE id = $1 AND callback_url IS NOT NULL AND callback_sent_at IS NULL
+           AND status IN ('completed', 'failed')
+         RETURNING callback_url, status"
+    ))
+    .bind(job_id)
+    .fetch_optional(state.pool_ref())
+    .await;
+
+    let (url, status) = match claimed {
+        Ok(Some(claimed)) => claimed,
+        Ok(None) => return,
+        Err(err) => {
+            error!(?err, module, %job_id, "failed to claim job callback");
+            return;
+        }
+    };
+
+    let payload = JobCallback {
+        job_id: job_id.to_string(),
+        module,
+        status,
+        download_urls: download_urls().await,
+    };
+    state.webhooks().deliver_job_callback(&url, &payload).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::usage;
+
+    #[test]
+    fn mailer_is_optional_but_needs_a_sender_once_configured() {
+        assert_eq!(SmtpSecurity::parse("").unwrap(), SmtpSecurity::StartTls);
+        assert_eq!(SmtpSecurity::parse(" TLS ").unwrap(), SmtpSecurity::Tls);
+        assert!(SmtpSecurity::parse("ssl3").is_err());
+
+        let mailer = |host: Option<&str>, from: Option<&str>| {
+            Mailer::new(
+                host.map(Into::into),
+                None,
+                SmtpSecurity::StartTls,
+                None,
+                from.map(Into::into),
+            )
+        };
+        assert!(mailer(None, None).unwrap().is_none());
+        assert!(
+            mailer(Some("  "), Some("AI 工具箱 <noreply@example.com>"))
+                .unwrap()
+                .is_none()
+        );
+        assert!(mailer(Some("smtp.example.com"), None).is_err());
+        assert!(mailer(Some("smtp.example.com"), Some("not an address")).is_err());
+        assert!(
+            mailer(
+                Some("smtp.example.com"),
+                Some("AI 工具箱 <noreply@example.com>")
+            )
+            .unwrap()
+            .is_some()
+        );
+    }
+
+    #[test]
+    fn job_messages_name_the_module_and_outcome() {
+        let (subject, body) = compose_job_message(usage::MODULE_SUMMARIZER, "job-1", true);
+        assert_eq!(subject, "【摘要与翻译】任务已完成");
+        assert!(body.contains("job-1"));
+        assert!(body.contains("/tools/summarizer"));
+
+        let (subject, _) = compose_job_message("unknown", "job-2", false);
+        assert_eq!(subject, "【unknown】任务失败");
+    }
+
+    #[test]
+    fn callback_urls_must_be_https() {
+        assert_eq!(parse_callback_url(None), Ok(None));
+        assert_eq!(parse_callback_url(Some("  ")), Ok(None));
+        assert_eq!(
+            parse_callback_url(Some(" https://hooks.example.com/done?x=1 ")),
+            Ok(Some("https://hooks.example.com/done?x=1".to_string()))
+        );
+        assert!(parse_callback_url(Some("http://hooks.example.com/done")).is_err());
+        assert!(parse_callback_url(Some("hooks.example.com/done")).is_err());
+        assert!(parse_callback_url(Some("https://")).is_err());
+    }
+
+    #[test]
+    fn callback_urls_to_internal_hosts_are_rejected() {
+        for url in [
+            "https://localhost/hook",
+            "https://api.localhost./hook",
+            "https://127.0.0.1/hook",
+            "https://10.1.2.3/hook",
+            "https://172.16.0.5/hook",
+            "https://192.168.1.1/hook",
+            "https://169.254.169.254/latest/meta-data/",
+            "https://100.64.0.1/hook",
+            "https://0.0.0.0/hook",
+            "https://[::1]/hook",
+            "https://[fd00::1]/hook",
+            "https://[fe80::1]/hook",
+            "https://[::ffff:127.0.0.1]/hook",
+        ] {
+            assert!(parse_callback_url(Some(url)).is_err(), "{url} is accepted");
+        }
+        assert!(parse_callback_url(Some("https://93.184.216.34/hook")).is_ok());
+        assert!(parse_callback_url(Some("https://[2606:4700::1111]/hook")).is_ok());
+    }
+
+    #[tokio::test]
+    async fn resolver_refuses_hosts_with_internal_addresses() {
+        let resolved = PublicOnlyResolver
+            .resolve("localhost".parse().unwrap())
+            .await;
+        assert!(resolved.is_err());
+    }
+
+    #[tokio::test]
+    async fn job_callbacks_are_retried_until_accepted() {
+        use std::sync::{
+            Arc, Mutex,
+            atomic::{AtomicUsize, Ordering},
+        };
+
+        use axum::{Json, Router, extract::State, http::StatusCode, routing::post};
+        use serde_json::Value;
+
+        type Received = (Arc<AtomicUsize>, Arc<Mutex<Vec<Value>>>);
+        async fn hook(
+            State((attempts, bodies)): State<Received>,
+            Json(body): Json<Value>,
+        ) -> StatusCode {
+            bodies.lock().unwrap().push(body);
+            if attempts.fetch_add(1, Ordering::SeqCst) == 0 {
+                StatusCode::SERVICE_UNAVAILABLE
+            } else {
+                StatusCode::NO_CONTENT
+            }
+        }
+
+        let received: Received = Default::default();
+        let app = Router::new()
+            .route("/hook", post(hook))
+            .with_state(received.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let _ = axum::serve(listener, app).await;
+        });
+
+        let payload = JobCallback {
+            job_id: "job-1".into(),
+            module: usage::MODULE_INFO_EXTRACT,
+            status: "completed".into(),
+            download_urls: vec!["/api/infoextract/jobs/job-1/download/result".into()],
+        };
+
+        // The production client never calls a loopback listener.
+        let webhooks = WebhookClient::new()
+            .unwrap()
+            .with_retry_delay(Duration::from_millis(10));
+        webhooks.deliver_job_callback(&url, &payload).await;
+        assert_eq!(received.0.load(Ordering::SeqCst), 0);
+
+        let webhooks = webhooks.allowing_private_hosts();
+        webhooks.deliver_job_callback(&url, &payload).await;
+
+        assert_eq!(received.0.load(Ordering::SeqCst), 2);
+        let bodies = received.1.lock().unwrap();
+        assert_eq!(bodies[1]["job_id"], "job-1");
+        assert_eq!(bodies[1]["module"], usage::MODULE_INFO_EXTRACT);
+        assert_eq!(bodies[1]["status"], "completed");
+        assert_eq!(
+            bodies[1]["download_urls"][0],
+            "/api/infoextract/jobs/job-1/download/result"
+        );
+    }
+
+    #[sqlx::test]
+    #[ignore = "needs DATABASE_URL (disposable Postgres)"]
+    async fn job_callbacks_are_claimed_once_at_a_terminal_status(pool: PgPool) {
+        use crate::test_support::{StubProvider, StubReply, create_user, test_state};
+
+        let provider = StubProvider::start(|_| StubReply::text("unused")).await;
+        let state = test_state(pool.clone(), &provider).await;
+        let user_id = create_user(&pool).await;
+        let job_id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO summary_jobs (id, user_id, status, document_type, callback_url)
+             VALUES ($1, $2, 'processing', 'research', 'https://127.0.0.1/hook')",
+        )
+        .bind(job_id)
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let built = std::sync::atomic::AtomicUsize::new(0);
+        let send = || {
+            send_job_callback(
+                &state,
+                "summary_jobs",
+                usage::MODULE_SUMMARIZER,
+                job_id,
+                async || {
+                    built.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                    Vec::new()
+                },
+            )
+        };
+        let claimed = || async {
+            sqlx::query_scalar::<_, bool>(
+                "SELECT callback_sent_at IS NOT NULL FROM summary_jobs WHERE id = $1",
+            )
+            .bind(job_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap()
+        };
+
+        send().await;
+        assert!(!claimed().await);
+
+        sqlx::query("UPDATE summary_jobs SET status = 'completed' WHERE id = $1")
+            .bind(job_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        send().await;
+        send().await;
+        assert!(claimed().await);
+        assert_eq!(built.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,370 @@
+//! Fixtures for worker tests that run a job end to end against a disposable Postgres database
+//! and a stub OpenRouter endpoint.
+//!
+//! Those tests use `#[sqlx::test]`, which creates a fresh database per test from
+//! `DATABASE_URL` and applies `migrations/`. They are `#[ignore]`d so a plain `cargo test`
+//! needs no database; run them with
+//! `DATABASE_URL=postgres://… cargo test -- --include-ignored`.
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use axum::{
+    Json, Router,
+    body::Body,
+    extract::{FromRequest, Multipart, State},
+    http::{HeaderMap, Request, StatusCode, header},
+    routing::post,
+};
+use axum_extra::extract::cookie::{Cookie, CookieJar};
+use pdf_extract::{Dictionary, Document as PdfDocument, Object};
+use serde_json::{Value, json};
+use sqlx::PgPool;
+use tokio::net::TcpListener;
+use uuid::Uuid;
+
+use crate::{AppState, llm::LlmClient, web::auth::SESSION_COOKIE};
+
+/// Tokens the stub reports for every completion.
+pub const STUB_TOTAL_TOKENS: i64 = 150;
+
+/// A chat completion request as seen by the stub provider: the requested model, its user
+/// message text and the `file` parts (data URLs) attached to it, in order.
+pub struct StubRequest {
+    pub model: String,
+    /// OpenRouter `models` route list, empty when the request had none.
+    pub route: Vec<String>,
+    pub user: String,
+    pub files: Vec<String>,
+    pub images: Vec<String>,
+    /// `format` of every `input_audio` part, in order.
+    pub audio: Vec<String>,
+    /// The `messages` array exactly as sent, for assertions on roles and ordering.
+    pub messages: Value,
+}
+
+/// How the stub provider answers one request.
+pub enum StubReply {
+    Text(String),
+    /// Text reported as coming from the given model, like an OpenRouter route fallback.
+    TextFrom(String, String),
+    Error(StatusCode, Value),
+    /// Never answers, like a provider stuck on a long generation.
+    Hang,
+}
+
+impl StubReply {
+    pub fn text(text: impl Into<String>) -> Self {
+        StubReply::Text(text.into())
+    }
+}
+
+type StubHandler = Arc<dyn Fn(&StubRequest) -> StubReply + Send + Sync>;
+
+/// OpenRouter-compatible `/chat/completions` endpoint on a local port that answers from a
+/// closure and counts the calls it received. It also serves a `/files` upload endpoint for the
+/// `PdfDelivery::Upload` strategy.
+pub struct StubProvider {
+    base_url: String,
+    calls: Arc<AtomicUsize>,
+    uploads: Arc<AtomicUsize>,
+}
+
+impl StubProvider {
+    pub async fn start(
+        handler: impl Fn(&StubRequest) -> StubReply + Send + Sync + 'static,
+    ) -> Self {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let uploads = Arc::new(AtomicUsize::new(0));
+        let handler: StubHandler = Arc::new(handler);
+        let app = Router::new()
+            .route("/chat/completions", post(complete))
+            .with_state((handler, calls.clone()))
+            .merge(
+                Router::new()
+                    .route("/files", post(upload))
+                    .with_state(uploads.clone()),
+            );
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("failed to bind stub provider");
+        let addr = listener
+            .local_addr()
+            .expect("stub provider has no local address");
+        tokio::spawn(async move {
+            let _ = axum::serve(listener, app).await;
+        });
+
+        Self {
+            base_url: format!("http://{}", addr),
+            calls,
+            uploads,
+        }
+    }
+
+    pub fn calls(&self) -> usize {
+        self.calls.load(Ordering::SeqCst)
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub fn uploads(&self) -> usize {
+        self.uploads.load(Ordering::SeqCst)
+    }
+
+    pub fn upload_url(&self) -> String {
+        format!("{}/files", self.base_url)
+    }
+
+    pub fn client(&self) -> LlmClient {
+        LlmClient::with_openrouter_base_url(&self.base_url)
+    }
+}
+
+async fn complete(
+    State((handler, calls)): State<(StubHandler, Arc<AtomicUsize>)>,
+    Json(body): Json<Value>,
+) -> (StatusCode, Json<Value>) {
+    calls.fetch_add(1, Ordering::SeqCst);
+    let request = StubRequest {
+        model: body["model"].as_str().unwrap_or_default().to_string(),
+        route: body["models"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|model| model.as_str().map(str::to_string))
+            .collect(),
+        user: message_text(&body, "user"),
+        files: message_files(&body, "user"),
+        images: message_images(&body, "user"),
+        audio: message_audio(&body, "user"),
+        messages: body["messages"].clone(),
+    };
+
+    let (model, text) = match handler(&request) {
+        StubReply::Text(text) => (request.model, text),
+        StubReply::TextFrom(model, text) => (model, text),
+        StubReply::Error(status, body) => return (status, Json(body)),
+        StubReply::Hang => std::future::pending().await,
+    };
+    (
+        StatusCode::OK,
+        Json(json!({
+            "model": model,
+            "choices": [{
+                "message": { "role": "assistant", "content": text },
+                "finish_reason": "stop",
+            }],
+            "usage": {
+                "prompt_tokens": 100,
+                "completion_tokens": STUB_TOTAL_TOKENS - 100,
+                "total_tokens": STUB_TOTAL_TOKENS,
+            },
+        })),
+    )
+}
+
+/// Accepts an uploaded file and answers with a fake public URL named after it.
+async fn upload(State(uploads): State<Arc<AtomicUsize>>, headers: HeaderMap) -> Json<Value> {
+    uploads.fetch_add(1, Ordering::SeqCst);
+    let filename = headers
+        .get("X-Filename")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("upload.bin");
+    Json(json!({ "url": format!("https://files.test/{filename}") }))
+}
+
+/// Data URLs (or uploaded file URLs) of the `file` content parts of every message with `role`.
+fn message_files(body: &Value, role: &str) -> Vec<String> {
+    body["messages"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|message| message["role"] == role)
+        .filter_map(|message| message["content"].as_array())
+        .flatten()
+        .filter(|part| part["type"] == "file")
+        .filter_map(|part| {
+            part["file"]
+                .as_str()
+                .or_else(|| part["file"]["file_data"].as_str())
+                .map(str::to_string)
+        })
+        .collect()
+}
+
+/// Data URLs of the `image_url` content parts of every message with `role`, in order.
+fn message_images(body: &Value, role: &str) -> Vec<String> {
+    body["messages"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|message| message["role"] == role)
+        .filter_map(|message| message["content"].as_array())
+        .flatten()
+        .filter(|part| part["type"] == "image_url")
+        .filter_map(|part| part["image_url"]["url"].as_str().map(str::to_string))
+        .collect()
+}
+
+/// Formats of the `input_audio` content parts of every message with `role`, in order.
+fn message_audio(body: &Value, role: &str) -> Vec<String> {
+    body["messages"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|message| message["role"] == role)
+        .filter_map(|message| message["content"].as_array())
+        .flatten()
+        .filter(|part| part["type"] == "input_audio")
+        .filter_map(|part| part["input_audio"]["format"].as_str().map(str::to_string))
+        .collect()
+}
+
+/// Text of every message with `role`, whether sent as a plain string or as content parts.
+fn message_text(body: &Value, role: &str) -> String {
+    body["messages"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|message| message["role"] == role)
+        .map(|message| match &message["content"] {
+            Value::String(text) => text.clone(),
+            Value::Array(parts) => parts
+                .iter()
+                .filter_map(|part| part["text"].as_str())
+                .collect::<Vec<_>>()
+                .join("\n"),
+            _ => String::new(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// App state over the test database with default module settings, calling `provider`.
+pub async fn test_state(pool: PgPool, provider: &StubProvider) -> AppState {
+    AppState::for_tests(pool, provider.client())
+        .await
+        .expect("failed to build test app state")
+}
+
+/// Inserts a regular user in the seeded default usage group.
+pub async fn create_user(pool: &PgPool) -> Uuid {
+    let user_id = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO users (id, username, password_hash, usage_group_id) VALUES ($1, $2, $3, (SELECT id FROM usage_groups ORDER BY created_at LIMIT 1))",
+    )
+    .bind(user_id)
+    .bind(format!("worker-test-{}", user_id))
+    .bind("not-a-real-hash")
+    .execute(pool)
+    .await
+    .expect("failed to insert test user");
+    user_id
+}
+
+/// Opens a session for `user_id` and returns the cookie jar a signed-in browser would send.
+pub async fn session_jar(pool: &PgPool, user_id: Uuid) -> CookieJar {
+    let token = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO sessions (id, user_id, expires_at) VALUES ($1, $2, NOW() + INTERVAL '1 day')",
+    )
+    .bind(token)
+    .bind(user_id)
+    .execute(pool)
+    .await
+    .expect("failed to insert test session");
+    CookieJar::new().add(Cookie::new(SESSION_COOKIE, token.to_string()))
+}
+
+/// A `multipart/form-data` submission with `(field, filename, bytes)` files and text fields.
+pub async fn multipart_form(files: &[(&str, &str, &[u8])], fields: &[(&str, &str)]) -> Multipart {
+    const BOUNDARY: &str = "test-form-boundary";
+    let mut body = Vec::new();
+    for (name, value) in fields {
+        body.extend_from_slice(
+            format!(
+                "--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"{name}\"\r\n\r\n{value}\r\n"
+            )
+            .as_bytes(),
+        );
+    }
+    for (name, filename, bytes) in files {
+        body.extend_from_slice(
+            format!(
+                "--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"{name}\"; filename=\"{filename}\"\r\nContent-Type: application/octet-stream\r\n\r\n"
+            )
+            .as_bytes(),
+        );
+        body.extend_from_slice(bytes);
+        body.extend_from_slice(b"\r\n");
+    }
+    body.extend_from_slice(format!("--{BOUNDARY}--\r\n").as_bytes());
+
+    let request = Request::builder()
+        .method("POST")
+        .header(
+            header::CONTENT_TYPE,
+            format!("multipart/form-data; boundary={BOUNDARY}"),
+        )
+        .body(Body::from(body))
+        .expect("failed to build multipart request");
+    Multipart::from_request(request, &())
+        .await
+        .expect("failed to read multipart request")
+}
+
+/// Total `(tokens, units)` recorded in `usage_events` for one user and module.
+pub async fn recorded_usage(pool: &PgPool, user_id: Uuid, module_key: &str) -> (i64, i64) {
+    sqlx::query_as(
+        "SELECT COALESCE(SUM(tokens), 0)::BIGINT, COALESCE(SUM(units), 0)::BIGINT FROM usage_events WHERE user_id = $1 AND module_key = $2",
+    )
+    .bind(user_id)
+    .bind(module_key)
+    .fetch_one(pool)
+    .await
+    .expect("failed to read usage events")
+}
+
+/// A minimal PDF with `pages` empty A4 pages.
+pub fn blank_pdf(pages: usize) -> Vec<u8> {
+    let mut document = PdfDocument::with_version("1.5");
+    let pages_id = document.new_object_id();
+    let kids = (0..pages)
+        .map(|_| {
+            let mut page = Dictionary::new();
+            page.set("Type", Object::Name(b"Page".to_vec()));
+            page.set("Parent", Object::Reference(pages_id));
+            page.set(
+                "MediaBox",
+                Object::Array(vec![0.into(), 0.into(), 595.into(), 842.into()]),
+            );
+            Object::Reference(document.add_object(page))
+        })
+        .collect::<Vec<_>>();
+
+    let mut page_tree = Dictionary::new();
+    page_tree.set("Type", Object::Name(b"Pages".to_vec()));
+    page_tree.set("Count", Object::Integer(pages as i64));
+    page_tree.set("Kids", Object::Array(kids));
+    document
+        .objects
+        .insert(pages_id, Object::Dictionary(page_tree));
+
+    let mut catalog = Dictionary::new();
+    catalog.set("Type", Object::Name(b"Catalog".to_vec()));
+    catalog.set("Pages", Object::Reference(pages_id));
+    let catalog_id = document.add_object(catalog);
+    document.trailer.set("Root", Object::Reference(catalog_id));
+
+    let mut bytes = Vec::new();
+    document
+        .save_to(&mut bytes)
+        .expect("failed to write blank PDF");
+    bytes
+}
@@ -2,6 +2,7 @@ use std::collections::HashMap;
 
 use anyhow::{Context, Result, anyhow, bail};
 use chrono::{Duration, Utc};
+use serde::Serialize;
 use sqlx::{PgPool, Row};
 use tracing::error;
 use uuid::Uuid;
@@ -14,12 +15,85 @@ pub const MODULE_TRANSLATE_DOCX: &str = "translatedocx";
 pub const MODULE_GRADER: &str = "grader";
 pub const MODULE_REVIEWER: &str = "reviewer";
 pub const MODULE_INFO_EXTRACT: &str = "info_extract";
+pub const MODULE_TRANSCRIBE: &str = "transcribe";
+pub const MODULE_DOCX_TO_PDF: &str = "docx2pdf";
+
+/// How a module converts a finished job into `usage_events.units`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnitPolicy {
+    Documents,
+    Tokens,
+    Jobs,
+}
+
+impl UnitPolicy {
+    pub const ALL: [UnitPolicy; 3] = [UnitPolicy::Documents, UnitPolicy::Tokens, UnitPolicy::Jobs];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            UnitPolicy::Documents => "per_document",
+            UnitPolicy::Tokens => "per_token",
+            UnitPolicy::Jobs => "per_job",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "per_document" => Some(UnitPolicy::Documents),
+            "per_token" => Some(UnitPolicy::Tokens),
+            "per_job" => Some(UnitPolicy::Jobs),
+            _ => None,
+        }
+    }
+
+    pub fn label_zh(self) -> &'static str {
+        match self {
+            UnitPolicy::Documents => "按文档计",
+            UnitPolicy::Tokens => "按令牌计",
+            UnitPolicy::Jobs => "按任务计",
+        }
+    }
+
+    /// Unit name shown next to quotas for a module under this policy.
+    pub fn unit_label(self, descriptor: &ModuleDescriptor) -> &'static str {
+        match self {
+            UnitPolicy::Documents => descriptor.unit_label,
+            UnitPolicy::Tokens => "令牌数",
+            UnitPolicy::Jobs => "任务次数",
+        }
+    }
+
+    /// Counter word for one unit in quota messages, e.g. "剩余 3 次".
+    pub fn count_word(self) -> &'static str {
+        match self {
+            UnitPolicy::Documents => "份",
+            UnitPolicy::Tokens => "个令牌",
+            UnitPolicy::Jobs => "次",
+        }
+    }
+
+    /// Units charged for a job that processed `documents` files and consumed `tokens`.
+    pub fn units_for(self, documents: i64, tokens: i64) -> i64 {
+        match self {
+            UnitPolicy::Documents => documents.max(0),
+            UnitPolicy::Tokens => tokens.max(0),
+            UnitPolicy::Jobs => 1,
+        }
+    }
+}
 
 /// Human readable labels for known modules to drive forms and display.
 pub struct ModuleDescriptor {
     pub key: &'static str,
     pub label: &'static str,
     pub unit_label: &'static str,
+    pub default_unit_policy: UnitPolicy,
+}
+
+pub fn module_descriptor(module_key: &str) -> Option<&'static ModuleDescriptor> {
+    REGISTERED_MODULES
+        .iter()
+        .find(|descriptor| descriptor.key == module_key)
 }
 
 pub const REGISTERED_MODULES: &[ModuleDescriptor] = &[
@@ -27,26 +101,43 @@ pub const REGISTERED_MODULES: &[ModuleDescriptor] = &[
         key: MODULE_SUMMARIZER,
         label: "摘要与翻译",
         unit_label: "文档数量",
+        default_unit_policy: UnitPolicy::Documents,
     },
     ModuleDescriptor {
         key: MODULE_INFO_EXTRACT,
         label: "信息提取",
         unit_label: "文献数量",
+        default_unit_policy: UnitPolicy::Documents,
     },
     ModuleDescriptor {
         key: MODULE_TRANSLATE_DOCX,
         label: "DOCX 翻译",
         unit_label: "文档数量",
+        default_unit_policy: UnitPolicy::Documents,
     },
     ModuleDescriptor {
         key: MODULE_GRADER,
         label: "稿件评估",
         unit_label: "任务次数",
+        default_unit_policy: UnitPolicy::Jobs,
     },
     ModuleDescriptor {
         key: MODULE_REVIEWER,
         label: "审稿助手",
         unit_label: "稿件数量",
+        default_unit_policy: UnitPolicy::Jobs,
+    },
+    ModuleDescriptor {
+        key: MODULE_TRANSCRIBE,
+        label: "音频转写",
+        unit_label: "音频数量",
+        default_unit_policy: UnitPolicy::Documents,
+    },
+    ModuleDescriptor {
+        key: MODULE_DOCX_TO_PDF,
+        label: "DOCX 转 PDF",
+        unit_label: "文档数量",
+        default_unit_policy: UnitPolicy::Documents,
     },
 ];
 
@@ -83,6 +174,8 @@ pub enum UsageLimitErrorKind {
         limit: i64,
         used: i64,
         requested: i64,
+        /// `UnitPolicy::count_word` of the module's policy.
+        unit: &'static str,
     },
     Backend,
 }
@@ -92,29 +185,90 @@ pub struct UsageLimitError {
     pub kind: UsageLimitErrorKind,
 }
 
+/// Machine-readable numbers behind a quota rejection, returned next to the message.
+#[derive(Debug, Clone, Serialize)]
+pub struct QuotaDetail {
+    /// `tokens` for the group-wide token cap, `units` for the module's unit cap.
+    pub scope: &'static str,
+    pub used: i64,
+    pub limit: i64,
+    pub remaining: i64,
+    pub requested: i64,
+    pub unit: &'static str,
+}
+
 impl UsageLimitError {
     pub fn message(&self) -> String {
-        match &self.kind {
-            UsageLimitErrorKind::TokensExceeded { limit, used } => {
-                format!("近 7 日累计令牌数已达上限（{used}/{limit}）。",)
+        match self.detail() {
+            Some(QuotaDetail {
+                scope: "tokens",
+                used,
+                limit,
+                remaining,
+                ..
+            }) => format!(
+                "近 7 日令牌额度已用完：剩余 {remaining} 个令牌（已用 {used}，上限 {limit}）。"
+            ),
+            Some(QuotaDetail {
+                used,
+                limit,
+                remaining,
+                requested: 0,
+                unit,
+                ..
+            }) => {
+                format!("近 7 日额度已用完：剩余 {remaining} {unit}（已用 {used}，上限 {limit}）。")
             }
+            Some(QuotaDetail {
+                used,
+                limit,
+                remaining,
+                requested,
+                unit,
+                ..
+            }) => format!(
+                "近 7 日剩余 {remaining} {unit}，当前任务需要 {requested} {unit}（已用 {used}，上限 {limit}）。"
+            ),
+            None => "额度校验失败，请稍后再试。".to_string(),
+        }
+    }
+
+    /// Quota figures for the rejection, or `None` when the check itself failed.
+    pub fn detail(&self) -> Option<QuotaDetail> {
+        match self.kind {
+            UsageLimitErrorKind::TokensExceeded { limit, used } => Some(QuotaDetail {
+                scope: "tokens",
+                used,
+                limit,
+                remaining: remaining(limit, used),
+                requested: 0,
+                unit: UnitPolicy::Tokens.count_word(),
+            }),
             UsageLimitErrorKind::UnitsExceeded {
                 limit,
                 used,
                 requested,
-            } => format!(
-                "近 7 日累计任务数将超出上限（当前 {used}，本次 +{requested}，上限 {limit}）。",
-            ),
-            UsageLimitErrorKind::Backend => "额度校验失败，请稍后再试。".to_string(),
+                unit,
+            } => Some(QuotaDetail {
+                scope: "units",
+                used,
+                limit,
+                remaining: remaining(limit, used),
+                requested,
+                unit,
+            }),
+            UsageLimitErrorKind::Backend => None,
         }
     }
 }
 
+/// Checks quotas before a job starts. `documents` is the number of files in the job;
+/// it is converted to units with the module's `UnitPolicy`.
 pub async fn ensure_within_limits(
     pool: &PgPool,
     user_id: Uuid,
     module_key: &str,
-    units_to_add: i64,
+    documents: i64,
 ) -> Result<(), UsageLimitError> {
     let limits_row = match sqlx::query(
         "SELECT ug.token_limit, ugl.unit_limit \
@@ -207,6 +361,11 @@ pub async fn ensure_within_limits(
     }
 
     if let Some(limit) = unit_limit {
+        let policy = unit_policy(pool, module_key).await;
+        // Token counts are unknown before the job runs, so per-token quotas only
+        // block once the window is already exhausted.
+        let units_to_add = policy.units_for(documents, 0);
+
         let module_units = match sqlx::query(
             "SELECT COALESCE(SUM(units)::BIGINT, 0::BIGINT) AS units \
              FROM usage_events \
@@ -235,12 +394,13 @@ pub async fn ensure_within_limits(
             }
         };
 
-        if module_units + units_to_add > limit {
+        if exceeds_unit_limit(module_units, units_to_add, limit) {
             return Err(UsageLimitError {
                 kind: UsageLimitErrorKind::UnitsExceeded {
                     limit,
                     used: module_units,
                     requested: units_to_add,
+                    unit: policy.count_word(),
                 },
             });
         }
@@ -249,13 +409,27 @@ pub async fn ensure_within_limits(
     Ok(())
 }
 
+fn exceeds_unit_limit(used: i64, requested: i64, limit: i64) -> bool {
+    if requested == 0 {
+        used >= limit
+    } else {
+        used + requested > limit
+    }
+}
+
+/// Records a finished job. Units are derived from `documents` and `tokens`
+/// according to the module's `UnitPolicy`.
 pub async fn record_usage(
     pool: &PgPool,
     user_id: Uuid,
     module_key: &str,
     tokens: i64,
-    units: i64,
+    documents: i64,
 ) -> Result<()> {
+    let units = unit_policy(pool, module_key)
+        .await
+        .units_for(documents, tokens);
+
     sqlx::query(
         "INSERT INTO usage_events (id, user_id, module_key, tokens, units, occurred_at) VALUES ($1, $2, $3, $4, $5, NOW())",
     )
@@ -271,6 +445,73 @@ pub async fn record_usage(
     Ok(())
 }
 
+/// Returns the configured unit policy for a module, falling back to its default.
+pub async fn unit_policy(pool: &PgPool, module_key: &str) -> UnitPolicy {
+    let default = module_descriptor(module_key)
+        .map(|descriptor| descriptor.default_unit_policy)
+        .unwrap_or(UnitPolicy::Documents);
+
+    match sqlx::query_scalar::<_, String>(
+        "SELECT unit_policy FROM usage_unit_policies WHERE module_key = $1",
+    )
+    .bind(module_key)
+    .fetch_optional(pool)
+    .await
+    {
+        Ok(Some(value)) => UnitPolicy::parse(&value).unwrap_or(default),
+        Ok(None) => default,
+        Err(err) => {
+            error!(?err, module_key, "failed to load unit policy");
+            default
+        }
+    }
+}
+
+/// Loads the unit policy of every registered module.
+pub async fn unit_policies(pool: &PgPool) -> Result<HashMap<String, UnitPolicy>> {
+    let mut result: HashMap<String, UnitPolicy> = REGISTERED_MODULES
+        .iter()
+        .map(|descriptor| (descriptor.key.to_string(), descriptor.default_unit_policy))
+        .collect();
+
+    let rows = sqlx::query("SELECT module_key, unit_policy FROM usage_unit_policies")
+        .fetch_all(pool)
+        .await
+        .context("failed to fetch unit policies")?;
+
+    for row in rows {
+        let module: String = row.try_get("module_key")?;
+        let value: String = row.try_get("unit_policy")?;
+        if let (Some(entry), Some(policy)) = (result.get_mut(&module), UnitPolicy::parse(&value)) {
+            *entry = policy;
+        }
+    }
+
+    Ok(result)
+}
+
+pub async fn save_unit_policies(
+    pool: &PgPool,
+    policies: &HashMap<String, UnitPolicy>,
+) -> Result<()> {
+    let mut transaction = pool.begin().await?;
+
+    for (module, policy) in policies {
+        sqlx::query(
+            "INSERT INTO usage_unit_policies (module_key, unit_policy) VALUES ($1, $2) \
+             ON CONFLICT (module_key) DO UPDATE SET unit_policy = EXCLUDED.unit_policy, updated_at = NOW()",
+        )
+        .bind(module.as_str())
+        .bind(policy.as_str())
+        .execute(&mut *transaction)
+        .await?;
+    }
+
+    transaction.commit().await?;
+
+    Ok(())
+}
+
 pub async fn usage_for_users(
     pool: &PgPool,
     user_ids: &[Uuid],
@@ -407,6 +648,107 @@ pub async fn upsert_group_limits(
     Ok(())
 }
 
+/// One module's rolling-window usage next to the quota `ensure_within_limits` enforces.
+#[derive(Clone)]
+pub struct ModuleQuotaSummary {
+    pub descriptor: &'static ModuleDescriptor,
+    pub unit_policy: UnitPolicy,
+    pub tokens: i64,
+    pub units: i64,
+    pub unit_limit: Option<i64>,
+}
+
+impl ModuleQuotaSummary {
+    /// Units left in the window, or `None` when the module is unlimited.
+    pub fn units_remaining(&self) -> Option<i64> {
+        self.unit_limit.map(|limit| remaining(limit, self.units))
+    }
+}
+
+/// A user's usage over the rolling window with the limits of their usage group.
+#[derive(Clone)]
+pub struct UserQuotaSummary {
+    pub window_days: i64,
+    pub group_name: String,
+    pub token_limit: Option<i64>,
+    pub tokens_used: i64,
+    pub modules: Vec<ModuleQuotaSummary>,
+}
+
+impl UserQuotaSummary {
+    /// Tokens left in the window, or `None` when the group has no token cap.
+    pub fn tokens_remaining(&self) -> Option<i64> {
+        self.token_limit
+            .map(|limit| remaining(limit, self.tokens_used))
+    }
+}
+
+fn remaining(limit: i64, used: i64) -> i64 {
+    limit.saturating_sub(used).max(0)
+}
+
+/// Loads the same totals and limits `ensure_within_limits` checks, for every registered
+/// module. Returns `None` when the user does not exist.
+pub async fn quota_summary(pool: &PgPool, user_id: Uuid) -> Result<Option<UserQuotaSummary>> {
+    let Some(row) = sqlx::query(
+        "SELECT ug.id AS group_id, ug.name AS group_name \
+         FROM users u \
+         JOIN usage_groups ug ON ug.id = u.usage_group_id \
+         WHERE u.id = $1",
+    )
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await
+    .context("failed to load usage group for user")?
+    else {
+        return Ok(None);
+    };
+    let group_id: Uuid = row.try_get("group_id")?;
+    let group_name: String = row.try_get("group_name")?;
+
+    let usage = usage_for_users(pool, &[user_id])
+        .await?
+        .remove(&user_id)
+        .unwrap_or_default();
+    let limits = group_limits(pool, &[group_id])
+        .await?
+        .remove(&group_id)
+        .unwrap_or_default();
+    let policies = unit_policies(pool).await?;
+
+    let modules = REGISTERED_MODULES
+        .iter()
+        .map(|descriptor| {
+            let used = usage
+                .modules
+                .get(descriptor.key)
+                .copied()
+                .unwrap_or_default();
+            ModuleQuotaSummary {
+                descriptor,
+                unit_policy: policies
+                    .get(descriptor.key)
+                    .copied()
+                    .unwrap_or(descriptor.default_unit_policy),
+                tokens: used.tokens,
+                units: used.units,
+                unit_limit: limits
+                    .module_limits
+                    .get(descriptor.key)
+                    .and_then(|limit| limit.unit_limit),
+            }
+        })
+        .collect();
+
+    Ok(Some(UserQuotaSummary {
+        window_days: WINDOW_DAYS,
+        group_name,
+        token_limit: limits.token_limit,
+        tokens_used: usage.global_tokens,
+        modules,
+    }))
+}
+
 pub fn parse_optional_limit(input: Option<&str>) -> Result<Option<i64>> {
     match input.map(str::trim).filter(|v| !v.is_empty()) {
         Some(value) => {
@@ -419,3 +761,127 @@ pub fn parse_optional_limit(input: Option<&str>) -> Result<Option<i64>> {
         None => Ok(None),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn switching_policy_changes_units_for_same_job() {
+        let (documents, tokens) = (3, 12_500);
+
+        assert_eq!(UnitPolicy::Documents.units_for(documents, tokens), 3);
+        assert_eq!(UnitPolicy::Tokens.units_for(documents, tokens), 12_500);
+        assert_eq!(UnitPolicy::Jobs.units_for(documents, tokens), 1);
+    }
+
+    #[test]
+    fn limit_messages_state_remaining_quota() {
+        let units = UsageLimitError {
+            kind: UsageLimitErrorKind::UnitsExceeded {
+                limit: 20,
+                used: 17,
+                requested: 10,
+                unit: UnitPolicy::Jobs.count_word(),
+            },
+        };
+        assert_eq!(
+            units.message(),
+            "近 7 日剩余 3 次，当前任务需要 10 次（已用 17，上限 20）。"
+        );
+        let detail = units.detail().unwrap();
+        assert_eq!((detail.scope, detail.remaining), ("units", 3));
+
+        let exhausted = UsageLimitError {
+            kind: UsageLimitErrorKind::UnitsExceeded {
+                limit: 5_000,
+                used: 5_200,
+                requested: 0,
+                unit: UnitPolicy::Tokens.count_word(),
+            },
+        };
+        assert_eq!(
+            exhausted.message(),
+            "近 7 日额度已用完：剩余 0 个令牌（已用 5200，上限 5000）。"
+        );
+
+        let tokens = UsageLimitError {
+            kind: UsageLimitErrorKind::TokensExceeded {
+                limit: 1_000,
+                used: 1_000,
+            },
+        };
+        assert!(tokens.message().contains("剩余 0 个令牌"));
+
+        let backend = UsageLimitError {
+            kind: UsageLimitErrorKind::Backend,
+        };
+        assert!(backend.detail().is_none());
+    }
+
+    #[sqlx::test]
+    #[ignore = "needs DATABASE_URL (disposable Postgres)"]
+    async fn rejection_carries_remaining_units(pool: PgPool) {
+        let user_id = crate::test_support::create_user(&pool).await;
+        let group_id: Uuid = sqlx::query_scalar("SELECT usage_group_id FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let allocations = [(MODULE_SUMMARIZER.to_string(), Some(5))]
+            .into_iter()
+            .collect();
+        upsert_group_limits(&pool, group_id, None, &allocations)
+            .await
+            .unwrap();
+        record_usage(&pool, user_id, MODULE_SUMMARIZER, 0, 2)
+            .await
+            .unwrap();
+
+        ensure_within_limits(&pool, user_id, MODULE_SUMMARIZER, 3)
+            .await
+            .unwrap();
+        let err = ensure_within_limits(&pool, user_id, MODULE_SUMMARIZER, 4)
+            .await
+            .unwrap_err();
+        let detail = err.detail().unwrap();
+        assert_eq!(
+            (
+                detail.used,
+                detail.limit,
+                detail.remaining,
+                detail.requested
+            ),
+            (2, 5, 3, 4)
+        );
+        assert_eq!(
+            err.message(),
+            "近 7 日剩余 3 份，当前任务需要 4 份（已用 2，上限 5）。"
+        );
+    }
+
+    #[test]
+    fn remaining_quota_never_goes_negative() {
+        assert_eq!(remaining(100, 30), 70);
+        assert_eq!(remaining(100, 130), 0);
+        assert_eq!(remaining(i64::MAX, -1), i64::MAX);
+    }
+
+    #[test]
+    fn per_token_precheck_blocks_only_exhausted_quota() {
+        let requested = UnitPolicy::Tokens.units_for(2, 0);
+        assert!(!exceeds_unit_limit(9_999, requested, 10_000));
+        assert!(exceeds_unit_limit(10_000, requested, 10_000));
+
+        let requested = UnitPolicy::Documents.units_for(2, 0);
+        assert!(exceeds_unit_limit(9, requested, 10));
+    }
+
+    #[test]
+    fn unit_policy_round_trips() {
+        for policy in UnitPolicy::ALL {
+            assert_eq!(UnitPolicy::parse(policy.as_str()), Some(policy));
+        }
+        assert_eq!(UnitPolicy::parse("per_page"), None);
+    }
+}
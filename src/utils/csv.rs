@@ -0,0 +1,107 @@
+/// Byte order mark written at the start of CSV exports so Excel detects UTF-8.
+pub const UTF8_BOM: &str = "\u{FEFF}";
+
+/// Appends one CSV row, quoting cells that contain commas, quotes or line breaks.
+pub fn push_csv_row<'a>(output: &mut String, cells: impl Iterator<Item = &'a str>) {
+    for (idx, cell) in cells.enumerate() {
+        if idx > 0 {
+            output.push(',');
+        }
+        if cell.contains([',', '"', '\n', '\r']) {
+            output.push('"');
+            output.push_str(&cell.replace('"', "\"\""));
+            output.push('"');
+        } else {
+            output.push_str(cell);
+        }
+    }
+    output.push_str("\r\n");
+}
+
+/// Splits CSV text into rows of cells (RFC 4180: quoted cells may hold commas, `""` and line
+/// breaks). A leading BOM and blank lines are ignored. Returns the 1-based line of an
+/// unterminated quote as the error.
+pub fn parse_csv(input: &str) -> Result<Vec<Vec<String>>, usize> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut cell = String::new();
+    let mut in_quotes = false;
+    let mut quote_line = 0;
+    let mut line = 1;
+    let mut chars = input.trim_start_matches(UTF8_BOM).chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        if in_quotes {
+            match ch {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    cell.push('"');
+                }
+                '"' => in_quotes = false,
+                '\n' => {
+                    line += 1;
+                    cell.push(ch);
+                }
+                _ => cell.push(ch),
+            }
+            continue;
+        }
+
+        match ch {
+            '"' if cell.is_empty() => {
+                in_quotes = true;
+                quote_line = line;
+            }
+            ',' => row.push(std::mem::take(&mut cell)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\r' | '\n' => {
+                line += 1;
+                row.push(std::mem::take(&mut cell));
+                push_row(&mut rows, std::mem::take(&mut row));
+            }
+            _ => cell.push(ch),
+        }
+    }
+
+    if in_quotes {
+        return Err(quote_line);
+    }
+    if !cell.is_empty() || !row.is_empty() {
+        row.push(cell);
+        push_row(&mut rows, row);
+    }
+    Ok(rows)
+}
+
+fn push_row(rows: &mut Vec<Vec<String>>, row: Vec<String>) {
+    if row.iter().any(|cell| !cell.trim().is_empty()) {
+        rows.push(row);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_round_trip_through_writer_and_parser() {
+        let cells = ["plain", "with, comma", "say \"hi\"", "two\nlines", ""];
+        let mut output = String::from(UTF8_BOM);
+        push_csv_row(&mut output, ["a", "b", "c", "d", "e"].into_iter());
+        push_csv_row(&mut output, cells.into_iter());
+
+        let rows = parse_csv(&output).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0], ["a", "b", "c", "d", "e"]);
+        assert_eq!(rows[1], cells);
+    }
+
+    #[test]
+    fn blank_lines_are_skipped_and_open_quotes_rejected() {
+        assert_eq!(
+            parse_csv("x,y\n\n,\nz,w").unwrap(),
+            [vec!["x", "y"], vec!["z", "w"]]
+        );
+        assert_eq!(parse_csv("x,y\n\"open,z\nmore"), Err(2));
+    }
+}
@@ -0,0 +1,112 @@
+use std::{env, path::Path};
+
+use anyhow::{Context, Result};
+use uuid::Uuid;
+
+use crate::utils::{docx_to_pdf::convert_docx_to_pdf, pdf_ocr::render_first_page_png};
+
+/// Longest edge, in pixels, of rendered preview thumbnails.
+pub const PREVIEW_MAX_DIMENSION: u32 = 320;
+
+pub enum DocumentPreview {
+    Png(Vec<u8>),
+    /// The source file no longer exists (or never did).
+    Missing,
+    /// Only PDF and DOCX uploads can be rasterized.
+    Unsupported,
+}
+
+/// Whether `filename` has an extension `load_preview` can render.
+pub fn supports_preview(filename: &str) -> bool {
+    preview_kind(Path::new(filename)).is_some()
+}
+
+#[derive(Clone, Copy)]
+enum PreviewKind {
+    Pdf,
+    Docx,
+}
+
+fn preview_kind(path: &Path) -> Option<PreviewKind> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    match extension.as_str() {
+        "pdf" => Some(PreviewKind::Pdf),
+        "docx" => Some(PreviewKind::Docx),
+        _ => None,
+    }
+}
+
+/// Returns a PNG of the first page of `source`, reusing `cache_path` when a previous call
+/// already rendered it. DOCX files go through LibreOffice first, in a scratch directory that is
+/// removed afterwards.
+pub async fn load_preview(source: &Path, cache_path: &Path) -> Result<DocumentPreview> {
+    if let Ok(bytes) = tokio::fs::read(cache_path).await {
+        return Ok(DocumentPreview::Png(bytes));
+    }
+    if !tokio::fs::try_exists(source).await.unwrap_or(false) {
+        return Ok(DocumentPreview::Missing);
+    }
+
+    match preview_kind(source) {
+        Some(PreviewKind::Pdf) => {
+            render_first_page_png(source, cache_path, PREVIEW_MAX_DIMENSION).await?;
+        }
+        Some(PreviewKind::Docx) => {
+            // Converting next to the upload would let concurrent requests for the same
+            // document overwrite and delete each other's intermediate PDF.
+            let scratch = env::temp_dir().join(format!("preview-{}", Uuid::new_v4()));
+            let result = render_docx_preview(source, &scratch, cache_path).await;
+            let _ = tokio::fs::remove_dir_all(&scratch).await;
+            result?;
+        }
+        None => return Ok(DocumentPreview::Unsupported),
+    }
+
+    let bytes = tokio::fs::read(cache_path)
+        .await
+        .with_context(|| format!("failed to read preview {}", cache_path.display()))?;
+    Ok(DocumentPreview::Png(bytes))
+}
+
+async fn render_docx_preview(source: &Path, scratch: &Path, cache_path: &Path) -> Result<()> {
+    tokio::fs::create_dir_all(scratch)
+        .await
+        .with_context(|| format!("failed to create preview dir {}", scratch.display()))?;
+    let copy = scratch.join("source.docx");
+    tokio::fs::copy(source, &copy)
+        .await
+        .with_context(|| format!("failed to copy {} for preview", source.display()))?;
+
+    let pdf_path = convert_docx_to_pdf(&copy).await?;
+    render_first_page_png(&pdf_path, cache_path, PREVIEW_MAX_DIMENSION).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn serves_cached_preview_and_reports_missing_sources() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let source = dir.path().join("paper.pdf");
+        let cache = dir.path().join("preview_paper.png");
+
+        let missing = load_preview(&source, &cache).await.expect("missing source");
+        assert!(matches!(missing, DocumentPreview::Missing));
+
+        std::fs::write(&source, b"%PDF-1.4").unwrap();
+        std::fs::write(&cache, b"\x89PNG cached").unwrap();
+        match load_preview(&source, &cache).await.expect("cached preview") {
+            DocumentPreview::Png(bytes) => assert_eq!(bytes, b"\x89PNG cached"),
+            _ => panic!("expected cached PNG"),
+        }
+
+        let text = dir.path().join("notes.txt");
+        std::fs::write(&text, "plain").unwrap();
+        let unsupported = load_preview(&text, &dir.path().join("preview_notes.png"))
+            .await
+            .expect("unsupported source");
+        assert!(matches!(unsupported, DocumentPreview::Unsupported));
+        assert!(supports_preview("Scan.PDF") && !supports_preview("notes.txt"));
+    }
+}
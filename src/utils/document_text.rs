@@ -0,0 +1,196 @@
+use std::{fs, io::Read, path::Path};
+
+use anyhow::{Context, Result, anyhow};
+use pdf_extract::{Document as PdfDocument, extract_text as extract_pdf_text};
+use quick_xml::{Reader as XmlReader, events::Event};
+use tracing::warn;
+use zip::ZipArchive;
+
+use crate::utils::pdf_ocr;
+
+/// File extensions accepted by `read_document_text`.
+pub const SUPPORTED_EXTENSIONS: &[&str] = &["pdf", "docx", "txt"];
+
+/// Extracts DOCX body text, separating paragraphs with blank lines.
+pub fn extract_docx_text(path: &Path) -> Result<String> {
+    let file = fs::File::open(path)
+        .with_context(|| format!("failed to open DOCX file {}", path.display()))?;
+    let mut archive = ZipArchive::new(file)
+        .with_context(|| format!("failed to open DOCX archive {}", path.display()))?;
+
+    let mut document = archive
+        .by_name("word/document.xml")
+        .with_context(|| format!("missing word/document.xml in {}", path.display()))?;
+
+    let mut xml = String::new();
+    document
+        .read_to_string(&mut xml)
+        .with_context(|| format!("failed to read DOCX XML for {}", path.display()))?;
+
+    let mut reader = XmlReader::from_str(&xml);
+    let mut buf = Vec::new();
+    let mut output = String::new();
+    let mut in_text_node = false;
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(ref e)) => match e.name().as_ref() {
+                b"w:p" if !output.is_empty() => output.push_str("\n\n"),
+                b"w:tab" => output.push('\t'),
+                b"w:br" => output.push('\n'),
+                b"w:t" => in_text_node = true,
+                _ => {}
+            },
+            Ok(Event::Empty(ref e)) => match e.name().as_ref() {
+                b"w:p" if !output.is_empty() => output.push_str("\n\n"),
+                b"w:tab" => output.push('\t'),
+                b"w:br" => output.push('\n'),
+                _ => {}
+            },
+            Ok(Event::Text(e)) if in_text_node => {
+                let value = e.unescape().map_err(|err| anyhow!(err))?.into_owned();
+                output.push_str(&value);
+            }
+            Ok(Event::End(ref e)) if e.name().as_ref() == b"w:t" => in_text_node = false,
+            Ok(Event::Eof) => break,
+            Err(err) => return Err(anyhow!("failed to parse DOCX XML: {}", err)),
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok(output.trim().to_string())
+}
+
+/// Text read from an uploaded document, plus how it was obtained.
+#[derive(Debug, Clone)]
+pub struct DocumentText {
+    pub text: String,
+    /// True when the PDF text layer was too sparse and the text came from OCR.
+    pub ocr_used: bool,
+}
+
+/// Reads plain text from a PDF, DOCX, or TXT file, dispatching on the extension.
+pub fn read_document_text(path: &Path) -> Result<String> {
+    load_document_text(path).map(|document| document.text)
+}
+
+/// Like `read_document_text`, but reports whether the OCR fallback for image-only PDFs
+/// (see `pdf_ocr`) supplied the text.
+pub fn load_document_text(path: &Path) -> Result<DocumentText> {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+
+    let text = match extension.as_str() {
+        "pdf" => return read_pdf_text(path),
+        "docx" => extract_docx_text(path),
+        "txt" => fs::read_to_string(path)
+            .with_context(|| format!("failed to read text file {}", path.display())),
+        other => Err(anyhow!("Unsupported file type: {}", other)),
+    }?;
+
+    Ok(DocumentText {
+        text: text.trim().to_string(),
+        ocr_used: false,
+    })
+}
+
+/// Number of pages in a PDF, read from its page tree without extracting any text.
+pub fn pdf_page_count(path: &Path) -> Result<usize> {
+    let document = PdfDocument::load(path)
+        .with_context(|| format!("failed to open PDF {}", path.display()))?;
+    Ok(document.get_pages().len())
+}
+
+fn read_pdf_text(path: &Path) -> Result<DocumentText> {
+    let extracted = extract_pdf_text(path)
+        .with_context(|| format!("failed to extract PDF text from {}", path.display()))
+        .map(|content| content.trim().to_string());
+
+    let sparse = match &extracted {
+        Ok(text) => pdf_ocr::needs_ocr(text),
+        Err(_) => true,
+    };
+    if sparse && pdf_ocr::ocr_enabled() {
+        match pdf_ocr::ocr_pdf(path) {
+            Ok(text) if !text.trim().is_empty() => {
+                return Ok(DocumentText {
+                    text: text.trim().to_string(),
+                    ocr_used: true,
+                });
+            }
+            Ok(_) => warn!(file = %path.display(), "OCR produced no text"),
+            Err(err) => warn!(?err, file = %path.display(), "OCR fallback failed"),
+        }
+    }
+
+    extracted.map(|text| DocumentText {
+        text,
+        ocr_used: false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::tempdir;
+    use zip::write::SimpleFileOptions;
+
+    #[test]
+    fn extract_docx_text_returns_plain_text() {
+        let dir = tempdir().expect("temp dir");
+        let docx_path = dir.path().join("sample.docx");
+        let file = fs::File::create(&docx_path).expect("create docx");
+        let mut zip = zip::ZipWriter::new(file);
+
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+  <w:body>
+    <w:p><w:r><w:t>Hello</w:t></w:r></w:p>
+    <w:p><w:r><w:t>World</w:t></w:r></w:p>
+  </w:body>
+</w:document>"#;
+
+        zip.start_file("word/document.xml", SimpleFileOptions::default())
+            .expect("zip start file");
+        zip.write_all(xml.as_bytes()).expect("write xml");
+        zip.finish().expect("finish zip");
+
+        let extracted = extract_docx_text(&docx_path).expect("extract docx");
+        assert_eq!(extracted, "Hello\n\nWorld");
+    }
+
+    #[test]
+    fn sparse_pdf_text_triggers_ocr_threshold() {
+        assert!(pdf_ocr::needs_ocr(""));
+        assert!(pdf_ocr::needs_ocr(" 1 \n\n 2 \x0c"));
+        assert!(!pdf_ocr::needs_ocr(
+            &"字".repeat(pdf_ocr::MIN_PDF_TEXT_CHARS)
+        ));
+    }
+
+    #[test]
+    fn pdf_page_count_reads_page_tree() {
+        let dir = tempdir().expect("temp dir");
+        let path = dir.path().join("blank.pdf");
+        fs::write(&path, crate::test_support::blank_pdf(3)).expect("write pdf");
+
+        assert_eq!(pdf_page_count(&path).expect("count pages"), 3);
+        assert!(pdf_page_count(&dir.path().join("missing.pdf")).is_err());
+    }
+
+    #[test]
+    fn non_pdf_documents_never_use_ocr() {
+        let dir = tempdir().expect("temp dir");
+        let path = dir.path().join("notes.txt");
+        fs::write(&path, "  short  ").expect("write txt");
+
+        let document = load_document_text(&path).expect("read txt");
+        assert_eq!(document.text, "short");
+        assert!(!document.ocr_used);
+    }
+}
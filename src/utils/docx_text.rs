@@ -0,0 +1,108 @@
+use std::{fs, io::Cursor, path::Path};
+
+use anyhow::{Context, Result};
+use docx_rs::{BreakType, Docx, Paragraph, Run};
+
+/// Heading font size in half-points (14pt).
+const HEADING_SIZE: usize = 28;
+
+/// A block of LLM output rendered into a DOCX: an optional bold heading followed by the body,
+/// split into paragraphs on blank lines.
+pub struct DocxSection<'a> {
+    pub heading: Option<&'a str>,
+    pub body: &'a str,
+}
+
+/// Builds a DOCX from plain-text sections. Single newlines inside a paragraph become line breaks.
+pub fn build_text_docx(sections: &[DocxSection<'_>]) -> Docx {
+    let mut docx = Docx::new();
+    for section in sections {
+        if let Some(heading) = section.heading {
+            docx = docx.add_paragraph(
+                Paragraph::new().add_run(Run::new().add_text(heading).bold().size(HEADING_SIZE)),
+            );
+        }
+        for block in section.body.split("\n\n") {
+            let block = block.trim_matches('\n');
+            if block.trim().is_empty() {
+                continue;
+            }
+            docx = docx.add_paragraph(text_paragraph(block));
+        }
+    }
+    docx
+}
+
+fn text_paragraph(text: &str) -> Paragraph {
+    let mut paragraph = Paragraph::new();
+    for (idx, line) in text.split('\n').enumerate() {
+        if idx > 0 {
+            paragraph = paragraph.add_run(Run::new().add_break(BreakType::TextWrapping));
+        }
+        paragraph = paragraph.add_run(Run::new().add_text(line));
+    }
+    paragraph
+}
+
+/// Packs the sections into an in-memory DOCX, for responses generated on request.
+pub fn text_docx_bytes(sections: &[DocxSection<'_>]) -> Result<Vec<u8>> {
+    let mut buffer = Cursor::new(Vec::new());
+    build_text_docx(sections)
+        .build()
+        .pack(&mut buffer)
+        .context("failed to pack DOCX")?;
+    Ok(buffer.into_inner())
+}
+
+/// Writes `text` as an untitled DOCX at `output_path`.
+pub fn write_text_docx(text: &str, output_path: &Path) -> Result<()> {
+    let file = fs::File::create(output_path)
+        .with_context(|| format!("failed to create DOCX at {}", output_path.display()))?;
+    build_text_docx(&[DocxSection {
+        heading: None,
+        body: text,
+    }])
+    .build()
+    .pack(file)
+    .with_context(|| format!("failed to pack DOCX to {}", output_path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use super::*;
+
+    #[test]
+    fn sections_render_headings_and_paragraphs() {
+        let bytes = text_docx_bytes(&[
+            DocxSection {
+                heading: Some("Document 1 — a.pdf"),
+                body: "First paragraph\nsecond line\n\nSecond paragraph",
+            },
+            DocxSection {
+                heading: Some("Document 2 — b.pdf"),
+                body: "Only paragraph",
+            },
+        ])
+        .expect("docx bytes");
+
+        let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).expect("zip archive");
+        let mut xml = String::new();
+        archive
+            .by_name("word/document.xml")
+            .expect("document part")
+            .read_to_string(&mut xml)
+            .expect("read document part");
+
+        assert!(xml.contains("Document 1 — a.pdf"));
+        assert!(xml.contains("Document 2 — b.pdf"));
+        assert!(xml.contains("second line"));
+        assert_eq!(
+            xml.matches("<w:p>").count() + xml.matches("<w:p ").count(),
+            5
+        );
+        assert!(xml.find("Second paragraph") < xml.find("Document 2"));
+    }
+}
@@ -1,40 +1,35 @@
 use anyhow::{Context, Result, anyhow};
 use std::path::{Path, PathBuf};
-use std::process::Command;
-use tokio::task;
+use std::sync::LazyLock;
+use std::time::Duration;
+use tokio::{process::Command, sync::Semaphore, time};
+use uuid::Uuid;
+
+/// LibreOffice is memory hungry and single-threaded per process, so only a couple of
+/// conversions run at once; the rest wait for a slot.
+const MAX_CONCURRENT_CONVERSIONS: usize = 2;
+/// A conversion that has not finished by then is assumed hung and its process is killed.
+const CONVERSION_TIMEOUT: Duration = Duration::from_secs(180);
+
+static CONVERSION_SLOTS: LazyLock<Semaphore> =
+    LazyLock::new(|| Semaphore::new(MAX_CONCURRENT_CONVERSIONS));
 
 pub async fn convert_docx_to_pdf(docx_path: &Path) -> Result<PathBuf> {
     let output_dir = docx_path
         .parent()
         .ok_or_else(|| anyhow!("Invalid DOCX path: missing parent directory"))?;
 
-    let docx_path_owned = docx_path.to_path_buf();
-    let output_dir_owned = output_dir.to_path_buf();
-
-    let command_result = task::spawn_blocking(move || {
-        Command::new("libreoffice")
-            .args([
-                "--headless",
-                "--convert-to",
-                "pdf:writer_pdf_Export",
-                "--outdir",
-                &output_dir_owned.to_string_lossy(),
-                &docx_path_owned.to_string_lossy(),
-            ])
-            .output()
-    })
-    .await
-    .context("LibreOffice conversion task failed")?;
-
-    let output = command_result.context("Failed to execute libreoffice command")?;
+    let _slot = CONVERSION_SLOTS
+        .acquire()
+        .await
+        .context("LibreOffice conversion queue closed")?;
 
-    if !output.status.success() {
-        return Err(anyhow!(
-            "LibreOffice conversion failed with status {:?}: {}",
-            output.status.code(),
-            String::from_utf8_lossy(&output.stderr)
-        ));
-    }
+    // Each run gets its own profile directory; concurrent runs sharing the default profile
+    // trip over its lock file and fail or hang.
+    let profile_dir = std::env::temp_dir().join(format!("lo-profile-{}", Uuid::new_v4()));
+    let result = run_libreoffice(docx_path, output_dir, &profile_dir).await;
+    let _ = tokio::fs::remove_dir_all(&profile_dir).await;
+    result?;
 
     let pdf_filename = format!(
         "{}.pdf",
@@ -55,3 +50,42 @@ pub async fn convert_docx_to_pdf(docx_path: &Path) -> Result<PathBuf> {
 
     Ok(pdf_path)
 }
+
+async fn run_libreoffice(docx_path: &Path, output_dir: &Path, profile_dir: &Path) -> Result<()> {
+    let child = Command::new("libreoffice")
+        .arg(format!(
+            "-env:UserInstallation=file://{}",
+            profile_dir.display()
+        ))
+        .args([
+            "--headless",
+            "--convert-to",
+            "pdf:writer_pdf_Export",
+            "--outdir",
+        ])
+        .arg(output_dir)
+        .arg(docx_path)
+        .kill_on_drop(true)
+        .output();
+
+    // Dropping the timed-out future drops the child, which kills it.
+    let output = time::timeout(CONVERSION_TIMEOUT, child)
+        .await
+        .map_err(|_| {
+            anyhow!(
+                "LibreOffice conversion timed out after {} seconds",
+                CONVERSION_TIMEOUT.as_secs()
+            )
+        })?
+        .context("Failed to execute libreoffice command")?;
+
+    if !output.status.success() {
+        return Err(anyhow!(
+            "LibreOffice conversion failed with status {:?}: {}",
+            output.status.code(),
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    Ok(())
+}
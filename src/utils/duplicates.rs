@@ -0,0 +1,268 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::{Arc, Mutex},
+};
+
+use tokio::sync::Notify;
+
+/// How closely a document matched one seen earlier in the same batch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateKind {
+    /// Extracted text is identical.
+    Exact,
+    /// Text differs only in whitespace, punctuation, or letter case.
+    Near,
+}
+
+/// The earlier document a duplicate matched, identified by its batch ordinal and filename.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateMatch {
+    pub kind: DuplicateKind,
+    pub ordinal: usize,
+    pub filename: String,
+}
+
+impl DuplicateMatch {
+    /// Chinese description for status details, e.g. "与第 2 个文件《a.pdf》内容完全相同".
+    pub fn describe_zh(&self) -> String {
+        let relation = match self.kind {
+            DuplicateKind::Exact => "内容完全相同",
+            DuplicateKind::Near => "内容高度相似",
+        };
+        format!(
+            "与第 {} 个文件《{}》{}",
+            self.ordinal + 1,
+            self.filename,
+            relation
+        )
+    }
+}
+
+/// Documents whose normalised text is shorter than this are never fingerprinted: scans without
+/// a text layer (or with only page numbers) would otherwise all match each other.
+const MIN_FINGERPRINT_CHARS: usize = 50;
+
+/// Batch-scoped registry of extracted-text fingerprints, shared between a job's concurrent
+/// document tasks. Among documents with the same fingerprint the lowest ordinal owns it and
+/// the others are reported as its duplicates, whatever order the tasks finish extraction in.
+pub struct DuplicateRegistry {
+    seen: Mutex<SeenFingerprints>,
+    settled: Notify,
+}
+
+#[derive(Default)]
+struct SeenFingerprints {
+    /// Ordinals of this run that have neither registered nor given up yet.
+    pending: BTreeSet<usize>,
+    exact: HashMap<u64, (usize, String)>,
+    normalized: HashMap<u64, (usize, String)>,
+    matches: usize,
+}
+
+impl DuplicateRegistry {
+    /// A registry for a run over the documents at `ordinals`; each of them must take its
+    /// `DuplicateSlot` before registering.
+    pub fn new(ordinals: impl IntoIterator<Item = usize>) -> Self {
+        Self {
+            seen: Mutex::new(SeenFingerprints {
+                pending: ordinals.into_iter().collect(),
+                ..SeenFingerprints::default()
+            }),
+            settled: Notify::new(),
+        }
+    }
+
+    /// The registration handle for the document at `ordinal`. Dropping it without registering
+    /// (a failed read, a cancelled task) releases later documents waiting on it.
+    pub fn slot(self: &Arc<Self>, ordinal: usize) -> DuplicateSlot {
+        DuplicateSlot {
+            registry: Arc::clone(self),
+            ordinal,
+            settled: false,
+        }
+    }
+
+    /// Number of registrations so far that matched an earlier document.
+    pub fn duplicate_count(&self) -> usize {
+        self.lock().matches
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, SeenFingerprints> {
+        self.seen
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn settle(&self, ordinal: usize) {
+        self.lock().pending.remove(&ordinal);
+        self.settled.notify_waiters();
+    }
+}
+
+/// One document's turn in a `DuplicateRegistry`.
+pub struct DuplicateSlot {
+    registry: Arc<DuplicateRegistry>,
+    ordinal: usize,
+    settled: bool,
+}
+
+impl DuplicateSlot {
+    /// Records `text` and returns the lowest-ordinal document with the same fingerprint, if
+    /// that is an earlier one. Waits until every lower ordinal has registered or dropped its
+    /// slot, so callers must not hold a concurrency permit while awaiting this.
+    pub async fn register(mut self, filename: &str, text: &str) -> Option<DuplicateMatch> {
+        let registry = Arc::clone(&self.registry);
+        let ordinal = self.ordinal;
+        let normalized = normalize_text(text);
+        let hashes = (normalized.chars().count() >= MIN_FINGERPRINT_CHARS)
+            .then(|| (text_hash(text.trim()), text_hash(&normalized)));
+
+        if let Some((exact, normalized)) = hashes {
+            let mut guard = registry.lock();
+            let seen = &mut *guard;
+            for (map, hash) in [(&mut seen.exact, exact), (&mut seen.normalized, normalized)] {
+                let owner = map
+                    .entry(hash)
+                    .or_insert_with(|| (ordinal, filename.to_string()));
+                if ordinal < owner.0 {
+                    *owner = (ordinal, filename.to_string());
+                }
+            }
+        }
+        self.settled = true;
+        registry.settle(ordinal);
+
+        let (exact, normalized) = hashes?;
+        loop {
+            let notified = registry.settled.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+            {
+                let mut seen = registry.lock();
+                if seen.pending.range(..ordinal).next().is_none() {
+                    let found = [
+                        (DuplicateKind::Exact, seen.exact.get(&exact)),
+                        (DuplicateKind::Near, seen.normalized.get(&normalized)),
+                    ]
+                    .into_iter()
+                    .find_map(|(kind, owner)| {
+                        owner
+                            .filter(|(owner, _)| *owner < ordinal)
+                            .map(|(owner, filename)| (kind, *owner, filename.clone()))
+                    });
+                    let (kind, ordinal, filename) = found?;
+                    seen.matches += 1;
+                    return Some(DuplicateMatch {
+                        kind,
+                        ordinal,
+                        filename,
+                    });
+                }
+            }
+            notified.await;
+        }
+    }
+}
+
+impl Drop for DuplicateSlot {
+    fn drop(&mut self) {
+        if !self.settled {
+            self.registry.settle(self.ordinal);
+        }
+    }
+}
+
+/// Lowercased letters and digits only, so re-exports with different spacing, line wrapping,
+/// or punctuation normalise to the same string.
+fn normalize_text(text: &str) -> String {
+    text.chars()
+        .filter(|ch| ch.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn text_hash(text: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAPER: &str =
+        "Deep learning for protein folding.\n\nWe present a method that predicts structures.";
+
+    #[tokio::test]
+    async fn identical_content_is_flagged_as_duplicate() {
+        let registry = Arc::new(DuplicateRegistry::new(0..4));
+
+        assert_eq!(registry.slot(0).register("paper.pdf", PAPER).await, None);
+        let duplicate = registry
+            .slot(1)
+            .register("paper (1).pdf", PAPER)
+            .await
+            .expect("duplicate detected");
+        assert_eq!(duplicate.kind, DuplicateKind::Exact);
+        assert_eq!(duplicate.ordinal, 0);
+        assert_eq!(duplicate.filename, "paper.pdf");
+
+        let near = registry
+            .slot(2)
+            .register(
+                "paper-copy.docx",
+                "DEEP learning for protein-folding. We present a method that predicts structures",
+            )
+            .await
+            .expect("near duplicate detected");
+        assert_eq!(near.kind, DuplicateKind::Near);
+        assert_eq!(near.describe_zh(), "与第 1 个文件《paper.pdf》内容高度相似");
+
+        assert_eq!(
+            registry
+                .slot(3)
+                .register(
+                    "other.pdf",
+                    "A different paper about soil carbon and grassland restoration."
+                )
+                .await,
+            None
+        );
+        assert_eq!(registry.duplicate_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn lowest_ordinal_owns_the_text_whatever_the_finishing_order() {
+        let registry = Arc::new(DuplicateRegistry::new(0..3));
+        let (low, high) = (registry.slot(0), registry.slot(2));
+        let failed = registry.slot(1);
+
+        // The later upload finishes extraction first and has to wait for the earlier ones.
+        let later = tokio::spawn(async move { high.register("copy.pdf", PAPER).await });
+        tokio::task::yield_now().await;
+        assert!(!later.is_finished());
+        drop(failed);
+        assert_eq!(low.register("paper.pdf", PAPER).await, None);
+
+        let duplicate = later.await.unwrap().expect("later upload is the duplicate");
+        assert_eq!(
+            (duplicate.ordinal, duplicate.filename.as_str()),
+            (0, "paper.pdf")
+        );
+        assert_eq!(registry.duplicate_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn texts_without_enough_content_are_never_duplicates() {
+        let registry = Arc::new(DuplicateRegistry::new(0..3));
+        assert_eq!(registry.slot(0).register("scan-a.pdf", "").await, None);
+        assert_eq!(
+            registry.slot(1).register("scan-b.pdf", " \n\t ").await,
+            None
+        );
+        assert_eq!(registry.slot(2).register("scan-c.pdf", "1 2 3").await, None);
+        assert_eq!(registry.duplicate_count(), 0);
+    }
+}
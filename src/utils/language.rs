@@ -0,0 +1,50 @@
+/// Coarse language classification used to pick a translation direction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DetectedLanguage {
+    Chinese,
+    English,
+    Unknown,
+}
+
+/// Ratio of CJK characters to Latin words above which text counts as Chinese.
+/// One Latin word carries roughly as much content as 1.5 Chinese characters.
+const CJK_PER_LATIN_WORD: f64 = 1.5;
+
+/// Classifies `text` by comparing CJK ideographs with Latin-script words.
+///
+/// Mixed academic text (Chinese prose with English terms or citations) is
+/// classified by whichever script dominates after weighting.
+pub fn detect_language(text: &str) -> DetectedLanguage {
+    let mut cjk_chars = 0_usize;
+    let mut latin_words = 0_usize;
+    let mut in_latin_word = false;
+
+    for ch in text.chars() {
+        if is_cjk(ch) {
+            cjk_chars += 1;
+            in_latin_word = false;
+        } else if ch.is_ascii_alphabetic() {
+            if !in_latin_word {
+                latin_words += 1;
+                in_latin_word = true;
+            }
+        } else {
+            in_latin_word = false;
+        }
+    }
+
+    if cjk_chars == 0 && latin_words == 0 {
+        return DetectedLanguage::Unknown;
+    }
+
+    if cjk_chars as f64 >= latin_words as f64 * CJK_PER_LATIN_WORD {
+        DetectedLanguage::Chinese
+    } else {
+        DetectedLanguage::English
+    }
+}
+
+/// Whether `ch` is a CJK unified ideograph (basic block or extension A).
+pub fn is_cjk(ch: char) -> bool {
+    ('\u{4E00}'..='\u{9FFF}').contains(&ch) || ('\u{3400}'..='\u{4DBF}').contains(&ch)
+}
@@ -1 +1,9 @@
+pub mod csv;
+pub mod document_preview;
+pub mod document_text;
+pub mod docx_text;
 pub mod docx_to_pdf;
+pub mod duplicates;
+pub mod language;
+pub mod pdf_ocr;
+pub mod prompt_template;
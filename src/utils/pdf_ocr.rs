@@ -0,0 +1,204 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Output,
+    sync::{LazyLock, OnceLock},
+    time::Duration,
+};
+
+use anyhow::{Context, Result, anyhow};
+use tokio::{process::Command, runtime::Handle, sync::Semaphore, time};
+use uuid::Uuid;
+
+/// PDFs whose extracted text has fewer non-whitespace characters than this are treated as
+/// image-only scans and sent through OCR (when enabled).
+pub const MIN_PDF_TEXT_CHARS: usize = 200;
+/// Upper bound on pages rendered for OCR, so a huge scan cannot stall a worker for hours.
+const MAX_OCR_PAGES: usize = 60;
+const OCR_RESOLUTION_DPI: &str = "300";
+const DEFAULT_OCR_LANGUAGES: &str = "chi_sim+eng";
+/// Rendering the OCR pages is killed (and OCR abandoned) when it takes longer than this.
+const OCR_RENDER_TIMEOUT: Duration = Duration::from_secs(300);
+/// Limit for `tesseract` on a single rendered page.
+const OCR_PAGE_TIMEOUT: Duration = Duration::from_secs(120);
+/// Preview renders are triggered by page views, so only a couple run at once.
+const MAX_CONCURRENT_PREVIEW_RENDERS: usize = 2;
+/// A single-page preview render that takes longer than this is killed.
+const PREVIEW_RENDER_TIMEOUT: Duration = Duration::from_secs(30);
+
+static PREVIEW_RENDER_SLOTS: LazyLock<Semaphore> =
+    LazyLock::new(|| Semaphore::new(MAX_CONCURRENT_PREVIEW_RENDERS));
+
+/// Whether `PDF_OCR_ENABLED` is set. OCR shells out to `pdftoppm` (poppler) and `tesseract`,
+/// so deployments without those binaries leave it off and keep plain text extraction.
+pub fn ocr_enabled() -> bool {
+    static ENABLED: OnceLock<bool> = OnceLock::new();
+    *ENABLED.get_or_init(|| {
+        env::var("PDF_OCR_ENABLED")
+            .map(|value| {
+                matches!(
+                    value.trim().to_ascii_lowercase().as_str(),
+                    "1" | "true" | "yes" | "on"
+                )
+            })
+            .unwrap_or(false)
+    })
+}
+
+fn ocr_languages() -> String {
+    env::var("PDF_OCR_LANGUAGES")
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .unwrap_or_else(|| DEFAULT_OCR_LANGUAGES.to_string())
+}
+
+/// Returns true when extracted PDF text is too sparse to be useful.
+pub fn needs_ocr(text: &str) -> bool {
+    text.chars().filter(|ch| !ch.is_whitespace()).count() < MIN_PDF_TEXT_CHARS
+}
+
+/// Renders each page of `pdf_path` to PNG and runs it through `tesseract`, returning the
+/// recognised text with pages separated by blank lines. A tool that hangs is killed and
+/// reported as an error. Blocking; call from `spawn_blocking` inside the Tokio runtime.
+pub fn ocr_pdf(pdf_path: &Path) -> Result<String> {
+    let runtime = Handle::try_current().context("OCR must run inside the Tokio runtime")?;
+    let work_dir = env::temp_dir().join(format!("pdf-ocr-{}", Uuid::new_v4()));
+    fs::create_dir_all(&work_dir)
+        .with_context(|| format!("failed to create OCR work dir {}", work_dir.display()))?;
+
+    let result = runtime.block_on(render_and_recognise(pdf_path, &work_dir));
+    let _ = fs::remove_dir_all(&work_dir);
+    result
+}
+
+/// Renders only the first page of `pdf_path` to `output_png`, scaled so its longest edge is
+/// `max_dimension` pixels. The image is written under a temporary name and renamed into place,
+/// so concurrent renders of the same document never expose a half-written file.
+pub async fn render_first_page_png(
+    pdf_path: &Path,
+    output_png: &Path,
+    max_dimension: u32,
+) -> Result<()> {
+    let _slot = PREVIEW_RENDER_SLOTS
+        .acquire()
+        .await
+        .context("preview render queue closed")?;
+
+    let output_dir = output_png
+        .parent()
+        .ok_or_else(|| anyhow!("Invalid preview path: missing parent directory"))?;
+    // pdftoppm appends `.png` to the prefix when `-singlefile` is set.
+    let prefix = output_dir.join(format!("preview-render-{}", Uuid::new_v4()));
+    let rendered = prefix.with_extension("png");
+
+    let mut command = Command::new("pdftoppm");
+    command
+        .args(["-f", "1", "-l", "1", "-singlefile", "-png", "-scale-to"])
+        .arg(max_dimension.to_string())
+        .arg(pdf_path)
+        .arg(&prefix);
+    let result = match run_tool(command, "pdftoppm", PREVIEW_RENDER_TIMEOUT).await {
+        Ok(_) => tokio::fs::rename(&rendered, output_png)
+            .await
+            .with_context(|| format!("failed to move preview to {}", output_png.display())),
+        Err(err) => Err(err),
+    };
+    if result.is_err() {
+        let _ = tokio::fs::remove_file(&rendered).await;
+    }
+    result
+}
+
+/// Runs an external tool to completion, killing it when it outlives `timeout`, and fails on a
+/// non-zero exit status.
+async fn run_tool(mut command: Command, tool: &str, timeout: Duration) -> Result<Output> {
+    let child = command.kill_on_drop(true).output();
+
+    // Dropping the timed-out future drops the child, which kills it.
+    let output = time::timeout(timeout, child)
+        .await
+        .map_err(|_| anyhow!("{tool} timed out after {} seconds", timeout.as_secs()))?
+        .with_context(|| format!("Failed to execute {tool} command"))?;
+
+    if !output.status.success() {
+        return Err(anyhow!(
+            "{tool} failed with status {:?}: {}",
+            output.status.code(),
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    Ok(output)
+}
+
+async fn render_and_recognise(pdf_path: &Path, work_dir: &Path) -> Result<String> {
+    let prefix = work_dir.join("page");
+    let mut render = Command::new("pdftoppm");
+    render
+        .args([
+            "-r",
+            OCR_RESOLUTION_DPI,
+            "-l",
+            &MAX_OCR_PAGES.to_string(),
+            "-png",
+        ])
+        .arg(pdf_path)
+        .arg(&prefix);
+    run_tool(render, "pdftoppm", OCR_RENDER_TIMEOUT).await?;
+
+    let mut pages: Vec<PathBuf> = fs::read_dir(work_dir)
+        .context("failed to list rendered OCR pages")?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("png"))
+        .collect();
+    // pdftoppm zero-pads page numbers, so lexical order is page order.
+    pages.sort();
+
+    if pages.is_empty() {
+        return Err(anyhow!(
+            "pdftoppm produced no pages for {}",
+            pdf_path.display()
+        ));
+    }
+
+    let languages = ocr_languages();
+    let mut text = String::new();
+    for page in pages {
+        let mut recognise = Command::new("tesseract");
+        recognise.arg(&page).args(["stdout", "-l", &languages]);
+        let output = run_tool(recognise, "tesseract", OCR_PAGE_TIMEOUT)
+            .await
+            .with_context(|| format!("OCR failed on {}", page.display()))?;
+
+        let page_text = String::from_utf8_lossy(&output.stdout);
+        let page_text = page_text.trim();
+        if !page_text.is_empty() {
+            if !text.is_empty() {
+                text.push_str("\n\n");
+            }
+            text.push_str(page_text);
+        }
+    }
+
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn hung_tools_are_killed_at_the_timeout() {
+        let mut command = Command::new("sleep");
+        command.arg("30");
+        let err = run_tool(command, "sleep", Duration::from_millis(100))
+            .await
+            .expect_err("sleep outlives the timeout");
+        assert!(err.to_string().contains("timed out"), "{err}");
+    }
+
+    #[test]
+    fn ocr_outside_the_runtime_fails_instead_of_panicking() {
+        assert!(ocr_pdf(Path::new("missing.pdf")).is_err());
+    }
+}
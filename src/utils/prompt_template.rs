@@ -0,0 +1,51 @@
+/// Replaces each listed placeholder (e.g. `{{GLOSSARY}}`) in one left-to-right pass.
+///
+/// Inserted values are never rescanned, so a value that itself contains placeholder text is
+/// kept literally. Placeholders not listed in `values` are left as written.
+pub fn fill_placeholders(template: &str, values: &[(&str, &str)]) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        output.push_str(&rest[..start]);
+        let candidate = &rest[start..];
+        match values
+            .iter()
+            .find(|(placeholder, _)| candidate.starts_with(placeholder))
+        {
+            Some((placeholder, value)) => {
+                output.push_str(value);
+                rest = &candidate[placeholder.len()..];
+            }
+            None => {
+                output.push('{');
+                rest = &candidate[1..];
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+/// Collapses whitespace runs, line breaks included, to single spaces so an inserted value
+/// stays on its own prompt line.
+pub fn single_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn substitution_is_single_pass() {
+        let template = "A {{X}} B {{{X}} C {{Y}} D {{X}}";
+        let filled = fill_placeholders(template, &[("{{X}}", "{{Y}}"), ("{{Y}}", "y")]);
+        assert_eq!(filled, "A {{Y}} B {{{Y}} C y D {{Y}}");
+        assert_eq!(fill_placeholders("{{Z}} {{", &[("{{X}}", "x")]), "{{Z}} {{");
+    }
+
+    #[test]
+    fn single_line_collapses_breaks() {
+        assert_eq!(single_line("  stem\r\n cell\tline "), "stem cell line");
+    }
+}
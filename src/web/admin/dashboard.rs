@@ -11,8 +11,8 @@ use tracing::error;
 use uuid::Uuid;
 
 use crate::{
-    usage,
-    web::{AppState, admin_utils::compose_flash_message, escape_html, render_footer},
+    llm, maintenance, usage,
+    web::{AppState, admin_utils::compose_query_flash, escape_html, render_footer},
 };
 
 use super::{auth::require_admin_user, types::DashboardQuery};
@@ -48,6 +48,23 @@ pub async fn dashboard(
         return Err(Redirect::to("/login"));
     }
 
+    let unit_policies = usage::unit_policies(state.pool_ref())
+        .await
+        .unwrap_or_else(|err| {
+            error!(?err, "failed to load unit policies");
+            usage::REGISTERED_MODULES
+                .iter()
+                .map(|descriptor| (descriptor.key.to_string(), descriptor.default_unit_policy))
+                .collect()
+        });
+    let unit_label_for = |descriptor: &usage::ModuleDescriptor| {
+        unit_policies
+            .get(descriptor.key)
+            .copied()
+            .unwrap_or(descriptor.default_unit_policy)
+            .unit_label(descriptor)
+    };
+
     let mut group_lookup: HashMap<Uuid, UsageGroupDisplay> = HashMap::new();
     let mut group_options_for_create = String::new();
     let mut group_options_for_assign = String::new();
@@ -117,9 +134,9 @@ pub async fn dashboard(
                 let unit_text = match unit_limit {
                     Some(limit) => format!(
                         "{units_used}/{limit} {label}",
-                        label = descriptor.unit_label
+                        label = unit_label_for(descriptor)
                     ),
-                    None => format!("{units_used} {label}", label = descriptor.unit_label),
+                    None => format!("{units_used} {label}", label = unit_label_for(descriptor)),
                 };
 
                 let token_text = format!("{tokens_used} 令牌");
@@ -132,7 +149,12 @@ pub async fn dashboard(
                 ));
             }
 
-            let usage_detail_html = format!(r#"<div class="usage-grid">{chips}</div>"#);
+            let email_form = format!(
+                r#"<form method="post" action="/dashboard/users/email" class="inline-form"><input type="hidden" name="username" value="{username}"><label>任务完成通知邮箱 <input type="email" name="email" value="{email}" placeholder="留空则不发送"></label> <button type="submit" class="btn-sm">保存</button></form>"#,
+                username = escape_html(&user.username),
+                email = escape_html(user.notification_email.as_deref().unwrap_or("")),
+            );
+            let usage_detail_html = format!(r#"<div class="usage-grid">{chips}</div>{email_form}"#);
             let usage_summary = format!(
                 "{total_units} 项 · {token_text}",
                 token_text = global_token_text,
@@ -181,7 +203,7 @@ pub async fn dashboard(
         }
     }
 
-    let message_block = compose_flash_message(params.status.as_deref(), params.error.as_deref());
+    let message_block = compose_query_flash(&params);
 
     let user_controls = format!(
         r##"<div class="admin-actions">
@@ -259,7 +281,7 @@ pub async fn dashboard(
                 title = escape_html(descriptor.label),
                 key = descriptor.key,
                 id = group.id,
-                unit_label = descriptor.unit_label,
+                unit_label = unit_label_for(descriptor),
                 units_attr = units_attr,
             ));
         }
@@ -333,7 +355,7 @@ pub async fn dashboard(
     </div>"#,
             title = escape_html(descriptor.label),
             key = descriptor.key,
-            unit_label = descriptor.unit_label,
+            unit_label = unit_label_for(descriptor),
         ));
     }
 
@@ -368,6 +390,123 @@ pub async fn dashboard(
         new_group_fields = new_group_fields,
     );
 
+    let mut policy_fields = String::new();
+    for descriptor in usage::REGISTERED_MODULES {
+        let current = unit_policies
+            .get(descriptor.key)
+            .copied()
+            .unwrap_or(descriptor.default_unit_policy);
+        let options: String = usage::UnitPolicy::ALL
+            .iter()
+            .map(|policy| {
+                format!(
+                    r#"<option value="{value}"{selected}>{label}</option>"#,
+                    value = policy.as_str(),
+                    label = policy.label_zh(),
+                    selected = if *policy == current { " selected" } else { "" },
+                )
+            })
+            .collect();
+
+        policy_fields.push_str(&format!(
+            r#"<div class="field">
+                <label for="policy-{key}">{title}</label>
+                <select id="policy-{key}" name="policy_{key}">{options}</select>
+            </div>"#,
+            key = descriptor.key,
+            title = escape_html(descriptor.label),
+            options = options,
+        ));
+    }
+
+    let policy_section = format!(
+        r##"<section class="admin">
+    <h2>计量方式</h2>
+    <p class="meta-note">决定各模块任务计入额度的单位；修改后仅影响之后的任务记录。</p>
+    <form method="post" action="/dashboard/usage-policies">
+        {policy_fields}
+        <div class="action-stack">
+            <button type="submit" class="btn-primary">保存计量方式</button>
+        </div>
+    </form>
+</section>"##,
+        policy_fields = policy_fields,
+    );
+
+    let model_aliases_section = format!(
+        r##"<section class="admin collapsible-section">
+            <h2 class="section-header" onclick="toggleSection('model-aliases')">
+                <span class="toggle-icon" id="icon-model-aliases">▶</span> 备用模型映射
+            </h2>
+            <div class="section-content collapsed" id="content-model-aliases">
+                <p class="meta-note">Poe 服务故障（网络错误、超时、5xx 或 429）时，请求会自动改用映射的 OpenRouter 模型重试一次。每行一条：<code>Poe 模型=OpenRouter 模型</code>，均填写不带 <code>poe/</code>、<code>openrouter/</code> 前缀的名称，例如 <code>Claude-Sonnet-4=anthropic/claude-sonnet-4</code>。</p>
+                <form method="post" action="/dashboard/model-aliases">
+                    <div class="field">
+                        <label for="model-aliases">映射规则</label>
+                        <textarea id="model-aliases" name="aliases" rows="6" style="padding:0.75rem 1rem;border-radius:8px;border:1px solid #cbd5e1;font-family:monospace;font-size:0.95rem;">{aliases}</textarea>
+                    </div>
+                    <div class="action-stack">
+                        <button type="submit" class="btn-primary">保存映射</button>
+                    </div>
+                </form>
+            </div>
+        </section>"##,
+        aliases = escape_html(&llm::format_alias_lines(
+            &state.llm_client().provider_aliases().entries()
+        )),
+    );
+
+    let retention_policies = maintenance::retention_policies(state.pool_ref())
+        .await
+        .unwrap_or_else(|err| {
+            error!(?err, "failed to load file retention policies");
+            HashMap::new()
+        });
+    let retention_fields: String = usage::REGISTERED_MODULES
+        .iter()
+        .map(|descriptor| {
+            let policy = retention_policies
+                .get(descriptor.key)
+                .copied()
+                .unwrap_or_default();
+            format!(
+                r#"<div class="field">
+                <label for="retention-{key}">{title}：保留天数</label>
+                <input type="number" id="retention-{key}" name="retention_{key}" min="1" max="{max}" value="{days}" required>
+                <label for="archive-{key}">压缩归档天数</label>
+                <input type="number" id="archive-{key}" name="archive_{key}" min="1" max="{max}" value="{archive}" placeholder="留空不归档">
+            </div>"#,
+                key = descriptor.key,
+                title = escape_html(descriptor.label),
+                max = maintenance::MAX_RETENTION_DAYS,
+                days = policy.retention_days,
+                archive = policy
+                    .archive_after_days
+                    .map(|days| days.to_string())
+                    .unwrap_or_default(),
+            )
+        })
+        .collect();
+
+    let retention_section = format!(
+        r##"<section class="admin collapsible-section">
+            <h2 class="section-header" onclick="toggleSection('file-retention')">
+                <span class="toggle-icon" id="icon-file-retention">▶</span> 文件保留期限
+            </h2>
+            <div class="section-content collapsed" id="content-file-retention">
+                <p class="meta-note">任务最后更新超过保留天数后，后台清理会删除其上传文件与结果文件（默认 {default} 天，上限 {max} 天）。填写压缩归档天数后，已结束的任务会先在该天数后压缩存放，下载时自动解压；归档天数需小于保留天数。历史记录面板按同一保留天数显示任务。</p>
+                <form method="post" action="/dashboard/retention">
+                    {retention_fields}
+                    <div class="action-stack">
+                        <button type="submit" class="btn-primary">保存保留期限</button>
+                    </div>
+                </form>
+            </div>
+        </section>"##,
+        default = maintenance::DEFAULT_RETENTION_DAYS,
+        max = maintenance::MAX_RETENTION_DAYS,
+    );
+
     let footer = render_footer();
 
     let html = format!(
@@ -538,8 +677,11 @@ pub async fn dashboard(
             <div class="section-content collapsed" id="content-group-management">
                 {group_sections}
                 {new_group}
+                {policy_section}
             </div>
         </section>
+        {model_aliases_section}
+        {retention_section}
         <div id="password-modal" class="modal">
             <div class="modal-content">
                 <div class="modal-header">
@@ -654,6 +796,7 @@ pub async fn dashboard(
         user_controls = user_controls,
         group_sections = group_sections,
         new_group = new_group_section,
+        policy_section = policy_section,
         footer = footer,
     );
 
@@ -668,6 +811,7 @@ struct DashboardUserRow {
     usage_group_id: Uuid,
     usage_group_name: String,
     is_admin: bool,
+    notification_email: Option<String>,
 }
 
 #[derive(Clone)]
@@ -688,7 +832,7 @@ struct UsageGroupRow {
 
 async fn fetch_dashboard_users(pool: &PgPool) -> sqlx::Result<Vec<DashboardUserRow>> {
     sqlx::query_as::<_, DashboardUserRow>(
-        "SELECT u.id, u.username, u.usage_group_id, ug.name AS usage_group_name, u.is_admin FROM users u JOIN usage_groups ug ON ug.id = u.usage_group_id ORDER BY u.username",
+        "SELECT u.id, u.username, u.usage_group_id, ug.name AS usage_group_name, u.is_admin, u.notification_email FROM users u JOIN usage_groups ug ON ug.id = u.usage_group_id ORDER BY u.username",
     )
     .fetch_all(pool)
     .await
@@ -1,16 +1,36 @@
+use std::collections::HashMap;
+
 use axum::{
-    extract::{Form, State},
-    response::Redirect,
+    extract::{Form, Multipart, State},
+    http::header,
+    response::{IntoResponse, Redirect, Response},
 };
 use axum_extra::extract::cookie::CookieJar;
 use serde::Deserialize;
+use sqlx::PgPool;
 use tracing::error;
 use uuid::Uuid;
 
-use crate::web::{AppState, admin_utils::sanitize_module_redirect};
+use crate::{
+    utils::csv::{UTF8_BOM, parse_csv, push_csv_row},
+    web::{AppState, GLOSSARY_SCOPES, admin_utils::sanitize_module_redirect, fetch_glossary_terms},
+};
 
 use super::auth::require_admin_user;
 
+/// Validates a submitted glossary scope: blank means global, otherwise a `GLOSSARY_SCOPES` key.
+fn parse_glossary_scope(input: Option<&str>) -> Result<Option<String>, ()> {
+    let value = input.map(str::trim).unwrap_or("");
+    if value.is_empty() {
+        return Ok(None);
+    }
+    GLOSSARY_SCOPES
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(value))
+        .map(|(key, _)| Some(key.to_string()))
+        .ok_or(())
+}
+
 #[derive(Deserialize)]
 pub(crate) struct GlossaryCreateForm {
     source_term: String,
@@ -18,6 +38,8 @@ pub(crate) struct GlossaryCreateForm {
     #[serde(default)]
     notes: Option<String>,
     #[serde(default)]
+    scope: Option<String>,
+    #[serde(default)]
     redirect: Option<String>,
 }
 
@@ -29,6 +51,8 @@ pub(crate) struct GlossaryUpdateForm {
     #[serde(default)]
     notes: Option<String>,
     #[serde(default)]
+    scope: Option<String>,
+    #[serde(default)]
     redirect: Option<String>,
 }
 
@@ -56,6 +80,11 @@ pub async fn create_glossary_term(
             "{redirect_base}?error=glossary_missing_fields"
         )));
     }
+    let Ok(scope) = parse_glossary_scope(form.scope.as_deref()) else {
+        return Ok(Redirect::to(&format!(
+            "{redirect_base}?error=glossary_scope_invalid"
+        )));
+    };
 
     let notes_clean = form
         .notes
@@ -65,12 +94,13 @@ pub async fn create_glossary_term(
         .map(str::to_string);
 
     let insert_result = sqlx::query(
-        "INSERT INTO glossary_terms (id, source_term, target_term, notes) VALUES ($1, $2, $3, $4)",
+        "INSERT INTO glossary_terms (id, source_term, target_term, notes, scope) VALUES ($1, $2, $3, $4, $5)",
     )
     .bind(Uuid::new_v4())
     .bind(&source_clean)
     .bind(&target_clean)
     .bind(notes_clean.as_deref())
+    .bind(scope.as_deref())
     .execute(state.pool_ref())
     .await;
 
@@ -79,7 +109,7 @@ pub async fn create_glossary_term(
             "{redirect_base}?status=glossary_created"
         ))),
         Err(sqlx::Error::Database(db_err))
-            if db_err.constraint() == Some("idx_glossary_terms_source_lower") =>
+            if db_err.constraint() == Some("idx_glossary_terms_scope_source_lower") =>
         {
             Ok(Redirect::to(&format!(
                 "{redirect_base}?error=glossary_duplicate"
@@ -108,6 +138,11 @@ pub async fn update_glossary_term(
             "{redirect_base}?error=glossary_missing_fields"
         )));
     }
+    let Ok(scope) = parse_glossary_scope(form.scope.as_deref()) else {
+        return Ok(Redirect::to(&format!(
+            "{redirect_base}?error=glossary_scope_invalid"
+        )));
+    };
 
     let notes_clean = form
         .notes
@@ -117,12 +152,13 @@ pub async fn update_glossary_term(
         .map(str::to_string);
 
     let update_result = sqlx::query(
-        "UPDATE glossary_terms SET source_term = $2, target_term = $3, notes = $4 WHERE id = $1",
+        "UPDATE glossary_terms SET source_term = $2, target_term = $3, notes = $4, scope = $5 WHERE id = $1",
     )
     .bind(form.id)
     .bind(&source_clean)
     .bind(&target_clean)
     .bind(notes_clean.as_deref())
+    .bind(scope.as_deref())
     .execute(state.pool_ref())
     .await;
 
@@ -134,7 +170,7 @@ pub async fn update_glossary_term(
             "{redirect_base}?error=glossary_not_found"
         ))),
         Err(sqlx::Error::Database(db_err))
-            if db_err.constraint() == Some("idx_glossary_terms_source_lower") =>
+            if db_err.constraint() == Some("idx_glossary_terms_scope_source_lower") =>
         {
             Ok(Redirect::to(&format!(
                 "{redirect_base}?error=glossary_duplicate"
@@ -172,3 +208,304 @@ pub async fn delete_glossary_term(
         }
     }
 }
+
+/// Largest glossary CSV accepted by the import.
+const MAX_GLOSSARY_CSV_BYTES: usize = 2 * 1024 * 1024;
+const GLOSSARY_CSV_HEADER: [&str; 4] = ["source_term", "target_term", "notes", "scope"];
+
+/// One CSV row to upsert.
+#[derive(Debug, PartialEq)]
+struct GlossaryImportRow {
+    source_term: String,
+    target_term: String,
+    notes: Option<String>,
+    scope: Option<String>,
+}
+
+/// Parses a `source_term,target_term,notes,scope` CSV (header required, trailing `notes` and
+/// `scope` columns optional). Rows repeating a source term (case-insensitively) within the same
+/// scope keep the last one, like the upsert would.
+fn parse_glossary_csv(text: &str) -> Option<Vec<GlossaryImportRow>> {
+    let mut rows = parse_csv(text).ok()?.into_iter();
+    let header = rows.next()?;
+    let header: Vec<String> = header
+        .iter()
+        .map(|cell| cell.trim().to_ascii_lowercase())
+        .collect();
+    if header.len() < 2
+        || header.len() > GLOSSARY_CSV_HEADER.len()
+        || header[..] != GLOSSARY_CSV_HEADER[..header.len()]
+    {
+        return None;
+    }
+
+    let mut by_key: HashMap<(Option<String>, String), usize> = HashMap::new();
+    let mut terms: Vec<GlossaryImportRow> = Vec::new();
+    for row in rows {
+        if row.len() > header.len() {
+            return None;
+        }
+        let cell = |idx: usize| row.get(idx).map(|value| value.trim()).unwrap_or("");
+        let (source_term, target_term) = (cell(0), cell(1));
+        if source_term.is_empty() || target_term.is_empty() {
+            return None;
+        }
+        let scope = parse_glossary_scope(Some(cell(3))).ok()?;
+        let key = (scope.clone(), source_term.to_lowercase());
+        let term = GlossaryImportRow {
+            source_term: source_term.to_string(),
+            target_term: target_term.to_string(),
+            notes: Some(cell(2))
+                .filter(|notes| !notes.is_empty())
+                .map(str::to_string),
+            scope,
+        };
+        match by_key.get(&key) {
+            Some(&idx) => terms[idx] = term,
+            None => {
+                by_key.insert(key, terms.len());
+                terms.push(term);
+            }
+        }
+    }
+
+    (!terms.is_empty()).then_some(terms)
+}
+
+/// Upserts the rows on scope and case-insensitive source term in one transaction and returns
+/// `(added, updated)`; rows identical to the stored term count as neither.
+async fn upsert_glossary_terms(
+    pool: &PgPool,
+    terms: &[GlossaryImportRow],
+) -> sqlx::Result<(u64, u64)> {
+    let mut tx = pool.begin().await?;
+    let (mut added, mut updated) = (0, 0);
+    for term in terms {
+        let inserted: Option<bool> = sqlx::query_scalar(
+            "INSERT INTO glossary_terms (id, source_term, target_term, notes, scope)
+             VALUES ($1, $2, $3, $4, $5)
+             ON CONFLICT ((COALESCE(scope, '')), (LOWER(source_term))) DO UPDATE
+                SET source_term = EXCLUDED.source_term,
+                    target_term = EXCLUDED.target_term,
+                    notes = EXCLUDED.notes,
+                    updated_at = NOW()
+              WHERE (glossary_terms.source_term, glossary_terms.target_term, glossary_terms.notes)
+                    IS DISTINCT FROM (EXCLUDED.source_term, EXCLUDED.target_term, EXCLUDED.notes)
+             RETURNING (xmax = 0)",
+        )
+        .bind(Uuid::new_v4())
+        .bind(&term.source_term)
+        .bind(&term.target_term)
+        .bind(term.notes.as_deref())
+        .bind(term.scope.as_deref())
+        .fetch_optional(&mut *tx)
+        .await?;
+        match inserted {
+            Some(true) => added += 1,
+            Some(false) => updated += 1,
+            None => {}
+        }
+    }
+    tx.commit().await?;
+    Ok((added, updated))
+}
+
+/// `POST /dashboard/glossary/import`: multipart form with a CSV `file` and the usual `redirect`.
+pub async fn import_glossary_csv(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    mut multipart: Multipart,
+) -> Result<Redirect, Redirect> {
+    let _admin = require_admin_user(&state, &jar).await?;
+
+    let mut redirect = None;
+    let mut csv_bytes = None;
+    while let Ok(Some(field)) = multipart.next_field().await {
+        match field.name() {
+            Some("redirect") => redirect = field.text().await.ok(),
+            Some("file") => csv_bytes = field.bytes().await.ok(),
+            _ => {}
+        }
+    }
+    let redirect_base = sanitize_module_redirect(redirect.as_deref());
+
+    let terms = csv_bytes
+        .filter(|bytes| bytes.len() <= MAX_GLOSSARY_CSV_BYTES)
+        .and_then(|bytes| String::from_utf8(bytes.to_vec()).ok())
+        .and_then(|text| parse_glossary_csv(&text));
+    let Some(terms) = terms else {
+        return Ok(Redirect::to(&format!(
+            "{redirect_base}?error=glossary_csv_invalid"
+        )));
+    };
+
+    match upsert_glossary_terms(state.pool_ref(), &terms).await {
+        Ok((added, updated)) => Ok(Redirect::to(&format!(
+            "{redirect_base}?status=glossary_imported&added={added}&updated={updated}"
+        ))),
+        Err(err) => {
+            error!(?err, "failed to import glossary terms");
+            Ok(Redirect::to(&format!("{redirect_base}?error=unknown")))
+        }
+    }
+}
+
+/// `GET /dashboard/glossary/export`: every term as `source_term,target_term,notes,scope` CSV.
+pub async fn export_glossary_csv(
+    State(state): State<AppState>,
+    jar: CookieJar,
+) -> Result<Response, Redirect> {
+    let _admin = require_admin_user(&state, &jar).await?;
+
+    let terms = fetch_glossary_terms(state.pool_ref())
+        .await
+        .map_err(|err| {
+            error!(?err, "failed to load glossary terms for export");
+            Redirect::to("/dashboard?error=unknown")
+        })?;
+
+    let mut output = String::from(UTF8_BOM);
+    push_csv_row(&mut output, GLOSSARY_CSV_HEADER.into_iter());
+    for term in &terms {
+        push_csv_row(
+            &mut output,
+            [
+                term.source_term.as_str(),
+                term.target_term.as_str(),
+                term.notes.as_deref().unwrap_or(""),
+                term.scope.as_deref().unwrap_or(""),
+            ]
+            .into_iter(),
+        );
+    }
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8"),
+            (
+                header::CONTENT_DISPOSITION,
+                "attachment; filename=\"glossary.csv\"",
+            ),
+        ],
+        output,
+    )
+        .into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_user, session_jar};
+    use crate::{AppState, llm::LlmClient};
+
+    fn row(source: &str, target: &str, notes: Option<&str>) -> GlossaryImportRow {
+        GlossaryImportRow {
+            source_term: source.to_string(),
+            target_term: target.to_string(),
+            notes: notes.map(str::to_string),
+            scope: None,
+        }
+    }
+
+    #[test]
+    fn glossary_csv_requires_header_and_both_terms() {
+        let parsed = parse_glossary_csv(
+            "\u{FEFF}Source_Term,target_term,notes\r\nCell,细胞,\r\n\"Stem cell, adult\",成体干细胞,\"医学, 生物\"\r\ncell,细胞单元,更正\r\n",
+        )
+        .unwrap();
+        assert_eq!(
+            parsed,
+            [
+                row("cell", "细胞单元", Some("更正")),
+                row("Stem cell, adult", "成体干细胞", Some("医学, 生物")),
+            ]
+        );
+        assert_eq!(
+            parse_glossary_csv("source_term,target_term\nGene,基因").unwrap(),
+            [row("Gene", "基因", None)]
+        );
+
+        let scoped = parse_glossary_csv(
+            "source_term,target_term,notes,scope\nCell,细胞,,\nCell,电池,,TranslateDocx\n",
+        )
+        .unwrap();
+        assert_eq!(scoped.len(), 2);
+        assert_eq!(scoped[1].scope.as_deref(), Some("translatedocx"));
+        assert!(
+            parse_glossary_csv("source_term,target_term,notes,scope\nCell,细胞,,grader").is_none()
+        );
+
+        assert!(parse_glossary_csv("Gene,基因").is_none());
+        assert!(parse_glossary_csv("source_term,target_term,notes\nGene,,x").is_none());
+        assert!(parse_glossary_csv("source_term,target_term\nGene,基因,extra").is_none());
+        assert!(parse_glossary_csv("source_term,target_term,notes\n").is_none());
+    }
+
+    #[sqlx::test]
+    #[ignore = "needs DATABASE_URL (disposable Postgres)"]
+    async fn glossary_import_upserts_and_counts(pool: PgPool) {
+        sqlx::query(
+            "INSERT INTO glossary_terms (id, source_term, target_term, notes) VALUES ($1, 'Cell', '细胞', NULL), ($2, 'Gene', '基因', NULL)",
+        )
+        .bind(Uuid::new_v4())
+        .bind(Uuid::new_v4())
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let terms = [
+            row("cell", "细胞单元", None),
+            row("Gene", "基因", None),
+            row("Protein", "蛋白质", Some("生化")),
+        ];
+        assert_eq!(upsert_glossary_terms(&pool, &terms).await.unwrap(), (1, 1));
+
+        // A scoped term sits beside the global one with the same source term.
+        let scoped = GlossaryImportRow {
+            scope: Some("translatedocx".to_string()),
+            ..row("Cell", "电池", None)
+        };
+        assert_eq!(
+            upsert_glossary_terms(&pool, &[scoped]).await.unwrap(),
+            (1, 0)
+        );
+
+        let stored = fetch_glossary_terms(&pool).await.unwrap();
+        let mut pairs: Vec<_> = stored
+            .iter()
+            .filter(|term| term.scope.is_none())
+            .map(|term| (term.source_term.as_str(), term.target_term.as_str()))
+            .collect();
+        pairs.sort();
+        assert_eq!(
+            pairs,
+            [
+                ("Gene", "基因"),
+                ("Protein", "蛋白质"),
+                ("cell", "细胞单元")
+            ]
+        );
+
+        let admin = create_user(&pool).await;
+        sqlx::query("UPDATE users SET is_admin = TRUE WHERE id = $1")
+            .bind(admin)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let state = AppState::for_tests(
+            pool.clone(),
+            LlmClient::with_openrouter_base_url("http://127.0.0.1:9"),
+        )
+        .await
+        .unwrap();
+        let response = export_glossary_csv(State(state), session_jar(&pool, admin).await)
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let exported = parse_glossary_csv(std::str::from_utf8(&body).unwrap()).unwrap();
+        assert_eq!(exported.len(), 4);
+        assert!(exported.contains(&row("Protein", "蛋白质", Some("生化"))));
+    }
+}
@@ -0,0 +1,75 @@
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Redirect, Response},
+};
+use axum_extra::extract::cookie::CookieJar;
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use crate::{
+    llm_metrics::{self, CallMetricsGroup},
+    web::{AppState, json_error},
+};
+
+use super::auth::require_admin_user;
+
+const DEFAULT_WINDOW_HOURS: i64 = 24;
+/// Matches the retention of `llm_call_metrics`; older calls are already gone.
+const MAX_WINDOW_HOURS: i64 = 30 * 24;
+
+#[derive(Deserialize)]
+pub(crate) struct LlmMetricsQuery {
+    #[serde(default)]
+    hours: Option<i64>,
+}
+
+#[derive(Serialize)]
+struct LlmMetricsResponse {
+    window_hours: i64,
+    since: String,
+    generated_at: String,
+    /// Set when the window held more calls than are aggregated; only the newest count.
+    truncated: bool,
+    groups: Vec<CallMetricsGroup>,
+}
+
+/// Per module/provider/model call counts, latency percentiles, average sizes and error rates
+/// for the last `hours` (default 24, at most 30 days).
+pub async fn llm_call_metrics(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Query(query): Query<LlmMetricsQuery>,
+) -> Result<Response, Redirect> {
+    let _admin = require_admin_user(&state, &jar).await?;
+
+    let window_hours = query
+        .hours
+        .unwrap_or(DEFAULT_WINDOW_HOURS)
+        .clamp(1, MAX_WINDOW_HOURS);
+    let now = Utc::now();
+    let since = now - Duration::hours(window_hours);
+
+    let (samples, truncated) = match llm_metrics::load_samples(state.pool_ref(), since).await {
+        Ok(loaded) => loaded,
+        Err(err) => {
+            error!(?err, "failed to load LLM call metrics");
+            return Ok(json_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "无法读取调用统计，请稍后再试。",
+            )
+            .into_response());
+        }
+    };
+
+    Ok(Json(LlmMetricsResponse {
+        window_hours,
+        since: since.to_rfc3339(),
+        generated_at: now.to_rfc3339(),
+        truncated,
+        groups: llm_metrics::summarize(&samples),
+    })
+    .into_response())
+}
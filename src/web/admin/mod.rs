@@ -2,16 +2,25 @@ mod auth;
 mod dashboard;
 mod glossary;
 mod journals;
+mod llm_metrics;
+mod model_aliases;
+mod retention;
 mod types;
 mod usage_groups;
 mod users;
 
 pub use auth::require_admin_user;
 pub use dashboard::dashboard;
-pub use glossary::{create_glossary_term, delete_glossary_term, update_glossary_term};
+pub use glossary::{
+    create_glossary_term, delete_glossary_term, export_glossary_csv, import_glossary_csv,
+    update_glossary_term,
+};
 pub use journals::{
     delete_journal_reference, delete_journal_topic, upsert_journal_reference, upsert_journal_topic,
 };
+pub use llm_metrics::llm_call_metrics;
+pub use model_aliases::save_model_aliases;
+pub use retention::save_retention_policies;
 pub use types::DashboardQuery;
-pub use usage_groups::save_usage_group;
-pub use users::{assign_user_group, create_user, update_user_password};
+pub use usage_groups::{save_unit_policies, save_usage_group};
+pub use users::{assign_user_group, create_user, update_notification_email, update_user_password};
@@ -0,0 +1,54 @@
+use axum::{
+    extract::{Form, State},
+    response::Redirect,
+};
+use axum_extra::extract::cookie::CookieJar;
+use serde::Deserialize;
+use tracing::error;
+
+use crate::{llm, web::AppState};
+
+use super::auth::require_admin_user;
+
+#[derive(Deserialize)]
+pub(crate) struct ModelAliasesForm {
+    #[serde(default)]
+    aliases: String,
+}
+
+/// Replaces the Poe → OpenRouter fallback aliases with the submitted `poe=openrouter` lines.
+pub async fn save_model_aliases(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Form(form): Form<ModelAliasesForm>,
+) -> Result<Redirect, Redirect> {
+    let _admin = require_admin_user(&state, &jar).await?;
+
+    let Ok(aliases) = llm::parse_alias_lines(&form.aliases) else {
+        return Ok(Redirect::to("/dashboard?error=model_aliases_invalid"));
+    };
+
+    let saved = async {
+        let mut transaction = state.pool_ref().begin().await?;
+        sqlx::query("DELETE FROM llm_model_aliases")
+            .execute(&mut *transaction)
+            .await?;
+        for (poe_model, openrouter_model) in &aliases {
+            sqlx::query(
+                "INSERT INTO llm_model_aliases (poe_model, openrouter_model) VALUES ($1, $2)",
+            )
+            .bind(poe_model)
+            .bind(openrouter_model)
+            .execute(&mut *transaction)
+            .await?;
+        }
+        transaction.commit().await?;
+        state.reload_model_aliases().await
+    };
+    if let Err(err) = saved.await {
+        error!(?err, "failed to save model aliases");
+        return Ok(Redirect::to("/dashboard?error=unknown"));
+    }
+
+    Ok(Redirect::to("/dashboard?status=model_aliases_saved"))
+}
@@ -0,0 +1,50 @@
+use std::collections::HashMap;
+
+use axum::{
+    extract::{Form, State},
+    response::Redirect,
+};
+use axum_extra::extract::cookie::CookieJar;
+use tracing::error;
+
+use crate::{maintenance, usage, web::AppState};
+
+use super::auth::require_admin_user;
+
+pub async fn save_retention_policies(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Form(form): Form<HashMap<String, String>>,
+) -> Result<Redirect, Redirect> {
+    let _admin = require_admin_user(&state, &jar).await?;
+
+    let mut policies: HashMap<String, maintenance::RetentionPolicy> = HashMap::new();
+    for module in usage::REGISTERED_MODULES {
+        let field = |prefix: &str| form.get(&format!("{prefix}_{}", module.key));
+        let Ok(retention_days) =
+            maintenance::parse_retention_days(field("retention").map(String::as_str))
+        else {
+            return Ok(Redirect::to("/dashboard?error=retention_invalid"));
+        };
+        let Ok(archive_after_days) = maintenance::parse_archive_after_days(
+            field("archive").map(String::as_str),
+            retention_days,
+        ) else {
+            return Ok(Redirect::to("/dashboard?error=archive_invalid"));
+        };
+        policies.insert(
+            module.key.to_string(),
+            maintenance::RetentionPolicy {
+                retention_days,
+                archive_after_days,
+            },
+        );
+    }
+
+    if let Err(err) = maintenance::save_retention_policies(state.pool_ref(), &policies).await {
+        error!(?err, "failed to save file retention policies");
+        return Ok(Redirect::to("/dashboard?error=unknown"));
+    }
+
+    Ok(Redirect::to("/dashboard?status=retention_saved"))
+}
@@ -4,4 +4,7 @@ use serde::Deserialize;
 pub struct DashboardQuery {
     pub status: Option<String>,
     pub error: Option<String>,
+    /// Row counts reported by `status=glossary_imported`.
+    pub added: Option<u64>,
+    pub updated: Option<u64>,
 }
@@ -120,3 +120,30 @@ pub async fn save_usage_group(
     };
     Ok(Redirect::to(&format!("/dashboard?status={status}")))
 }
+
+pub async fn save_unit_policies(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Form(form): Form<HashMap<String, String>>,
+) -> Result<Redirect, Redirect> {
+    let _admin = require_admin_user(&state, &jar).await?;
+
+    let mut policies: HashMap<String, usage::UnitPolicy> = HashMap::new();
+    for module in usage::REGISTERED_MODULES {
+        let field = format!("policy_{}", module.key);
+        let Some(policy) = form
+            .get(&field)
+            .and_then(|value| usage::UnitPolicy::parse(value))
+        else {
+            return Ok(Redirect::to("/dashboard?error=unit_policy_invalid"));
+        };
+        policies.insert(module.key.to_string(), policy);
+    }
+
+    if let Err(err) = usage::save_unit_policies(state.pool_ref(), &policies).await {
+        error!(?err, "failed to save unit policies");
+        return Ok(Redirect::to("/dashboard?error=unknown"));
+    }
+
+    Ok(Redirect::to("/dashboard?status=unit_policies_saved"))
+}
@@ -30,6 +30,13 @@ pub(crate) struct UpdatePasswordForm {
     password: String,
 }
 
+#[derive(Deserialize)]
+pub(crate) struct UpdateEmailForm {
+    username: String,
+    #[serde(default)]
+    email: String,
+}
+
 #[derive(Deserialize)]
 pub(crate) struct AssignUserGroupForm {
     username: String,
@@ -138,6 +145,43 @@ pub async fn update_user_password(
     }
 }
 
+/// Sets the address that receives job completion notices; a blank email clears it.
+pub async fn update_notification_email(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Form(form): Form<UpdateEmailForm>,
+) -> Result<Redirect, Redirect> {
+    let _admin = require_admin_user(&state, &jar).await?;
+
+    let username = form.username.trim();
+    if username.is_empty() {
+        return Ok(Redirect::to("/dashboard?error=user_missing"));
+    }
+
+    let email = form.email.trim();
+    if !email.is_empty() && email.parse::<lettre::Address>().is_err() {
+        return Ok(Redirect::to("/dashboard?error=email_invalid"));
+    }
+
+    let result = sqlx::query("UPDATE users SET notification_email = $2 WHERE username = $1")
+        .bind(username)
+        .bind((!email.is_empty()).then_some(email))
+        .execute(state.pool_ref())
+        .await;
+
+    match result {
+        Ok(res) if res.rows_affected() > 0 && email.is_empty() => {
+            Ok(Redirect::to("/dashboard?status=email_cleared"))
+        }
+        Ok(res) if res.rows_affected() > 0 => Ok(Redirect::to("/dashboard?status=email_updated")),
+        Ok(_) => Ok(Redirect::to("/dashboard?error=user_missing")),
+        Err(err) => {
+            error!(?err, "failed to update notification email");
+            Ok(Redirect::to("/dashboard?error=unknown"))
+        }
+    }
+}
+
 pub async fn assign_user_group(
     State(state): State<AppState>,
     jar: CookieJar,
@@ -1,19 +1,36 @@
+use crate::web::admin::DashboardQuery;
+
 /// Returns a sanitized redirect target for module admin pages to prevent arbitrary redirects.
 pub fn sanitize_module_redirect(input: Option<&str>) -> &'static str {
     match input {
         Some("/dashboard/modules/summarizer") => "/dashboard/modules/summarizer",
         Some("/dashboard/modules/translatedocx") => "/dashboard/modules/translatedocx",
         Some("/dashboard/modules/grader") => "/dashboard/modules/grader",
+        Some("/dashboard/modules/transcribe") => "/dashboard/modules/transcribe",
         _ => "/dashboard",
     }
 }
 
+/// Compose the flash message for a module admin page, including statuses that carry counts.
+pub fn compose_query_flash(params: &DashboardQuery) -> String {
+    if params.status.as_deref() == Some("glossary_imported") {
+        return format!(
+            r#"<div class="flash success">已导入术语：新增 {added} 条，更新 {updated} 条。</div>"#,
+            added = params.added.unwrap_or(0),
+            updated = params.updated.unwrap_or(0),
+        );
+    }
+    compose_flash_message(params.status.as_deref(), params.error.as_deref())
+}
+
 /// Compose a flash message HTML snippet for known admin status or error codes.
 pub fn compose_flash_message(status: Option<&str>, error: Option<&str>) -> String {
     if let Some(status) = status {
         let message = match status {
             "created" => "已成功创建用户。",
             "password_updated" => "已更新密码。",
+            "email_updated" => "已更新通知邮箱。",
+            "email_cleared" => "已清除通知邮箱。",
             "glossary_created" => "已新增术语。",
             "glossary_updated" => "已更新术语。",
             "glossary_deleted" => "已删除术语。",
@@ -27,9 +44,14 @@ pub fn compose_flash_message(status: Option<&str>, error: Option<&str>) -> Strin
             "docx_prompts_saved" => "已更新 DOCX 模块提示词。",
             "grader_models_saved" => "已更新稿件评估模型。",
             "grader_prompts_saved" => "已更新稿件评估提示词。",
+            "transcribe_models_saved" => "已更新音频转写模型。",
+            "transcribe_prompts_saved" => "已更新音频转写提示词。",
             "group_created" => "已创建额度组。",
             "group_saved" => "已更新额度组。",
             "group_assigned" => "已更新用户额度组。",
+            "unit_policies_saved" => "已更新计量方式。",
+            "model_aliases_saved" => "已更新备用模型映射。",
+            "retention_saved" => "已更新文件保留期限。",
             _ => "",
         };
 
@@ -46,10 +68,15 @@ pub fn compose_flash_message(status: Option<&str>, error: Option<&str>) -> Strin
             "missing_password" => "请输入密码。",
             "password_missing" => "请输入新密码。",
             "user_missing" => "未找到该用户。",
+            "email_invalid" => "请输入有效的邮箱地址。",
             "hash_failed" => "处理密码时出错，请重试。",
             "glossary_missing_fields" => "请填写英文和中文术语。",
             "glossary_duplicate" => "已存在相同英文术语。",
             "glossary_not_found" => "未找到对应术语。",
+            "glossary_scope_invalid" => "请选择有效的术语适用范围。",
+            "glossary_csv_invalid" => {
+                "CSV 格式有误：首行需为 source_term,target_term,notes,scope，每行须填写英文和中文术语且范围有效，文件不超过 2 MB。"
+            }
             "topic_missing_name" => "请填写主题名称。",
             "topic_not_found" => "未找到对应主题。",
             "journal_missing_name" => "请填写期刊名称。",
@@ -59,14 +86,40 @@ pub fn compose_flash_message(status: Option<&str>, error: Option<&str>) -> Strin
             "summarizer_invalid_models" => "请提供摘要模块所需的全部模型字段。",
             "summarizer_invalid_prompts" => "请填写摘要模块的所有提示文案。",
             "docx_invalid_models" => "请提供 DOCX 模块的模型配置。",
+            "chunk_limits_invalid" => {
+                "分段大小格式错误：每行应为“模型=段落数,等效词数”，段落数 1-200，词数 1-20000。"
+            }
+            "chunk_default_invalid" => "分段大小需为正整数：段落数 1-200，等效词数 1-20000。",
+            "docx_batch_invalid" => "单次任务文件上限需为 1-100 之间的整数。",
             "docx_invalid_prompts" => "请填写 DOCX 模块的提示文案。",
             "grader_invalid_models" => "请提供稿件评估模块的模型配置。",
+            "failure_budget_invalid" => "连续失败上限需为 0-100 之间的整数（0 表示不限制）。",
+            "token_budget_invalid" => "Token 预算需为非负整数（0 或留空表示不限制）。",
+            "trim_fraction_invalid" => "评分截尾比例需为 0 到 0.45 之间的小数。",
+            "grading_weights_invalid" => "评分权重需为 6 个非负数，且不能全为 0。",
+            "docx_penalty_invalid" => "DOCX 格式惩罚比例需为 0 到 0.5 之间的小数。",
+            "grader_sampling_invalid" => {
+                "采样次数需满足 1 ≤ 最少有效次数 ≤ 目标有效次数 ≤ 最大尝试次数 ≤ 100。"
+            }
             "grader_invalid_prompts" => "请填写稿件评估模块的提示文案。",
+            "transcribe_invalid_models" => "请填写音频转写模型。",
+            "transcribe_invalid_prompts" => "请填写音频转写提示词。",
+            "model_routes_invalid" => "路由规则格式有误，请按每行“token 上限=模型”填写。",
+            "concurrency_invalid" => "并行处理文档数需为 1-50 之间的整数。",
+            "reviewer_round1_models_invalid" => "请填写 1-16 个首轮审稿模型，每行一个。",
+            "success_ratio_invalid" => "最低成功比例需为大于 0 且不超过 1 的小数。",
+            "manuscript_limit_invalid" => "稿件大小上限和页数上限需为非负整数（0 表示不限制）。",
             "group_missing" => "请选择有效的额度组。",
             "group_invalid" => "额度组标识无效。",
             "group_invalid_limit" => "额度上限需为非负整数。",
             "group_duplicate" => "已存在同名额度组。",
             "group_name_missing" => "请输入额度组名称。",
+            "unit_policy_invalid" => "请选择有效的计量方式。",
+            "retention_invalid" => "保留期限需为 1 至 365 之间的整数天数。",
+            "archive_invalid" => "压缩归档天数需为正整数且小于保留期限，留空表示不归档。",
+            "model_aliases_invalid" => {
+                "备用模型映射格式有误，请按每行“Poe 模型=OpenRouter 模型”填写，且每个 Poe 模型只能出现一次。"
+            }
             _ => "发生未知错误，请查看日志。",
         };
 
@@ -0,0 +1,411 @@
+use anyhow::{Context, Result};
+use axum::{
+    extract::{Form, Query, State},
+    response::{Html, IntoResponse, Redirect, Response},
+};
+use axum_extra::extract::cookie::CookieJar;
+use chrono::{DateTime, Utc};
+use rand_core::{OsRng, RngCore};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use tracing::error;
+use uuid::Uuid;
+
+use crate::web::{
+    AppState, AuthUser,
+    auth::{self, API_TOKEN_COOKIE},
+    escape_html, render_footer,
+};
+
+/// Marks API tokens so they are recognisable in scripts and secret scanners.
+pub const API_TOKEN_PREFIX: &str = "aitk_";
+/// Active tokens a single user may hold.
+pub const MAX_API_TOKENS_PER_USER: i64 = 20;
+const MAX_TOKEN_NAME_CHARS: usize = 64;
+/// Leading characters kept in clear so users can tell their tokens apart.
+const DISPLAY_PREFIX_CHARS: usize = API_TOKEN_PREFIX.len() + 8;
+
+/// Returns a fresh token: the prefix followed by 32 random bytes in hex.
+pub fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    format!("{API_TOKEN_PREFIX}{}", to_hex(&bytes))
+}
+
+/// SHA-256 of the token, hex encoded. Tokens carry 256 random bits, so a fast hash suffices.
+pub fn hash_token(token: &str) -> String {
+    to_hex(&Sha256::digest(token.as_bytes()))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Resolves the owner of an API token, refreshing `last_used_at` at most every five minutes.
+pub async fn fetch_user_by_api_token(pool: &PgPool, token: &str) -> sqlx::Result<Option<AuthUser>> {
+    sqlx::query_as::<_, AuthUser>(
+        "WITH token AS (
+             SELECT id, user_id FROM api_tokens WHERE token_hash = $1
+         ), touched AS (
+             UPDATE api_tokens SET last_used_at = NOW()
+             FROM token
+             WHERE api_tokens.id = token.id
+               AND (api_tokens.last_used_at IS NULL
+                    OR api_tokens.last_used_at < NOW() - INTERVAL '5 minutes')
+         )
+         SELECT users.id, users.username, users.is_admin
+         FROM token JOIN users ON users.id = token.user_id",
+    )
+    .bind(hash_token(token))
+    .fetch_optional(pool)
+    .await
+}
+
+/// Stores a new token for `user_id` and returns its plain value, which is never persisted.
+pub async fn insert_api_token(pool: &PgPool, user_id: Uuid, name: &str) -> Result<String> {
+    let token = generate_token();
+    sqlx::query(
+        "INSERT INTO api_tokens (id, user_id, name, token_hash, token_prefix)
+         VALUES ($1, $2, $3, $4, $5)",
+    )
+    .bind(Uuid::new_v4())
+    .bind(user_id)
+    .bind(name)
+    .bind(hash_token(&token))
+    .bind(&token[..DISPLAY_PREFIX_CHARS])
+    .execute(pool)
+    .await
+    .context("failed to store API token")?;
+    Ok(token)
+}
+
+#[derive(sqlx::FromRow)]
+struct ApiTokenRow {
+    id: Uuid,
+    name: String,
+    token_prefix: String,
+    created_at: DateTime<Utc>,
+    last_used_at: Option<DateTime<Utc>>,
+}
+
+async fn list_api_tokens(pool: &PgPool, user_id: Uuid) -> sqlx::Result<Vec<ApiTokenRow>> {
+    sqlx::query_as::<_, ApiTokenRow>(
+        "SELECT id, name, token_prefix, created_at, last_used_at
+         FROM api_tokens WHERE user_id = $1 ORDER BY created_at DESC",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Tokens are managed from a browser session only, so a leaked token cannot mint more.
+async fn require_session_user(state: &AppState, jar: &CookieJar) -> Result<AuthUser, Redirect> {
+    if jar.get(API_TOKEN_COOKIE).is_some() {
+        return Err(Redirect::to("/login"));
+    }
+    auth::require_user_redirect(state, jar).await
+}
+
+#[derive(Default, Deserialize)]
+pub struct ApiTokensQuery {
+    pub status: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct CreateApiTokenForm {
+    #[serde(default)]
+    pub name: String,
+}
+
+#[derive(Deserialize)]
+pub struct RevokeApiTokenForm {
+    pub token_id: Uuid,
+}
+
+/// `GET /account/tokens`: lists the user's API tokens with mint and revoke forms.
+pub async fn api_tokens_page(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Query(params): Query<ApiTokensQuery>,
+) -> Result<Html<String>, Redirect> {
+    let user = require_session_user(&state, &jar).await?;
+    render_for_user(&state, &user, &params, None).await
+}
+
+/// `POST /account/tokens`: mints a token and shows its value once.
+pub async fn create_api_token(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Form(form): Form<CreateApiTokenForm>,
+) -> Result<Response, Redirect> {
+    let user = require_session_user(&state, &jar).await?;
+
+    let name = form.name.trim();
+    if name.is_empty() || name.chars().count() > MAX_TOKEN_NAME_CHARS {
+        return Ok(Redirect::to("/account/tokens?error=token_name_invalid").into_response());
+    }
+
+    let pool = state.pool();
+    let active: i64 = match sqlx::query_scalar("SELECT COUNT(*) FROM api_tokens WHERE user_id = $1")
+        .bind(user.id)
+        .fetch_one(&pool)
+        .await
+    {
+        Ok(count) => count,
+        Err(err) => {
+            error!(?err, "failed to count API tokens");
+            return Ok(Redirect::to("/account/tokens?error=unknown").into_response());
+        }
+    };
+    if active >= MAX_API_TOKENS_PER_USER {
+        return Ok(Redirect::to("/account/tokens?error=token_limit").into_response());
+    }
+
+    let token = match insert_api_token(&pool, user.id, name).await {
+        Ok(token) => token,
+        Err(err) => {
+            error!(?err, "failed to create API token");
+            return Ok(Redirect::to("/account/tokens?error=unknown").into_response());
+        }
+    };
+
+    let page = render_for_user(&state, &user, &ApiTokensQuery::default(), Some(&token)).await?;
+    Ok(page.into_response())
+}
+
+/// `POST /account/tokens/revoke`: deletes one of the user's tokens.
+pub async fn revoke_api_token(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Form(form): Form<RevokeApiTokenForm>,
+) -> Result<Redirect, Redirect> {
+    let user = require_session_user(&state, &jar).await?;
+
+    let result = sqlx::query("DELETE FROM api_tokens WHERE id = $1 AND user_id = $2")
+        .bind(form.token_id)
+        .bind(user.id)
+        .execute(state.pool_ref())
+        .await;
+
+    match result {
+        Ok(res) if res.rows_affected() > 0 => {
+            Ok(Redirect::to("/account/tokens?status=token_revoked"))
+        }
+        Ok(_) => Ok(Redirect::to("/account/tokens?error=token_missing")),
+        Err(err) => {
+            error!(?err, "failed to revoke API token");
+            Ok(Redirect::to("/account/tokens?error=unknown"))
+        }
+    }
+}
+
+async fn render_for_user(
+    state: &AppState,
+    user: &AuthUser,
+    params: &ApiTokensQuery,
+    new_token: Option<&str>,
+) -> Result<Html<String>, Redirect> {
+    match list_api_tokens(state.pool_ref(), user.id).await {
+        Ok(tokens) => Ok(Html(render_tokens_page(user, &tokens, params, new_token))),
+        Err(err) => {
+            error!(?err, "failed to list API tokens");
+            Err(Redirect::to("/?error=unknown"))
+        }
+    }
+}
+
+fn compose_tokens_flash(params: &ApiTokensQuery, new_token: Option<&str>) -> String {
+    if let Some(token) = new_token {
+        return format!(
+            r#"<div class="flash success">新令牌已创建，请立即复制保存，离开本页后将无法再次查看：<code class="new-token">{token}</code></div>"#,
+            token = escape_html(token),
+        );
+    }
+
+    if params.status.as_deref() == Some("token_revoked") {
+        return r#"<div class="flash success">令牌已撤销。</div>"#.to_string();
+    }
+
+    if let Some(error) = params.error.as_deref() {
+        let message = match error {
+            "token_name_invalid" => "请填写令牌名称（不超过 64 个字符）。",
+            "token_limit" => "令牌数量已达上限，请先撤销不再使用的令牌。",
+            "token_missing" => "未找到该令牌。",
+            _ => "发生未知错误，请稍后重试。",
+        };
+        return format!(r#"<div class="flash error">{message}</div>"#);
+    }
+
+    String::new()
+}
+
+fn render_tokens_page(
+    user: &AuthUser,
+    tokens: &[ApiTokenRow],
+    params: &ApiTokensQuery,
+    new_token: Option<&str>,
+) -> String {
+    let flash = compose_tokens_flash(params, new_token);
+    let footer = render_footer();
+    let format_time = |time: &DateTime<Utc>| time.format("%Y-%m-%d %H:%M UTC").to_string();
+
+    let rows = if tokens.is_empty() {
+        r#"<tr><td colspan="5" class="empty">暂无令牌。</td></tr>"#.to_string()
+    } else {
+        tokens
+            .iter()
+            .map(|token| {
+                format!(
+                    r#"<tr><td>{name}</td><td><code>{prefix}…</code></td><td>{created}</td><td>{used}</td><td><form method="post" action="/account/tokens/revoke"><input type="hidden" name="token_id" value="{id}"><button type="submit" class="danger">撤销</button></form></td></tr>"#,
+                    name = escape_html(&token.name),
+                    prefix = escape_html(&token.token_prefix),
+                    created = format_time(&token.created_at),
+                    used = token
+                        .last_used_at
+                        .as_ref()
+                        .map(format_time)
+                        .unwrap_or_else(|| "从未使用".to_string()),
+                    id = token.id,
+                )
+            })
+            .collect()
+    };
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="zh-CN">
+<head>
+    <meta charset="UTF-8">
+    <title>API 令牌 | 张圆教授课题组 AI 工具箱</title>
+    <meta name="viewport" content="width=device-width, initial-scale=1">
+    <meta name="robots" content="noindex,nofollow">
+    <style>
+        :root {{ color-scheme: light; }}
+        body {{ font-family: "Helvetica Neue", Arial, sans-serif; margin: 0; background: #f8fafc; color: #0f172a; }}
+        main {{ padding: clamp(2rem, 5vw, 3rem); max-width: 960px; margin: 0 auto; box-sizing: border-box; }}
+        a.back {{ color: #2563eb; text-decoration: none; font-weight: 600; }}
+        h1 {{ margin: 1rem 0 0.5rem; }}
+        p.description {{ color: #475569; line-height: 1.6; }}
+        code {{ background: #e2e8f0; padding: 0.15rem 0.4rem; border-radius: 6px; font-size: 0.9rem; word-break: break-all; }}
+        .flash {{ padding: 1rem 1.25rem; border-radius: 10px; margin: 1.5rem 0; font-weight: 600; border: 1px solid transparent; }}
+        .flash.success {{ background: #ecfdf3; border-color: #bbf7d0; color: #166534; }}
+        .flash.error {{ background: #fef2f2; border-color: #fecaca; color: #b91c1c; }}
+        .new-token {{ display: block; margin-top: 0.75rem; }}
+        section {{ background: #ffffff; padding: 1.5rem 1.75rem; border-radius: 16px; border: 1px solid #e2e8f0; margin-top: 1.5rem; }}
+        section h2 {{ margin: 0 0 1rem; font-size: 1.15rem; }}
+        .create-form {{ display: flex; flex-wrap: wrap; gap: 0.75rem; }}
+        .create-form input {{ flex: 1; min-width: 220px; padding: 0.6rem 0.75rem; border: 1px solid #cbd5f5; border-radius: 8px; font-size: 0.95rem; }}
+        button {{ padding: 0.6rem 1.3rem; border: none; border-radius: 999px; background: #2563eb; color: #ffffff; font-weight: 600; cursor: pointer; }}
+        button.danger {{ background: #dc2626; padding: 0.4rem 1rem; }}
+        table {{ width: 100%; border-collapse: collapse; font-size: 0.92rem; }}
+        th, td {{ text-align: left; padding: 0.6rem 0.5rem; border-bottom: 1px solid #e2e8f0; }}
+        td.empty {{ color: #94a3b8; text-align: center; }}
+        .app-footer {{ margin-top: 3rem; text-align: center; font-size: 0.85rem; color: #94a3b8; }}
+    </style>
+</head>
+<body>
+    <main>
+        <a class="back" href="/">← 返回首页</a>
+        <h1>API 令牌</h1>
+        <p class="description">当前用户：<strong>{username}</strong>。令牌可代替浏览器登录调用各模块接口，例如 <code>curl -H "Authorization: Bearer &lt;令牌&gt;" -F "files=@paper.pdf" …/tools/summarizer/jobs</code>。令牌与账号拥有相同权限，请妥善保管，不再使用时及时撤销。</p>
+        {flash}
+        <section>
+            <h2>创建令牌</h2>
+            <form class="create-form" method="post" action="/account/tokens">
+                <input name="name" maxlength="{max_name}" placeholder="用途，例如：CI 批量摘要" required>
+                <button type="submit">创建</button>
+            </form>
+        </section>
+        <section>
+            <h2>已有令牌</h2>
+            <table>
+                <thead><tr><th>名称</th><th>前缀</th><th>创建时间</th><th>最近使用</th><th></th></tr></thead>
+                <tbody>{rows}</tbody>
+            </table>
+        </section>
+        {footer}
+    </main>
+</body>
+</html>"#,
+        username = escape_html(&user.username),
+        flash = flash,
+        max_name = MAX_TOKEN_NAME_CHARS,
+        rows = rows,
+        footer = footer,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use axum_extra::extract::cookie::Cookie;
+
+    use super::*;
+    use crate::{llm::LlmClient, test_support::create_user};
+
+    #[test]
+    fn tokens_are_random_and_hashed() {
+        let first = generate_token();
+        let second = generate_token();
+        assert!(first.starts_with(API_TOKEN_PREFIX));
+        assert_eq!(first.len(), API_TOKEN_PREFIX.len() + 64);
+        assert_ne!(first, second);
+        assert_eq!(hash_token(&first), hash_token(&first));
+        assert_ne!(hash_token(&first), hash_token(&second));
+        assert_eq!(
+            hash_token("abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[sqlx::test]
+    #[ignore = "needs DATABASE_URL (disposable Postgres)"]
+    async fn api_tokens_resolve_until_revoked(pool: PgPool) {
+        let user_id = create_user(&pool).await;
+        let state = AppState::for_tests(
+            pool.clone(),
+            LlmClient::with_openrouter_base_url("http://127.0.0.1:9"),
+        )
+        .await
+        .unwrap();
+        let token = insert_api_token(&pool, user_id, "ci").await.unwrap();
+        let jar = CookieJar::new().add(Cookie::new(API_TOKEN_COOKIE, token.clone()));
+
+        let user = auth::current_user(&state, &jar).await.unwrap();
+        assert_eq!(user.id, user_id);
+        let last_used: Option<DateTime<Utc>> =
+            sqlx::query_scalar("SELECT last_used_at FROM api_tokens WHERE user_id = $1")
+                .bind(user_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert!(last_used.is_some());
+
+        // A bearer token cannot manage tokens.
+        assert!(require_session_user(&state, &jar).await.is_err());
+
+        let wrong = CookieJar::new().add(Cookie::new(API_TOKEN_COOKIE, generate_token()));
+        assert!(auth::current_user(&state, &wrong).await.is_err());
+
+        let token_id: Uuid = sqlx::query_scalar("SELECT id FROM api_tokens WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let session = crate::test_support::session_jar(&pool, user_id).await;
+        let revoked = revoke_api_token(
+            State(state.clone()),
+            session,
+            Form(RevokeApiTokenForm { token_id }),
+        )
+        .await
+        .unwrap()
+        .into_response();
+        assert_eq!(
+            revoked.headers()["location"],
+            "/account/tokens?status=token_revoked"
+        );
+        assert!(auth::current_user(&state, &jar).await.is_err());
+    }
+}
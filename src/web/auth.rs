@@ -1,9 +1,12 @@
 use argon2::Argon2;
 use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use std::net::SocketAddr;
+
 use axum::{
-    extract::{Form, State},
-    http::StatusCode,
-    response::{Html, Redirect},
+    extract::{ConnectInfo, Form, Request, State},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    middleware::Next,
+    response::{Html, Redirect, Response},
 };
 use axum_extra::extract::cookie::{Cookie, CookieJar, SameSite};
 use chrono::{Duration as ChronoDuration, Utc};
@@ -14,7 +17,7 @@ use sqlx::PgPool;
 use tracing::{error, warn};
 use uuid::Uuid;
 
-use crate::web::{AppState, render_login_page};
+use crate::web::{AppState, api_tokens, login_limit::client_ip, render_login_page};
 
 #[derive(Debug)]
 pub enum AuthError {
@@ -51,6 +54,11 @@ impl From<sqlx::Error> for AuthError {
 }
 
 pub async fn current_user(state: &AppState, jar: &CookieJar) -> Result<AuthUser, AuthError> {
+    if let Some(api_token) = jar.get(API_TOKEN_COOKIE) {
+        let user = api_tokens::fetch_user_by_api_token(state.pool_ref(), api_token.value()).await?;
+        return user.ok_or(AuthError::InvalidToken);
+    }
+
     let token_cookie = jar.get(SESSION_COOKIE).ok_or(AuthError::MissingCookie)?;
     let token = Uuid::parse_str(token_cookie.value()).map_err(|_| AuthError::InvalidToken)?;
     let pool = state.pool();
@@ -119,6 +127,72 @@ pub struct AuthUser {
 
 pub const SESSION_COOKIE: &str = "auth_token";
 pub const SESSION_TTL_DAYS: i64 = 7;
+/// Request-internal cookie through which [`bearer_token_cookie`] hands an API token to
+/// [`current_user`]; browsers never receive it.
+pub const API_TOKEN_COOKIE: &str = "api_token";
+
+/// Router middleware that copies an `Authorization: Bearer` token into the request cookies as
+/// [`API_TOKEN_COOKIE`], so every handler resolving its user from the `CookieJar` accepts API
+/// tokens unchanged. Client-sent cookies of that name are dropped.
+pub async fn bearer_token_cookie(mut request: Request, next: Next) -> Response {
+    let bearer = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split_once(' '))
+        .filter(|(scheme, _)| scheme.eq_ignore_ascii_case("bearer"))
+        .map(|(_, token)| token.trim().to_string());
+
+    if let Some(cookie) = cookie_header_with_token(request.headers(), bearer.as_deref()) {
+        let headers = request.headers_mut();
+        headers.remove(header::COOKIE);
+        if let Some(value) = cookie {
+            headers.insert(header::COOKIE, value);
+        }
+    }
+
+    next.run(request).await
+}
+
+/// The rewritten `Cookie` header (`Some(None)` to drop it), or `None` when it can stay as is.
+fn cookie_header_with_token(
+    headers: &HeaderMap,
+    bearer: Option<&str>,
+) -> Option<Option<HeaderValue>> {
+    let is_api_cookie =
+        |pair: &str| pair.split('=').next().map(str::trim) == Some(API_TOKEN_COOKIE);
+    let pairs: Vec<&str> = headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .collect();
+    if bearer.is_none() && !pairs.iter().any(|pair| is_api_cookie(pair)) {
+        return None;
+    }
+
+    let mut kept: Vec<String> = pairs
+        .into_iter()
+        .filter(|pair| !is_api_cookie(pair))
+        .map(str::to_string)
+        .collect();
+    if let Some(token) = bearer.filter(|token| {
+        !token.is_empty()
+            && token
+                .bytes()
+                .all(|byte| byte.is_ascii_alphanumeric() || byte == b'_')
+    }) {
+        kept.push(format!("{API_TOKEN_COOKIE}={token}"));
+    }
+
+    Some(if kept.is_empty() {
+        None
+    } else {
+        HeaderValue::from_str(&kept.join("; ")).ok()
+    })
+}
 
 #[derive(Deserialize)]
 pub struct LoginForm {
@@ -139,15 +213,27 @@ pub async fn login_page(
 
 pub async fn process_login(
     State(state): State<AppState>,
+    peer: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
     jar: CookieJar,
     Form(form): Form<LoginForm>,
 ) -> Result<(CookieJar, Redirect), (StatusCode, Html<String>)> {
     let username = form.username.trim();
     let pool = state.pool();
+    let limiter = state.login_limiter();
+    let ip = client_ip(&headers, peer.map(|ConnectInfo(addr)| addr));
+
+    if let Some(wait) = limiter.retry_after(ip, username) {
+        warn!(username, ?ip, "login throttled after repeated failures");
+        return Err(too_many_attempts(wait));
+    }
 
     let user = match fetch_user_by_username(&pool, username).await {
         Ok(Some(user)) => user,
-        Ok(None) => return Err(invalid_credentials()),
+        Ok(None) => {
+            limiter.record_failure(ip, username);
+            return Err(invalid_credentials());
+        }
         Err(err) => {
             error!(?err, "failed to fetch user during login");
             return Err(server_error());
@@ -155,8 +241,10 @@ pub async fn process_login(
     };
 
     if !verify_password(&form.password, &user.password_hash) {
+        limiter.record_failure(ip, username);
         return Err(invalid_credentials());
     }
+    limiter.reset(ip, username);
 
     let session_token = Uuid::new_v4();
     let expires_at = Utc::now() + ChronoDuration::days(SESSION_TTL_DAYS);
@@ -183,6 +271,112 @@ pub async fn process_login(
     Ok((jar, Redirect::to("/")))
 }
 
+/// Minimum length accepted for a self-chosen password.
+pub const MIN_PASSWORD_LENGTH: usize = 8;
+
+#[derive(Deserialize)]
+pub struct ChangePasswordForm {
+    pub current_password: String,
+    pub new_password: String,
+    pub confirm_password: String,
+}
+
+/// Checks a self-chosen password: at least [`MIN_PASSWORD_LENGTH`] characters, both letters
+/// and digits, and not the username itself. Returns the landing-page error code on failure.
+pub fn password_strength_error(password: &str, username: &str) -> Option<&'static str> {
+    let long_enough = password.chars().count() >= MIN_PASSWORD_LENGTH;
+    let has_letter = password.chars().any(char::is_alphabetic);
+    let has_digit = password.chars().any(|ch| ch.is_ascii_digit());
+    let is_username = password.eq_ignore_ascii_case(username);
+
+    (!long_enough || !has_letter || !has_digit || is_username).then_some("password_weak")
+}
+
+/// `POST /account/password`: lets a signed-in user replace their password after confirming the
+/// current one. Every other session of the user is revoked; the current one stays signed in.
+pub async fn change_password(
+    State(state): State<AppState>,
+    peer: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
+    jar: CookieJar,
+    Form(form): Form<ChangePasswordForm>,
+) -> Result<Redirect, Redirect> {
+    let user = require_user_redirect(&state, &jar).await?;
+    let session_token = jar
+        .get(SESSION_COOKIE)
+        .and_then(|cookie| Uuid::parse_str(cookie.value()).ok())
+        .ok_or_else(|| Redirect::to("/login"))?;
+    let limiter = state.login_limiter();
+    let ip = client_ip(&headers, peer.map(|ConnectInfo(addr)| addr));
+
+    if limiter.retry_after(ip, &user.username).is_some() {
+        warn!(username = %user.username, "password change throttled after repeated failures");
+        return Ok(Redirect::to("/?error=password_throttled"));
+    }
+
+    let pool = state.pool();
+    let stored = match fetch_user_by_username(&pool, &user.username).await {
+        Ok(Some(stored)) => stored,
+        Ok(None) => return Err(Redirect::to("/login")),
+        Err(err) => {
+            error!(?err, "failed to fetch user during password change");
+            return Ok(Redirect::to("/?error=unknown"));
+        }
+    };
+
+    if !verify_password(&form.current_password, &stored.password_hash) {
+        limiter.record_failure(ip, &user.username);
+        return Ok(Redirect::to("/?error=password_incorrect"));
+    }
+
+    if form.new_password != form.confirm_password {
+        return Ok(Redirect::to("/?error=password_mismatch"));
+    }
+    if form.new_password == form.current_password {
+        return Ok(Redirect::to("/?error=password_unchanged"));
+    }
+    if let Some(code) = password_strength_error(&form.new_password, &user.username) {
+        return Ok(Redirect::to(&format!("/?error={code}")));
+    }
+
+    let password_hash = match hash_password(&form.new_password) {
+        Ok(hash) => hash,
+        Err(err) => {
+            error!(?err, "failed to hash password during password change");
+            return Ok(Redirect::to("/?error=unknown"));
+        }
+    };
+
+    if let Err(err) = replace_password(&pool, user.id, &password_hash, session_token).await {
+        error!(?err, "failed to update password");
+        return Ok(Redirect::to("/?error=unknown"));
+    }
+    limiter.reset(ip, &user.username);
+
+    Ok(Redirect::to("/?status=password_changed"))
+}
+
+/// Stores the new hash and drops every session of the user except `keep_session`.
+async fn replace_password(
+    pool: &PgPool,
+    user_id: Uuid,
+    password_hash: &str,
+    keep_session: Uuid,
+) -> sqlx::Result<()> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("UPDATE users SET password_hash = $2 WHERE id = $1")
+        .bind(user_id)
+        .bind(password_hash)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("DELETE FROM sessions WHERE user_id = $1 AND id <> $2")
+        .bind(user_id)
+        .bind(keep_session)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await
+}
+
 pub async fn logout(State(state): State<AppState>, jar: CookieJar) -> (CookieJar, Redirect) {
     let mut jar = jar;
 
@@ -266,9 +460,218 @@ fn invalid_credentials() -> (StatusCode, Html<String>) {
     )
 }
 
+fn too_many_attempts(wait: std::time::Duration) -> (StatusCode, Html<String>) {
+    let minutes = wait.as_secs().div_ceil(60).max(1);
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        Html(format!(
+            "<h1>登录受限</h1><p>登录失败次数过多，请 {minutes} 分钟后再试。</p>"
+        )),
+    )
+}
+
 fn server_error() -> (StatusCode, Html<String>) {
     (
         StatusCode::INTERNAL_SERVER_ERROR,
         Html("<h1>服务器错误</h1><p>请稍后再试。</p>".to_string()),
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use axum::response::IntoResponse;
+
+    use super::*;
+    use crate::{
+        llm::LlmClient,
+        test_support::{create_user, session_jar},
+        web::login_limit::DEFAULT_MAX_LOGIN_FAILURES,
+    };
+
+    #[test]
+    fn bearer_tokens_replace_client_api_cookies() {
+        let mut headers = HeaderMap::new();
+        assert!(cookie_header_with_token(&headers, None).is_none());
+
+        headers.insert(header::COOKIE, "auth_token=abc".parse().unwrap());
+        assert!(cookie_header_with_token(&headers, None).is_none());
+        assert_eq!(
+            cookie_header_with_token(&headers, Some("aitk_0f")).unwrap(),
+            Some(HeaderValue::from_static(
+                "auth_token=abc; api_token=aitk_0f"
+            ))
+        );
+
+        headers.insert(
+            header::COOKIE,
+            "api_token=forged; theme=dark".parse().unwrap(),
+        );
+        assert_eq!(
+            cookie_header_with_token(&headers, None).unwrap(),
+            Some(HeaderValue::from_static("theme=dark"))
+        );
+        assert_eq!(
+            cookie_header_with_token(&headers, Some("bad;token")).unwrap(),
+            Some(HeaderValue::from_static("theme=dark"))
+        );
+
+        headers.insert(header::COOKIE, "api_token=forged".parse().unwrap());
+        assert_eq!(cookie_header_with_token(&headers, None).unwrap(), None);
+    }
+
+    #[test]
+    fn weak_passwords_are_rejected() {
+        assert_eq!(
+            password_strength_error("abc123", "alice"),
+            Some("password_weak")
+        );
+        assert_eq!(
+            password_strength_error("abcdefghij", "alice"),
+            Some("password_weak")
+        );
+        assert_eq!(
+            password_strength_error("1234567890", "alice"),
+            Some("password_weak")
+        );
+        assert_eq!(
+            password_strength_error("Alice2024", "alice2024"),
+            Some("password_weak")
+        );
+        assert_eq!(password_strength_error("课题组密码2024", "alice"), None);
+        assert_eq!(password_strength_error("correct horse 9", "alice"), None);
+    }
+
+    #[sqlx::test]
+    #[ignore = "needs DATABASE_URL (disposable Postgres)"]
+    async fn password_change_revokes_other_sessions(pool: PgPool) {
+        let user_id = create_user(&pool).await;
+        sqlx::query("UPDATE users SET password_hash = $1 WHERE id = $2")
+            .bind(hash_password("old password 1").unwrap())
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let jar = session_jar(&pool, user_id).await;
+        let other = session_jar(&pool, user_id).await;
+        let state = AppState::for_tests(
+            pool.clone(),
+            LlmClient::with_openrouter_base_url("http://127.0.0.1:9"),
+        )
+        .await
+        .unwrap();
+
+        let change = |current: &'static str, new: &'static str, confirm: &'static str| {
+            let state = state.clone();
+            let jar = jar.clone();
+            async move {
+                let redirect = change_password(
+                    State(state),
+                    None,
+                    HeaderMap::new(),
+                    jar,
+                    Form(ChangePasswordForm {
+                        current_password: current.to_string(),
+                        new_password: new.to_string(),
+                        confirm_password: confirm.to_string(),
+                    }),
+                )
+                .await
+                .unwrap();
+                redirect
+                    .into_response()
+                    .headers()
+                    .get("location")
+                    .unwrap()
+                    .to_str()
+                    .unwrap()
+                    .to_string()
+            }
+        };
+
+        assert_eq!(
+            change("wrong", "new password 2", "new password 2").await,
+            "/?error=password_incorrect"
+        );
+        assert_eq!(
+            change("old password 1", "new password 2", "new password 3").await,
+            "/?error=password_mismatch"
+        );
+        assert_eq!(
+            change("old password 1", "short1", "short1").await,
+            "/?error=password_weak"
+        );
+        assert_eq!(
+            change("old password 1", "new password 2", "new password 2").await,
+            "/?status=password_changed"
+        );
+
+        let hash: String = sqlx::query_scalar("SELECT password_hash FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert!(verify_password("new password 2", &hash));
+        assert!(current_user(&state, &jar).await.is_ok());
+        assert!(matches!(
+            current_user(&state, &other).await,
+            Err(AuthError::SessionExpired)
+        ));
+    }
+
+    #[sqlx::test]
+    #[ignore = "needs DATABASE_URL (disposable Postgres)"]
+    async fn repeated_login_failures_are_throttled(pool: PgPool) {
+        let user_id = create_user(&pool).await;
+        let username: String = sqlx::query_scalar("SELECT username FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        sqlx::query("UPDATE users SET password_hash = $1 WHERE id = $2")
+            .bind(hash_password("correct horse").unwrap())
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let state = AppState::for_tests(
+            pool.clone(),
+            LlmClient::with_openrouter_base_url("http://127.0.0.1:9"),
+        )
+        .await
+        .unwrap();
+
+        let attempt = |password: &'static str| {
+            let state = state.clone();
+            let username = username.clone();
+            async move {
+                process_login(
+                    State(state),
+                    None,
+                    HeaderMap::new(),
+                    CookieJar::new(),
+                    Form(LoginForm {
+                        username,
+                        password: password.to_string(),
+                    }),
+                )
+                .await
+                .map(|_| StatusCode::SEE_OTHER)
+                .unwrap_or_else(|(status, _)| status)
+            }
+        };
+
+        // A successful login clears earlier failures.
+        for _ in 1..DEFAULT_MAX_LOGIN_FAILURES {
+            assert_eq!(attempt("wrong").await, StatusCode::UNAUTHORIZED);
+        }
+        assert_eq!(attempt("correct horse").await, StatusCode::SEE_OTHER);
+
+        for _ in 0..DEFAULT_MAX_LOGIN_FAILURES {
+            assert_eq!(attempt("wrong").await, StatusCode::UNAUTHORIZED);
+        }
+        assert_eq!(
+            attempt("correct horse").await,
+            StatusCode::TOO_MANY_REQUESTS
+        );
+    }
+}
@@ -4,7 +4,7 @@ use super::models::{GlossaryTermRow, JournalReferenceRow, JournalTopicRow, Journ
 
 pub async fn fetch_glossary_terms(pool: &PgPool) -> sqlx::Result<Vec<GlossaryTermRow>> {
     sqlx::query_as::<_, GlossaryTermRow>(
-        "SELECT id, source_term, target_term, notes, created_at, updated_at FROM glossary_terms ORDER BY source_term",
+        "SELECT id, source_term, target_term, notes, scope, created_at, updated_at FROM glossary_terms ORDER BY source_term, scope NULLS FIRST",
     )
     .fetch_all(pool)
     .await
@@ -0,0 +1,192 @@
+use std::{
+    collections::HashMap,
+    env,
+    sync::{Arc, Mutex},
+};
+
+use axum::{
+    Json,
+    body::{Body, Bytes},
+    http::StatusCode,
+    response::Response,
+};
+use futures::{StreamExt, stream};
+use uuid::Uuid;
+
+use crate::web::{ApiMessage, AuthUser, json_error};
+
+/// Default number of simultaneous downloads a single non-admin user may run.
+pub const DEFAULT_MAX_CONCURRENT_DOWNLOADS: usize = 3;
+/// In-memory bodies are handed to the connection in pieces of this size, so the permit is
+/// only released once the last piece has been taken.
+const BODY_CHUNK_BYTES: usize = 64 * 1024;
+
+/// Caps how many downloads each user can have in flight at once.
+///
+/// Configured via `MAX_CONCURRENT_DOWNLOADS_PER_USER`; `0` disables the limit.
+/// Admins are never throttled.
+#[derive(Clone)]
+pub struct DownloadLimiter {
+    limit: usize,
+    active: Arc<Mutex<HashMap<Uuid, usize>>>,
+}
+
+impl DownloadLimiter {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            active: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let limit = env::var("MAX_CONCURRENT_DOWNLOADS_PER_USER")
+            .ok()
+            .and_then(|value| value.trim().parse::<usize>().ok())
+            .unwrap_or(DEFAULT_MAX_CONCURRENT_DOWNLOADS);
+        Self::new(limit)
+    }
+
+    /// Reserves a download slot for `user`, or rejects with 429 when the user is at the limit.
+    /// Returns `Ok(None)` when no slot is needed (admins, or the limit is disabled).
+    pub fn acquire(
+        &self,
+        user: &AuthUser,
+    ) -> Result<Option<DownloadPermit>, (StatusCode, Json<ApiMessage>)> {
+        if user.is_admin || self.limit == 0 {
+            return Ok(None);
+        }
+
+        self.try_acquire(user.id).map(Some).ok_or_else(|| {
+            json_error(
+                StatusCode::TOO_MANY_REQUESTS,
+                "同时进行的下载过多，请等待当前下载完成后再试。",
+            )
+        })
+    }
+
+    fn try_acquire(&self, user_id: Uuid) -> Option<DownloadPermit> {
+        let mut active = self.active.lock().unwrap_or_else(|err| err.into_inner());
+        let count = active.entry(user_id).or_insert(0);
+        if *count >= self.limit {
+            return None;
+        }
+        *count += 1;
+
+        Some(DownloadPermit {
+            user_id,
+            active: Arc::clone(&self.active),
+        })
+    }
+}
+
+/// Held for the duration of a download; releases the user's slot when dropped.
+pub struct DownloadPermit {
+    user_id: Uuid,
+    active: Arc<Mutex<HashMap<Uuid, usize>>>,
+}
+
+impl Drop for DownloadPermit {
+    fn drop(&mut self) {
+        let mut active = self.active.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some(count) = active.get_mut(&self.user_id) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                active.remove(&self.user_id);
+            }
+        }
+    }
+}
+
+/// Moves `permit` into `response`'s body, keeping the download slot reserved until the body
+/// has been fully sent or the client disconnects.
+pub fn hold_permit(response: Response, permit: Option<DownloadPermit>) -> Response {
+    let Some(permit) = permit else {
+        return response;
+    };
+
+    let (parts, body) = response.into_parts();
+    let body = Body::from_stream(stream::unfold(
+        (body.into_data_stream(), Bytes::new(), permit),
+        |(mut data, mut pending, permit)| async move {
+            while pending.is_empty() {
+                match data.next().await? {
+                    Ok(chunk) => pending = chunk,
+                    Err(err) => return Some((Err(err), (data, pending, permit))),
+                }
+            }
+            let piece = pending.split_to(pending.len().min(BODY_CHUNK_BYTES));
+            Some((Ok(piece), (data, pending, permit)))
+        },
+    ));
+    Response::from_parts(parts, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::response::IntoResponse;
+
+    use super::*;
+
+    fn user(is_admin: bool) -> AuthUser {
+        AuthUser {
+            id: Uuid::new_v4(),
+            username: "someone".to_string(),
+            is_admin,
+        }
+    }
+
+    #[test]
+    fn downloads_beyond_limit_are_throttled() {
+        let limiter = DownloadLimiter::new(2);
+        let alice = user(false);
+        let bob = user(false);
+
+        let first = limiter.acquire(&alice).expect("first slot");
+        let _second = limiter.acquire(&alice).expect("second slot");
+        let (status, _) = limiter.acquire(&alice).err().expect("third is throttled");
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+
+        // Other users are unaffected.
+        assert!(limiter.acquire(&bob).is_ok());
+
+        drop(first);
+        assert!(limiter.acquire(&alice).is_ok());
+    }
+
+    #[tokio::test]
+    async fn held_permit_is_released_with_the_body() {
+        let limiter = DownloadLimiter::new(1);
+        let alice = user(false);
+        let payload = vec![7u8; 3 * BODY_CHUNK_BYTES + 5];
+
+        let permit = limiter.acquire(&alice).unwrap();
+        let response = hold_permit(Body::from(payload.clone()).into_response(), permit);
+        assert!(limiter.acquire(&alice).is_err());
+
+        let sent = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(sent.as_ref(), payload.as_slice());
+        assert!(limiter.acquire(&alice).is_ok());
+
+        // A client that goes away mid-download frees the slot too.
+        let permit = limiter.acquire(&alice).unwrap();
+        drop(hold_permit(Body::from(payload).into_response(), permit));
+        assert!(limiter.acquire(&alice).is_ok());
+    }
+
+    #[test]
+    fn admins_and_disabled_limit_are_exempt() {
+        let limiter = DownloadLimiter::new(1);
+        let admin = user(true);
+        let _a = limiter.acquire(&admin).unwrap();
+        let _b = limiter.acquire(&admin).unwrap();
+        assert!(limiter.acquire(&admin).unwrap().is_none());
+
+        let unlimited = DownloadLimiter::new(0);
+        let regular = user(false);
+        let _c = unlimited.acquire(&regular).unwrap();
+        assert!(unlimited.acquire(&regular).is_ok());
+    }
+}
@@ -0,0 +1,551 @@
+use std::{
+    fs::File,
+    io::{self, Seek, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result, anyhow};
+use axum::{
+    Json,
+    body::{Body, Bytes},
+    extract::State,
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use axum_extra::extract::cookie::CookieJar;
+use chrono::{DateTime, Utc};
+use futures::stream;
+use serde::Serialize;
+use sqlx::PgPool;
+use tokio::{fs as tokio_fs, io::AsyncReadExt, task};
+use tracing::{error, warn};
+use uuid::Uuid;
+use zip::{ZipWriter, write::SimpleFileOptions};
+
+use crate::{
+    usage::{
+        MODULE_DOCX_TO_PDF, MODULE_INFO_EXTRACT, MODULE_REVIEWER, MODULE_SUMMARIZER,
+        MODULE_TRANSCRIBE, MODULE_TRANSLATE_DOCX,
+    },
+    web::{
+        ApiMessage, AppState, StoredFile,
+        auth::{self, JsonAuthError},
+        ensure_storage_root, json_error, stored_file_exists,
+    },
+};
+
+const EXPORT_STORAGE_ROOT: &str = "storage/exports";
+const MANIFEST_NAME: &str = "manifest.json";
+const STREAM_CHUNK_BYTES: usize = 64 * 1024;
+
+/// One completed output file that belongs in a user's data export.
+#[derive(Debug, Clone)]
+pub(crate) struct ExportEntry {
+    pub module: &'static str,
+    pub job_key: String,
+    pub kind: &'static str,
+    pub original_filename: Option<String>,
+    pub path: PathBuf,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ExportEntry {
+    fn archive_path(&self) -> String {
+        let basename = self
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| "output".to_string());
+        format!("{}/{}/{}", self.module, self.job_key, basename)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct ExportManifest {
+    generated_at: String,
+    user_id: Uuid,
+    files: Vec<ManifestFile>,
+    skipped: Vec<ManifestSkipped>,
+}
+
+#[derive(Debug, Serialize)]
+struct ManifestFile {
+    module: &'static str,
+    job_key: String,
+    kind: &'static str,
+    original_filename: Option<String>,
+    archive_path: String,
+    size_bytes: u64,
+    created_at: String,
+}
+
+#[derive(Debug, Serialize)]
+struct ManifestSkipped {
+    module: &'static str,
+    job_key: String,
+    kind: &'static str,
+    reason: String,
+}
+
+/// `GET /api/export/me`: streams a ZIP of every completed, unpurged output the caller owns.
+pub async fn export_my_outputs(
+    State(state): State<AppState>,
+    jar: CookieJar,
+) -> Result<Response, (StatusCode, Json<ApiMessage>)> {
+    let user = auth::current_user_or_json_error(&state, &jar)
+        .await
+        .map_err(|JsonAuthError { status, message }| json_error(status, message))?;
+
+    let permit = state.download_limiter().acquire(&user)?;
+
+    let entries = collect_export_entries(&state.pool(), user.id)
+        .await
+        .map_err(|err| export_error(err, user.id))?;
+
+    ensure_storage_root(EXPORT_STORAGE_ROOT)
+        .await
+        .map_err(|err| export_error(err, user.id))?;
+    let archive_path = Path::new(EXPORT_STORAGE_ROOT).join(format!("{}.zip", Uuid::new_v4()));
+
+    let build_path = archive_path.clone();
+    let user_id = user.id;
+    let build = task::spawn_blocking(move || -> Result<()> {
+        let file = File::create(&build_path).context("failed to create export archive")?;
+        write_export_archive(file, user_id, &entries)?;
+        Ok(())
+    })
+    .await
+    .map_err(|err| anyhow!("export worker panicked: {}", err))
+    .and_then(|result| result);
+
+    if let Err(err) = build {
+        let _ = tokio_fs::remove_file(&archive_path).await;
+        return Err(export_error(err, user.id));
+    }
+
+    let file = tokio_fs::File::open(&archive_path)
+        .await
+        .context("failed to reopen export archive")
+        .map_err(|err| export_error(err, user.id))?;
+    // The open handle keeps the data readable; unlinking now means nothing is left behind
+    // if the client disconnects mid-download.
+    if let Err(err) = tokio_fs::remove_file(&archive_path).await {
+        warn!(?err, file = %archive_path.display(), "failed to remove export archive");
+    }
+
+    // The download slot stays reserved until the archive has been fully streamed.
+    let body = Body::from_stream(stream::unfold(
+        (file, permit),
+        |(mut file, permit)| async move {
+            let mut buffer = vec![0u8; STREAM_CHUNK_BYTES];
+            match file.read(&mut buffer).await {
+                Ok(0) => None,
+                Ok(read) => {
+                    buffer.truncate(read);
+                    Some((Ok::<_, io::Error>(Bytes::from(buffer)), (file, permit)))
+                }
+                Err(err) => Some((Err(err), (file, permit))),
+            }
+        },
+    ));
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/zip"),
+    );
+    let disposition = format!(
+        "attachment; filename=\"ai-toolkit-export-{}.zip\"",
+        Utc::now().format("%Y%m%d")
+    );
+    if let Ok(value) = HeaderValue::from_str(&disposition) {
+        headers.insert(header::CONTENT_DISPOSITION, value);
+    }
+
+    Ok((headers, body).into_response())
+}
+
+fn export_error(err: anyhow::Error, user_id: Uuid) -> (StatusCode, Json<ApiMessage>) {
+    error!(?err, %user_id, "failed to build data export");
+    json_error(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "导出数据失败，请稍后再试。",
+    )
+}
+
+type SummaryDocumentRow = (Uuid, String, Option<String>, Option<String>, DateTime<Utc>);
+
+/// Lists completed output files for `user_id` across modules, skipping purged jobs.
+pub(crate) async fn collect_export_entries(
+    pool: &PgPool,
+    user_id: Uuid,
+) -> Result<Vec<ExportEntry>> {
+    let mut entries = Vec::new();
+
+    let summary_docs = sqlx::query_as::<_, SummaryDocumentRow>(
+        "SELECT d.job_id, d.original_filename, d.summary_path, d.translation_path, j.created_at
+         FROM summary_documents d
+         JOIN summary_jobs j ON j.id = d.job_id
+         WHERE j.user_id = $1 AND j.files_purged_at IS NULL
+         ORDER BY j.created_at, d.ordinal",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+    .context("failed to load summarizer outputs")?;
+
+    for (job_id, original, summary_path, translation_path, created_at) in summary_docs {
+        for (kind, path) in [("summary", summary_path), ("translation", translation_path)] {
+            if let Some(path) = path {
+                entries.push(ExportEntry {
+                    module: MODULE_SUMMARIZER,
+                    job_key: job_id.to_string(),
+                    kind,
+                    original_filename: Some(original.clone()),
+                    path: PathBuf::from(path),
+                    created_at,
+                });
+            }
+        }
+    }
+
+    let summary_jobs = sqlx::query_as::<_, (Uuid, Option<String>, Option<String>, DateTime<Utc>)>(
+        "SELECT id, combined_summary_path, combined_translation_path, created_at
+         FROM summary_jobs
+         WHERE user_id = $1 AND files_purged_at IS NULL
+         ORDER BY created_at",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+    .context("failed to load summarizer combined outputs")?;
+
+    for (job_id, combined_summary, combined_translation, created_at) in summary_jobs {
+        for (kind, path) in [
+            ("combined_summary", combined_summary),
+            ("combined_translation", combined_translation),
+        ] {
+            if let Some(path) = path {
+                entries.push(ExportEntry {
+                    module: MODULE_SUMMARIZER,
+                    job_key: job_id.to_string(),
+                    kind,
+                    original_filename: None,
+                    path: PathBuf::from(path),
+                    created_at,
+                });
+            }
+        }
+    }
+
+    let docx_docs = sqlx::query_as::<_, (Uuid, String, String, DateTime<Utc>)>(
+        "SELECT d.job_id, d.original_filename, d.translated_path, j.created_at
+         FROM docx_documents d
+         JOIN docx_jobs j ON j.id = d.job_id
+         WHERE j.user_id = $1 AND j.files_purged_at IS NULL AND d.translated_path IS NOT NULL
+         ORDER BY j.created_at, d.job_id, d.ordinal",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+    .context("failed to load DOCX translation outputs")?;
+
+    for (job_id, original, path, created_at) in docx_docs {
+        entries.push(ExportEntry {
+            module: MODULE_TRANSLATE_DOCX,
+            job_key: job_id.to_string(),
+            kind: "translation",
+            original_filename: Some(original),
+            path: PathBuf::from(path),
+            created_at,
+        });
+    }
+
+    let docx_pdfs = sqlx::query_as::<_, (Uuid, String, String, DateTime<Utc>)>(
+        "SELECT d.job_id, d.original_filename, d.translated_pdf_path, j.created_at
+         FROM docx_documents d
+         JOIN docx_jobs j ON j.id = d.job_id
+         WHERE j.user_id = $1 AND j.files_purged_at IS NULL AND d.translated_pdf_path IS NOT NULL
+         ORDER BY j.created_at, d.job_id, d.ordinal",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+    .context("failed to load DOCX translation PDFs")?;
+
+    for (job_id, original, path, created_at) in docx_pdfs {
+        entries.push(ExportEntry {
+            module: MODULE_TRANSLATE_DOCX,
+            job_key: job_id.to_string(),
+            kind: "translation_pdf",
+            original_filename: Some(original),
+            path: PathBuf::from(path),
+            created_at,
+        });
+    }
+
+    let extract_jobs = sqlx::query_as::<_, (Uuid, String, DateTime<Utc>)>(
+        "SELECT id, result_path, created_at
+         FROM info_extract_jobs
+         WHERE user_id = $1 AND files_purged_at IS NULL AND result_path IS NOT NULL
+         ORDER BY created_at",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+    .context("failed to load info extract outputs")?;
+
+    for (job_id, path, created_at) in extract_jobs {
+        let workbook = PathBuf::from(path);
+        let csv = workbook.with_extension("csv");
+        entries.push(ExportEntry {
+            module: MODULE_INFO_EXTRACT,
+            job_key: job_id.to_string(),
+            kind: "result_xlsx",
+            original_filename: None,
+            path: workbook,
+            created_at,
+        });
+        if stored_file_exists(&csv).await {
+            entries.push(ExportEntry {
+                module: MODULE_INFO_EXTRACT,
+                job_key: job_id.to_string(),
+                kind: "result_csv",
+                original_filename: None,
+                path: csv,
+                created_at,
+            });
+        }
+    }
+
+    let reviews = sqlx::query_as::<_, (i32, String, String, DateTime<Utc>)>(
+        "SELECT j.job_id, j.filename, d.file_path, j.created_at
+         FROM reviewer_documents d
+         JOIN reviewer_jobs j ON j.job_id = d.job_id
+         WHERE j.user_id = $1 AND j.files_purged_at IS NULL
+           AND d.status = 'completed' AND d.file_path IS NOT NULL
+         ORDER BY j.created_at, d.round, d.review_index",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+    .context("failed to load reviewer outputs")?;
+
+    for (job_id, filename, path, created_at) in reviews {
+        entries.push(ExportEntry {
+            module: MODULE_REVIEWER,
+            job_key: job_id.to_string(),
+            kind: "review",
+            original_filename: Some(filename),
+            path: PathBuf::from(path),
+            created_at,
+        });
+    }
+
+    let transcripts = sqlx::query_as::<_, (Uuid, String, String, DateTime<Utc>)>(
+        "SELECT id, original_filename, transcript_path, created_at
+         FROM transcribe_jobs
+         WHERE user_id = $1 AND files_purged_at IS NULL
+           AND status = 'completed' AND transcript_path IS NOT NULL
+         ORDER BY created_at",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+    .context("failed to load transcription outputs")?;
+
+    for (job_id, filename, path, created_at) in transcripts {
+        entries.push(ExportEntry {
+            module: MODULE_TRANSCRIBE,
+            job_key: job_id.to_string(),
+            kind: "transcript",
+            original_filename: Some(filename),
+            path: PathBuf::from(path),
+            created_at,
+        });
+    }
+
+    let converted_pdfs = sqlx::query_as::<_, (Uuid, String, String, DateTime<Utc>)>(
+        "SELECT id, original_filename, pdf_path, created_at
+         FROM docx2pdf_jobs
+         WHERE user_id = $1 AND files_purged_at IS NULL
+           AND status = 'completed' AND pdf_path IS NOT NULL
+         ORDER BY created_at",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+    .context("failed to load DOCX to PDF outputs")?;
+
+    for (job_id, filename, path, created_at) in converted_pdfs {
+        entries.push(ExportEntry {
+            module: MODULE_DOCX_TO_PDF,
+            job_key: job_id.to_string(),
+            kind: "pdf",
+            original_filename: Some(filename),
+            path: PathBuf::from(path),
+            created_at,
+        });
+    }
+
+    Ok(entries)
+}
+
+/// Writes `entries` into a ZIP one file at a time, followed by a JSON manifest.
+/// Files missing from disk are listed under `skipped` rather than failing the export.
+pub(crate) fn write_export_archive<W: Write + Seek>(
+    writer: W,
+    user_id: Uuid,
+    entries: &[ExportEntry],
+) -> Result<ExportManifest> {
+    let mut zip = ZipWriter::new(writer);
+    let options = SimpleFileOptions::default().large_file(true);
+
+    let mut manifest = ExportManifest {
+        generated_at: Utc::now().to_rfc3339(),
+        user_id,
+        files: Vec::new(),
+        skipped: Vec::new(),
+    };
+
+    for entry in entries {
+        let mut source = match StoredFile::open_blocking(&entry.path) {
+            Ok(source) => source,
+            Err(err) => {
+                manifest.skipped.push(ManifestSkipped {
+                    module: entry.module,
+                    job_key: entry.job_key.clone(),
+                    kind: entry.kind,
+                    reason: err.to_string(),
+                });
+                continue;
+            }
+        };
+
+        let archive_path = entry.archive_path();
+        zip.start_file(archive_path.as_str(), options)
+            .with_context(|| format!("failed to add {} to export", archive_path))?;
+        let size_bytes = source
+            .copy_to(&mut zip)
+            .with_context(|| format!("failed to copy {} into export", entry.path.display()))?;
+
+        manifest.files.push(ManifestFile {
+            module: entry.module,
+            job_key: entry.job_key.clone(),
+            kind: entry.kind,
+            original_filename: entry.original_filename.clone(),
+            archive_path,
+            size_bytes,
+            created_at: entry.created_at.to_rfc3339(),
+        });
+    }
+
+    zip.start_file(MANIFEST_NAME, options)
+        .context("failed to add export manifest")?;
+    serde_json::to_writer_pretty(&mut zip, &manifest).context("failed to write export manifest")?;
+    zip.finish().context("failed to finalize export archive")?;
+
+    Ok(manifest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Read};
+    use zip::ZipArchive;
+
+    #[test]
+    fn export_archive_includes_outputs_from_each_module() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let summary = dir.path().join("summary_1.txt");
+        let workbook = dir.path().join("extraction_result.xlsx");
+        std::fs::write(&summary, "摘要内容").unwrap();
+        std::fs::write(&workbook, b"xlsx-bytes").unwrap();
+        let archived_dir = dir.path().join("archived-job");
+        std::fs::create_dir_all(&archived_dir).unwrap();
+        let archived = archived_dir.join("review.docx");
+        std::fs::write(&archived, b"docx-bytes").unwrap();
+        assert!(crate::web::storage::archive_job_directory(&archived_dir).unwrap());
+
+        let summary_job = Uuid::new_v4();
+        let extract_job = Uuid::new_v4();
+        let now = Utc::now();
+        let entries = vec![
+            ExportEntry {
+                module: MODULE_SUMMARIZER,
+                job_key: summary_job.to_string(),
+                kind: "summary",
+                original_filename: Some("paper.pdf".to_string()),
+                path: summary,
+                created_at: now,
+            },
+            ExportEntry {
+                module: MODULE_INFO_EXTRACT,
+                job_key: extract_job.to_string(),
+                kind: "result_xlsx",
+                original_filename: None,
+                path: workbook,
+                created_at: now,
+            },
+            ExportEntry {
+                module: MODULE_REVIEWER,
+                job_key: "6".to_string(),
+                kind: "review",
+                original_filename: Some("archived.docx".to_string()),
+                path: archived,
+                created_at: now,
+            },
+            ExportEntry {
+                module: MODULE_REVIEWER,
+                job_key: "7".to_string(),
+                kind: "review",
+                original_filename: Some("draft.docx".to_string()),
+                path: dir.path().join("purged.docx"),
+                created_at: now,
+            },
+        ];
+
+        let mut buffer = Cursor::new(Vec::new());
+        let manifest =
+            write_export_archive(&mut buffer, Uuid::new_v4(), &entries).expect("write archive");
+        assert_eq!(manifest.files.len(), 3);
+        assert_eq!(manifest.skipped.len(), 1);
+        assert_eq!(manifest.files[2].size_bytes, 10);
+
+        let mut archive = ZipArchive::new(Cursor::new(buffer.into_inner())).expect("read zip");
+        let mut summary_text = String::new();
+        archive
+            .by_name(&format!("summarizer/{summary_job}/summary_1.txt"))
+            .expect("summary entry")
+            .read_to_string(&mut summary_text)
+            .unwrap();
+        assert_eq!(summary_text, "摘要内容");
+        archive
+            .by_name(&format!(
+                "info_extract/{extract_job}/extraction_result.xlsx"
+            ))
+            .expect("workbook entry");
+        let mut archived_bytes = Vec::new();
+        archive
+            .by_name("reviewer/6/review.docx")
+            .expect("archived entry")
+            .read_to_end(&mut archived_bytes)
+            .unwrap();
+        assert_eq!(archived_bytes, b"docx-bytes");
+
+        let mut manifest_json = String::new();
+        archive
+            .by_name(MANIFEST_NAME)
+            .expect("manifest entry")
+            .read_to_string(&mut manifest_json)
+            .unwrap();
+        let manifest: serde_json::Value = serde_json::from_str(&manifest_json).unwrap();
+        let modules: Vec<&str> = manifest["files"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|file| file["module"].as_str())
+            .collect();
+        assert_eq!(modules, vec!["summarizer", "info_extract", "reviewer"]);
+    }
+}
@@ -4,23 +4,89 @@ use axum::{
     http::StatusCode,
 };
 use axum_extra::extract::cookie::CookieJar;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use serde::Deserialize;
+use std::collections::HashMap;
 use tracing::error;
 
-use crate::history;
+use crate::history::{self, HistoryEntry, HistoryFilter};
 use crate::web::{
     ApiMessage, AppState, JobStatus,
     auth::{self, JsonAuthError},
     json_error,
 };
 
-#[derive(Deserialize)]
+/// Page size when the request names none.
+const DEFAULT_PAGE_SIZE: i64 = 20;
+
+#[derive(Deserialize, Default)]
 pub struct HistoryQuery {
     #[serde(default)]
     module: Option<String>,
     #[serde(default)]
+    page: Option<i64>,
+    #[serde(default)]
+    page_size: Option<i64>,
+    /// Older name for `page_size`, still accepted.
+    #[serde(default)]
     limit: Option<i64>,
+    /// Case-insensitive substring of an uploaded filename.
+    #[serde(default)]
+    filename: Option<String>,
+    #[serde(default)]
+    status: Option<String>,
+    /// RFC 3339 lower bound (inclusive) on the submission time.
+    #[serde(default)]
+    from: Option<String>,
+    /// RFC 3339 upper bound (exclusive) on the submission time.
+    #[serde(default)]
+    to: Option<String>,
+}
+
+impl HistoryQuery {
+    /// The requested 1-based page and its size, clamped to `1..=history::MAX_PAGE_SIZE`.
+    fn page_params(&self) -> (i64, i64) {
+        let page = self.page.unwrap_or(1).max(1);
+        let page_size = self
+            .page_size
+            .or(self.limit)
+            .unwrap_or(DEFAULT_PAGE_SIZE)
+            .clamp(1, history::MAX_PAGE_SIZE);
+        (page, page_size)
+    }
+
+    /// Validates the search parameters; blank values are treated as absent.
+    fn filter(&self) -> Result<HistoryFilter, &'static str> {
+        fn present(value: &Option<String>) -> Option<&str> {
+            value
+                .as_deref()
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+        }
+        fn timestamp(value: &Option<String>) -> Result<Option<DateTime<Utc>>, &'static str> {
+            present(value)
+                .map(|raw| {
+                    DateTime::parse_from_rfc3339(raw)
+                        .map(|ts| ts.with_timezone(&Utc))
+                        .map_err(|_| "时间范围格式无效。")
+                })
+                .transpose()
+        }
+
+        let status = present(&self.status)
+            .map(|raw| match JobStatus::from_str(raw) {
+                JobStatus::Other(_) => Err("未知任务状态。"),
+                status => Ok(status.as_str().to_string()),
+            })
+            .transpose()?;
+
+        Ok(HistoryFilter {
+            filename: present(&self.filename).map(str::to_string),
+            status,
+            created_from: timestamp(&self.from)?,
+            created_to: timestamp(&self.to)?,
+        })
+    }
 }
 
 #[derive(serde::Serialize)]
@@ -39,13 +105,57 @@ pub(crate) struct HistoryItem {
     supports_downloads: bool,
 }
 
+impl HistoryItem {
+    fn from_entry(entry: HistoryEntry) -> Option<Self> {
+        let meta = history::module_metadata(&entry.module)?;
+        Some(HistoryItem {
+            module: entry.module.clone(),
+            module_label: meta.label.to_string(),
+            tool_path: meta.tool_path.to_string(),
+            status_path: format!("{}{}", meta.status_path_prefix, entry.job_key),
+            job_key: entry.job_key,
+            created_at: entry.created_at.to_rfc3339(),
+            updated_at: entry.updated_at.map(|ts| ts.to_rfc3339()),
+            status_label: entry
+                .status
+                .as_deref()
+                .map(|status| JobStatus::from_str(status).label_zh().to_string()),
+            status: entry.status,
+            status_detail: entry.status_detail,
+            files_purged: entry.files_purged,
+            supports_downloads: meta.supports_downloads,
+        })
+    }
+}
+
 #[derive(serde::Serialize)]
 pub(crate) struct HistoryResponse {
     jobs: Vec<HistoryItem>,
+    page: i64,
+    page_size: i64,
+    /// Jobs across all pages.
+    total: i64,
     retention_seconds: u64,
     generated_at: String,
 }
 
+/// Recent jobs shown on the home page for each module.
+const HOME_JOBS_PER_MODULE: usize = 3;
+
+#[derive(serde::Serialize)]
+pub(crate) struct ModuleActivity {
+    module: String,
+    module_label: String,
+    tool_path: String,
+    jobs: Vec<HistoryItem>,
+}
+
+#[derive(serde::Serialize)]
+pub(crate) struct HomeSummaryResponse {
+    modules: Vec<ModuleActivity>,
+    generated_at: String,
+}
+
 pub async fn recent_history(
     State(state): State<AppState>,
     jar: CookieJar,
@@ -61,48 +171,456 @@ pub async fn recent_history(
         }
     }
 
-    let limit = query.limit.unwrap_or(20);
+    let (page, page_size) = query.page_params();
+    let filter = query
+        .filter()
+        .map_err(|message| json_error(StatusCode::BAD_REQUEST, message))?;
+
+    let history_page = history::fetch_recent_jobs(
+        &state.pool(),
+        user.id,
+        query.module.as_deref(),
+        &filter,
+        page,
+        page_size,
+    )
+    .await
+    .map_err(|err| {
+        error!(?err, user_id = %user.id, "failed to load history entries");
+        json_error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "无法读取历史记录，请稍后再试。",
+        )
+    })?;
+
+    let retention = history::retention_interval(&state.pool(), query.module.as_deref())
+        .await
+        .map_err(|err| {
+            error!(?err, user_id = %user.id, "failed to load history retention window");
+            json_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "无法读取历史记录，请稍后再试。",
+            )
+        })?;
+
+    let jobs = history_page
+        .entries
+        .into_iter()
+        .filter_map(HistoryItem::from_entry)
+        .collect::<Vec<_>>();
+
+    let response = HistoryResponse {
+        jobs,
+        page,
+        page_size,
+        total: history_page.total,
+        retention_seconds: retention.as_secs(),
+        generated_at: Utc::now().to_rfc3339(),
+    };
+
+    Ok(Json(response))
+}
+
+pub async fn home_summary(
+    State(state): State<AppState>,
+    jar: CookieJar,
+) -> Result<Json<HomeSummaryResponse>, (StatusCode, Json<ApiMessage>)> {
+    let user = auth::current_user_or_json_error(&state, &jar)
+        .await
+        .map_err(|JsonAuthError { status, message }| json_error(status, message))?;
 
     let entries =
-        history::fetch_recent_jobs(&state.pool(), user.id, query.module.as_deref(), limit)
+        history::fetch_recent_jobs_by_module(&state.pool(), user.id, HOME_JOBS_PER_MODULE as i64)
             .await
             .map_err(|err| {
-                error!(?err, user_id = %user.id, "failed to load history entries");
+                error!(?err, user_id = %user.id, "failed to load home summary");
                 json_error(
                     StatusCode::INTERNAL_SERVER_ERROR,
-                    "无法读取历史记录，请稍后再试。",
+                    "无法读取最近任务，请稍后再试。",
                 )
             })?;
 
-    let jobs = entries
-        .into_iter()
-        .filter_map(|entry| {
-            let meta = history::module_metadata(&entry.module)?;
-            Some(HistoryItem {
-                module: entry.module.clone(),
-                module_label: meta.label.to_string(),
-                tool_path: meta.tool_path.to_string(),
-                status_path: format!("{}{}", meta.status_path_prefix, entry.job_key),
-                job_key: entry.job_key,
-                created_at: entry.created_at.to_rfc3339(),
-                updated_at: entry.updated_at.map(|ts| ts.to_rfc3339()),
-                status_label: entry
-                    .status
-                    .as_deref()
-                    .map(|status| JobStatus::from_str(status).label_zh().to_string()),
-                status: entry.status,
-                status_detail: entry.status_detail,
-                files_purged: entry.files_purged,
-                supports_downloads: meta.supports_downloads,
-            })
+    Ok(Json(HomeSummaryResponse {
+        modules: group_by_module(entries, HOME_JOBS_PER_MODULE),
+        generated_at: Utc::now().to_rfc3339(),
+    }))
+}
+
+/// Buckets entries (newest first) under every known module, keeping module order stable so
+/// modules without recent jobs still appear on the home page.
+fn group_by_module(entries: Vec<HistoryEntry>, per_module: usize) -> Vec<ModuleActivity> {
+    let mut grouped: HashMap<String, Vec<HistoryItem>> = HashMap::new();
+    for item in entries.into_iter().filter_map(HistoryItem::from_entry) {
+        let jobs = grouped.entry(item.module.clone()).or_default();
+        if jobs.len() < per_module {
+            jobs.push(item);
+        }
+    }
+
+    history::all_modules()
+        .iter()
+        .map(|meta| ModuleActivity {
+            module: meta.key.to_string(),
+            module_label: meta.label.to_string(),
+            tool_path: meta.tool_path.to_string(),
+            jobs: grouped.remove(meta.key).unwrap_or_default(),
         })
-        .collect::<Vec<_>>();
+        .collect()
+}
 
-    let response = HistoryResponse {
-        jobs,
-        retention_seconds: history::retention_interval().as_secs(),
-        generated_at: Utc::now().to_rfc3339(),
-    };
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_user, session_jar};
+    use crate::usage::{MODULE_GRADER, MODULE_REVIEWER, MODULE_SUMMARIZER, MODULE_TRANSLATE_DOCX};
+    use crate::{llm::LlmClient, web::AppState};
+    use chrono::Duration;
+    use sqlx::PgPool;
+    use uuid::Uuid;
 
-    Ok(Json(response))
+    fn entry(module: &str, job_key: &str, minutes_ago: i64) -> HistoryEntry {
+        HistoryEntry {
+            module: module.to_string(),
+            job_key: job_key.to_string(),
+            created_at: Utc::now() - Duration::minutes(minutes_ago),
+            status: Some("completed".to_string()),
+            status_detail: None,
+            updated_at: None,
+            files_purged: false,
+        }
+    }
+
+    #[test]
+    fn home_summary_groups_recent_jobs_per_module() {
+        let entries = vec![
+            entry(MODULE_SUMMARIZER, "s1", 1),
+            entry(MODULE_GRADER, "g1", 2),
+            entry(MODULE_SUMMARIZER, "s2", 3),
+            entry(MODULE_SUMMARIZER, "s3", 4),
+            entry("retired_module", "x1", 5),
+        ];
+
+        let modules = group_by_module(entries, 2);
+        assert_eq!(modules.len(), history::all_modules().len());
+
+        let jobs_for = |key: &str| -> Vec<String> {
+            modules
+                .iter()
+                .find(|module| module.module == key)
+                .map(|module| module.jobs.iter().map(|job| job.job_key.clone()).collect())
+                .unwrap_or_default()
+        };
+        assert_eq!(jobs_for(MODULE_SUMMARIZER), vec!["s1", "s2"]);
+        assert_eq!(jobs_for(MODULE_GRADER), vec!["g1"]);
+        assert!(jobs_for(MODULE_TRANSLATE_DOCX).is_empty());
+        assert!(jobs_for("retired_module").is_empty());
+
+        let summarizer = modules
+            .iter()
+            .find(|module| module.module == MODULE_SUMMARIZER)
+            .unwrap();
+        assert_eq!(summarizer.jobs[0].status_label.as_deref(), Some("已完成"));
+    }
+
+    #[test]
+    fn page_params_default_and_clamp() {
+        let query = |page: Option<i64>, page_size: Option<i64>, limit: Option<i64>| HistoryQuery {
+            page,
+            page_size,
+            limit,
+            ..HistoryQuery::default()
+        };
+        assert_eq!(
+            query(None, None, None).page_params(),
+            (1, DEFAULT_PAGE_SIZE)
+        );
+        assert_eq!(query(Some(0), Some(0), None).page_params(), (1, 1));
+        assert_eq!(
+            query(Some(3), Some(10_000), None).page_params(),
+            (3, history::MAX_PAGE_SIZE)
+        );
+        assert_eq!(query(Some(2), None, Some(5)).page_params(), (2, 5));
+        assert_eq!(query(None, Some(10), Some(5)).page_params(), (1, 10));
+    }
+
+    #[sqlx::test]
+    #[ignore = "needs DATABASE_URL (disposable Postgres)"]
+    async fn history_pages_report_the_total(pool: PgPool) {
+        let state = AppState::for_tests(
+            pool.clone(),
+            LlmClient::with_openrouter_base_url("http://127.0.0.1:9"),
+        )
+        .await
+        .unwrap();
+        let user_id = create_user(&pool).await;
+        let mut keys = Vec::new();
+        for _ in 0..3 {
+            let key = Uuid::new_v4().to_string();
+            history::record_job_start(&pool, MODULE_SUMMARIZER, user_id, key.clone())
+                .await
+                .unwrap();
+            keys.push(key);
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+        history::record_job_start(&pool, MODULE_GRADER, user_id, Uuid::new_v4().to_string())
+            .await
+            .unwrap();
+
+        let page = |page: i64| HistoryQuery {
+            module: Some(MODULE_SUMMARIZER.to_string()),
+            page: Some(page),
+            page_size: Some(2),
+            ..HistoryQuery::default()
+        };
+        let first = recent_history(
+            State(state.clone()),
+            session_jar(&pool, user_id).await,
+            Query(page(1)),
+        )
+        .await
+        .unwrap()
+        .0;
+        assert_eq!((first.page, first.page_size, first.total), (1, 2, 3));
+        let first_keys: Vec<_> = first.jobs.iter().map(|job| job.job_key.as_str()).collect();
+        assert_eq!(first_keys, vec![keys[2].as_str(), keys[1].as_str()]);
+
+        let second = recent_history(
+            State(state),
+            session_jar(&pool, user_id).await,
+            Query(page(2)),
+        )
+        .await
+        .unwrap()
+        .0;
+        assert_eq!(second.total, 3);
+        assert_eq!(second.jobs.len(), 1);
+        assert_eq!(second.jobs[0].job_key, keys[0]);
+    }
+
+    #[test]
+    fn filter_params_are_validated() {
+        let query = HistoryQuery {
+            filename: Some("  report ".to_string()),
+            status: Some("completed".to_string()),
+            from: Some("2026-10-16T08:00:00+08:00".to_string()),
+            to: Some(" ".to_string()),
+            ..HistoryQuery::default()
+        };
+        let filter = query.filter().unwrap();
+        assert_eq!(filter.filename.as_deref(), Some("report"));
+        assert_eq!(filter.status.as_deref(), Some("completed"));
+        assert_eq!(
+            filter.created_from.map(|ts| ts.to_rfc3339()).as_deref(),
+            Some("2026-10-16T00:00:00+00:00")
+        );
+        assert!(filter.created_to.is_none());
+
+        let bad_status = HistoryQuery {
+            status: Some("done'; DROP TABLE users; --".to_string()),
+            ..HistoryQuery::default()
+        };
+        assert!(bad_status.filter().is_err());
+        let bad_date = HistoryQuery {
+            to: Some("yesterday".to_string()),
+            ..HistoryQuery::default()
+        };
+        assert!(bad_date.filter().is_err());
+    }
+
+    #[sqlx::test]
+    #[ignore = "needs DATABASE_URL (disposable Postgres)"]
+    async fn history_filters_by_filename_status_and_time(pool: PgPool) {
+        let user_id = create_user(&pool).await;
+        let insert_summary = |filename: &'static str, status: &'static str| {
+            let pool = pool.clone();
+            async move {
+                let job_id = Uuid::new_v4();
+                sqlx::query(
+                    "INSERT INTO summary_jobs (id, user_id, status, document_type) VALUES ($1, $2, $3, 'research')",
+                )
+                .bind(job_id)
+                .bind(user_id)
+                .bind(status)
+                .execute(&pool)
+                .await
+                .unwrap();
+                sqlx::query(
+                    "INSERT INTO summary_documents (id, job_id, ordinal, original_filename, source_path, status)
+                     VALUES ($1, $2, 0, $3, '/tmp/x', $4)",
+                )
+                .bind(Uuid::new_v4())
+                .bind(job_id)
+                .bind(filename)
+                .bind(status)
+                .execute(&pool)
+                .await
+                .unwrap();
+                history::record_job_start(&pool, MODULE_SUMMARIZER, user_id, job_id.to_string())
+                    .await
+                    .unwrap();
+                job_id.to_string()
+            }
+        };
+        let annual = insert_summary("Annual_Report.pdf", "completed").await;
+        let draft = insert_summary("draft-notes.docx", "failed").await;
+        let percent = insert_summary("growth 100%.pdf", "completed").await;
+        let review: i32 = sqlx::query_scalar(
+            "INSERT INTO reviewer_jobs (user_id, filename, language, status)
+             VALUES ($1, 'ANNUAL review.pdf', 'english', 'processing')
+             RETURNING job_id",
+        )
+        .bind(user_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        let review = review.to_string();
+        history::record_job_start(&pool, MODULE_REVIEWER, user_id, review.clone())
+            .await
+            .unwrap();
+
+        let keys = |filter: HistoryFilter| {
+            let pool = pool.clone();
+            async move {
+                let mut keys: Vec<String> =
+                    history::fetch_recent_jobs(&pool, user_id, None, &filter, 1, 50)
+                        .await
+                        .unwrap()
+                        .entries
+                        .into_iter()
+                        .map(|entry| entry.job_key)
+                        .collect();
+                keys.sort();
+                keys
+            }
+        };
+        let sorted = |mut keys: Vec<String>| {
+            keys.sort();
+            keys
+        };
+
+        let by_name = HistoryFilter {
+            filename: Some("annual_REPORT".to_string()),
+            ..HistoryFilter::default()
+        };
+        assert_eq!(keys(by_name).await, vec![annual.clone()]);
+
+        let across_modules = HistoryFilter {
+            filename: Some("annual".to_string()),
+            ..HistoryFilter::default()
+        };
+        assert_eq!(
+            keys(across_modules).await,
+            sorted(vec![annual.clone(), review.clone()])
+        );
+
+        let literal_percent = HistoryFilter {
+            filename: Some("100%".to_string()),
+            ..HistoryFilter::default()
+        };
+        assert_eq!(keys(literal_percent).await, vec![percent.clone()]);
+
+        let wildcard_is_literal = HistoryFilter {
+            filename: Some("%".to_string()),
+            ..HistoryFilter::default()
+        };
+        assert_eq!(keys(wildcard_is_literal).await, vec![percent.clone()]);
+
+        let injection = HistoryFilter {
+            filename: Some("' OR '1'='1".to_string()),
+            ..HistoryFilter::default()
+        };
+        assert!(keys(injection).await.is_empty());
+
+        let completed = HistoryFilter {
+            status: Some("completed".to_string()),
+            ..HistoryFilter::default()
+        };
+        assert_eq!(
+            keys(completed).await,
+            sorted(vec![annual.clone(), percent.clone()])
+        );
+
+        let failed_docx = HistoryFilter {
+            filename: Some(".docx".to_string()),
+            status: Some("failed".to_string()),
+            ..HistoryFilter::default()
+        };
+        assert_eq!(keys(failed_docx).await, vec![draft.clone()]);
+
+        let future = HistoryFilter {
+            created_from: Some(Utc::now() + Duration::hours(1)),
+            ..HistoryFilter::default()
+        };
+        assert!(keys(future).await.is_empty());
+
+        let past = HistoryFilter {
+            created_to: Some(Utc::now() - Duration::hours(1)),
+            ..HistoryFilter::default()
+        };
+        assert!(keys(past).await.is_empty());
+    }
+
+    #[sqlx::test]
+    #[ignore = "needs DATABASE_URL (disposable Postgres)"]
+    async fn history_follows_module_retention_window(pool: PgPool) {
+        let user_id = create_user(&pool).await;
+        let job_id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO summary_jobs (id, user_id, status, document_type) VALUES ($1, $2, 'completed', 'research')",
+        )
+        .bind(job_id)
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+        history::record_job_start(&pool, MODULE_SUMMARIZER, user_id, job_id.to_string())
+            .await
+            .unwrap();
+        sqlx::query("UPDATE user_job_history SET created_at = NOW() - INTERVAL '3 days'")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let listed = |pool: PgPool| async move {
+            let page =
+                history::fetch_recent_jobs(&pool, user_id, None, &HistoryFilter::default(), 1, 50)
+                    .await
+                    .unwrap();
+            let recent = history::fetch_recent_jobs_by_module(&pool, user_id, 5)
+                .await
+                .unwrap();
+            (page.total, recent.len())
+        };
+        assert_eq!(listed(pool.clone()).await, (0, 0));
+
+        sqlx::query(
+            "INSERT INTO file_retention_policies (module_key, retention_days) VALUES ($1, 7)",
+        )
+        .bind(MODULE_SUMMARIZER)
+        .execute(&pool)
+        .await
+        .unwrap();
+        assert_eq!(listed(pool.clone()).await, (1, 1));
+        assert_eq!(
+            history::retention_interval(&pool, Some(MODULE_SUMMARIZER))
+                .await
+                .unwrap()
+                .as_secs(),
+            7 * 86_400
+        );
+        assert_eq!(
+            history::retention_interval(&pool, Some(MODULE_GRADER))
+                .await
+                .unwrap()
+                .as_secs(),
+            86_400
+        );
+
+        assert_eq!(history::purge_stale_history(&pool).await.unwrap(), 0);
+        sqlx::query("UPDATE user_job_history SET created_at = NOW() - INTERVAL '8 days'")
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert_eq!(history::purge_stale_history(&pool).await.unwrap(), 1);
+    }
 }
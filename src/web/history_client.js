@@ -45,11 +45,14 @@
         const moduleKey = panel.dataset.historyModule;
         if (!moduleKey) return;
 
-        const limitAttr = panel.dataset.historyLimit;
-        const limit = limitAttr ? parseInt(limitAttr, 10) || 20 : 20;
+        const sizeAttr = panel.dataset.historyPageSize;
+        const pageSize = sizeAttr ? parseInt(sizeAttr, 10) || 20 : 20;
+        const pager = { page: 1, pageSize, filters: {} };
         const fetchAndRender = async () => {
-          await loadHistory(panel, moduleKey, limit);
+          await loadHistory(panel, moduleKey, pager);
         };
+        initPager(panel, pager, fetchAndRender);
+        initFilters(panel, pager, fetchAndRender);
 
         fetchAndRender();
         const timerId = window.setInterval(fetchAndRender, POLL_INTERVAL_MS);
@@ -57,14 +60,85 @@
       });
   }
 
-  async function loadHistory(panel, moduleKey, limit) {
+  function initPager(panel, pager, reload) {
+    const prev = panel.querySelector('[data-history-prev]');
+    const next = panel.querySelector('[data-history-next]');
+    if (prev) {
+      prev.addEventListener('click', () => {
+        if (pager.page > 1) {
+          pager.page -= 1;
+          reload();
+        }
+      });
+    }
+    if (next) {
+      next.addEventListener('click', () => {
+        pager.page += 1;
+        reload();
+      });
+    }
+  }
+
+  function initFilters(panel, pager, reload) {
+    const form = panel.querySelector('[data-history-filters]');
+    if (!form) return;
+
+    const apply = () => {
+      const filters = {};
+      const filename = form.elements.filename.value.trim();
+      if (filename) filters.filename = filename;
+      if (form.elements.status.value) filters.status = form.elements.status.value;
+      // datetime-local values carry no zone; send them as the browser's local time.
+      ['from', 'to'].forEach((name) => {
+        const value = form.elements[name].value;
+        const date = value ? new Date(value) : null;
+        if (date && !Number.isNaN(date.getTime())) filters[name] = date.toISOString();
+      });
+      pager.filters = filters;
+      pager.page = 1;
+      reload();
+    };
+
+    form.addEventListener('submit', (event) => {
+      event.preventDefault();
+      apply();
+    });
+    form.addEventListener('change', apply);
+    form.addEventListener('reset', () => {
+      window.setTimeout(apply, 0);
+    });
+  }
+
+  function renderPager(panel, pager, total) {
+    const container = panel.querySelector('[data-history-pager]');
+    if (!container) return;
+
+    const pages = Math.max(1, Math.ceil(total / pager.pageSize));
+    container.hidden = pages <= 1;
+    const label = container.querySelector('[data-history-page-label]');
+    if (label) {
+      label.textContent = `第 ${pager.page} / ${pages} 页（共 ${total} 条）`;
+    }
+    const prev = container.querySelector('[data-history-prev]');
+    const next = container.querySelector('[data-history-next]');
+    if (prev) prev.disabled = pager.page <= 1;
+    if (next) next.disabled = pager.page >= pages;
+  }
+
+  async function loadHistory(panel, moduleKey, pager) {
     const tbody = panel.querySelector('[data-history-body]');
     if (!tbody) {
       return;
     }
 
     try {
-      const response = await fetch(`/api/history?module=${encodeURIComponent(moduleKey)}&limit=${limit}`);
+      const params = new URLSearchParams({
+        module: moduleKey,
+        page: String(pager.page),
+        page_size: String(pager.pageSize),
+        ...pager.filters,
+      });
+      const response = await fetch(`/api/history?${params}`);
       if (response.status === 401) {
         stopPolling(panel);
         tbody.innerHTML = '<tr class="history-empty-row"><td colspan="4">登录已过期，请刷新页面。</td></tr>';
@@ -75,7 +149,16 @@
       }
 
       const data = await response.json();
+      const total = typeof data.total === 'number' ? data.total : 0;
+      const lastPage = Math.max(1, Math.ceil(total / pager.pageSize));
+      if (pager.page > lastPage) {
+        // Jobs expired since the page was opened; fall back to the last page that exists.
+        pager.page = lastPage;
+        await loadHistory(panel, moduleKey, pager);
+        return;
+      }
       renderHistoryTable(panel, tbody, data.jobs || []);
+      renderPager(panel, pager, total);
     } catch (error) {
       console.error('Failed to load history', error);
       tbody.innerHTML = '<tr class="history-empty-row"><td colspan="4">无法加载历史记录，请稍后再试。</td></tr>';
@@ -261,6 +344,9 @@
           if (doc.translated_download_url) {
             links.push(createDownloadLink(`译文 ${index + 1}`, doc.translated_download_url));
           }
+          if (doc.translated_pdf_download_url) {
+            links.push(createDownloadLink(`译文 ${index + 1}（PDF）`, doc.translated_pdf_download_url));
+          }
         });
       }
     } else if (moduleKey === 'info_extract') {
@@ -281,6 +367,14 @@
       if (status.round3_review && status.round3_review.download_url) {
         links.push(createDownloadLink('第三轮事实核查', status.round3_review.download_url));
       }
+    } else if (moduleKey === 'transcribe') {
+      if (status.transcript_download_url) {
+        links.push(createDownloadLink('文字稿', status.transcript_download_url));
+      }
+    } else if (moduleKey === 'docx2pdf') {
+      if (status.pdf_download_url) {
+        links.push(createDownloadLink('PDF', status.pdf_download_url));
+      }
     }
 
     if (!links.length) {
@@ -291,41 +385,19 @@
   }
 
   function renderExtraInfo(moduleKey, status) {
-    if (moduleKey === 'grader') {
-      const parts = [];
-      if (typeof status.iqm_score === 'number') {
-        parts.push(`<strong>评分：</strong>${status.iqm_score.toFixed(2)}`);
-      }
-      if (status.justification) {
-        parts.push(`<strong>理由：</strong>${escapeHtml(status.justification)}`);
-      }
-      if (status.decision_reason) {
-        parts.push(`<strong>结论：</strong>${escapeHtml(status.decision_reason)}`);
-      }
-      if (status.keyword_main || (Array.isArray(status.keyword_peripherals) && status.keyword_peripherals.length)) {
-        const extras = [];
-        if (status.keyword_main) {
-          extras.push(`主题：${escapeHtml(status.keyword_main)}`);
-        }
-        if (Array.isArray(status.keyword_peripherals) && status.keyword_peripherals.length) {
-          extras.push(`关联关键词：${escapeHtml(status.keyword_peripherals.join('、'))}`);
-        }
-        parts.push(`<strong>关键词：</strong>${extras.join('；')}`);
-      }
-      if (status.recommendations && Array.isArray(status.recommendations) && status.recommendations.length) {
-        const items = status.recommendations
-          .map((rec) => {
-            const score = typeof rec.match_score === 'number' ? `匹配度 ${(rec.match_score * 100).toFixed(0)}%` : '';
-            const ref = rec.reference_mark ? `（${escapeHtml(rec.reference_mark)}）` : '';
-            return `<li>${escapeHtml(rec.journal_name || '期刊')}${ref}${score ? ` - ${score}` : ''}</li>`;
-          })
-          .join('');
-        parts.push(`<strong>推荐期刊：</strong><ul>${items}</ul>`);
-      }
-      if (!parts.length) {
-        return '';
-      }
-      return `<div class="history-extra">${parts.join('<br>')}</div>`;
+    if (moduleKey === 'grader' && Array.isArray(status.documents)) {
+      const multiple = status.documents.length > 1;
+      const blocks = status.documents
+        .map((doc) => {
+          const parts = renderGraderDocument(doc);
+          if (!parts.length) {
+            return '';
+          }
+          const heading = multiple ? `<strong>${escapeHtml(doc.original_filename || '稿件')}</strong><br>` : '';
+          return `<div class="history-extra">${heading}${parts.join('<br>')}</div>`;
+        })
+        .filter(Boolean);
+      return blocks.join('');
     }
 
     if (moduleKey === 'summarizer' && Array.isArray(status.documents)) {
@@ -341,6 +413,40 @@
     return '';
   }
 
+  function renderGraderDocument(doc) {
+    const parts = [];
+    if (typeof doc.iqm_score === 'number') {
+      parts.push(`<strong>评分：</strong>${doc.iqm_score.toFixed(2)}`);
+    }
+    if (doc.justification) {
+      parts.push(`<strong>理由：</strong>${escapeHtml(doc.justification)}`);
+    }
+    if (doc.decision_reason) {
+      parts.push(`<strong>结论：</strong>${escapeHtml(doc.decision_reason)}`);
+    }
+    if (doc.keyword_main || (Array.isArray(doc.keyword_peripherals) && doc.keyword_peripherals.length)) {
+      const extras = [];
+      if (doc.keyword_main) {
+        extras.push(`主题：${escapeHtml(doc.keyword_main)}`);
+      }
+      if (Array.isArray(doc.keyword_peripherals) && doc.keyword_peripherals.length) {
+        extras.push(`关联关键词：${escapeHtml(doc.keyword_peripherals.join('、'))}`);
+      }
+      parts.push(`<strong>关键词：</strong>${extras.join('；')}`);
+    }
+    if (Array.isArray(doc.recommendations) && doc.recommendations.length) {
+      const items = doc.recommendations
+        .map((rec) => {
+          const score = typeof rec.match_score === 'number' ? `匹配度 ${(rec.match_score * 100).toFixed(0)}%` : '';
+          const ref = rec.reference_mark ? `（${escapeHtml(rec.reference_mark)}）` : '';
+          return `<li>${escapeHtml(rec.journal_name || '期刊')}${ref}${score ? ` - ${score}` : ''}</li>`;
+        })
+        .join('');
+      parts.push(`<strong>推荐期刊：</strong><ul>${items}</ul>`);
+    }
+    return parts;
+  }
+
   function createDownloadLink(label, href) {
     return `<a href="${escapeAttribute(href)}" target="_blank" rel="noopener">${escapeHtml(label)} 下载</a>`;
   }
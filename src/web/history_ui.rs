@@ -3,9 +3,23 @@ pub const HISTORY_SCRIPT: &str = include_str!("history_client.js");
 
 pub fn render_history_panel(module_key: &str) -> String {
     format!(
-        r#"<section class="panel history-panel" data-history-module="{module}" data-history-limit="20">
+        r#"<section class="panel history-panel" data-history-module="{module}" data-history-page-size="20">
     <h2>历史记录</h2>
-    <p class="note">展示最近 24 小时提交的任务，可在后台完成后直接下载结果。</p>
+    <p class="note">展示文件保留期内提交的任务，可在后台完成后直接下载结果。</p>
+    <form class="history-filters" data-history-filters>
+        <input type="search" name="filename" placeholder="按文件名搜索" maxlength="200">
+        <select name="status">
+            <option value="">全部状态</option>
+            <option value="pending">排队中</option>
+            <option value="processing">处理中</option>
+            <option value="completed">已完成</option>
+            <option value="failed">失败</option>
+        </select>
+        <label>从 <input type="datetime-local" name="from"></label>
+        <label>至 <input type="datetime-local" name="to"></label>
+        <button type="submit">筛选</button>
+        <button type="reset">清除</button>
+    </form>
     <div class="history-table-wrapper">
         <table class="history-table">
             <thead>
@@ -21,6 +35,11 @@ pub fn render_history_panel(module_key: &str) -> String {
             </tbody>
         </table>
     </div>
+    <div class="history-pager" data-history-pager hidden>
+        <button type="button" data-history-prev>上一页</button>
+        <span data-history-page-label></span>
+        <button type="button" data-history-next>下一页</button>
+    </div>
 </section>"#,
         module = html_escape(module_key),
     )
@@ -73,9 +73,19 @@ fn render_main_page(user: &AuthUser, params: &LandingQuery) -> String {
         ),
         (
             "审稿助手",
-            "上传学术稿件，通过8个模型并行审稿，生成元审稿报告和事实核查。",
+            "上传学术稿件，通过多个模型并行审稿，生成元审稿报告和事实核查。",
             "/tools/reviewer",
         ),
+        (
+            "音频转写",
+            "上传访谈或讲座录音，由支持音频输入的模型转写为文字稿。",
+            "/tools/transcribe",
+        ),
+        (
+            "DOCX 转 PDF",
+            "上传 Word 文档，转换为排版一致的 PDF 文件。",
+            "/tools/docx2pdf",
+        ),
     ];
 
     let module_cards = modules
@@ -115,6 +125,7 @@ fn render_main_page(user: &AuthUser, params: &LandingQuery) -> String {
         .header-actions span {{ color: #475569; font-size: 0.95rem; }}
         .logout-form button {{ padding: 0.6rem 1.3rem; border: none; border-radius: 999px; background: #2563eb; color: #ffffff; font-weight: 600; cursor: pointer; transition: background 0.15s ease; }}
         .logout-form button:hover {{ background: #1d4ed8; }}
+        .account-link {{ color: #2563eb; font-weight: 600; text-decoration: none; font-size: 0.95rem; }}
         main {{ flex: 1; padding: clamp(2rem, 5vw, 3rem); max-width: 1100px; margin: 0 auto; width: 100%; box-sizing: border-box; }}
         .flash {{ padding: 1rem 1.25rem; border-radius: 10px; margin-bottom: 1.5rem; font-weight: 600; border: 1px solid transparent; }}
         .flash.success {{ background: #ecfdf3; border-color: #bbf7d0; color: #166534; }}
@@ -127,6 +138,21 @@ fn render_main_page(user: &AuthUser, params: &LandingQuery) -> String {
         .module-card .cta {{ font-weight: 600; color: #2563eb; }}
         .admin-link {{ display: inline-flex; align-items: center; justify-content: center; margin-top: 2.5rem; padding: 0.85rem 1.5rem; border-radius: 12px; background: #e0f2fe; color: #1d4ed8; text-decoration: none; font-weight: 600; border: 1px solid #bfdbfe; transition: background 0.15s ease, border 0.15s ease; }}
         .admin-link:hover {{ background: #bfdbfe; border-color: #93c5fd; }}
+        .recent-activity {{ margin-top: 2.5rem; background: #ffffff; padding: 1.5rem 1.75rem; border-radius: 16px; border: 1px solid #e2e8f0; }}
+        .recent-activity h2 {{ margin: 0 0 1rem 0; font-size: 1.2rem; }}
+        .recent-grid {{ display: grid; gap: 1.25rem; grid-template-columns: repeat(auto-fit, minmax(200px, 1fr)); }}
+        .recent-module h3 {{ margin: 0 0 0.5rem 0; font-size: 1rem; }}
+        .recent-module h3 a {{ color: #1d4ed8; text-decoration: none; }}
+        .recent-module ul {{ margin: 0; padding: 0; list-style: none; display: flex; flex-direction: column; gap: 0.35rem; }}
+        .recent-module li {{ font-size: 0.9rem; color: #475569; display: flex; justify-content: space-between; gap: 0.5rem; }}
+        .recent-module .empty {{ color: #94a3b8; font-size: 0.9rem; }}
+        .account-panel {{ margin-top: 2.5rem; background: #ffffff; padding: 1.25rem 1.75rem; border-radius: 16px; border: 1px solid #e2e8f0; }}
+        .account-panel summary {{ cursor: pointer; font-weight: 600; font-size: 1.05rem; }}
+        .account-panel form {{ display: grid; gap: 0.85rem; max-width: 360px; margin-top: 1rem; }}
+        .account-panel label {{ display: flex; flex-direction: column; gap: 0.35rem; font-size: 0.9rem; color: #475569; }}
+        .account-panel input {{ padding: 0.6rem 0.75rem; border: 1px solid #cbd5f5; border-radius: 8px; font-size: 0.95rem; }}
+        .account-panel .hint {{ margin: 0; font-size: 0.85rem; color: #94a3b8; }}
+        .account-panel button {{ justify-self: start; padding: 0.6rem 1.3rem; border: none; border-radius: 999px; background: #2563eb; color: #ffffff; font-weight: 600; cursor: pointer; }}
         .app-footer {{ margin-top: 3rem; text-align: center; font-size: 0.85rem; color: #94a3b8; }}
     </style>
 </head>
@@ -138,6 +164,7 @@ fn render_main_page(user: &AuthUser, params: &LandingQuery) -> String {
         </div>
         <div class="header-actions">
             <span>当前登录：<strong>{username}</strong></span>
+            <a class="account-link" href="/account/tokens">API 令牌</a>
             <form class="logout-form" method="post" action="/logout">
                 <button type="submit">退出登录</button>
             </form>
@@ -148,15 +175,57 @@ fn render_main_page(user: &AuthUser, params: &LandingQuery) -> String {
         <div class="modules-grid">
             {module_cards}
         </div>
+        <section class="recent-activity">
+            <h2>最近任务</h2>
+            <div class="recent-grid" data-home-summary><p class="empty">正在载入...</p></div>
+        </section>
+        <details class="account-panel">
+            <summary>修改密码</summary>
+            <form method="post" action="/account/password">
+                <label>当前密码<input type="password" name="current_password" autocomplete="current-password" required></label>
+                <label>新密码<input type="password" name="new_password" autocomplete="new-password" minlength="{min_password}" required></label>
+                <label>确认新密码<input type="password" name="confirm_password" autocomplete="new-password" minlength="{min_password}" required></label>
+                <p class="hint">至少 {min_password} 位，需同时包含字母和数字。修改后其他设备需重新登录。</p>
+                <button type="submit">更新密码</button>
+            </form>
+        </details>
         {admin_button}
         {footer}
     </main>
+    <script>
+    (() => {{
+        const container = document.querySelector('[data-home-summary]');
+        if (!container) return;
+        const escapeHtml = (value) => String(value ?? '').replace(/[&<>"']/g, (ch) => ({{ '&': '&amp;', '<': '&lt;', '>': '&gt;', '"': '&quot;', "'": '&#39;' }})[ch]);
+        const formatTime = (value) => {{
+            const date = new Date(value);
+            return Number.isNaN(date.getTime()) ? '' : date.toLocaleString('zh-CN', {{ hour12: false }});
+        }};
+        fetch('/api/home/summary', {{ credentials: 'same-origin' }})
+            .then((response) => {{
+                if (!response.ok) throw new Error('request failed');
+                return response.json();
+            }})
+            .then((data) => {{
+                container.innerHTML = (data.modules || []).map((module) => {{
+                    const jobs = module.jobs.length
+                        ? `<ul>${{module.jobs.map((job) => `<li><span>${{escapeHtml(job.status_label || job.status || '未知')}}</span><span>${{escapeHtml(formatTime(job.updated_at || job.created_at))}}</span></li>`).join('')}}</ul>`
+                        : '<p class="empty">保留期内暂无任务。</p>';
+                    return `<div class="recent-module"><h3><a href="${{escapeHtml(module.tool_path)}}">${{escapeHtml(module.module_label)}}</a></h3>${{jobs}}</div>`;
+                }}).join('');
+            }})
+            .catch(() => {{
+                container.innerHTML = '<p class="empty">无法读取最近任务。</p>';
+            }});
+    }})();
+    </script>
 </body>
 </html>"#,
         username = username,
         flash = flash,
         module_cards = module_cards,
         admin_button = admin_button,
+        min_password = auth::MIN_PASSWORD_LENGTH,
         footer = footer,
     )
 }
@@ -166,11 +235,20 @@ fn compose_landing_flash(params: &LandingQuery) -> String {
         if status == "logged_out" {
             return r#"<div class="flash success">已退出登录。</div>"#.to_string();
         }
+        if status == "password_changed" {
+            return r#"<div class="flash success">密码已更新，其他设备上的登录已失效。</div>"#
+                .to_string();
+        }
     }
 
     if let Some(error) = params.error.as_deref() {
         let message = match error {
             "not_authorized" => "该操作需要管理员权限。",
+            "password_incorrect" => "当前密码错误。",
+            "password_mismatch" => "两次输入的新密码不一致。",
+            "password_unchanged" => "新密码不能与当前密码相同。",
+            "password_weak" => "新密码至少 8 位，需同时包含字母和数字，且不能与用户名相同。",
+            "password_throttled" => "密码验证失败次数过多，请稍后再试。",
             _ => "发生未知错误，请稍后重试。",
         };
 
@@ -0,0 +1,217 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    env,
+    net::{IpAddr, SocketAddr},
+    sync::{Arc, Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+
+use axum::http::HeaderMap;
+
+/// Default number of failed logins allowed per client IP or username within the window.
+pub const DEFAULT_MAX_LOGIN_FAILURES: usize = 5;
+/// Default length of the failed-login window, in minutes.
+pub const DEFAULT_LOGIN_WINDOW_MINUTES: u64 = 15;
+/// Tracked keys beyond which expired entries are swept on the next failure.
+const SWEEP_THRESHOLD: usize = 10_000;
+
+/// Counts failed password attempts per client IP and per username so the login handler can
+/// answer 429 once either has failed too often within the window.
+///
+/// Configured via `LOGIN_MAX_FAILURES` (`0` disables the limit) and
+/// `LOGIN_FAILURE_WINDOW_MINUTES`. A successful login clears both counters.
+#[derive(Clone)]
+pub struct LoginLimiter {
+    max_failures: usize,
+    window: Duration,
+    failures: Arc<Mutex<HashMap<String, VecDeque<Instant>>>>,
+}
+
+impl LoginLimiter {
+    pub fn new(max_failures: usize, window: Duration) -> Self {
+        Self {
+            max_failures,
+            window,
+            failures: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let max_failures = env::var("LOGIN_MAX_FAILURES")
+            .ok()
+            .and_then(|value| value.trim().parse::<usize>().ok())
+            .unwrap_or(DEFAULT_MAX_LOGIN_FAILURES);
+        let minutes = env::var("LOGIN_FAILURE_WINDOW_MINUTES")
+            .ok()
+            .and_then(|value| value.trim().parse::<u64>().ok())
+            .filter(|minutes| *minutes > 0)
+            .unwrap_or(DEFAULT_LOGIN_WINDOW_MINUTES);
+        Self::new(max_failures, Duration::from_secs(minutes * 60))
+    }
+
+    /// How long the caller must wait before trying again, or `None` when the attempt may proceed.
+    pub fn retry_after(&self, ip: Option<IpAddr>, username: &str) -> Option<Duration> {
+        self.retry_after_at(ip, username, Instant::now())
+    }
+
+    pub fn record_failure(&self, ip: Option<IpAddr>, username: &str) {
+        self.record_failure_at(ip, username, Instant::now());
+    }
+
+    pub fn reset(&self, ip: Option<IpAddr>, username: &str) {
+        let mut failures = self.failures.lock().unwrap_or_else(|err| err.into_inner());
+        for key in limiter_keys(ip, username) {
+            failures.remove(&key);
+        }
+    }
+
+    fn retry_after_at(&self, ip: Option<IpAddr>, username: &str, now: Instant) -> Option<Duration> {
+        if self.max_failures == 0 {
+            return None;
+        }
+
+        let mut failures = self.failures.lock().unwrap_or_else(|err| err.into_inner());
+        limiter_keys(ip, username)
+            .into_iter()
+            .filter_map(|key| {
+                let attempts = failures.get_mut(&key)?;
+                prune(attempts, self.window, now);
+                if attempts.len() < self.max_failures {
+                    return None;
+                }
+                // The window reopens once enough of the oldest failures have aged out.
+                let oldest = attempts[attempts.len() - self.max_failures];
+                Some(self.window.saturating_sub(now.duration_since(oldest)))
+            })
+            .max()
+    }
+
+    fn record_failure_at(&self, ip: Option<IpAddr>, username: &str, now: Instant) {
+        if self.max_failures == 0 {
+            return;
+        }
+
+        let mut failures = self.failures.lock().unwrap_or_else(|err| err.into_inner());
+        if failures.len() >= SWEEP_THRESHOLD {
+            failures.retain(|_, attempts| {
+                prune(attempts, self.window, now);
+                !attempts.is_empty()
+            });
+        }
+        for key in limiter_keys(ip, username) {
+            let attempts = failures.entry(key).or_default();
+            prune(attempts, self.window, now);
+            attempts.push_back(now);
+        }
+    }
+}
+
+fn prune(attempts: &mut VecDeque<Instant>, window: Duration, now: Instant) {
+    while attempts
+        .front()
+        .is_some_and(|attempt| now.duration_since(*attempt) >= window)
+    {
+        attempts.pop_front();
+    }
+}
+
+fn limiter_keys(ip: Option<IpAddr>, username: &str) -> Vec<String> {
+    let mut keys = vec![format!("user:{}", username.trim())];
+    if let Some(ip) = ip {
+        keys.push(format!("ip:{ip}"));
+    }
+    keys
+}
+
+/// Whether `TRUST_FORWARDED_FOR` is set, i.e. the app runs behind a proxy that appends the
+/// client address to `X-Forwarded-For`.
+fn trust_forwarded_for() -> bool {
+    static TRUST: OnceLock<bool> = OnceLock::new();
+    *TRUST.get_or_init(|| {
+        env::var("TRUST_FORWARDED_FOR")
+            .map(|value| {
+                matches!(
+                    value.trim().to_ascii_lowercase().as_str(),
+                    "1" | "true" | "yes" | "on"
+                )
+            })
+            .unwrap_or(false)
+    })
+}
+
+/// Resolves the client IP used for login throttling: the last `X-Forwarded-For` hop when
+/// `TRUST_FORWARDED_FOR` is set, otherwise the peer address of the connection.
+pub fn client_ip(headers: &HeaderMap, peer: Option<SocketAddr>) -> Option<IpAddr> {
+    if trust_forwarded_for()
+        && let Some(ip) = forwarded_ip(headers)
+    {
+        return Some(ip);
+    }
+    peer.map(|addr| addr.ip())
+}
+
+fn forwarded_ip(headers: &HeaderMap) -> Option<IpAddr> {
+    headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|hop| hop.trim().parse::<IpAddr>().ok())
+        .next_back()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeated_failures_are_throttled_until_reset() {
+        let limiter = LoginLimiter::new(3, Duration::from_secs(600));
+        let ip: IpAddr = "203.0.113.7".parse().unwrap();
+        let start = Instant::now();
+
+        for offset in 0..3 {
+            assert!(limiter.retry_after_at(Some(ip), "alice", start).is_none());
+            limiter.record_failure_at(Some(ip), "alice", start + Duration::from_secs(offset));
+        }
+        let wait = limiter
+            .retry_after_at(Some(ip), "alice", start + Duration::from_secs(10))
+            .expect("throttled after three failures");
+        assert_eq!(wait, Duration::from_secs(590));
+
+        // The same IP is blocked for other usernames, and the username from other IPs.
+        assert!(limiter.retry_after_at(Some(ip), "bob", start).is_some());
+        assert!(limiter.retry_after_at(None, "alice", start).is_some());
+        assert!(limiter.retry_after_at(None, "bob", start).is_none());
+
+        // Failures age out of the window.
+        assert!(
+            limiter
+                .retry_after_at(Some(ip), "alice", start + Duration::from_secs(601))
+                .is_none()
+        );
+
+        limiter.reset(Some(ip), "alice");
+        assert!(limiter.retry_after_at(Some(ip), "alice", start).is_none());
+    }
+
+    #[test]
+    fn disabled_limit_never_throttles() {
+        let limiter = LoginLimiter::new(0, Duration::from_secs(60));
+        for _ in 0..10 {
+            limiter.record_failure(None, "alice");
+        }
+        assert!(limiter.retry_after(None, "alice").is_none());
+    }
+
+    #[test]
+    fn forwarded_ip_uses_last_hop() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", "10.0.0.1, 198.51.100.4".parse().unwrap());
+        assert_eq!(
+            forwarded_ip(&headers),
+            Some("198.51.100.4".parse().unwrap())
+        );
+        assert_eq!(forwarded_ip(&HeaderMap::new()), None);
+    }
+}
@@ -1,11 +1,16 @@
 pub mod admin;
 pub mod admin_utils;
+pub mod api_tokens;
 pub mod auth;
 pub mod data;
+pub mod download_limit;
+pub mod export;
 pub mod history;
 pub mod history_ui;
 pub mod landing;
+pub mod login_limit;
 pub mod models;
+pub mod page_cache;
 pub mod responses;
 pub mod router;
 pub mod state;
@@ -14,21 +19,27 @@ pub mod storage;
 pub mod templates;
 pub mod upload_ui;
 pub mod uploads;
+pub mod usage_summary;
 
 pub use auth::{AuthUser, SESSION_COOKIE, SESSION_TTL_DAYS};
 pub use data::{
     fetch_glossary_terms, fetch_journal_references, fetch_journal_topic_scores,
     fetch_journal_topics,
 };
-pub use models::{GlossaryTermRow, JournalReferenceRow, JournalTopicRow, JournalTopicScoreRow};
-pub use responses::{ApiMessage, JobSubmission, json_error};
+pub use models::{
+    GLOSSARY_SCOPES, GlossaryTermRow, JournalReferenceRow, JournalTopicRow, JournalTopicScoreRow,
+    glossary_for_scope,
+};
+pub use responses::{ApiMessage, JobSubmission, json_error, quota_error};
 pub use state::AppState;
 pub use status::{JobStatus, STATUS_CLIENT_SCRIPT};
 pub use storage::{
-    AccessMessages, ensure_storage_root, require_path, stream_file, verify_job_access,
+    AccessMessages, StoredFile, attachment_response, ensure_job_owner, ensure_storage_root,
+    read_stored_file, read_stored_file_blocking, require_path, stored_file_exists, stream_file,
+    verify_job_access,
 };
 pub use templates::{
-    ToolAdminLink, ToolPageLayout, escape_html, render_footer, render_login_page, render_tool_page,
+    ToolAdminLink, ToolPageLayout, ToolPageUser, escape_html, render_footer, render_login_page,
 };
 #[allow(unused_imports)]
 pub use upload_ui::{
@@ -36,6 +47,6 @@ pub use upload_ui::{
 };
 #[allow(unused_imports)]
 pub use uploads::{
-    FileFieldConfig, FileNaming, SavedFile, UploadError, UploadOutcome, UploadResult,
-    ensure_directory as ensure_upload_directory, process_upload_form,
+    FileFieldConfig, FileNaming, InlineFile, SavedFile, UploadError, UploadOutcome, UploadResult,
+    ensure_directory as ensure_upload_directory, process_upload_form, store_inline_files,
 };
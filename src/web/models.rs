@@ -2,16 +2,46 @@ use chrono::{DateTime, Utc};
 use sqlx::FromRow;
 use uuid::Uuid;
 
+use crate::usage::{MODULE_SUMMARIZER, MODULE_TRANSLATE_DOCX};
+
 #[derive(Clone, FromRow)]
 pub struct GlossaryTermRow {
     pub id: Uuid,
     pub source_term: String,
     pub target_term: String,
     pub notes: Option<String>,
+    /// Module key the term is limited to (see `GLOSSARY_SCOPES`); `None` applies everywhere.
+    pub scope: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+/// Scopes a glossary term can be limited to, as `(module key, label)`.
+pub const GLOSSARY_SCOPES: [(&str, &str); 2] = [
+    (MODULE_SUMMARIZER, "摘要"),
+    (MODULE_TRANSLATE_DOCX, "DOCX 翻译"),
+];
+
+/// The terms a module with glossary scope `scope` should use: its scoped terms plus global
+/// ones, where a scoped term replaces a global term with the same source term.
+pub fn glossary_for_scope<'a>(
+    terms: &'a [GlossaryTermRow],
+    scope: &str,
+) -> Vec<&'a GlossaryTermRow> {
+    let overridden = |term: &GlossaryTermRow| {
+        term.scope.is_none()
+            && terms.iter().any(|other| {
+                other.scope.as_deref() == Some(scope)
+                    && other.source_term.to_lowercase() == term.source_term.to_lowercase()
+            })
+    };
+    terms
+        .iter()
+        .filter(|term| term.scope.as_deref().is_none_or(|value| value == scope))
+        .filter(|term| !overridden(term))
+        .collect()
+}
+
 #[derive(Clone, FromRow)]
 pub struct JournalTopicRow {
     pub id: Uuid,
@@ -37,3 +67,40 @@ pub struct JournalTopicScoreRow {
     pub topic_id: Uuid,
     pub score: i16,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn term(source: &str, target: &str, scope: Option<&str>) -> GlossaryTermRow {
+        let now = Utc::now();
+        GlossaryTermRow {
+            id: Uuid::new_v4(),
+            source_term: source.to_string(),
+            target_term: target.to_string(),
+            notes: None,
+            scope: scope.map(str::to_string),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn scoped_terms_replace_global_ones_for_their_module_only() {
+        let terms = [
+            term("cell", "细胞", None),
+            term("Cell", "电池", Some("translatedocx")),
+            term("gene", "基因", None),
+            term("abstract", "摘要", Some("summarizer")),
+        ];
+        let targets = |scope| {
+            glossary_for_scope(&terms, scope)
+                .into_iter()
+                .map(|term| term.target_term.as_str())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(targets("summarizer"), ["细胞", "基因", "摘要"]);
+        assert_eq!(targets("translatedocx"), ["电池", "基因"]);
+    }
+}
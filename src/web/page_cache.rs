@@ -0,0 +1,155 @@
+use std::{
+    collections::HashMap,
+    env,
+    sync::{Arc, RwLock},
+};
+
+use crate::web::templates::{
+    ToolPageLayout, ToolPageShell, ToolPageUser, render_footer, render_tool_page,
+};
+
+/// Caches the rendered static part of each tool page, keyed by module.
+///
+/// Handlers pass the per-user parts and a builder for the layout; the builder only runs on a
+/// miss. Entries are dropped when module settings are reloaded and rebuilt when the footer
+/// changes (at the turn of the year). Set `TOOL_PAGE_CACHE=off` to render every request.
+#[derive(Clone)]
+pub struct ToolPageCache {
+    enabled: bool,
+    pages: Arc<RwLock<HashMap<&'static str, CachedPage>>>,
+}
+
+struct CachedPage {
+    footer_html: String,
+    shell: Arc<ToolPageShell>,
+}
+
+impl ToolPageCache {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            pages: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let enabled = env::var("TOOL_PAGE_CACHE")
+            .map(|value| {
+                !matches!(
+                    value.trim().to_ascii_lowercase().as_str(),
+                    "0" | "false" | "no" | "off"
+                )
+            })
+            .unwrap_or(true);
+        Self::new(enabled)
+    }
+
+    /// Renders the page for `module`, building its layout with `layout` only when no current
+    /// shell is cached.
+    pub fn render<'a>(
+        &self,
+        module: &'static str,
+        user: &ToolPageUser<'_>,
+        layout: impl FnOnce() -> ToolPageLayout<'a>,
+    ) -> String {
+        if !self.enabled {
+            return render_tool_page(layout(), user);
+        }
+
+        let footer_html = render_footer();
+        if let Some(shell) = self.cached(module, &footer_html) {
+            return shell.fill(user);
+        }
+
+        let shell = Arc::new(ToolPageShell::render(layout()));
+        let html = shell.fill(user);
+        let mut pages = self.pages.write().unwrap_or_else(|err| err.into_inner());
+        pages.insert(module, CachedPage { footer_html, shell });
+        html
+    }
+
+    /// Drops every cached page, e.g. after module settings change.
+    pub fn clear(&self) {
+        self.pages
+            .write()
+            .unwrap_or_else(|err| err.into_inner())
+            .clear();
+    }
+
+    fn cached(&self, module: &str, footer_html: &str) -> Option<Arc<ToolPageShell>> {
+        let pages = self.pages.read().unwrap_or_else(|err| err.into_inner());
+        pages
+            .get(module)
+            .filter(|page| page.footer_html == footer_html)
+            .map(|page| Arc::clone(&page.shell))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{borrow::Cow, cell::Cell};
+
+    use super::*;
+    use crate::web::templates::ToolAdminLink;
+
+    fn layout(heading: &str) -> ToolPageLayout<'_> {
+        ToolPageLayout {
+            meta_title: "测试工具",
+            page_heading: heading,
+            tab_group: "test",
+            new_tab_label: "新任务",
+            new_tab_html: Cow::Borrowed("<section class=\"panel\">form</section>"),
+            history_tab_label: "历史记录",
+            history_panel_html: Cow::Borrowed("<div>history</div>"),
+            footer_html: Cow::Owned(render_footer()),
+            extra_style_blocks: vec![Cow::Borrowed(".extra {}")],
+            body_scripts: vec![Cow::Borrowed("<script>run();</script>")],
+        }
+    }
+
+    fn user<'a>(name: &str, admin: bool) -> ToolPageUser<'a> {
+        ToolPageUser {
+            note_html: Cow::Owned(format!("当前登录：<strong>{name}</strong>。")),
+            admin_link: admin.then_some(ToolAdminLink {
+                href: "/dashboard/modules/test",
+                label: "模块管理",
+            }),
+        }
+    }
+
+    #[test]
+    fn cached_page_matches_fresh_render() {
+        let cache = ToolPageCache::new(true);
+        let builds = Cell::new(0);
+        let build = || {
+            builds.set(builds.get() + 1);
+            layout("测试")
+        };
+
+        for (name, admin) in [("alice", false), ("root", true), ("bob", false)] {
+            let visitor = user(name, admin);
+            let cached = cache.render("test", &visitor, build);
+            assert_eq!(cached, render_tool_page(layout("测试"), &visitor));
+            assert!(cached.contains(name));
+            assert_eq!(cached.contains("admin-link\" href"), admin);
+        }
+        assert_eq!(builds.get(), 1);
+
+        cache.clear();
+        let rebuilt = cache.render("test", &user("alice", false), || layout("已更新"));
+        assert!(rebuilt.contains("<h1>已更新</h1>"));
+    }
+
+    #[test]
+    fn disabled_cache_always_rebuilds() {
+        let cache = ToolPageCache::new(false);
+        let builds = Cell::new(0);
+        for _ in 0..2 {
+            cache.render("test", &user("alice", false), || {
+                builds.set(builds.get() + 1);
+                layout("测试")
+            });
+        }
+        assert_eq!(builds.get(), 2);
+    }
+}
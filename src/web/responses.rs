@@ -3,16 +3,22 @@ use axum::http::StatusCode;
 use serde::Serialize;
 use uuid::Uuid;
 
+use crate::usage::{QuotaDetail, UsageLimitError};
+
 /// Canonical JSON payload for error responses.
 #[derive(Debug, Serialize, Clone)]
 pub struct ApiMessage {
     pub message: String,
+    /// Set on quota rejections so clients can show the remaining allowance.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quota: Option<QuotaDetail>,
 }
 
 impl ApiMessage {
     pub fn new(message: impl Into<String>) -> Self {
         Self {
             message: message.into(),
+            quota: None,
         }
     }
 }
@@ -40,3 +46,18 @@ pub fn json_error(
 ) -> (StatusCode, Json<ApiMessage>) {
     (status, Json(ApiMessage::new(message)))
 }
+
+/// Rejection for a failed `usage::ensure_within_limits` check: `status` with the quota
+/// numbers when a limit was hit, 500 when the check itself failed.
+pub fn quota_error(status: StatusCode, err: &UsageLimitError) -> (StatusCode, Json<ApiMessage>) {
+    let Some(detail) = err.detail() else {
+        return json_error(StatusCode::INTERNAL_SERVER_ERROR, err.message());
+    };
+    (
+        status,
+        Json(ApiMessage {
+            message: err.message(),
+            quota: Some(detail),
+        }),
+    )
+}
@@ -1,17 +1,26 @@
+use std::time::Duration;
+
+use axum::extract::DefaultBodyLimit;
 use axum::{
-    Router,
+    Json, Router,
+    extract::State,
     http::{StatusCode, header},
-    response::IntoResponse,
+    middleware,
+    response::{IntoResponse, Response},
     routing::{get, post},
 };
-use axum::extract::DefaultBodyLimit;
+use serde_json::json;
+use tracing::warn;
 
 use crate::{
     modules,
-    web::{AppState, admin, auth, history, landing},
+    web::{AppState, admin, api_tokens, auth, export, history, landing, usage_summary},
 };
 
 const ROBOTS_TXT_BODY: &str = include_str!("../../robots.txt");
+/// Upper bound on the readiness database probe, so a stuck pool fails the check instead of
+/// hanging the load balancer's request.
+const HEALTH_DB_TIMEOUT: Duration = Duration::from_secs(5);
 
 pub fn build_router(state: AppState) -> Router {
     // Set a higher body limit to accommodate large manuscript batches (500MB)
@@ -22,7 +31,14 @@ pub fn build_router(state: AppState) -> Router {
         .route("/", get(landing::landing_page))
         .route("/login", get(auth::login_page).post(auth::process_login))
         .route("/logout", post(auth::logout))
+        .route("/account/password", post(auth::change_password))
+        .route(
+            "/account/tokens",
+            get(api_tokens::api_tokens_page).post(api_tokens::create_api_token),
+        )
+        .route("/account/tokens/revoke", post(api_tokens::revoke_api_token))
         .route("/healthz", get(healthz))
+        .route("/livez", get(livez))
         .route("/robots.txt", get(robots_txt))
         .route("/dashboard", get(admin::dashboard))
         .route("/dashboard/users", post(admin::create_user))
@@ -30,8 +46,16 @@ pub fn build_router(state: AppState) -> Router {
             "/dashboard/users/password",
             post(admin::update_user_password),
         )
+        .route(
+            "/dashboard/users/email",
+            post(admin::update_notification_email),
+        )
         .route("/dashboard/users/group", post(admin::assign_user_group))
         .route("/dashboard/usage-groups", post(admin::save_usage_group))
+        .route("/dashboard/usage-policies", post(admin::save_unit_policies))
+        .route("/dashboard/model-aliases", post(admin::save_model_aliases))
+        .route("/dashboard/retention", post(admin::save_retention_policies))
+        .route("/dashboard/llm-metrics", get(admin::llm_call_metrics))
         .route("/dashboard/glossary", post(admin::create_glossary_term))
         .route(
             "/dashboard/glossary/update",
@@ -41,6 +65,14 @@ pub fn build_router(state: AppState) -> Router {
             "/dashboard/glossary/delete",
             post(admin::delete_glossary_term),
         )
+        .route(
+            "/dashboard/glossary/import",
+            post(admin::import_glossary_csv),
+        )
+        .route(
+            "/dashboard/glossary/export",
+            get(admin::export_glossary_csv),
+        )
         .route(
             "/dashboard/journal-topics",
             post(admin::upsert_journal_topic),
@@ -58,12 +90,18 @@ pub fn build_router(state: AppState) -> Router {
             post(admin::delete_journal_reference),
         )
         .route("/api/history", get(history::recent_history))
+        .route("/api/home/summary", get(history::home_summary))
+        .route("/api/usage/summary", get(usage_summary::usage_summary))
+        .route("/api/export/me", get(export::export_my_outputs))
         .merge(modules::summarizer::router())
         .merge(modules::translatedocx::router())
         .merge(modules::grader::router())
         .merge(modules::info_extract::router())
         .merge(modules::reviewer::router())
+        .merge(modules::transcribe::router())
+        .merge(modules::docx2pdf::router())
         .layer(DefaultBodyLimit::max(body_limit))
+        .layer(middleware::from_fn(auth::bearer_token_cookie))
         .with_state(state)
 }
 
@@ -74,6 +112,54 @@ async fn robots_txt() -> impl IntoResponse {
     )
 }
 
-async fn healthz() -> impl IntoResponse {
+/// Readiness probe: 200 only when Postgres answers `SELECT 1` and at least one LLM provider
+/// key is configured, otherwise 503. The body reports each check.
+async fn healthz(State(state): State<AppState>) -> Response {
+    let probe = sqlx::query("SELECT 1").execute(state.pool_ref());
+    let database = match tokio::time::timeout(HEALTH_DB_TIMEOUT, probe).await {
+        Ok(Ok(_)) => true,
+        Ok(Err(err)) => {
+            warn!(?err, "readiness check could not reach the database");
+            false
+        }
+        Err(_) => {
+            warn!("readiness check timed out waiting for the database");
+            false
+        }
+    };
+    let llm = state.llm_client().has_provider_key();
+
+    let status = if database && llm {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(json!({ "database": database, "llm": llm }))).into_response()
+}
+
+/// Liveness probe: the process is up and serving requests.
+async fn livez() -> impl IntoResponse {
     StatusCode::OK
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{StubProvider, StubReply, test_state};
+    use sqlx::PgPool;
+
+    #[sqlx::test]
+    #[ignore = "needs DATABASE_URL (disposable Postgres)"]
+    async fn healthz_requires_the_database(pool: PgPool) {
+        let provider = StubProvider::start(|_| StubReply::text("")).await;
+        let state = test_state(pool.clone(), &provider).await;
+        assert_eq!(healthz(State(state.clone())).await.status(), StatusCode::OK);
+
+        pool.close().await;
+        assert_eq!(
+            healthz(State(state)).await.status(),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+        assert_eq!(livez().await.into_response().status(), StatusCode::OK);
+    }
+}
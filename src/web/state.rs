@@ -1,4 +1,4 @@
-use std::{env, sync::Arc};
+use std::{env, sync::Arc, time::Duration};
 
 use anyhow::{Context, Result, anyhow};
 use sqlx::{PgPool, postgres::PgPoolOptions};
@@ -9,23 +9,36 @@ use uuid::Uuid;
 use crate::{
     config::{
         DocxTranslatorSettings, GraderSettings, InfoExtractSettings, ModuleSettings,
-        ReviewerSettings, SummarizerSettings,
+        ReviewerSettings, SummarizerSettings, TranscribeSettings,
     },
     llm::LlmClient,
+    llm_metrics,
+    notifications::{Mailer, WebhookClient},
+    web::{download_limit::DownloadLimiter, login_limit::LoginLimiter, page_cache::ToolPageCache},
 };
 
+/// Upper bound for a single provider call; long PDF reviews finish well within this.
+const LLM_REQUEST_TIMEOUT: Duration = Duration::from_secs(600);
+
 #[derive(Clone)]
 pub struct AppState {
     pool: PgPool,
     settings: Arc<RwLock<ModuleSettings>>,
     llm: LlmClient,
+    downloads: DownloadLimiter,
+    logins: LoginLimiter,
+    pages: ToolPageCache,
+    mailer: Option<Mailer>,
+    webhooks: WebhookClient,
 }
 
 impl AppState {
     pub async fn new() -> Result<Self> {
         let database_url = env::var("DATABASE_URL").context("DATABASE_URL env var is missing")?;
 
-        let llm_client = LlmClient::from_env().context("failed to initialize LLM client")?;
+        let llm_client = LlmClient::from_env_with_timeout(LLM_REQUEST_TIMEOUT)
+            .context("failed to initialize LLM client")?;
+        let mailer = Mailer::from_env().context("failed to initialize SMTP mailer")?;
 
         let pool = PgPoolOptions::new()
             .max_connections(10)
@@ -45,11 +58,44 @@ impl AppState {
             .await
             .context("failed to load module settings")?;
 
-        Ok(Self {
+        let state = Self {
             pool,
             settings: Arc::new(RwLock::new(settings)),
             llm: llm_client,
-        })
+            downloads: DownloadLimiter::from_env(),
+            logins: LoginLimiter::from_env(),
+            pages: ToolPageCache::from_env(),
+            mailer,
+            webhooks: WebhookClient::new()?,
+        };
+        state.reload_model_aliases().await?;
+        llm_metrics::spawn_recorder(state.pool.clone(), &state.llm);
+        Ok(state)
+    }
+
+    /// State over an already migrated pool (e.g. from `#[sqlx::test]`) with default module
+    /// settings and the given LLM client.
+    #[cfg(test)]
+    pub async fn for_tests(pool: PgPool, llm: LlmClient) -> Result<Self> {
+        ModuleSettings::ensure_defaults(&pool)
+            .await
+            .context("failed to seed default module settings")?;
+        let settings = ModuleSettings::load(&pool)
+            .await
+            .context("failed to load module settings")?;
+
+        let state = Self {
+            pool,
+            settings: Arc::new(RwLock::new(settings)),
+            llm,
+            downloads: DownloadLimiter::from_env(),
+            logins: LoginLimiter::from_env(),
+            pages: ToolPageCache::from_env(),
+            mailer: None,
+            webhooks: WebhookClient::new()?,
+        };
+        state.reload_model_aliases().await?;
+        Ok(state)
     }
 
     pub async fn ensure_seed_admin(&self) -> Result<()> {
@@ -93,6 +139,28 @@ impl AppState {
         self.llm.clone()
     }
 
+    pub fn download_limiter(&self) -> &DownloadLimiter {
+        &self.downloads
+    }
+
+    pub fn login_limiter(&self) -> &LoginLimiter {
+        &self.logins
+    }
+
+    pub fn tool_page_cache(&self) -> &ToolPageCache {
+        &self.pages
+    }
+
+    /// SMTP sender for job completion notices; `None` when SMTP is not configured.
+    pub fn mailer(&self) -> Option<&Mailer> {
+        self.mailer.as_ref()
+    }
+
+    /// Client for the optional job completion callbacks.
+    pub fn webhooks(&self) -> &WebhookClient {
+        &self.webhooks
+    }
+
     pub fn pool(&self) -> PgPool {
         self.pool.clone()
     }
@@ -126,12 +194,32 @@ impl AppState {
         guard.info_extract().cloned()
     }
 
+    pub async fn transcribe_settings(&self) -> Option<TranscribeSettings> {
+        let guard = self.settings.read().await;
+        guard.transcribe().cloned()
+    }
+
+    /// Loads the admin-maintained Poe → OpenRouter fallback aliases into the LLM client.
+    pub async fn reload_model_aliases(&self) -> Result<()> {
+        let rows: Vec<(String, String)> =
+            sqlx::query_as("SELECT poe_model, openrouter_model FROM llm_model_aliases")
+                .fetch_all(&self.pool)
+                .await
+                .context("failed to load model aliases")?;
+        self.llm
+            .provider_aliases()
+            .replace(rows.into_iter().collect());
+        Ok(())
+    }
+
     pub async fn reload_settings(&self) -> Result<()> {
         let latest = ModuleSettings::load(&self.pool)
             .await
             .context("failed to reload module settings")?;
         let mut guard = self.settings.write().await;
         *guard = latest;
+        // Tool pages can show settings (e.g. the translator's batch limit).
+        self.pages.clear();
         Ok(())
     }
 }
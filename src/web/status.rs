@@ -1,5 +1,8 @@
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use std::borrow::Cow;
+use std::{borrow::Cow, env, sync::OnceLock};
+
+const DEFAULT_POLL_ACTIVE_MS: u64 = 3000;
+const DEFAULT_POLL_IDLE_MS: u64 = 8000;
 
 pub const STATUS_CLIENT_SCRIPT: &str = concat!(
     "<script>\n",
@@ -14,6 +17,8 @@ pub enum JobStatus {
     Completed,
     Failed,
     Queued,
+    Cancelled,
+    Skipped,
     Other(Cow<'static, str>),
 }
 
@@ -25,6 +30,8 @@ impl JobStatus {
             JobStatus::Completed => "completed",
             JobStatus::Failed => "failed",
             JobStatus::Queued => "queued",
+            JobStatus::Cancelled => "cancelled",
+            JobStatus::Skipped => "skipped",
             JobStatus::Other(value) => value.as_ref(),
         }
     }
@@ -36,10 +43,32 @@ impl JobStatus {
             JobStatus::Completed => "已完成",
             JobStatus::Failed => "已失败",
             JobStatus::Queued => "排队中",
+            JobStatus::Cancelled => "已取消",
+            JobStatus::Skipped => "已跳过",
             JobStatus::Other(value) => value.as_ref(),
         }
     }
 
+    /// Whether the job has settled and its status will no longer change.
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            JobStatus::Completed | JobStatus::Failed | JobStatus::Cancelled | JobStatus::Skipped
+        )
+    }
+
+    /// Delay the status pages should wait before polling again: short while the job is being
+    /// processed, longer while it waits in the queue, and `0` (stop polling) once it is terminal.
+    /// Every module's status response carries it as `poll_interval_ms`.
+    pub fn poll_interval_ms(&self) -> u64 {
+        let (active, idle) = poll_intervals();
+        match self {
+            JobStatus::Processing => active,
+            status if status.is_terminal() => 0,
+            _ => idle,
+        }
+    }
+
     pub fn from_str(value: &str) -> Self {
         match value {
             "pending" => JobStatus::Pending,
@@ -47,6 +76,8 @@ impl JobStatus {
             "completed" => JobStatus::Completed,
             "failed" => JobStatus::Failed,
             "queued" => JobStatus::Queued,
+            "cancelled" => JobStatus::Cancelled,
+            "skipped" => JobStatus::Skipped,
             other => JobStatus::Other(Cow::Owned(other.to_string())),
         }
     }
@@ -70,3 +101,45 @@ impl<'de> Deserialize<'de> for JobStatus {
         Ok(JobStatus::from_str(&value))
     }
 }
+
+/// Active and idle poll intervals, overridable with `STATUS_POLL_ACTIVE_MS` and
+/// `STATUS_POLL_IDLE_MS`. Zero or unparsable values fall back to the defaults.
+fn poll_intervals() -> (u64, u64) {
+    static INTERVALS: OnceLock<(u64, u64)> = OnceLock::new();
+    *INTERVALS.get_or_init(|| {
+        (
+            env_interval("STATUS_POLL_ACTIVE_MS", DEFAULT_POLL_ACTIVE_MS),
+            env_interval("STATUS_POLL_IDLE_MS", DEFAULT_POLL_IDLE_MS),
+        )
+    })
+}
+
+fn env_interval(key: &str, default: u64) -> u64 {
+    env::var(key)
+        .ok()
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .filter(|ms| *ms > 0)
+        .unwrap_or(default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn poll_hint_stops_for_terminal_statuses() {
+        for status in ["completed", "failed", "cancelled", "skipped"] {
+            assert_eq!(
+                JobStatus::from_str(status).poll_interval_ms(),
+                0,
+                "{status}"
+            );
+        }
+
+        let processing = JobStatus::Processing.poll_interval_ms();
+        let pending = JobStatus::Pending.poll_interval_ms();
+        assert!(processing > 0);
+        assert!(pending > processing);
+        assert_eq!(JobStatus::Queued.poll_interval_ms(), pending);
+    }
+}
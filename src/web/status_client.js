@@ -9,6 +9,8 @@
     completed: '已完成',
     failed: '已失败',
     queued: '排队中',
+    cancelled: '已取消',
+    skipped: '已跳过',
   };
 
   window.translateJobStatus = function (status) {
@@ -18,4 +20,11 @@
     const key = String(status).toLowerCase();
     return LABELS[key] || status;
   };
+
+  // Delay before the next status poll, from the server's `poll_interval_ms` hint: 0 means the
+  // job has settled and polling should stop. Falls back when the hint is missing.
+  window.nextPollDelay = function (payload, fallback) {
+    const hint = payload ? payload.poll_interval_ms : undefined;
+    return typeof hint === 'number' && hint >= 0 ? hint : fallback;
+  };
 })();
@@ -1,4 +1,11 @@
-use std::path::Path;
+use std::{
+    env,
+    ffi::OsString,
+    fs::{self, File},
+    io::{self, ErrorKind, Read, Write},
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
 
 use anyhow::{Context, Result};
 use axum::Json;
@@ -8,6 +15,7 @@ use axum::{
 };
 use tracing::error;
 use uuid::Uuid;
+use zip::{CompressionMethod, ZipArchive, ZipWriter, result::ZipError, write::SimpleFileOptions};
 
 use crate::web::{ApiMessage, AuthUser, json_error};
 
@@ -30,12 +38,76 @@ pub struct AccessMessages<'a> {
     pub purged: &'a str,
 }
 
+/// Whether `JOB_PRIVACY_MODE` is enabled. In privacy mode, jobs owned by another
+/// user are reported as missing (404) so job ids cannot be enumerated.
+pub fn job_privacy_mode() -> bool {
+    static MODE: OnceLock<bool> = OnceLock::new();
+    *MODE.get_or_init(|| {
+        env::var("JOB_PRIVACY_MODE")
+            .map(|value| {
+                matches!(
+                    value.trim().to_ascii_lowercase().as_str(),
+                    "1" | "true" | "yes" | "on"
+                )
+            })
+            .unwrap_or(false)
+    })
+}
+
+/// Reject requesters who neither own the job nor are admins.
+pub fn ensure_job_owner(
+    owner_id: Uuid,
+    requester: &AuthUser,
+    not_found: &str,
+    forbidden: &str,
+) -> Result<(), (StatusCode, Json<ApiMessage>)> {
+    check_job_owner(
+        owner_id,
+        requester,
+        not_found,
+        forbidden,
+        job_privacy_mode(),
+    )
+}
+
+fn check_job_owner(
+    owner_id: Uuid,
+    requester: &AuthUser,
+    not_found: &str,
+    forbidden: &str,
+    privacy_mode: bool,
+) -> Result<(), (StatusCode, Json<ApiMessage>)> {
+    if owner_id == requester.id || requester.is_admin {
+        return Ok(());
+    }
+
+    if privacy_mode {
+        Err(json_error(StatusCode::NOT_FOUND, not_found))
+    } else {
+        Err(json_error(StatusCode::FORBIDDEN, forbidden))
+    }
+}
+
 /// Validate job access for the current user, enforcing ownership and purge status.
 pub async fn verify_job_access<T, F, Fut>(
     fetch: F,
     requester: &AuthUser,
     messages: AccessMessages<'_>,
 ) -> Result<T, (StatusCode, Json<ApiMessage>)>
+where
+    T: JobAccess,
+    F: FnOnce() -> Fut,
+    Fut: std::future::Future<Output = sqlx::Result<Option<T>>>,
+{
+    verify_job_access_with_mode(fetch, requester, messages, job_privacy_mode()).await
+}
+
+async fn verify_job_access_with_mode<T, F, Fut>(
+    fetch: F,
+    requester: &AuthUser,
+    messages: AccessMessages<'_>,
+    privacy_mode: bool,
+) -> Result<T, (StatusCode, Json<ApiMessage>)>
 where
     T: JobAccess,
     F: FnOnce() -> Fut,
@@ -49,9 +121,13 @@ where
         })?
         .ok_or_else(|| json_error(StatusCode::NOT_FOUND, messages.not_found))?;
 
-    if record.user_id() != requester.id && !requester.is_admin {
-        return Err(json_error(StatusCode::FORBIDDEN, messages.forbidden));
-    }
+    check_job_owner(
+        record.user_id(),
+        requester,
+        messages.not_found,
+        messages.forbidden,
+        privacy_mode,
+    )?;
 
     if record.files_purged_at().is_some() {
         return Err(json_error(StatusCode::GONE, messages.purged));
@@ -68,13 +144,182 @@ pub fn require_path(
     path.ok_or_else(|| json_error(StatusCode::NOT_FOUND, message))
 }
 
+/// Archive that maintenance writes in place of an old job directory: `<job_dir>.zip`.
+pub fn job_archive_path(job_dir: &Path) -> PathBuf {
+    let mut name = job_dir.file_name().map(OsString::from).unwrap_or_default();
+    name.push(".zip");
+    job_dir.with_file_name(name)
+}
+
+/// Compresses `job_dir` into `job_archive_path(job_dir)` and removes the directory.
+/// Returns `false` when there was no directory to archive. Blocking.
+pub fn archive_job_directory(job_dir: &Path) -> Result<bool> {
+    if !job_dir.is_dir() {
+        return Ok(false);
+    }
+
+    let archive_path = job_archive_path(job_dir);
+    let partial_path = archive_path.with_extension("zip.partial");
+    let file = File::create(&partial_path)
+        .with_context(|| format!("failed to create archive {}", partial_path.display()))?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .large_file(true);
+
+    let mut pending = vec![job_dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in
+            fs::read_dir(&dir).with_context(|| format!("failed to list {}", dir.display()))?
+        {
+            let path = entry?.path();
+            if path.is_dir() {
+                pending.push(path);
+                continue;
+            }
+            let name = archive_entry_name(job_dir, &path)
+                .with_context(|| format!("unexpected path {} in job directory", path.display()))?;
+            zip.start_file(name.as_str(), options)
+                .with_context(|| format!("failed to add {name} to archive"))?;
+            let mut source =
+                File::open(&path).with_context(|| format!("failed to open {}", path.display()))?;
+            io::copy(&mut source, &mut zip)
+                .with_context(|| format!("failed to compress {}", path.display()))?;
+        }
+    }
+
+    zip.finish()?
+        .flush()
+        .context("failed to finalize job archive")?;
+    fs::rename(&partial_path, &archive_path)
+        .with_context(|| format!("failed to move archive to {}", archive_path.display()))?;
+    fs::remove_dir_all(job_dir)
+        .with_context(|| format!("failed to remove archived directory {}", job_dir.display()))?;
+    Ok(true)
+}
+
+/// `path` relative to `root`, with `/` separators as zip entry names use.
+fn archive_entry_name(root: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root).ok()?;
+    let parts: Option<Vec<&str>> = relative.iter().map(|part| part.to_str()).collect();
+    Some(parts?.join("/"))
+}
+
+/// Reads a stored job file, falling back to the job archive when maintenance has
+/// compressed its directory. Blocking.
+pub fn read_stored_file_blocking(path: &Path) -> io::Result<Vec<u8>> {
+    match fs::read(path) {
+        Err(err) if err.kind() == ErrorKind::NotFound => read_archived_file(path),
+        result => result,
+    }
+}
+
+/// Async `read_stored_file_blocking`.
+pub async fn read_stored_file(path: &Path) -> io::Result<Vec<u8>> {
+    match tokio::fs::read(path).await {
+        Err(err) if err.kind() == ErrorKind::NotFound => {
+            let path = path.to_path_buf();
+            tokio::task::spawn_blocking(move || read_archived_file(&path))
+                .await
+                .map_err(io::Error::other)?
+        }
+        result => result,
+    }
+}
+
+/// Whether `path` exists on disk or inside its job archive.
+pub async fn stored_file_exists(path: &Path) -> bool {
+    if tokio::fs::try_exists(path).await.unwrap_or(false) {
+        return true;
+    }
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || find_archived_entry(&path).is_some())
+        .await
+        .unwrap_or(false)
+}
+
+/// The nearest enclosing job archive holding `path`, with the entry name inside it.
+fn find_archived_entry(path: &Path) -> Option<(PathBuf, String)> {
+    path.ancestors()
+        .skip(1)
+        .take_while(|dir| !dir.as_os_str().is_empty())
+        .find_map(|dir| {
+            let archive = job_archive_path(dir);
+            if !archive.is_file() {
+                return None;
+            }
+            let name = archive_entry_name(dir, path)?;
+            let mut zip = ZipArchive::new(File::open(&archive).ok()?).ok()?;
+            zip.by_name(&name).ok()?;
+            Some((archive, name))
+        })
+}
+
+fn read_archived_file(path: &Path) -> io::Result<Vec<u8>> {
+    let Some((archive, name)) = find_archived_entry(path) else {
+        return Err(io::Error::new(
+            ErrorKind::NotFound,
+            format!("{} not found on disk or in a job archive", path.display()),
+        ));
+    };
+    let mut zip = ZipArchive::new(File::open(&archive)?).map_err(zip_io_error)?;
+    let mut entry = zip.by_name(&name).map_err(zip_io_error)?;
+    let mut bytes = Vec::with_capacity(entry.size() as usize);
+    entry.read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// A stored job file opened for copying, either on disk or inside its job archive.
+pub enum StoredFile {
+    Disk(File),
+    Archived { zip: ZipArchive<File>, name: String },
+}
+
+impl StoredFile {
+    /// Opens `path`, falling back to the job archive when maintenance has compressed its
+    /// directory. Blocking.
+    pub fn open_blocking(path: &Path) -> io::Result<Self> {
+        match File::open(path) {
+            Ok(file) => Ok(Self::Disk(file)),
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                let Some((archive, name)) = find_archived_entry(path) else {
+                    return Err(err);
+                };
+                let zip = ZipArchive::new(File::open(&archive)?).map_err(zip_io_error)?;
+                Ok(Self::Archived { zip, name })
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Streams the contents into `writer`, decompressing archived entries on the fly, and
+    /// returns the bytes copied. Blocking.
+    pub fn copy_to(&mut self, writer: &mut impl Write) -> io::Result<u64> {
+        match self {
+            Self::Disk(file) => io::copy(file, writer),
+            Self::Archived { zip, name } => {
+                let mut entry = zip.by_name(name).map_err(zip_io_error)?;
+                io::copy(&mut entry, writer)
+            }
+        }
+    }
+}
+
+fn zip_io_error(err: ZipError) -> io::Error {
+    match err {
+        ZipError::Io(err) => err,
+        ZipError::FileNotFound => io::Error::new(ErrorKind::NotFound, err),
+        other => io::Error::new(ErrorKind::InvalidData, other),
+    }
+}
+
 /// Stream a file with a standard attachment disposition.
 pub async fn stream_file(
     path: &Path,
     filename: &str,
     content_type: &str,
 ) -> Result<Response, (StatusCode, Json<ApiMessage>)> {
-    let bytes = tokio::fs::read(path).await.map_err(|err| {
+    let bytes = read_stored_file(path).await.map_err(|err| {
         error!(?err, file = %path.display(), "failed to read download file");
         json_error(StatusCode::INTERNAL_SERVER_ERROR, "文件读取失败。")
     })?;
@@ -92,6 +337,22 @@ pub async fn stream_file(
     Ok((headers, bytes).into_response())
 }
 
+/// Wrap in-memory bytes in an attachment response.
+pub fn attachment_response(bytes: Vec<u8>, filename: &str, content_type: &'static str) -> Response {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        axum::http::header::CONTENT_TYPE,
+        HeaderValue::from_static(content_type),
+    );
+    headers.insert(
+        axum::http::header::CONTENT_DISPOSITION,
+        HeaderValue::from_str(&format!(r#"attachment; filename="{}""#, filename))
+            .unwrap_or_else(|_| HeaderValue::from_static("attachment")),
+    );
+
+    (headers, bytes).into_response()
+}
+
 // Blanket implementation for tuples returned from SQL queries.
 impl JobAccess for (Uuid, Option<chrono::DateTime<chrono::Utc>>) {
     fn user_id(&self) -> Uuid {
@@ -102,3 +363,89 @@ impl JobAccess for (Uuid, Option<chrono::DateTime<chrono::Utc>>) {
         self.1
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MESSAGES: AccessMessages<'static> = AccessMessages {
+        not_found: "未找到任务。",
+        forbidden: "您无权访问该任务。",
+        purged: "任务文件已清除。",
+    };
+
+    fn requester(is_admin: bool) -> AuthUser {
+        AuthUser {
+            id: Uuid::new_v4(),
+            username: "tester".to_string(),
+            is_admin,
+        }
+    }
+
+    async fn access_status(user: &AuthUser, owner: Uuid, privacy_mode: bool) -> StatusCode {
+        let result = verify_job_access_with_mode(
+            || async move { Ok(Some((owner, None))) },
+            user,
+            MESSAGES,
+            privacy_mode,
+        )
+        .await;
+
+        match result {
+            Ok(_) => StatusCode::OK,
+            Err((status, _)) => status,
+        }
+    }
+
+    #[tokio::test]
+    async fn unowned_job_is_not_found_in_privacy_mode() {
+        let user = requester(false);
+        let other = Uuid::new_v4();
+
+        assert_eq!(
+            access_status(&user, other, true).await,
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            access_status(&user, other, false).await,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(access_status(&user, user.id, true).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn archived_job_files_stay_readable() {
+        let root = tempfile::tempdir().unwrap();
+        let job_dir = root.path().join("job-1");
+        fs::create_dir_all(job_dir.join("outputs")).unwrap();
+        fs::write(job_dir.join("summary.txt"), "摘要").unwrap();
+        fs::write(job_dir.join("outputs").join("result.xlsx"), b"xlsx").unwrap();
+
+        assert!(archive_job_directory(&job_dir).unwrap());
+        assert!(!job_dir.exists());
+        assert!(root.path().join("job-1.zip").is_file());
+        assert!(!archive_job_directory(&job_dir).unwrap());
+
+        let summary = job_dir.join("summary.txt");
+        assert_eq!(read_stored_file(&summary).await.unwrap(), "摘要".as_bytes());
+        let result = job_dir.join("outputs").join("result.xlsx");
+        assert_eq!(read_stored_file_blocking(&result).unwrap(), b"xlsx");
+        assert!(stored_file_exists(&result).await);
+
+        let missing = job_dir.join("missing.csv");
+        assert!(!stored_file_exists(&missing).await);
+        assert_eq!(
+            read_stored_file(&missing).await.unwrap_err().kind(),
+            ErrorKind::NotFound
+        );
+    }
+
+    #[tokio::test]
+    async fn admins_bypass_privacy_mode() {
+        let admin = requester(true);
+        assert_eq!(
+            access_status(&admin, Uuid::new_v4(), true).await,
+            StatusCode::OK
+        );
+    }
+}
@@ -41,6 +41,8 @@ const TOOL_PAGE_BASE_STYLES: &str = r#"
         .status-tag.processing { background: #e0f2fe; color: #1d4ed8; }
         .status-tag.completed { background: #dcfce7; color: #166534; }
         .status-tag.failed { background: #fee2e2; color: #b91c1c; }
+        .status-tag.cancelled { background: #e2e8f0; color: #475569; }
+        .status-tag.skipped { background: #fef9c3; color: #854d0e; }
         .job-table { width: 100%; border-collapse: collapse; margin-top: 1rem; }
         .job-table th, .job-table td { padding: 0.65rem 0.85rem; border: 1px solid #e2e8f0; text-align: left; font-size: 0.92rem; }
         .job-table th { background: #f1f5f9; }
@@ -60,63 +62,68 @@ pub struct ToolAdminLink<'a> {
     pub label: &'a str,
 }
 
+/// The parts of a tool page that are the same for every visitor.
 pub struct ToolPageLayout<'a> {
     pub meta_title: &'a str,
     pub page_heading: &'a str,
-    pub username: &'a str,
-    pub note_html: Cow<'a, str>,
     pub tab_group: &'a str,
     pub new_tab_label: &'a str,
     pub new_tab_html: Cow<'a, str>,
     pub history_tab_label: &'a str,
     pub history_panel_html: Cow<'a, str>,
-    pub admin_link: Option<ToolAdminLink<'a>>,
     pub footer_html: Cow<'a, str>,
     pub extra_style_blocks: Vec<Cow<'a, str>>,
     pub body_scripts: Vec<Cow<'a, str>>,
 }
 
-pub fn render_tool_page(layout: ToolPageLayout<'_>) -> String {
-    let ToolPageLayout {
-        meta_title,
-        page_heading,
-        username: _username,
-        note_html,
-        tab_group,
-        new_tab_label,
-        new_tab_html,
-        history_tab_label,
-        history_panel_html,
-        admin_link,
-        footer_html,
-        extra_style_blocks,
-        body_scripts,
-    } = layout;
-
-    let admin_link_html = admin_link
-        .map(|link| {
-            format!(
-                r#"<a class="admin-link" href="{href}">{label}</a>"#,
-                href = link.href,
-                label = link.label,
-            )
-        })
-        .unwrap_or_default();
-
-    let styles = std::iter::once(Cow::Borrowed(TOOL_PAGE_BASE_STYLES))
-        .chain(extra_style_blocks.into_iter())
-        .map(|block| block.into_owned())
-        .collect::<Vec<_>>()
-        .join("\n");
-
-    let scripts = body_scripts
-        .into_iter()
-        .map(|script| script.into_owned())
-        .collect::<Vec<_>>()
-        .join("\n");
+/// The per-request parts of a tool page: the header note (which greets the user) and the
+/// admin link shown to admins.
+pub struct ToolPageUser<'a> {
+    pub note_html: Cow<'a, str>,
+    pub admin_link: Option<ToolAdminLink<'a>>,
+}
+
+/// A rendered tool page with the per-user parts left out, so it can be cached and filled in
+/// per request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolPageShell {
+    /// Everything before the admin link.
+    head: String,
+    /// Between the admin link and the header note.
+    middle: String,
+    /// Everything after the header note.
+    tail: String,
+}
 
-    format!(
-        r#"<!DOCTYPE html>
+impl ToolPageShell {
+    pub fn render(layout: ToolPageLayout<'_>) -> Self {
+        let ToolPageLayout {
+            meta_title,
+            page_heading,
+            tab_group,
+            new_tab_label,
+            new_tab_html,
+            history_tab_label,
+            history_panel_html,
+            footer_html,
+            extra_style_blocks,
+            body_scripts,
+        } = layout;
+
+        let styles = std::iter::once(Cow::Borrowed(TOOL_PAGE_BASE_STYLES))
+            .chain(extra_style_blocks)
+            .map(|block| block.into_owned())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let scripts = body_scripts
+            .into_iter()
+            .map(|script| script.into_owned())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let head = format!(
+            r#"<!DOCTYPE html>
 <html lang="zh-CN">
 <head>
     <meta charset="UTF-8">
@@ -133,10 +140,15 @@ pub fn render_tool_page(layout: ToolPageLayout<'_>) -> String {
             <h1>{page_heading}</h1>
             <div style="display:flex; gap:0.75rem; align-items:center; flex-wrap:wrap;">
                 <a class="back-link" href="/">← 返回首页</a>
-                {admin_link_html}
+                "#
+        );
+        let middle = r#"
             </div>
         </div>
-        <p class="note">{note_html}</p>
+        <p class="note">"#
+            .to_string();
+        let tail = format!(
+            r#"</p>
     </header>
     <main>
         <div class="tool-tabs" data-tab-group="{tab_group}">
@@ -155,20 +167,44 @@ pub fn render_tool_page(layout: ToolPageLayout<'_>) -> String {
     </main>
 {scripts}
 </body>
-</html>"#,
-        meta_title = meta_title,
-        page_heading = page_heading,
-        note_html = note_html,
-        tab_group = tab_group,
-        new_tab_label = new_tab_label,
-        new_tab_html = new_tab_html,
-        history_tab_label = history_tab_label,
-        history_panel_html = history_panel_html,
-        admin_link_html = admin_link_html,
-        footer_html = footer_html,
-        styles = styles,
-        scripts = scripts,
-    )
+</html>"#
+        );
+
+        Self { head, middle, tail }
+    }
+
+    /// Splices the per-user parts into the shell.
+    pub fn fill(&self, user: &ToolPageUser<'_>) -> String {
+        let admin_link_html = user
+            .admin_link
+            .as_ref()
+            .map(|link| {
+                format!(
+                    r#"<a class="admin-link" href="{href}">{label}</a>"#,
+                    href = link.href,
+                    label = link.label,
+                )
+            })
+            .unwrap_or_default();
+
+        let mut html = String::with_capacity(
+            self.head.len()
+                + admin_link_html.len()
+                + self.middle.len()
+                + user.note_html.len()
+                + self.tail.len(),
+        );
+        html.push_str(&self.head);
+        html.push_str(&admin_link_html);
+        html.push_str(&self.middle);
+        html.push_str(&user.note_html);
+        html.push_str(&self.tail);
+        html
+    }
+}
+
+pub fn render_tool_page(layout: ToolPageLayout<'_>, user: &ToolPageUser<'_>) -> String {
+    ToolPageShell::render(layout).fill(user)
 }
 
 pub fn render_login_page() -> String {
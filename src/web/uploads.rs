@@ -157,44 +157,9 @@ pub async fn process_upload_form(
 ) -> UploadResult<UploadOutcome> {
     ensure_directory(dest_dir).await?;
 
-    let mut field_states = HashMap::new();
-    for config in field_configs {
-        if config.max_files == 0 {
-            return Err(UploadError::new(format!(
-                "字段 `{}` 的 max_files 必须大于 0",
-                config.field_name
-            )));
-        }
-        if config.min_files > config.max_files {
-            return Err(UploadError::new(format!(
-                "字段 `{}` 的 min_files 不能大于 max_files",
-                config.field_name
-            )));
-        }
-        field_states.insert(
-            config.field_name.to_string(),
-            FieldState {
-                config: *config,
-                count: 0,
-            },
-        );
-    }
-
-    let allowed_lookup: HashMap<&str, HashSet<String>> = field_configs
-        .iter()
-        .map(|config| {
-            let set = config
-                .allowed_extensions
-                .iter()
-                .map(|ext| ext.to_ascii_lowercase())
-                .collect();
-            (config.field_name, set)
-        })
-        .collect();
-
+    let mut tracker = FieldTracker::new(field_configs)?;
     let mut text_fields: HashMap<String, Vec<String>> = HashMap::new();
     let mut saved_files: Vec<SavedFile> = Vec::new();
-    let mut used_names: HashSet<String> = HashSet::new();
 
     while let Some(mut field) = multipart
         .next_field()
@@ -215,50 +180,13 @@ pub async fn process_upload_form(
             continue;
         }
 
-        let Some(state) = field_states.get_mut(field_name.as_str()) else {
-            return Err(UploadError::new(format!(
-                "不支持的文件字段: `{field_name}`"
-            )));
-        };
-
-        if state.count >= state.config.max_files {
-            return Err(UploadError::new(format!(
-                "字段 `{}` 上传文件数量超过限制 (最多 {})",
-                state.config.field_name, state.config.max_files
-            )));
+        // Browsers submit an untouched optional file input as an empty, unnamed file part.
+        if field.file_name() == Some("") {
+            continue;
         }
 
         let file_name = field.file_name().unwrap_or("upload.bin").to_string();
-        let mut sanitized = sanitize_filename::sanitize(&file_name);
-        let extension = Path::new(&file_name)
-            .extension()
-            .and_then(|ext| ext.to_str())
-            .map(|ext| ext.to_ascii_lowercase())
-            .unwrap_or_default();
-
-        if sanitized.is_empty() {
-            sanitized = if extension.is_empty() {
-                format!("file_{}", state.count)
-            } else {
-                format!("file_{}.{}", state.count, extension)
-            };
-        }
-
-        let allowed = allowed_lookup
-            .get(state.config.field_name)
-            .expect("allowed lookup should exist");
-
-        if !allowed.is_empty() && !allowed.contains(&extension) {
-            return Err(UploadError::new(format!(
-                "字段 `{}` 不支持 `{extension}` 文件类型",
-                state.config.field_name
-            )));
-        }
-
-        let stored_name = unique_name(
-            state.config.naming.build_name(state.count, &sanitized),
-            &mut used_names,
-        );
+        let (config_field, stored_name) = tracker.reserve(&field_name, &file_name)?;
         let stored_path = dest_dir.join(&stored_name);
         let mut file = File::create(&stored_path)
             .await
@@ -280,25 +208,15 @@ pub async fn process_upload_form(
             .map_err(|err| UploadError::new(format!("刷新文件失败: {err}")))?;
 
         saved_files.push(SavedFile {
-            field_name: state.config.field_name.to_string(),
+            field_name: config_field.to_string(),
             original_name: file_name,
             stored_name,
             stored_path,
             file_size: total_bytes,
         });
-
-        state.count += 1;
     }
 
-    // Validate minimum counts.
-    for state in field_states.values() {
-        if state.count < state.config.min_files {
-            return Err(UploadError::new(format!(
-                "字段 `{}` 至少需要上传 {} 个文件",
-                state.config.field_name, state.config.min_files
-            )));
-        }
-    }
+    tracker.check_min_files()?;
 
     Ok(UploadOutcome {
         files: saved_files,
@@ -306,12 +224,158 @@ pub async fn process_upload_form(
     })
 }
 
+/// A file supplied in a request body (e.g. base64 in JSON) rather than as a multipart part.
+#[derive(Debug, Clone)]
+pub struct InlineFile<'a> {
+    pub field_name: &'a str,
+    pub file_name: &'a str,
+    pub bytes: &'a [u8],
+}
+
+/// Persists inline files under the same per-field limits, extension checks and naming as
+/// [`process_upload_form`], so JSON APIs can hand the result to the multipart job code.
+pub async fn store_inline_files(
+    files: &[InlineFile<'_>],
+    dest_dir: &Path,
+    field_configs: &[FileFieldConfig<'_>],
+) -> UploadResult<UploadOutcome> {
+    ensure_directory(dest_dir).await?;
+
+    let mut tracker = FieldTracker::new(field_configs)?;
+    let mut saved_files: Vec<SavedFile> = Vec::new();
+
+    for inline in files {
+        let (config_field, stored_name) = tracker.reserve(inline.field_name, inline.file_name)?;
+        let stored_path = dest_dir.join(&stored_name);
+        tokio::fs::write(&stored_path, inline.bytes)
+            .await
+            .map_err(|err| UploadError::new(format!("保存文件失败: {err}")))?;
+
+        saved_files.push(SavedFile {
+            field_name: config_field.to_string(),
+            original_name: inline.file_name.to_string(),
+            stored_name,
+            stored_path,
+            file_size: inline.bytes.len() as u64,
+        });
+    }
+
+    tracker.check_min_files()?;
+
+    Ok(UploadOutcome {
+        files: saved_files,
+        text_fields: HashMap::new(),
+    })
+}
+
 #[derive(Clone, Copy, Debug)]
 struct FieldState<'a> {
     config: FileFieldConfig<'a>,
     count: usize,
 }
 
+/// Per-request bookkeeping shared by the multipart and inline upload paths.
+struct FieldTracker<'a> {
+    states: HashMap<&'a str, FieldState<'a>>,
+    used_names: HashSet<String>,
+}
+
+impl<'a> FieldTracker<'a> {
+    fn new(field_configs: &[FileFieldConfig<'a>]) -> UploadResult<Self> {
+        let mut states = HashMap::new();
+        for config in field_configs {
+            if config.max_files == 0 {
+                return Err(UploadError::new(format!(
+                    "字段 `{}` 的 max_files 必须大于 0",
+                    config.field_name
+                )));
+            }
+            if config.min_files > config.max_files {
+                return Err(UploadError::new(format!(
+                    "字段 `{}` 的 min_files 不能大于 max_files",
+                    config.field_name
+                )));
+            }
+            states.insert(
+                config.field_name,
+                FieldState {
+                    config: *config,
+                    count: 0,
+                },
+            );
+        }
+
+        Ok(Self {
+            states,
+            used_names: HashSet::new(),
+        })
+    }
+
+    /// Checks the field's file count and extension for `file_name` and returns the configured
+    /// field name with a unique stored name for it.
+    fn reserve(&mut self, field_name: &str, file_name: &str) -> UploadResult<(&'a str, String)> {
+        let Some(state) = self.states.get_mut(field_name) else {
+            return Err(UploadError::new(format!(
+                "不支持的文件字段: `{field_name}`"
+            )));
+        };
+
+        if state.count >= state.config.max_files {
+            return Err(UploadError::new(format!(
+                "字段 `{}` 上传文件数量超过限制 (最多 {})",
+                state.config.field_name, state.config.max_files
+            )));
+        }
+
+        let mut sanitized = sanitize_filename::sanitize(file_name);
+        let extension = Path::new(file_name)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_ascii_lowercase())
+            .unwrap_or_default();
+
+        if sanitized.is_empty() {
+            sanitized = if extension.is_empty() {
+                format!("file_{}", state.count)
+            } else {
+                format!("file_{}.{}", state.count, extension)
+            };
+        }
+
+        let allowed = state.config.allowed_extensions;
+        if !allowed.is_empty()
+            && !allowed
+                .iter()
+                .any(|ext| ext.eq_ignore_ascii_case(&extension))
+        {
+            return Err(UploadError::new(format!(
+                "字段 `{}` 不支持 `{extension}` 文件类型",
+                state.config.field_name
+            )));
+        }
+
+        let stored_name = unique_name(
+            state.config.naming.build_name(state.count, &sanitized),
+            &mut self.used_names,
+        );
+        state.count += 1;
+
+        Ok((state.config.field_name, stored_name))
+    }
+
+    fn check_min_files(&self) -> UploadResult<()> {
+        for state in self.states.values() {
+            if state.count < state.config.min_files {
+                return Err(UploadError::new(format!(
+                    "字段 `{}` 至少需要上传 {} 个文件",
+                    state.config.field_name, state.config.min_files
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
 fn unique_name(candidate: String, used: &mut HashSet<String>) -> String {
     if used.insert(candidate.clone()) {
         return candidate;
@@ -384,6 +448,54 @@ mod tests {
         assert_eq!(second, "file_1.pdf");
     }
 
+    #[tokio::test]
+    async fn inline_files_follow_field_rules() {
+        let dir = tempfile::tempdir().unwrap();
+        let configs = [FileFieldConfig::new(
+            "documents",
+            &["pdf", "txt"],
+            2,
+            FileNaming::Indexed {
+                prefix: "paper_",
+                pad_width: 3,
+            },
+        )];
+        let file = |file_name| InlineFile {
+            field_name: "documents",
+            file_name,
+            bytes: b"text",
+        };
+
+        let outcome = store_inline_files(&[file("a.TXT"), file("b.pdf")], dir.path(), &configs)
+            .await
+            .unwrap();
+        let stored: Vec<_> = outcome
+            .files_for("documents")
+            .map(|saved| saved.stored_name.as_str())
+            .collect();
+        assert_eq!(stored, ["paper_000_a.TXT", "paper_001_b.pdf"]);
+        assert_eq!(
+            std::fs::read(dir.path().join("paper_000_a.TXT")).unwrap(),
+            b"text"
+        );
+
+        assert!(
+            store_inline_files(&[file("a.exe")], dir.path(), &configs)
+                .await
+                .is_err()
+        );
+        assert!(
+            store_inline_files(
+                &[file("a.txt"), file("b.txt"), file("c.txt")],
+                dir.path(),
+                &configs
+            )
+            .await
+            .is_err()
+        );
+        assert!(store_inline_files(&[], dir.path(), &configs).await.is_err());
+    }
+
     #[test]
     fn split_name_handles_extension() {
         let (stem, ext) = split_name("report.final.docx");
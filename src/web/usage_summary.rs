@@ -0,0 +1,225 @@
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::StatusCode,
+};
+use axum_extra::extract::cookie::CookieJar;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use tracing::error;
+use uuid::Uuid;
+
+use crate::usage;
+use crate::web::{
+    ApiMessage, AppState,
+    auth::{self, JsonAuthError},
+    json_error,
+};
+
+#[derive(Deserialize, Default)]
+pub struct UsageSummaryQuery {
+    /// Another user to inspect; admins only.
+    #[serde(default)]
+    user_id: Option<Uuid>,
+}
+
+#[derive(Serialize)]
+pub(crate) struct ModuleUsageItem {
+    module: &'static str,
+    module_label: &'static str,
+    unit_label: &'static str,
+    unit_policy: &'static str,
+    tokens: i64,
+    units: i64,
+    unit_limit: Option<i64>,
+    units_remaining: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub(crate) struct UsageSummaryResponse {
+    user_id: Uuid,
+    usage_group: String,
+    window_days: i64,
+    tokens_used: i64,
+    token_limit: Option<i64>,
+    tokens_remaining: Option<i64>,
+    modules: Vec<ModuleUsageItem>,
+    generated_at: String,
+}
+
+impl UsageSummaryResponse {
+    fn new(user_id: Uuid, summary: usage::UserQuotaSummary) -> Self {
+        let tokens_remaining = summary.tokens_remaining();
+        let modules = summary
+            .modules
+            .iter()
+            .map(|module| ModuleUsageItem {
+                module: module.descriptor.key,
+                module_label: module.descriptor.label,
+                unit_label: module.unit_policy.unit_label(module.descriptor),
+                unit_policy: module.unit_policy.as_str(),
+                tokens: module.tokens,
+                units: module.units,
+                unit_limit: module.unit_limit,
+                units_remaining: module.units_remaining(),
+            })
+            .collect();
+
+        UsageSummaryResponse {
+            user_id,
+            usage_group: summary.group_name,
+            window_days: summary.window_days,
+            tokens_used: summary.tokens_used,
+            token_limit: summary.token_limit,
+            tokens_remaining,
+            modules,
+            generated_at: Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+/// Per-module usage in the quota window with what is left before `ensure_within_limits`
+/// starts rejecting submissions.
+pub async fn usage_summary(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Query(query): Query<UsageSummaryQuery>,
+) -> Result<Json<UsageSummaryResponse>, (StatusCode, Json<ApiMessage>)> {
+    let user = auth::current_user_or_json_error(&state, &jar)
+        .await
+        .map_err(|JsonAuthError { status, message }| json_error(status, message))?;
+
+    let target = query.user_id.unwrap_or(user.id);
+    if target != user.id && !user.is_admin {
+        return Err(json_error(
+            StatusCode::FORBIDDEN,
+            "仅管理员可以查看其他用户的用量。",
+        ));
+    }
+
+    let summary = usage::quota_summary(&state.pool(), target)
+        .await
+        .map_err(|err| {
+            error!(?err, user_id = %target, "failed to load usage summary");
+            json_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "无法读取用量信息，请稍后再试。",
+            )
+        })?
+        .ok_or_else(|| json_error(StatusCode::NOT_FOUND, "用户不存在。"))?;
+
+    Ok(Json(UsageSummaryResponse::new(target, summary)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_user, session_jar};
+    use crate::usage::{MODULE_GRADER, MODULE_SUMMARIZER};
+    use crate::{llm::LlmClient, web::AppState};
+    use sqlx::PgPool;
+
+    #[sqlx::test]
+    #[ignore = "needs DATABASE_URL (disposable Postgres)"]
+    async fn summary_reports_usage_and_remaining_quota(pool: PgPool) {
+        let state = AppState::for_tests(
+            pool.clone(),
+            LlmClient::with_openrouter_base_url("http://127.0.0.1:9"),
+        )
+        .await
+        .unwrap();
+        let user_id = create_user(&pool).await;
+        let other_id = create_user(&pool).await;
+
+        let group_id: Uuid = sqlx::query_scalar("SELECT usage_group_id FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let allocations = [(MODULE_SUMMARIZER.to_string(), Some(5))]
+            .into_iter()
+            .collect();
+        usage::upsert_group_limits(&pool, group_id, Some(10_000), &allocations)
+            .await
+            .unwrap();
+        usage::record_usage(&pool, user_id, MODULE_SUMMARIZER, 1_200, 2)
+            .await
+            .unwrap();
+        usage::record_usage(&pool, user_id, MODULE_GRADER, 300, 1)
+            .await
+            .unwrap();
+
+        let own = usage_summary(
+            State(state.clone()),
+            session_jar(&pool, user_id).await,
+            Query(UsageSummaryQuery::default()),
+        )
+        .await
+        .unwrap()
+        .0;
+        assert_eq!(own.user_id, user_id);
+        assert_eq!(own.window_days, 7);
+        assert_eq!(
+            (own.tokens_used, own.token_limit, own.tokens_remaining),
+            (1_500, Some(10_000), Some(8_500))
+        );
+        assert_eq!(own.modules.len(), usage::REGISTERED_MODULES.len());
+        let summarizer = own
+            .modules
+            .iter()
+            .find(|module| module.module == MODULE_SUMMARIZER)
+            .unwrap();
+        assert_eq!(
+            (summarizer.tokens, summarizer.units, summarizer.unit_limit),
+            (1_200, 2, Some(5))
+        );
+        assert_eq!(summarizer.units_remaining, Some(3));
+        let grader = own
+            .modules
+            .iter()
+            .find(|module| module.module == MODULE_GRADER)
+            .unwrap();
+        assert_eq!((grader.units, grader.units_remaining), (1, None));
+
+        let forbidden = usage_summary(
+            State(state.clone()),
+            session_jar(&pool, other_id).await,
+            Query(UsageSummaryQuery {
+                user_id: Some(user_id),
+            }),
+        )
+        .await
+        .err()
+        .unwrap();
+        assert_eq!(forbidden.0, StatusCode::FORBIDDEN);
+
+        sqlx::query("UPDATE users SET is_admin = TRUE WHERE id = $1")
+            .bind(other_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let as_admin = usage_summary(
+            State(state.clone()),
+            session_jar(&pool, other_id).await,
+            Query(UsageSummaryQuery {
+                user_id: Some(user_id),
+            }),
+        )
+        .await
+        .unwrap()
+        .0;
+        assert_eq!((as_admin.user_id, as_admin.tokens_used), (user_id, 1_500));
+
+        let missing = usage_summary(
+            State(state),
+            session_jar(&pool, other_id).await,
+            Query(UsageSummaryQuery {
+                user_id: Some(Uuid::new_v4()),
+            }),
+        )
+        .await
+        .err()
+        .unwrap();
+        assert_eq!(missing.0, StatusCode::NOT_FOUND);
+    }
+}